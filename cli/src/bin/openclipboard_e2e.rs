@@ -6,12 +6,11 @@
 //! - Avoids BLE, OS clipboard APIs, or UI
 
 use anyhow::Context;
-use base64::Engine as _;
 use clap::{Parser, Subcommand};
 use openclipboard::bench;
 use openclipboard_core::{
-    ClipboardContent, ClipboardProvider, Ed25519Identity, Listener, Message, MockClipboard,
-    Session, Transport,
+    ClipboardContent, ClipboardProvider, Ed25519Identity, FileTransferConfig, Listener, Message,
+    MockClipboard, Session, Transport,
     protocol::Frame,
     quic_transport::{
         QuicListener, QuicTransport, make_insecure_client_endpoint, make_server_endpoint,
@@ -78,12 +77,22 @@ enum Cmd {
         #[arg(long, default_value_t = 100 * 1024 * 1024)]
         total_bytes: u64,
         /// Chunk size in bytes (before base64 encoding).
-        #[arg(long, default_value_t = 64 * 1024)]
+        #[arg(long, default_value_t = FileTransferConfig::default().chunk_size)]
         chunk_bytes: usize,
+        /// Chunks prepared ahead of the one being sent (1: one at a time).
+        #[arg(long, default_value_t = FileTransferConfig::default().window)]
+        window: usize,
         /// Timeout for connect/handshake/recv (ms)
         #[arg(long, default_value_t = 15000)]
         timeout_ms: u64,
     },
+
+    /// Run two in-process nodes (discovery, handshake, clip, history) and report each stage.
+    Selftest {
+        /// Timeout for each stage (ms)
+        #[arg(long, default_value_t = 5000)]
+        timeout_ms: u64,
+    },
 }
 
 #[tokio::main]
@@ -103,11 +112,24 @@ async fn main() -> anyhow::Result<()> {
             bind,
             total_bytes,
             chunk_bytes,
+            window,
             timeout_ms,
-        } => bench_throughput(bind, total_bytes, chunk_bytes, timeout_ms).await,
+        } => bench_throughput(bind, total_bytes, FileTransferConfig { chunk_size: chunk_bytes, window }, timeout_ms).await,
+        Cmd::Selftest { timeout_ms } => selftest(timeout_ms).await,
     }
 }
 
+async fn selftest(timeout_ms: u64) -> anyhow::Result<()> {
+    let report = openclipboard::selftest::run(Duration::from_millis(timeout_ms)).await;
+    for stage in &report.stages {
+        println!("{}", serde_json::to_string(stage)?);
+    }
+    let passed = report.passed();
+    println!("{}", serde_json::json!({ "type": "selftest", "passed": passed }));
+    anyhow::ensure!(passed, "selftest failed");
+    Ok(())
+}
+
 async fn listen(bind: String, timeout_ms: u64) -> anyhow::Result<()> {
     let bind_addr: SocketAddr = bind.parse().context("parse --bind")?;
     let (endpoint, _cert) = make_server_endpoint(bind_addr).context("make_server_endpoint")?;
@@ -217,7 +239,7 @@ async fn bench_latency(bind: String, n: u32, size: usize, timeout_ms: u64) -> an
                     continue;
                 };
 
-                let bytes = base64::engine::general_purpose::STANDARD.decode(text)?;
+                let bytes = openclipboard_core::Base64Alphabet::Standard.decode(text)?;
                 anyhow::ensure!(bytes.len() >= 8, "payload too small");
                 let sent_ns = u64::from_be_bytes(bytes[0..8].try_into().unwrap());
                 let sent_at = start + Duration::from_nanos(sent_ns);
@@ -260,8 +282,9 @@ async fn bench_latency(bind: String, n: u32, size: usize, timeout_ms: u64) -> an
 
         let msg = Message::ClipText {
             mime: "application/octet-stream".into(),
-            text: base64::engine::general_purpose::STANDARD.encode(payload),
+            text: openclipboard_core::Base64Alphabet::Standard.encode(payload),
             ts_ms: 0,
+            origin: None,
         };
         send_msg(&session, &mut send_seq, msg).await?;
     }
@@ -283,13 +306,15 @@ async fn bench_latency(bind: String, n: u32, size: usize, timeout_ms: u64) -> an
     Ok(())
 }
 
+/// Send `total_bytes` as file chunks cut and pipelined per `config`; the
+/// receiver fails the run if a chunk arrives out of order.
 async fn bench_throughput(
     bind: String,
     total_bytes: u64,
-    chunk_bytes: usize,
+    config: FileTransferConfig,
     timeout_ms: u64,
 ) -> anyhow::Result<()> {
-    anyhow::ensure!(chunk_bytes > 0, "--chunk-bytes must be > 0");
+    anyhow::ensure!(config.chunk_size > 0, "--chunk-bytes must be > 0");
 
     let bind_addr: SocketAddr = bind.parse().context("parse --bind")?;
 
@@ -321,8 +346,9 @@ async fn bench_throughput(
             let mut recv_total: u64 = 0;
             while recv_total < total_bytes {
                 let msg = session.recv_message().await?;
-                if let Message::ClipText { text, .. } = msg {
-                    let bytes = base64::engine::general_purpose::STANDARD.decode(text)?;
+                if let Message::FileChunk { offset, data_b64, .. } = msg {
+                    anyhow::ensure!(offset == recv_total, "chunk at {offset} arrived after {recv_total} bytes");
+                    let bytes = openclipboard_core::Base64Alphabet::Standard.decode(data_b64)?;
                     recv_total += bytes.len() as u64;
                 }
             }
@@ -350,20 +376,14 @@ async fn bench_throughput(
         .await
         .context("handshake")?;
 
-    let mut send_seq = AtomicU64::new(0);
+    // Sent a slab at a time rather than from one `total_bytes` buffer.
+    let slab = vec![0u8; config.chunk_size.saturating_mul(config.window.max(1)).saturating_mul(64)];
 
     let start = Instant::now();
     let mut sent: u64 = 0;
     while sent < total_bytes {
-        let remaining = (total_bytes - sent) as usize;
-        let sz = remaining.min(chunk_bytes);
-        let payload = vec![0u8; sz];
-        let msg = Message::ClipText {
-            mime: "application/octet-stream".into(),
-            text: base64::engine::general_purpose::STANDARD.encode(payload),
-            ts_ms: 0,
-        };
-        send_msg(&session, &mut send_seq, msg).await?;
+        let sz = (total_bytes - sent).min(slab.len() as u64) as usize;
+        session.send_file_data("bench", sent, &slab[..sz], &config).await?;
         sent += sz as u64;
     }
 
@@ -378,8 +398,8 @@ async fn bench_throughput(
     let mbps = mb / elapsed;
 
     println!(
-        "throughput: total_bytes={} elapsed_s={:.6} MB/s={:.3}",
-        total_bytes, elapsed, mbps
+        "throughput: total_bytes={} chunk_bytes={} window={} elapsed_s={:.6} MB/s={:.3}",
+        total_bytes, config.chunk_size, config.window, elapsed, mbps
     );
     Ok(())
 }
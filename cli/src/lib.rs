@@ -1,9 +1,11 @@
 use anyhow::{Context, Result};
 
 pub mod bench;
-use base64::Engine as _;
+pub mod output;
+pub mod selftest;
+pub mod status;
 use openclipboard_core::{
-    Ed25519Identity, IdentityProvider, PairingPayload, TrustRecord, derive_confirmation_code,
+    Ed25519Identity, IdentityProvider, PairingPayload, PairingSession, TrustRecord, derive_confirmation_code,
 };
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -12,6 +14,17 @@ use std::path::{Path, PathBuf};
 pub struct IdentityFile {
     /// base64 secret key bytes (ed25519 signing key seed)
     pub signing_key_b64: String,
+    /// How this device presents itself when pairing. Absent in older files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<IdentityProfile>,
+}
+
+/// Public metadata about this device, stored alongside its identity.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct IdentityProfile {
+    pub display_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
 }
 
 pub fn default_identity_path() -> PathBuf {
@@ -40,10 +53,8 @@ pub fn load_or_create_identity(path: &Path) -> Result<Ed25519Identity> {
 }
 
 pub fn load_identity(path: &Path) -> Result<Ed25519Identity> {
-    let s = fs::read_to_string(path)
-        .with_context(|| format!("read identity file {}", path.display()))?;
-    let file: IdentityFile = serde_json::from_str(&s)?;
-    let sk_bytes = base64::engine::general_purpose::STANDARD
+    let file = read_identity_file(path)?;
+    let sk_bytes = openclipboard_core::Base64Alphabet::Standard
         .decode(file.signing_key_b64)
         .context("decode signing_key_b64")?;
     let sk_arr: [u8; 32] = sk_bytes
@@ -53,10 +64,20 @@ pub fn load_identity(path: &Path) -> Result<Ed25519Identity> {
     Ok(Ed25519Identity::from_signing_key(signing_key))
 }
 
+/// The profile saved with the identity at `path`, if any.
+pub fn load_identity_profile(path: &Path) -> Result<Option<IdentityProfile>> {
+    Ok(read_identity_file(path)?.profile)
+}
+
 pub fn save_identity(path: &Path, id: &Ed25519Identity) -> Result<()> {
+    save_identity_with_profile(path, id, None)
+}
+
+pub fn save_identity_with_profile(path: &Path, id: &Ed25519Identity, profile: Option<IdentityProfile>) -> Result<()> {
     let sk_bytes = id.signing_key_seed_bytes();
     let file = IdentityFile {
-        signing_key_b64: base64::engine::general_purpose::STANDARD.encode(sk_bytes),
+        signing_key_b64: openclipboard_core::Base64Alphabet::Standard.encode(sk_bytes),
+        profile,
     };
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
@@ -66,10 +87,26 @@ pub fn save_identity(path: &Path, id: &Ed25519Identity) -> Result<()> {
     Ok(())
 }
 
+fn read_identity_file(path: &Path) -> Result<IdentityFile> {
+    let s = fs::read_to_string(path)
+        .with_context(|| format!("read identity file {}", path.display()))?;
+    Ok(serde_json::from_str(&s)?)
+}
+
 /// Create a pairing init payload and return its QR string.
 ///
-/// Exposed for tests so the nonce can be deterministic.
-pub fn pairing_init_qr(name: String, port: u16, id: &Ed25519Identity, nonce: [u8; 32]) -> String {
+/// Without an explicit `name` the profile's display name is used, falling back to
+/// the peer id. Exposed for tests so the nonce can be deterministic.
+pub fn pairing_init_qr(
+    name: Option<String>,
+    profile: Option<&IdentityProfile>,
+    port: u16,
+    id: &Ed25519Identity,
+    nonce: [u8; 32],
+) -> String {
+    let name = name
+        .or_else(|| profile.map(|p| p.display_name.clone()))
+        .unwrap_or_else(|| id.peer_id().to_string());
     let payload = PairingPayload {
         version: 1,
         peer_id: id.peer_id().to_string(),
@@ -104,28 +141,27 @@ pub fn pairing_respond_qr(
     Ok((resp_qr, code))
 }
 
-/// Finalize a pairing exchange; validates nonce and returns:
+/// Finalize a pairing exchange; checks the response answers this init (see
+/// [`PairingSession::confirm_response`]) and returns:
 /// (confirmation_code, trust_records_to_write)
 pub fn pairing_finalize(init_qr: &str, resp_qr: &str) -> Result<(String, [TrustRecord; 2])> {
-    let init = PairingPayload::from_qr_string(init_qr)?;
-    let resp = PairingPayload::from_qr_string(resp_qr)?;
-
-    if init.nonce != resp.nonce {
-        anyhow::bail!("nonce mismatch between init and resp payload");
-    }
-    let code = derive_confirmation_code(&init.nonce, &init.peer_id, &resp.peer_id);
+    let session = PairingSession::new(PairingPayload::from_qr_string(init_qr)?);
+    let (resp, code) = session.confirm_response(resp_qr)?;
+    let init = session.init().clone();
 
     let a = TrustRecord {
         peer_id: init.peer_id,
         identity_pk: init.identity_pk,
         display_name: init.name,
         created_at: chrono::Utc::now(),
+        sync_direction: Default::default(),
     };
     let b = TrustRecord {
         peer_id: resp.peer_id,
         identity_pk: resp.identity_pk,
         display_name: resp.name,
         created_at: chrono::Utc::now(),
+        sync_direction: Default::default(),
     };
 
     Ok((code, [a, b]))
@@ -141,61 +177,133 @@ pub fn sanitize_filename(name: &str) -> String {
 }
 
 pub fn preview(s: &str) -> String {
-    const N: usize = 80;
-    if s.len() <= N {
-        return s.to_string();
-    }
-    format!("{}…", &s[..N])
+    openclipboard_core::preview_text(s, openclipboard_core::PREVIEW_MAX_CHARS)
 }
 
+/// The id `send_file` offers a file under: the SHA-256 hex of its content,
+/// the same for every attempt at sending it, so a receiver can match a
+/// resumed transfer to its partial file and never to different bytes.
+pub fn file_id_for(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Send the file at `path`, skipping its first `start_offset` bytes (already
+/// with the receiver; see [`query_resume_offset`]), in chunks cut and
+/// pipelined per `config` and paced by the session's file rate limiters. The
+/// offer's SHA-256 and
+/// `FileDone`'s blake3 hash cover the whole file either way. Fails with
+/// [`openclipboard_core::FileRejected`] if the receiver declines the offer.
 pub async fn send_file<C, I, CB>(
     session: &openclipboard_core::Session<C, I, CB>,
     path: &Path,
+    start_offset: u64,
+    config: &openclipboard_core::FileTransferConfig,
 ) -> Result<()>
 where
     C: openclipboard_core::Connection,
     I: openclipboard_core::IdentityProvider,
     CB: openclipboard_core::ClipboardProvider,
 {
-    const CHUNK: usize = 64 * 1024;
-
     let data = fs::read(path).with_context(|| format!("read file {}", path.display()))?;
     let size = data.len() as u64;
     let name = path
         .file_name()
         .and_then(|s| s.to_str())
         .unwrap_or("file.bin");
+    if start_offset > size {
+        anyhow::bail!("resume offset {start_offset} is past the end of {name} ({size} bytes)");
+    }
 
-    let file_id = blake3::hash(format!("{}:{}", name, size).as_bytes())
-        .to_hex()
-        .to_string();
+    let sha256 = file_id_for(&data);
+    let file_id = sha256.clone();
 
     session
-        .send_file_offer(&file_id, name, size, "application/octet-stream")
+        .send_file_offer_with_hash(&file_id, name, size, openclipboard_core::mime_for_name(name), Some(&sha256))
         .await?;
 
-    // Wait a short time for accept, but don't require it.
-    let _ = tokio::time::timeout(
-        std::time::Duration::from_millis(500),
-        session.recv_message(),
-    )
-    .await;
-
-    let mut offset = 0u64;
-    for chunk in data.chunks(CHUNK) {
-        session.send_file_chunk(&file_id, offset, chunk).await?;
-        offset += chunk.len() as u64;
+    // Wait a short time for accept, but don't require it; a reject stops here.
+    let answer = async {
+        loop {
+            match session.recv_message().await? {
+                openclipboard_core::Message::FileAccept { file_id: id } if id == file_id => return Ok(None),
+                openclipboard_core::Message::FileReject { file_id: id, reason } if id == file_id => return Ok(Some(reason)),
+                _ => {}
+            }
+        }
+    };
+    match tokio::time::timeout(std::time::Duration::from_millis(500), answer).await {
+        Ok(Ok(Some(reason))) => return Err(openclipboard_core::FileRejected { name: name.to_string(), reason }.into()),
+        Ok(Err(e)) => return Err(e),
+        Ok(Ok(None)) | Err(_) => {}
     }
 
+    session.send_file_data(&file_id, start_offset, &data[start_offset as usize..], config).await?;
+
     let hash = blake3::hash(&data).to_hex().to_string();
     session.send_file_done(&file_id, &hash).await?;
     Ok(())
 }
 
+/// What `serve` takes from a `FileOffer`: a declined offer gets a
+/// `FileReject` with the reason [`FileOfferPolicy::check`] gives.
+#[derive(Debug, Clone, Default)]
+pub struct FileOfferPolicy {
+    pub rules: openclipboard_core::FileTypeRules,
+    /// Offers larger than this many bytes are declined.
+    pub max_size: Option<u64>,
+}
+
+impl FileOfferPolicy {
+    /// `Err` with the `FileReject` reason if the offer is declined.
+    pub fn check(&self, name: &str, size: u64, mime: &str) -> std::result::Result<(), &'static str> {
+        if !self.rules.allows(name, mime) {
+            return Err(openclipboard_core::REJECT_MIME_NOT_ALLOWED);
+        }
+        if self.max_size.is_some_and(|max| size > max) {
+            return Err(openclipboard_core::REJECT_TOO_LARGE);
+        }
+        Ok(())
+    }
+}
+
+/// Ask the receiver how much of `file_id` it already has, to pass to
+/// [`send_file`] after a dropped connection. Other messages arriving first are
+/// skipped.
+pub async fn query_resume_offset<C, I, CB>(session: &openclipboard_core::Session<C, I, CB>, file_id: &str) -> Result<u64>
+where
+    C: openclipboard_core::Connection,
+    I: openclipboard_core::IdentityProvider,
+    CB: openclipboard_core::ClipboardProvider,
+{
+    session.query_resume_offset(file_id, std::time::Duration::from_secs(5)).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn preview_never_splits_a_character() {
+        // The old byte slice at 80 would land inside a three-byte character.
+        let text = "日本語".repeat(30);
+        assert!(!text.is_char_boundary(80));
+        assert_eq!(preview(&text).chars().count(), 81);
+        assert_eq!(preview("short"), "short");
+    }
+
+    #[test]
+    fn file_offer_policy_checks_type_then_size() {
+        let policy = FileOfferPolicy {
+            rules: openclipboard_core::FileTypeRules { allow: Vec::new(), deny: vec![".exe".into()] },
+            max_size: Some(100),
+        };
+        assert_eq!(policy.check("notes.txt", 100, "text/plain"), Ok(()));
+        assert_eq!(policy.check("notes.txt", 101, "text/plain"), Err(openclipboard_core::REJECT_TOO_LARGE));
+        assert_eq!(policy.check("setup.exe", 1, "application/octet-stream"), Err(openclipboard_core::REJECT_MIME_NOT_ALLOWED));
+        assert_eq!(FileOfferPolicy::default().check("any.bin", u64::MAX, ""), Ok(()));
+    }
+
     #[test]
     fn identity_json_roundtrip() {
         let dir = tempfile::tempdir().unwrap();
@@ -229,7 +337,7 @@ mod tests {
         let bob = Ed25519Identity::generate();
         let nonce = [7u8; 32];
 
-        let init_qr = pairing_init_qr("Alice".into(), 1111, &alice, nonce);
+        let init_qr = pairing_init_qr(Some("Alice".into()), None, 1111, &alice, nonce);
         let (resp_qr, code1) = pairing_respond_qr(&init_qr, "Bob".into(), 2222, &bob).unwrap();
         let (code2, records) = pairing_finalize(&init_qr, &resp_qr).unwrap();
 
@@ -238,4 +346,27 @@ mod tests {
         assert!(ids.contains(&alice.peer_id().to_string()));
         assert!(ids.contains(&bob.peer_id().to_string()));
     }
+
+    #[test]
+    fn saved_profile_reloads_and_names_pairing_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("identity.json");
+        let id = Ed25519Identity::generate();
+        let profile = IdentityProfile { display_name: "Alice's MacBook".into(), platform: Some("macos".into()) };
+        save_identity_with_profile(&path, &id, Some(profile.clone())).unwrap();
+
+        assert_eq!(load_identity(&path).unwrap().peer_id(), id.peer_id());
+        let loaded = load_identity_profile(&path).unwrap();
+        assert_eq!(loaded.as_ref(), Some(&profile));
+
+        let qr = pairing_init_qr(None, loaded.as_ref(), 1111, &id, [1u8; 32]);
+        assert_eq!(PairingPayload::from_qr_string(&qr).unwrap().name, "Alice's MacBook");
+        // An explicit name still wins.
+        let qr = pairing_init_qr(Some("Work".into()), loaded.as_ref(), 1111, &id, [1u8; 32]);
+        assert_eq!(PairingPayload::from_qr_string(&qr).unwrap().name, "Work");
+
+        // Files written before profiles existed load without one.
+        save_identity(&path, &id).unwrap();
+        assert_eq!(load_identity_profile(&path).unwrap(), None);
+    }
 }
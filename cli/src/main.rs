@@ -1,19 +1,22 @@
 use anyhow::Result;
-use base64::Engine as _;
 use clap::{Parser, Subcommand};
-use chrono::Utc;
 use openclipboard::{
-    default_identity_path, default_trust_path, load_or_create_identity, load_identity,
-    pairing_finalize, pairing_init_qr, pairing_respond_qr, preview, sanitize_filename, save_identity,
-    send_file,
+    default_identity_path, default_trust_path, load_or_create_identity, load_identity, load_identity_profile,
+    pairing_finalize, pairing_init_qr, pairing_respond_qr, preview, save_identity_with_profile,
+    send_file, file_id_for, query_resume_offset, FileOfferPolicy, IdentityProfile,
 };
 use openclipboard_core::{
-    ClipboardContent, ClipboardProvider, Ed25519Identity, FileTrustStore, IdentityProvider, Listener,
-    MemoryReplayProtector, Session, Transport, TrustStore,
+    ClipboardContent, ClipboardHistory, ClipboardProvider, Ed25519Identity, FileAssembler, FileReceiveLog, FileReplayProtector, FileTrustStore, HistoryKey, IdentityProvider,
+    Listener, FileTransferConfig, FileTypeRules, MemoryReplayProtector, PartialFiles, RateLimiter, Session, Transport, TrustStore, replay_path_for,
 };
+use openclipboard::output::{
+    CommandOutput, IdentityOutput, OutputFormat, PairFinalizeOutput, PairInitOutput, PairRespondOutput, PairedPeer,
+    SendOutput, ServeEvent, TrustRemoveOutput,
+};
+use openclipboard::status::NodeStatus;
 use openclipboard_core::clipboard::MockClipboard;
 use openclipboard_core::quic_transport::{
-    make_insecure_client_endpoint, make_server_endpoint, QuicListener, QuicTransport,
+    make_insecure_client_endpoint, make_server_endpoint, AcceptError, QuicListener, QuicTransport,
 };
 use rand_core::RngCore;
 use std::collections::HashMap;
@@ -26,6 +29,10 @@ use std::sync::Arc;
 #[derive(Parser, Debug)]
 #[command(name = "openclipboard", version, about = "OpenClipboard LAN prototype CLI")]
 struct Cli {
+    /// `json` prints each result as a JSON object (one per line for `serve`).
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
     #[command(subcommand)]
     cmd: Command,
 }
@@ -36,6 +43,11 @@ enum Command {
     IdNew {
         #[arg(long)]
         path: Option<PathBuf>,
+        /// Device name to save with the identity; used by `pair:init` when `--name` is omitted.
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        platform: Option<String>,
     },
     #[command(name = "id:show")]
     IdShow {
@@ -45,8 +57,9 @@ enum Command {
 
     #[command(name = "pair:init")]
     PairInit {
+        /// Defaults to the display name saved with the identity.
         #[arg(long)]
-        name: String,
+        name: Option<String>,
         #[arg(long)]
         port: u16,
         #[arg(long)]
@@ -75,6 +88,16 @@ enum Command {
         trust_path: Option<PathBuf>,
     },
 
+    /// Stop trusting a peer. The removal is kept as a revocation, so linked
+    /// devices drop the peer too.
+    #[command(name = "trust:remove")]
+    TrustRemove {
+        #[arg(long)]
+        peer_id: String,
+        #[arg(long)]
+        trust_path: Option<PathBuf>,
+    },
+
     #[command(name = "serve")]
     Serve {
         #[arg(long)]
@@ -85,6 +108,23 @@ enum Command {
         id_path: Option<PathBuf>,
         #[arg(long)]
         trust_path: Option<PathBuf>,
+        /// Serve `GET /status` on 127.0.0.1:<port> (needs the `status-http` feature).
+        #[arg(long)]
+        status_port: Option<u16>,
+        /// Only accept files matching one of these types (`image/*`, `application/pdf`, `.txt`).
+        #[arg(long = "allow-type")]
+        allow_types: Vec<String>,
+        /// Reject files matching any of these types; wins over `--allow-type`.
+        #[arg(long = "deny-type")]
+        deny_types: Vec<String>,
+        /// Reject files larger than this many bytes; a file is assembled in
+        /// memory before it is written.
+        #[arg(long, default_value_t = openclipboard_core::DEFAULT_MAX_RECEIVED_FILE_SIZE)]
+        max_file_size: u64,
+        /// Keep clipboard history across restarts in this file, encrypted
+        /// with the identity's key.
+        #[arg(long)]
+        history_file: Option<PathBuf>,
     },
 
     #[command(name = "send:text")]
@@ -105,42 +145,64 @@ enum Command {
     SendFile {
         #[arg(long)]
         addr: String,
-        #[arg(long)]
-        path: PathBuf,
+        /// Repeat to send several files, interleaved a few at a time.
+        #[arg(long, required = true)]
+        path: Vec<PathBuf>,
         #[arg(long)]
         id_path: Option<PathBuf>,
         #[arg(long)]
         trust_path: Option<PathBuf>,
         #[arg(long, default_value_t = false)]
         pairing_mode: bool,
+        /// Continue an earlier, interrupted send of the same file from where the receiver left off.
+        #[arg(long, default_value_t = false)]
+        resume: bool,
+        /// Send at most this many bytes per second (0: unlimited).
+        #[arg(long, default_value_t = 0)]
+        rate_limit: u64,
+        /// File bytes per chunk.
+        #[arg(long, default_value_t = FileTransferConfig::default().chunk_size)]
+        chunk_size: usize,
+        /// Chunks prepared ahead of the one being sent (1: one at a time).
+        #[arg(long, default_value_t = FileTransferConfig::default().window)]
+        window: usize,
     },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let format = cli.output;
 
     match cli.cmd {
-        Command::IdNew { path } => {
+        Command::IdNew { path, name, platform } => {
             let path = path.unwrap_or_else(default_identity_path);
             let id = Ed25519Identity::generate();
-            save_identity(&path, &id)?;
-            println!("wrote identity: {}", path.display());
-            println!("peer_id: {}", id.peer_id());
-            println!(
-                "pubkey_b64: {}",
-                base64::engine::general_purpose::STANDARD.encode(id.public_key_bytes())
-            );
+            let profile = name.map(|display_name| IdentityProfile { display_name, platform });
+            save_identity_with_profile(&path, &id, profile.clone())?;
+            IdentityOutput {
+                path: path.display().to_string(),
+                peer_id: id.peer_id().to_string(),
+                pubkey_b64: openclipboard_core::Base64Alphabet::Standard.encode(id.public_key_bytes()),
+                name: profile.as_ref().map(|p| p.display_name.clone()),
+                platform: profile.and_then(|p| p.platform),
+                created: true,
+            }
+            .print(format);
         }
         Command::IdShow { path } => {
             let path = path.unwrap_or_else(default_identity_path);
             let id = load_identity(&path)?;
-            println!("identity: {}", path.display());
-            println!("peer_id: {}", id.peer_id());
-            println!(
-                "pubkey_b64: {}",
-                base64::engine::general_purpose::STANDARD.encode(id.public_key_bytes())
-            );
+            let profile = load_identity_profile(&path)?;
+            IdentityOutput {
+                path: path.display().to_string(),
+                peer_id: id.peer_id().to_string(),
+                pubkey_b64: openclipboard_core::Base64Alphabet::Standard.encode(id.public_key_bytes()),
+                name: profile.as_ref().map(|p| p.display_name.clone()),
+                platform: profile.and_then(|p| p.platform),
+                created: false,
+            }
+            .print(format);
         }
         Command::PairInit { name, port, id_path } => {
             let id_path = id_path.unwrap_or_else(default_identity_path);
@@ -149,17 +211,16 @@ async fn main() -> Result<()> {
             let mut nonce = [0u8; 32];
             rand_core::OsRng.fill_bytes(&mut nonce);
 
-            let qr = pairing_init_qr(name, port, &id, nonce);
-            println!("init_qr: {qr}");
-            println!("note: waiting for responder payload to derive code");
+            let profile = load_identity_profile(&id_path)?;
+            let init_qr = pairing_init_qr(name, profile.as_ref(), port, &id, nonce);
+            PairInitOutput { init_qr }.print(format);
         }
         Command::PairRespond { qr, name, port, id_path } => {
             let id_path = id_path.unwrap_or_else(default_identity_path);
             let id = load_or_create_identity(&id_path)?;
 
             let (resp_qr, code) = pairing_respond_qr(&qr, name, port, &id)?;
-            println!("resp_qr: {resp_qr}");
-            println!("code: {code}");
+            PairRespondOutput { resp_qr, code }.print(format);
         }
         Command::PairFinalize { init_qr, resp_qr, trust_path } => {
             let trust_path = trust_path.unwrap_or_else(default_trust_path);
@@ -173,76 +234,116 @@ async fn main() -> Result<()> {
             io::stdout().flush().ok();
             let mut line = String::new();
             io::stdin().read_line(&mut line)?;
-            if line.trim().to_lowercase() != "y" {
-                eprintln!("aborted");
-                return Ok(());
+            let written = line.trim().to_lowercase() == "y";
+            if written {
+                // Both records or neither, so a failed write can't leave a half-pairing.
+                let store = FileTrustStore::new(trust_path.clone())?;
+                store.save_many(&records)?;
             }
 
-            let store = FileTrustStore::new(trust_path.clone())?;
-            for rec in records {
-                store.save(openclipboard_core::TrustRecord {
-                    peer_id: rec.peer_id,
-                    identity_pk: rec.identity_pk,
-                    display_name: rec.display_name,
-                    created_at: Utc::now(),
-                })?;
+            let out = PairFinalizeOutput {
+                code,
+                init: PairedPeer { peer_id: records[0].peer_id.clone(), name: records[0].display_name.clone() },
+                resp: PairedPeer { peer_id: records[1].peer_id.clone(), name: records[1].display_name.clone() },
+                trust_path: trust_path.display().to_string(),
+                written,
+            };
+            if written || format == OutputFormat::Json {
+                out.print(format);
+            } else {
+                eprintln!("{}", out.text());
             }
-            println!("wrote trust store: {}", trust_path.display());
         }
-        Command::Serve { port, name: _name, id_path, trust_path } => {
+        Command::TrustRemove { peer_id, trust_path } => {
+            let trust_path = trust_path.unwrap_or_else(default_trust_path);
+            let removed = FileTrustStore::new(trust_path.clone())?.revoke(&peer_id)?;
+            TrustRemoveOutput { peer_id, trust_path: trust_path.display().to_string(), removed }.print(format);
+        }
+        Command::Serve { port, name: _name, id_path, trust_path, status_port, allow_types, deny_types, max_file_size, history_file } => {
             let id_path = id_path.unwrap_or_else(default_identity_path);
             let trust_path = trust_path.unwrap_or_else(default_trust_path);
             let identity = load_or_create_identity(&id_path)?;
             let trust = Arc::new(FileTrustStore::new(trust_path.clone())?);
-            let replay = Arc::new(MemoryReplayProtector::new(1024));
+            // Persisted, so a handshake captured before a restart can't be replayed after it.
+            let replay = Arc::new(FileReplayProtector::new(replay_path_for(&trust_path), 4096)?);
 
             let bind: SocketAddr = format!("0.0.0.0:{port}").parse()?;
             let (endpoint, _cert) = make_server_endpoint(bind)?;
             let listener = QuicListener::new(endpoint);
-            println!(
-                "listening on {} (trust: {})",
-                listener.local_addr()?,
-                trust_path.display()
-            );
+            ServeEvent::Listening {
+                addr: listener.local_addr()?.to_string(),
+                trust_path: trust_path.display().to_string(),
+            }
+            .print(format);
+
+            let history = match history_file {
+                Some(path) => ClipboardHistory::new(100).with_encrypted_persistence(path, HistoryKey::from_identity(&identity))?,
+                None => ClipboardHistory::new(100),
+            };
+            let history = Arc::new(history);
+            let status = Arc::new(NodeStatus::new(Arc::clone(&history)));
+            if let Some(status_port) = status_port {
+                start_status_endpoint(status_port, Arc::clone(&status), format).await?;
+            }
 
-            let mut files: HashMap<String, IncomingFile> = HashMap::new();
+            let file_policy = FileOfferPolicy { rules: FileTypeRules { allow: allow_types, deny: deny_types }, max_size: Some(max_file_size) };
+            let mut files: HashMap<String, FileAssembler> = HashMap::new();
+            let partial = PartialFiles::new(openclipboard_core::default_partial_dir());
+            partial.expire(openclipboard_core::PARTIAL_FILE_MAX_AGE);
+            // Received files go under ./received, never over an existing file.
+            let received = FileReceiveLog::new(1);
+            received.set_download_dir(PathBuf::from("received"));
 
             loop {
-                let conn = listener.accept().await?;
+                let conn = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) if AcceptError::of(&e) == Some(&AcceptError::Closed) => return Err(e),
+                    Err(e) => {
+                        eprintln!("accept failed: {e}");
+                        continue;
+                    }
+                };
                 let session = Session::with_trust_and_replay(
                     conn,
                     identity.clone(),
                     MockClipboard::new(),
                     trust.clone(),
                     replay.clone(),
-                );
+                )
+                .with_raw_file_chunks();
 
-                match session.handshake().await {
+                let peer_id = match session.handshake().await {
                     Ok(peer_id) => {
-                        println!("trusted peer connected: {peer_id}");
+                        ServeEvent::PeerConnected { peer_id: peer_id.clone() }.print(format);
+                        status.peer_connected();
+                        peer_id
                     }
                     Err(e) => {
                         eprintln!("handshake failed: {e:#}");
+                        status.handshake_failed();
                         continue;
                     }
-                }
+                };
 
                 loop {
                     let msg = match session.recv_message().await {
                         Ok(m) => m,
                         Err(e) => {
                             eprintln!("connection ended: {e:#}");
+                            status.peer_disconnected();
+                            // Keep what arrived so the sender can resume.
+                            if let Err(e) = partial.save_all(&peer_id, &mut files) {
+                                eprintln!("saving partial files failed: {e:#}");
+                            }
                             break;
                         }
                     };
 
                     match msg {
-                        openclipboard_core::Message::ClipText { mime: _, text, ts_ms } => {
-                            println!(
-                                "clip:text ts_ms={ts_ms} bytes={} preview={:?}",
-                                text.len(),
-                                preview(&text)
-                            );
+                        openclipboard_core::Message::ClipText { text, ts_ms, .. } => {
+                            ServeEvent::ClipText { ts_ms, bytes: text.len(), preview: preview(&text) }.print(format);
+                            history.record(text, peer_id.clone());
+                            status.clip_received();
                         }
                         openclipboard_core::Message::ClipImage {
                             mime,
@@ -251,48 +352,72 @@ async fn main() -> Result<()> {
                             bytes_b64,
                             ts_ms,
                         } => {
-                            let bytes = base64::engine::general_purpose::STANDARD.decode(bytes_b64)?;
-                            println!(
-                                "clip:image ts_ms={ts_ms} mime={mime} {width}x{height} bytes={}",
-                                bytes.len()
-                            );
+                            let bytes = openclipboard_core::Base64Alphabet::Standard.decode(bytes_b64)?;
+                            ServeEvent::ClipImage { ts_ms, mime: mime.clone(), width, height, bytes: bytes.len() }.print(format);
+                            history.record_image(mime, width, height, bytes, peer_id.clone());
                         }
-                        openclipboard_core::Message::FileOffer { file_id, name, size, mime } => {
-                            println!("file:offer id={file_id} name={name} size={size} mime={mime}");
-                            files.insert(
-                                file_id.clone(),
-                                IncomingFile { name, expected: size, buf: Vec::new() },
-                            );
+                        openclipboard_core::Message::FileOffer { file_id, name, size, mime, hash } => {
+                            ServeEvent::FileOffer { file_id: file_id.clone(), name: name.clone(), size, mime: mime.clone() }
+                                .print(format);
+                            if let Err(reason) = file_policy.check(&name, size, &mime) {
+                                ServeEvent::FileReject { file_id: file_id.clone(), reason: reason.into() }.print(format);
+                                session.send_file_reject(&file_id, reason).await.ok();
+                                continue;
+                            }
+                            let file = FileAssembler::new(name.clone(), size).with_hash(hash.clone());
+                            let file = partial.resume(&peer_id, &file_id, file).unwrap_or_else(|e| {
+                                eprintln!("{e:#}");
+                                FileAssembler::new(name, size).with_hash(hash)
+                            });
+                            files.insert(file_id.clone(), file);
                             session.send_file_accept(&file_id).await.ok();
                         }
                         openclipboard_core::Message::FileAccept { file_id } => {
-                            println!("file:accept id={file_id}");
+                            ServeEvent::FileAccept { file_id }.print(format);
                         }
                         openclipboard_core::Message::FileChunk { file_id, offset, data_b64 } => {
-                            let data = base64::engine::general_purpose::STANDARD.decode(data_b64)?;
-                            println!("file:chunk id={file_id} offset={offset} len={}", data.len());
-                            if let Some(f) = files.get_mut(&file_id) {
-                                f.buf.extend_from_slice(&data);
-                            }
+                            let data = openclipboard_core::Base64Alphabet::Standard.decode(data_b64)?;
+                            place_file_chunk(&mut files, &partial, &peer_id, file_id, offset, &data, format);
+                        }
+                        openclipboard_core::Message::FileChunkRaw { file_id, offset, data } => {
+                            place_file_chunk(&mut files, &partial, &peer_id, file_id, offset, &data, format);
+                        }
+                        openclipboard_core::Message::FileResumeQuery { file_id } => {
+                            let offset = files.get(&file_id).map_or_else(|| partial.saved_len(&peer_id, &file_id), FileAssembler::next_offset);
+                            ServeEvent::FileResume { file_id: file_id.clone(), offset }.print(format);
+                            session.send_file_resume_offset(&file_id, offset).await.ok();
+                        }
+                        openclipboard_core::Message::Ping { ts_ms } => {
+                            session.send_pong(ts_ms).await.ok();
                         }
                         openclipboard_core::Message::FileDone { file_id, hash } => {
-                            println!("file:done id={file_id} hash={hash}");
+                            ServeEvent::FileDone { file_id: file_id.clone(), hash: hash.clone() }.print(format);
                             if let Some(f) = files.remove(&file_id) {
-                                println!(
-                                    "file:received name={} bytes={} expected={}",
-                                    f.name,
-                                    f.buf.len(),
-                                    f.expected
-                                );
-                                let out_dir = PathBuf::from("received");
-                                fs::create_dir_all(&out_dir).ok();
-                                let out_path = out_dir.join(sanitize_filename(&f.name));
-                                fs::write(&out_path, &f.buf).ok();
-                                println!("file:written {}", out_path.display());
+                                partial.discard(&peer_id, &file_id);
+                                let name = f.name.clone();
+                                ServeEvent::FileReceived { name: name.clone(), bytes: f.received() as usize, expected: f.expected() }
+                                    .print(format);
+                                let data = match f.finish() {
+                                    Ok(data) => data,
+                                    Err(e) => {
+                                        eprintln!("not writing {e}");
+                                        continue;
+                                    }
+                                };
+                                let written = match received.save(&peer_id, &name, &data, &hash) {
+                                    Ok(written) => written,
+                                    Err(e) => {
+                                        eprintln!("writing {name} failed: {e:#}");
+                                        continue;
+                                    }
+                                };
+                                ServeEvent::FileWritten { path: written.path.clone() }.print(format);
+                                history.record_file(name, written.size, written.path, peer_id.clone());
+                                status.file_received();
                             }
                         }
                         other => {
-                            println!("msg: {:?}", other.msg_type());
+                            ServeEvent::Message { msg_type: format!("{:?}", other.msg_type()) }.print(format);
                         }
                     }
                 }
@@ -316,13 +441,19 @@ async fn main() -> Result<()> {
             };
 
             let peer = session.handshake().await?;
-            println!("connected to {peer}");
+            if format == OutputFormat::Text {
+                println!("connected to {peer}");
+            }
 
+            let bytes = text.len() as u64;
             session.clipboard.write(ClipboardContent::Text(text))?;
             session.send_clipboard().await?;
-            println!("sent clip:text");
+            SendOutput { peer_id: peer, sent: "clip:text".into(), bytes }.print(format);
         }
-        Command::SendFile { addr, path, id_path, trust_path, pairing_mode } => {
+        Command::SendFile { addr, path: paths, id_path, trust_path, pairing_mode, resume, rate_limit, chunk_size, window } => {
+            if resume && paths.len() > 1 {
+                anyhow::bail!("--resume takes a single --path");
+            }
             let id_path = id_path.unwrap_or_else(default_identity_path);
             let trust_path = trust_path.unwrap_or_else(default_trust_path);
             let identity = load_or_create_identity(&id_path)?;
@@ -337,21 +468,76 @@ async fn main() -> Result<()> {
                 Session::with_pairing_mode_and_replay(conn, identity, MockClipboard::new(), trust, replay)
             } else {
                 Session::with_trust_and_replay(conn, identity, MockClipboard::new(), trust, replay)
-            };
+            }
+            .with_raw_file_chunks()
+            .with_file_transfer_config(FileTransferConfig { chunk_size, window });
 
             let peer = session.handshake().await?;
-            println!("connected to {peer}");
-
-            send_file(&session, &path).await?;
-            println!("sent file {}", path.display());
+            if format == OutputFormat::Text {
+                println!("connected to {peer}");
+            }
+            session.set_file_rate_limiters(vec![Arc::new(RateLimiter::new(Some(rate_limit)))]);
+
+            if let [path] = paths.as_slice() {
+                let bytes = fs::metadata(path)?.len();
+                let start_offset = if resume {
+                    query_resume_offset(&session, &file_id_for(&fs::read(path)?)).await?
+                } else {
+                    0
+                };
+                send_file(&session, path, start_offset, &FileTransferConfig { chunk_size, window }).await?;
+                SendOutput { peer_id: peer, sent: path.display().to_string(), bytes }.print(format);
+            } else {
+                let rejected = session.send_files(&paths).await?;
+                for path in &paths {
+                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file.bin");
+                    if let Some(r) = rejected.iter().find(|r| r.name == name) {
+                        eprintln!("{r}");
+                        continue;
+                    }
+                    let bytes = fs::metadata(path)?.len();
+                    SendOutput { peer_id: peer.clone(), sent: path.display().to_string(), bytes }.print(format);
+                }
+                if !rejected.is_empty() {
+                    anyhow::bail!("{} of {} files were rejected", rejected.len(), paths.len());
+                }
+            }
         }
     }
 
     Ok(())
 }
 
-struct IncomingFile {
-    name: String,
-    expected: u64,
-    buf: Vec<u8>,
+/// Report a received file chunk and place it; a bad one drops the file.
+fn place_file_chunk(
+    files: &mut HashMap<String, FileAssembler>,
+    partial: &PartialFiles,
+    peer_id: &str,
+    file_id: String,
+    offset: u64,
+    data: &[u8],
+    format: OutputFormat,
+) {
+    ServeEvent::FileChunk { file_id: file_id.clone(), offset, len: data.len() }.print(format);
+    if let Some(f) = files.get_mut(&file_id)
+        && let Err(e) = f.insert(offset, data)
+    {
+        eprintln!("bad chunk: {e}");
+        files.remove(&file_id);
+        partial.discard(peer_id, &file_id);
+    }
 }
+
+#[cfg(feature = "status-http")]
+async fn start_status_endpoint(port: u16, status: Arc<NodeStatus>, format: OutputFormat) -> Result<()> {
+    let listener = openclipboard::status::bind(port).await?;
+    ServeEvent::StatusEndpoint { url: format!("http://{}/status", listener.local_addr()?) }.print(format);
+    tokio::spawn(openclipboard::status::serve(listener, status));
+    Ok(())
+}
+
+#[cfg(not(feature = "status-http"))]
+async fn start_status_endpoint(_port: u16, _status: Arc<NodeStatus>, _format: OutputFormat) -> Result<()> {
+    anyhow::bail!("--status-port requires building with the `status-http` feature")
+}
+
@@ -0,0 +1,205 @@
+//! What each CLI command reports, as typed values printed either as the usual
+//! human lines or, with `--output json`, as one JSON object per line.
+//!
+//! Field names are part of the scripting interface; add fields rather than
+//! renaming them.
+
+use serde::Serialize;
+
+/// The global `--output` flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// A command result that can be printed in either format.
+pub trait CommandOutput: Serialize {
+    /// The human-readable lines, without a trailing newline.
+    fn text(&self) -> String;
+
+    /// Print to stdout in `format`.
+    fn print(&self, format: OutputFormat) {
+        match format {
+            OutputFormat::Text => println!("{}", self.text()),
+            OutputFormat::Json => println!("{}", serde_json::to_string(self).expect("output serializes")),
+        }
+    }
+}
+
+/// `id:new` and `id:show`.
+#[derive(Debug, Clone, Serialize)]
+pub struct IdentityOutput {
+    pub path: String,
+    pub peer_id: String,
+    pub pubkey_b64: String,
+    pub name: Option<String>,
+    pub platform: Option<String>,
+    /// Written by this command (`id:new`) rather than loaded.
+    pub created: bool,
+}
+
+impl CommandOutput for IdentityOutput {
+    fn text(&self) -> String {
+        let mut lines = Vec::new();
+        if self.created {
+            lines.push(format!("wrote identity: {}", self.path));
+            lines.push(format!("peer_id: {}", self.peer_id));
+        } else {
+            lines.push(format!("identity: {}", self.path));
+            lines.push(format!("peer_id: {}", self.peer_id));
+            if let Some(name) = &self.name {
+                lines.push(format!("name: {name}"));
+                if let Some(platform) = &self.platform {
+                    lines.push(format!("platform: {platform}"));
+                }
+            }
+        }
+        lines.push(format!("pubkey_b64: {}", self.pubkey_b64));
+        lines.join("\n")
+    }
+}
+
+/// `pair:init`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PairInitOutput {
+    pub init_qr: String,
+}
+
+impl CommandOutput for PairInitOutput {
+    fn text(&self) -> String {
+        format!("init_qr: {}\nnote: waiting for responder payload to derive code", self.init_qr)
+    }
+}
+
+/// `pair:respond`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PairRespondOutput {
+    pub resp_qr: String,
+    pub code: String,
+}
+
+impl CommandOutput for PairRespondOutput {
+    fn text(&self) -> String {
+        format!("resp_qr: {}\ncode: {}", self.resp_qr, self.code)
+    }
+}
+
+/// One side of a finalized pairing.
+#[derive(Debug, Clone, Serialize)]
+pub struct PairedPeer {
+    pub peer_id: String,
+    pub name: String,
+}
+
+/// `pair:finalize`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PairFinalizeOutput {
+    pub code: String,
+    pub init: PairedPeer,
+    pub resp: PairedPeer,
+    pub trust_path: String,
+    /// False if the user declined to write the records.
+    pub written: bool,
+}
+
+impl CommandOutput for PairFinalizeOutput {
+    fn text(&self) -> String {
+        if self.written { format!("wrote trust store: {}", self.trust_path) } else { "aborted".into() }
+    }
+}
+
+/// `trust:remove`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrustRemoveOutput {
+    pub peer_id: String,
+    pub trust_path: String,
+    /// False if the peer wasn't trusted.
+    pub removed: bool,
+}
+
+impl CommandOutput for TrustRemoveOutput {
+    fn text(&self) -> String {
+        if self.removed {
+            format!("removed {} from {}", self.peer_id, self.trust_path)
+        } else {
+            format!("{} was not trusted", self.peer_id)
+        }
+    }
+}
+
+/// `send:text` and `send:file`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SendOutput {
+    pub peer_id: String,
+    /// `clip:text`, or the path of the file sent.
+    pub sent: String,
+    pub bytes: u64,
+}
+
+impl CommandOutput for SendOutput {
+    fn text(&self) -> String {
+        match self.sent.as_str() {
+            "clip:text" => "sent clip:text".into(),
+            path => format!("sent file {path}"),
+        }
+    }
+}
+
+/// What `serve` reports as it runs, one line each.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ServeEvent {
+    Listening { addr: String, trust_path: String },
+    StatusEndpoint { url: String },
+    PeerConnected { peer_id: String },
+    ClipText { ts_ms: u64, bytes: usize, preview: String },
+    ClipImage { ts_ms: u64, mime: String, width: u32, height: u32, bytes: usize },
+    FileOffer { file_id: String, name: String, size: u64, mime: String },
+    FileReject { file_id: String, reason: String },
+    FileAccept { file_id: String },
+    FileChunk { file_id: String, offset: u64, len: usize },
+    FileDone { file_id: String, hash: String },
+    /// A reconnecting sender asked where to continue `file_id`.
+    FileResume { file_id: String, offset: u64 },
+    FileReceived { name: String, bytes: usize, expected: u64 },
+    FileWritten { path: String },
+    Message { msg_type: String },
+}
+
+impl CommandOutput for ServeEvent {
+    fn text(&self) -> String {
+        match self {
+            Self::Listening { addr, trust_path } => format!("listening on {addr} (trust: {trust_path})"),
+            Self::StatusEndpoint { url } => format!("status endpoint on {url}"),
+            Self::PeerConnected { peer_id } => format!("trusted peer connected: {peer_id}"),
+            Self::ClipText { ts_ms, bytes, preview } => format!("clip:text ts_ms={ts_ms} bytes={bytes} preview={preview:?}"),
+            Self::ClipImage { ts_ms, mime, width, height, bytes } => {
+                format!("clip:image ts_ms={ts_ms} mime={mime} {width}x{height} bytes={bytes}")
+            }
+            Self::FileOffer { file_id, name, size, mime } => format!("file:offer id={file_id} name={name} size={size} mime={mime}"),
+            Self::FileReject { file_id, reason } => format!("file:reject id={file_id} reason={reason}"),
+            Self::FileAccept { file_id } => format!("file:accept id={file_id}"),
+            Self::FileChunk { file_id, offset, len } => format!("file:chunk id={file_id} offset={offset} len={len}"),
+            Self::FileDone { file_id, hash } => format!("file:done id={file_id} hash={hash}"),
+            Self::FileResume { file_id, offset } => format!("file:resume id={file_id} offset={offset}"),
+            Self::FileReceived { name, bytes, expected } => format!("file:received name={name} bytes={bytes} expected={expected}"),
+            Self::FileWritten { path } => format!("file:written {path}"),
+            Self::Message { msg_type } => format!("msg: {msg_type}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serve_events_are_tagged_and_keep_their_text_form() {
+        let ev = ServeEvent::FileReject { file_id: "f1".into(), reason: "mime-not-allowed".into() };
+        assert_eq!(ev.text(), "file:reject id=f1 reason=mime-not-allowed");
+        let json: serde_json::Value = serde_json::to_value(&ev).unwrap();
+        assert_eq!(json, serde_json::json!({"event": "file_reject", "file_id": "f1", "reason": "mime-not-allowed"}));
+    }
+}
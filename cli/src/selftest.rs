@@ -0,0 +1,217 @@
+//! In-process connectivity self-test: two nodes with mutual trust over
+//! `MockDiscovery` run discovery → handshake → clip → history on loopback.
+//!
+//! Each stage is reported separately so a failure points at the layer that broke.
+
+use openclipboard_core::{
+    Discovery, Ed25519Identity, IdentityProvider, MemoryReplayProtector, MemoryTrustStore,
+    MockDiscovery, SyncHandler, SyncService, TrustRecord, TrustStore,
+};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const SELFTEST_TEXT: &str = "openclipboard selftest";
+
+/// Outcome of one self-test stage.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StageResult {
+    pub stage: &'static str,
+    pub ok: bool,
+    pub detail: String,
+}
+
+/// Stage results in run order; stops after the first failed stage.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SelftestReport {
+    pub stages: Vec<StageResult>,
+}
+
+impl SelftestReport {
+    /// True if every stage ran and passed.
+    pub fn passed(&self) -> bool {
+        self.stages.len() == STAGES.len() && self.stages.iter().all(|s| s.ok)
+    }
+}
+
+const STAGES: [&str; 4] = ["discovery", "handshake", "clip", "history"];
+
+#[derive(Default)]
+struct Recorder {
+    texts: Mutex<Vec<(String, String)>>,
+    connected: Mutex<Vec<String>>,
+    errors: Mutex<Vec<String>>,
+}
+
+impl SyncHandler for Recorder {
+    fn on_clipboard_text(&self, peer_id: String, text: String, _ts_ms: u64) {
+        self.texts.lock().unwrap().push((peer_id, text));
+    }
+
+    fn on_peer_connected(&self, peer_id: String) {
+        self.connected.lock().unwrap().push(peer_id);
+    }
+
+    fn on_peer_disconnected(&self, _peer_id: String) {}
+
+    fn on_error(&self, message: String) {
+        self.errors.lock().unwrap().push(message);
+    }
+}
+
+struct Node {
+    identity: Ed25519Identity,
+    discovery: MockDiscovery,
+    handler: Arc<Recorder>,
+    service: SyncService<MockDiscovery>,
+}
+
+fn make_node(
+    identity: Ed25519Identity,
+    peer: &Ed25519Identity,
+    discovery: MockDiscovery,
+    name: &str,
+) -> anyhow::Result<Node> {
+    let trust = Arc::new(MemoryTrustStore::new());
+    trust.save(TrustRecord {
+        peer_id: peer.peer_id().to_string(),
+        identity_pk: peer.public_key_bytes(),
+        display_name: "selftest peer".into(),
+        created_at: chrono::Utc::now(),
+        sync_direction: Default::default(),
+    })?;
+    let handler = Arc::new(Recorder::default());
+    let service = SyncService::new(
+        identity.clone(),
+        trust,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(discovery.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        name.into(),
+        handler.clone(),
+    )?;
+    Ok(Node { identity, discovery, handler, service })
+}
+
+/// Poll `check` until it returns true or `timeout` elapses.
+async fn wait_for<F, Fut>(timeout: Duration, mut check: F) -> bool
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    let start = Instant::now();
+    loop {
+        if check().await {
+            return true;
+        }
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+fn errors_of(nodes: &[&Node]) -> String {
+    let errors: Vec<String> = nodes
+        .iter()
+        .flat_map(|n| n.handler.errors.lock().unwrap().clone())
+        .collect();
+    if errors.is_empty() {
+        "no errors reported".into()
+    } else {
+        errors.join("; ")
+    }
+}
+
+/// Run the self-test; `stage_timeout` bounds each stage.
+pub async fn run(stage_timeout: Duration) -> SelftestReport {
+    let mut stages = Vec::new();
+    let shared = MockDiscovery::new_shared();
+    let id_a = Ed25519Identity::generate();
+    let id_b = Ed25519Identity::generate();
+
+    let nodes = make_node(id_a.clone(), &id_b, shared.clone_shared(), "selftest-a")
+        .and_then(|a| Ok((a, make_node(id_b.clone(), &id_a, shared.clone_shared(), "selftest-b")?)));
+    let (a, b) = match nodes {
+        Ok(n) => n,
+        Err(e) => {
+            stages.push(StageResult { stage: "discovery", ok: false, detail: format!("node setup failed: {e}") });
+            return SelftestReport { stages };
+        }
+    };
+
+    run_stages(&a, &b, stage_timeout, &mut stages).await;
+
+    a.service.stop().await;
+    b.service.stop().await;
+    SelftestReport { stages }
+}
+
+async fn run_stages(a: &Node, b: &Node, timeout: Duration, stages: &mut Vec<StageResult>) {
+    for node in [a, b] {
+        if let Err(e) = node.service.start().await {
+            stages.push(StageResult { stage: "discovery", ok: false, detail: format!("start failed: {e}") });
+            return;
+        }
+    }
+
+    let sees = |n: &Node, other: &Node| {
+        let other_id = other.identity.peer_id().to_string();
+        let disc = n.discovery.clone_shared();
+        async move { disc.scan().await.is_ok_and(|peers| peers.iter().any(|p| p.peer_id == other_id)) }
+    };
+    let ok = wait_for(timeout, || {
+        let (ab, ba) = (sees(a, b), sees(b, a));
+        async move { ab.await && ba.await }
+    })
+    .await;
+    stages.push(StageResult {
+        stage: "discovery",
+        ok,
+        detail: if ok { "both nodes advertised and found each other".into() } else { errors_of(&[a, b]) },
+    });
+    if !ok {
+        return;
+    }
+
+    let ok = wait_for(timeout, || async {
+        !a.handler.connected.lock().unwrap().is_empty() && !b.handler.connected.lock().unwrap().is_empty()
+    })
+    .await;
+    stages.push(StageResult {
+        stage: "handshake",
+        ok,
+        detail: if ok { "authenticated QUIC session established".into() } else { errors_of(&[a, b]) },
+    });
+    if !ok {
+        return;
+    }
+
+    a.service.broadcast_clip_text(SELFTEST_TEXT.into()).await;
+    let ok = wait_for(timeout, || async {
+        b.handler.texts.lock().unwrap().iter().any(|(_, t)| t == SELFTEST_TEXT)
+    })
+    .await;
+    stages.push(StageResult {
+        stage: "clip",
+        ok,
+        detail: if ok { "clip delivered to peer".into() } else { errors_of(&[a, b]) },
+    });
+    if !ok {
+        return;
+    }
+
+    let entry = b.service.history().get_recent(1).into_iter().next();
+    let ok = entry
+        .as_ref()
+        .is_some_and(|e| e.content == SELFTEST_TEXT && e.source_peer == a.identity.peer_id());
+    stages.push(StageResult {
+        stage: "history",
+        ok,
+        detail: match entry {
+            Some(e) if ok => format!("recorded as entry {}", e.id),
+            Some(e) => format!("unexpected latest entry from {}: {:?}", e.source_peer, e.content),
+            None => "receiver history is empty".into(),
+        },
+    });
+}
@@ -0,0 +1,148 @@
+//! Node status counters, plus a minimal localhost HTTP endpoint serving them
+//! (`status-http` feature).
+//!
+//! `GET /status` returns a JSON snapshot of the running node so a headless
+//! instance can be scraped without parsing logs. Plain HTTP/1.1 over tokio;
+//! one request per connection.
+
+use openclipboard_core::ClipboardHistory;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+#[cfg(feature = "status-http")]
+use anyhow::{Context, Result};
+#[cfg(feature = "status-http")]
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+#[cfg(feature = "status-http")]
+use tokio::net::{TcpListener, TcpStream};
+
+/// Largest request head we read before giving up on a client.
+#[cfg(feature = "status-http")]
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// JSON body served at `/status`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StatusSnapshot {
+    pub peer_count: usize,
+    pub uptime_secs: u64,
+    pub history_size: usize,
+    pub metrics: BTreeMap<String, u64>,
+}
+
+/// Live counters shared between the node and the status endpoint.
+pub struct NodeStatus {
+    started: Instant,
+    history: Arc<ClipboardHistory>,
+    peers: AtomicUsize,
+    clips_received: AtomicU64,
+    files_received: AtomicU64,
+    handshakes_ok: AtomicU64,
+    handshakes_failed: AtomicU64,
+}
+
+impl NodeStatus {
+    pub fn new(history: Arc<ClipboardHistory>) -> Self {
+        Self {
+            started: Instant::now(),
+            history,
+            peers: AtomicUsize::new(0),
+            clips_received: AtomicU64::new(0),
+            files_received: AtomicU64::new(0),
+            handshakes_ok: AtomicU64::new(0),
+            handshakes_failed: AtomicU64::new(0),
+        }
+    }
+
+    pub fn peer_connected(&self) {
+        self.peers.fetch_add(1, Ordering::Relaxed);
+        self.handshakes_ok.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn peer_disconnected(&self) {
+        let _ = self.peers.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+    }
+
+    pub fn handshake_failed(&self) {
+        self.handshakes_failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn clip_received(&self) {
+        self.clips_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn file_received(&self) {
+        self.files_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> StatusSnapshot {
+        let metrics = [
+            ("clips_received", &self.clips_received),
+            ("files_received", &self.files_received),
+            ("handshakes_ok", &self.handshakes_ok),
+            ("handshakes_failed", &self.handshakes_failed),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.load(Ordering::Relaxed)))
+        .collect();
+        StatusSnapshot {
+            peer_count: self.peers.load(Ordering::Relaxed),
+            uptime_secs: self.started.elapsed().as_secs(),
+            history_size: self.history.len(),
+            metrics,
+        }
+    }
+}
+
+/// Bind the status endpoint on localhost. Port 0 picks an ephemeral port.
+#[cfg(feature = "status-http")]
+pub async fn bind(port: u16) -> Result<TcpListener> {
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("bind status endpoint {addr}"))
+}
+
+/// Serve `/status` until the listener fails; run it in its own task.
+#[cfg(feature = "status-http")]
+pub async fn serve(listener: TcpListener, status: Arc<NodeStatus>) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let status = Arc::clone(&status);
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream, &status).await {
+                eprintln!("status request failed: {e:#}");
+            }
+        });
+    }
+}
+
+#[cfg(feature = "status-http")]
+async fn handle(mut stream: TcpStream, status: &NodeStatus) -> Result<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        anyhow::ensure!(buf.len() <= MAX_REQUEST_BYTES, "request head too large");
+    }
+
+    let head = String::from_utf8_lossy(&buf);
+    let mut parts = head.lines().next().unwrap_or_default().split_whitespace();
+    let (code, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/status")) => ("200 OK", serde_json::to_string(&status.snapshot())?),
+        (Some("GET"), Some(_)) => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+        _ => ("405 Method Not Allowed", r#"{"error":"method not allowed"}"#.to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {code}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
@@ -19,6 +19,7 @@ async fn e2e_quic_trusted_handshake_and_cliptext() {
             identity_pk: bob.public_key_bytes(),
             display_name: "Bob".into(),
             created_at: chrono::Utc::now(),
+            sync_direction: Default::default(),
         })
         .unwrap();
 
@@ -29,6 +30,7 @@ async fn e2e_quic_trusted_handshake_and_cliptext() {
             identity_pk: alice.public_key_bytes(),
             display_name: "Alice".into(),
             created_at: chrono::Utc::now(),
+            sync_direction: Default::default(),
         })
         .unwrap();
 
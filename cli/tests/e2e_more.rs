@@ -1,4 +1,3 @@
-use base64::Engine as _;
 use openclipboard::{pairing_finalize, pairing_init_qr, pairing_respond_qr};
 use openclipboard_core::clipboard::MockClipboard;
 use openclipboard_core::identity::IdentityProvider;
@@ -6,7 +5,7 @@ use openclipboard_core::quic_transport::{
     make_insecure_client_endpoint, make_server_endpoint, QuicListener, QuicTransport,
 };
 use openclipboard_core::{
-    ClipboardContent, ClipboardProvider, Ed25519Identity, Listener, MemoryReplayProtector,
+    ClipboardContent, ClipboardProvider, Ed25519Identity, FileTransferConfig, Listener, MemoryReplayProtector,
     MemoryTrustStore, Session, Transport, TrustRecord, TrustStore,
 };
 use std::net::SocketAddr;
@@ -14,7 +13,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 fn trust_from_pairing(alice: &Ed25519Identity, bob: &Ed25519Identity) -> (Arc<MemoryTrustStore>, Arc<MemoryTrustStore>) {
-    let init_qr = pairing_init_qr("Alice".into(), 1234, alice, [1u8; 32]);
+    let init_qr = pairing_init_qr(Some("Alice".into()), None, 1234, alice, [1u8; 32]);
     let (resp_qr, _code) = pairing_respond_qr(&init_qr, "Bob".into(), 2345, bob).unwrap();
     let (_code2, recs) = pairing_finalize(&init_qr, &resp_qr).unwrap();
 
@@ -33,6 +32,7 @@ fn trust_from_pairing(alice: &Ed25519Identity, bob: &Ed25519Identity) -> (Arc<Me
                     identity_pk: rec.identity_pk,
                     display_name: rec.display_name,
                     created_at: chrono::Utc::now(),
+                    sync_direction: Default::default(),
                 })
                 .unwrap();
         } else if rec.peer_id == rec_bob {
@@ -42,6 +42,7 @@ fn trust_from_pairing(alice: &Ed25519Identity, bob: &Ed25519Identity) -> (Arc<Me
                     identity_pk: rec.identity_pk,
                     display_name: rec.display_name,
                     created_at: chrono::Utc::now(),
+                    sync_direction: Default::default(),
                 })
                 .unwrap();
         }
@@ -166,7 +167,9 @@ async fn e2e_pair_and_send_image() {
     server.await.unwrap();
 }
 
-async fn e2e_send_file_case(size: usize) {
+/// Send `size` bytes, as `FileChunkRaw` if `raw` (negotiated by both sides)
+/// and as base64 `FileChunk` otherwise.
+async fn e2e_send_file_case(size: usize, raw: bool, config: FileTransferConfig) {
     let alice = Ed25519Identity::generate();
     let bob = Ed25519Identity::generate();
     let (trust_a, trust_b) = trust_from_pairing(&alice, &bob);
@@ -190,8 +193,12 @@ async fn e2e_send_file_case(size: usize) {
 
     let server = tokio::spawn(async move {
         let conn = listener.accept().await.unwrap();
-        let session = Session::with_trust_and_replay(conn, bob, MockClipboard::new(), trust_b, replay_b);
+        let mut session = Session::with_trust_and_replay(conn, bob, MockClipboard::new(), trust_b, replay_b);
+        if raw {
+            session = session.with_raw_file_chunks();
+        }
         session.handshake().await.unwrap();
+        assert_eq!(session.raw_file_chunks_enabled(), raw);
 
         let mut buf: Vec<u8> = Vec::new();
         let mut want_file_id: Option<String> = None;
@@ -205,12 +212,21 @@ async fn e2e_send_file_case(size: usize) {
                     want_size = Some(size);
                     session.send_file_accept(&file_id).await.unwrap();
                 }
-                openclipboard_core::Message::FileChunk { file_id, data_b64, .. } => {
+                openclipboard_core::Message::FileChunk { file_id, offset, data_b64 } => {
+                    assert!(!raw, "base64 chunk on a raw session");
                     if want_file_id.as_deref() == Some(&file_id) {
-                        let bytes = base64::engine::general_purpose::STANDARD.decode(data_b64).unwrap();
+                        assert_eq!(offset, buf.len() as u64, "chunk out of order");
+                        let bytes = openclipboard_core::Base64Alphabet::Standard.decode(data_b64).unwrap();
                         buf.extend_from_slice(&bytes);
                     }
                 }
+                openclipboard_core::Message::FileChunkRaw { file_id, offset, data } => {
+                    assert!(raw, "raw chunk on a base64 session");
+                    if want_file_id.as_deref() == Some(&file_id) {
+                        assert_eq!(offset, buf.len() as u64, "chunk out of order");
+                        buf.extend_from_slice(&data);
+                    }
+                }
                 openclipboard_core::Message::FileDone { file_id, hash } => {
                     if want_file_id.as_deref() == Some(&file_id) {
                         tx.send(format!("done:{}:{}", buf.len(), hash)).unwrap();
@@ -231,7 +247,10 @@ async fn e2e_send_file_case(size: usize) {
     let transport = QuicTransport::new(endpoint);
     let conn = transport.connect(&addr.to_string()).await.unwrap();
 
-    let session = Session::with_trust_and_replay(conn, alice, MockClipboard::new(), trust_a, replay_a);
+    let mut session = Session::with_trust_and_replay(conn, alice, MockClipboard::new(), trust_a, replay_a);
+    if raw {
+        session = session.with_raw_file_chunks();
+    }
     session.handshake().await.unwrap();
 
     let file_id = blake3::hash(format!("file.bin:{}", data.len()).as_bytes())
@@ -245,12 +264,7 @@ async fn e2e_send_file_case(size: usize) {
     // Wait briefly for accept to reduce flakiness.
     let _ = tokio::time::timeout(Duration::from_secs(1), session.recv_message()).await;
 
-    const CHUNK: usize = 64 * 1024;
-    let mut offset = 0u64;
-    for chunk in data.chunks(CHUNK) {
-        session.send_file_chunk(&file_id, offset, chunk).await.unwrap();
-        offset += chunk.len() as u64;
-    }
+    session.send_file_data(&file_id, 0, &data, &config).await.unwrap();
 
     session.send_file_done(&file_id, &expected_hash).await.unwrap();
 
@@ -265,13 +279,56 @@ async fn e2e_send_file_case(size: usize) {
 
 #[tokio::test]
 async fn e2e_pair_and_send_file_small() {
-    e2e_send_file_case(32 * 1024).await;
+    e2e_send_file_case(32 * 1024, false, FileTransferConfig::default()).await;
+}
+
+#[tokio::test]
+async fn e2e_pair_and_send_file_small_raw() {
+    e2e_send_file_case(32 * 1024, true, FileTransferConfig::default()).await;
 }
 
 #[tokio::test]
 async fn e2e_pair_and_send_file_large() {
     // ~8 MiB, big enough to exercise chunking but still fast.
-    e2e_send_file_case(8 * 1024 * 1024).await;
+    e2e_send_file_case(8 * 1024 * 1024, false, FileTransferConfig::default()).await;
+}
+
+#[tokio::test]
+async fn e2e_pair_and_send_file_large_raw() {
+    e2e_send_file_case(8 * 1024 * 1024, true, FileTransferConfig::default()).await;
+}
+
+#[tokio::test]
+async fn e2e_pair_and_send_file_large_pipelined_keeps_chunks_in_order() {
+    let config = FileTransferConfig { chunk_size: 16 * 1024, window: 8 };
+    e2e_send_file_case(8 * 1024 * 1024, false, config).await;
+    e2e_send_file_case(8 * 1024 * 1024, true, config).await;
+}
+
+/// Run `bench-throughput` with `window` and return the elapsed seconds it
+/// reports; its receiver fails the run if a chunk arrives out of order or any
+/// are missing.
+fn bench_throughput_elapsed(window: usize) -> f64 {
+    let total = 16 * 1024 * 1024;
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_openclipboard_e2e"))
+        .args(["bench-throughput", "--total-bytes", &total.to_string(), "--window", &window.to_string()])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "stdout: {stdout}\nstderr: {}", String::from_utf8_lossy(&out.stderr));
+    assert!(stdout.contains(&format!("total_bytes={total} ")), "{stdout}");
+    assert!(stdout.contains(&format!("window={window} ")), "{stdout}");
+    let elapsed = stdout.split_whitespace().find_map(|kv| kv.strip_prefix("elapsed_s=")).unwrap();
+    elapsed.parse().unwrap()
+}
+
+#[test]
+fn bench_throughput_with_a_window_is_not_slower_than_one_at_a_time() {
+    let one = bench_throughput_elapsed(1);
+    let eight = bench_throughput_elapsed(8);
+    // Loopback timing on a shared runner is noisy, so this leaves room for it
+    // and only catches a window that makes the pipeline slower.
+    assert!(eight <= one * 1.5, "window=8 took {eight:.3}s vs {one:.3}s for window=1");
 }
 
 #[tokio::test]
@@ -351,6 +408,7 @@ async fn e2e_reject_spoofed_peer() {
             identity_pk: victim.public_key_bytes(),
             display_name: "Victim".into(),
             created_at: chrono::Utc::now(),
+            sync_direction: Default::default(),
         })
         .unwrap();
 
@@ -397,3 +455,176 @@ async fn e2e_reject_spoofed_peer() {
 
     server.await.unwrap();
 }
+
+/// A transfer cut off halfway is picked up from the receiver's saved part on
+/// reconnect, and the reassembled file still hashes to the original.
+#[tokio::test]
+async fn e2e_dropped_file_transfer_resumes_from_the_saved_offset() {
+    use openclipboard::{file_id_for, query_resume_offset, send_file};
+    use openclipboard_core::{Connection, FileAssembler, Message, PartialFiles};
+    use std::collections::HashMap;
+
+    const SIZE: usize = 8 * 1024 * 1024;
+    let config = FileTransferConfig::default();
+
+    let alice = Ed25519Identity::generate();
+    let bob = Ed25519Identity::generate();
+    let (trust_a, trust_b) = trust_from_pairing(&alice, &bob);
+    let replay_a = Arc::new(MemoryReplayProtector::new(128));
+    let replay_b = Arc::new(MemoryReplayProtector::new(128));
+
+    let dir = tempfile::tempdir().unwrap();
+    let data: Vec<u8> = (0..SIZE).map(|i| (i as u8).wrapping_mul(31).wrapping_add(7)).collect();
+    let path = dir.path().join("big.bin");
+    std::fs::write(&path, &data).unwrap();
+    let file_id = file_id_for(&data);
+
+    let bind: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let (endpoint, _cert) = make_server_endpoint(bind).unwrap();
+    let listener = QuicListener::new(endpoint);
+    let addr = listener.local_addr().unwrap();
+
+    // The receiver, handling files the way `serve` does.
+    let partial = PartialFiles::new(dir.path().join("partial"));
+    let server = tokio::spawn(async move {
+        let mut files: HashMap<String, FileAssembler> = HashMap::new();
+        loop {
+            let conn = listener.accept().await.unwrap();
+            let session = Session::with_trust_and_replay(conn, bob.clone(), MockClipboard::new(), trust_b.clone(), replay_b.clone())
+                .with_raw_file_chunks();
+            let peer_id = session.handshake().await.unwrap();
+            loop {
+                let Ok(msg) = session.recv_message().await else {
+                    partial.save_all(&peer_id, &mut files).unwrap();
+                    break;
+                };
+                match msg {
+                    Message::FileOffer { file_id, name, size, hash, .. } => {
+                        let file = partial.resume(&peer_id, &file_id, FileAssembler::new(name, size).with_hash(hash)).unwrap();
+                        files.insert(file_id.clone(), file);
+                        session.send_file_accept(&file_id).await.unwrap();
+                    }
+                    Message::FileChunkRaw { file_id, offset, data } => {
+                        files.get_mut(&file_id).unwrap().insert(offset, &data).unwrap();
+                    }
+                    Message::FileResumeQuery { file_id } => {
+                        let offset = files.get(&file_id).map_or_else(|| partial.saved_len(&peer_id, &file_id), FileAssembler::next_offset);
+                        session.send_file_resume_offset(&file_id, offset).await.unwrap();
+                    }
+                    Message::Ping { ts_ms } => session.send_pong(ts_ms).await.unwrap(),
+                    Message::FileDone { file_id, hash } => {
+                        let received = files.remove(&file_id).unwrap().finish().unwrap();
+                        partial.discard(&peer_id, &file_id);
+                        return (hash, blake3::hash(&received).to_hex().to_string());
+                    }
+                    _ => {}
+                }
+            }
+        }
+    });
+
+    let connect = || async {
+        let transport = QuicTransport::new(make_insecure_client_endpoint().unwrap());
+        let conn = transport.connect(&addr.to_string()).await.unwrap();
+        let session = Session::with_trust_and_replay(conn, alice.clone(), MockClipboard::new(), trust_a.clone(), replay_a.clone())
+            .with_raw_file_chunks();
+        session.handshake().await.unwrap();
+        session
+    };
+
+    // First attempt: half the chunks get through, then the connection drops.
+    let half = SIZE / 2;
+    let first = connect().await;
+    first.send_file_offer(&file_id, "big.bin", SIZE as u64, "application/octet-stream").await.unwrap();
+    assert!(matches!(recv_with_timeout(&first, Duration::from_secs(5)).await, Message::FileAccept { .. }));
+    first.send_file_data(&file_id, 0, &data[..half], &config).await.unwrap();
+    // The pong confirms the receiver has read every chunk before cutting it off.
+    first.send_ping(1).await.unwrap();
+    assert_eq!(recv_with_timeout(&first, Duration::from_secs(5)).await, Message::Pong { ts_ms: 1 });
+    first.conn.close();
+    drop(first);
+
+    // Reconnect, ask where to continue, and send only the rest.
+    let second = connect().await;
+    let offset = query_resume_offset(&second, &file_id).await.unwrap();
+    assert_eq!(offset, half as u64);
+    send_file(&second, &path, offset, &config).await.unwrap();
+
+    let (sent_hash, received_hash) = tokio::time::timeout(Duration::from_secs(10), server).await.unwrap().unwrap();
+    let expected = blake3::hash(&data).to_hex().to_string();
+    assert_eq!(sent_hash, expected);
+    assert_eq!(received_hash, expected);
+}
+
+#[tokio::test]
+async fn e2e_rejected_file_returns_the_receivers_reason() {
+    use openclipboard::{send_file, FileOfferPolicy};
+    use openclipboard_core::{FileAssembler, FileRejected, Message, REJECT_TOO_LARGE};
+    use std::collections::HashMap;
+
+    const MAX: u64 = 1024 * 1024;
+
+    let alice = Ed25519Identity::generate();
+    let bob = Ed25519Identity::generate();
+    let (trust_a, trust_b) = trust_from_pairing(&alice, &bob);
+
+    let dir = tempfile::tempdir().unwrap();
+    let big = dir.path().join("big.bin");
+    std::fs::write(&big, vec![1u8; MAX as usize + 1]).unwrap();
+    let small = dir.path().join("small.bin");
+    std::fs::write(&small, vec![2u8; 1000]).unwrap();
+
+    let bind: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let (endpoint, _cert) = make_server_endpoint(bind).unwrap();
+    let listener = QuicListener::new(endpoint);
+    let addr = listener.local_addr().unwrap();
+
+    // A receiver that declines anything over 1 MiB, the way `serve --max-file-size` does.
+    let server = tokio::spawn(async move {
+        let policy = FileOfferPolicy { max_size: Some(MAX), ..Default::default() };
+        let conn = listener.accept().await.unwrap();
+        let session = Session::with_trust_and_replay(conn, bob, MockClipboard::new(), trust_b, Arc::new(MemoryReplayProtector::new(128)))
+            .with_raw_file_chunks();
+        session.handshake().await.unwrap();
+        let mut files: HashMap<String, FileAssembler> = HashMap::new();
+        loop {
+            match recv_with_timeout(&session, Duration::from_secs(5)).await {
+                Message::FileOffer { file_id, name, size, mime, hash } => match policy.check(&name, size, &mime) {
+                    Ok(()) => {
+                        files.insert(file_id.clone(), FileAssembler::new(name, size).with_hash(hash));
+                        session.send_file_accept(&file_id).await.unwrap();
+                    }
+                    Err(reason) => session.send_file_reject(&file_id, reason).await.unwrap(),
+                },
+                Message::FileChunkRaw { file_id, offset, data } => {
+                    // Nothing of a rejected file may arrive.
+                    files.get_mut(&file_id).expect("chunk of a rejected file").insert(offset, &data).unwrap();
+                }
+                Message::FileDone { file_id, .. } => {
+                    let f = files.remove(&file_id).unwrap();
+                    return (f.name.clone(), f.finish().unwrap());
+                }
+                _ => {}
+            }
+        }
+    });
+
+    let transport = QuicTransport::new(make_insecure_client_endpoint().unwrap());
+    let conn = transport.connect(&addr.to_string()).await.unwrap();
+    let session = Session::with_trust_and_replay(conn, alice, MockClipboard::new(), trust_a, Arc::new(MemoryReplayProtector::new(128)))
+        .with_raw_file_chunks();
+    session.handshake().await.unwrap();
+
+    let err = send_file(&session, &big, 0, &FileTransferConfig::default()).await.unwrap_err();
+    assert_eq!(
+        FileRejected::of(&err),
+        Some(&FileRejected { name: "big.bin".into(), reason: REJECT_TOO_LARGE.into() })
+    );
+    assert!(err.to_string().contains(REJECT_TOO_LARGE), "{err}");
+
+    // The same session still takes a file under the limit.
+    send_file(&session, &small, 0, &FileTransferConfig::default()).await.unwrap();
+    let (name, data) = tokio::time::timeout(Duration::from_secs(5), server).await.unwrap().unwrap();
+    assert_eq!(name, "small.bin");
+    assert_eq!(data, vec![2u8; 1000]);
+}
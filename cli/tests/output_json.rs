@@ -0,0 +1,59 @@
+use std::process::Command;
+
+fn openclipboard(args: &[&str]) -> String {
+    let out = Command::new(env!("CARGO_BIN_EXE_openclipboard")).args(args).output().unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout).to_string();
+    assert!(out.status.success(), "stdout: {stdout}\nstderr: {}", String::from_utf8_lossy(&out.stderr));
+    stdout
+}
+
+#[test]
+fn id_show_prints_json_with_output_json_and_text_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("identity.json");
+    let path = path.to_str().unwrap();
+
+    let created: serde_json::Value =
+        serde_json::from_str(&openclipboard(&["--output", "json", "id:new", "--path", path, "--name", "Desk"])).unwrap();
+    assert_eq!(created["created"], true);
+
+    let stdout = openclipboard(&["id:show", "--path", path, "--output", "json"]);
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    let shown: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(shown["peer_id"], created["peer_id"]);
+    assert_eq!(shown["pubkey_b64"], created["pubkey_b64"]);
+    assert_eq!(shown["name"], "Desk");
+    assert_eq!(shown["path"], path);
+    assert!(shown["peer_id"].as_str().is_some_and(|s| !s.is_empty()));
+
+    let text = openclipboard(&["id:show", "--path", path]);
+    assert!(text.contains(&format!("peer_id: {}", shown["peer_id"].as_str().unwrap())), "{text}");
+    assert!(serde_json::from_str::<serde_json::Value>(&text).is_err());
+}
+
+#[test]
+fn trust_remove_revokes_the_peer_and_reports_whether_it_was_trusted() {
+    use openclipboard_core::{FileTrustStore, TrustRecord, TrustStore};
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("trust.json");
+    FileTrustStore::new(path.clone())
+        .unwrap()
+        .save(TrustRecord {
+            peer_id: "peer-a".into(),
+            identity_pk: vec![1; 32],
+            display_name: "A".into(),
+            created_at: chrono::Utc::now(),
+            sync_direction: Default::default(),
+        })
+        .unwrap();
+    let remove = || openclipboard(&["--output", "json", "trust:remove", "--peer-id", "peer-a", "--trust-path", path.to_str().unwrap()]);
+
+    let out: serde_json::Value = serde_json::from_str(&remove()).unwrap();
+    assert_eq!(out["removed"], true);
+    let store = FileTrustStore::new(path.clone()).unwrap();
+    assert!(store.get("peer-a").unwrap().is_none());
+    assert_eq!(store.revocations().unwrap()[0].peer_id, "peer-a");
+
+    let out: serde_json::Value = serde_json::from_str(&remove()).unwrap();
+    assert_eq!(out["removed"], false);
+}
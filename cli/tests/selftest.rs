@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+#[tokio::test]
+async fn selftest_reports_every_stage_passing() {
+    let report = openclipboard::selftest::run(Duration::from_secs(5)).await;
+    let stages: Vec<_> = report.stages.iter().map(|s| s.stage).collect();
+    assert_eq!(stages, ["discovery", "handshake", "clip", "history"]);
+    assert!(report.passed(), "selftest failed: {:?}", report.stages);
+}
+
+#[test]
+fn selftest_subcommand_exits_successfully() {
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_openclipboard_e2e"))
+        .args(["selftest", "--timeout-ms", "5000"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "stdout: {stdout}\nstderr: {}", String::from_utf8_lossy(&out.stderr));
+    assert!(stdout.contains(r#""passed":true"#), "{stdout}");
+}
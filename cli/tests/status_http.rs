@@ -0,0 +1,46 @@
+#![cfg(feature = "status-http")]
+
+use openclipboard::status::{NodeStatus, StatusSnapshot};
+use openclipboard_core::ClipboardHistory;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+async fn get(addr: std::net::SocketAddr, path: &str) -> (String, String) {
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes())
+        .await
+        .unwrap();
+    let mut resp = String::new();
+    stream.read_to_string(&mut resp).await.unwrap();
+    let (head, body) = resp.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), body.to_string())
+}
+
+#[tokio::test]
+async fn status_endpoint_serves_node_snapshot_as_json() {
+    let history = Arc::new(ClipboardHistory::new(10));
+    let status = Arc::new(NodeStatus::new(Arc::clone(&history)));
+    status.peer_connected();
+    status.clip_received();
+    history.record("hello".into(), "peer-a".into());
+
+    let listener = openclipboard::status::bind(0).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert!(addr.ip().is_loopback());
+    tokio::spawn(openclipboard::status::serve(listener, Arc::clone(&status)));
+
+    let (line, body) = get(addr, "/status").await;
+    assert_eq!(line, "HTTP/1.1 200 OK");
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    for field in ["peer_count", "uptime_secs", "history_size", "metrics"] {
+        assert!(json.get(field).is_some(), "missing {field} in {body}");
+    }
+    let snapshot: StatusSnapshot = serde_json::from_value(json).unwrap();
+    assert_eq!(snapshot.peer_count, 1);
+    assert_eq!(snapshot.history_size, 1);
+    assert_eq!(snapshot.metrics["clips_received"], 1);
+
+    let (line, _) = get(addr, "/nope").await;
+    assert_eq!(line, "HTTP/1.1 404 Not Found");
+}
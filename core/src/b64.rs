@@ -0,0 +1,89 @@
+//! One place to pick the base64 alphabet for payloads that leave the process.
+//!
+//! Wire messages keep `Standard`; strings meant to be scanned, typed or put in
+//! URLs (pairing QR codes) can use `UrlSafe`, since some scanners and
+//! transports mangle `+` and `/`. Decoders don't need to be told which one
+//! was used: [`decode_any`] tells the two apart from the characters present.
+
+use anyhow::Result;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine as _;
+
+/// Padding optional on decode, so padded and unpadded strings both parse.
+const LENIENT: GeneralPurposeConfig = GeneralPurposeConfig::new()
+    .with_encode_padding(false)
+    .with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const STANDARD_LENIENT: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, LENIENT);
+const URL_SAFE_LENIENT: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, LENIENT);
+
+/// Which base64 alphabet an encoded payload uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Base64Alphabet {
+    /// RFC 4648 `+` and `/`, padded.
+    #[default]
+    Standard,
+    /// RFC 4648 URL- and filename-safe `-` and `_`, unpadded.
+    UrlSafe,
+}
+
+impl Base64Alphabet {
+    pub fn encode(self, bytes: impl AsRef<[u8]>) -> String {
+        match self {
+            Self::Standard => base64::engine::general_purpose::STANDARD.encode(bytes),
+            Self::UrlSafe => base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes),
+        }
+    }
+
+    /// Decode strictly in this alphabet (padding optional).
+    pub fn decode(self, s: impl AsRef<[u8]>) -> Result<Vec<u8>> {
+        let engine = match self {
+            Self::Standard => &STANDARD_LENIENT,
+            Self::UrlSafe => &URL_SAFE_LENIENT,
+        };
+        Ok(engine.decode(s)?)
+    }
+
+    /// The alphabet `s` must be in: `UrlSafe` if it has `-` or `_`, else
+    /// `Standard`. The alphabets share every other character, so a string
+    /// with none of `+/-_` decodes the same either way.
+    pub fn detect(s: impl AsRef<[u8]>) -> Self {
+        if s.as_ref().iter().any(|b| matches!(b, b'-' | b'_')) { Self::UrlSafe } else { Self::Standard }
+    }
+}
+
+/// Decode base64 in either alphabet, padded or not. A string mixing the two
+/// alphabets is rejected.
+pub fn decode_any(s: impl AsRef<[u8]>) -> Result<Vec<u8>> {
+    let s = s.as_ref();
+    Base64Alphabet::detect(s).decode(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0xfb 0xff encodes to `+/8` or `-_8`, so both alphabets show up.
+    const PAYLOAD: &[u8] = &[0xfb, 0xff, 0xbf, 0x00, 0x10, 0x83];
+
+    #[test]
+    fn roundtrips_through_both_alphabets_and_detects_which() {
+        for alphabet in [Base64Alphabet::Standard, Base64Alphabet::UrlSafe] {
+            let encoded = alphabet.encode(PAYLOAD);
+            assert_eq!(Base64Alphabet::detect(&encoded), alphabet, "{encoded}");
+            assert_eq!(alphabet.decode(&encoded).unwrap(), PAYLOAD);
+            assert_eq!(decode_any(&encoded).unwrap(), PAYLOAD);
+        }
+        assert_eq!(Base64Alphabet::Standard.encode(PAYLOAD), "+/+/ABCD");
+        assert_eq!(Base64Alphabet::UrlSafe.encode(PAYLOAD), "-_-_ABCD");
+    }
+
+    #[test]
+    fn decode_any_takes_padding_either_way_and_rejects_mixed_alphabets() {
+        assert_eq!(decode_any("aGk=").unwrap(), b"hi");
+        assert_eq!(decode_any("aGk").unwrap(), b"hi");
+        assert_eq!(decode_any("-_8=").unwrap(), [0xfb, 0xff]);
+        assert!(decode_any("+_8").is_err());
+        assert!(Base64Alphabet::Standard.decode("-_8").is_err());
+    }
+}
@@ -0,0 +1,46 @@
+//! Wall-clock time behind a trait, so behavior that depends on the time of
+//! day (quiet hours) can be tested without waiting for it.
+
+use chrono::NaiveDateTime;
+use std::sync::Mutex;
+
+/// Source of the current local time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> NaiveDateTime;
+}
+
+/// The system's local time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        chrono::Local::now().naive_local()
+    }
+}
+
+/// A clock that only moves when told to.
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<NaiveDateTime>,
+}
+
+impl MockClock {
+    pub fn new(now: NaiveDateTime) -> Self {
+        Self { now: Mutex::new(now) }
+    }
+
+    pub fn set(&self, now: NaiveDateTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, by: chrono::Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> NaiveDateTime {
+        *self.now.lock().unwrap()
+    }
+}
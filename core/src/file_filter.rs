@@ -0,0 +1,162 @@
+//! Receive-side file type filtering: MIME / extension allow- and denylists
+//! checked against an incoming `FileOffer`.
+
+use std::collections::HashMap;
+
+/// `FileReject.reason` sent when an offer's type is filtered out.
+pub const REJECT_MIME_NOT_ALLOWED: &str = "mime-not-allowed";
+
+/// `FileReject.reason` sent when an offer is larger than the receiver takes.
+pub const REJECT_TOO_LARGE: &str = "too-large";
+
+/// `FileReject.reason` sent when the receiver doesn't take anything from the
+/// sender (its sync direction excludes receiving).
+pub const REJECT_NOT_ACCEPTING: &str = "not-accepting";
+
+/// The MIME type to offer a file as, from its name's extension;
+/// `application/octet-stream` when unknown.
+pub fn mime_for_name(name: &str) -> &'static str {
+    let ext = name.rsplit_once('.').map(|(_, e)| e.to_ascii_lowercase()).unwrap_or_default();
+    match ext.as_str() {
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "heic" => "image/heic",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Allow- and denylist of file type patterns.
+///
+/// A pattern is a MIME type (`image/png`), a MIME wildcard (`image/*`, `*/*`),
+/// or a filename extension with a leading dot (`.exe`); matching ignores case
+/// and MIME parameters. The denylist wins; an empty allowlist allows anything
+/// not denied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileTypeRules {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl FileTypeRules {
+    pub fn allows(&self, name: &str, mime: &str) -> bool {
+        let hit = |p: &String| pattern_matches(p, name, mime);
+        if self.deny.iter().any(hit) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(hit)
+    }
+}
+
+fn pattern_matches(pattern: &str, name: &str, mime: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    if let Some(ext) = pattern.strip_prefix('.') {
+        return name
+            .rsplit_once('.')
+            .is_some_and(|(_, e)| e.eq_ignore_ascii_case(ext));
+    }
+    let mime = mime.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    match pattern.strip_suffix("/*") {
+        Some("*") => true,
+        Some(top) => mime.split('/').next() == Some(top),
+        None => pattern == mime,
+    }
+}
+
+/// Global rules plus per-peer overrides; a peer with its own rules is judged
+/// by those alone.
+#[derive(Debug, Clone, Default)]
+pub struct FileTypeFilter {
+    global: FileTypeRules,
+    per_peer: HashMap<String, FileTypeRules>,
+}
+
+impl FileTypeFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_global(&mut self, rules: FileTypeRules) {
+        self.global = rules;
+    }
+
+    /// Set (or with `None`, clear) the rules for one peer.
+    pub fn set_peer(&mut self, peer_id: &str, rules: Option<FileTypeRules>) {
+        match rules {
+            Some(r) => {
+                self.per_peer.insert(peer_id.to_string(), r);
+            }
+            None => {
+                self.per_peer.remove(peer_id);
+            }
+        }
+    }
+
+    /// Whether to accept a file offered by `peer_id`.
+    pub fn allows(&self, peer_id: &str, name: &str, mime: &str) -> bool {
+        self.per_peer.get(peer_id).unwrap_or(&self.global).allows(name, mime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(allow: &[&str], deny: &[&str]) -> FileTypeRules {
+        FileTypeRules {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+            deny: deny.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn patterns_match_mime_wildcards_and_extensions() {
+        let r = rules(&[], &["application/x-executable", ".EXE"]);
+        assert!(!r.allows("tool", "application/x-executable"));
+        assert!(!r.allows("setup.exe", "application/octet-stream"));
+        assert!(r.allows("photo.png", "image/png"));
+
+        let r = rules(&["image/*", "application/pdf"], &["image/svg+xml"]);
+        assert!(r.allows("a.png", "IMAGE/PNG"));
+        assert!(r.allows("doc.pdf", "application/pdf; charset=binary"));
+        assert!(!r.allows("logo.svg", "image/svg+xml"));
+        assert!(!r.allows("notes.txt", "text/plain"));
+
+        assert!(FileTypeRules::default().allows("anything", "application/x-executable"));
+    }
+
+    #[test]
+    fn mime_comes_from_the_extension() {
+        assert_eq!(mime_for_name("Photo.JPG"), "image/jpeg");
+        assert_eq!(mime_for_name("notes.txt"), "text/plain");
+        assert_eq!(mime_for_name("archive.tar.zip"), "application/zip");
+        assert_eq!(mime_for_name("Makefile"), "application/octet-stream");
+        assert_eq!(mime_for_name("blob.xyz"), "application/octet-stream");
+    }
+
+    #[test]
+    fn peer_rules_replace_the_global_ones() {
+        let mut f = FileTypeFilter::new();
+        f.set_global(rules(&[], &["application/x-executable"]));
+        f.set_peer("build-box", Some(rules(&[], &[])));
+
+        assert!(!f.allows("phone", "a.out", "application/x-executable"));
+        assert!(f.allows("build-box", "a.out", "application/x-executable"));
+
+        f.set_peer("build-box", None);
+        assert!(!f.allows("build-box", "a.out", "application/x-executable"));
+    }
+}
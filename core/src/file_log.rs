@@ -0,0 +1,379 @@
+//! Saving files received from peers, and a bounded record of them so an app
+//! can list "files I received" after a restart.
+
+use crate::protocol::FileAssembler;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// `FileReject.reason` sent for an offer whose hash matches a file already
+/// received; the sender can treat the transfer as done.
+pub const REJECT_ALREADY_RECEIVED: &str = "already-received";
+
+/// `FileReject.reason` sent mid-transfer when a chunk can't belong to the
+/// offered file (it overlaps, overruns, or completes it with the wrong hash);
+/// the sender should stop sending it.
+pub const REJECT_CORRUPTED: &str = "corrupted";
+
+/// Largest incoming file a [`FileReceiveLog`] takes unless set otherwise;
+/// a file is assembled in memory before it is saved.
+pub const DEFAULT_MAX_RECEIVED_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Default directory received files are saved in: `temp_dir()/openclipboard`,
+/// which exists on every platform, `HOME` or not.
+pub fn default_download_dir() -> PathBuf {
+    std::env::temp_dir().join("openclipboard")
+}
+
+/// Default directory [`PartialFiles`] keeps cut-off transfers in:
+/// `~/.openclipboard/partial`.
+pub fn default_partial_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+    PathBuf::from(home).join(".openclipboard").join("partial")
+}
+
+/// How long a saved partial waits for its sender to resume it before
+/// [`PartialFiles::expire`] removes it.
+pub const PARTIAL_FILE_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(7 * 24 * 60 * 60);
+
+/// One completed incoming transfer.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReceivedFile {
+    pub peer_id: String,
+    /// Name as offered by the sender.
+    pub name: String,
+    pub size: u64,
+    /// SHA-256 hex of the content, as checked against the sender's `FileDone`.
+    pub hash: String,
+    /// Where the content was saved.
+    pub path: String,
+    pub received_at_ms: u64,
+}
+
+/// Writes received files into a download directory and keeps the newest
+/// `cap` [`ReceivedFile`] records, optionally persisted as a JSON file.
+pub struct FileReceiveLog {
+    cap: usize,
+    download_dir: Mutex<PathBuf>,
+    persist_path: Mutex<Option<PathBuf>>,
+    max_file_size: Mutex<Option<u64>>,
+    /// Where transfers cut off by a dropped connection are kept for resuming.
+    partial_dir: Mutex<Option<PathBuf>>,
+    /// Oldest first.
+    entries: Mutex<VecDeque<ReceivedFile>>,
+}
+
+impl FileReceiveLog {
+    /// In-memory log saving into [`default_download_dir`].
+    pub fn new(cap: usize) -> Self {
+        Self {
+            cap: cap.max(1),
+            download_dir: Mutex::new(default_download_dir()),
+            persist_path: Mutex::new(None),
+            max_file_size: Mutex::new(Some(DEFAULT_MAX_RECEIVED_FILE_SIZE)),
+            partial_dir: Mutex::new(Some(default_partial_dir())),
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Refuse offers of files larger than `max` bytes (`None`: no limit).
+    pub fn set_max_file_size(&self, max: Option<u64>) {
+        *self.max_file_size.lock().unwrap() = max;
+    }
+
+    pub fn max_file_size(&self) -> Option<u64> {
+        *self.max_file_size.lock().unwrap()
+    }
+
+    /// Where later files are saved.
+    pub fn set_download_dir(&self, dir: PathBuf) {
+        *self.download_dir.lock().unwrap() = dir;
+    }
+
+    /// Keep cut-off transfers in `dir` so their senders can resume them,
+    /// dropping any there older than [`PARTIAL_FILE_MAX_AGE`]. `None` forgets
+    /// them when the connection drops.
+    pub fn set_partial_dir(&self, dir: Option<PathBuf>) {
+        if let Some(dir) = &dir {
+            PartialFiles::new(dir.clone()).expire(PARTIAL_FILE_MAX_AGE);
+        }
+        *self.partial_dir.lock().unwrap() = dir;
+    }
+
+    /// The store for cut-off transfers, unless resuming is off.
+    pub fn partial_files(&self) -> Option<PartialFiles> {
+        self.partial_dir.lock().unwrap().clone().map(PartialFiles::new)
+    }
+
+    /// Persist the log at `path`, replacing the in-memory records with the ones
+    /// already there (none if the file doesn't exist yet). `None` stops persisting.
+    pub fn set_persist_path(&self, path: Option<PathBuf>) -> Result<()> {
+        if let Some(path) = &path {
+            let mut loaded: VecDeque<ReceivedFile> = if path.exists() {
+                let data = std::fs::read_to_string(path)?;
+                serde_json::from_str(&data).with_context(|| format!("parse {}", path.display()))?
+            } else {
+                VecDeque::new()
+            };
+            while loaded.len() > self.cap {
+                loaded.pop_front();
+            }
+            *self.entries.lock().unwrap() = loaded;
+        }
+        *self.persist_path.lock().unwrap() = path;
+        Ok(())
+    }
+
+    /// Save `data` into the download directory under a sanitized `name`
+    /// (suffixed if taken) and record it.
+    pub fn save(&self, peer_id: &str, name: &str, data: &[u8], hash: &str) -> Result<ReceivedFile> {
+        let dir = self.download_dir.lock().unwrap().clone();
+        std::fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+        let (path, mut out) = create_free_file(&dir, &sanitize_file_name(name))?;
+        if let Err(e) = out.write_all(data) {
+            let _ = std::fs::remove_file(&path);
+            return Err(e).with_context(|| format!("write {}", path.display()));
+        }
+
+        let file = ReceivedFile {
+            peer_id: peer_id.to_string(),
+            name: name.to_string(),
+            size: data.len() as u64,
+            hash: hash.to_string(),
+            path: path.to_string_lossy().to_string(),
+            received_at_ms: chrono::Utc::now().timestamp_millis() as u64,
+        };
+        self.record(file.clone())?;
+        Ok(file)
+    }
+
+    /// Add a record, evicting the oldest beyond the cap, and persist if enabled.
+    pub fn record(&self, file: ReceivedFile) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.push_back(file);
+        while entries.len() > self.cap {
+            entries.pop_front();
+        }
+        if let Some(path) = self.persist_path.lock().unwrap().as_ref() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let tmp = path.with_extension("json.tmp");
+            std::fs::write(&tmp, serde_json::to_string_pretty(&*entries)?)?;
+            std::fs::rename(&tmp, path)?;
+        }
+        Ok(())
+    }
+
+    /// The newest file `peer_id` sent with SHA-256 hex `hash` that is still on
+    /// disk. Only that peer's files count, so a peer can't probe for, or be
+    /// pointed at, what others sent.
+    pub fn find_by_hash(&self, peer_id: &str, hash: &str) -> Option<ReceivedFile> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().rev().find(|f| f.peer_id == peer_id && f.hash == hash && std::path::Path::new(&f.path).exists()).cloned()
+    }
+
+    /// Records, newest first.
+    pub fn list(&self) -> Vec<ReceivedFile> {
+        self.entries.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Where a receiver keeps files cut off by a dropped connection, one
+/// `<peer_id>-<file_id>.part` each, until the sender resumes them. Senders
+/// use the content's SHA-256 hex as the file id, so a resume only ever
+/// continues the same bytes from the same peer.
+pub struct PartialFiles {
+    dir: PathBuf,
+}
+
+impl PartialFiles {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, peer_id: &str, file_id: &str) -> PathBuf {
+        self.dir.join(format!("{}-{}.part", sanitize_file_name(peer_id), sanitize_file_name(file_id)))
+    }
+
+    /// How much of `file_id` from `peer_id` is saved, for answering a
+    /// `FileResumeQuery`.
+    pub fn saved_len(&self, peer_id: &str, file_id: &str) -> u64 {
+        std::fs::symlink_metadata(self.path(peer_id, file_id)).map_or(0, |m| if m.file_type().is_file() { m.len() } else { 0 })
+    }
+
+    /// `file` continued from what was saved for `file_id`, if anything. A
+    /// saved part that doesn't fit the new offer is dropped.
+    pub fn resume(&self, peer_id: &str, file_id: &str, file: FileAssembler) -> Result<FileAssembler> {
+        let name = file.name.clone();
+        let expected = file.expected();
+        file.with_partial(&self.path(peer_id, file_id)).map_err(|e| {
+            self.discard(peer_id, file_id);
+            e.context(format!("discarded the partial {name} ({expected} bytes offered)"))
+        })
+    }
+
+    /// Save the gap-free start of `file`, if it has one.
+    pub fn save(&self, peer_id: &str, file_id: &str, file: &FileAssembler) -> Result<()> {
+        if file.next_offset() == 0 {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.dir).with_context(|| format!("create {}", self.dir.display()))?;
+        let path = self.path(peer_id, file_id);
+        file.save_partial(&path).with_context(|| format!("save {}", path.display()))
+    }
+
+    /// Save the gap-free start of every file in `files`, emptying it.
+    pub fn save_all(&self, peer_id: &str, files: &mut HashMap<String, FileAssembler>) -> Result<()> {
+        for (file_id, file) in files.drain() {
+            self.save(peer_id, &file_id, &file)?;
+        }
+        Ok(())
+    }
+
+    /// Forget `file_id`'s saved part, once it completed or went bad.
+    pub fn discard(&self, peer_id: &str, file_id: &str) {
+        let _ = std::fs::remove_file(self.path(peer_id, file_id));
+    }
+
+    /// Remove saved parts nobody resumed within `max_age`.
+    pub fn expire(&self, max_age: std::time::Duration) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else { return };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let stale = std::fs::symlink_metadata(&path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > max_age);
+            if stale && path.extension().is_some_and(|ext| ext == "part") {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// Keep a peer-chosen name from escaping the download directory.
+fn sanitize_file_name(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '.' || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if safe.trim_matches('.').is_empty() { "file".into() } else { safe }
+}
+
+/// Create `dir/name`, or `dir/stem (n).ext` for the first `n` not already
+/// taken. Creation is exclusive, so an existing file or symlink is never
+/// written through, even one that appears after the name was picked.
+fn create_free_file(dir: &Path, name: &str) -> Result<(PathBuf, std::fs::File)> {
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((s, e)) if !s.is_empty() => (s, format!(".{e}")),
+        _ => (name, String::new()),
+    };
+    let candidates = std::iter::once(dir.join(name)).chain((1..).map(|n| dir.join(format!("{stem} ({n}){ext}"))));
+    for path in candidates {
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e).with_context(|| format!("create {}", path.display())),
+        }
+    }
+    unreachable!("unbounded range")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("oc-file-log-{label}-{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn save_keeps_names_inside_the_dir_and_never_overwrites() {
+        let dir = temp_dir("save");
+        let log = FileReceiveLog::new(10);
+        log.set_download_dir(dir.clone());
+
+        let a = log.save("phone", "../notes.txt", b"one", "h1").unwrap();
+        let b = log.save("phone", "../notes.txt", b"two", "h2").unwrap();
+        assert_eq!(PathBuf::from(&a.path), dir.join(".._notes.txt"));
+        assert_eq!(PathBuf::from(&b.path), dir.join(".._notes (1).txt"));
+        assert_eq!(std::fs::read(&a.path).unwrap(), b"one");
+        assert_eq!(std::fs::read(&b.path).unwrap(), b"two");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn save_never_writes_through_a_symlink() {
+        let dir = temp_dir("symlink");
+        let target = dir.join("target.txt");
+        std::fs::write(&target, b"keep").unwrap();
+        let downloads = dir.join("downloads");
+        std::fs::create_dir_all(&downloads).unwrap();
+        std::os::unix::fs::symlink(&target, downloads.join("notes.txt")).unwrap();
+        let log = FileReceiveLog::new(10);
+        log.set_download_dir(downloads.clone());
+
+        let saved = log.save("phone", "notes.txt", b"evil", "h").unwrap();
+        assert_eq!(PathBuf::from(&saved.path), downloads.join("notes (1).txt"));
+        assert_eq!(std::fs::read(&target).unwrap(), b"keep");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn partials_are_kept_per_peer_and_expire() {
+        let dir = temp_dir("partial");
+        let partials = PartialFiles::new(dir.clone());
+        let mut file = FileAssembler::new("f.bin".into(), 8);
+        file.insert(0, b"abcd").unwrap();
+        partials.save("phone", "h", &file).unwrap();
+        assert_eq!(partials.saved_len("phone", "h"), 4);
+        assert_eq!(partials.saved_len("laptop", "h"), 0);
+
+        let resumed = partials.resume("phone", "h", FileAssembler::new("f.bin".into(), 8)).unwrap();
+        assert_eq!(resumed.next_offset(), 4);
+
+        partials.expire(std::time::Duration::from_secs(3600));
+        assert_eq!(partials.saved_len("phone", "h"), 4);
+        partials.expire(std::time::Duration::ZERO);
+        assert_eq!(partials.saved_len("phone", "h"), 0);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn log_is_bounded_and_survives_a_reload() {
+        let dir = temp_dir("persist");
+        let path = dir.join("received.json");
+        let log = FileReceiveLog::new(2);
+        log.set_download_dir(dir.clone());
+        log.set_persist_path(Some(path.clone())).unwrap();
+        for name in ["a.bin", "b.bin", "c.bin"] {
+            log.save("laptop", name, name.as_bytes(), "h").unwrap();
+        }
+        let names = |l: &FileReceiveLog| l.list().into_iter().map(|f| f.name).collect::<Vec<_>>();
+        assert_eq!(names(&log), ["c.bin", "b.bin"]);
+
+        let reopened = FileReceiveLog::new(2);
+        reopened.set_persist_path(Some(path)).unwrap();
+        assert_eq!(names(&reopened), ["c.bin", "b.bin"]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
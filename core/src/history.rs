@@ -1,34 +1,475 @@
 //! Bounded, thread-safe clipboard history store.
 
+use crate::clipboard::ClipboardContent;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
-use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// What a history entry holds, with what it takes to recall it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryItemKind {
+    /// The text is the entry's `content`.
+    Text,
+    Image {
+        mime: String,
+        width: u32,
+        height: u32,
+        #[serde(with = "image_bytes")]
+        bytes: Vec<u8>,
+    },
+    /// A received file, by where it was saved.
+    File { name: String, size: u64, path: String },
+}
+
+/// Image bytes as base64 in the history file rather than a JSON array of
+/// numbers; files written with the array still load.
+mod image_bytes {
+    use crate::b64::Base64Alphabet;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&Base64Alphabet::Standard.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Base64(String),
+            Array(Vec<u8>),
+        }
+        match Stored::deserialize(deserializer)? {
+            Stored::Base64(s) => Base64Alphabet::Standard.decode(s).map_err(serde::de::Error::custom),
+            Stored::Array(bytes) => Ok(bytes),
+        }
+    }
+}
 
 /// A single clipboard history entry.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClipboardEntry {
     pub id: String,
+    /// The text of a `Text` entry; a short label (`image/png 640×480`, a file
+    /// name) for the other kinds.
     pub content: String,
+    pub kind: HistoryItemKind,
     pub source_peer: String,
+    /// Device name the sender attached to the clip, if any.
+    pub origin_name: Option<String>,
     pub timestamp: u64,
 }
 
+/// Maximum length (in characters) of a clipboard preview.
+pub const PREVIEW_MAX_CHARS: usize = 80;
+
+/// Short single-line preview of clipboard text: whitespace runs collapse to one
+/// space and the result is cut at `max_chars` characters with a trailing `…`.
+pub fn preview_text(text: &str, max_chars: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= max_chars {
+        return collapsed;
+    }
+    let mut out: String = collapsed.chars().take(max_chars).collect();
+    out.push('…');
+    out
+}
+
+/// Short single-line preview of any clipboard content, cut like
+/// [`preview_text`]: text itself, an image as `image 1024×768 PNG`.
+pub fn preview(content: &ClipboardContent, max_chars: usize) -> String {
+    match content {
+        ClipboardContent::Text(text) => preview_text(text, max_chars),
+        ClipboardContent::Image { mime, width, height, .. } => preview_image(mime, *width, *height, max_chars),
+        ClipboardContent::Empty => String::new(),
+    }
+}
+
+fn preview_image(mime: &str, width: u32, height: u32, max_chars: usize) -> String {
+    let format = mime.strip_prefix("image/").unwrap_or(mime).to_uppercase();
+    preview_text(&format!("image {width}×{height} {format}"), max_chars)
+}
+
+/// Preview of a file as `report.pdf, 2.3 MB` (decimal units).
+pub fn preview_file(name: &str, size: u64, max_chars: usize) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    let size = if size < 1000 {
+        format!("{size} B")
+    } else {
+        let mut value = size as f64 / 1000.0;
+        let mut unit = 0;
+        while value >= 999.95 && unit + 1 < UNITS.len() {
+            value /= 1000.0;
+            unit += 1;
+        }
+        format!("{value:.1} {}", UNITS[unit])
+    };
+    preview_text(&format!("{name}, {size}"), max_chars)
+}
+
+impl ClipboardEntry {
+    /// [`preview`] of this entry, whatever its kind.
+    pub fn preview(&self, max_chars: usize) -> String {
+        match &self.kind {
+            HistoryItemKind::Text => preview_text(&self.content, max_chars),
+            HistoryItemKind::Image { mime, width, height, .. } => preview_image(mime, *width, *height, max_chars),
+            HistoryItemKind::File { name, size, .. } => preview_file(name, *size, max_chars),
+        }
+    }
+
+    /// Whether this entry can stand in for `older` when coalescing: the same
+    /// content, or text that extends it (someone still typing).
+    fn supersedes(&self, older: &ClipboardEntry) -> bool {
+        match (&self.kind, &older.kind) {
+            (HistoryItemKind::Text, HistoryItemKind::Text) => self.content.starts_with(&older.content),
+            (kind, older_kind) => kind == older_kind && self.content == older.content,
+        }
+    }
+
+    /// Bytes this entry counts against the history's byte budget.
+    fn stored_bytes(&self) -> usize {
+        match &self.kind {
+            HistoryItemKind::Image { bytes, .. } => self.content.len() + bytes.len(),
+            _ => self.content.len(),
+        }
+    }
+}
+
+/// Callback invoked with each entry dropped by count- or byte-based eviction.
+pub type EvictCallback = Arc<dyn Fn(ClipboardEntry) + Send + Sync>;
+
+/// Leads every encrypted history file, ahead of the nonce.
+const HISTORY_FILE_MAGIC: &[u8; 4] = b"OCH1";
+
+/// Key for [`ClipboardHistory::with_encrypted_persistence`].
+#[derive(Clone)]
+pub struct HistoryKey([u8; 32]);
+
+impl HistoryKey {
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Derived from the device identity's secret, so the file is only
+    /// readable with that identity.
+    pub fn from_identity(identity: &crate::identity::Ed25519Identity) -> Self {
+        Self(blake3::derive_key("openclipboard history v0", &identity.signing_key_seed_bytes()))
+    }
+
+    /// Seal `plaintext` as magic, a random 96-bit nonce, then the
+    /// ChaCha20-Poly1305 ciphertext.
+    fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+        let cipher = chacha20poly1305::ChaCha20Poly1305::new((&self.0).into());
+        let nonce = chacha20poly1305::ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = cipher.encrypt(&nonce, plaintext).map_err(|_| anyhow::anyhow!("encrypting history failed"))?;
+        Ok([HISTORY_FILE_MAGIC.as_slice(), nonce.as_slice(), &sealed].concat())
+    }
+
+    fn open(&self, data: &[u8]) -> Result<Vec<u8>> {
+        use chacha20poly1305::aead::{Aead, KeyInit};
+        let body = data.strip_prefix(HISTORY_FILE_MAGIC.as_slice()).context("not an encrypted history file")?;
+        anyhow::ensure!(body.len() >= 12, "encrypted history file is truncated");
+        let (nonce, sealed) = body.split_at(12);
+        let cipher = chacha20poly1305::ChaCha20Poly1305::new((&self.0).into());
+        cipher
+            .decrypt(nonce.into(), sealed)
+            .map_err(|_| anyhow::anyhow!("history file can't be decrypted with this key (or is corrupted)"))
+    }
+}
+
+/// The encrypted history file, with writes held back by the flush interval.
+struct Persistence {
+    path: PathBuf,
+    key: HistoryKey,
+    /// Numbers snapshots in the order the history changed, since they are
+    /// serialized after its lock is released.
+    next_seq: AtomicU64,
+    state: Mutex<FlushState>,
+    /// Wakes the writer thread when a change is to be written, and `Drop`
+    /// when the writer finishes one.
+    wake: Condvar,
+}
+
+#[derive(Default)]
+struct FlushState {
+    last_write: Option<Instant>,
+    /// The newest snapshot written, so an older one finishing late is dropped.
+    seq: u64,
+    /// When the writer thread is to write the changes held back, if any.
+    due: Option<Instant>,
+    /// The writer thread has been started.
+    writer: bool,
+    /// The writer thread is snapshotting and writing the history.
+    writing: bool,
+    /// The history is gone; the writer thread exits.
+    closed: bool,
+    /// Snapshots serialized so far.
+    snapshots: usize,
+    /// Files written so far.
+    writes: usize,
+}
+
+impl Persistence {
+    /// The history changed: have the writer thread snapshot and write it,
+    /// straight away or, within `interval` of the last write, once the
+    /// interval is up. The recording thread never serializes or writes.
+    fn changed(self: &Arc<Self>, history: &Entries, interval: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let due = state.last_write.map(|t| t + interval).filter(|&due| due > now).unwrap_or(now);
+        state.due = Some(state.due.map_or(due, |held| held.min(due)));
+        if !state.writer {
+            state.writer = true;
+            let this = Arc::clone(self);
+            let history = Arc::downgrade(history);
+            std::thread::spawn(move || this.run_writer(history));
+        }
+        self.wake.notify_all();
+    }
+
+    /// The writer thread: write the history whenever a held-back write is due.
+    fn run_writer(&self, history: Weak<Mutex<VecDeque<ClipboardEntry>>>) {
+        let mut state = self.state.lock().unwrap();
+        while !state.closed {
+            let now = Instant::now();
+            match state.due {
+                None => state = self.wake.wait(state).unwrap(),
+                Some(due) if due > now => state = self.wake.wait_timeout(state, due - now).unwrap().0,
+                Some(_) => {
+                    let Some(entries) = history.upgrade() else { break };
+                    state.due = None;
+                    state.writing = true;
+                    drop(state);
+                    let _ = self.write_snapshot(entries.lock().unwrap());
+                    state = self.state.lock().unwrap();
+                    state.writing = false;
+                    self.wake.notify_all();
+                }
+            }
+        }
+    }
+
+    /// Stop the writer thread, writing any changes it still held back. The
+    /// entries lock is only taken once the writer is done, since a write in
+    /// progress needs it.
+    fn close(&self, history: &Entries) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        self.wake.notify_all();
+        while state.writing {
+            state = self.wake.wait(state).unwrap();
+        }
+        if state.due.take().is_none() {
+            return Ok(());
+        }
+        drop(state);
+        self.write_snapshot(history.lock().unwrap())
+    }
+
+    /// Write the history now, in place of any write held back, once a write
+    /// the writer thread already started is done; so nothing the flush
+    /// covered is written again after it returns.
+    fn flush(&self, history: &Entries) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        while state.writing {
+            state = self.wake.wait(state).unwrap();
+        }
+        state.due = None;
+        drop(state);
+        self.write_snapshot(history.lock().unwrap())
+    }
+
+    /// Snapshot `entries`, release their lock, then serialize and write the
+    /// snapshot, so recording never waits on serialization.
+    fn write_snapshot(&self, entries: MutexGuard<'_, VecDeque<ClipboardEntry>>) -> Result<()> {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let snapshot: Vec<ClipboardEntry> = entries.iter().cloned().collect();
+        drop(entries);
+        let json = serde_json::to_vec(&snapshot)?;
+        let mut state = self.state.lock().unwrap();
+        state.snapshots += 1;
+        if seq < state.seq {
+            return Ok(());
+        }
+        state.seq = seq;
+        self.write(&mut state, &json)
+    }
+
+    fn write(&self, state: &mut FlushState, json: &[u8]) -> Result<()> {
+        state.last_write = Some(Instant::now());
+        state.writes += 1;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let sealed = self.key.seal(json)?;
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, sealed).with_context(|| format!("write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+type Entries = Arc<Mutex<VecDeque<ClipboardEntry>>>;
+
 /// Thread-safe bounded clipboard history.
+///
+/// Bounded by entry count and, optionally, by the total bytes of stored
+/// content; whichever limit binds first evicts the oldest entries.
 pub struct ClipboardHistory {
     max_entries: usize,
-    entries: Mutex<VecDeque<ClipboardEntry>>,
+    /// Byte budget for stored content; 0 means unlimited.
+    max_bytes: AtomicUsize,
+    /// Shared with the persistence writer thread, if there is one.
+    entries: Entries,
+    /// Sum of stored content (text, labels and image bytes) over `entries`;
+    /// only changed under the `entries` lock.
+    total_bytes: AtomicUsize,
+    on_evict: Mutex<Option<EvictCallback>>,
+    /// Where every change is written, encrypted; see `with_encrypted_persistence`.
+    persist: Option<Arc<Persistence>>,
+    /// See `set_flush_interval`; 0 writes every change straight away.
+    flush_interval_ms: AtomicU64,
+    /// See `set_coalesce_window`; 0 keeps every record.
+    coalesce_window_ms: AtomicU64,
 }
 
 impl ClipboardHistory {
     pub fn new(max_entries: usize) -> Self {
         Self {
             max_entries: max_entries.max(1),
-            entries: Mutex::new(VecDeque::new()),
+            max_bytes: AtomicUsize::new(0),
+            entries: Arc::new(Mutex::new(VecDeque::new())),
+            total_bytes: AtomicUsize::new(0),
+            on_evict: Mutex::new(None),
+            persist: None,
+            flush_interval_ms: AtomicU64::new(0),
+            coalesce_window_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Keep the history in an encrypted file at `path`, so clipboard content
+    /// (passwords included) never sits on disk in plaintext. Entries already
+    /// in the file are loaded, newest kept up to the entry limit; a file that
+    /// doesn't decrypt with `key` is an error rather than being overwritten.
+    /// Every later change rewrites the file (see `set_flush_interval`).
+    pub fn with_encrypted_persistence(mut self, path: impl Into<PathBuf>, key: HistoryKey) -> Result<Self> {
+        let path = path.into();
+        if path.exists() {
+            let data = std::fs::read(&path).with_context(|| format!("read {}", path.display()))?;
+            let plaintext = key.open(&data).with_context(|| format!("open {}", path.display()))?;
+            let mut loaded: VecDeque<ClipboardEntry> =
+                serde_json::from_slice(&plaintext).with_context(|| format!("parse {}", path.display()))?;
+            while loaded.len() > self.max_entries {
+                loaded.pop_front();
+            }
+            let bytes = loaded.iter().map(ClipboardEntry::stored_bytes).sum();
+            *self.entries.lock().unwrap() = loaded;
+            *self.total_bytes.get_mut() = bytes;
+        }
+        self.persist = Some(Arc::new(Persistence {
+            path,
+            key,
+            next_seq: AtomicU64::new(0),
+            state: Mutex::default(),
+            wake: Condvar::new(),
+        }));
+        Ok(self)
+    }
+
+    /// Write the history to its encrypted file now, reporting any error.
+    /// Changes are written as they happen anyway, but failures there are
+    /// dropped, since the in-memory history stays authoritative.
+    pub fn flush(&self) -> Result<()> {
+        let Some(persist) = &self.persist else { return Ok(()) };
+        persist.flush(&self.entries)
+    }
+
+    /// Hand a change to the writer thread, to be written now or, within
+    /// `interval` of the last write, later.
+    fn persist_change(&self, interval: Duration) {
+        if let Some(persist) = &self.persist {
+            persist.changed(&self.entries, interval);
         }
     }
 
+    fn flush_interval(&self) -> Duration {
+        Duration::from_millis(self.flush_interval_ms.load(Ordering::Relaxed))
+    }
+
+    /// Write the file at most once per `interval`: changes in between only
+    /// update memory, and the latest state is written when the interval is
+    /// up (or on `flush`, or when the history is dropped). Reads are never
+    /// delayed. `None` (the default) writes every change straight away, on
+    /// the writer thread rather than the one recording.
+    pub fn set_flush_interval(&self, interval: Option<Duration>) {
+        self.flush_interval_ms.store(interval.map_or(0, |d| d.as_millis() as u64), Ordering::Relaxed);
+    }
+
+    /// Replace the newest entry, instead of adding one, when a record from the
+    /// same source follows it within `window` with the same content or text
+    /// that extends it, so an app rewriting the clipboard many times a second
+    /// leaves one entry rather than a burst. A different clip is always kept.
+    /// `None` (the default) keeps every record.
+    pub fn set_coalesce_window(&self, window: Option<Duration>) {
+        self.coalesce_window_ms.store(window.map_or(0, |d| d.as_millis() as u64), Ordering::Relaxed);
+    }
+
+    /// Cap the total bytes of stored content (`None` for no cap), evicting the
+    /// oldest entries straight away if the history is already over it. The
+    /// newest entry is always kept, even if it alone exceeds the budget.
+    pub fn set_max_bytes(&self, max_bytes: Option<usize>) {
+        self.max_bytes.store(max_bytes.unwrap_or(0), Ordering::Relaxed);
+        let evicted = {
+            let mut entries = self.entries.lock().unwrap();
+            self.evict_over_limits(&mut entries)
+        };
+        if !evicted.is_empty() {
+            self.persist_change(self.flush_interval());
+        }
+        self.notify_evicted(evicted);
+    }
+
+    /// Total bytes of content currently stored.
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Register a callback for entries evicted when the history is full, e.g. to
+    /// archive them elsewhere. It runs on the recording thread after the history
+    /// lock is released, so it should return quickly.
+    pub fn set_on_evict(&self, callback: Option<EvictCallback>) {
+        *self.on_evict.lock().unwrap() = callback;
+    }
+
     /// Record a clipboard event. Returns the generated entry id.
     pub fn record(&self, content: String, source_peer: String) -> String {
+        self.record_with_origin(content, source_peer, None)
+    }
+
+    /// Record a clipboard event along with the sender's device name.
+    pub fn record_with_origin(&self, content: String, source_peer: String, origin_name: Option<String>) -> String {
+        self.record_kind(HistoryItemKind::Text, content, source_peer, origin_name)
+    }
+
+    /// Record a clipboard image; its bytes count against the byte budget.
+    pub fn record_image(&self, mime: String, width: u32, height: u32, bytes: Vec<u8>, source_peer: String) -> String {
+        let label = format!("{mime} {width}×{height}");
+        self.record_kind(HistoryItemKind::Image { mime, width, height, bytes }, label, source_peer, None)
+    }
+
+    /// Record a file saved at `path`, labelled with its name.
+    pub fn record_file(&self, name: String, size: u64, path: String, source_peer: String) -> String {
+        let label = name.clone();
+        self.record_kind(HistoryItemKind::File { name, size, path }, label, source_peer, None)
+    }
+
+    /// Record an entry of any kind; `content` is its text or label (see
+    /// [`ClipboardEntry::content`]). Returns the generated entry id.
+    pub fn record_kind(&self, kind: HistoryItemKind, content: String, source_peer: String, origin_name: Option<String>) -> String {
         let id = format!("{:032x}", rand::random::<u128>());
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -38,25 +479,76 @@ impl ClipboardHistory {
         let entry = ClipboardEntry {
             id: id.clone(),
             content,
+            kind,
             source_peer,
+            origin_name,
             timestamp,
         };
 
-        let mut entries = self.entries.lock().unwrap();
-        entries.push_back(entry);
-        while entries.len() > self.max_entries {
-            entries.pop_front();
-        }
+        let coalesce_ms = self.coalesce_window_ms.load(Ordering::Relaxed);
+        let evicted = {
+            let mut entries = self.entries.lock().unwrap();
+            let coalesce = entries.back().is_some_and(|last| {
+                last.source_peer == entry.source_peer
+                    && timestamp.saturating_sub(last.timestamp) < coalesce_ms
+                    && entry.supersedes(last)
+            });
+            if coalesce && let Some(replaced) = entries.pop_back() {
+                self.total_bytes.fetch_sub(replaced.stored_bytes(), Ordering::Relaxed);
+            }
+            self.total_bytes.fetch_add(entry.stored_bytes(), Ordering::Relaxed);
+            entries.push_back(entry);
+            self.evict_over_limits(&mut entries)
+        };
+        self.persist_change(self.flush_interval());
+        self.notify_evicted(evicted);
 
         id
     }
 
+    /// Pop the oldest entries while either limit is exceeded. Call with the
+    /// `entries` lock held.
+    fn evict_over_limits(&self, entries: &mut VecDeque<ClipboardEntry>) -> Vec<ClipboardEntry> {
+        let max_bytes = match self.max_bytes.load(Ordering::Relaxed) {
+            0 => usize::MAX,
+            n => n,
+        };
+        let mut evicted = Vec::new();
+        while entries.len() > self.max_entries
+            || (entries.len() > 1 && self.total_bytes.load(Ordering::Relaxed) > max_bytes)
+        {
+            let Some(e) = entries.pop_front() else { break };
+            self.total_bytes.fetch_sub(e.stored_bytes(), Ordering::Relaxed);
+            evicted.push(e);
+        }
+        evicted
+    }
+
+    /// Hand evicted entries to the `on_evict` callback, outside the history lock.
+    fn notify_evicted(&self, evicted: Vec<ClipboardEntry>) {
+        if evicted.is_empty() {
+            return;
+        }
+        let callback = self.on_evict.lock().unwrap().clone();
+        if let Some(cb) = callback {
+            for e in evicted {
+                cb(e);
+            }
+        }
+    }
+
     /// Get most recent entries (newest first), up to `limit`.
     pub fn get_recent(&self, limit: usize) -> Vec<ClipboardEntry> {
         let entries = self.entries.lock().unwrap();
         entries.iter().rev().take(limit).cloned().collect()
     }
 
+    /// Entries recorded after `ts_ms`, oldest first.
+    pub fn since(&self, ts_ms: u64) -> Vec<ClipboardEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().filter(|e| e.timestamp > ts_ms).cloned().collect()
+    }
+
     /// Get most recent entries for a specific peer (newest first), up to `limit`.
     pub fn get_for_peer(&self, peer_name: &str, limit: usize) -> Vec<ClipboardEntry> {
         let entries = self.entries.lock().unwrap();
@@ -69,16 +561,52 @@ impl ClipboardHistory {
             .collect()
     }
 
+    /// Most recent entry from a specific peer.
+    pub fn last_for_peer(&self, peer_name: &str) -> Option<ClipboardEntry> {
+        self.get_for_peer(peer_name, 1).into_iter().next()
+    }
+
     /// Look up an entry by id.
     pub fn get_by_id(&self, id: &str) -> Option<ClipboardEntry> {
         let entries = self.entries.lock().unwrap();
         entries.iter().find(|e| e.id == id).cloned()
     }
 
+    /// Per-peer activity: `(source_peer, entry count, newest timestamp)`, most
+    /// recently active peer first.
+    pub fn peer_summary(&self) -> Vec<(String, usize, u64)> {
+        let entries = self.entries.lock().unwrap();
+        let mut summary: Vec<(String, usize, u64)> = Vec::new();
+        // Newest first, so a peer's first appearance fixes both its order and last_ts.
+        for e in entries.iter().rev() {
+            match summary.iter_mut().find(|(peer, _, _)| *peer == e.source_peer) {
+                Some((_, count, _)) => *count += 1,
+                None => summary.push((e.source_peer.clone(), 1, e.timestamp)),
+            }
+        }
+        summary
+    }
+
     /// Current number of entries stored.
     pub fn len(&self) -> usize {
         self.entries.lock().unwrap().len()
     }
+
+    /// `get_recent(limit)` together with `len()` and `total_bytes()`, all read
+    /// under one lock so they agree with each other.
+    pub fn recent_with_totals(&self, limit: usize) -> (Vec<ClipboardEntry>, usize, usize) {
+        let entries = self.entries.lock().unwrap();
+        let recent = entries.iter().rev().take(limit).cloned().collect();
+        (recent, entries.len(), self.total_bytes.load(Ordering::Relaxed))
+    }
+}
+
+impl Drop for ClipboardHistory {
+    fn drop(&mut self) {
+        if let Some(persist) = &self.persist {
+            let _ = persist.close(&self.entries);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -114,6 +642,264 @@ mod tests {
         assert_eq!(phone[0].content, "b");
     }
 
+    #[test]
+    fn entries_of_each_kind_come_back_typed_newest_first() {
+        let h = ClipboardHistory::new(100);
+        h.record("some text".into(), "local".into());
+        h.record_image("image/png".into(), 2, 1, vec![0; 8], "phone".into());
+        h.record_file("notes.pdf".into(), 1234, "/tmp/notes.pdf".into(), "laptop".into());
+
+        let recent = h.get_recent(10);
+        assert_eq!(recent.len(), 3);
+        assert_eq!(
+            recent[0].kind,
+            HistoryItemKind::File { name: "notes.pdf".into(), size: 1234, path: "/tmp/notes.pdf".into() }
+        );
+        assert_eq!(recent[0].content, "notes.pdf");
+        assert_eq!(
+            recent[1].kind,
+            HistoryItemKind::Image { mime: "image/png".into(), width: 2, height: 1, bytes: vec![0; 8] }
+        );
+        assert_eq!(recent[1].content, "image/png 2×1");
+        assert_eq!(recent[2].kind, HistoryItemKind::Text);
+        assert_eq!(recent[2].content, "some text");
+        assert!(recent[0].timestamp >= recent[1].timestamp && recent[1].timestamp >= recent[2].timestamp);
+
+        // Image bytes count against the byte budget along with the labels.
+        assert_eq!(h.total_bytes(), "some text".len() + "image/png 2×1".len() + 8 + "notes.pdf".len());
+        assert_eq!(h.get_by_id(&recent[1].id).unwrap().kind, recent[1].kind);
+    }
+
+    #[test]
+    fn encrypted_persistence_hides_content_on_disk_and_reopens() {
+        let dir = std::env::temp_dir().join(format!("oc-history-{}", rand::random::<u64>()));
+        let path = dir.join("history.bin");
+        let identity = crate::identity::Ed25519Identity::generate();
+
+        let h = ClipboardHistory::new(10).with_encrypted_persistence(&path, HistoryKey::from_identity(&identity)).unwrap();
+        h.record("correct horse battery staple".into(), "local".into());
+        h.record_image("image/png".into(), 1, 1, vec![7; 4], "phone".into());
+        h.flush().unwrap();
+
+        let on_disk = std::fs::read(&path).unwrap();
+        let needle = b"battery staple";
+        assert!(!on_disk.windows(needle.len()).any(|w| w == needle), "secret stored in plaintext");
+        assert!(!on_disk.windows(5).any(|w| w == b"local"));
+
+        let reopened = ClipboardHistory::new(10).with_encrypted_persistence(&path, HistoryKey::from_identity(&identity)).unwrap();
+        let (before, after) = (h.get_recent(10), reopened.get_recent(10));
+        assert_eq!(after.len(), 2);
+        for (a, b) in before.iter().zip(&after) {
+            assert_eq!((&a.id, &a.content, &a.kind, a.timestamp), (&b.id, &b.content, &b.kind, b.timestamp));
+        }
+        assert_eq!(reopened.total_bytes(), h.total_bytes());
+
+        // Another identity can't read it, and the file is left alone.
+        let other = HistoryKey::from_identity(&crate::identity::Ed25519Identity::generate());
+        assert!(ClipboardHistory::new(10).with_encrypted_persistence(&path, other).is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), on_disk);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn image_bytes_are_stored_as_base64_and_old_arrays_still_load() {
+        let kind = HistoryItemKind::Image { mime: "image/png".into(), width: 1, height: 1, bytes: vec![1, 2, 3] };
+        let json = serde_json::to_value(&kind).unwrap();
+        assert_eq!(json["Image"]["bytes"], "AQID");
+        assert_eq!(serde_json::from_value::<HistoryItemKind>(json).unwrap(), kind);
+
+        let old = serde_json::json!({ "Image": { "mime": "image/png", "width": 1, "height": 1, "bytes": [1, 2, 3] } });
+        assert_eq!(serde_json::from_value::<HistoryItemKind>(old).unwrap(), kind);
+    }
+
+    #[test]
+    fn capacity_eviction_survives_a_reload() {
+        let dir = std::env::temp_dir().join(format!("oc-history-{}", rand::random::<u64>()));
+        let path = dir.join("history.bin");
+        let open = |cap| ClipboardHistory::new(cap).with_encrypted_persistence(&path, HistoryKey::from_bytes([5; 32])).unwrap();
+        let contents = |h: &ClipboardHistory| h.get_recent(usize::MAX).into_iter().map(|e| e.content).collect::<Vec<_>>();
+
+        let h = open(5);
+        for i in 0..8 {
+            h.record(format!("clip {i}"), "local".into());
+        }
+        drop(h);
+        let reopened = open(5);
+        assert_eq!(contents(&reopened), ["clip 7", "clip 6", "clip 5", "clip 4", "clip 3"]);
+        drop(reopened);
+
+        // Reopened with a smaller limit: the oldest go, and stay gone once rewritten.
+        let smaller = open(2);
+        assert_eq!(contents(&smaller), ["clip 7", "clip 6"]);
+        smaller.record("clip 8".into(), "local".into());
+        drop(smaller);
+        assert_eq!(contents(&open(5)), ["clip 8", "clip 7"]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn burst_of_records_is_flushed_a_few_times_and_ends_up_on_disk() {
+        let dir = std::env::temp_dir().join(format!("oc-history-{}", rand::random::<u64>()));
+        let path = dir.join("history.bin");
+        let key = || HistoryKey::from_bytes([3; 32]);
+
+        let h = ClipboardHistory::new(500).with_encrypted_persistence(&path, key()).unwrap();
+        h.set_flush_interval(Some(Duration::from_millis(200)));
+        for i in 0..300 {
+            h.record(format!("clip {i}"), "local".into());
+        }
+        // Reads see every record straight away.
+        assert_eq!(h.len(), 300);
+        assert_eq!(h.get_recent(1)[0].content, "clip 299");
+
+        // The held-back state is written once the interval is up.
+        std::thread::sleep(Duration::from_millis(500));
+        let (writes, snapshots) = {
+            let state = h.persist.as_ref().unwrap().state.lock().unwrap();
+            (state.writes, state.snapshots)
+        };
+        assert!(writes <= 5, "{writes} writes for 300 records");
+        // Held-back changes aren't serialized until they are written.
+        assert_eq!(snapshots, writes);
+        let reopened = ClipboardHistory::new(500).with_encrypted_persistence(&path, key()).unwrap();
+        assert_eq!(reopened.len(), 300);
+        assert_eq!(reopened.get_recent(1)[0].content, "clip 299");
+
+        // Dropping the history writes whatever is still held back.
+        h.record("last".into(), "local".into());
+        h.record("really last".into(), "local".into());
+        drop(h);
+        let reopened = ClipboardHistory::new(500).with_encrypted_persistence(&path, key()).unwrap();
+        assert_eq!(reopened.get_recent(1)[0].content, "really last");
+        assert_eq!(reopened.len(), 302);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn changes_are_written_by_the_writer_thread_without_a_flush() {
+        let dir = std::env::temp_dir().join(format!("oc-history-{}", rand::random::<u64>()));
+        let path = dir.join("history.bin");
+        let key = || HistoryKey::from_bytes([6; 32]);
+
+        let h = ClipboardHistory::new(10).with_encrypted_persistence(&path, key()).unwrap();
+        h.record("written".into(), "local".into());
+        let start = Instant::now();
+        loop {
+            let reopened = ClipboardHistory::new(10).with_encrypted_persistence(&path, key());
+            if reopened.is_ok_and(|r| r.get_recent(1).first().is_some_and(|e| e.content == "written")) {
+                break;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5), "never written");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        drop(h);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn dropping_the_history_while_a_held_back_write_is_due_finishes() {
+        let dir = std::env::temp_dir().join(format!("oc-history-{}", rand::random::<u64>()));
+        let path = dir.join("history.bin");
+        let key = || HistoryKey::from_bytes([4; 32]);
+
+        let h = ClipboardHistory::new(10).with_encrypted_persistence(&path, key()).unwrap();
+        h.set_flush_interval(Some(Duration::from_millis(50)));
+        let persist = Arc::clone(h.persist.as_ref().unwrap());
+        h.record("first".into(), "local".into());
+        while persist.state.lock().unwrap().writes == 0 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        h.record("second".into(), "local".into());
+
+        // Hold the writer thread back until the write is due and the history
+        // is being dropped, so it starts writing while `Drop` closes.
+        let state = persist.state.lock().unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            drop(h);
+            let _ = done_tx.send(());
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        drop(state);
+        done_rx.recv_timeout(Duration::from_secs(5)).expect("dropping the history deadlocked");
+
+        let reopened = ClipboardHistory::new(10).with_encrypted_persistence(&path, key()).unwrap();
+        assert_eq!(reopened.get_recent(1)[0].content, "second");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn rapid_records_from_one_source_coalesce() {
+        let h = ClipboardHistory::new(10);
+        h.set_coalesce_window(Some(Duration::from_secs(60)));
+        for c in ["h", "he", "hel", "hello"] {
+            h.record(c.into(), "local".into());
+        }
+        h.record("from phone".into(), "phone".into());
+        h.record("hello again".into(), "local".into());
+
+        let contents: Vec<_> = h.get_recent(10).into_iter().map(|e| e.content).collect();
+        assert_eq!(contents, ["hello again", "from phone", "hello"]);
+        assert_eq!(h.total_bytes(), "hello again".len() + "from phone".len() + "hello".len());
+
+        h.set_coalesce_window(None);
+        h.record("kept".into(), "local".into());
+        assert_eq!(h.len(), 4);
+    }
+
+    #[test]
+    fn different_clips_within_the_coalesce_window_are_both_kept() {
+        let h = ClipboardHistory::new(10);
+        h.set_coalesce_window(Some(Duration::from_secs(60)));
+        h.record("password123".into(), "local".into());
+        h.record("https://example.com".into(), "local".into());
+        h.record("https://example.com".into(), "local".into());
+        h.record_image("image/png".into(), 1, 1, vec![1], "local".into());
+        h.record_image("image/png".into(), 1, 1, vec![2], "local".into());
+
+        let contents: Vec<_> = h.get_recent(10).into_iter().map(|e| e.content).collect();
+        assert_eq!(contents, ["image/png 1×1", "image/png 1×1", "https://example.com", "password123"]);
+    }
+
+    #[test]
+    fn preview_collapses_whitespace_and_truncates() {
+        assert_eq!(preview_text("meeting\n  notes", 80), "meeting notes");
+        assert_eq!(preview_text("abcdef", 3), "abc…");
+        assert_eq!(preview_text("ééé", 3), "ééé");
+    }
+
+    #[test]
+    fn preview_cuts_multibyte_text_on_char_boundaries() {
+        // Byte 80 falls inside a three-byte character, so `&s[..80]` panics.
+        let text = "日本語".repeat(30);
+        assert!(!text.is_char_boundary(80));
+        let p = preview(&ClipboardContent::Text(text), PREVIEW_MAX_CHARS);
+        assert_eq!(p.chars().count(), PREVIEW_MAX_CHARS + 1);
+        assert!(p.ends_with("日本…"));
+    }
+
+    #[test]
+    fn images_and_files_preview_by_what_they_are() {
+        let image = ClipboardContent::Image { mime: "image/png".into(), width: 1024, height: 768, bytes: vec![0; 64] };
+        assert_eq!(preview(&image, PREVIEW_MAX_CHARS), "image 1024×768 PNG");
+        assert_eq!(preview(&ClipboardContent::Empty, PREVIEW_MAX_CHARS), "");
+        assert_eq!(preview_file("file.pdf", 2_300_000, PREVIEW_MAX_CHARS), "file.pdf, 2.3 MB");
+        assert_eq!(preview_file("a.txt", 512, PREVIEW_MAX_CHARS), "a.txt, 512 B");
+        assert_eq!(preview_file("disk.img", 999_999_999, PREVIEW_MAX_CHARS), "disk.img, 1.0 GB");
+
+        let h = ClipboardHistory::new(10);
+        h.record_file("file.pdf".into(), 2_300_000, "/tmp/file.pdf".into(), "peer".into());
+        h.record_image("image/jpeg".into(), 640, 480, vec![1, 2, 3], "peer".into());
+        let previews: Vec<_> = h.get_recent(2).iter().map(|e| e.preview(PREVIEW_MAX_CHARS)).collect();
+        assert_eq!(previews, ["image 640×480 JPEG", "file.pdf, 2.3 MB"]);
+    }
+
     #[test]
     fn eviction_when_full() {
         let h = ClipboardHistory::new(3);
@@ -150,4 +936,21 @@ mod tests {
         let limited = h.get_recent(3);
         assert_eq!(limited.len(), 3);
     }
+
+    #[test]
+    fn on_evict_receives_evicted_entries_in_order() {
+        let h = ClipboardHistory::new(2);
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&evicted);
+        h.set_on_evict(Some(Arc::new(move |e: ClipboardEntry| sink.lock().unwrap().push(e.content))));
+
+        for c in ["a", "b", "c", "d"] {
+            h.record(c.into(), "local".into());
+        }
+        assert_eq!(*evicted.lock().unwrap(), ["a", "b"]);
+
+        h.set_on_evict(None);
+        h.record("e".into(), "local".into());
+        assert_eq!(evicted.lock().unwrap().len(), 2);
+    }
 }
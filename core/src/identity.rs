@@ -1,6 +1,7 @@
 //! Peer identity abstraction.
 
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use std::sync::Arc;
 
 /// Identity provider trait.
 pub trait IdentityProvider: Send + Sync {
@@ -12,15 +13,50 @@ pub trait IdentityProvider: Send + Sync {
     fn verify(&self, peer_id: &str, data: &[u8], signature: &[u8]) -> bool;
     /// Return the public key bytes (for serialization/pairing).
     fn public_key_bytes(&self) -> Vec<u8>;
+
+    /// Whether a remote `peer_id` belongs to `public_key` under the scheme
+    /// this identity's own peer_id uses. The handshake refuses peers for which
+    /// it doesn't.
+    fn verify_peer_id(&self, peer_id: &str, public_key: &[u8]) -> bool {
+        self.peer_id_scheme().verify(peer_id, public_key)
+    }
+
+    /// The scheme this identity's peer_id, and those it checks, use.
+    fn peer_id_scheme(&self) -> Arc<dyn PeerIdScheme> {
+        Arc::new(Blake3HexPeerId)
+    }
+}
+
+/// How a peer_id is derived from an identity public key. Whatever the format,
+/// the id stays bound to the key: anyone holding the key can re-derive it.
+pub trait PeerIdScheme: Send + Sync {
+    fn derive(&self, public_key: &[u8]) -> String;
+
+    /// Whether `peer_id` is the one `public_key` derives to.
+    fn verify(&self, peer_id: &str, public_key: &[u8]) -> bool {
+        self.derive(public_key) == peer_id
+    }
+}
+
+/// The default [`PeerIdScheme`]: `blake3(public_key)` in hex.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Blake3HexPeerId;
+
+impl PeerIdScheme for Blake3HexPeerId {
+    fn derive(&self, public_key: &[u8]) -> String {
+        blake3::hash(public_key).to_hex().to_string()
+    }
 }
 
 /// Ed25519-based identity using ed25519-dalek.
 ///
-/// Peer ID is `blake3(public_key)` in hex.
+/// Peer ID is `blake3(public_key)` in hex, unless another [`PeerIdScheme`]
+/// is set with [`Ed25519Identity::with_peer_id_scheme`].
 #[derive(Clone)]
 pub struct Ed25519Identity {
     signing_key: SigningKey,
     id: String,
+    scheme: Arc<dyn PeerIdScheme>,
 }
 
 impl Ed25519Identity {
@@ -28,13 +64,21 @@ impl Ed25519Identity {
         // ed25519-dalek requires a CSPRNG; use OsRng.
         let mut rng = rand_core::OsRng;
         let signing_key = SigningKey::generate(&mut rng);
-        let id = Self::peer_id_from_public_key(signing_key.verifying_key().as_bytes());
-        Self { signing_key, id }
+        Self::from_signing_key(signing_key)
     }
 
     pub fn from_signing_key(signing_key: SigningKey) -> Self {
         let id = Self::peer_id_from_public_key(signing_key.verifying_key().as_bytes());
-        Self { signing_key, id }
+        Self { signing_key, id, scheme: Arc::new(Blake3HexPeerId) }
+    }
+
+    /// Derive this identity's peer_id, and check those of peers it
+    /// handshakes with, using `scheme` instead of blake3 hex. Both sides of a
+    /// session must use the same scheme.
+    pub fn with_peer_id_scheme(mut self, scheme: Arc<dyn PeerIdScheme>) -> Self {
+        self.id = scheme.derive(self.signing_key.verifying_key().as_bytes());
+        self.scheme = scheme;
+        self
     }
 
     /// Export the signing key seed bytes (32 bytes). Useful for local persistence.
@@ -57,8 +101,18 @@ impl Ed25519Identity {
         pk.verify(data, &sig).is_ok()
     }
 
+    /// The peer_id `public_key` has under the default scheme ([`Blake3HexPeerId`]).
     pub fn peer_id_from_public_key(public_key: &[u8]) -> String {
-        blake3::hash(public_key).to_hex().to_string()
+        Blake3HexPeerId.derive(public_key)
+    }
+
+    /// Short human-comparable fingerprint of a public key (`AB:CD:...`, 8 bytes).
+    pub fn fingerprint_from_public_key(public_key: &[u8]) -> String {
+        blake3::hash(public_key).as_bytes()[..8]
+            .iter()
+            .map(|b| format!("{b:02X}"))
+            .collect::<Vec<_>>()
+            .join(":")
     }
 }
 
@@ -81,6 +135,10 @@ impl IdentityProvider for Ed25519Identity {
     fn public_key_bytes(&self) -> Vec<u8> {
         self.signing_key.verifying_key().as_bytes().to_vec()
     }
+
+    fn peer_id_scheme(&self) -> Arc<dyn PeerIdScheme> {
+        Arc::clone(&self.scheme)
+    }
 }
 
 /// Blake3-based identity (stub — uses blake3 hash as "signing").
@@ -143,6 +201,33 @@ fn padded_key(secret: &[u8]) -> [u8; 32] {
     key
 }
 
+/// A one-off X25519 key pair, made per handshake to agree on a session key.
+/// The secret never leaves it and is wiped when the pair is dropped.
+pub struct EphemeralX25519 {
+    secret: zeroize::Zeroizing<[u8; 32]>,
+    public: [u8; 32],
+}
+
+impl EphemeralX25519 {
+    pub fn generate() -> Self {
+        let mut secret = zeroize::Zeroizing::new([0u8; 32]);
+        rand_core::RngCore::fill_bytes(&mut rand_core::OsRng, secret.as_mut());
+        let public = curve25519_dalek::MontgomeryPoint::mul_base_clamped(*secret).to_bytes();
+        Self { secret, public }
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public
+    }
+
+    /// X25519 with `peer_public`. `None` for a low-order peer key, which
+    /// would make the result predictable.
+    pub fn diffie_hellman(&self, peer_public: &[u8; 32]) -> Option<[u8; 32]> {
+        let shared = curve25519_dalek::MontgomeryPoint(*peer_public).mul_clamped(*self.secret).to_bytes();
+        (shared != [0; 32]).then_some(shared)
+    }
+}
+
 /// Mock identity for testing.
 pub struct MockIdentity {
     id: String,
@@ -196,6 +281,16 @@ mod tests {
         assert!(!id.verify(id.peer_id(), b"wrong", &sig));
     }
 
+    #[test]
+    fn ephemeral_x25519_agrees_and_refuses_low_order_keys() {
+        let a = EphemeralX25519::generate();
+        let b = EphemeralX25519::generate();
+        let ab = a.diffie_hellman(&b.public_key()).unwrap();
+        assert_eq!(Some(ab), b.diffie_hellman(&a.public_key()));
+        assert_ne!(Some(ab), EphemeralX25519::generate().diffie_hellman(&b.public_key()));
+        assert_eq!(a.diffie_hellman(&[0; 32]), None);
+    }
+
     #[test]
     fn mock_identity_sign_verify() {
         let id = MockIdentity::new("peer-a");
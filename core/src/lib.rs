@@ -1,28 +1,96 @@
 //! openclipboard_core — trait-based architecture for cross-device clipboard sync.
+//!
+//! The `net` feature (on by default) enables sessions, transports, discovery
+//! and the sync services. With `--no-default-features` only the pure-logic
+//! modules are built, so the codec and identity also compile for wasm32.
 
 pub mod protocol;
 pub mod identity;
+#[cfg(feature = "net")]
 pub mod transport;
+#[cfg(feature = "net")]
 pub mod discovery;
 pub mod clipboard;
+#[cfg(feature = "net")]
 pub mod session;
+#[cfg(feature = "net")]
 pub mod quic_transport;
 pub mod pairing;
 pub mod trust;
 pub mod replay;
+#[cfg(feature = "net")]
 pub mod sync;
+#[cfg(feature = "net")]
 pub mod mesh;
 pub mod history;
+pub mod file_filter;
+pub mod file_log;
+pub mod visual;
+pub mod clock;
+pub mod b64;
+#[cfg(feature = "net")]
+pub mod rate_limit;
 
-pub use protocol::{Frame, MsgType, StreamId, Message, encode_frame, decode_frame, encode_message, decode_message, PROTOCOL_VERSION};
-pub use identity::{IdentityProvider, Blake3Identity, MockIdentity, Ed25519Identity};
-pub use transport::{Connection, Transport, Listener, MemoryConnection, memory_connection_pair, MemoryListener};
+pub use protocol::{Codec, Frame, MsgType, StreamId, Message, TrustSyncRecord, TrustSyncRevocation, FileAssembler, FileRejected, ProtocolDescriptor, MessageTypeDescriptor, StreamDescriptor, protocol_descriptor, encode_frame, decode_frame, decode_frame_with_len, encode_message, decode_message, encode_payload, decode_payload, encode_file_chunk_raw, decode_file_chunk_raw, PROTOCOL_VERSION, FEATURES, FEATURE_CLIP_ACK, FEATURE_ENCRYPTED_PAYLOADS, FEATURE_RAW_FILE_CHUNKS, FEATURE_TRUST_ACK, FEATURE_ZSTD_CLIP_TEXT, PayloadCipher, trust_ack_transcript};
+pub use identity::{IdentityProvider, Blake3Identity, MockIdentity, Ed25519Identity, EphemeralX25519, PeerIdScheme, Blake3HexPeerId};
+#[cfg(feature = "net")]
+pub use transport::{
+    box_listener, box_transport, BoxConnection, BoxListener, BoxTransport, Connection, Transport, Listener,
+    MemoryConnection, memory_connection_pair, MemoryListener,
+};
+#[cfg(feature = "net")]
 pub use discovery::{Discovery, PeerInfo, MockDiscovery, MdnsDiscovery, DiscoveryEvent, DiscoveryListener, BoxDiscovery};
 pub use clipboard::{ClipboardContent, ClipboardProvider, MockClipboard};
-pub use session::Session;
-pub use trust::{TrustRecord, TrustStore, MemoryTrustStore, FileTrustStore, default_trust_store_path};
-pub use replay::{ReplayProtector, MemoryReplayProtector};
-pub use pairing::{PairingPayload, derive_confirmation_code, get_local_ip_addresses};
-pub use sync::{SyncService, SyncHandler, EchoSuppressor};
-pub use mesh::{PeerRegistry, PeerEntry, PeerStatus, FanoutResult, start_clipboard_watcher};
-pub use history::{ClipboardHistory, ClipboardEntry};
+#[cfg(feature = "net")]
+pub use session::{FileTransferConfig, HandshakeError, HandshakeLimiter, SendPriority, Session};
+pub use trust::{TrustRecord, TrustRevocation, TrustStore, SyncDirection, IntegrityIssue, MemoryTrustStore, FileTrustStore, default_trust_store_path, merge_trust_records};
+#[cfg(feature = "net")]
+pub use trust::{AsyncTrustStore, BlockingTrustStore};
+pub use replay::{replay_path_for, ReplayProtector, MemoryReplayProtector, FileReplayProtector};
+pub use pairing::{PairingPayload, PairingSession, PairingResponseError, NotAnOpenClipboardCode, ConfirmationTranscript, derive_confirmation_code, derive_confirmation_code_with_transcript, verify_confirmation_code};
+#[cfg(feature = "net")]
+pub use pairing::get_local_ip_addresses;
+#[cfg(feature = "net")]
+pub use sync::{SyncService, SyncServiceOptions, SyncHandler, EchoSuppressor, NotedWrite, PendingRequest, AppInfo, SessionInfo, SyncSnapshot, SyncSummary, ConnectionEvent, FileProgressFn, RetryBudget, Keepalive, QuietRange, QuietHours, ErrorEntry, ErrorCategory};
+#[cfg(feature = "net")]
+pub use mesh::{PeerRegistry, PeerEntry, PeerStatus, LabelStyle, FanoutResult, start_clipboard_watcher};
+pub use history::{ClipboardHistory, ClipboardEntry, EvictCallback, HistoryItemKind, HistoryKey, preview, preview_file, preview_text, PREVIEW_MAX_CHARS};
+pub use file_filter::{mime_for_name, FileTypeFilter, FileTypeRules, REJECT_MIME_NOT_ALLOWED, REJECT_NOT_ACCEPTING, REJECT_TOO_LARGE};
+pub use file_log::{default_download_dir, default_partial_dir, FileReceiveLog, PartialFiles, ReceivedFile, DEFAULT_MAX_RECEIVED_FILE_SIZE, PARTIAL_FILE_MAX_AGE, REJECT_ALREADY_RECEIVED, REJECT_CORRUPTED};
+pub use visual::{DeviceVisual, peer_visual};
+pub use clock::{Clock, SystemClock, MockClock};
+pub use b64::{Base64Alphabet, decode_any as base64_decode_any};
+#[cfg(feature = "net")]
+pub use rate_limit::{RateLimiter, FileRateLimits, FileSendSlots};
+
+/// Exercises the `net`-free subset: framing and identity with no tokio runtime.
+#[cfg(test)]
+mod portable_tests {
+    use super::*;
+
+    #[test]
+    fn message_frame_roundtrip_without_runtime() {
+        let msg = Message::ClipText {
+            mime: "text/plain".into(),
+            text: "hello from wasm".into(),
+            ts_ms: 42,
+            origin: None,
+        };
+        let payload = encode_message(&msg, 7).unwrap();
+        let frame = Frame::new(msg.msg_type(), msg.stream_id(), 7, payload);
+        let decoded = decode_frame(&encode_frame(&frame).unwrap()).unwrap();
+        assert_eq!(decoded, frame);
+        assert_eq!(decode_message(&decoded.payload).unwrap(), (msg, 7));
+    }
+
+    #[test]
+    fn ed25519_identity_signs_and_verifies_without_runtime() {
+        let id = Ed25519Identity::generate();
+        let sig = id.sign(b"challenge");
+        assert!(id.verify(id.peer_id(), b"challenge", &sig));
+        assert!(!id.verify(id.peer_id(), b"tampered", &sig));
+        let pk = id.public_key_bytes();
+        assert!(Ed25519Identity::verify_with_public_key(b"challenge", &sig, &pk));
+        assert_eq!(id.peer_id(), Ed25519Identity::peer_id_from_public_key(&pk));
+    }
+}
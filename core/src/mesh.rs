@@ -2,7 +2,8 @@
 
 use crate::clipboard::{ClipboardContent, ClipboardProvider};
 use crate::sync::EchoSuppressor;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
 
@@ -10,11 +11,52 @@ use tokio::sync::{Mutex, RwLock};
 // PeerRegistry
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Set every entry's `label`, deterministically for a given set of peers.
+fn assign_labels(map: &mut BTreeMap<String, PeerEntry>, style: LabelStyle) {
+    let key = |name: &str| name.trim().to_lowercase();
+    let mut groups: BTreeMap<String, Vec<(i64, String)>> = BTreeMap::new();
+    for e in map.values() {
+        groups.entry(key(&e.display_name)).or_default().push((e.paired_at_ms, e.peer_id.clone()));
+    }
+    // A suffixed label must not collide with a peer actually named that way.
+    let mut taken: BTreeSet<String> = groups.keys().cloned().collect();
+    for members in groups.values_mut() {
+        members.sort();
+        let shared = members.len() > 1;
+        for (rank, (_, peer_id)) in members.iter().enumerate() {
+            let entry = map.get_mut(peer_id).expect("grouped from the map");
+            let name = entry.display_name.trim();
+            entry.label = match style {
+                _ if !shared => name.to_string(),
+                LabelStyle::Numbered if rank == 0 => name.to_string(),
+                LabelStyle::Numbered => (rank + 1..)
+                    .map(|n| format!("{name} ({n})"))
+                    .find(|label| taken.insert(key(label)))
+                    .expect("unbounded range"),
+                LabelStyle::PeerIdPrefix => format!("{name} ({})", &peer_id[..peer_id.len().min(8)]),
+            };
+        }
+    }
+}
+
 /// Online/offline status of a peer.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PeerStatus {
     Online,
     Offline,
+    /// Offline, and no longer dialed after its retry budget ran out.
+    Unreachable,
+}
+
+/// How [`PeerEntry::label`] tells apart peers that share a display name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LabelStyle {
+    /// The earliest-paired peer keeps the name; later ones get `"MacBook (2)"`,
+    /// `"MacBook (3)"`, ... so a label never changes when a newer device joins.
+    #[default]
+    Numbered,
+    /// Every peer sharing the name gets its peer id prefix: `"MacBook (3fa9c1d2)"`.
+    PeerIdPrefix,
 }
 
 /// Runtime info for a known peer.
@@ -22,36 +64,86 @@ pub enum PeerStatus {
 pub struct PeerEntry {
     pub peer_id: String,
     pub display_name: String,
+    /// `display_name`, suffixed when another known peer has the same name
+    /// (compared ignoring case); unique among the registry's peers.
+    pub label: String,
+    /// When the peer was paired (its trust record's `created_at`).
+    pub paired_at_ms: i64,
     pub last_addr: Option<String>,
+    /// Every address the peer currently advertises via discovery, primary first.
+    pub addresses: Vec<String>,
     pub status: PeerStatus,
+    /// Whether the current (or last) session is application-layer encrypted.
+    pub encrypted: bool,
+    /// Device details the peer sent after its last handshake; `None` until it sends them.
+    pub device_name: Option<String>,
+    pub platform: Option<String>,
+    pub app_version: Option<String>,
 }
 
 /// Thread-safe runtime registry of known peers.
 #[derive(Clone)]
 pub struct PeerRegistry {
-    peers: Arc<RwLock<HashMap<String, PeerEntry>>>,
+    peers: Arc<RwLock<BTreeMap<String, PeerEntry>>>,
+    /// Peers whose inbound clips are dropped; kept apart from `peers` so a
+    /// peer can be muted before (or without) being loaded from trust.
+    muted: Arc<RwLock<BTreeSet<String>>>,
+    /// The owner's other devices, the only peers whose `TrustSync` is merged.
+    linked: Arc<RwLock<BTreeSet<String>>>,
+    /// Where `linked` is persisted, if anywhere.
+    linked_path: Arc<std::sync::Mutex<Option<PathBuf>>>,
+    label_style: Arc<std::sync::Mutex<LabelStyle>>,
 }
 
 impl PeerRegistry {
     pub fn new() -> Self {
         Self {
-            peers: Arc::new(RwLock::new(HashMap::new())),
+            peers: Arc::new(RwLock::new(BTreeMap::new())),
+            muted: Arc::new(RwLock::new(BTreeSet::new())),
+            linked: Arc::new(RwLock::new(BTreeSet::new())),
+            linked_path: Arc::new(std::sync::Mutex::new(None)),
+            label_style: Arc::new(std::sync::Mutex::new(LabelStyle::default())),
         }
     }
 
     /// Seed from trust store records.
     pub async fn load_from_trust<T: crate::trust::TrustStore + ?Sized>(&self, store: &T) -> anyhow::Result<()> {
-        let records = store.list()?;
+        self.load_records(store.list()?).await;
+        Ok(())
+    }
+
+    /// [`Self::load_from_trust`] for a store read off the runtime's worker
+    /// threads, e.g. a [`crate::trust::BlockingTrustStore`].
+    pub async fn load_from_async_trust(&self, store: &dyn crate::trust::AsyncTrustStore) -> anyhow::Result<()> {
+        self.load_records(store.list().await?).await;
+        Ok(())
+    }
+
+    async fn load_records(&self, records: Vec<crate::trust::TrustRecord>) {
         let mut map = self.peers.write().await;
         for rec in records {
             map.entry(rec.peer_id.clone()).or_insert_with(|| PeerEntry {
                 peer_id: rec.peer_id,
+                label: rec.display_name.clone(),
                 display_name: rec.display_name,
+                paired_at_ms: rec.created_at.timestamp_millis(),
                 last_addr: None,
+                addresses: Vec::new(),
                 status: PeerStatus::Offline,
+                encrypted: false,
+                device_name: None,
+                platform: None,
+                app_version: None,
             });
         }
-        Ok(())
+        assign_labels(&mut map, *self.label_style.lock().unwrap());
+    }
+
+    /// Change how peers sharing a display name are labelled, relabelling the
+    /// known ones.
+    pub async fn set_label_style(&self, style: LabelStyle) {
+        *self.label_style.lock().unwrap() = style;
+        assign_labels(&mut *self.peers.write().await, style);
     }
 
     pub async fn set_online(&self, peer_id: &str, addr: Option<String>) {
@@ -64,6 +156,32 @@ impl PeerRegistry {
         }
     }
 
+    /// Record the addresses a peer advertised in its latest discovery record.
+    pub async fn set_addresses(&self, peer_id: &str, addresses: Vec<String>) {
+        let mut map = self.peers.write().await;
+        if let Some(entry) = map.get_mut(peer_id) {
+            entry.addresses = addresses;
+        }
+    }
+
+    /// Record the negotiated encryption state of the peer's session.
+    pub async fn set_encrypted(&self, peer_id: &str, encrypted: bool) {
+        let mut map = self.peers.write().await;
+        if let Some(entry) = map.get_mut(peer_id) {
+            entry.encrypted = encrypted;
+        }
+    }
+
+    /// Record the device details the peer sent (`Message::DeviceInfo`).
+    pub async fn set_device_info(&self, peer_id: &str, name: String, platform: String, app_version: String) {
+        let mut map = self.peers.write().await;
+        if let Some(entry) = map.get_mut(peer_id) {
+            entry.device_name = Some(name);
+            entry.platform = Some(platform);
+            entry.app_version = Some(app_version);
+        }
+    }
+
     pub async fn set_offline(&self, peer_id: &str) {
         let mut map = self.peers.write().await;
         if let Some(entry) = map.get_mut(peer_id) {
@@ -71,6 +189,24 @@ impl PeerRegistry {
         }
     }
 
+    pub async fn set_unreachable(&self, peer_id: &str) {
+        let mut map = self.peers.write().await;
+        if let Some(entry) = map.get_mut(peer_id) {
+            entry.status = PeerStatus::Unreachable;
+        }
+    }
+
+    /// Back to `Offline` if the peer was `Unreachable`.
+    pub async fn clear_unreachable(&self, peer_id: &str) {
+        let mut map = self.peers.write().await;
+        if let Some(entry) = map.get_mut(peer_id)
+            && entry.status == PeerStatus::Unreachable
+        {
+            entry.status = PeerStatus::Offline;
+        }
+    }
+
+    /// Online peers, sorted by `peer_id`.
     pub async fn list_online(&self) -> Vec<PeerEntry> {
         let map = self.peers.read().await;
         map.values()
@@ -79,6 +215,7 @@ impl PeerRegistry {
             .collect()
     }
 
+    /// All known peers, sorted by `peer_id`.
     pub async fn list_all(&self) -> Vec<PeerEntry> {
         let map = self.peers.read().await;
         map.values().cloned().collect()
@@ -88,6 +225,85 @@ impl PeerRegistry {
         let map = self.peers.read().await;
         map.get(peer_id).cloned()
     }
+
+    /// Forget a peer that is no longer trusted. Returns whether it was known.
+    pub async fn remove(&self, peer_id: &str) -> bool {
+        let mut map = self.peers.write().await;
+        let removed = map.remove(peer_id).is_some();
+        if removed {
+            assign_labels(&mut map, *self.label_style.lock().unwrap());
+        }
+        removed
+    }
+
+    /// Drop (or stop dropping) inbound clips from a peer without disconnecting
+    /// or untrusting it.
+    pub async fn set_muted(&self, peer_id: &str, muted: bool) {
+        let mut set = self.muted.write().await;
+        if muted {
+            set.insert(peer_id.to_string());
+        } else {
+            set.remove(peer_id);
+        }
+    }
+
+    pub async fn is_muted(&self, peer_id: &str) -> bool {
+        self.muted.read().await.contains(peer_id)
+    }
+
+    /// Mark (or unmark) a peer as one of this owner's own devices, which
+    /// exchanges trust records with it. Persisted if [`Self::set_linked_path`]
+    /// is set; the change only applies if that write succeeds.
+    pub async fn set_linked(&self, peer_id: &str, linked: bool) -> anyhow::Result<()> {
+        let mut set = self.linked.write().await;
+        let mut next = set.clone();
+        if linked {
+            next.insert(peer_id.to_string());
+        } else {
+            next.remove(peer_id);
+        }
+        let path = self.linked_path.lock().unwrap().clone();
+        if let Some(path) = path {
+            let ids: Vec<String> = next.iter().cloned().collect();
+            tokio::task::spawn_blocking(move || crate::trust::write_json_atomically(&path, &ids)).await??;
+        }
+        *set = next;
+        Ok(())
+    }
+
+    /// Persist the linked devices at `path` (a JSON array of peer ids),
+    /// replacing the in-memory set with the one already there (none if the
+    /// file doesn't exist yet). `None` stops persisting.
+    pub async fn set_linked_path(&self, path: Option<PathBuf>) -> anyhow::Result<()> {
+        if let Some(path) = path.clone() {
+            let loaded = tokio::task::spawn_blocking(move || -> anyhow::Result<BTreeSet<String>> {
+                if !path.exists() {
+                    return Ok(BTreeSet::new());
+                }
+                let data = std::fs::read_to_string(&path)?;
+                Ok(serde_json::from_str(&data)?)
+            })
+            .await??;
+            *self.linked.write().await = loaded;
+        }
+        *self.linked_path.lock().unwrap() = path;
+        Ok(())
+    }
+
+    pub async fn is_linked(&self, peer_id: &str) -> bool {
+        self.linked.read().await.contains(peer_id)
+    }
+
+    /// Linked devices, sorted.
+    pub async fn linked(&self) -> Vec<String> {
+        self.linked.read().await.iter().cloned().collect()
+    }
+
+    /// Receive-time filter, consulted before a peer's clip reaches history
+    /// or the handler.
+    pub async fn should_accept(&self, peer_id: &str, _content: &ClipboardContent) -> bool {
+        !self.is_muted(peer_id).await
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -139,12 +355,15 @@ where
                 continue;
             }
 
-            // Check echo suppression for text content.
-            if let ClipboardContent::Text(ref t) = current {
-                if echo_suppressor.lock().await.should_ignore_local_change(t) {
-                    last = Some(current);
-                    continue;
-                }
+            // Check echo suppression for text and image content.
+            let echo = match &current {
+                ClipboardContent::Text(t) => echo_suppressor.lock().await.should_ignore_local_change(t),
+                ClipboardContent::Image { bytes, .. } => echo_suppressor.lock().await.should_ignore_local_image(bytes),
+                ClipboardContent::Empty => false,
+            };
+            if echo {
+                last = Some(current);
+                continue;
             }
 
             last = Some(current.clone());
@@ -170,12 +389,14 @@ mod tests {
                 identity_pk: vec![1],
                 display_name: "Peer1".into(),
                 created_at: chrono::Utc::now(),
+                sync_direction: Default::default(),
             }).unwrap();
             store.save(crate::trust::TrustRecord {
                 peer_id: "p2".into(),
                 identity_pk: vec![2],
                 display_name: "Peer2".into(),
                 created_at: chrono::Utc::now(),
+                sync_direction: Default::default(),
             }).unwrap();
 
             reg.load_from_trust(&store).await.unwrap();
@@ -190,6 +411,93 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn peer_registry_lists_sorted_by_peer_id() {
+        let reg = PeerRegistry::new();
+        let store = crate::trust::MemoryTrustStore::new();
+        for id in ["p3", "p1", "p2"] {
+            store.save(crate::trust::TrustRecord {
+                peer_id: id.into(),
+                identity_pk: vec![1],
+                display_name: id.into(),
+                created_at: chrono::Utc::now(),
+                sync_direction: Default::default(),
+            }).unwrap();
+        }
+        reg.load_from_trust(&store).await.unwrap();
+        reg.set_online("p3", None).await;
+        reg.set_online("p1", None).await;
+
+        let all: Vec<_> = reg.list_all().await.into_iter().map(|e| e.peer_id).collect();
+        assert_eq!(all, ["p1", "p2", "p3"]);
+        let online: Vec<_> = reg.list_online().await.into_iter().map(|e| e.peer_id).collect();
+        assert_eq!(online, ["p1", "p3"]);
+    }
+
+    #[tokio::test]
+    async fn peers_sharing_a_name_get_distinct_stable_labels() {
+        let store = crate::trust::MemoryTrustStore::new();
+        let pair = |id: &str, name: &str, day: u32| {
+            store.save(crate::trust::TrustRecord {
+                peer_id: id.into(),
+                identity_pk: vec![1],
+                display_name: name.into(),
+                created_at: chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2026, 1, day, 0, 0, 0).unwrap(),
+                sync_direction: Default::default(),
+            }).unwrap();
+        };
+        // Paired first, so it keeps the bare name despite the higher peer id.
+        pair("p9", "MacBook", 1);
+        pair("p2", "MacBook", 2);
+        pair("p5", "Phone", 3);
+
+        let labels = |reg: PeerRegistry| async move {
+            reg.list_all().await.into_iter().map(|e| (e.peer_id, e.label)).collect::<Vec<_>>()
+        };
+        let reg = PeerRegistry::new();
+        reg.load_from_trust(&store).await.unwrap();
+        let expected = [("p2", "MacBook (2)"), ("p5", "Phone"), ("p9", "MacBook")].map(|(p, l)| (p.to_string(), l.to_string()));
+        assert_eq!(labels(reg.clone()).await, expected);
+
+        // Same labels from a fresh registry, and a newer namesake doesn't shift them.
+        let again = PeerRegistry::new();
+        again.load_from_trust(&store).await.unwrap();
+        assert_eq!(labels(again).await, expected);
+        pair("p1", "macbook", 4);
+        reg.load_from_trust(&store).await.unwrap();
+        assert_eq!(reg.get("p9").await.unwrap().label, "MacBook");
+        assert_eq!(reg.get("p2").await.unwrap().label, "MacBook (2)");
+        assert_eq!(reg.get("p1").await.unwrap().label, "macbook (3)");
+
+        reg.set_label_style(LabelStyle::PeerIdPrefix).await;
+        assert_eq!(reg.get("p9").await.unwrap().label, "MacBook (p9)");
+        assert_eq!(reg.get("p5").await.unwrap().label, "Phone");
+    }
+
+    #[test]
+    fn numbered_labels_skip_names_already_in_use() {
+        let entry = |id: &str, name: &str, at: i64| PeerEntry {
+            peer_id: id.into(),
+            display_name: name.into(),
+            label: String::new(),
+            paired_at_ms: at,
+            last_addr: None,
+            addresses: Vec::new(),
+            status: PeerStatus::Offline,
+            encrypted: false,
+            device_name: None,
+            platform: None,
+            app_version: None,
+        };
+        let mut map: BTreeMap<_, _> = [entry("a", "Mac", 1), entry("b", "Mac", 2), entry("c", "Mac (2)", 3)]
+            .into_iter()
+            .map(|e| (e.peer_id.clone(), e))
+            .collect();
+        assign_labels(&mut map, LabelStyle::Numbered);
+        let labels: Vec<_> = map.values().map(|e| e.label.as_str()).collect();
+        assert_eq!(labels, ["Mac", "Mac (3)", "Mac (2)"]);
+    }
+
     #[tokio::test]
     async fn clipboard_watcher_detects_change() {
         let cb = Arc::new(MockClipboard::new());
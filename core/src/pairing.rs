@@ -1,8 +1,10 @@
 //! Pairing protocol: QR payload generation and confirmation code derivation.
 
+use crate::b64::{decode_any, Base64Alphabet};
+use crate::identity::{Blake3HexPeerId, PeerIdScheme};
 use anyhow::Result;
-use base64::Engine as _;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Payload exchanged during pairing (e.g. encoded as QR).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -18,7 +20,31 @@ pub struct PairingPayload {
     pub lan_addrs: Vec<String>,
 }
 
+/// Scheme prefix carried by every OpenClipboard pairing QR string.
+pub const QR_PREFIX: &str = "openclipboard:v1:";
+
+/// Prefix of the compact pairing QR string: base45 over a packed binary
+/// payload. Upper-case only, so the whole string fits QR alphanumeric mode.
+pub const QR_COMPACT_PREFIX: &str = "OC1:";
+
+/// Returned (inside `anyhow::Error`) when a scanned string is not an OpenClipboard
+/// pairing code at all, e.g. a URL or WiFi QR code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotAnOpenClipboardCode;
+
+impl std::fmt::Display for NotAnOpenClipboardCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "not an OpenClipboard pairing code (expected prefix {QR_PREFIX:?} or {QR_COMPACT_PREFIX:?})"
+        )
+    }
+}
+
+impl std::error::Error for NotAnOpenClipboardCode {}
+
 /// Get all non-loopback IPv4 addresses on this machine.
+#[cfg(feature = "net")]
 pub fn get_local_ip_addresses() -> Vec<String> {
     match local_ip_address::list_afinet_netifas() {
         Ok(ifaces) => ifaces
@@ -31,32 +57,346 @@ pub fn get_local_ip_addresses() -> Vec<String> {
 }
 
 impl PairingPayload {
-    /// Serialize to JSON then base64 (URL-safe, no padding) for QR embedding.
+    /// Serialize to JSON then base64 (URL-safe, no padding) for QR embedding,
+    /// prefixed with `QR_PREFIX`.
     pub fn to_qr_string(&self) -> String {
+        self.to_qr_string_with(Base64Alphabet::UrlSafe)
+    }
+
+    /// [`to_qr_string`](Self::to_qr_string) in a chosen base64 alphabet, for
+    /// consumers that expect standard base64. `from_qr_string` reads either.
+    pub fn to_qr_string_with(&self, alphabet: Base64Alphabet) -> String {
         let json = serde_json::to_vec(self).expect("PairingPayload JSON serialize");
-        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json)
+        format!("{QR_PREFIX}{}", alphabet.encode(json))
+    }
+
+    /// Compact alternative to [`to_qr_string`](Self::to_qr_string): packed
+    /// binary, base45-encoded, prefixed with `QR_COMPACT_PREFIX`. Yields a
+    /// lower-density QR code for the same payload.
+    pub fn to_qr_string_compact(&self) -> Result<String> {
+        Ok(format!("{QR_COMPACT_PREFIX}{}", base45_encode(&self.to_compact_bytes()?)))
     }
 
-    /// Parse from base64(JSON).
+    /// Parse either QR form, detected by prefix; the base64 alphabet of the
+    /// `QR_PREFIX` form is detected too.
+    ///
+    /// Fails with `NotAnOpenClipboardCode` if neither prefix is present.
     pub fn from_qr_string(s: &str) -> Result<Self> {
-        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(s)?;
+        let s = s.trim();
+        if let Some(encoded) = s.strip_prefix(QR_COMPACT_PREFIX) {
+            return Self::from_compact_bytes(&base45_decode(encoded)?);
+        }
+        let Some(encoded) = s.strip_prefix(QR_PREFIX) else {
+            return Err(NotAnOpenClipboardCode.into());
+        };
+        let bytes = decode_any(encoded)?;
         Ok(serde_json::from_slice(&bytes)?)
     }
+
+    /// Layout: `version`, `peer_id`, `name`, `identity_pk`, `lan_port` (u16 BE),
+    /// `nonce`, then `lan_addrs`. Byte strings are u8-length-prefixed; a peer
+    /// ID that is 64 hex chars is packed as its 32 raw bytes; addresses that
+    /// parse as IPv4/IPv6 are stored as raw octets.
+    fn to_compact_bytes(&self) -> Result<Vec<u8>> {
+        fn put(out: &mut Vec<u8>, bytes: &[u8]) -> Result<()> {
+            let len = u8::try_from(bytes.len()).map_err(|_| anyhow::anyhow!("pairing field too long"))?;
+            out.push(len);
+            out.extend_from_slice(bytes);
+            Ok(())
+        }
+
+        let mut out = vec![self.version];
+        match hex::decode(&self.peer_id) {
+            Ok(raw) if raw.len() == 32 && self.peer_id == hex::encode(&raw) => {
+                out.push(PEER_ID_HEX32);
+                out.extend_from_slice(&raw);
+            }
+            _ => {
+                out.push(PEER_ID_TEXT);
+                put(&mut out, self.peer_id.as_bytes())?;
+            }
+        }
+        put(&mut out, self.name.as_bytes())?;
+        put(&mut out, &self.identity_pk)?;
+        out.extend_from_slice(&self.lan_port.to_be_bytes());
+        put(&mut out, &self.nonce)?;
+
+        let count = u8::try_from(self.lan_addrs.len()).map_err(|_| anyhow::anyhow!("too many LAN addresses"))?;
+        out.push(count);
+        for addr in &self.lan_addrs {
+            match addr.parse::<std::net::IpAddr>() {
+                Ok(std::net::IpAddr::V4(ip)) if ip.to_string() == *addr => {
+                    out.push(ADDR_V4);
+                    out.extend_from_slice(&ip.octets());
+                }
+                Ok(std::net::IpAddr::V6(ip)) if ip.to_string() == *addr => {
+                    out.push(ADDR_V6);
+                    out.extend_from_slice(&ip.octets());
+                }
+                _ => {
+                    out.push(ADDR_TEXT);
+                    put(&mut out, addr.as_bytes())?;
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn from_compact_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut r = CompactReader(bytes);
+        let version = r.take(1)?[0];
+        let peer_id = match r.take(1)?[0] {
+            PEER_ID_HEX32 => hex::encode(r.take(32)?),
+            PEER_ID_TEXT => r.string()?,
+            tag => anyhow::bail!("unknown peer id tag {tag}"),
+        };
+        let name = r.string()?;
+        let identity_pk = r.bytes()?.to_vec();
+        let port = r.take(2)?;
+        let lan_port = u16::from_be_bytes([port[0], port[1]]);
+        let nonce = r.bytes()?.to_vec();
+
+        let count = r.take(1)?[0];
+        let mut lan_addrs = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let addr = match r.take(1)?[0] {
+                ADDR_V4 => {
+                    let o: [u8; 4] = r.take(4)?.try_into()?;
+                    std::net::Ipv4Addr::from(o).to_string()
+                }
+                ADDR_V6 => {
+                    let o: [u8; 16] = r.take(16)?.try_into()?;
+                    std::net::Ipv6Addr::from(o).to_string()
+                }
+                ADDR_TEXT => r.string()?,
+                tag => anyhow::bail!("unknown address tag {tag}"),
+            };
+            lan_addrs.push(addr);
+        }
+        anyhow::ensure!(r.0.is_empty(), "trailing bytes in compact pairing payload");
+
+        Ok(Self { version, peer_id, name, identity_pk, lan_port, nonce, lan_addrs })
+    }
+}
+
+const PEER_ID_HEX32: u8 = 0;
+const PEER_ID_TEXT: u8 = 1;
+const ADDR_V4: u8 = 4;
+const ADDR_V6: u8 = 6;
+const ADDR_TEXT: u8 = 0;
+
+struct CompactReader<'a>(&'a [u8]);
+
+impl<'a> CompactReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        anyhow::ensure!(self.0.len() >= n, "truncated compact pairing payload");
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.take(1)?[0] as usize;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        Ok(String::from_utf8(self.bytes()?.to_vec())?)
+    }
+}
+
+/// RFC 9285 alphabet; every character is valid in QR alphanumeric mode.
+const BASE45_ALPHABET: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+fn base45_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(2) * 3);
+    for chunk in data.chunks(2) {
+        let (mut n, digits) = match *chunk {
+            [a, b] => ((a as usize) * 256 + b as usize, 3),
+            [a] => (a as usize, 2),
+            _ => unreachable!(),
+        };
+        for _ in 0..digits {
+            out.push(BASE45_ALPHABET[n % 45] as char);
+            n /= 45;
+        }
+    }
+    out
+}
+
+fn base45_decode(s: &str) -> Result<Vec<u8>> {
+    let digits = s
+        .bytes()
+        .map(|c| {
+            BASE45_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .ok_or_else(|| anyhow::anyhow!("invalid base45 character {:?}", c as char))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut out = Vec::with_capacity(digits.len() / 3 * 2 + 1);
+    for chunk in digits.chunks(3) {
+        match *chunk {
+            [c, d, e] => {
+                let n = c + d * 45 + e * 45 * 45;
+                anyhow::ensure!(n <= 0xFFFF, "invalid base45 group");
+                out.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            [c, d] => {
+                let n = c + d * 45;
+                anyhow::ensure!(n <= 0xFF, "invalid base45 group");
+                out.push(n as u8);
+            }
+            _ => anyhow::bail!("invalid base45 length"),
+        }
+    }
+    Ok(out)
+}
+
+/// The exact inputs behind a pairing confirmation code, for auditing the
+/// derivation or checking another implementation against this one.
+///
+/// The hashed bytes are `nonce || peer_a_id || peer_b_id` (peer ids as UTF-8),
+/// with no separators or length prefixes; `peer_a` is the initiator. Public
+/// keys are bound through the peer ids, which are derived from them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmationTranscript {
+    pub nonce: Vec<u8>,
+    pub peer_a_id: String,
+    pub peer_b_id: String,
+}
+
+impl ConfirmationTranscript {
+    pub fn new(nonce: &[u8], peer_a_id: &str, peer_b_id: &str) -> Self {
+        Self { nonce: nonce.to_vec(), peer_a_id: peer_a_id.to_string(), peer_b_id: peer_b_id.to_string() }
+    }
+
+    /// The canonical byte string that is hashed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.nonce.len() + self.peer_a_id.len() + self.peer_b_id.len());
+        out.extend_from_slice(&self.nonce);
+        out.extend_from_slice(self.peer_a_id.as_bytes());
+        out.extend_from_slice(self.peer_b_id.as_bytes());
+        out
+    }
+
+    /// `u32::from_le_bytes(blake3(to_bytes())[0..4]) % 1_000_000`, zero-padded to 6 digits.
+    pub fn code(&self) -> String {
+        let hash = blake3::hash(&self.to_bytes());
+        let b = hash.as_bytes();
+        let n = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+        format!("{:06}", n % 1_000_000)
+    }
 }
 
 /// Derive a 6-digit confirmation code from the nonce and both peer IDs.
 ///
-/// `code = blake3(nonce || peer_a_id || peer_b_id) % 1_000_000`.
+/// `code = blake3(nonce || peer_a_id || peer_b_id) % 1_000_000`; see
+/// [`ConfirmationTranscript`] for the exact format.
 pub fn derive_confirmation_code(nonce: &[u8], peer_a_id: &str, peer_b_id: &str) -> String {
-    let mut hasher = blake3::Hasher::new();
-    hasher.update(nonce);
-    hasher.update(peer_a_id.as_bytes());
-    hasher.update(peer_b_id.as_bytes());
-    let hash = hasher.finalize();
+    ConfirmationTranscript::new(nonce, peer_a_id, peer_b_id).code()
+}
+
+/// Like [`derive_confirmation_code`], also returning the transcript it hashed.
+pub fn derive_confirmation_code_with_transcript(
+    nonce: &[u8],
+    peer_a_id: &str,
+    peer_b_id: &str,
+) -> (String, ConfirmationTranscript) {
+    let transcript = ConfirmationTranscript::new(nonce, peer_a_id, peer_b_id);
+    (transcript.code(), transcript)
+}
+
+/// Whether `code` is the confirmation code for `transcript`. Surrounding
+/// whitespace is ignored; the comparison doesn't short-circuit.
+pub fn verify_confirmation_code(transcript: &ConfirmationTranscript, code: &str) -> bool {
+    let expected = transcript.code();
+    let code = code.trim().as_bytes();
+    code.len() == expected.len()
+        && code.iter().zip(expected.as_bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
 
-    let b = hash.as_bytes();
-    let n = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
-    format!("{:06}", n % 1_000_000)
+/// Why [`PairingSession::confirm_response`] refused a response (inside `anyhow::Error`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PairingResponseError {
+    /// The response echoes a different nonce: it answers some other (or a stale) init.
+    NonceMismatch,
+    /// The response carries our own peer id, e.g. our init QR scanned back.
+    OwnPayload,
+    /// The responder's peer id isn't derived from the key it sent.
+    PeerIdMismatch,
+}
+
+impl std::fmt::Display for PairingResponseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonceMismatch => write!(f, "pairing response is not for this init (nonce mismatch)"),
+            Self::OwnPayload => write!(f, "pairing response is this device's own init"),
+            Self::PeerIdMismatch => write!(f, "pairing response peer id does not match its key"),
+        }
+    }
+}
+
+impl std::error::Error for PairingResponseError {}
+
+impl PairingResponseError {
+    /// The typed error inside `e`, if it is one.
+    pub fn of(e: &anyhow::Error) -> Option<&Self> {
+        e.downcast_ref::<Self>()
+    }
+}
+
+/// Initiator-side pairing state: the init payload this device showed, kept so
+/// a scanned response can be checked against it before anything is trusted.
+#[derive(Clone)]
+pub struct PairingSession {
+    init: PairingPayload,
+    scheme: Arc<dyn PeerIdScheme>,
+}
+
+impl std::fmt::Debug for PairingSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PairingSession").field("init", &self.init).finish_non_exhaustive()
+    }
+}
+
+impl PairingSession {
+    pub fn new(init: PairingPayload) -> Self {
+        Self { init, scheme: Arc::new(Blake3HexPeerId) }
+    }
+
+    /// Check the responder's peer id with `scheme` instead of blake3 hex, as
+    /// for an identity set up with `Ed25519Identity::with_peer_id_scheme`.
+    pub fn with_peer_id_scheme(mut self, scheme: Arc<dyn PeerIdScheme>) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// The init payload to show (e.g. `init().to_qr_string()`).
+    pub fn init(&self) -> &PairingPayload {
+        &self.init
+    }
+
+    /// Parse a scanned response and check it answers *this* init: it must echo
+    /// our nonce, come from another device, and carry a peer id matching its
+    /// key. Returns the responder's payload and the confirmation code to show.
+    pub fn confirm_response(&self, resp_qr: &str) -> Result<(PairingPayload, String)> {
+        let resp = PairingPayload::from_qr_string(resp_qr)?;
+        let nonce_matches = resp.nonce.len() == self.init.nonce.len()
+            && resp.nonce.iter().zip(&self.init.nonce).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0;
+        if !nonce_matches {
+            return Err(PairingResponseError::NonceMismatch.into());
+        }
+        if resp.peer_id == self.init.peer_id {
+            return Err(PairingResponseError::OwnPayload.into());
+        }
+        if !self.scheme.verify(&resp.peer_id, &resp.identity_pk) {
+            return Err(PairingResponseError::PeerIdMismatch.into());
+        }
+        let code = derive_confirmation_code(&self.init.nonce, &self.init.peer_id, &resp.peer_id);
+        Ok((resp, code))
+    }
 }
 
 #[cfg(test)]
@@ -76,10 +416,77 @@ mod tests {
         };
 
         let s = payload.to_qr_string();
+        assert!(s.starts_with(QR_PREFIX));
         let decoded = PairingPayload::from_qr_string(&s).unwrap();
         assert_eq!(payload, decoded);
     }
 
+    #[test]
+    fn qr_string_in_either_base64_alphabet_parses() {
+        // A run of `~` (0x7e) encodes to `fn5+` / `fn5-`.
+        let payload = PairingPayload {
+            version: 1,
+            peer_id: "peer-a".into(),
+            name: "~~~~~~".into(),
+            identity_pk: vec![0xfb; 32],
+            lan_port: 18455,
+            nonce: vec![0xff; 32],
+            lan_addrs: vec![],
+        };
+
+        let standard = payload.to_qr_string_with(Base64Alphabet::Standard);
+        let url_safe = payload.to_qr_string_with(Base64Alphabet::UrlSafe);
+        assert!(standard.contains('+') && !url_safe.contains('+'), "{standard} / {url_safe}");
+        assert_eq!(url_safe, payload.to_qr_string());
+        for s in [standard, url_safe] {
+            assert_eq!(PairingPayload::from_qr_string(&s).unwrap(), payload);
+        }
+    }
+
+    #[test]
+    fn compact_qr_roundtrip_and_is_shorter() {
+        let pk = vec![7u8; 32];
+        let payload = PairingPayload {
+            version: 2,
+            peer_id: blake3::hash(&pk).to_hex().to_string(),
+            name: "Alice's MacBook Pro".into(),
+            identity_pk: pk,
+            lan_port: 18455,
+            nonce: vec![9; 32],
+            lan_addrs: vec!["192.168.1.10".into(), "10.0.0.4".into(), "fe80::1".into(), "host.local".into()],
+        };
+
+        let compact = payload.to_qr_string_compact().unwrap();
+        assert!(compact.starts_with(QR_COMPACT_PREFIX));
+        assert!(compact.bytes().all(|c| BASE45_ALPHABET.contains(&c)));
+        assert_eq!(PairingPayload::from_qr_string(&compact).unwrap(), payload);
+
+        let json = payload.to_qr_string();
+        assert_eq!(PairingPayload::from_qr_string(&json).unwrap(), payload);
+        // At least a third shorter, before even counting alphanumeric-mode savings.
+        assert!(compact.len() * 3 < json.len() * 2, "compact {} vs json {}", compact.len(), json.len());
+    }
+
+    #[test]
+    fn base45_matches_rfc_examples() {
+        assert_eq!(base45_encode(b"AB"), "BB8");
+        assert_eq!(base45_encode(b"Hello!!"), "%69 VD92EX0");
+        assert_eq!(base45_decode("QED8WEX0").unwrap(), b"ietf!");
+        assert!(base45_decode("GGW").is_err());
+    }
+
+    #[test]
+    fn foreign_qr_string_is_not_an_openclipboard_code() {
+        for s in ["https://example.com/pair", "WIFI:S:home;T:WPA;P:secret;;", ""] {
+            let err = PairingPayload::from_qr_string(s).unwrap_err();
+            assert!(err.downcast_ref::<NotAnOpenClipboardCode>().is_some(), "{s:?}: {err}");
+        }
+
+        // Correct prefix but garbage body is a different (decode) error.
+        let err = PairingPayload::from_qr_string("openclipboard:v1:!!!").unwrap_err();
+        assert!(err.downcast_ref::<NotAnOpenClipboardCode>().is_none());
+    }
+
     #[test]
     fn derive_code_is_deterministic_and_6_digits() {
         let nonce = vec![42u8; 32];
@@ -97,4 +504,82 @@ mod tests {
         let c2 = derive_confirmation_code(&nonce, "peer-a", "peer-c");
         assert_ne!(c1, c2);
     }
+
+    #[test]
+    fn transcript_is_nonce_then_both_peer_ids() {
+        let nonce = [1u8, 2, 3, 4];
+        let (code, transcript) = derive_confirmation_code_with_transcript(&nonce, "peer-a", "peer-b");
+
+        assert_eq!(transcript.nonce, nonce);
+        assert_eq!(transcript.peer_a_id, "peer-a");
+        assert_eq!(transcript.peer_b_id, "peer-b");
+        assert_eq!(transcript.to_bytes(), b"\x01\x02\x03\x04peer-apeer-b".to_vec());
+
+        let b = *blake3::hash(&transcript.to_bytes()).as_bytes();
+        let expected = format!("{:06}", u32::from_le_bytes([b[0], b[1], b[2], b[3]]) % 1_000_000);
+        assert_eq!(code, expected);
+        assert_eq!(code, derive_confirmation_code(&nonce, "peer-a", "peer-b"));
+    }
+
+    #[test]
+    fn pairing_session_accepts_only_responses_to_its_own_init() {
+        use crate::identity::{Ed25519Identity, IdentityProvider};
+
+        let payload = |id: &Ed25519Identity, name: &str, nonce: [u8; 32]| PairingPayload {
+            version: 1,
+            peer_id: id.peer_id().to_string(),
+            name: name.into(),
+            identity_pk: id.public_key_bytes(),
+            lan_port: 18455,
+            nonce: nonce.to_vec(),
+            lan_addrs: vec![],
+        };
+        let (alice, bob) = (Ed25519Identity::generate(), Ed25519Identity::generate());
+        let session = PairingSession::new(payload(&alice, "Alice", [1; 32]));
+
+        let (resp, code) = session.confirm_response(&payload(&bob, "Bob", [1; 32]).to_qr_string()).unwrap();
+        assert_eq!(resp.peer_id, bob.peer_id());
+        assert_eq!(code, derive_confirmation_code(&[1; 32], alice.peer_id(), bob.peer_id()));
+
+        // A response to some other init (e.g. a stale one) is refused.
+        let err = session.confirm_response(&payload(&bob, "Bob", [2; 32]).to_qr_string()).unwrap_err();
+        assert_eq!(PairingResponseError::of(&err), Some(&PairingResponseError::NonceMismatch));
+
+        let err = session.confirm_response(&session.init().to_qr_string()).unwrap_err();
+        assert_eq!(PairingResponseError::of(&err), Some(&PairingResponseError::OwnPayload));
+
+        let mut forged = payload(&bob, "Bob", [1; 32]);
+        forged.identity_pk = Ed25519Identity::generate().public_key_bytes();
+        let err = session.confirm_response(&forged.to_qr_string()).unwrap_err();
+        assert_eq!(PairingResponseError::of(&err), Some(&PairingResponseError::PeerIdMismatch));
+
+        // Under another scheme, ids derived with it verify and default ones don't.
+        struct ReversedHex;
+        impl PeerIdScheme for ReversedHex {
+            fn derive(&self, public_key: &[u8]) -> String {
+                Blake3HexPeerId.derive(public_key).chars().rev().collect()
+            }
+        }
+        let carol = Ed25519Identity::generate().with_peer_id_scheme(Arc::new(ReversedHex));
+        let session = session.with_peer_id_scheme(Arc::new(ReversedHex));
+        assert!(session.confirm_response(&payload(&carol, "Carol", [1; 32]).to_qr_string()).is_ok());
+        let err = session.confirm_response(&payload(&bob, "Bob", [1; 32]).to_qr_string()).unwrap_err();
+        assert_eq!(PairingResponseError::of(&err), Some(&PairingResponseError::PeerIdMismatch));
+    }
+
+    #[test]
+    fn verify_confirmation_code_accepts_only_the_matching_code() {
+        let (code, transcript) = derive_confirmation_code_with_transcript(&[42u8; 32], "peer-a", "peer-b");
+        assert!(verify_confirmation_code(&transcript, &code));
+        assert!(verify_confirmation_code(&transcript, &format!(" {code}\n")));
+
+        let wrong = format!("{:06}", (code.parse::<u32>().unwrap() + 1) % 1_000_000);
+        assert!(!verify_confirmation_code(&transcript, &wrong));
+        assert!(!verify_confirmation_code(&transcript, &code[..5]));
+        assert!(!verify_confirmation_code(&transcript, ""));
+
+        // The same code doesn't verify against a transcript with the peers swapped.
+        let swapped = ConfirmationTranscript::new(&[42u8; 32], "peer-b", "peer-a");
+        assert!(!verify_confirmation_code(&swapped, &code));
+    }
 }
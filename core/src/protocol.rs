@@ -10,6 +10,54 @@ pub const PROTOCOL_VERSION: u8 = 0;
 /// Prevents memory exhaustion when decoding untrusted frames.
 pub const MAX_PAYLOAD_LEN: usize = 4 * 1024 * 1024;
 
+/// Clipboard text longer than this (in bytes) is sent as `ClipTextBegin`/`Chunk`/`End`.
+///
+/// Kept well under `MAX_PAYLOAD_LEN` so a chunk still fits after JSON escaping.
+pub const CLIP_TEXT_CHUNK_SIZE: usize = 512 * 1024;
+
+/// Upper bound on the total size of a chunked clipboard text accepted from a peer.
+pub const MAX_CLIP_TEXT_LEN: u64 = 64 * 1024 * 1024;
+
+/// File bytes per `FileChunk` / `FileChunkRaw` sent by this crate's senders.
+pub const FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Largest clipboard image (raw bytes) sent as a single `ClipImage`: its base64
+/// grows by a third, and the message still has to fit in `MAX_PAYLOAD_LEN`.
+pub const MAX_CLIP_IMAGE_LEN: usize = MAX_PAYLOAD_LEN / 4 * 3 - 64 * 1024;
+
+/// Maximum number of chunked clipboard texts being reassembled at once per connection.
+const MAX_PARTIAL_CLIP_TEXTS: usize = 4;
+
+/// `Message::Hello::features` entry: the sender acknowledges applied clips
+/// with `Message::ClipAck`.
+pub const FEATURE_CLIP_ACK: &str = "clip-ack";
+
+/// `Message::Hello::features` entry: the sender accepts file chunks as
+/// `Message::FileChunkRaw`.
+pub const FEATURE_RAW_FILE_CHUNKS: &str = "raw-file-chunks";
+
+/// `Message::Hello::features` entry: the sender accepts large clip text as
+/// zstd-compressed `Message::ClipTextCompressed`.
+pub const FEATURE_ZSTD_CLIP_TEXT: &str = "zstd-clip-text";
+
+/// `Message::Hello::features` entry: right after the handshake the sender
+/// says whether it trusts the receiver, with `Message::TrustAck`.
+pub const FEATURE_TRUST_ACK: &str = "trust-ack";
+
+/// `Message::Hello::features` entry: once the handshake derives a session
+/// key, every later payload goes sealed in an `MsgType::Encrypted` frame.
+pub const FEATURE_ENCRYPTED_PAYLOADS: &str = "encrypted-payloads";
+
+/// Every `Message::Hello::features` entry this implementation knows, in the
+/// order a HELLO lists them.
+pub const FEATURES: [&str; 5] = [
+    FEATURE_CLIP_ACK,
+    FEATURE_RAW_FILE_CHUNKS,
+    FEATURE_ZSTD_CLIP_TEXT,
+    FEATURE_TRUST_ACK,
+    FEATURE_ENCRYPTED_PAYLOADS,
+];
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StreamId {
@@ -24,13 +72,28 @@ pub enum MsgType {
     Hello = 1,
     Ping = 2,
     Pong = 3,
+    DeviceInfo = 4,
+    DescribeRequest = 5,
+    Describe = 6,
+    TrustAck = 7,
+    Bye = 8,
+    Encrypted = 9,
     ClipText = 10,
     ClipImage = 11,
+    ClipTextBegin = 12,
+    ClipTextChunk = 13,
+    ClipTextEnd = 14,
+    ClipTextCompressed = 15,
     FileOffer = 20,
     FileAccept = 21,
     FileReject = 22,
     FileChunk = 23,
     FileDone = 24,
+    FileChunkRaw = 25,
+    FileResumeQuery = 26,
+    FileResumeOffset = 27,
+    TrustSync = 30,
+    ClipAck = 31,
 }
 
 impl MsgType {
@@ -39,16 +102,147 @@ impl MsgType {
             1 => Ok(Self::Hello),
             2 => Ok(Self::Ping),
             3 => Ok(Self::Pong),
+            4 => Ok(Self::DeviceInfo),
+            5 => Ok(Self::DescribeRequest),
+            6 => Ok(Self::Describe),
+            7 => Ok(Self::TrustAck),
+            8 => Ok(Self::Bye),
+            9 => Ok(Self::Encrypted),
             10 => Ok(Self::ClipText),
             11 => Ok(Self::ClipImage),
+            12 => Ok(Self::ClipTextBegin),
+            13 => Ok(Self::ClipTextChunk),
+            14 => Ok(Self::ClipTextEnd),
+            15 => Ok(Self::ClipTextCompressed),
             20 => Ok(Self::FileOffer),
             21 => Ok(Self::FileAccept),
             22 => Ok(Self::FileReject),
             23 => Ok(Self::FileChunk),
             24 => Ok(Self::FileDone),
+            25 => Ok(Self::FileChunkRaw),
+            26 => Ok(Self::FileResumeQuery),
+            27 => Ok(Self::FileResumeOffset),
+            30 => Ok(Self::TrustSync),
+            31 => Ok(Self::ClipAck),
             _ => anyhow::bail!("unknown MsgType: {v}"),
         }
     }
+
+    /// Every message type, in wire-id order.
+    pub const ALL: [MsgType; 25] = [
+        Self::Hello,
+        Self::Ping,
+        Self::Pong,
+        Self::DeviceInfo,
+        Self::DescribeRequest,
+        Self::Describe,
+        Self::TrustAck,
+        Self::Bye,
+        Self::Encrypted,
+        Self::ClipText,
+        Self::ClipImage,
+        Self::ClipTextBegin,
+        Self::ClipTextChunk,
+        Self::ClipTextEnd,
+        Self::ClipTextCompressed,
+        Self::FileOffer,
+        Self::FileAccept,
+        Self::FileReject,
+        Self::FileChunk,
+        Self::FileDone,
+        Self::FileChunkRaw,
+        Self::FileResumeQuery,
+        Self::FileResumeOffset,
+        Self::TrustSync,
+        Self::ClipAck,
+    ];
+
+    /// Whether a protocol v0 peer knows this type; it fails to decode the rest.
+    pub fn in_v0(self) -> bool {
+        matches!(
+            self,
+            Self::Hello
+                | Self::Ping
+                | Self::Pong
+                | Self::ClipText
+                | Self::ClipImage
+                | Self::FileOffer
+                | Self::FileAccept
+                | Self::FileReject
+                | Self::FileChunk
+                | Self::FileDone
+        )
+    }
+
+    /// The logical stream messages of this type are sent on.
+    pub fn stream_id(self) -> StreamId {
+        match self {
+            Self::Hello | Self::Ping | Self::Pong | Self::DeviceInfo | Self::DescribeRequest | Self::Describe
+            | Self::TrustAck | Self::Bye | Self::Encrypted | Self::TrustSync => StreamId::Control,
+            Self::ClipText | Self::ClipImage | Self::ClipTextBegin | Self::ClipTextChunk | Self::ClipTextEnd
+            | Self::ClipTextCompressed | Self::ClipAck => StreamId::Clipboard,
+            Self::FileOffer | Self::FileAccept | Self::FileReject | Self::FileChunk | Self::FileDone
+            | Self::FileChunkRaw | Self::FileResumeQuery | Self::FileResumeOffset => StreamId::File,
+        }
+    }
+}
+
+/// Machine-readable summary of the protocol a peer speaks, sent in
+/// `Message::Describe` so third-party clients can check framing and schema
+/// against a live peer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProtocolDescriptor {
+    pub version: u8,
+    /// Frame header fields in wire order, as `"<type> <name>"` (big-endian).
+    pub frame_header: Vec<String>,
+    /// How the signed HELLO transcript is laid out.
+    pub hello_transcript: String,
+    pub streams: Vec<StreamDescriptor>,
+    pub message_types: Vec<MessageTypeDescriptor>,
+    /// Payload codecs this peer can use (`Codec::name`).
+    pub codecs: Vec<String>,
+    /// The codec negotiated for this session.
+    pub codec: String,
+    /// Optional features negotiated for this session (e.g. [`FEATURE_CLIP_ACK`]).
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StreamDescriptor {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MessageTypeDescriptor {
+    pub id: u8,
+    /// The payload's `type` tag.
+    pub name: String,
+    pub stream_id: u32,
+}
+
+/// Describe this implementation's protocol for a session using `codec` and
+/// the negotiated `features`.
+pub fn protocol_descriptor(codec: Codec, features: &[String]) -> ProtocolDescriptor {
+    ProtocolDescriptor {
+        version: PROTOCOL_VERSION,
+        frame_header: ["u8 version", "u8 msg_type", "u32 stream_id", "u64 seq", "u32 len"]
+            .map(String::from)
+            .to_vec(),
+        hello_transcript: "b\"openclipboard-hello\" | u8 version | u32 len, peer_id | u32 len, identity_pk | u32 len, nonce \
+                           [| b\"psk\" | blake3::derive_key(\"openclipboard hello psk v0\", psk)] [| b\"x25519\" | ephemeral_pk] \
+                           [| b\"offers\" | u32 count, (u32 len, codec) sorted | u32 count, (u32 len, feature) sorted]"
+            .into(),
+        streams: [StreamId::Control, StreamId::Clipboard, StreamId::File]
+            .map(|s| StreamDescriptor { id: s as u32, name: format!("{s:?}").to_lowercase() })
+            .to_vec(),
+        message_types: MsgType::ALL
+            .map(|t| MessageTypeDescriptor { id: t as u8, name: format!("{t:?}"), stream_id: t.stream_id() as u32 })
+            .to_vec(),
+        codecs: Codec::ALL.map(|c| c.name().to_string()).to_vec(),
+        codec: codec.name().into(),
+        features: features.to_vec(),
+    }
 }
 
 /// Wire frame.
@@ -73,7 +267,12 @@ impl Frame {
     }
 }
 
-pub fn encode_frame(f: &Frame) -> Vec<u8> {
+/// Encode a frame. Fails if the payload exceeds `MAX_PAYLOAD_LEN`, which also
+/// keeps the length within the header's `u32` field.
+pub fn encode_frame(f: &Frame) -> anyhow::Result<Vec<u8>> {
+    if f.payload.len() > MAX_PAYLOAD_LEN {
+        anyhow::bail!("payload too large: {} > {MAX_PAYLOAD_LEN}", f.payload.len());
+    }
     let mut b = BytesMut::with_capacity(18 + f.payload.len());
     b.put_u8(f.version);
     b.put_u8(f.msg_type);
@@ -81,10 +280,16 @@ pub fn encode_frame(f: &Frame) -> Vec<u8> {
     b.put_u64(f.seq);
     b.put_u32(f.payload.len() as u32);
     b.extend_from_slice(&f.payload);
-    b.to_vec()
+    Ok(b.to_vec())
+}
+
+pub fn decode_frame(bytes: &[u8]) -> anyhow::Result<Frame> {
+    decode_frame_with_len(bytes).map(|(frame, _)| frame)
 }
 
-pub fn decode_frame(mut bytes: &[u8]) -> anyhow::Result<Frame> {
+/// Decode one frame from the start of `bytes`, returning it with the number of
+/// bytes consumed (`18 + payload len`). Trailing bytes are not inspected.
+pub fn decode_frame_with_len(mut bytes: &[u8]) -> anyhow::Result<(Frame, usize)> {
     if bytes.len() < 18 {
         anyhow::bail!("insufficient data");
     }
@@ -100,7 +305,7 @@ pub fn decode_frame(mut bytes: &[u8]) -> anyhow::Result<Frame> {
         anyhow::bail!("payload truncated");
     }
     let payload = bytes[..len].to_vec();
-    Ok(Frame { version, msg_type, stream_id, seq, payload })
+    Ok((Frame { version, msg_type, stream_id, seq, payload }, 18 + len))
 }
 
 /// Canonical transcript for `Message::Hello` authentication.
@@ -136,6 +341,147 @@ pub fn hello_transcript(
     out
 }
 
+/// [`hello_transcript`], followed when `psk` is set by b"psk" and
+/// `blake3::derive_key("openclipboard hello psk v0", psk)` (32 bytes), so the
+/// HELLO signature only verifies for a peer holding the same pre-shared key.
+/// Without a key this is exactly `hello_transcript`.
+pub fn hello_transcript_with_psk(
+    version: u8,
+    peer_id: &str,
+    identity_pk_bytes: &[u8],
+    nonce_bytes: &[u8],
+    psk: Option<&[u8]>,
+) -> Vec<u8> {
+    let mut out = hello_transcript(version, peer_id, identity_pk_bytes, nonce_bytes);
+    if let Some(psk) = psk {
+        out.extend_from_slice(b"psk");
+        out.extend_from_slice(&blake3::derive_key("openclipboard hello psk v0", psk));
+    }
+    out
+}
+
+/// [`hello_transcript_with_psk`], followed when `ephemeral_pk` is set by
+/// b"x25519" and the sender's 32-byte ephemeral X25519 key, so the key can't
+/// be swapped in transit. Without one this is exactly
+/// `hello_transcript_with_psk`.
+pub fn hello_transcript_with_ephemeral(
+    version: u8,
+    peer_id: &str,
+    identity_pk_bytes: &[u8],
+    nonce_bytes: &[u8],
+    psk: Option<&[u8]>,
+    ephemeral_pk: Option<&[u8]>,
+) -> Vec<u8> {
+    let mut out = hello_transcript_with_psk(version, peer_id, identity_pk_bytes, nonce_bytes, psk);
+    if let Some(pk) = ephemeral_pk {
+        out.extend_from_slice(b"x25519");
+        out.extend_from_slice(pk);
+    }
+    out
+}
+
+/// `transcript` (a [`hello_transcript_with_ephemeral`]) followed, when the
+/// HELLO lists any codecs or features, by b"offers", then the sorted codecs
+/// and the sorted features, each list as u32 count and per entry u32 len,
+/// bytes. Stripping a codec or feature (say, encryption) from a HELLO then
+/// breaks its signature instead of quietly downgrading the session. v0 peers
+/// list neither, so their transcript is `transcript` unchanged.
+pub fn hello_transcript_with_offers(mut transcript: Vec<u8>, codecs: &[String], features: &[String]) -> Vec<u8> {
+    if codecs.is_empty() && features.is_empty() {
+        return transcript;
+    }
+    transcript.extend_from_slice(b"offers");
+    for list in [codecs, features] {
+        let mut sorted: Vec<&String> = list.iter().collect();
+        sorted.sort();
+        transcript.extend_from_slice(&(sorted.len() as u32).to_be_bytes());
+        for item in sorted {
+            transcript.extend_from_slice(&(item.len() as u32).to_be_bytes());
+            transcript.extend_from_slice(item.as_bytes());
+        }
+    }
+    transcript
+}
+
+/// The session key both sides derive from their X25519 shared secret:
+/// HKDF-SHA256 over `shared`, salted with `lower pk | higher pk` and with info
+/// `"openclipboard session key v0" | lower pk | higher pk`. The two ephemeral
+/// public keys go in byte order so both sides agree.
+pub fn derive_session_key(shared: &[u8; 32], ephemeral_pk_a: &[u8; 32], ephemeral_pk_b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if ephemeral_pk_a <= ephemeral_pk_b { (ephemeral_pk_a, ephemeral_pk_b) } else { (ephemeral_pk_b, ephemeral_pk_a) };
+    let mut keys = Vec::with_capacity(64);
+    keys.extend_from_slice(lo);
+    keys.extend_from_slice(hi);
+    let mut info = b"openclipboard session key v0".to_vec();
+    info.extend_from_slice(&keys);
+
+    let mut key = [0u8; 32];
+    hkdf::Hkdf::<sha2::Sha256>::new(Some(&keys), shared)
+        .expand(&info, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+/// The ChaCha20-Poly1305 nonce for the `Encrypted` frame sent as `seq` in
+/// `direction`: `u8 direction | 3 zero bytes | u64 seq`, big-endian.
+pub fn encrypted_frame_nonce(direction: u8, seq: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[0] = direction;
+    nonce[4..].copy_from_slice(&seq.to_be_bytes());
+    nonce
+}
+
+/// Seals and opens `Encrypted` frame payloads under a session key. The two
+/// sides send in opposite directions (0 and 1), so they never use the same
+/// nonce under the shared key, and each frame's nonce is bound to its `seq`.
+pub struct PayloadCipher {
+    aead: chacha20poly1305::ChaCha20Poly1305,
+    send_direction: u8,
+}
+
+impl PayloadCipher {
+    pub fn new(key: &[u8; 32], send_direction: u8) -> Self {
+        use chacha20poly1305::aead::KeyInit;
+        Self { aead: chacha20poly1305::ChaCha20Poly1305::new(key.into()), send_direction }
+    }
+
+    /// The payload of the `Encrypted` frame sent as `seq` that carries a frame
+    /// of type `msg_type`: the ciphertext of `u8 msg_type | payload`.
+    pub fn seal(&self, seq: u64, msg_type: u8, payload: &[u8]) -> anyhow::Result<Vec<u8>> {
+        use chacha20poly1305::aead::Aead;
+        let nonce = encrypted_frame_nonce(self.send_direction, seq);
+        let plaintext = [&[msg_type], payload].concat();
+        self.aead.encrypt(&nonce.into(), plaintext.as_slice()).map_err(|_| anyhow::anyhow!("encrypting frame {seq} failed"))
+    }
+
+    /// Inverse of [`PayloadCipher::seal`] for a frame the peer sent as `seq`,
+    /// returning the inner type and payload. Fails if the payload or `seq`
+    /// was altered.
+    pub fn open(&self, seq: u64, sealed: &[u8]) -> anyhow::Result<(u8, Vec<u8>)> {
+        use chacha20poly1305::aead::Aead;
+        let nonce = encrypted_frame_nonce(1 - self.send_direction, seq);
+        let mut plaintext = self
+            .aead
+            .decrypt(&nonce.into(), sealed)
+            .map_err(|_| anyhow::anyhow!("encrypted frame {seq} failed authentication"))?;
+        anyhow::ensure!(!plaintext.is_empty(), "encrypted frame {seq} is empty");
+        let msg_type = plaintext.remove(0);
+        Ok((msg_type, plaintext))
+    }
+}
+
+/// What a `TrustAck` signs: b"openclipboard-trust-ack", then 1 if the sender
+/// trusts the receiver (else 0), then the receiver's HELLO nonce (u32 BE
+/// length, bytes), so an ack only verifies for the session it was sent on.
+pub fn trust_ack_transcript(trusted: bool, receiver_nonce: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(23 + 1 + 4 + receiver_nonce.len());
+    out.extend_from_slice(b"openclipboard-trust-ack");
+    out.push(trusted as u8);
+    out.extend_from_slice(&(receiver_nonce.len() as u32).to_be_bytes());
+    out.extend_from_slice(receiver_nonce);
+    out
+}
+
 /// Typed application messages.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type")]
@@ -151,16 +497,130 @@ pub enum Message {
         nonce_b64: String,
         /// Base64 encoded 64-byte Ed25519 signature over `hello_transcript(...)`.
         sig_b64: String,
+        /// Set when the signature covers a pre-shared key
+        /// (`hello_transcript_with_psk`). Omitted when false.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        psk: bool,
+        /// Payload codecs the sender can decode beyond JSON (`Codec::name`),
+        /// covered by the signature (`hello_transcript_with_offers`).
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        codecs: Vec<String>,
+        /// Optional protocol features the sender supports (e.g.
+        /// [`FEATURE_CLIP_ACK`]), covered by the signature; one is used only
+        /// once both HELLOs list it.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        features: Vec<String>,
+        /// Base64 encoded 32-byte ephemeral X25519 key, covered by the
+        /// signature (`hello_transcript_with_ephemeral`). Once both HELLOs
+        /// carry one, each side derives the same [`derive_session_key`].
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        x25519_pk_b64: Option<String>,
     },
     Ping { ts_ms: u64 },
     Pong { ts_ms: u64 },
-    ClipText { mime: String, text: String, ts_ms: u64 },
+    /// Sent once by each side right after the handshake. Informational only:
+    /// peers that never send it are simply shown without device details.
+    DeviceInfo { name: String, platform: String, app_version: String },
+    /// Ask the peer for its `Describe`.
+    DescribeRequest,
+    /// Answer to `DescribeRequest`: what the sender speaks on this session.
+    Describe { descriptor: ProtocolDescriptor },
+    /// Whether the sender trusts the receiver, signed over
+    /// [`trust_ack_transcript`]. Sent first thing after the handshake once
+    /// both sides negotiated [`FEATURE_TRUST_ACK`].
+    TrustAck { trusted: bool, sig_b64: String },
+    /// The sender is closing the session on purpose (e.g. it is stopping),
+    /// so the receiver can tell it apart from a dropped connection.
+    Bye { reason: String },
+    ClipText {
+        mime: String,
+        text: String,
+        ts_ms: u64,
+        /// Sender's device name for display ("from Alice's MacBook"). Optional so
+        /// peers that don't send it still interoperate.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        origin: Option<String>,
+    },
     ClipImage { mime: String, width: u32, height: u32, bytes_b64: String, ts_ms: u64 },
-    FileOffer { file_id: String, name: String, size: u64, mime: String },
+    /// Start of a clipboard text too large for a single frame; `total_len` is in bytes.
+    ClipTextBegin {
+        clip_id: String,
+        mime: String,
+        total_len: u64,
+        ts_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        origin: Option<String>,
+    },
+    /// A UTF-8 slice of a chunked clipboard text, starting at byte `offset`.
+    ClipTextChunk { clip_id: String, offset: u64, text: String },
+    ClipTextEnd { clip_id: String },
+    /// `ClipText` with the UTF-8 text zstd-compressed, for text over the
+    /// sender's threshold. Only sent once both sides negotiated
+    /// [`FEATURE_ZSTD_CLIP_TEXT`]; it must decompress to at most
+    /// [`MAX_PAYLOAD_LEN`] bytes.
+    ClipTextCompressed {
+        mime: String,
+        data_b64: String,
+        ts_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        origin: Option<String>,
+    },
+    FileOffer {
+        file_id: String,
+        name: String,
+        size: u64,
+        mime: String,
+        /// SHA-256 hex of the content, when the sender knows it up front.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
+    },
     FileAccept { file_id: String },
     FileReject { file_id: String, reason: String },
     FileChunk { file_id: String, offset: u64, data_b64: String },
     FileDone { file_id: String, hash: String },
+    /// `FileChunk` with the bytes as-is: its frame payload is
+    /// [`encode_file_chunk_raw`] whatever the codec. Only sent once both sides
+    /// negotiated [`FEATURE_RAW_FILE_CHUNKS`].
+    FileChunkRaw { file_id: String, offset: u64, data: Vec<u8> },
+    /// A sender reconnecting after a dropped transfer asks where to continue;
+    /// the receiver answers with `FileResumeOffset`.
+    FileResumeQuery { file_id: String },
+    /// How much of `file_id` the receiver already holds without a gap from the
+    /// start (0 if none); chunks should continue from `offset`.
+    FileResumeOffset { file_id: String, offset: u64 },
+    /// The sender's whole trust list and its revocations, exchanged only
+    /// between linked devices of one owner. Receivers merge it newest-wins
+    /// and ignore it from anyone else.
+    TrustSync {
+        records: Vec<TrustSyncRecord>,
+        /// Absent from senders that predate revocations.
+        #[serde(default)]
+        revoked: Vec<TrustSyncRevocation>,
+    },
+    /// The receiver applied the clip completed by the frame with `seq`
+    /// `ack_of_seq` (a `ClipText`, or a `ClipTextEnd`). Only sent once both
+    /// sides negotiated [`FEATURE_CLIP_ACK`]; never sent for a dropped clip.
+    ClipAck { ack_of_seq: u64 },
+}
+
+/// One trust record as carried by `Message::TrustSync`. The local sync
+/// direction is a per-device preference and is not shared.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrustSyncRecord {
+    pub peer_id: String,
+    /// Base64 encoded 32-byte Ed25519 verifying key.
+    pub identity_pk_b64: String,
+    pub display_name: String,
+    /// When the record was created (or last re-paired); the newest one wins.
+    pub created_at_ms: i64,
+}
+
+/// A peer removed from trust, as carried by `Message::TrustSync`; it removes
+/// records created before `revoked_at_ms`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrustSyncRevocation {
+    pub peer_id: String,
+    pub revoked_at_ms: i64,
 }
 
 impl Message {
@@ -169,35 +629,422 @@ impl Message {
             Self::Hello { .. } => MsgType::Hello,
             Self::Ping { .. } => MsgType::Ping,
             Self::Pong { .. } => MsgType::Pong,
+            Self::DeviceInfo { .. } => MsgType::DeviceInfo,
+            Self::DescribeRequest => MsgType::DescribeRequest,
+            Self::Describe { .. } => MsgType::Describe,
+            Self::TrustAck { .. } => MsgType::TrustAck,
+            Self::Bye { .. } => MsgType::Bye,
             Self::ClipText { .. } => MsgType::ClipText,
             Self::ClipImage { .. } => MsgType::ClipImage,
+            Self::ClipTextBegin { .. } => MsgType::ClipTextBegin,
+            Self::ClipTextChunk { .. } => MsgType::ClipTextChunk,
+            Self::ClipTextEnd { .. } => MsgType::ClipTextEnd,
+            Self::ClipTextCompressed { .. } => MsgType::ClipTextCompressed,
             Self::FileOffer { .. } => MsgType::FileOffer,
             Self::FileAccept { .. } => MsgType::FileAccept,
             Self::FileReject { .. } => MsgType::FileReject,
             Self::FileChunk { .. } => MsgType::FileChunk,
             Self::FileDone { .. } => MsgType::FileDone,
+            Self::FileChunkRaw { .. } => MsgType::FileChunkRaw,
+            Self::FileResumeQuery { .. } => MsgType::FileResumeQuery,
+            Self::FileResumeOffset { .. } => MsgType::FileResumeOffset,
+            Self::TrustSync { .. } => MsgType::TrustSync,
+            Self::ClipAck { .. } => MsgType::ClipAck,
         }
     }
 
     pub fn stream_id(&self) -> StreamId {
+        self.msg_type().stream_id()
+    }
+}
+
+/// Split `text` into `ClipTextBegin`/`ClipTextChunk`/`ClipTextEnd` messages.
+///
+/// Chunks are cut on UTF-8 character boundaries and are at most `chunk_size` bytes
+/// (or one character, if a single character is larger).
+pub fn chunk_clip_text(
+    clip_id: &str,
+    mime: &str,
+    text: &str,
+    ts_ms: u64,
+    origin: Option<&str>,
+    chunk_size: usize,
+) -> Vec<Message> {
+    let chunk_size = chunk_size.max(1);
+    let mut out = vec![Message::ClipTextBegin {
+        clip_id: clip_id.into(),
+        mime: mime.into(),
+        total_len: text.len() as u64,
+        ts_ms,
+        origin: origin.map(Into::into),
+    }];
+    let mut start = 0;
+    while start < text.len() {
+        let mut end = (start + chunk_size).min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == start {
+            end = start + text[start..].chars().next().map_or(1, char::len_utf8);
+        }
+        out.push(Message::ClipTextChunk {
+            clip_id: clip_id.into(),
+            offset: start as u64,
+            text: text[start..end].to_string(),
+        });
+        start = end;
+    }
+    out.push(Message::ClipTextEnd { clip_id: clip_id.into() });
+    out
+}
+
+struct PartialClipText {
+    mime: String,
+    total_len: u64,
+    ts_ms: u64,
+    origin: Option<String>,
+    text: String,
+}
+
+/// Reassembles chunked clipboard text into a single `Message::ClipText`.
+///
+/// Every other message passes straight through.
+#[derive(Default)]
+pub struct ClipTextAssembler {
+    partial: std::collections::HashMap<String, PartialClipText>,
+}
+
+impl ClipTextAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one received message. Returns `Ok(None)` while a chunked text is incomplete
+    /// and the reassembled `ClipText` once its `ClipTextEnd` arrives.
+    pub fn push(&mut self, msg: Message) -> anyhow::Result<Option<Message>> {
+        match msg {
+            Message::ClipTextBegin { clip_id, mime, total_len, ts_ms, origin } => {
+                if total_len > MAX_CLIP_TEXT_LEN {
+                    anyhow::bail!("clip text too large: {total_len} > {MAX_CLIP_TEXT_LEN}");
+                }
+                if self.partial.len() >= MAX_PARTIAL_CLIP_TEXTS && !self.partial.contains_key(&clip_id) {
+                    anyhow::bail!("too many clip texts in flight");
+                }
+                // `total_len` is the peer's claim: reserve at most one frame's worth up
+                // front and let the text grow as chunks actually arrive.
+                let text = String::with_capacity((total_len as usize).min(MAX_PAYLOAD_LEN));
+                self.partial.insert(clip_id, PartialClipText { mime, total_len, ts_ms, origin, text });
+                Ok(None)
+            }
+            Message::ClipTextChunk { clip_id, offset, text } => {
+                let Some(p) = self.partial.get_mut(&clip_id) else {
+                    anyhow::bail!("chunk for unknown clip {clip_id}");
+                };
+                if offset != p.text.len() as u64 || p.text.len() as u64 + text.len() as u64 > p.total_len {
+                    self.partial.remove(&clip_id);
+                    anyhow::bail!("out-of-order or oversized chunk for clip {clip_id}");
+                }
+                p.text.push_str(&text);
+                Ok(None)
+            }
+            Message::ClipTextEnd { clip_id } => {
+                let Some(p) = self.partial.remove(&clip_id) else {
+                    anyhow::bail!("end for unknown clip {clip_id}");
+                };
+                if p.text.len() as u64 != p.total_len {
+                    anyhow::bail!("clip {clip_id} incomplete: {} of {} bytes", p.text.len(), p.total_len);
+                }
+                Ok(Some(Message::ClipText { mime: p.mime, text: p.text, ts_ms: p.ts_ms, origin: p.origin }))
+            }
+            other => Ok(Some(other)),
+        }
+    }
+}
+
+/// Reassembles one incoming file from its `FileChunk`s, placing each by its
+/// `offset` so chunks that arrive out of order still land where they belong.
+///
+/// Memory grows only with the bytes actually received, never with the size
+/// the offer announced, so callers cap that size when accepting the offer.
+pub struct FileAssembler {
+    pub name: String,
+    expected: u64,
+    /// The bytes received without a gap from the start.
+    buf: Vec<u8>,
+    /// Chunks received past a gap, by offset, until the gap fills.
+    pending: std::collections::BTreeMap<u64, Vec<u8>>,
+    /// Received byte ranges, start -> end, merged and non-overlapping.
+    ranges: std::collections::BTreeMap<u64, u64>,
+    /// SHA-256 hex the offer advertised, if any.
+    hash: Option<String>,
+}
+
+impl FileAssembler {
+    /// Expect `expected` bytes, as announced in the `FileOffer`.
+    pub fn new(name: String, expected: u64) -> Self {
+        Self {
+            name,
+            expected,
+            buf: Vec::new(),
+            pending: std::collections::BTreeMap::new(),
+            ranges: std::collections::BTreeMap::new(),
+            hash: None,
+        }
+    }
+
+    /// Check the content against the SHA-256 hex the `FileOffer` advertised:
+    /// the chunk completing the file fails if it doesn't match, as does `finish`.
+    pub fn with_hash(mut self, hash: Option<String>) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    pub fn expected(&self) -> u64 {
+        self.expected
+    }
+
+    /// SHA-256 hex the offer advertised, if any.
+    pub fn hash(&self) -> Option<&str> {
+        self.hash.as_deref()
+    }
+
+    /// Bytes received so far.
+    pub fn received(&self) -> u64 {
+        self.ranges.iter().map(|(start, end)| end - start).sum()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.received() == self.expected
+    }
+
+    /// Bytes received without a gap from the start: where a resumed transfer
+    /// picks up (see `Message::FileResumeOffset`).
+    pub fn next_offset(&self) -> u64 {
+        self.ranges.get(&0).copied().unwrap_or(0)
+    }
+
+    /// Write the first `next_offset` bytes to `path`, for [`Self::with_partial`]
+    /// to continue from after the connection drops. The bytes go to a freshly
+    /// created sibling that is renamed over `path`, so a symlink planted at
+    /// `path` is replaced rather than written through.
+    pub fn save_partial(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        use std::io::Write;
+        let tmp = path.with_extension(format!("tmp-{:016x}", rand::random::<u64>()));
+        let written = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp)
+            .and_then(|mut out| out.write_all(&self.buf))
+            .and_then(|()| std::fs::rename(&tmp, path));
+        if let Err(e) = written {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Start from the bytes an earlier transfer saved at `path`; without one,
+    /// start empty. The hash check still covers the whole file. Anything but a
+    /// regular file at `path` is refused.
+    pub fn with_partial(mut self, path: &std::path::Path) -> anyhow::Result<Self> {
+        match std::fs::symlink_metadata(path) {
+            Ok(meta) if !meta.file_type().is_file() => anyhow::bail!("{} is not a regular file", path.display()),
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(self),
+            Err(e) => return Err(e.into()),
+        }
+        let data = std::fs::read(path)?;
+        self.insert(0, &data)?;
+        Ok(self)
+    }
+
+    /// Place `data` at `offset`. Fails for a chunk past the announced size or
+    /// one overlapping bytes already received.
+    pub fn insert(&mut self, offset: u64, data: &[u8]) -> anyhow::Result<()> {
+        let end = offset
+            .checked_add(data.len() as u64)
+            .filter(|end| *end <= self.expected)
+            .ok_or_else(|| anyhow::anyhow!("chunk at {offset} runs past the {} byte size of {}", self.expected, self.name))?;
+        if data.is_empty() {
+            return Ok(());
+        }
+        let before = self.ranges.range(..end).next_back().map(|(s, e)| (*s, *e));
+        if before.is_some_and(|(_, prev_end)| prev_end > offset) {
+            anyhow::bail!("chunk at {offset} of {} overlaps data already received", self.name);
+        }
+
+        if offset == self.buf.len() as u64 {
+            self.buf.extend_from_slice(data);
+            while let Some(chunk) = self.pending.remove(&(self.buf.len() as u64)) {
+                self.buf.extend_from_slice(&chunk);
+            }
+        } else {
+            self.pending.insert(offset, data.to_vec());
+        }
+
+        // Merge with the ranges it touches on either side.
+        let mut start = offset;
+        let mut stop = end;
+        if let Some((prev_start, prev_end)) = before
+            && prev_end == offset
+        {
+            self.ranges.remove(&prev_start);
+            start = prev_start;
+        }
+        if let Some(next_end) = self.ranges.remove(&end) {
+            stop = next_end;
+        }
+        self.ranges.insert(start, stop);
+        if self.is_complete() {
+            self.check_hash()?;
+        }
+        Ok(())
+    }
+
+    fn check_hash(&self) -> anyhow::Result<()> {
+        use sha2::{Digest, Sha256};
+        match &self.hash {
+            Some(hash) if *hash != format!("{:x}", Sha256::digest(&self.buf)) => {
+                anyhow::bail!("{} doesn't match the hash it was offered with", self.name)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The whole file, or an error naming how much is missing.
+    pub fn finish(self) -> anyhow::Result<Vec<u8>> {
+        let received = self.received();
+        if received != self.expected {
+            anyhow::bail!("{} incomplete: {received} of {} bytes", self.name, self.expected);
+        }
+        self.check_hash()?;
+        Ok(self.buf)
+    }
+}
+
+/// Returned (inside `anyhow::Error`) when the receiver answers a `FileOffer`
+/// with `FileReject`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRejected {
+    pub name: String,
+    /// The receiver's reason, e.g. [`crate::REJECT_TOO_LARGE`].
+    pub reason: String,
+}
+
+impl FileRejected {
+    /// The rejection inside `e`, if the receiver declining a file is what failed.
+    pub fn of(e: &anyhow::Error) -> Option<&Self> {
+        e.downcast_ref()
+    }
+}
+
+impl std::fmt::Display for FileRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} was rejected: {}", self.name, self.reason)
+    }
+}
+
+impl std::error::Error for FileRejected {}
+
+/// How a frame's `Message` payload is serialized.
+///
+/// JSON is the interoperable default and is always used for HELLO; a binary
+/// codec is only used after both HELLOs list it (see `Session::with_binary_codec`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Codec {
+    #[default]
+    Json,
+    /// MessagePack with named fields (self-describing, so the tagged `Message`
+    /// enum and its optional fields round-trip as with JSON).
+    MessagePack,
+}
+
+impl Codec {
+    /// Every codec this implementation speaks. JSON is always spoken, so a
+    /// HELLO lists only the others it offers.
+    pub const ALL: [Codec; 2] = [Codec::Json, Codec::MessagePack];
+
+    /// Name advertised in `Message::Hello::codecs`.
+    pub fn name(self) -> &'static str {
         match self {
-            Self::Hello { .. } | Self::Ping { .. } | Self::Pong { .. } => StreamId::Control,
-            Self::ClipText { .. } | Self::ClipImage { .. } => StreamId::Clipboard,
-            Self::FileOffer { .. } | Self::FileAccept { .. } | Self::FileReject { .. }
-            | Self::FileChunk { .. } | Self::FileDone { .. } => StreamId::File,
+            Self::Json => "json",
+            Self::MessagePack => "msgpack",
         }
     }
+
+    pub fn encode(self, msg: &Message) -> anyhow::Result<Vec<u8>> {
+        Ok(match self {
+            Self::Json => serde_json::to_vec(msg)?,
+            Self::MessagePack => rmp_serde::to_vec_named(msg)?,
+        })
+    }
+
+    pub fn decode(self, payload: &[u8]) -> anyhow::Result<Message> {
+        Ok(match self {
+            Self::Json => serde_json::from_slice(payload)?,
+            Self::MessagePack => rmp_serde::from_slice(payload)?,
+        })
+    }
+}
+
+/// The payload of a `FileChunkRaw` frame, big-endian:
+/// `u32 len, file_id | u64 offset | u32 len, data`. No codec is involved, so
+/// the chunk isn't base64-encoded and costs one allocation to build.
+pub fn encode_file_chunk_raw(file_id: &str, offset: u64, data: &[u8]) -> Vec<u8> {
+    let mut b = Vec::with_capacity(4 + file_id.len() + 8 + 4 + data.len());
+    b.put_u32(file_id.len() as u32);
+    b.extend_from_slice(file_id.as_bytes());
+    b.put_u64(offset);
+    b.put_u32(data.len() as u32);
+    b.extend_from_slice(data);
+    b
+}
+
+/// Inverse of [`encode_file_chunk_raw`]; fails on truncated or trailing bytes.
+pub fn decode_file_chunk_raw(mut payload: &[u8]) -> anyhow::Result<Message> {
+    if payload.len() < 4 {
+        anyhow::bail!("raw file chunk truncated");
+    }
+    let id_len = payload.get_u32() as usize;
+    if payload.len() < id_len + 12 {
+        anyhow::bail!("raw file chunk truncated");
+    }
+    let file_id = String::from_utf8(payload[..id_len].to_vec())?;
+    payload.advance(id_len);
+    let offset = payload.get_u64();
+    let len = payload.get_u32() as usize;
+    if payload.len() != len {
+        anyhow::bail!("raw file chunk says {len} bytes but carries {}", payload.len());
+    }
+    Ok(Message::FileChunkRaw { file_id, offset, data: payload.to_vec() })
+}
+
+/// A frame payload for `msg`: `FileChunkRaw` is always raw, everything else
+/// is serialized with `codec`.
+pub fn encode_payload(codec: Codec, msg: &Message) -> anyhow::Result<Vec<u8>> {
+    match msg {
+        Message::FileChunkRaw { file_id, offset, data } => Ok(encode_file_chunk_raw(file_id, *offset, data)),
+        _ => codec.encode(msg),
+    }
+}
+
+/// Decode the payload of a frame of type `msg_type`; see [`encode_payload`].
+pub fn decode_payload(codec: Codec, msg_type: u8, payload: &[u8]) -> anyhow::Result<Message> {
+    if msg_type == MsgType::FileChunkRaw as u8 {
+        return decode_file_chunk_raw(payload);
+    }
+    codec.decode(payload)
 }
 
 pub fn encode_message(msg: &Message, seq: u64) -> anyhow::Result<Vec<u8>> {
-    let payload = serde_json::to_vec(msg)?;
+    let payload = encode_payload(Codec::Json, msg)?;
     let frame = Frame::new(msg.msg_type(), msg.stream_id(), seq, payload);
-    Ok(encode_frame(&frame))
+    encode_frame(&frame)
 }
 
 pub fn decode_message(bytes: &[u8]) -> anyhow::Result<(Message, u64)> {
     let frame = decode_frame(bytes)?;
-    let msg: Message = serde_json::from_slice(&frame.payload)?;
+    let msg = decode_payload(Codec::Json, frame.msg_type, &frame.payload)?;
     Ok((msg, frame.seq))
 }
 
@@ -210,6 +1057,9 @@ mod tests {
         let (dec, seq) = decode_message(&enc).unwrap();
         assert_eq!(seq, 1);
         assert_eq!(dec, msg);
+
+        let bin = Codec::MessagePack.encode(&msg).unwrap();
+        assert_eq!(Codec::MessagePack.decode(&bin).unwrap(), msg);
     }
 
     #[test]
@@ -220,6 +1070,10 @@ mod tests {
             identity_pk_b64: "AQID".into(),
             nonce_b64: "BAUG".into(),
             sig_b64: "BwgJ".into(),
+            psk: false,
+            codecs: vec!["msgpack".into()],
+            features: vec![FEATURE_CLIP_ACK.into()],
+            x25519_pk_b64: Some("CgsM".into()),
         });
     }
     #[test]
@@ -227,11 +1081,39 @@ mod tests {
     #[test]
     fn roundtrip_pong() { roundtrip(Message::Pong { ts_ms: 456 }); }
     #[test]
-    fn roundtrip_clip_text() { roundtrip(Message::ClipText { mime: "text/plain".into(), text: "hello".into(), ts_ms: 1 }); }
+    fn roundtrip_device_info() {
+        roundtrip(Message::DeviceInfo { name: "Alice's MacBook".into(), platform: "macos".into(), app_version: "0.3.1".into() });
+    }
+    #[test]
+    fn roundtrip_bye() { roundtrip(Message::Bye { reason: "stopping".into() }); }
+    #[test]
+    fn roundtrip_clip_text() {
+        roundtrip(Message::ClipText { mime: "text/plain".into(), text: "hello".into(), ts_ms: 1, origin: None });
+        roundtrip(Message::ClipText { mime: "text/plain".into(), text: "hello".into(), ts_ms: 1, origin: Some("Alice's MacBook".into()) });
+    }
+    #[test]
+    fn clip_text_without_origin_field_still_decodes() {
+        let json = br#"{"type":"ClipText","mime":"text/plain","text":"hi","ts_ms":5}"#;
+        let msg: Message = serde_json::from_slice(json).unwrap();
+        assert_eq!(msg, Message::ClipText { mime: "text/plain".into(), text: "hi".into(), ts_ms: 5, origin: None });
+        // And an absent origin is not sent, so older peers see the same JSON.
+        assert_eq!(serde_json::to_vec(&msg).unwrap(), json.to_vec());
+    }
     #[test]
     fn roundtrip_clip_image() { roundtrip(Message::ClipImage { mime: "image/png".into(), width: 10, height: 10, bytes_b64: "AAAA".into(), ts_ms: 2 }); }
     #[test]
-    fn roundtrip_file_offer() { roundtrip(Message::FileOffer { file_id: "f1".into(), name: "a.txt".into(), size: 100, mime: "text/plain".into() }); }
+    fn roundtrip_clip_text_chunks() {
+        roundtrip(Message::ClipTextBegin { clip_id: "c1".into(), mime: "text/plain".into(), total_len: 5, ts_ms: 3, origin: None });
+        roundtrip(Message::ClipTextChunk { clip_id: "c1".into(), offset: 0, text: "hello".into() });
+        roundtrip(Message::ClipTextEnd { clip_id: "c1".into() });
+    }
+    #[test]
+    fn roundtrip_file_offer() { roundtrip(Message::FileOffer { file_id: "f1".into(), name: "a.txt".into(), size: 100, mime: "text/plain".into(), hash: None }); }
+
+    #[test]
+    fn roundtrip_file_offer_with_hash() {
+        roundtrip(Message::FileOffer { file_id: "f1".into(), name: "a.txt".into(), size: 100, mime: "text/plain".into(), hash: Some("ab".repeat(32)) });
+    }
     #[test]
     fn roundtrip_file_accept() { roundtrip(Message::FileAccept { file_id: "f1".into() }); }
     #[test]
@@ -239,16 +1121,145 @@ mod tests {
     #[test]
     fn roundtrip_file_chunk() { roundtrip(Message::FileChunk { file_id: "f1".into(), offset: 0, data_b64: "AQID".into() }); }
     #[test]
+    fn roundtrip_file_chunk_raw() { roundtrip(Message::FileChunkRaw { file_id: "f1".into(), offset: 7, data: vec![0, 1, 255] }); }
+
+    #[test]
+    fn raw_file_chunk_payload_is_the_bytes_themselves() {
+        let data = vec![0xAB; 1000];
+        let msg = Message::FileChunkRaw { file_id: "f1".into(), offset: 64, data: data.clone() };
+        for codec in [Codec::Json, Codec::MessagePack] {
+            let payload = encode_payload(codec, &msg).unwrap();
+            assert_eq!(payload.len(), 4 + 2 + 8 + 4 + data.len());
+            assert!(payload.ends_with(&data));
+            assert_eq!(decode_payload(codec, MsgType::FileChunkRaw as u8, &payload).unwrap(), msg);
+        }
+
+        let payload = encode_file_chunk_raw("f1", 0, &data);
+        assert!(decode_file_chunk_raw(&payload[..payload.len() - 1]).is_err());
+        assert!(decode_file_chunk_raw(&[payload.as_slice(), &[0]].concat()).is_err());
+        assert!(decode_file_chunk_raw(&payload[..10]).is_err());
+    }
+    #[test]
     fn roundtrip_file_done() { roundtrip(Message::FileDone { file_id: "f1".into(), hash: "abc123".into() }); }
 
+    #[test]
+    fn hello_offers_are_covered_in_sorted_order() {
+        let base = hello_transcript(PROTOCOL_VERSION, "peer", &[1; 32], &[2; 32]);
+        let offers = |codecs: &[&str], features: &[&str]| {
+            let list = |l: &[&str]| l.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            hello_transcript_with_offers(base.clone(), &list(codecs), &list(features))
+        };
+        assert_eq!(offers(&[], &[]), base);
+        let signed = offers(&["msgpack"], &[FEATURE_CLIP_ACK, FEATURE_ENCRYPTED_PAYLOADS]);
+        assert_eq!(signed, offers(&["msgpack"], &[FEATURE_ENCRYPTED_PAYLOADS, FEATURE_CLIP_ACK]));
+        assert_ne!(signed, offers(&["msgpack"], &[FEATURE_CLIP_ACK]));
+        assert_ne!(signed, offers(&[], &[FEATURE_CLIP_ACK, FEATURE_ENCRYPTED_PAYLOADS]));
+        // A name can't move between the lists.
+        assert_ne!(offers(&["a"], &[]), offers(&[], &["a"]));
+    }
+
+    #[test]
+    fn session_key_is_hkdf_sha256_salted_with_both_ephemeral_keys() {
+        let (shared, a, b) = ([7; 32], [1; 32], [2; 32]);
+        let key = derive_session_key(&shared, &a, &b);
+        assert_eq!(key, derive_session_key(&shared, &b, &a));
+
+        let salt = [a, b].concat();
+        let info = [b"openclipboard session key v0".as_slice(), &salt].concat();
+        let mut expected = [0u8; 32];
+        hkdf::Hkdf::<sha2::Sha256>::new(Some(&salt), &shared).expand(&info, &mut expected).unwrap();
+        assert_eq!(key, expected);
+
+        assert_ne!(key, derive_session_key(&shared, &a, &[3; 32]));
+        assert_ne!(key, derive_session_key(&[8; 32], &a, &b));
+    }
+
+    #[test]
+    fn roundtrip_file_resume_query() { roundtrip(Message::FileResumeQuery { file_id: "f1".into() }); }
+
+    #[test]
+    fn roundtrip_file_resume_offset() { roundtrip(Message::FileResumeOffset { file_id: "f1".into(), offset: 65536 }); }
+    #[test]
+    fn roundtrip_trust_sync() {
+        let record = TrustSyncRecord {
+            peer_id: "p1".into(),
+            identity_pk_b64: "AAAA".into(),
+            display_name: "Phone".into(),
+            created_at_ms: 1_700_000_000_000,
+        };
+        let revoked = TrustSyncRevocation { peer_id: "p2".into(), revoked_at_ms: 1_700_000_000_001 };
+        roundtrip(Message::TrustSync { records: vec![record], revoked: vec![revoked] });
+        roundtrip(Message::TrustSync { records: vec![], revoked: vec![] });
+        // From a sender that predates revocations.
+        let old = br#"{"type":"TrustSync","records":[]}"#;
+        assert_eq!(serde_json::from_slice::<Message>(old).unwrap(), Message::TrustSync { records: vec![], revoked: vec![] });
+    }
+    #[test]
+    fn roundtrip_clip_ack() { roundtrip(Message::ClipAck { ack_of_seq: 7 }); }
+    #[test]
+    fn roundtrip_describe() {
+        roundtrip(Message::DescribeRequest);
+        roundtrip(Message::Describe { descriptor: protocol_descriptor(Codec::MessagePack, &[FEATURE_CLIP_ACK.into()]) });
+    }
+    #[test]
+    fn payload_cipher_opens_only_the_other_directions_frames() {
+        let key = [7u8; 32];
+        let (a, b) = (PayloadCipher::new(&key, 0), PayloadCipher::new(&key, 1));
+        let sealed = a.seal(5, MsgType::ClipText as u8, b"payload").unwrap();
+        assert_eq!(b.open(5, &sealed).unwrap(), (MsgType::ClipText as u8, b"payload".to_vec()));
+        // A frame reflected back to its sender doesn't open.
+        assert!(a.open(5, &sealed).is_err());
+        assert_ne!(encrypted_frame_nonce(0, 5), encrypted_frame_nonce(1, 5));
+        assert!(PayloadCipher::new(&[8u8; 32], 1).open(5, &sealed).is_err());
+    }
+    #[test]
+    fn all_msg_types_are_listed_once_with_their_wire_ids() {
+        for t in MsgType::ALL {
+            assert_eq!(MsgType::from_u8(t as u8).unwrap(), t);
+        }
+        let ids: std::collections::HashSet<u8> = MsgType::ALL.iter().map(|t| *t as u8).collect();
+        assert_eq!(ids.len(), MsgType::ALL.len());
+        assert_eq!((0..=u8::MAX).filter(|v| MsgType::from_u8(*v).is_ok()).count(), MsgType::ALL.len());
+    }
+
     #[test]
     fn frame_roundtrip() {
         let f = Frame::new(MsgType::Ping, StreamId::Control, 42, b"hi".to_vec());
-        let enc = encode_frame(&f);
+        let enc = encode_frame(&f).unwrap();
         let dec = decode_frame(&enc).unwrap();
         assert_eq!(dec, f);
     }
 
+    #[test]
+    fn decode_frame_with_len_reports_consumed_bytes() {
+        let f = Frame::new(MsgType::Ping, StreamId::Control, 7, b"hello".to_vec());
+        let mut buf = encode_frame(&f).unwrap();
+        buf.extend_from_slice(b"trailing");
+        let (dec, used) = decode_frame_with_len(&buf).unwrap();
+        assert_eq!(dec, f);
+        assert_eq!(used, 18 + f.payload.len());
+        assert_eq!(&buf[used..], b"trailing");
+
+        // Back-to-back frames parse one after another.
+        let g = Frame::new(MsgType::Pong, StreamId::Control, 8, Vec::new());
+        let mut two = encode_frame(&f).unwrap();
+        two.extend(encode_frame(&g).unwrap());
+        let (first, n) = decode_frame_with_len(&two).unwrap();
+        let (second, m) = decode_frame_with_len(&two[n..]).unwrap();
+        assert_eq!((first, second), (f, g));
+        assert_eq!(n + m, two.len());
+    }
+
+    #[test]
+    fn encode_rejects_oversized_payload() {
+        let f = Frame::new(MsgType::FileChunk, StreamId::File, 1, vec![0; MAX_PAYLOAD_LEN + 1]);
+        let err = encode_frame(&f).unwrap_err();
+        assert!(err.to_string().contains("payload too large"));
+
+        let f = Frame::new(MsgType::FileChunk, StreamId::File, 1, vec![0; MAX_PAYLOAD_LEN]);
+        assert_eq!(encode_frame(&f).unwrap().len(), 18 + MAX_PAYLOAD_LEN);
+    }
+
     #[test]
     fn reject_oversized_payload_len() {
         // Only provide the header; decoder should reject based on length before reading payload.
@@ -261,4 +1272,119 @@ mod tests {
         let err = decode_frame(&b).unwrap_err();
         assert!(err.to_string().contains("payload too large"));
     }
+
+    #[test]
+    fn chunked_clip_text_reassembles_on_char_boundaries() {
+        let text = "héllo wörld ✂️ ".repeat(50);
+        let msgs = chunk_clip_text("c1", "text/plain", &text, 7, Some("laptop"), 16);
+        assert!(msgs.len() > 3);
+
+        let mut asm = ClipTextAssembler::new();
+        let mut out = None;
+        for m in msgs {
+            if let Some(done) = asm.push(m).unwrap() {
+                out = Some(done);
+            }
+        }
+        assert_eq!(out, Some(Message::ClipText { mime: "text/plain".into(), text, ts_ms: 7, origin: Some("laptop".into()) }));
+    }
+
+    #[test]
+    fn clip_text_assembler_rejects_out_of_order_chunk() {
+        let mut asm = ClipTextAssembler::new();
+        asm.push(Message::ClipTextBegin { clip_id: "c1".into(), mime: "text/plain".into(), total_len: 4, ts_ms: 0, origin: None }).unwrap();
+        assert!(asm.push(Message::ClipTextChunk { clip_id: "c1".into(), offset: 2, text: "ab".into() }).is_err());
+        assert!(asm.push(Message::ClipTextEnd { clip_id: "c1".into() }).is_err());
+        let ping = Message::Ping { ts_ms: 1 };
+        assert_eq!(asm.push(ping.clone()).unwrap(), Some(ping));
+    }
+
+    #[test]
+    fn file_assembler_places_out_of_order_chunks_by_offset() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        let mut chunks: Vec<(u64, &[u8])> = data.chunks(64).enumerate().map(|(i, c)| (i as u64 * 64, c)).collect();
+        // Deterministic shuffle: odd chunks first, then the even ones backwards.
+        let (odd, even): (Vec<_>, Vec<_>) = chunks.drain(..).enumerate().partition(|(i, _)| i % 2 == 1);
+        let order: Vec<_> = odd.into_iter().chain(even.into_iter().rev()).map(|(_, c)| c).collect();
+
+        let mut asm = FileAssembler::new("f.bin".into(), data.len() as u64);
+        let (last, rest) = order.split_last().unwrap();
+        for (offset, chunk) in rest {
+            asm.insert(*offset, chunk).unwrap();
+        }
+        assert!(!asm.is_complete());
+        assert_eq!(asm.received(), (data.len() - last.1.len()) as u64);
+        asm.insert(last.0, last.1).unwrap();
+        assert!(asm.is_complete());
+        assert_eq!(asm.finish().unwrap(), data);
+    }
+
+    #[test]
+    fn file_assembler_rejects_overlaps_overruns_and_gaps() {
+        let mut asm = FileAssembler::new("f.bin".into(), 8);
+        asm.insert(4, b"efgh").unwrap();
+        assert!(asm.insert(2, b"cd").is_ok());
+        assert!(asm.insert(3, b"x").is_err(), "overlap accepted");
+        assert!(asm.insert(6, b"ghi").is_err(), "overrun accepted");
+        assert!(asm.insert(u64::MAX, b"z").is_err());
+        let err = asm.finish().unwrap_err().to_string();
+        assert_eq!(err, "f.bin incomplete: 6 of 8 bytes");
+    }
+
+    #[test]
+    fn file_assembler_holds_only_the_bytes_it_received() {
+        // A chunk far into a huge announced file mustn't allocate up to it.
+        let mut asm = FileAssembler::new("f.bin".into(), u64::MAX);
+        asm.insert(u64::MAX - 4, b"wxyz").unwrap();
+        asm.insert(0, b"ab").unwrap();
+        assert_eq!((asm.received(), asm.next_offset()), (6, 2));
+        assert!(asm.buf.capacity() < 1024);
+    }
+
+    #[test]
+    fn file_assembler_checks_the_offered_hash_once_complete() {
+        use sha2::{Digest, Sha256};
+        let hash = Some(format!("{:x}", Sha256::digest(b"abcdefgh")));
+
+        let mut asm = FileAssembler::new("f.bin".into(), 8).with_hash(hash.clone());
+        asm.insert(4, b"efgh").unwrap();
+        let err = asm.insert(0, b"abcX").unwrap_err().to_string();
+        assert_eq!(err, "f.bin doesn't match the hash it was offered with");
+        assert!(asm.finish().is_err());
+
+        let mut asm = FileAssembler::new("f.bin".into(), 8).with_hash(hash);
+        asm.insert(4, b"efgh").unwrap();
+        asm.insert(0, b"abcd").unwrap();
+        assert_eq!(asm.finish().unwrap(), b"abcdefgh");
+    }
+
+    #[test]
+    fn file_assembler_saves_its_gap_free_start_and_resumes_from_it() {
+        use sha2::{Digest, Sha256};
+        let dir = std::env::temp_dir().join(format!("oc-partial-{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("f1.part");
+        let hash = Some(format!("{:x}", Sha256::digest(b"abcdefgh")));
+
+        let mut asm = FileAssembler::new("f.bin".into(), 8).with_hash(hash.clone());
+        asm.insert(0, b"abc").unwrap();
+        asm.insert(6, b"gh").unwrap();
+        assert_eq!(asm.next_offset(), 3);
+        asm.save_partial(&path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"abc");
+
+        let mut resumed = FileAssembler::new("f.bin".into(), 8).with_hash(hash.clone()).with_partial(&path).unwrap();
+        assert_eq!(resumed.next_offset(), 3);
+        resumed.insert(3, b"defgh").unwrap();
+        assert_eq!(resumed.finish().unwrap(), b"abcdefgh");
+
+        // A bad saved prefix still fails the whole-file hash.
+        std::fs::write(&path, b"abX").unwrap();
+        let mut resumed = FileAssembler::new("f.bin".into(), 8).with_hash(hash).with_partial(&path).unwrap();
+        assert!(resumed.insert(3, b"defgh").is_err());
+
+        let fresh = FileAssembler::new("f.bin".into(), 8).with_partial(&dir.join("none.part")).unwrap();
+        assert_eq!(fresh.next_offset(), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
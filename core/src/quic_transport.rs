@@ -1,6 +1,6 @@
 //! QUIC transport implementation using quinn.
 
-use crate::protocol::{decode_frame, encode_frame, Frame};
+use crate::protocol::{decode_frame, encode_frame, Frame, PROTOCOL_VERSION};
 use crate::transport::{Connection, Listener, Transport};
 use anyhow::Result;
 use async_trait::async_trait;
@@ -8,9 +8,32 @@ use quinn::{Endpoint, RecvStream, SendStream};
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::io::AsyncWriteExt;
 
+/// How long an accepted connection may take to open its stream by default.
+pub const DEFAULT_STREAM_OPEN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long an incoming QUIC/TLS handshake may take by default.
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Incoming connections a listener handshakes at once; more are refused.
+const MAX_PENDING_ACCEPTS: usize = 64;
+
+/// ALPN identifier for a protocol version: `oclip/<version>`.
+///
+/// Both ends require it, so non-openclipboard QUIC peers (or peers speaking another
+/// protocol version) are rejected during the TLS handshake.
+pub fn alpn_for_version(version: u8) -> Vec<u8> {
+    format!("oclip/{version}").into_bytes()
+}
+
+/// ALPN for the current `PROTOCOL_VERSION` (`oclip/0`).
+pub fn default_alpn() -> Vec<u8> {
+    alpn_for_version(PROTOCOL_VERSION)
+}
+
 /// rustls verifier that accepts any server certificate.
 ///
 /// This is acceptable for the prototype because OpenClipboard authenticates peers at the
@@ -63,6 +86,7 @@ pub struct QuicConnection {
     send: Arc<Mutex<SendStream>>,
     recv: Arc<Mutex<RecvStream>>,
     closed: Arc<AtomicBool>,
+    remote_addr: Option<SocketAddr>,
 }
 
 impl QuicConnection {
@@ -71,8 +95,15 @@ impl QuicConnection {
             send: Arc::new(Mutex::new(send)),
             recv: Arc::new(Mutex::new(recv)),
             closed: Arc::new(AtomicBool::new(false)),
+            remote_addr: None,
         }
     }
+
+    /// Attach the remote socket address this connection was established with.
+    pub fn with_remote_addr(mut self, addr: SocketAddr) -> Self {
+        self.remote_addr = Some(addr);
+        self
+    }
 }
 
 #[async_trait]
@@ -81,7 +112,7 @@ impl Connection for QuicConnection {
         if self.is_closed() {
             anyhow::bail!("connection closed");
         }
-        let bytes = encode_frame(&frame);
+        let bytes = encode_frame(&frame)?;
         let len = (bytes.len() as u32).to_be_bytes();
         let mut send = self.send.lock().await;
         send.write_all(&len).await?;
@@ -108,21 +139,182 @@ impl Connection for QuicConnection {
     fn is_closed(&self) -> bool {
         self.closed.load(Ordering::SeqCst)
     }
+
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+}
+
+/// Why `QuicListener::accept` failed.
+///
+/// Carried inside the returned `anyhow::Error`; recover it with [`AcceptError::of`].
+/// Only `Closed` is final: after any other error the listener keeps accepting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AcceptError {
+    /// The endpoint was closed; no further connections will arrive.
+    Closed,
+    /// The QUIC/TLS handshake failed (e.g. the client offered the wrong ALPN).
+    Handshake(String),
+    /// The QUIC/TLS handshake didn't finish within the timeout.
+    HandshakeTimeout,
+    /// The client connected but didn't open a stream within the timeout.
+    StreamTimeout,
+    /// The connection was lost while waiting for its stream.
+    Stream(String),
+}
+
+impl AcceptError {
+    /// The accept error inside `e`, if it came from `QuicListener::accept`.
+    pub fn of(e: &anyhow::Error) -> Option<&Self> {
+        e.downcast_ref()
+    }
+}
+
+impl std::fmt::Display for AcceptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Closed => write!(f, "listener closed"),
+            Self::Handshake(e) => write!(f, "incoming QUIC handshake failed: {e}"),
+            Self::HandshakeTimeout => write!(f, "incoming QUIC handshake timed out"),
+            Self::StreamTimeout => write!(f, "client opened no stream in time"),
+            Self::Stream(e) => write!(f, "incoming stream failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AcceptError {}
+
+/// Why a listener couldn't be set up.
+///
+/// Carried inside the `anyhow::Error` from `SyncService::start`; recover it
+/// with [`ListenError::of`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenError {
+    /// The fixed listen port is taken, e.g. by another instance.
+    AddressInUse { port: u16 },
+    /// Creating the endpoint at `addr` failed for any other reason.
+    BindFailed { addr: SocketAddr, reason: String },
+}
+
+impl ListenError {
+    /// The listen error inside `e`, if binding a listener is what failed.
+    pub fn of(e: &anyhow::Error) -> Option<&Self> {
+        e.downcast_ref()
+    }
 }
 
+impl std::fmt::Display for ListenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AddressInUse { port } => write!(f, "listen port {port} is already in use"),
+            Self::BindFailed { addr, reason } => write!(f, "bind listener {addr}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ListenError {}
+
+/// Creates the server endpoint a listener accepts on: [`make_server_endpoint`]
+/// unless replaced, e.g. by one that fails to test bind errors without
+/// fighting over real ports.
+pub type ServerEndpointFactory =
+    Arc<dyn Fn(SocketAddr) -> Result<(Endpoint, rustls::pki_types::CertificateDer<'static>)> + Send + Sync>;
+
 /// QUIC listener that accepts incoming connections.
+///
+/// Each incoming connection is handshaked on its own task, so a slow or idle
+/// client never holds up the ones behind it; `accept` yields them as they finish.
 pub struct QuicListener {
     endpoint: Endpoint,
+    handshake_timeout: Duration,
+    stream_open_timeout: Duration,
+    /// Finished handshakes, once the first `accept` started the accept task.
+    accepted: Mutex<Option<tokio::sync::mpsc::Receiver<Result<QuicConnection>>>>,
+    accept_task: std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
 }
 
 impl QuicListener {
     pub fn new(endpoint: Endpoint) -> Self {
-        Self { endpoint }
+        Self {
+            endpoint,
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+            stream_open_timeout: DEFAULT_STREAM_OPEN_TIMEOUT,
+            accepted: Mutex::new(None),
+            accept_task: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Drop connections whose QUIC/TLS handshake hasn't finished after `timeout`.
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    /// Drop connections that haven't opened their stream after `timeout`, so an idle
+    /// client can't hold a pending slot.
+    pub fn with_stream_open_timeout(mut self, timeout: Duration) -> Self {
+        self.stream_open_timeout = timeout;
+        self
     }
 
     pub fn local_addr(&self) -> Result<SocketAddr> {
         Ok(self.endpoint.local_addr()?)
     }
+
+    /// Spawn the task that takes incoming connections off the endpoint and
+    /// handshakes each on its own task, refusing any past `MAX_PENDING_ACCEPTS`.
+    fn start_accepting(&self) -> tokio::sync::mpsc::Receiver<Result<QuicConnection>> {
+        let (tx, rx) = tokio::sync::mpsc::channel(MAX_PENDING_ACCEPTS);
+        let endpoint = self.endpoint.clone();
+        let (handshake_timeout, stream_open_timeout) = (self.handshake_timeout, self.stream_open_timeout);
+        let pending = Arc::new(tokio::sync::Semaphore::new(MAX_PENDING_ACCEPTS));
+        let task = tokio::spawn(async move {
+            while let Some(incoming) = endpoint.accept().await {
+                let Ok(slot) = Arc::clone(&pending).try_acquire_owned() else {
+                    incoming.refuse();
+                    continue;
+                };
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let accepted = accept_incoming(incoming, handshake_timeout, stream_open_timeout).await;
+                    let _ = tx.send(accepted).await;
+                    drop(slot);
+                });
+            }
+        });
+        *self.accept_task.lock().unwrap() = Some(task);
+        rx
+    }
+}
+
+/// Finish the handshake of `incoming` and wait for its stream, each within its timeout.
+async fn accept_incoming(
+    incoming: quinn::Incoming,
+    handshake_timeout: Duration,
+    stream_open_timeout: Duration,
+) -> Result<QuicConnection> {
+    let conn = match tokio::time::timeout(handshake_timeout, incoming).await {
+        Ok(Ok(conn)) => conn,
+        Ok(Err(e)) => return Err(AcceptError::Handshake(e.to_string()).into()),
+        Err(_) => return Err(AcceptError::HandshakeTimeout.into()),
+    };
+    let (send, recv) = match tokio::time::timeout(stream_open_timeout, conn.accept_bi()).await {
+        Ok(Ok(streams)) => streams,
+        Ok(Err(e)) => return Err(AcceptError::Stream(e.to_string()).into()),
+        Err(_) => {
+            conn.close(0u32.into(), b"stream open timeout");
+            return Err(AcceptError::StreamTimeout.into());
+        }
+    };
+    Ok(QuicConnection::new(send, recv).with_remote_addr(conn.remote_address()))
+}
+
+impl Drop for QuicListener {
+    fn drop(&mut self) {
+        if let Some(task) = self.accept_task.lock().unwrap().take() {
+            task.abort();
+        }
+    }
 }
 
 #[async_trait]
@@ -130,11 +322,9 @@ impl Listener for QuicListener {
     type Conn = QuicConnection;
 
     async fn accept(&self) -> Result<QuicConnection> {
-        let incoming = self.endpoint.accept().await
-            .ok_or_else(|| anyhow::anyhow!("listener closed"))?;
-        let conn = incoming.await?;
-        let (send, recv) = conn.accept_bi().await?;
-        Ok(QuicConnection::new(send, recv))
+        let mut accepted = self.accepted.lock().await;
+        let accepted = accepted.get_or_insert_with(|| self.start_accepting());
+        accepted.recv().await.unwrap_or_else(|| Err(AcceptError::Closed.into()))
     }
 }
 
@@ -157,7 +347,7 @@ impl Transport for QuicTransport {
         let socket_addr: SocketAddr = addr.parse()?;
         let conn = self.endpoint.connect(socket_addr, "localhost")?.await?;
         let (send, recv) = conn.open_bi().await?;
-        Ok(QuicConnection::new(send, recv))
+        Ok(QuicConnection::new(send, recv).with_remote_addr(conn.remote_address()))
     }
 }
 
@@ -176,10 +366,19 @@ pub fn self_signed_cert() -> Result<(rustls::pki_types::CertificateDer<'static>,
 
 /// Create a server endpoint bound to the given address with self-signed certs.
 pub fn make_server_endpoint(bind_addr: SocketAddr) -> Result<(Endpoint, rustls::pki_types::CertificateDer<'static>)> {
+    make_server_endpoint_with_alpn(bind_addr, vec![default_alpn()])
+}
+
+/// Like [`make_server_endpoint`], accepting only clients that offer one of `alpn`.
+pub fn make_server_endpoint_with_alpn(
+    bind_addr: SocketAddr,
+    alpn: Vec<Vec<u8>>,
+) -> Result<(Endpoint, rustls::pki_types::CertificateDer<'static>)> {
     let (cert, key) = self_signed_cert()?;
-    let server_crypto = rustls::ServerConfig::builder()
+    let mut server_crypto = rustls::ServerConfig::builder()
         .with_no_client_auth()
         .with_single_cert(vec![cert.clone()], key)?;
+    server_crypto.alpn_protocols = alpn;
     let server_config = quinn::ServerConfig::with_crypto(Arc::new(
         quinn::crypto::rustls::QuicServerConfig::try_from(server_crypto)?,
     ));
@@ -189,37 +388,58 @@ pub fn make_server_endpoint(bind_addr: SocketAddr) -> Result<(Endpoint, rustls::
 
 /// Create a client endpoint that trusts the given server certificate.
 pub fn make_client_endpoint(server_cert: rustls::pki_types::CertificateDer<'static>) -> Result<Endpoint> {
+    make_client_endpoint_with_alpn(server_cert, vec![default_alpn()])
+}
+
+/// Like [`make_client_endpoint`], offering `alpn` during the handshake.
+pub fn make_client_endpoint_with_alpn(
+    server_cert: rustls::pki_types::CertificateDer<'static>,
+    alpn: Vec<Vec<u8>>,
+) -> Result<Endpoint> {
     let mut roots = rustls::RootCertStore::empty();
     roots.add(server_cert)?;
-    let client_crypto = rustls::ClientConfig::builder()
+    let mut client_crypto = rustls::ClientConfig::builder()
         .with_root_certificates(roots)
         .with_no_client_auth();
+    client_crypto.alpn_protocols = alpn;
     let client_config = quinn::ClientConfig::new(Arc::new(
         quinn::crypto::rustls::QuicClientConfig::try_from(client_crypto)?,
     ));
-    let mut endpoint = Endpoint::client("0.0.0.0:0".parse()?)?;
+    let mut endpoint = client_endpoint()?;
     endpoint.set_default_client_config(client_config);
     Ok(endpoint)
 }
 
+/// An unbound client endpoint that can dial both IPv4 and IPv6 peers: a
+/// dual-stack `[::]:0` socket, or IPv4 only where IPv6 isn't available.
+fn client_endpoint() -> Result<Endpoint> {
+    match Endpoint::client((std::net::Ipv6Addr::UNSPECIFIED, 0).into()) {
+        Ok(endpoint) => Ok(endpoint),
+        Err(_) => Ok(Endpoint::client((std::net::Ipv4Addr::UNSPECIFIED, 0).into())?),
+    }
+}
+
 /// Create a client endpoint that does **not** validate the server certificate.
 ///
 /// Use this for the LAN prototype where we rely on the application-layer session handshake.
 pub fn make_insecure_client_endpoint() -> Result<Endpoint> {
+    make_insecure_client_endpoint_with_alpn(vec![default_alpn()])
+}
+
+/// Like [`make_insecure_client_endpoint`], offering `alpn` during the handshake.
+pub fn make_insecure_client_endpoint_with_alpn(alpn: Vec<Vec<u8>>) -> Result<Endpoint> {
     let _ = rustls::crypto::ring::default_provider().install_default();
 
     let mut client_crypto = rustls::ClientConfig::builder()
         .dangerous()
         .with_custom_certificate_verifier(Arc::new(AcceptAnyServerCert))
         .with_no_client_auth();
-
-    // Keep ALPN empty; Quinn will set QUIC defaults.
-    client_crypto.alpn_protocols.clear();
+    client_crypto.alpn_protocols = alpn;
 
     let client_config = quinn::ClientConfig::new(Arc::new(
         quinn::crypto::rustls::QuicClientConfig::try_from(client_crypto)?,
     ));
-    let mut endpoint = Endpoint::client("0.0.0.0:0".parse()?)?;
+    let mut endpoint = client_endpoint()?;
     endpoint.set_default_client_config(client_config);
     Ok(endpoint)
 }
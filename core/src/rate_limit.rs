@@ -0,0 +1,237 @@
+//! Token-bucket pacing for file transfers, so a large file doesn't saturate a
+//! metered or shared link, and a cap on how many run at once.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Paces bytes to at most a given rate, allowing a burst of one second's
+/// worth. Unlimited (`None`) until a rate is set; the rate can change while
+/// transfers are using it.
+#[derive(Debug)]
+pub struct RateLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    bytes_per_sec: Option<u64>,
+    /// Bytes that may go out now; negative once reservations run ahead of the rate.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(bytes_per_sec: Option<u64>) -> Self {
+        let limiter = Self {
+            bucket: Mutex::new(Bucket { bytes_per_sec: None, tokens: 0.0, refilled_at: Instant::now() }),
+        };
+        limiter.set_rate(bytes_per_sec);
+        limiter
+    }
+
+    /// Limit to `bytes_per_sec` (`None` or 0: unlimited), starting from a full
+    /// burst.
+    pub fn set_rate(&self, bytes_per_sec: Option<u64>) {
+        let bytes_per_sec = bytes_per_sec.filter(|&r| r > 0);
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.bytes_per_sec = bytes_per_sec;
+        bucket.tokens = bytes_per_sec.unwrap_or(0) as f64;
+        bucket.refilled_at = Instant::now();
+    }
+
+    pub fn rate(&self) -> Option<u64> {
+        self.bucket.lock().unwrap().bytes_per_sec
+    }
+
+    /// Take `bytes` from the bucket, returning how long to wait before sending
+    /// them: zero if the burst covers them, otherwise until the rate has.
+    pub fn reserve(&self, bytes: u64) -> Duration {
+        self.reserve_at(bytes, Instant::now())
+    }
+
+    fn reserve_at(&self, bytes: u64, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+        let Some(rate) = bucket.bytes_per_sec else { return Duration::ZERO };
+        let rate = rate as f64;
+        let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate) - bytes as f64;
+        bucket.refilled_at = now;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / rate)
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+/// How long to wait before sending `bytes` under every one of `limiters`.
+pub fn reserve_all(limiters: &[Arc<RateLimiter>], bytes: u64) -> Duration {
+    limiters.iter().map(|l| l.reserve(bytes)).max().unwrap_or(Duration::ZERO)
+}
+
+/// A global file transfer limit shared by every peer, plus one per peer.
+#[derive(Debug, Default)]
+pub struct FileRateLimits {
+    global: Arc<RateLimiter>,
+    peers: Mutex<HashMap<String, Arc<RateLimiter>>>,
+}
+
+impl FileRateLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cap all file transfers together at `bytes_per_sec` (`None` or 0: unlimited).
+    pub fn set_global(&self, bytes_per_sec: Option<u64>) {
+        self.global.set_rate(bytes_per_sec);
+    }
+
+    pub fn global(&self) -> Option<u64> {
+        self.global.rate()
+    }
+
+    /// Cap file transfers to `peer_id` at `bytes_per_sec` (`None` or 0: only
+    /// the global limit applies).
+    pub fn set_peer(&self, peer_id: &str, bytes_per_sec: Option<u64>) {
+        self.peer_limiter(peer_id).set_rate(bytes_per_sec);
+    }
+
+    pub fn peer(&self, peer_id: &str) -> Option<u64> {
+        self.peers.lock().unwrap().get(peer_id).and_then(|l| l.rate())
+    }
+
+    /// The limiters a transfer to `peer_id` goes through: its own, then the
+    /// global one. Later `set_*` calls apply to them too.
+    pub fn for_peer(&self, peer_id: &str) -> Vec<Arc<RateLimiter>> {
+        vec![self.peer_limiter(peer_id), Arc::clone(&self.global)]
+    }
+
+    fn peer_limiter(&self, peer_id: &str) -> Arc<RateLimiter> {
+        Arc::clone(self.peers.lock().unwrap().entry(peer_id.to_string()).or_default())
+    }
+}
+
+/// Caps how many file sends run at once; further sends wait for a slot.
+/// Like [`FileRateLimits`], one can be shared by a service and transfers made
+/// outside it so the cap covers both.
+#[derive(Debug)]
+pub struct FileSendSlots {
+    /// The cap and the semaphore enforcing it, replaced together by `set_limit`.
+    slots: Mutex<(Option<usize>, Arc<tokio::sync::Semaphore>)>,
+}
+
+impl FileSendSlots {
+    /// At most `limit` sends at once (`None`: no cap).
+    pub fn new(limit: Option<usize>) -> Self {
+        Self { slots: Mutex::new(Self::slots_for(limit)) }
+    }
+
+    /// Change the cap (minimum 1). Sends already running or waiting keep the
+    /// old one; sends started after the call get the new one.
+    pub fn set_limit(&self, limit: Option<usize>) {
+        *self.slots.lock().unwrap() = Self::slots_for(limit);
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.slots.lock().unwrap().0
+    }
+
+    /// Wait for a slot; the send holds it until the permit is dropped.
+    pub async fn acquire(&self) -> tokio::sync::OwnedSemaphorePermit {
+        let permits = Arc::clone(&self.slots.lock().unwrap().1);
+        permits.acquire_owned().await.expect("file send slots are never closed")
+    }
+
+    fn slots_for(limit: Option<usize>) -> (Option<usize>, Arc<tokio::sync::Semaphore>) {
+        let limit = limit.map(|n| n.max(1));
+        (limit, Arc::new(tokio::sync::Semaphore::new(limit.unwrap_or(tokio::sync::Semaphore::MAX_PERMITS))))
+    }
+}
+
+impl Default for FileSendSlots {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlimited_never_waits() {
+        let l = RateLimiter::new(None);
+        assert_eq!(l.reserve(u64::MAX), Duration::ZERO);
+        let l = RateLimiter::new(Some(0));
+        assert_eq!(l.rate(), None);
+        assert_eq!(l.reserve(1 << 30), Duration::ZERO);
+    }
+
+    #[test]
+    fn paces_past_the_burst_at_the_rate() {
+        let l = RateLimiter::new(Some(1000));
+        let t0 = Instant::now();
+        // One second's worth goes at once.
+        assert_eq!(l.reserve_at(1000, t0), Duration::ZERO);
+        // The next 500 bytes wait half a second, the 500 after that a second.
+        assert_eq!(l.reserve_at(500, t0), Duration::from_millis(500));
+        assert_eq!(l.reserve_at(500, t0), Duration::from_secs(1));
+        // Waiting it out pays the debt; idling refills at most one burst.
+        assert_eq!(l.reserve_at(0, t0 + Duration::from_secs(1)), Duration::ZERO);
+        assert_eq!(l.reserve_at(1000, t0 + Duration::from_secs(60)), Duration::ZERO);
+        assert_eq!(l.reserve_at(250, t0 + Duration::from_secs(60)), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn a_chunk_larger_than_the_burst_waits_for_the_remainder() {
+        let l = RateLimiter::new(Some(100));
+        assert_eq!(l.reserve_at(300, Instant::now()), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn the_strictest_limit_wins() {
+        let limits = FileRateLimits::new();
+        limits.set_global(Some(1000));
+        limits.set_peer("p", Some(100));
+        let limiters = limits.for_peer("p");
+        assert_eq!(reserve_all(&limiters, 100), Duration::ZERO);
+        assert!(reserve_all(&limiters, 100) >= Duration::from_millis(990));
+
+        // Another peer only has the global limit, which is barely touched.
+        assert_eq!(reserve_all(&limits.for_peer("q"), 500), Duration::ZERO);
+        assert_eq!(limits.peer("q"), None);
+
+        // Changing a limit applies to limiters already handed out.
+        limits.set_peer("p", None);
+        limits.set_global(None);
+        assert_eq!(reserve_all(&limiters, 1 << 20), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn file_send_slots_queue_sends_past_the_limit() {
+        let slots = Arc::new(FileSendSlots::new(Some(1)));
+        assert_eq!(slots.limit(), Some(1));
+        let first = slots.acquire().await;
+
+        let waiting = tokio::spawn({
+            let slots = Arc::clone(&slots);
+            async move { slots.acquire().await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+
+        drop(first);
+        let _second = tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap().unwrap();
+
+        slots.set_limit(None);
+        assert_eq!(slots.limit(), None);
+        let _both = (slots.acquire().await, slots.acquire().await);
+    }
+}
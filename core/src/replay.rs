@@ -2,6 +2,7 @@
 
 use anyhow::Result;
 use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::Mutex;
 
 /// Protects against replayed handshakes (e.g. re-sent `Hello` messages).
@@ -11,6 +12,13 @@ pub trait ReplayProtector: Send + Sync {
     /// Check whether `nonce` has already been seen for `peer_id`.
     /// If it is new, store it and return Ok(()). If it is a replay, return Err.
     fn check_and_store(&self, peer_id: &str, nonce: &[u8]) -> Result<()>;
+
+    /// Number of nonces currently remembered for `peer_id`.
+    fn nonce_count(&self, peer_id: &str) -> usize;
+
+    /// Drop every nonce remembered for `peer_id` (e.g. once it is no longer
+    /// trusted), returning how many were dropped.
+    fn forget_peer(&self, peer_id: &str) -> Result<usize>;
 }
 
 /// In-memory replay protector.
@@ -47,6 +55,162 @@ impl ReplayProtector for MemoryReplayProtector {
 
         Ok(())
     }
+
+    fn nonce_count(&self, peer_id: &str) -> usize {
+        let map = self.map.lock().expect("replay protector mutex poisoned");
+        map.get(peer_id).map_or(0, VecDeque::len)
+    }
+
+    fn forget_peer(&self, peer_id: &str) -> Result<usize> {
+        let mut map = self.map.lock().expect("replay protector mutex poisoned");
+        Ok(map.remove(peer_id).map_or(0, |q| q.len()))
+    }
+}
+
+/// Where a [`FileReplayProtector`] kept beside the trust store at
+/// `trust_path` lives: `trust.json` → `trust.replay.json`.
+pub fn replay_path_for(trust_path: &std::path::Path) -> PathBuf {
+    trust_path.with_extension("replay.json")
+}
+
+/// One remembered hello nonce in a [`FileReplayProtector`] file.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct NonceRecord {
+    peer_id: String,
+    nonce_b64: String,
+    seen_at_ms: u64,
+}
+
+/// Replay protector persisted as a JSON file, so replays are caught across restarts.
+///
+/// The file holds at most `max_entries` nonces across all peers. When full, the
+/// oldest nonces are pruned first, so the store always covers the most recent window.
+/// With [`FileReplayProtector::with_max_age`], nonces older than the age are pruned too.
+/// The file is replaced atomically, so a crash mid-write never leaves it truncated.
+pub struct FileReplayProtector {
+    path: PathBuf,
+    max_entries: usize,
+    max_age: Option<std::time::Duration>,
+    /// Oldest first.
+    entries: Mutex<VecDeque<NonceRecord>>,
+}
+
+impl FileReplayProtector {
+    pub fn new(path: PathBuf, max_entries: usize) -> Result<Self> {
+        let mut entries: VecDeque<NonceRecord> = if path.exists() {
+            let data = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&data)?
+        } else {
+            VecDeque::new()
+        };
+        let max_entries = max_entries.max(1);
+        let pruned = entries.len() > max_entries;
+        while entries.len() > max_entries {
+            entries.pop_front();
+        }
+
+        let store = Self { path, max_entries, max_age: None, entries: Mutex::new(entries) };
+        if pruned {
+            store.flush(&store.entries.lock().unwrap())?;
+        }
+        Ok(store)
+    }
+
+    /// Also prune nonces first seen more than `max_age` ago, on load and on
+    /// every store.
+    pub fn with_max_age(mut self, max_age: std::time::Duration) -> Result<Self> {
+        self.max_age = Some(max_age);
+        let mut entries = self.entries.lock().unwrap();
+        if self.prune_expired(&mut entries, now_ms()) {
+            self.flush(&entries)?;
+        }
+        drop(entries);
+        Ok(self)
+    }
+
+    /// Number of nonces currently stored.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop entries older than `max_age`, returning whether any were dropped.
+    fn prune_expired(&self, entries: &mut VecDeque<NonceRecord>, now_ms: u64) -> bool {
+        let Some(max_age) = self.max_age else { return false };
+        let cutoff = now_ms.saturating_sub(max_age.as_millis() as u64);
+        let before = entries.len();
+        entries.retain(|e| e.seen_at_ms >= cutoff);
+        entries.len() != before
+    }
+
+    /// Write `entries` to a sibling temp file and rename it over the store.
+    fn flush(&self, entries: &VecDeque<NonceRecord>) -> Result<()> {
+        let data = serde_json::to_string(entries)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut tmp_name = self.path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp = self.path.with_file_name(tmp_name);
+        std::fs::write(&tmp, data)?;
+        if let Err(e) = std::fs::rename(&tmp, &self.path) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+impl ReplayProtector for FileReplayProtector {
+    fn check_and_store(&self, peer_id: &str, nonce: &[u8]) -> Result<()> {
+        if nonce.len() != 32 {
+            anyhow::bail!("invalid nonce length for replay check");
+        }
+        let nonce_b64 = crate::b64::Base64Alphabet::Standard.encode(nonce);
+
+        let mut entries = self.entries.lock().expect("replay protector mutex poisoned");
+        // Expired nonces no longer count as seen, just as if they had been pruned earlier.
+        let now = now_ms();
+        let pruned = self.prune_expired(&mut entries, now);
+        if entries.iter().any(|e| e.peer_id == peer_id && e.nonce_b64 == nonce_b64) {
+            if pruned {
+                self.flush(&entries)?;
+            }
+            anyhow::bail!("replayed hello nonce for peer_id={peer_id}");
+        }
+
+        entries.push_back(NonceRecord { peer_id: peer_id.to_string(), nonce_b64, seen_at_ms: now });
+        while entries.len() > self.max_entries {
+            entries.pop_front();
+        }
+        self.flush(&entries)
+    }
+
+    fn nonce_count(&self, peer_id: &str) -> usize {
+        let entries = self.entries.lock().expect("replay protector mutex poisoned");
+        entries.iter().filter(|e| e.peer_id == peer_id).count()
+    }
+
+    fn forget_peer(&self, peer_id: &str) -> Result<usize> {
+        let mut entries = self.entries.lock().expect("replay protector mutex poisoned");
+        let before = entries.len();
+        entries.retain(|e| e.peer_id != peer_id);
+        let dropped = before - entries.len();
+        if dropped > 0 {
+            self.flush(&entries)?;
+        }
+        Ok(dropped)
+    }
 }
 
 #[cfg(test)]
@@ -75,4 +239,128 @@ mod tests {
         // n1 was evicted, so it can be seen again without being flagged.
         rp.check_and_store("peer", &n1).unwrap();
     }
+
+    #[test]
+    fn forgetting_a_peer_drops_only_its_nonces() {
+        let rp = MemoryReplayProtector::new(8);
+        for i in 0..3 {
+            rp.check_and_store("gone", &[i; 32]).unwrap();
+        }
+        rp.check_and_store("kept", &[9u8; 32]).unwrap();
+
+        assert_eq!(rp.forget_peer("gone").unwrap(), 3);
+        assert_eq!(rp.nonce_count("gone"), 0);
+        assert!(!rp.map.lock().unwrap().contains_key("gone"));
+        assert_eq!(rp.nonce_count("kept"), 1);
+        assert!(rp.check_and_store("kept", &[9u8; 32]).is_err());
+        assert_eq!(rp.forget_peer("gone").unwrap(), 0);
+
+        // In the shared file window, the freed slots go to the remaining peers.
+        let path = std::env::temp_dir()
+            .join(format!("openclipboard_replay_forget_{}", std::process::id()))
+            .join("replay.json");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        let rp = FileReplayProtector::new(path.clone(), 4).unwrap();
+        for i in 0..3 {
+            rp.check_and_store("gone", &[i; 32]).unwrap();
+        }
+        rp.check_and_store("kept", &[9u8; 32]).unwrap();
+        assert_eq!(rp.forget_peer("gone").unwrap(), 3);
+        assert_eq!((rp.len(), rp.nonce_count("gone")), (1, 0));
+        for i in 10..13 {
+            rp.check_and_store("kept", &[i; 32]).unwrap();
+        }
+        assert!(rp.check_and_store("kept", &[9u8; 32]).is_err(), "nothing of kept's was evicted");
+        assert_eq!(FileReplayProtector::new(path.clone(), 4).unwrap().nonce_count("gone"), 0);
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn file_store_prunes_oldest_beyond_cap_and_keeps_rejecting_newest() {
+        let path = std::env::temp_dir()
+            .join(format!(
+                "openclipboard_replay_test_{}_{}",
+                std::process::id(),
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos()
+            ))
+            .join("replay.json");
+        let nonce = |i: u8| [i; 32];
+
+        let rp = FileReplayProtector::new(path.clone(), 4).unwrap();
+        for i in 0..10 {
+            rp.check_and_store("peer", &nonce(i)).unwrap();
+        }
+        assert_eq!(rp.len(), 4);
+
+        // The file holds only the newest 4, and a fresh load still rejects them.
+        let on_disk: Vec<NonceRecord> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(on_disk.len(), 4);
+        let reloaded = FileReplayProtector::new(path.clone(), 4).unwrap();
+        for i in 6..10 {
+            assert!(reloaded.check_and_store("peer", &nonce(i)).is_err(), "nonce {i} should be a replay");
+        }
+        // The oldest were pruned, so they are no longer remembered.
+        reloaded.check_and_store("peer", &nonce(0)).unwrap();
+
+        // Reopening with a smaller cap prunes the file down to it.
+        let smaller = FileReplayProtector::new(path.clone(), 2).unwrap();
+        assert_eq!(smaller.len(), 2);
+        assert!(smaller.check_and_store("peer", &nonce(0)).is_err());
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn file_store_replaces_file_atomically_and_prunes_expired() {
+        let dir = std::env::temp_dir().join(format!("openclipboard_replay_age_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("replay.json");
+
+        let stale = NonceRecord { peer_id: "peer".into(), nonce_b64: "old".into(), seen_at_ms: 1 };
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, serde_json::to_string(&vec![stale]).unwrap()).unwrap();
+
+        let rp = FileReplayProtector::new(path.clone(), 8)
+            .unwrap()
+            .with_max_age(std::time::Duration::from_secs(3600))
+            .unwrap();
+        assert!(rp.is_empty(), "the stale nonce was pruned on load");
+        rp.check_and_store("peer", &[1u8; 32]).unwrap();
+        assert!(rp.check_and_store("peer", &[1u8; 32]).is_err());
+
+        // Only the store itself is left behind, no temp file.
+        let names: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("replay.json")]);
+        assert_eq!(FileReplayProtector::new(path, 8).unwrap().len(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_store_accepts_a_nonce_again_once_it_expires() {
+        let dir = std::env::temp_dir().join(format!("openclipboard_replay_expiry_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let rp = FileReplayProtector::new(dir.join("replay.json"), 8)
+            .unwrap()
+            .with_max_age(std::time::Duration::from_millis(20))
+            .unwrap();
+
+        rp.check_and_store("peer", &[1u8; 32]).unwrap();
+        assert!(rp.check_and_store("peer", &[1u8; 32]).is_err());
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        rp.check_and_store("peer", &[1u8; 32]).unwrap();
+        assert_eq!(rp.len(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn replay_file_sits_beside_the_trust_store() {
+        let trust = std::path::Path::new("/data/trust.json");
+        assert_eq!(replay_path_for(trust), std::path::Path::new("/data/trust.replay.json"));
+    }
 }
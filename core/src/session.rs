@@ -1,23 +1,287 @@
 //! Session manager: ties identity, transport, clipboard, and trust together.
 
+use crate::b64::Base64Alphabet;
 use crate::clipboard::{ClipboardContent, ClipboardProvider};
-use crate::identity::{Ed25519Identity, IdentityProvider};
-use crate::protocol::{hello_transcript, Frame, Message};
+use crate::identity::{Ed25519Identity, EphemeralX25519, IdentityProvider};
+use crate::protocol::{chunk_clip_text, decode_payload, derive_session_key, encode_payload, hello_transcript_with_ephemeral, hello_transcript_with_offers, protocol_descriptor, trust_ack_transcript, ClipTextAssembler, Codec, Frame, Message, MsgType, PayloadCipher, ProtocolDescriptor, TrustSyncRecord, TrustSyncRevocation, CLIP_TEXT_CHUNK_SIZE, FILE_CHUNK_SIZE, FEATURE_CLIP_ACK, FEATURE_ENCRYPTED_PAYLOADS, FEATURE_RAW_FILE_CHUNKS, FEATURE_TRUST_ACK, FEATURE_ZSTD_CLIP_TEXT, FEATURES, MAX_PAYLOAD_LEN};
+use crate::rate_limit::{reserve_all, RateLimiter};
 use crate::replay::ReplayProtector;
 use crate::transport::Connection;
 use crate::trust::TrustStore;
 use anyhow::Result;
-use base64::Engine as _;
 use rand_core::RngCore;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
+/// Sent clips remembered for matching acks; older ones just go unconfirmed.
+const MAX_UNACKED_CLIPS: usize = 64;
+
+/// Files [`Session::send_files`] sends at once unless set otherwise.
+pub const DEFAULT_MAX_CONCURRENT_FILES: usize = 4;
+
+/// How long a sender waits for the receiver to answer a `FileOffer`.
+pub(crate) const FILE_OFFER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long [`Session::send_files`] waits to hear where to resume a file.
+const RESUME_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long [`Session::send_files`] waits for the receiver to confirm it has
+/// read every file.
+const FILES_CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// SHA-256 hex and size of the file at `path`, read a chunk at a time.
+pub(crate) fn hash_file(path: &std::path::Path) -> Result<(u64, String)> {
+    use anyhow::Context as _;
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+    let mut file = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; FILE_CHUNK_SIZE];
+    let mut size = 0u64;
+    loop {
+        let n = file.read(&mut buf).with_context(|| format!("read {}", path.display()))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    Ok((size, format!("{:x}", hasher.finalize())))
+}
+
+/// How [`Session::send_file_data`] cuts up and pipelines a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileTransferConfig {
+    /// File bytes per chunk; at most half of `MAX_PAYLOAD_LEN`, so a chunk
+    /// still fits a frame once base64-encoded.
+    pub chunk_size: usize,
+    /// How many chunks may be paced and encoded ahead of the send that is
+    /// still in progress; 1 sends strictly one chunk at a time.
+    pub window: usize,
+}
+
+impl Default for FileTransferConfig {
+    fn default() -> Self {
+        Self { chunk_size: FILE_CHUNK_SIZE, window: 1 }
+    }
+}
+
 /// Result of a successful handshake.
 #[derive(Debug, Clone)]
 pub struct HandshakeResult {
     pub peer_id: String,
     pub identity_pk: Vec<u8>,
+    /// Whether frames after the handshake are encrypted at the application layer
+    /// (on top of QUIC); see [`Session::encryption_enabled`].
+    pub encrypted: bool,
+}
+
+/// Why a handshake was refused, for callers that react per reason (e.g. a re-pair
+/// prompt on `KeyChanged`). Carried inside the `anyhow::Error` returned by
+/// `handshake*`; failures that don't fit one of these (malformed frames, transport
+/// errors) carry no `HandshakeError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeError {
+    /// The peer is not in the trust store.
+    Untrusted,
+    /// The peer is trusted, but presented a different public key than the pinned one.
+    KeyChanged,
+    /// The HELLO signature (or the peer id it binds) did not verify.
+    BadSignature,
+    /// The HELLO nonce was already seen from this peer.
+    Replay,
+    /// One side requires a pre-shared key the other doesn't hold (or holds a
+    /// different one). See [`Session::with_psk`].
+    PskMismatch,
+    /// The peer's HELLO did not arrive in time.
+    Timeout,
+    /// The handshake went through, but the peer said it doesn't trust this
+    /// device (e.g. pairing was only completed on this side).
+    NotTrustedByPeer,
+    /// The peer presented this device's own identity: the identity file is in
+    /// use on another device too, and one of them needs a new one.
+    DuplicateIdentity,
+}
+
+impl HandshakeError {
+    /// The typed reason inside a handshake error, if there is one.
+    pub fn of(err: &anyhow::Error) -> Option<Self> {
+        err.downcast_ref::<Self>().copied()
+    }
+}
+
+impl std::fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Untrusted => "untrusted peer",
+            Self::KeyChanged => "peer public key changed",
+            Self::BadSignature => "bad hello signature",
+            Self::Replay => "replayed hello",
+            Self::PskMismatch => "pre-shared key mismatch",
+            Self::Timeout => "handshake timed out",
+            Self::NotTrustedByPeer => "peer does not trust this device",
+            Self::DuplicateIdentity => "peer uses this device's identity",
+        };
+        f.write_str(s)
+    }
+}
+
+impl std::error::Error for HandshakeError {}
+
+/// Caps how many handshakes check a peer's signature at once, so a burst of
+/// incoming connections can't tie up every core with ed25519 verification.
+/// Sessions share [`HandshakeLimiter::global`] unless given their own with
+/// [`Session::with_handshake_limiter`], so the cap holds across every
+/// `SyncService` in the process.
+#[derive(Debug)]
+pub struct HandshakeLimiter {
+    state: std::sync::Mutex<LimiterState>,
+    freed: tokio::sync::Notify,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    limit: usize,
+    in_flight: usize,
+    peak: usize,
+}
+
+impl HandshakeLimiter {
+    /// Allow `limit` verifications at once (minimum 1).
+    pub fn new(limit: usize) -> Self {
+        Self {
+            state: std::sync::Mutex::new(LimiterState { limit: limit.max(1), in_flight: 0, peak: 0 }),
+            freed: tokio::sync::Notify::new(),
+        }
+    }
+
+    /// The process-wide limiter, allowing half the available cores (at least
+    /// one) until [`HandshakeLimiter::set_limit`] says otherwise.
+    pub fn global() -> Arc<Self> {
+        static GLOBAL: std::sync::OnceLock<Arc<HandshakeLimiter>> = std::sync::OnceLock::new();
+        Arc::clone(GLOBAL.get_or_init(|| {
+            let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+            Arc::new(Self::new(cores / 2))
+        }))
+    }
+
+    /// Change the cap (minimum 1). Verifications already running finish;
+    /// waiting ones start as soon as they fit under the new cap.
+    pub fn set_limit(&self, limit: usize) {
+        self.state.lock().unwrap().limit = limit.max(1);
+        self.freed.notify_waiters();
+    }
+
+    pub fn limit(&self) -> usize {
+        self.state.lock().unwrap().limit
+    }
+
+    /// Verifications running right now.
+    pub fn in_flight(&self) -> usize {
+        self.state.lock().unwrap().in_flight
+    }
+
+    /// The most verifications that have run at once.
+    pub fn peak(&self) -> usize {
+        self.state.lock().unwrap().peak
+    }
+
+    /// Run `verify` on the blocking pool once there's room under the cap.
+    pub async fn run<T: Send + 'static>(&self, verify: impl FnOnce() -> T + Send + 'static) -> T {
+        let _slot = self.acquire().await;
+        match tokio::task::spawn_blocking(verify).await {
+            Ok(v) => v,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+
+    async fn acquire(&self) -> LimiterSlot<'_> {
+        loop {
+            let freed = self.freed.notified();
+            tokio::pin!(freed);
+            // Register before checking, so a slot freed in between still wakes us.
+            freed.as_mut().enable();
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.in_flight < state.limit {
+                    state.in_flight += 1;
+                    state.peak = state.peak.max(state.in_flight);
+                    return LimiterSlot(self);
+                }
+            }
+            freed.await;
+        }
+    }
+}
+
+/// A verification counted against a [`HandshakeLimiter`] until dropped.
+struct LimiterSlot<'a>(&'a HandshakeLimiter);
+
+impl Drop for LimiterSlot<'_> {
+    fn drop(&mut self) {
+        self.0.state.lock().unwrap().in_flight -= 1;
+        self.0.freed.notify_one();
+    }
+}
+
+/// `reason` as an error, keeping `message` as its displayed text.
+fn handshake_error(reason: HandshakeError, message: String) -> anyhow::Error {
+    anyhow::Error::new(reason).context(message)
+}
+
+/// Lanes of a session's send queue, highest priority first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SendPriority {
+    Control,
+    Clipboard,
+    /// File transfer traffic, sent only when nothing else is waiting.
+    Bulk,
+}
+
+impl SendPriority {
+    /// The lane a message goes in by default, from its stream.
+    pub fn of(msg: &Message) -> Self {
+        match msg.stream_id() {
+            crate::protocol::StreamId::Control => Self::Control,
+            crate::protocol::StreamId::Clipboard => Self::Clipboard,
+            crate::protocol::StreamId::File => Self::Bulk,
+        }
+    }
+}
+
+/// Called once a queued message has been written to the connection.
+pub type OnSent = Box<dyn FnOnce() + Send>;
+
+/// Outgoing messages waiting for a session's send task: every Control message
+/// before any Clipboard one before any Bulk one, FIFO within a lane.
+#[derive(Default)]
+pub struct SendQueue {
+    lanes: [std::collections::VecDeque<(Message, Option<OnSent>)>; 3],
+}
+
+impl SendQueue {
+    pub fn push(&mut self, msg: Message, priority: SendPriority, on_sent: Option<OnSent>) {
+        self.lanes[priority as usize].push_back((msg, on_sent));
+    }
+
+    /// Put a message back at the front of its lane, to be popped next again.
+    pub fn push_front(&mut self, msg: Message, priority: SendPriority, on_sent: Option<OnSent>) {
+        self.lanes[priority as usize].push_front((msg, on_sent));
+    }
+
+    /// The oldest message of the highest non-empty lane.
+    pub fn pop(&mut self) -> Option<(Message, Option<OnSent>)> {
+        self.lanes.iter_mut().find_map(|lane| lane.pop_front())
+    }
+
+    pub fn len(&self) -> usize {
+        self.lanes.iter().map(|lane| lane.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 pub struct Session<C: Connection, I: IdentityProvider, CB: ClipboardProvider> {
@@ -28,6 +292,64 @@ pub struct Session<C: Connection, I: IdentityProvider, CB: ClipboardProvider> {
     replay: Option<Arc<dyn ReplayProtector>>,
     pairing_mode: bool,
     seq: AtomicU64,
+    clip_assembler: std::sync::Mutex<ClipTextAssembler>,
+    origin: Option<String>,
+    psk: Option<Vec<u8>>,
+    offer_binary: bool,
+    codec: std::sync::Mutex<Codec>,
+    /// Messages for `run_send_queue`; `send_*` calls bypass it and go out at once.
+    send_queue: std::sync::Mutex<SendQueue>,
+    send_ready: tokio::sync::Notify,
+    offer_clip_ack: bool,
+    v0_compat: bool,
+    /// The peer's HELLO had no codecs, features or ephemeral key: it speaks
+    /// protocol v0, so only v0 messages are sent to it.
+    peer_v0: AtomicBool,
+    /// Both HELLOs listed `FEATURE_CLIP_ACK`.
+    clip_ack: AtomicBool,
+    offer_raw_file_chunks: bool,
+    /// Both HELLOs listed `FEATURE_RAW_FILE_CHUNKS`.
+    raw_file_chunks: AtomicBool,
+    /// Clip text longer than this is sent compressed; offers `FEATURE_ZSTD_CLIP_TEXT`.
+    compression_threshold: Option<usize>,
+    /// Both HELLOs listed `FEATURE_ZSTD_CLIP_TEXT`.
+    zstd_clip_text: AtomicBool,
+    offer_trust_ack: bool,
+    /// Both HELLOs listed `FEATURE_TRUST_ACK`.
+    trust_ack: AtomicBool,
+    offer_encryption: bool,
+    /// Set once both HELLOs listed `FEATURE_ENCRYPTED_PAYLOADS` and a session
+    /// key was derived.
+    payload_cipher: std::sync::Mutex<Option<PayloadCipher>>,
+    /// Lowest `seq` an encrypted frame may still have; earlier ones are replays.
+    recv_seq_floor: AtomicU64,
+    /// Held from taking a `seq` until the frame is sent, so frames go out in
+    /// `seq` order.
+    send_order: tokio::sync::Mutex<()>,
+    /// The nonce of our last HELLO, and the peer's public key and HELLO
+    /// nonce, which `TrustAck` signatures are bound to.
+    hello_nonce: std::sync::Mutex<[u8; 32]>,
+    peer_hello: std::sync::Mutex<Option<(Vec<u8>, Vec<u8>)>>,
+    /// Sent clips not acked yet, as (seq of the completing frame, clip ts_ms);
+    /// oldest first, at most `MAX_UNACKED_CLIPS`.
+    unacked_clips: std::sync::Mutex<std::collections::VecDeque<(u64, u64)>>,
+    /// `ts_ms` of chunked clips sent up to their `ClipTextEnd`.
+    chunked_clip_ts: std::sync::Mutex<std::collections::HashMap<String, u64>>,
+    /// Limits file chunks are paced under; see [`Session::set_file_rate_limiters`].
+    file_rate_limiters: std::sync::Mutex<Vec<Arc<RateLimiter>>>,
+    /// When the queued file chunk at the front of the bulk lane, already
+    /// reserved with the limiters, may go out.
+    file_chunk_due: std::sync::Mutex<Option<tokio::time::Instant>>,
+    /// How many files `send_files` interleaves at once.
+    max_concurrent_files: usize,
+    /// How `send_files` cuts up files.
+    file_transfer: FileTransferConfig,
+    /// Caps concurrent signature checks across sessions sharing it.
+    handshake_limiter: Arc<HandshakeLimiter>,
+    /// The X25519 key sent in our last HELLO, until the peer's arrives.
+    ephemeral: std::sync::Mutex<Option<EphemeralX25519>>,
+    /// Derived from both HELLOs' ephemeral keys; see [`Session::session_key`].
+    session_key: std::sync::Mutex<Option<[u8; 32]>>,
 }
 
 impl<C: Connection, I: IdentityProvider, CB: ClipboardProvider> Session<C, I, CB> {
@@ -40,20 +362,44 @@ impl<C: Connection, I: IdentityProvider, CB: ClipboardProvider> Session<C, I, CB
             replay: None,
             pairing_mode: false,
             seq: AtomicU64::new(0),
+            clip_assembler: std::sync::Mutex::new(ClipTextAssembler::new()),
+            origin: None,
+            psk: None,
+            offer_binary: false,
+            codec: std::sync::Mutex::new(Codec::Json),
+            send_queue: std::sync::Mutex::new(SendQueue::default()),
+            send_ready: tokio::sync::Notify::new(),
+            offer_clip_ack: false,
+            v0_compat: false,
+            peer_v0: AtomicBool::new(false),
+            clip_ack: AtomicBool::new(false),
+            offer_raw_file_chunks: false,
+            raw_file_chunks: AtomicBool::new(false),
+            compression_threshold: None,
+            zstd_clip_text: AtomicBool::new(false),
+            offer_trust_ack: false,
+            trust_ack: AtomicBool::new(false),
+            offer_encryption: false,
+            payload_cipher: std::sync::Mutex::new(None),
+            recv_seq_floor: AtomicU64::new(0),
+            send_order: tokio::sync::Mutex::new(()),
+            hello_nonce: std::sync::Mutex::new([0; 32]),
+            peer_hello: std::sync::Mutex::new(None),
+            unacked_clips: std::sync::Mutex::new(std::collections::VecDeque::new()),
+            chunked_clip_ts: std::sync::Mutex::new(std::collections::HashMap::new()),
+            file_rate_limiters: std::sync::Mutex::new(Vec::new()),
+            file_chunk_due: std::sync::Mutex::new(None),
+            max_concurrent_files: DEFAULT_MAX_CONCURRENT_FILES,
+            file_transfer: FileTransferConfig::default(),
+            handshake_limiter: HandshakeLimiter::global(),
+            ephemeral: std::sync::Mutex::new(None),
+            session_key: std::sync::Mutex::new(None),
         }
     }
 
     /// Create a session with trust verification.
     pub fn with_trust(conn: C, identity: I, clipboard: CB, trust_store: Arc<dyn TrustStore>) -> Self {
-        Self {
-            conn: Arc::new(conn),
-            identity: Arc::new(identity),
-            clipboard: Arc::new(clipboard),
-            trust_store: Some(trust_store),
-            replay: None,
-            pairing_mode: false,
-            seq: AtomicU64::new(0),
-        }
+        Self { trust_store: Some(trust_store), ..Self::new(conn, identity, clipboard) }
     }
 
     /// Create a session with trust verification and optional replay protection.
@@ -64,28 +410,12 @@ impl<C: Connection, I: IdentityProvider, CB: ClipboardProvider> Session<C, I, CB
         trust_store: Arc<dyn TrustStore>,
         replay: Arc<dyn ReplayProtector>,
     ) -> Self {
-        Self {
-            conn: Arc::new(conn),
-            identity: Arc::new(identity),
-            clipboard: Arc::new(clipboard),
-            trust_store: Some(trust_store),
-            replay: Some(replay),
-            pairing_mode: false,
-            seq: AtomicU64::new(0),
-        }
+        Self { trust_store: Some(trust_store), replay: Some(replay), ..Self::new(conn, identity, clipboard) }
     }
 
     /// Create a session in pairing mode (allows untrusted peers).
     pub fn with_pairing_mode(conn: C, identity: I, clipboard: CB, trust_store: Arc<dyn TrustStore>) -> Self {
-        Self {
-            conn: Arc::new(conn),
-            identity: Arc::new(identity),
-            clipboard: Arc::new(clipboard),
-            trust_store: Some(trust_store),
-            replay: None,
-            pairing_mode: true,
-            seq: AtomicU64::new(0),
-        }
+        Self { pairing_mode: true, ..Self::with_trust(conn, identity, clipboard, trust_store) }
     }
 
     /// Create a session in pairing mode (allows untrusted peers), with optional replay protection.
@@ -96,15 +426,156 @@ impl<C: Connection, I: IdentityProvider, CB: ClipboardProvider> Session<C, I, CB
         trust_store: Arc<dyn TrustStore>,
         replay: Arc<dyn ReplayProtector>,
     ) -> Self {
-        Self {
-            conn: Arc::new(conn),
-            identity: Arc::new(identity),
-            clipboard: Arc::new(clipboard),
-            trust_store: Some(trust_store),
-            replay: Some(replay),
-            pairing_mode: true,
-            seq: AtomicU64::new(0),
-        }
+        Self { pairing_mode: true, ..Self::with_trust_and_replay(conn, identity, clipboard, trust_store, replay) }
+    }
+
+    /// Label outgoing clipboard text with this device name.
+    pub fn with_origin(mut self, device_name: impl Into<String>) -> Self {
+        self.origin = Some(device_name.into());
+        self
+    }
+
+    /// Require peers to prove knowledge of a pre-shared group key: it is mixed
+    /// into the signed HELLO transcript, and a peer without the same key fails
+    /// the handshake with [`HandshakeError::PskMismatch`], trusted or not.
+    pub fn with_psk(mut self, psk: impl Into<Vec<u8>>) -> Self {
+        self.psk = Some(psk.into());
+        self
+    }
+
+    /// Offer the MessagePack codec in HELLO. Once both HELLOs offer it, every
+    /// later message is sent in it; otherwise the session stays on JSON.
+    pub fn with_binary_codec(mut self) -> Self {
+        self.offer_binary = true;
+        self
+    }
+
+    /// Offer `FEATURE_CLIP_ACK` in HELLO. Once both HELLOs offer it, each side
+    /// answers clips it applies with `Message::ClipAck` (see [`Session::send_clip_ack`]).
+    pub fn with_clip_ack(mut self) -> Self {
+        self.offer_clip_ack = true;
+        self
+    }
+
+    /// Offer `FEATURE_RAW_FILE_CHUNKS` in HELLO. Once both HELLOs offer it,
+    /// [`Session::raw_file_chunks_enabled`] tells senders they may use
+    /// [`Session::send_file_chunk_raw`].
+    pub fn with_raw_file_chunks(mut self) -> Self {
+        self.offer_raw_file_chunks = true;
+        self
+    }
+
+    /// Let [`Session::send_files`] interleave up to `limit` files at once
+    /// (minimum 1; default [`DEFAULT_MAX_CONCURRENT_FILES`]).
+    pub fn with_max_concurrent_files(mut self, limit: usize) -> Self {
+        self.max_concurrent_files = limit.max(1);
+        self
+    }
+
+    /// Cut the files [`Session::send_files`] sends into chunks of
+    /// `config.chunk_size` bytes (default [`FILE_CHUNK_SIZE`]).
+    pub fn with_file_transfer_config(mut self, config: FileTransferConfig) -> Self {
+        self.file_transfer = config;
+        self
+    }
+
+    /// Check the peer's signatures under `limiter` instead of
+    /// [`HandshakeLimiter::global`].
+    pub fn with_handshake_limiter(mut self, limiter: Arc<HandshakeLimiter>) -> Self {
+        self.handshake_limiter = limiter;
+        self
+    }
+
+    /// Offer `FEATURE_ZSTD_CLIP_TEXT` in HELLO. Once both HELLOs offer it,
+    /// clip text longer than `threshold` bytes goes out zstd-compressed as
+    /// `Message::ClipTextCompressed`; shorter text, where compression doesn't
+    /// pay, is sent as plain `ClipText`. Received compressed clips come back
+    /// from `recv_message` as `ClipText` either way.
+    pub fn with_compression_threshold(mut self, threshold: usize) -> Self {
+        self.compression_threshold = Some(threshold);
+        self
+    }
+
+    /// Offer `FEATURE_TRUST_ACK` in HELLO. Once both HELLOs offer it, each
+    /// side must say whether it trusts the other right after the handshake;
+    /// see [`Session::confirm_mutual_trust`].
+    pub fn with_trust_ack(mut self) -> Self {
+        self.offer_trust_ack = true;
+        self
+    }
+
+    /// Offer `FEATURE_ENCRYPTED_PAYLOADS` in HELLO. Once both HELLOs offer it
+    /// and carry ephemeral keys, every later frame goes out sealed with the
+    /// session key, and received frames must be sealed too; see
+    /// [`Session::encryption_enabled`].
+    pub fn with_encryption(mut self) -> Self {
+        self.offer_encryption = true;
+        self
+    }
+
+    /// Behave like a protocol v0 peer, for interop testing: HELLO offers no
+    /// codecs or features (so the session stays on JSON without clip acks),
+    /// clip text goes out whole and without an origin, and any message type
+    /// v0 doesn't know is an error to send or receive.
+    pub fn with_v0_compat(mut self) -> Self {
+        self.v0_compat = true;
+        self
+    }
+
+    /// Whether the handshake found the peer speaks only protocol v0. Messages
+    /// v0 doesn't know are then never sent: `send_*` fails for them, optional
+    /// ones (`DeviceInfo`, `Bye`) are skipped and queued ones are dropped.
+    pub fn peer_is_v0(&self) -> bool {
+        self.peer_v0.load(Ordering::SeqCst)
+    }
+
+    /// Only protocol v0 goes out: this end acts as v0, or the peer is v0.
+    fn sends_v0_only(&self) -> bool {
+        self.v0_compat || self.peer_is_v0()
+    }
+
+    /// Whether the handshake negotiated clip acks.
+    pub fn clip_ack_enabled(&self) -> bool {
+        self.clip_ack.load(Ordering::SeqCst)
+    }
+
+    /// Whether the handshake negotiated raw file chunks.
+    pub fn raw_file_chunks_enabled(&self) -> bool {
+        self.raw_file_chunks.load(Ordering::SeqCst)
+    }
+
+    /// Whether the handshake negotiated compressed clip text.
+    pub fn compression_enabled(&self) -> bool {
+        self.zstd_clip_text.load(Ordering::SeqCst)
+    }
+
+    /// Whether the handshake negotiated trust acks.
+    pub fn trust_ack_enabled(&self) -> bool {
+        self.trust_ack.load(Ordering::SeqCst)
+    }
+
+    /// Whether the handshake negotiated encrypted payloads: frames after it
+    /// are `MsgType::Encrypted`, and plaintext, tampered or replayed frames
+    /// are refused.
+    pub fn encryption_enabled(&self) -> bool {
+        self.payload_cipher.lock().unwrap().is_some()
+    }
+
+    /// The key both ends derived from their HELLOs' ephemeral X25519 keys,
+    /// unique to this session. `None` before the handshake, or when either
+    /// end sent no ephemeral key (v0 compatibility).
+    pub fn session_key(&self) -> Option<[u8; 32]> {
+        *self.session_key.lock().unwrap()
+    }
+
+    /// The payload codec in use (JSON until a handshake negotiates another).
+    pub fn codec(&self) -> Codec {
+        *self.codec.lock().unwrap()
+    }
+
+    /// The trust store peers are checked against, if any.
+    pub fn trust_store(&self) -> Option<&Arc<dyn TrustStore>> {
+        self.trust_store.as_ref()
     }
 
     fn next_seq(&self) -> u64 {
@@ -118,20 +589,72 @@ impl<C: Connection, I: IdentityProvider, CB: ClipboardProvider> Session<C, I, CB
 
         let mut nonce = [0u8; 32];
         rand_core::OsRng.fill_bytes(&mut nonce);
+        *self.hello_nonce.lock().unwrap() = nonce;
+
+        // A v0 peer would check the signature without the ephemeral key.
+        let ephemeral = (!self.v0_compat).then(EphemeralX25519::generate);
+        let ephemeral_pk = ephemeral.as_ref().map(EphemeralX25519::public_key);
+        *self.ephemeral.lock().unwrap() = ephemeral;
 
-        let transcript = hello_transcript(version, &peer_id, &identity_pk, &nonce);
-        let sig = self.identity.sign(&transcript);
+        let codecs = if self.v0_compat { Vec::new() } else { self.offered_codecs() };
+        let features = if self.v0_compat { Vec::new() } else { self.offered_features() };
+        let transcript = hello_transcript_with_ephemeral(version, &peer_id, &identity_pk, &nonce, self.psk.as_deref(), ephemeral_pk.as_ref().map(|pk| &pk[..]));
+        let sig = self.identity.sign(&hello_transcript_with_offers(transcript, &codecs, &features));
 
         let msg = Message::Hello {
             peer_id,
             version,
-            identity_pk_b64: base64::engine::general_purpose::STANDARD.encode(&identity_pk),
-            nonce_b64: base64::engine::general_purpose::STANDARD.encode(&nonce),
-            sig_b64: base64::engine::general_purpose::STANDARD.encode(&sig),
+            identity_pk_b64: Base64Alphabet::Standard.encode(&identity_pk),
+            nonce_b64: Base64Alphabet::Standard.encode(&nonce),
+            sig_b64: Base64Alphabet::Standard.encode(&sig),
+            psk: self.psk.is_some(),
+            codecs,
+            features,
+            x25519_pk_b64: ephemeral_pk.map(|pk| Base64Alphabet::Standard.encode(pk)),
         };
         self.send_message(&msg).await
     }
 
+    /// The codecs beyond JSON this session lists in its HELLO.
+    fn offered_codecs(&self) -> Vec<String> {
+        Codec::ALL.into_iter().filter(|c| self.offers_codec(*c)).map(|c| c.name().to_string()).collect()
+    }
+
+    fn offers_codec(&self, codec: Codec) -> bool {
+        match codec {
+            Codec::Json => false,
+            Codec::MessagePack => self.offer_binary,
+        }
+    }
+
+    fn offered_features(&self) -> Vec<String> {
+        FEATURES.into_iter().filter(|f| self.offers_feature(f)).map(String::from).collect()
+    }
+
+    /// Whether this session lists `feature` (one of [`FEATURES`]) in its HELLO.
+    fn offers_feature(&self, feature: &str) -> bool {
+        match feature {
+            FEATURE_CLIP_ACK => self.offer_clip_ack,
+            FEATURE_RAW_FILE_CHUNKS => self.offer_raw_file_chunks,
+            FEATURE_ZSTD_CLIP_TEXT => self.compression_threshold.is_some(),
+            FEATURE_TRUST_ACK => self.offer_trust_ack,
+            FEATURE_ENCRYPTED_PAYLOADS => self.offer_encryption,
+            _ => false,
+        }
+    }
+
+    /// Whether the handshake negotiated `feature` (one of [`FEATURES`]).
+    fn feature_enabled(&self, feature: &str) -> bool {
+        match feature {
+            FEATURE_CLIP_ACK => self.clip_ack_enabled(),
+            FEATURE_RAW_FILE_CHUNKS => self.raw_file_chunks_enabled(),
+            FEATURE_ZSTD_CLIP_TEXT => self.compression_enabled(),
+            FEATURE_TRUST_ACK => self.trust_ack_enabled(),
+            FEATURE_ENCRYPTED_PAYLOADS => self.encryption_enabled(),
+            _ => false,
+        }
+    }
+
     /// Send HELLO and receive peer's HELLO, verifying trust.
     /// Returns the peer's peer_id on success.
     pub async fn handshake(&self) -> Result<String> {
@@ -156,7 +679,7 @@ impl<C: Connection, I: IdentityProvider, CB: ClipboardProvider> Session<C, I, CB
         // Receive peer's HELLO
         let frame = tokio::time::timeout(timeout_dur, self.conn.recv())
             .await
-            .map_err(|_| anyhow::anyhow!("handshake timed out"))??;
+            .map_err(|_| handshake_error(HandshakeError::Timeout, "handshake timed out".into()))??;
         let msg: Message = serde_json::from_slice(&frame.payload)?;
 
         match msg {
@@ -166,10 +689,24 @@ impl<C: Connection, I: IdentityProvider, CB: ClipboardProvider> Session<C, I, CB
                 identity_pk_b64,
                 nonce_b64,
                 sig_b64,
+                psk,
+                codecs,
+                features,
+                x25519_pk_b64,
             } => {
-                let identity_pk = base64::engine::general_purpose::STANDARD.decode(&identity_pk_b64)?;
-                let nonce = base64::engine::general_purpose::STANDARD.decode(&nonce_b64)?;
-                let sig = base64::engine::general_purpose::STANDARD.decode(&sig_b64)?;
+                let identity_pk = Base64Alphabet::Standard.decode(&identity_pk_b64)?;
+                let nonce = Base64Alphabet::Standard.decode(&nonce_b64)?;
+                let sig = Base64Alphabet::Standard.decode(&sig_b64)?;
+                let peer_ephemeral: Option<[u8; 32]> = match x25519_pk_b64 {
+                    Some(pk) => match Base64Alphabet::Standard.decode(&pk)?.try_into() {
+                        Ok(pk) => Some(pk),
+                        Err(_) => {
+                            self.conn.close();
+                            anyhow::bail!("invalid x25519 key length");
+                        }
+                    },
+                    None => None,
+                };
 
                 if identity_pk.len() != 32 {
                     self.conn.close();
@@ -184,125 +721,833 @@ impl<C: Connection, I: IdentityProvider, CB: ClipboardProvider> Session<C, I, CB
                     anyhow::bail!("invalid signature length");
                 }
 
-                // Self-consistency: peer_id must be derived from the presented public key.
-                let derived = Ed25519Identity::peer_id_from_public_key(&identity_pk);
-                if derived != peer_id {
+                // Self-consistency: peer_id must be derived from the presented public key (under our scheme).
+                if !self.identity.verify_peer_id(&peer_id, &identity_pk) {
+                    self.conn.close();
+                    return Err(handshake_error(HandshakeError::BadSignature, "peer_id/public_key mismatch".into()));
+                }
+
+                if psk != self.psk.is_some() {
                     self.conn.close();
-                    anyhow::bail!("peer_id/public_key mismatch");
+                    return Err(handshake_error(HandshakeError::PskMismatch, format!("pre-shared key mismatch: {}", peer_id)));
                 }
 
-                // Verify proof-of-possession.
-                let transcript = hello_transcript(version, &peer_id, &identity_pk, &nonce);
-                if !Ed25519Identity::verify_with_public_key(&transcript, &sig, &identity_pk) {
+                // Verify proof-of-possession (and, with a PSK, knowledge of it).
+                let transcript = hello_transcript_with_ephemeral(version, &peer_id, &identity_pk, &nonce, self.psk.as_deref(), peer_ephemeral.as_ref().map(|pk| &pk[..]));
+                let transcript = hello_transcript_with_offers(transcript, &codecs, &features);
+                let pk = identity_pk.clone();
+                let verified = self
+                    .handshake_limiter
+                    .run(move || Ed25519Identity::verify_with_public_key(&transcript, &sig, &pk))
+                    .await;
+                if !verified {
                     self.conn.close();
-                    anyhow::bail!("invalid hello signature");
+                    if self.psk.is_some() {
+                        return Err(handshake_error(HandshakeError::PskMismatch, format!("pre-shared key mismatch: {}", peer_id)));
+                    }
+                    return Err(handshake_error(HandshakeError::BadSignature, "invalid hello signature".into()));
                 }
 
-                // Optional anti-replay: after signature verification, reject reused nonces.
-                if let Some(ref replay) = self.replay {
-                    replay.check_and_store(&peer_id, &nonce)?;
-                }
+                // Optional anti-replay: after signature verification, reject reused nonces.
+                if let Some(ref replay) = self.replay {
+                    replay
+                        .check_and_store(&peer_id, &nonce)
+                        .map_err(|e| handshake_error(HandshakeError::Replay, e.to_string()))?;
+                }
+
+                // Check trust if trust store is configured and not in pairing mode.
+                if let Some(ref store) = self.trust_store {
+                    if !self.pairing_mode {
+                        let lookup = crate::trust::BlockingTrustStore::new(Arc::clone(store));
+                        let Some(rec) = crate::trust::AsyncTrustStore::get(&lookup, &peer_id).await? else {
+                            self.conn.close();
+                            return Err(handshake_error(HandshakeError::Untrusted, format!("untrusted peer: {}", peer_id)));
+                        };
+                        if rec.identity_pk != identity_pk {
+                            self.conn.close();
+                            return Err(handshake_error(
+                                HandshakeError::KeyChanged,
+                                format!("trusted peer public key mismatch: {}", peer_id),
+                            ));
+                        }
+                    }
+                }
+
+                self.peer_v0.store(codecs.is_empty() && features.is_empty() && peer_ephemeral.is_none(), Ordering::SeqCst);
+                let both_codec = |codec: Codec| self.offers_codec(codec) && !self.v0_compat && codecs.iter().any(|c| c == codec.name());
+                let both = |feature: &str| self.offers_feature(feature) && !self.v0_compat && features.iter().any(|f| f == feature);
+                if both_codec(Codec::MessagePack) {
+                    *self.codec.lock().unwrap() = Codec::MessagePack;
+                }
+                self.clip_ack.store(both(FEATURE_CLIP_ACK), Ordering::SeqCst);
+                self.raw_file_chunks.store(both(FEATURE_RAW_FILE_CHUNKS), Ordering::SeqCst);
+                self.zstd_clip_text.store(both(FEATURE_ZSTD_CLIP_TEXT), Ordering::SeqCst);
+                self.trust_ack.store(both(FEATURE_TRUST_ACK), Ordering::SeqCst);
+                *self.peer_hello.lock().unwrap() = Some((identity_pk.clone(), nonce));
+                let ephemeral = self.ephemeral.lock().unwrap().take();
+                *self.session_key.lock().unwrap() = match (ephemeral, peer_ephemeral) {
+                    (Some(ours), Some(theirs)) => {
+                        let Some(shared) = ours.diffie_hellman(&theirs) else {
+                            self.conn.close();
+                            anyhow::bail!("weak x25519 key from {peer_id}");
+                        };
+                        // Our own key echoed back would put both directions on one nonce sequence.
+                        if ours.public_key() == theirs {
+                            self.conn.close();
+                            anyhow::bail!("{peer_id} sent our own x25519 key");
+                        }
+                        let key = derive_session_key(&shared, &ours.public_key(), &theirs);
+                        if both(FEATURE_ENCRYPTED_PAYLOADS) {
+                            let send_direction = u8::from(ours.public_key() > theirs);
+                            *self.payload_cipher.lock().unwrap() = Some(PayloadCipher::new(&key, send_direction));
+                        }
+                        Some(key)
+                    }
+                    _ => None,
+                };
+
+                Ok(HandshakeResult { peer_id, identity_pk, encrypted: self.encryption_enabled() })
+            }
+            _ => {
+                self.conn.close();
+                anyhow::bail!("expected Hello message, got {:?}", msg.msg_type());
+            }
+        }
+    }
+
+    pub async fn send_clipboard(&self) -> Result<()> {
+        let content = self.clipboard.read()?;
+        let msg = match content {
+            ClipboardContent::Empty => return Ok(()),
+            ClipboardContent::Text(text) => return self.send_clip_text(text).await,
+            ClipboardContent::Image { mime, width, height, bytes } => Message::ClipImage {
+                mime,
+                width,
+                height,
+                bytes_b64: Base64Alphabet::Standard.encode(&bytes),
+                ts_ms: now_ms(),
+            },
+        };
+        self.send_message(&msg).await
+    }
+
+    pub async fn receive_clipboard(&self) -> Result<()> {
+        let frame = self.open_frame(self.conn.recv().await?)?;
+        let payload = self.codec().decode(&frame.payload)?;
+        match payload {
+            Message::ClipText { text, .. } => {
+                self.clipboard.write(ClipboardContent::Text(text))?;
+            }
+            Message::ClipTextCompressed { data_b64, .. } => {
+                self.clipboard.write(ClipboardContent::Text(decompress_clip_text(&data_b64)?))?;
+            }
+            Message::ClipImage { mime, width, height, bytes_b64, .. } => {
+                let bytes = Base64Alphabet::Standard.decode(&bytes_b64)?;
+                self.clipboard.write(ClipboardContent::Image { mime, width, height, bytes })?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Tell the peer about this device. The name is the one set via `with_origin`.
+    /// A v0 peer doesn't know `DeviceInfo`, so it is told nothing.
+    pub async fn send_device_info(&self, platform: &str, app_version: &str) -> Result<()> {
+        if self.peer_is_v0() {
+            return Ok(());
+        }
+        let msg = Message::DeviceInfo {
+            name: self.origin.clone().unwrap_or_default(),
+            platform: platform.into(),
+            app_version: app_version.into(),
+        };
+        self.send_message(&msg).await
+    }
+
+    /// Tell the peer this session is being closed on purpose. A v0 peer
+    /// doesn't know `Bye`, so it is told nothing.
+    pub async fn send_bye(&self, reason: &str) -> Result<()> {
+        if self.sends_v0_only() {
+            return Ok(());
+        }
+        self.send_message(&Message::Bye { reason: reason.into() }).await
+    }
+
+    /// Like `send_bye`, through the send queue. Returns whether it was queued:
+    /// protocol v0 has no `Bye`, so nothing is, and `on_sent` never runs.
+    pub fn enqueue_bye(&self, reason: &str, on_sent: Option<OnSent>) -> bool {
+        if self.sends_v0_only() {
+            return false;
+        }
+        self.enqueue(Message::Bye { reason: reason.into() }, on_sent);
+        true
+    }
+
+    pub async fn send_file_offer(&self, file_id: &str, name: &str, size: u64, mime: &str) -> Result<()> {
+        self.send_file_offer_with_hash(file_id, name, size, mime, None).await
+    }
+
+    /// Offer a file along with the SHA-256 hex of its content, so the receiver
+    /// can skip one it already has and check the transfer as it completes.
+    pub async fn send_file_offer_with_hash(&self, file_id: &str, name: &str, size: u64, mime: &str, hash: Option<&str>) -> Result<()> {
+        let msg = Message::FileOffer {
+            file_id: file_id.into(),
+            name: name.into(),
+            size,
+            mime: mime.into(),
+            hash: hash.map(Into::into),
+        };
+        self.send_message(&msg).await
+    }
+
+    pub async fn send_file_accept(&self, file_id: &str) -> Result<()> {
+        self.send_message(&Message::FileAccept { file_id: file_id.into() }).await
+    }
+
+    pub async fn send_file_reject(&self, file_id: &str, reason: &str) -> Result<()> {
+        self.send_message(&Message::FileReject { file_id: file_id.into(), reason: reason.into() }).await
+    }
+
+    pub async fn send_file_chunk(&self, file_id: &str, offset: u64, data: &[u8]) -> Result<()> {
+        let msg = Message::FileChunk {
+            file_id: file_id.into(),
+            offset,
+            data_b64: Base64Alphabet::Standard.encode(data),
+        };
+        self.send_message(&msg).await
+    }
+
+    /// Like `send_file_chunk`, with `data` carried as-is in the frame rather
+    /// than base64 in the codec's encoding. The peer must understand
+    /// `FileChunkRaw`; see [`Session::raw_file_chunks_enabled`].
+    pub async fn send_file_chunk_raw(&self, file_id: &str, offset: u64, data: &[u8]) -> Result<()> {
+        self.send_message(&Message::FileChunkRaw { file_id: file_id.into(), offset, data: data.to_vec() }).await
+    }
+
+    /// Send `data` as chunks of `file_id` starting at `offset`, cut per
+    /// `config` and paced by the file rate limiters. Up to `config.window`
+    /// chunks are prepared while earlier ones are still going out; they are
+    /// sent in order either way. Raw chunks are used if negotiated.
+    pub async fn send_file_data(&self, file_id: &str, offset: u64, data: &[u8], config: &FileTransferConfig) -> Result<()> {
+        anyhow::ensure!(
+            (1..=MAX_PAYLOAD_LEN / 2).contains(&config.chunk_size),
+            "file chunk size must be between 1 and {} bytes, not {}",
+            MAX_PAYLOAD_LEN / 2,
+            config.chunk_size
+        );
+        let window = Arc::new(tokio::sync::Semaphore::new(config.window.max(1)));
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let raw = self.raw_file_chunks_enabled();
+
+        let send_window = Arc::clone(&window);
+        let prepare = async move {
+            let mut offset = offset;
+            for chunk in data.chunks(config.chunk_size) {
+                // Closed once the send side has failed.
+                let Ok(permit) = Arc::clone(&window).acquire_owned().await else { return };
+                self.pace_file_chunk(chunk.len()).await;
+                let msg = if raw {
+                    Message::FileChunkRaw { file_id: file_id.into(), offset, data: chunk.to_vec() }
+                } else {
+                    Message::FileChunk {
+                        file_id: file_id.into(),
+                        offset,
+                        data_b64: Base64Alphabet::Standard.encode(chunk),
+                    }
+                };
+                if tx.send((msg, permit)).is_err() {
+                    return;
+                }
+                offset += chunk.len() as u64;
+            }
+        };
+        let send = async move {
+            while let Some((msg, _permit)) = rx.recv().await {
+                if let Err(e) = self.send_message(&msg).await {
+                    send_window.close();
+                    return Err(e);
+                }
+            }
+            Ok(())
+        };
+        tokio::join!(prepare, send).1
+    }
+
+    pub async fn send_file_done(&self, file_id: &str, hash: &str) -> Result<()> {
+        self.send_message(&Message::FileDone { file_id: file_id.into(), hash: hash.into() }).await
+    }
+
+    /// Send the files at `paths`, interleaved: up to `max_concurrent_files`
+    /// are offered at a time and take turns sending a chunk each, so a small
+    /// file isn't stuck behind a large one; the next path is offered as one
+    /// finishes. Each file is offered under its SHA-256, waits for the
+    /// receiver's `FileAccept`, picks up where a cut-off earlier send left off,
+    /// and ends with a `FileDone` carrying that hash. Files are read a chunk at
+    /// a time, never whole, cut per [`Session::with_file_transfer_config`].
+    /// The receiver is read throughout, so a file it declines mid-transfer
+    /// gets no more chunks; once every file is done a `Ping` confirms it has
+    /// read them all. Returns the files the receiver declined, at offer or
+    /// later; the rest were sent.
+    pub async fn send_files(&self, paths: &[std::path::PathBuf]) -> Result<Vec<crate::FileRejected>> {
+        use anyhow::Context as _;
+        use tokio::io::{AsyncReadExt as _, AsyncSeekExt as _};
+
+        struct Outgoing {
+            name: String,
+            file_id: String,
+            file: tokio::fs::File,
+            size: u64,
+            offset: u64,
+        }
+
+        /// Drop `file_id` from the send, if it is still one of ours.
+        fn decline(
+            offered: &mut std::collections::HashMap<String, String>,
+            sending: &mut std::collections::VecDeque<Outgoing>,
+            rejected: &mut Vec<crate::FileRejected>,
+            file_id: String,
+            reason: String,
+        ) {
+            let Some(name) = offered.remove(&file_id) else { return };
+            sending.retain(|f| f.file_id != file_id);
+            rejected.push(crate::FileRejected { name, reason });
+        }
+
+        async fn next(inbox: &mut tokio::sync::mpsc::UnboundedReceiver<Result<Message>>) -> Result<Message> {
+            inbox.recv().await.unwrap_or_else(|| Err(anyhow::anyhow!("connection closed")))
+        }
+
+        let chunk_size = self.file_transfer.chunk_size;
+        anyhow::ensure!(
+            (1..=MAX_PAYLOAD_LEN / 2).contains(&chunk_size),
+            "file chunk size must be between 1 and {} bytes, not {chunk_size}",
+            MAX_PAYLOAD_LEN / 2
+        );
+
+        // One reader for the whole send, so no frame is cut off by a read
+        // being dropped; it stops, between frames, at the closing `Pong`.
+        let ping_ts = now_ms();
+        let (inbox_tx, mut inbox) = tokio::sync::mpsc::unbounded_channel();
+        let reader = async move {
+            loop {
+                let msg = self.recv_message().await;
+                let last = match &msg {
+                    Ok(Message::Pong { ts_ms }) => *ts_ms == ping_ts,
+                    Ok(_) => false,
+                    Err(_) => true,
+                };
+                if inbox_tx.send(msg).is_err() || last {
+                    break;
+                }
+            }
+            std::future::pending().await
+        };
+
+        let send = async {
+            let mut queued = paths.iter();
+            // Offered and not declined, by file id.
+            let mut offered = std::collections::HashMap::new();
+            let mut sending: std::collections::VecDeque<Outgoing> = std::collections::VecDeque::new();
+            let mut rejected = Vec::new();
+            let mut buf = vec![0u8; chunk_size];
+            loop {
+                while let Ok(msg) = inbox.try_recv() {
+                    if let Message::FileReject { file_id, reason } = msg? {
+                        decline(&mut offered, &mut sending, &mut rejected, file_id, reason);
+                    }
+                }
+                while sending.len() < self.max_concurrent_files {
+                    let Some(path) = queued.next() else { break };
+                    let to_hash = path.clone();
+                    let (size, hash) = tokio::task::spawn_blocking(move || hash_file(&to_hash)).await??;
+                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file.bin").to_string();
+                    anyhow::ensure!(!sending.iter().any(|f| f.file_id == hash), "{name} is already being sent");
+                    let mut file = tokio::fs::File::open(path).await.with_context(|| format!("open {}", path.display()))?;
+                    self.send_file_offer_with_hash(&hash, &name, size, crate::mime_for_name(&name), Some(&hash)).await?;
+                    offered.insert(hash.clone(), name.clone());
+
+                    // Files still sending may be declined (say, a corrupted
+                    // chunk) while this one waits for its answer.
+                    let answer = async {
+                        loop {
+                            match next(&mut inbox).await? {
+                                Message::FileAccept { file_id } if file_id == hash => return Ok(true),
+                                Message::FileReject { file_id, reason } => {
+                                    let this = file_id == hash;
+                                    decline(&mut offered, &mut sending, &mut rejected, file_id, reason);
+                                    if this {
+                                        return Ok(false);
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    };
+                    let accepted: Result<bool> = tokio::time::timeout(FILE_OFFER_TIMEOUT, answer)
+                        .await
+                        .map_err(|_| anyhow::anyhow!("no answer to the offer of {name} within {FILE_OFFER_TIMEOUT:?}"))?;
+                    if !accepted? {
+                        continue;
+                    }
+
+                    // A v0 peer, or one that doesn't answer, gets the whole file.
+                    let offset = if self.peer_is_v0() {
+                        0
+                    } else {
+                        self.send_file_resume_query(&hash).await?;
+                        let resume = async {
+                            loop {
+                                match next(&mut inbox).await? {
+                                    Message::FileResumeOffset { file_id, offset } if file_id == hash => return Ok(offset),
+                                    Message::FileReject { file_id, reason } => {
+                                        decline(&mut offered, &mut sending, &mut rejected, file_id, reason);
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        };
+                        let resume: Result<Result<u64>, _> = tokio::time::timeout(RESUME_QUERY_TIMEOUT, resume).await;
+                        match resume {
+                            Ok(offset) => offset?.min(size),
+                            Err(_) => 0,
+                        }
+                    };
+                    if !offered.contains_key(&hash) {
+                        continue;
+                    }
+                    file.seek(std::io::SeekFrom::Start(offset)).await.with_context(|| format!("seek {}", path.display()))?;
+                    sending.push_back(Outgoing { name, file_id: hash, file, size, offset });
+                }
+                let Some(mut f) = sending.pop_front() else { break };
+
+                let len = (f.size - f.offset).min(chunk_size as u64) as usize;
+                if len == 0 {
+                    self.send_file_done(&f.file_id, &f.file_id).await?;
+                    continue;
+                }
+                let chunk = &mut buf[..len];
+                f.file.read_exact(chunk).await.with_context(|| format!("read {}", f.name))?;
+                self.pace_file_chunk(len).await;
+                if self.raw_file_chunks_enabled() {
+                    self.send_file_chunk_raw(&f.file_id, f.offset, chunk).await?;
+                } else {
+                    self.send_file_chunk(&f.file_id, f.offset, chunk).await?;
+                }
+                f.offset += len as u64;
+                sending.push_back(f);
+            }
+
+            // The receiver answers only after reading every file, so any
+            // file it declines at `FileDone` is known by its `Pong`.
+            self.send_ping(ping_ts).await?;
+            let confirmed = async {
+                loop {
+                    match next(&mut inbox).await? {
+                        Message::Pong { ts_ms } if ts_ms == ping_ts => return Ok(()),
+                        Message::FileReject { file_id, reason } => {
+                            decline(&mut offered, &mut sending, &mut rejected, file_id, reason);
+                        }
+                        _ => {}
+                    }
+                }
+            };
+            let confirmed: Result<()> = tokio::time::timeout(FILES_CONFIRM_TIMEOUT, confirmed)
+                .await
+                .map_err(|_| anyhow::anyhow!("peer did not confirm the files within {FILES_CONFIRM_TIMEOUT:?}"))?;
+            confirmed?;
+            Ok(rejected)
+        };
+
+        tokio::select! {
+            res = send => res,
+            res = reader => res,
+        }
+    }
+
+    /// Ask where to continue `file_id` after a dropped connection; the peer
+    /// answers with `FileResumeOffset`.
+    pub async fn send_file_resume_query(&self, file_id: &str) -> Result<()> {
+        self.send_message(&Message::FileResumeQuery { file_id: file_id.into() }).await
+    }
+
+    pub async fn send_file_resume_offset(&self, file_id: &str, offset: u64) -> Result<()> {
+        self.send_message(&Message::FileResumeOffset { file_id: file_id.into(), offset }).await
+    }
+
+    /// Ask the receiver how much of `file_id` it already has and wait, up to
+    /// `timeout`, for its `FileResumeOffset`: where to resume sending from.
+    /// Anything else it sends meanwhile is dropped.
+    pub async fn query_resume_offset(&self, file_id: &str, timeout: Duration) -> Result<u64> {
+        self.send_file_resume_query(file_id).await?;
+        let answer = async {
+            loop {
+                if let Message::FileResumeOffset { file_id: id, offset } = self.recv_message().await?
+                    && id == file_id
+                {
+                    return Ok(offset);
+                }
+            }
+        };
+        tokio::time::timeout(timeout, answer).await.map_err(|_| anyhow::anyhow!("no resume offset for {file_id} within {timeout:?}"))?
+    }
+
+    pub async fn send_ping(&self, ts_ms: u64) -> Result<()> {
+        self.send_message(&Message::Ping { ts_ms }).await
+    }
+
+    /// Send a `Ping` and wait, up to `timeout`, for the peer's `Pong`: the peer
+    /// has then read everything sent before it. Anything else it sends
+    /// meanwhile is dropped, so only use this once a transfer is done.
+    pub async fn confirm_delivery(&self, timeout: Duration) -> Result<()> {
+        let ts_ms = now_ms();
+        self.send_ping(ts_ms).await?;
+        tokio::time::timeout(timeout, async {
+            loop {
+                if let Message::Pong { ts_ms: echoed } = self.recv_message().await?
+                    && echoed == ts_ms
+                {
+                    return Ok(());
+                }
+            }
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("peer did not confirm delivery within {timeout:?}"))?
+    }
+
+    /// Answer a `Ping`, echoing its `ts_ms`.
+    pub async fn send_pong(&self, ts_ms: u64) -> Result<()> {
+        self.send_message(&Message::Pong { ts_ms }).await
+    }
+
+    /// Send the local trust list and revocations to a linked device.
+    pub async fn send_trust_sync(&self, records: Vec<TrustSyncRecord>, revoked: Vec<TrustSyncRevocation>) -> Result<()> {
+        self.send_message(&Message::TrustSync { records, revoked }).await
+    }
+
+    /// Ask the peer to describe its protocol; it answers with `Message::Describe`.
+    pub async fn send_describe_request(&self) -> Result<()> {
+        self.send_message(&Message::DescribeRequest).await
+    }
+
+    /// Answer a `DescribeRequest` with [`Session::protocol_descriptor`].
+    pub async fn send_describe(&self) -> Result<()> {
+        self.send_message(&Message::Describe { descriptor: self.protocol_descriptor() }).await
+    }
+
+    /// The protocol this session speaks, with the codec and features its
+    /// handshake negotiated.
+    pub fn protocol_descriptor(&self) -> ProtocolDescriptor {
+        let features = FEATURES.into_iter().filter(|f| self.feature_enabled(f)).map(String::from).collect::<Vec<_>>();
+        protocol_descriptor(self.codec(), &features)
+    }
+
+    /// Tell the peer whether we trust it, signed over its HELLO nonce so the
+    /// ack can't be replayed into another session. Only after a handshake
+    /// that negotiated trust acks.
+    pub async fn send_trust_ack(&self, trusted: bool) -> Result<()> {
+        let Some((_, peer_nonce)) = self.peer_hello.lock().unwrap().clone() else {
+            anyhow::bail!("trust ack before handshake");
+        };
+        let sig = self.identity.sign(&trust_ack_transcript(trusted, &peer_nonce));
+        let sig_b64 = Base64Alphabet::Standard.encode(sig);
+        self.send_message(&Message::TrustAck { trusted, sig_b64 }).await
+    }
+
+    /// Wait up to `timeout` for the peer's `TrustAck`, which must be the first
+    /// message after the handshake, and check its signature. Returns whether
+    /// the peer trusts us.
+    pub async fn recv_trust_ack(&self, timeout: Duration) -> Result<bool> {
+        let msg = tokio::time::timeout(timeout, self.recv_message())
+            .await
+            .map_err(|_| anyhow::anyhow!("no trust ack within {timeout:?}"))??;
+        let Message::TrustAck { trusted, sig_b64 } = msg else {
+            anyhow::bail!("expected TrustAck, got {:?}", msg.msg_type());
+        };
+        let Some((peer_pk, _)) = self.peer_hello.lock().unwrap().clone() else {
+            anyhow::bail!("trust ack before handshake");
+        };
+        let sig = Base64Alphabet::Standard.decode(&sig_b64)?;
+        let transcript = trust_ack_transcript(trusted, &*self.hello_nonce.lock().unwrap());
+        let verified = self
+            .handshake_limiter
+            .run(move || Ed25519Identity::verify_with_public_key(&transcript, &sig, &peer_pk))
+            .await;
+        if !verified {
+            anyhow::bail!("invalid trust ack signature");
+        }
+        Ok(trusted)
+    }
+
+    /// Exchange trust acks after a handshake that negotiated them: say we
+    /// trust the peer, then fail with [`HandshakeError::NotTrustedByPeer`]
+    /// unless it says the same. Does nothing when trust acks weren't negotiated.
+    pub async fn confirm_mutual_trust(&self, timeout: Duration) -> Result<()> {
+        if !self.trust_ack_enabled() {
+            return Ok(());
+        }
+        self.send_trust_ack(true).await?;
+        if !self.recv_trust_ack(timeout).await? {
+            self.conn.close();
+            return Err(handshake_error(HandshakeError::NotTrustedByPeer, "peer does not trust this device".into()));
+        }
+        Ok(())
+    }
+
+    /// Confirm that the clip completed by the frame with `seq` was applied.
+    pub async fn send_clip_ack(&self, seq: u64) -> Result<()> {
+        self.send_message(&Message::ClipAck { ack_of_seq: seq }).await
+    }
+
+    /// The `ts_ms` of the sent clip a `ClipAck { ack_of_seq }` confirms, once;
+    /// `None` for unknown or already confirmed ones.
+    pub fn take_acked_clip(&self, ack_of_seq: u64) -> Option<u64> {
+        let mut unacked = self.unacked_clips.lock().unwrap();
+        let pos = unacked.iter().position(|(seq, _)| *seq == ack_of_seq)?;
+        unacked.remove(pos).map(|(_, ts_ms)| ts_ms)
+    }
+
+    /// Send clipboard text, splitting it into chunks if it is larger than
+    /// `CLIP_TEXT_CHUNK_SIZE`.
+    pub async fn send_clip_text(&self, text: String) -> Result<()> {
+        for msg in self.clip_text_messages(text, now_ms()) {
+            self.send_message(&msg).await?;
+        }
+        Ok(())
+    }
+
+    /// Like `send_clip_text`, but through the send queue.
+    pub fn enqueue_clip_text(&self, text: String) {
+        self.enqueue_clip_text_at(text, now_ms());
+    }
+
+    /// Like `enqueue_clip_text`, stamped `ts_ms` instead of now, e.g. to
+    /// resend a clip with the time it was originally copied.
+    pub fn enqueue_clip_text_at(&self, text: String, ts_ms: u64) {
+        for msg in self.clip_text_messages(text, ts_ms) {
+            self.enqueue(msg, None);
+        }
+    }
+
+    fn clip_text_messages(&self, text: String, ts_ms: u64) -> Vec<Message> {
+        let mime = "text/plain";
+        if self.sends_v0_only() {
+            return vec![Message::ClipText { mime: mime.into(), text, ts_ms, origin: None }];
+        }
+        if let Some(msg) = self.compressed_clip_text(&text, ts_ms) {
+            return vec![msg];
+        }
+        if text.len() <= CLIP_TEXT_CHUNK_SIZE {
+            return vec![Message::ClipText { mime: mime.into(), text, ts_ms, origin: self.origin.clone() }];
+        }
+        let clip_id = format!("clip-{:016x}", rand::random::<u64>());
+        chunk_clip_text(&clip_id, mime, &text, ts_ms, self.origin.as_deref(), CLIP_TEXT_CHUNK_SIZE)
+    }
+
+    /// `text` as a `ClipTextCompressed`, when negotiated, over the threshold,
+    /// and small enough compressed for one frame; otherwise `None`.
+    fn compressed_clip_text(&self, text: &str, ts_ms: u64) -> Option<Message> {
+        let threshold = self.compression_threshold?;
+        if !self.compression_enabled() || text.len() <= threshold || text.len() > MAX_PAYLOAD_LEN {
+            return None;
+        }
+        let data = zstd::bulk::compress(text.as_bytes(), 0).ok()?;
+        if data.len() >= text.len() || data.len() > CLIP_TEXT_CHUNK_SIZE {
+            return None;
+        }
+        Some(Message::ClipTextCompressed {
+            mime: "text/plain".into(),
+            data_b64: Base64Alphabet::Standard.encode(data),
+            ts_ms,
+            origin: self.origin.clone(),
+        })
+    }
+
+    /// Pace file chunks, queued or sent with `pace_file_chunk`, under every
+    /// one of `limiters`, e.g. a per-peer and a global one. None (the default)
+    /// sends them as fast as the connection takes them.
+    pub fn set_file_rate_limiters(&self, limiters: Vec<Arc<RateLimiter>>) {
+        *self.file_rate_limiters.lock().unwrap() = limiters;
+    }
+
+    /// Wait until `bytes` more of a file may go out under the file rate
+    /// limiters. For send loops that call `send_file_chunk*` directly; queued
+    /// chunks are paced by `flush_send_queue`.
+    pub async fn pace_file_chunk(&self, bytes: usize) {
+        let wait = reserve_all(&self.file_rate_limiters.lock().unwrap(), bytes as u64);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Queue `msg` for `run_send_queue` in its default lane (see [`SendPriority::of`]).
+    pub fn enqueue(&self, msg: Message, on_sent: Option<OnSent>) {
+        let priority = SendPriority::of(&msg);
+        self.enqueue_with_priority(msg, priority, on_sent);
+    }
+
+    /// Like `enqueue`, in the given lane. A message protocol v0 doesn't know
+    /// is dropped, `on_sent` unrun, when only v0 may be sent (see [`Self::peer_is_v0`]).
+    pub fn enqueue_with_priority(&self, msg: Message, priority: SendPriority, on_sent: Option<OnSent>) {
+        if self.sends_v0_only() && !msg.msg_type().in_v0() {
+            return;
+        }
+        self.send_queue.lock().unwrap().push(msg, priority, on_sent);
+        self.send_ready.notify_one();
+    }
 
-                // Check trust if trust store is configured and not in pairing mode.
-                if let Some(ref store) = self.trust_store {
-                    if !self.pairing_mode {
-                        let Some(rec) = store.get(&peer_id)? else {
-                            self.conn.close();
-                            anyhow::bail!("untrusted peer: {}", peer_id);
-                        };
-                        if rec.identity_pk != identity_pk {
-                            self.conn.close();
-                            anyhow::bail!("trusted peer public key mismatch: {}", peer_id);
-                        }
+    /// The session's send task: send queued messages as they arrive, highest
+    /// priority first. Only returns if a send fails.
+    pub async fn run_send_queue(&self) -> Result<()> {
+        loop {
+            self.flush_send_queue().await?;
+            self.send_ready.notified().await;
+        }
+    }
+
+    /// Send everything queued, including messages queued meanwhile, highest
+    /// priority first. File chunks are paced by the file rate limiters; while
+    /// one waits its turn, newly queued higher-priority messages still go out.
+    pub async fn flush_send_queue(&self) -> Result<()> {
+        loop {
+            let next = self.send_queue.lock().unwrap().pop();
+            let Some((msg, on_sent)) = next else { return Ok(()) };
+            if let Some(bytes) = file_chunk_len(&msg) {
+                let due = *self.file_chunk_due.lock().unwrap().get_or_insert_with(|| {
+                    tokio::time::Instant::now() + reserve_all(&self.file_rate_limiters.lock().unwrap(), bytes)
+                });
+                if tokio::time::Instant::now() < due {
+                    self.send_queue.lock().unwrap().push_front(msg, SendPriority::Bulk, on_sent);
+                    tokio::select! {
+                        () = tokio::time::sleep_until(due) => {}
+                        () = self.send_ready.notified() => {}
                     }
+                    continue;
                 }
-
-                Ok(HandshakeResult { peer_id, identity_pk })
+                *self.file_chunk_due.lock().unwrap() = None;
             }
-            _ => {
-                self.conn.close();
-                anyhow::bail!("expected Hello message, got {:?}", msg.msg_type());
+            self.send_message(&msg).await?;
+            if let Some(on_sent) = on_sent {
+                on_sent();
             }
         }
     }
 
-    pub async fn send_clipboard(&self) -> Result<()> {
-        let content = self.clipboard.read()?;
-        let msg = match content {
-            ClipboardContent::Empty => return Ok(()),
-            ClipboardContent::Text(text) => Message::ClipText {
-                mime: "text/plain".into(),
-                text,
-                ts_ms: now_ms(),
-            },
-            ClipboardContent::Image { mime, width, height, bytes } => Message::ClipImage {
-                mime,
-                width,
-                height,
-                bytes_b64: base64::engine::general_purpose::STANDARD.encode(&bytes),
-                ts_ms: now_ms(),
-            },
-        };
-        self.send_message(&msg).await
+    /// Receive the next message. Chunked clipboard text is reassembled and returned
+    /// as a single `Message::ClipText`.
+    pub async fn recv_message(&self) -> Result<Message> {
+        self.recv_message_with_seq().await.map(|(msg, _)| msg)
     }
 
-    pub async fn receive_clipboard(&self) -> Result<()> {
-        let frame = self.conn.recv().await?;
-        let payload: Message = serde_json::from_slice(&frame.payload)?;
-        match payload {
-            Message::ClipText { text, .. } => {
-                self.clipboard.write(ClipboardContent::Text(text))?;
+    /// Like `recv_message`, also returning the `seq` of the frame that completed
+    /// the message (what a `ClipAck` refers to).
+    pub async fn recv_message_with_seq(&self) -> Result<(Message, u64)> {
+        loop {
+            let frame = self.open_frame(self.conn.recv().await?)?;
+            if self.v0_compat && !MsgType::from_u8(frame.msg_type).is_ok_and(MsgType::in_v0) {
+                anyhow::bail!("unknown MsgType for protocol v0: {}", frame.msg_type);
             }
-            Message::ClipImage { mime, width, height, bytes_b64, .. } => {
-                let bytes = base64::engine::general_purpose::STANDARD.decode(&bytes_b64)?;
-                self.clipboard.write(ClipboardContent::Image { mime, width, height, bytes })?;
+            let msg = match decode_payload(self.codec(), frame.msg_type, &frame.payload)? {
+                Message::ClipTextCompressed { mime, data_b64, ts_ms, origin } => {
+                    Message::ClipText { mime, text: decompress_clip_text(&data_b64)?, ts_ms, origin }
+                }
+                msg => msg,
+            };
+            if let Some(msg) = self.clip_assembler.lock().unwrap().push(msg)? {
+                return Ok((msg, frame.seq));
             }
-            _ => {}
         }
-        Ok(())
     }
 
-    pub async fn send_file_offer(&self, file_id: &str, name: &str, size: u64, mime: &str) -> Result<()> {
-        let msg = Message::FileOffer {
-            file_id: file_id.into(),
-            name: name.into(),
-            size,
-            mime: mime.into(),
+    async fn send_message(&self, msg: &Message) -> Result<()> {
+        if self.sends_v0_only() && !msg.msg_type().in_v0() {
+            anyhow::bail!("{:?} is not part of protocol v0", msg.msg_type());
+        }
+        // HELLO is always JSON: it is how the codec gets negotiated.
+        let codec = if matches!(msg, Message::Hello { .. }) { Codec::Json } else { self.codec() };
+        let payload = encode_payload(codec, msg)?;
+        let _order = self.send_order.lock().await;
+        let seq = self.next_seq();
+        if self.clip_ack_enabled() {
+            self.note_sent_clip(msg, seq);
+        }
+        let sealed = match self.payload_cipher.lock().unwrap().as_ref() {
+            Some(cipher) => Some(cipher.seal(seq, msg.msg_type() as u8, &payload)?),
+            None => None,
         };
-        self.send_message(&msg).await
-    }
-
-    pub async fn send_file_accept(&self, file_id: &str) -> Result<()> {
-        self.send_message(&Message::FileAccept { file_id: file_id.into() }).await
+        let frame = match sealed {
+            Some(sealed) => Frame::new(MsgType::Encrypted, MsgType::Encrypted.stream_id(), seq, sealed),
+            None => Frame::new(msg.msg_type(), msg.stream_id(), seq, payload),
+        };
+        self.conn.send(frame).await
     }
 
-    pub async fn send_file_chunk(&self, file_id: &str, offset: u64, data: &[u8]) -> Result<()> {
-        let msg = Message::FileChunk {
-            file_id: file_id.into(),
-            offset,
-            data_b64: base64::engine::general_purpose::STANDARD.encode(data),
+    /// The frame inside `frame` once payloads are encrypted, refusing
+    /// plaintext frames, ones that fail authentication, and replays (a `seq`
+    /// at or below one already opened). Without encryption, `frame` itself.
+    fn open_frame(&self, frame: Frame) -> Result<Frame> {
+        let cipher = self.payload_cipher.lock().unwrap();
+        let Some(cipher) = cipher.as_ref() else { return Ok(frame) };
+        if frame.msg_type != MsgType::Encrypted as u8 {
+            anyhow::bail!("plaintext frame of type {} on an encrypted session", frame.msg_type);
+        }
+        let (msg_type, payload) = cipher.open(frame.seq, &frame.payload)?;
+        // The floor moves past `seq`; the last seq would leave nowhere to go.
+        let Some(next_seq) = frame.seq.checked_add(1) else {
+            anyhow::bail!("frame seq {} leaves no room for a next frame", frame.seq);
         };
-        self.send_message(&msg).await
+        if self.recv_seq_floor.fetch_max(next_seq, Ordering::SeqCst) > frame.seq {
+            anyhow::bail!("replayed frame {}", frame.seq);
+        }
+        Ok(Frame { msg_type, stream_id: MsgType::from_u8(msg_type)?.stream_id() as u32, payload, ..frame })
     }
 
-    pub async fn send_file_done(&self, file_id: &str, hash: &str) -> Result<()> {
-        self.send_message(&Message::FileDone { file_id: file_id.into(), hash: hash.into() }).await
+    /// Remember a clip about to go out as `seq`, so its `ClipAck` can be matched.
+    fn note_sent_clip(&self, msg: &Message, seq: u64) {
+        let ts_ms = match msg {
+            Message::ClipText { ts_ms, .. } | Message::ClipTextCompressed { ts_ms, .. } => *ts_ms,
+            Message::ClipTextBegin { clip_id, ts_ms, .. } => {
+                self.chunked_clip_ts.lock().unwrap().insert(clip_id.clone(), *ts_ms);
+                return;
+            }
+            Message::ClipTextEnd { clip_id } => match self.chunked_clip_ts.lock().unwrap().remove(clip_id) {
+                Some(ts_ms) => ts_ms,
+                None => return,
+            },
+            _ => return,
+        };
+        let mut unacked = self.unacked_clips.lock().unwrap();
+        unacked.push_back((seq, ts_ms));
+        while unacked.len() > MAX_UNACKED_CLIPS {
+            unacked.pop_front();
+        }
     }
+}
 
-    pub async fn recv_message(&self) -> Result<Message> {
-        let frame = self.conn.recv().await?;
-        let msg: Message = serde_json::from_slice(&frame.payload)?;
-        Ok(msg)
+/// The file bytes `msg` carries, if it is a file chunk.
+fn file_chunk_len(msg: &Message) -> Option<u64> {
+    match msg {
+        Message::FileChunkRaw { data, .. } => Some(data.len() as u64),
+        Message::FileChunk { data_b64, .. } => Some((data_b64.len() / 4 * 3) as u64),
+        _ => None,
     }
+}
 
-    async fn send_message(&self, msg: &Message) -> Result<()> {
-        let payload = serde_json::to_vec(msg)?;
-        let frame = Frame::new(msg.msg_type(), msg.stream_id(), self.next_seq(), payload);
-        self.conn.send(frame).await
+/// The text in a `ClipTextCompressed`, refusing any that would expand past
+/// `MAX_PAYLOAD_LEN` rather than decompressing it whole.
+fn decompress_clip_text(data_b64: &str) -> Result<String> {
+    use std::io::Read as _;
+    let data = Base64Alphabet::Standard.decode(data_b64)?;
+    let mut text = Vec::new();
+    zstd::stream::read::Decoder::new(data.as_slice())?
+        .take(MAX_PAYLOAD_LEN as u64 + 1)
+        .read_to_end(&mut text)?;
+    if text.len() > MAX_PAYLOAD_LEN {
+        anyhow::bail!("compressed clip text expands past {MAX_PAYLOAD_LEN} bytes");
     }
+    Ok(String::from_utf8(text)?)
 }
 
-fn now_ms() -> u64 {
+pub(crate) fn now_ms() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
@@ -315,7 +1560,7 @@ mod tests {
     use crate::clipboard::MockClipboard;
     use crate::identity::{Ed25519Identity, MockIdentity};
     use crate::replay::MemoryReplayProtector;
-    use crate::transport::memory_connection_pair;
+    use crate::transport::{memory_connection_pair, MemoryConnection};
     use crate::trust::MemoryTrustStore;
 
     #[tokio::test]
@@ -334,6 +1579,211 @@ mod tests {
         assert_eq!(session_b.clipboard.read().unwrap(), ClipboardContent::Text("hello world".into()));
     }
 
+    #[tokio::test]
+    async fn queued_clip_text_overtakes_queued_file_chunks() {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let session_a = Session::new(conn_a, MockIdentity::new("a"), MockClipboard::new());
+        let session_b = Session::new(conn_b, MockIdentity::new("b"), MockClipboard::new());
+
+        let chunk = |offset: u64| Message::FileChunk { file_id: "f1".into(), offset, data_b64: "AQID".into() };
+        let sent = Arc::new(AtomicU64::new(0));
+        for offset in 0..3 {
+            let sent = Arc::clone(&sent);
+            session_a.enqueue(chunk(offset), Some(Box::new(move || { sent.fetch_add(1, Ordering::SeqCst); })));
+        }
+        session_a.enqueue_clip_text("urgent".into());
+        session_a.enqueue(chunk(3), None);
+        session_a.flush_send_queue().await.unwrap();
+        assert_eq!(sent.load(Ordering::SeqCst), 3);
+
+        let mut received = Vec::new();
+        for _ in 0..5 {
+            received.push(session_b.recv_message().await.unwrap());
+        }
+        assert!(matches!(&received[0], Message::ClipText { text, .. } if text == "urgent"));
+        assert_eq!(received[1..], [chunk(0), chunk(1), chunk(2), chunk(3)]);
+    }
+
+    #[tokio::test]
+    async fn paced_file_chunks_let_clip_text_through_while_they_wait() {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let session_a = Arc::new(Session::new(conn_a, MockIdentity::new("a"), MockClipboard::new()));
+        let session_b = Session::new(conn_b, MockIdentity::new("b"), MockClipboard::new());
+        session_a.set_file_rate_limiters(vec![Arc::new(RateLimiter::new(Some(2000)))]);
+
+        // Two 1500-byte chunks at 2000/s: the second waits half a second.
+        let chunk = |offset: u64| Message::FileChunk { file_id: "f1".into(), offset, data_b64: "A".repeat(2000) };
+        let start = tokio::time::Instant::now();
+        session_a.enqueue(chunk(0), None);
+        session_a.enqueue(chunk(1500), None);
+        let sender = tokio::spawn({
+            let session_a = Arc::clone(&session_a);
+            async move { session_a.run_send_queue().await }
+        });
+        assert_eq!(session_b.recv_message().await.unwrap(), chunk(0));
+        session_a.enqueue_clip_text("urgent".into());
+        assert!(matches!(session_b.recv_message().await.unwrap(), Message::ClipText { text, .. } if text == "urgent"));
+        assert!(start.elapsed() < Duration::from_millis(400));
+        assert_eq!(session_b.recv_message().await.unwrap(), chunk(1500));
+        assert!(start.elapsed() >= Duration::from_millis(450), "{:?}", start.elapsed());
+        sender.abort();
+    }
+
+    #[tokio::test]
+    async fn send_file_data_pipelines_chunks_in_order() {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let a = Session::new(conn_a, MockIdentity::new("a"), MockClipboard::new());
+        let b = Session::new(conn_b, MockIdentity::new("b"), MockClipboard::new());
+        let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+
+        let config = FileTransferConfig { chunk_size: 1000, window: 4 };
+        a.send_file_data("f1", 500, &data, &config).await.unwrap();
+        let mut received = Vec::new();
+        for i in 0..10u64 {
+            let Message::FileChunk { offset, data_b64, .. } = b.recv_message().await.unwrap() else { panic!("expected FileChunk") };
+            assert_eq!(offset, 500 + i * 1000);
+            received.extend(Base64Alphabet::Standard.decode(data_b64).unwrap());
+        }
+        assert_eq!(received, data);
+
+        for chunk_size in [0, MAX_PAYLOAD_LEN] {
+            let config = FileTransferConfig { chunk_size, window: 1 };
+            assert!(a.send_file_data("f1", 0, &data, &config).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn send_files_interleaves_accepted_files_and_bounds_how_many_are_open() {
+        use crate::protocol::FileAssembler;
+        use sha2::{Digest, Sha256};
+
+        let dir = std::env::temp_dir().join(format!("oc-send-files-{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files = [("big.bin", 300 * 1024), ("small.txt", 1000), ("blocked.exe", 2000), ("medium.bin", 150 * 1024 + 7)];
+        let paths: Vec<_> = files
+            .iter()
+            .map(|(name, len)| {
+                let path = dir.join(name);
+                std::fs::write(&path, (0..*len).map(|i| (i % 251) as u8).collect::<Vec<_>>()).unwrap();
+                path
+            })
+            .collect();
+
+        let (conn_a, conn_b) = memory_connection_pair();
+        let session_a = Session::new(conn_a, MockIdentity::new("a"), MockClipboard::new()).with_max_concurrent_files(2);
+        let session_b = Session::new(conn_b, MockIdentity::new("b"), MockClipboard::new());
+        let sender = tokio::spawn(async move { session_a.send_files(&paths).await });
+
+        // (event, file name) in the order they arrived.
+        let mut events = Vec::new();
+        let mut names = std::collections::HashMap::new();
+        let mut incoming = std::collections::HashMap::new();
+        let mut received = std::collections::HashMap::new();
+        while received.len() < files.len() - 1 {
+            match session_b.recv_message().await.unwrap() {
+                Message::FileOffer { file_id, name, size, mime, hash } => {
+                    assert_eq!(hash.as_deref(), Some(file_id.as_str()));
+                    assert_eq!(mime, crate::mime_for_name(&name));
+                    events.push(("offer", name.clone()));
+                    if name.ends_with(".exe") {
+                        session_b.send_file_reject(&file_id, crate::REJECT_MIME_NOT_ALLOWED).await.unwrap();
+                        continue;
+                    }
+                    incoming.insert(file_id.clone(), FileAssembler::new(name.clone(), size));
+                    names.insert(file_id.clone(), name);
+                    session_b.send_file_accept(&file_id).await.unwrap();
+                }
+                Message::FileResumeQuery { file_id } => session_b.send_file_resume_offset(&file_id, 0).await.unwrap(),
+                Message::FileChunk { file_id, offset, data_b64 } => {
+                    let data = Base64Alphabet::Standard.decode(data_b64).unwrap();
+                    incoming.get_mut(&file_id).unwrap().insert(offset, &data).unwrap();
+                }
+                Message::FileDone { file_id, hash } => {
+                    let data = incoming.remove(&file_id).unwrap().finish().unwrap();
+                    assert_eq!(hash, format!("{:x}", Sha256::digest(&data)));
+                    assert_eq!(hash, file_id);
+                    events.push(("done", names[&file_id].clone()));
+                    received.insert(names[&file_id].clone(), data);
+                }
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+        match session_b.recv_message().await.unwrap() {
+            Message::Ping { ts_ms } => session_b.send_pong(ts_ms).await.unwrap(),
+            other => panic!("unexpected {other:?}"),
+        }
+        let rejected = sender.await.unwrap().unwrap();
+        assert_eq!(rejected, [crate::FileRejected { name: "blocked.exe".into(), reason: crate::REJECT_MIME_NOT_ALLOWED.into() }]);
+
+        for (name, len) in files.into_iter().filter(|(name, _)| *name != "blocked.exe") {
+            assert_eq!(received[name], (0..len).map(|i| (i % 251) as u8).collect::<Vec<_>>(), "{name}");
+        }
+        let at = |event: &str, name: &str| events.iter().position(|e| *e == (event, name.to_string())).unwrap();
+        // The small file finishes while the big one is still going, which
+        // frees the slot the files after it wait for.
+        assert!(at("done", "small.txt") < at("done", "big.bin"), "{events:?}");
+        assert!(at("done", "small.txt") < at("offer", "medium.bin"), "{events:?}");
+        assert!(at("offer", "medium.bin") < at("done", "big.bin"), "{events:?}");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn send_files_stops_a_file_the_receiver_declines_mid_transfer() {
+        let dir = std::env::temp_dir().join(format!("oc-send-files-reject-{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths: Vec<_> = [("first.txt", 3000), ("second.bin", 2 * 1024 * 1024)]
+            .iter()
+            .map(|(name, len)| {
+                let path = dir.join(name);
+                std::fs::write(&path, (0..*len).map(|i| (i % 251) as u8).collect::<Vec<_>>()).unwrap();
+                path
+            })
+            .collect();
+
+        let (conn_a, conn_b) = memory_connection_pair();
+        let config = FileTransferConfig { chunk_size: 1024, window: 1 };
+        let session_a = Session::new(conn_a, MockIdentity::new("a"), MockClipboard::new()).with_file_transfer_config(config);
+        let session_b = Session::new(conn_b, MockIdentity::new("b"), MockClipboard::new());
+        let sender = tokio::spawn(async move { session_a.send_files(&paths).await });
+
+        let mut names = std::collections::HashMap::new();
+        let mut chunks = std::collections::HashMap::<String, usize>::new();
+        let mut done = Vec::new();
+        loop {
+            match session_b.recv_message().await.unwrap() {
+                Message::FileOffer { file_id, name, .. } => {
+                    names.insert(file_id.clone(), name);
+                    session_b.send_file_accept(&file_id).await.unwrap();
+                }
+                Message::FileResumeQuery { file_id } => session_b.send_file_resume_offset(&file_id, 0).await.unwrap(),
+                Message::FileChunk { file_id, data_b64, .. } => {
+                    assert!(Base64Alphabet::Standard.decode(data_b64).unwrap().len() <= config.chunk_size);
+                    let name = &names[&file_id];
+                    let seen = chunks.entry(name.clone()).or_default();
+                    *seen += 1;
+                    // Changed its mind once the second file is under way.
+                    if name == "second.bin" && *seen == 1 {
+                        session_b.send_file_reject(&file_id, crate::REJECT_TOO_LARGE).await.unwrap();
+                    }
+                }
+                Message::FileDone { file_id, .. } => done.push(names[&file_id].clone()),
+                Message::Ping { ts_ms } => {
+                    session_b.send_pong(ts_ms).await.unwrap();
+                    break;
+                }
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+
+        let rejected = sender.await.unwrap().unwrap();
+        assert_eq!(rejected, [crate::FileRejected { name: "second.bin".into(), reason: crate::REJECT_TOO_LARGE.into() }]);
+        assert_eq!(done, ["first.txt"]);
+        assert!(chunks["second.bin"] < 2048, "sent {} chunks of the declined file", chunks["second.bin"]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
     #[tokio::test]
     async fn send_hello() {
         let (conn_a, conn_b) = memory_connection_pair();
@@ -352,17 +1802,18 @@ mod tests {
                 identity_pk_b64,
                 nonce_b64,
                 sig_b64,
+                ..
             } => {
                 assert_eq!(version, crate::protocol::PROTOCOL_VERSION);
                 assert_eq!(peer_id, expected_peer_id);
 
-                let pk = base64::engine::general_purpose::STANDARD.decode(identity_pk_b64).unwrap();
+                let pk = Base64Alphabet::Standard.decode(identity_pk_b64).unwrap();
                 assert_eq!(pk, expected_pk);
 
-                let nonce = base64::engine::general_purpose::STANDARD.decode(nonce_b64).unwrap();
+                let nonce = Base64Alphabet::Standard.decode(nonce_b64).unwrap();
                 assert_eq!(nonce.len(), 32);
 
-                let sig = base64::engine::general_purpose::STANDARD.decode(sig_b64).unwrap();
+                let sig = Base64Alphabet::Standard.decode(sig_b64).unwrap();
                 assert_eq!(sig.len(), 64);
             }
             _ => panic!("expected Hello"),
@@ -384,14 +1835,18 @@ mod tests {
         sig_override: Option<Vec<u8>>,
     ) -> Message {
         let version = crate::protocol::PROTOCOL_VERSION;
-        let transcript = hello_transcript(version, &claimed_peer_id, &presented_pk, &nonce);
+        let transcript = crate::protocol::hello_transcript(version, &claimed_peer_id, &presented_pk, &nonce);
         let sig = sig_override.unwrap_or_else(|| signing_identity.sign(&transcript));
         Message::Hello {
             peer_id: claimed_peer_id,
             version,
-            identity_pk_b64: base64::engine::general_purpose::STANDARD.encode(&presented_pk),
-            nonce_b64: base64::engine::general_purpose::STANDARD.encode(nonce),
-            sig_b64: base64::engine::general_purpose::STANDARD.encode(&sig),
+            identity_pk_b64: Base64Alphabet::Standard.encode(&presented_pk),
+            nonce_b64: Base64Alphabet::Standard.encode(nonce),
+            sig_b64: Base64Alphabet::Standard.encode(&sig),
+            psk: false,
+            codecs: Vec::new(),
+            features: Vec::new(),
+            x25519_pk_b64: None,
         }
     }
 
@@ -409,6 +1864,7 @@ mod tests {
                 identity_pk: bob.public_key_bytes(),
                 display_name: "Bob".into(),
                 created_at: chrono::Utc::now(),
+                sync_direction: Default::default(),
             })
             .unwrap();
 
@@ -419,6 +1875,7 @@ mod tests {
                 identity_pk: alice.public_key_bytes(),
                 display_name: "Alice".into(),
                 created_at: chrono::Utc::now(),
+                sync_direction: Default::default(),
             })
             .unwrap();
 
@@ -430,6 +1887,58 @@ mod tests {
         assert_eq!(result_b.unwrap(), session_a.identity.peer_id());
     }
 
+    /// sha2-256 multihash (`0x12 0x20` + digest), hex.
+    struct MultihashPeerId;
+
+    impl crate::identity::PeerIdScheme for MultihashPeerId {
+        fn derive(&self, public_key: &[u8]) -> String {
+            use sha2::{Digest, Sha256};
+            format!("1220{:x}", Sha256::digest(public_key))
+        }
+    }
+
+    #[tokio::test]
+    async fn handshake_uses_the_configured_peer_id_scheme_on_both_ends() {
+        let scheme: Arc<dyn crate::identity::PeerIdScheme> = Arc::new(MultihashPeerId);
+        let alice = Ed25519Identity::generate().with_peer_id_scheme(scheme.clone());
+        let bob = Ed25519Identity::generate().with_peer_id_scheme(scheme.clone());
+        assert!(alice.peer_id().starts_with("1220"));
+        assert_ne!(alice.peer_id(), Ed25519Identity::peer_id_from_public_key(&alice.public_key_bytes()));
+        assert!(scheme.verify(alice.peer_id(), &alice.public_key_bytes()));
+        assert!(!scheme.verify(alice.peer_id(), &bob.public_key_bytes()));
+
+        let trusting = |peer: &Ed25519Identity| {
+            let store = Arc::new(MemoryTrustStore::new());
+            store
+                .save(crate::trust::TrustRecord {
+                    peer_id: peer.peer_id().to_string(),
+                    identity_pk: peer.public_key_bytes(),
+                    display_name: "peer".into(),
+                    created_at: chrono::Utc::now(),
+                    sync_direction: Default::default(),
+                })
+                .unwrap();
+            store
+        };
+        let (conn_a, conn_b) = memory_connection_pair();
+        let session_a = Session::with_trust(conn_a, alice.clone(), MockClipboard::new(), trusting(&bob));
+        let session_b = Session::with_trust(conn_b, bob.clone(), MockClipboard::new(), trusting(&alice));
+        let (result_a, result_b) = tokio::join!(session_a.handshake(), session_b.handshake());
+        assert_eq!(result_a.unwrap(), bob.peer_id());
+        assert_eq!(result_b.unwrap(), alice.peer_id());
+
+        // A peer deriving its id differently doesn't verify, even with a valid signature.
+        let (conn_a, conn_b) = memory_connection_pair();
+        let plain = Ed25519Identity::from_signing_key(ed25519_dalek::SigningKey::from_bytes(&bob.signing_key_seed_bytes()));
+        let session_a = Session::with_pairing_mode(conn_a, alice, MockClipboard::new(), Arc::new(MemoryTrustStore::new()));
+        let session_b = Session::with_pairing_mode(conn_b, plain, MockClipboard::new(), Arc::new(MemoryTrustStore::new()));
+        let (result_a, _) = tokio::join!(
+            session_a.handshake_with_timeout(Duration::from_millis(500)),
+            session_b.handshake_with_timeout(Duration::from_millis(500)),
+        );
+        assert_eq!(HandshakeError::of(&result_a.unwrap_err()), Some(HandshakeError::BadSignature));
+    }
+
     #[tokio::test]
     async fn handshake_reject_spoofed_peer_id_with_different_public_key() {
         let (conn_a, conn_b) = memory_connection_pair();
@@ -446,6 +1955,7 @@ mod tests {
                 identity_pk: victim.public_key_bytes(),
                 display_name: "Victim".into(),
                 created_at: chrono::Utc::now(),
+                sync_direction: Default::default(),
             })
             .unwrap();
 
@@ -488,6 +1998,7 @@ mod tests {
                 identity_pk: bob.public_key_bytes(),
                 display_name: "Bob".into(),
                 created_at: chrono::Utc::now(),
+                sync_direction: Default::default(),
             })
             .unwrap();
 
@@ -613,4 +2124,538 @@ mod tests {
 
         handle.await.unwrap();
     }
+
+    /// Handshake two pairing-mode sessions with the given PSKs; returns both results.
+    async fn psk_handshake(psk_a: Option<&[u8]>, psk_b: Option<&[u8]>) -> (Result<String>, Result<String>) {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let mut session_a = Session::with_pairing_mode(
+            conn_a,
+            Ed25519Identity::generate(),
+            MockClipboard::new(),
+            Arc::new(MemoryTrustStore::new()),
+        );
+        let mut session_b = Session::with_pairing_mode(
+            conn_b,
+            Ed25519Identity::generate(),
+            MockClipboard::new(),
+            Arc::new(MemoryTrustStore::new()),
+        );
+        if let Some(psk) = psk_a {
+            session_a = session_a.with_psk(psk);
+        }
+        if let Some(psk) = psk_b {
+            session_b = session_b.with_psk(psk);
+        }
+        tokio::join!(
+            session_a.handshake_with_timeout(Duration::from_millis(500)),
+            session_b.handshake_with_timeout(Duration::from_millis(500)),
+        )
+    }
+
+    #[tokio::test]
+    async fn handshake_with_matching_psk_succeeds() {
+        let (a, b) = psk_handshake(Some(b"group-secret"), Some(b"group-secret")).await;
+        assert!(a.is_ok(), "{a:?}");
+        assert!(b.is_ok(), "{b:?}");
+    }
+
+    #[tokio::test]
+    async fn handshake_with_wrong_or_missing_psk_fails_with_psk_mismatch() {
+        let (a, b) = psk_handshake(Some(b"group-secret"), Some(b"other-secret")).await;
+        assert_eq!(HandshakeError::of(&a.unwrap_err()), Some(HandshakeError::PskMismatch));
+        assert_eq!(HandshakeError::of(&b.unwrap_err()), Some(HandshakeError::PskMismatch));
+
+        let (a, b) = psk_handshake(Some(b"group-secret"), None).await;
+        assert_eq!(HandshakeError::of(&a.unwrap_err()), Some(HandshakeError::PskMismatch));
+        assert_eq!(HandshakeError::of(&b.unwrap_err()), Some(HandshakeError::PskMismatch));
+    }
+
+    #[tokio::test]
+    async fn both_ends_derive_the_same_session_key() {
+        let handshake = |v0_compat: bool| async move {
+            let (conn_a, conn_b) = memory_connection_pair();
+            let new_session = |conn| {
+                let s = Session::with_pairing_mode(conn, Ed25519Identity::generate(), MockClipboard::new(), Arc::new(MemoryTrustStore::new()));
+                if v0_compat { s.with_v0_compat() } else { s }
+            };
+            let (a, b) = (new_session(conn_a), new_session(conn_b));
+            let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+            ra.unwrap();
+            rb.unwrap();
+            (a.session_key(), b.session_key())
+        };
+
+        let (a, b) = handshake(false).await;
+        assert!(a.is_some());
+        assert_eq!(a, b);
+        // Fresh ephemeral keys make every session's key different.
+        assert_ne!(handshake(false).await.0, a);
+
+        assert_eq!(handshake(true).await, (None, None));
+    }
+
+    #[tokio::test]
+    async fn handshake_rejects_an_ephemeral_key_outside_the_signature() {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let bob = Ed25519Identity::generate();
+        let session_a = Session::with_pairing_mode(conn_a, Ed25519Identity::generate(), MockClipboard::new(), Arc::new(MemoryTrustStore::new()));
+
+        // Signed without an ephemeral key, then one is slipped in.
+        let mut hello = make_signed_hello(&bob, bob.peer_id().to_string(), bob.public_key_bytes(), [1u8; 32], None);
+        if let Message::Hello { x25519_pk_b64, .. } = &mut hello {
+            *x25519_pk_b64 = Some(Base64Alphabet::Standard.encode(EphemeralX25519::generate().public_key()));
+        }
+
+        let handle = tokio::spawn(async move {
+            let _ = conn_b.recv().await.unwrap();
+            let payload = serde_json::to_vec(&hello).unwrap();
+            conn_b.send(Frame::new(hello.msg_type(), hello.stream_id(), 1, payload)).await.unwrap();
+        });
+
+        let err = session_a.handshake().await.unwrap_err();
+        assert_eq!(HandshakeError::of(&err), Some(HandshakeError::BadSignature));
+        assert_eq!(session_a.session_key(), None);
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn handshake_rejects_a_hello_stripped_of_a_feature() {
+        let (conn_a, relay_a) = memory_connection_pair();
+        let (relay_b, conn_b) = memory_connection_pair();
+        let new_session = |conn| {
+            Session::with_pairing_mode(conn, Ed25519Identity::generate(), MockClipboard::new(), Arc::new(MemoryTrustStore::new()))
+                .with_encryption()
+        };
+        let (a, b) = (new_session(conn_a), new_session(conn_b));
+
+        // On the path from a to b, drop encryption from a's HELLO.
+        let relay = tokio::spawn(async move {
+            let frame = relay_a.recv().await.unwrap();
+            let mut hello: Message = serde_json::from_slice(&frame.payload).unwrap();
+            if let Message::Hello { features, .. } = &mut hello {
+                assert!(features.iter().any(|f| f == FEATURE_ENCRYPTED_PAYLOADS));
+                features.retain(|f| f != FEATURE_ENCRYPTED_PAYLOADS);
+            }
+            let payload = serde_json::to_vec(&hello).unwrap();
+            relay_b.send(Frame::new(hello.msg_type(), hello.stream_id(), frame.seq, payload)).await.unwrap();
+            relay_a.send(relay_b.recv().await.unwrap()).await.unwrap();
+        });
+
+        let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+        ra.unwrap();
+        assert_eq!(HandshakeError::of(&rb.unwrap_err()), Some(HandshakeError::BadSignature));
+        assert!(!b.encryption_enabled());
+        relay.await.unwrap();
+    }
+
+    async fn encrypted_handshake() -> (Session<MemoryConnection, Ed25519Identity, MockClipboard>, Session<MemoryConnection, Ed25519Identity, MockClipboard>) {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let new_session = |conn| {
+            Session::with_pairing_mode(conn, Ed25519Identity::generate(), MockClipboard::new(), Arc::new(MemoryTrustStore::new()))
+                .with_encryption()
+        };
+        let (a, b) = (new_session(conn_a), new_session(conn_b));
+        let (ra, rb) = tokio::join!(a.handshake_full(), b.handshake_full());
+        assert!(ra.unwrap().encrypted);
+        assert!(rb.unwrap().encrypted);
+        (a, b)
+    }
+
+    #[tokio::test]
+    async fn encrypted_payloads_roundtrip_sealed_on_the_wire() {
+        let (a, b) = encrypted_handshake().await;
+        assert!(a.encryption_enabled() && b.encryption_enabled());
+
+        a.send_clip_text("secret clip".into()).await.unwrap();
+        let frame = b.conn.recv().await.unwrap();
+        assert_eq!(frame.msg_type, MsgType::Encrypted as u8);
+        assert!(!frame.payload.windows(6).any(|w| w == b"secret"));
+        a.conn.send(frame).await.unwrap();
+        assert!(matches!(b.recv_message().await.unwrap(), Message::ClipText { text, .. } if text == "secret clip"));
+
+        // Both directions, including file chunks that skip the codec.
+        b.send_file_chunk_raw("f1", 0, b"bytes").await.unwrap();
+        assert_eq!(a.recv_message().await.unwrap(), Message::FileChunkRaw { file_id: "f1".into(), offset: 0, data: b"bytes".to_vec() });
+
+        // Without the offer on both sides, nothing is encrypted.
+        let (a, b) = codec_handshake(false, false).await;
+        assert!(!a.encryption_enabled() && !b.encryption_enabled());
+        assert!(a.session_key().is_some());
+    }
+
+    #[tokio::test]
+    async fn encrypted_frames_that_are_tampered_replayed_or_plaintext_are_refused() {
+        let (a, b) = encrypted_handshake().await;
+
+        a.send_clip_text("one".into()).await.unwrap();
+        let mut frame = b.conn.recv().await.unwrap();
+        frame.payload[3] ^= 1;
+        a.conn.send(frame).await.unwrap();
+        assert!(b.recv_message().await.is_err());
+
+        a.send_clip_text("two".into()).await.unwrap();
+        let frame = b.conn.recv().await.unwrap();
+        // The seq is part of the nonce, so it can't be changed either.
+        a.conn.send(Frame { seq: frame.seq + 1, ..frame.clone() }).await.unwrap();
+        assert!(b.recv_message().await.is_err());
+        a.conn.send(frame.clone()).await.unwrap();
+        assert!(matches!(b.recv_message().await.unwrap(), Message::ClipText { text, .. } if text == "two"));
+        a.conn.send(frame).await.unwrap();
+        assert!(b.recv_message().await.unwrap_err().to_string().contains("replayed"));
+
+        let msg = Message::Ping { ts_ms: 1 };
+        a.conn.send(Frame::new(msg.msg_type(), msg.stream_id(), 100, serde_json::to_vec(&msg).unwrap())).await.unwrap();
+        assert!(b.recv_message().await.unwrap_err().to_string().contains("plaintext"));
+    }
+
+    #[tokio::test]
+    async fn an_encrypted_frame_at_the_last_seq_is_refused() {
+        let (a, b) = encrypted_handshake().await;
+
+        let msg = Message::Ping { ts_ms: 1 };
+        let payload = encode_payload(a.codec(), &msg).unwrap();
+        let sealed = a.payload_cipher.lock().unwrap().as_ref().unwrap().seal(u64::MAX, msg.msg_type() as u8, &payload).unwrap();
+        a.conn.send(Frame::new(MsgType::Encrypted, msg.stream_id(), u64::MAX, sealed)).await.unwrap();
+        assert!(b.recv_message().await.unwrap_err().to_string().contains("no room"));
+
+        // The floor didn't move, so the session carries on.
+        a.send_clip_text("after".into()).await.unwrap();
+        assert!(matches!(b.recv_message().await.unwrap(), Message::ClipText { text, .. } if text == "after"));
+    }
+
+    async fn codec_handshake(binary_a: bool, binary_b: bool) -> (Session<MemoryConnection, Ed25519Identity, MockClipboard>, Session<MemoryConnection, Ed25519Identity, MockClipboard>) {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let new_session = |conn, binary| {
+            let s = Session::with_pairing_mode(conn, Ed25519Identity::generate(), MockClipboard::new(), Arc::new(MemoryTrustStore::new()));
+            if binary { s.with_binary_codec() } else { s }
+        };
+        let (a, b) = (new_session(conn_a, binary_a), new_session(conn_b, binary_b));
+        let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+        ra.unwrap();
+        rb.unwrap();
+        (a, b)
+    }
+
+    #[tokio::test]
+    async fn peers_offering_binary_switch_to_msgpack() {
+        let (a, b) = codec_handshake(true, true).await;
+        assert_eq!(a.codec(), Codec::MessagePack);
+        assert_eq!(b.codec(), Codec::MessagePack);
+
+        a.send_clip_text("binary hello".into()).await.unwrap();
+        let frame = b.conn.recv().await.unwrap();
+        assert!(serde_json::from_slice::<Message>(&frame.payload).is_err());
+        assert!(matches!(
+            Codec::MessagePack.decode(&frame.payload).unwrap(),
+            Message::ClipText { text, .. } if text == "binary hello"
+        ));
+
+        b.send_clip_text("and back".into()).await.unwrap();
+        assert!(matches!(a.recv_message().await.unwrap(), Message::ClipText { text, .. } if text == "and back"));
+    }
+
+    #[tokio::test]
+    async fn clip_acks_need_both_sides_and_match_the_completing_frame() {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let new_session = |conn| Session::new(conn, Ed25519Identity::generate(), MockClipboard::new()).with_clip_ack();
+        let (a, b) = (new_session(conn_a), new_session(conn_b));
+        let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+        ra.unwrap();
+        rb.unwrap();
+        assert!(a.clip_ack_enabled() && b.clip_ack_enabled());
+
+        // A chunked clip is acked by the seq of its `ClipTextEnd`.
+        a.send_clip_text("x".repeat(CLIP_TEXT_CHUNK_SIZE + 1)).await.unwrap();
+        a.send_clip_text("small".into()).await.unwrap();
+        let (big, big_seq) = b.recv_message_with_seq().await.unwrap();
+        let (small, small_seq) = b.recv_message_with_seq().await.unwrap();
+        let Message::ClipText { ts_ms: big_ts, .. } = big else { panic!("expected ClipText") };
+        let Message::ClipText { ts_ms: small_ts, .. } = small else { panic!("expected ClipText") };
+
+        b.send_clip_ack(small_seq).await.unwrap();
+        b.send_clip_ack(big_seq).await.unwrap();
+        for (seq, ts) in [(small_seq, small_ts), (big_seq, big_ts)] {
+            let Message::ClipAck { ack_of_seq } = a.recv_message().await.unwrap() else { panic!("expected ClipAck") };
+            assert_eq!(ack_of_seq, seq);
+            assert_eq!(a.take_acked_clip(ack_of_seq), Some(ts));
+            assert_eq!(a.take_acked_clip(ack_of_seq), None);
+        }
+
+        // Offered by one side only: off on both.
+        let (conn_a, conn_b) = memory_connection_pair();
+        let a = Session::new(conn_a, Ed25519Identity::generate(), MockClipboard::new()).with_clip_ack();
+        let b = Session::new(conn_b, Ed25519Identity::generate(), MockClipboard::new());
+        let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+        ra.unwrap();
+        rb.unwrap();
+        assert!(!a.clip_ack_enabled() && !b.clip_ack_enabled());
+    }
+
+    #[tokio::test]
+    async fn raw_file_chunks_carry_bytes_unencoded_once_negotiated() {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let new_session = |conn| {
+            Session::new(conn, Ed25519Identity::generate(), MockClipboard::new()).with_binary_codec().with_raw_file_chunks()
+        };
+        let (a, b) = (new_session(conn_a), new_session(conn_b));
+        let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+        ra.unwrap();
+        rb.unwrap();
+        assert!(a.raw_file_chunks_enabled() && b.raw_file_chunks_enabled());
+
+        let data: Vec<u8> = (0..=255).collect();
+        a.send_file_chunk_raw("f1", 512, &data).await.unwrap();
+        let frame = b.conn.recv().await.unwrap();
+        assert_eq!(frame.msg_type, MsgType::FileChunkRaw as u8);
+        assert!(frame.payload.ends_with(&data));
+        assert!(frame.payload.len() < data.len() + 32);
+
+        a.send_file_chunk_raw("f1", 768, &data).await.unwrap();
+        assert_eq!(b.recv_message().await.unwrap(), Message::FileChunkRaw { file_id: "f1".into(), offset: 768, data });
+
+        // Offered by one side only: off on both.
+        let (conn_a, conn_b) = memory_connection_pair();
+        let a = Session::new(conn_a, Ed25519Identity::generate(), MockClipboard::new()).with_raw_file_chunks();
+        let b = Session::new(conn_b, Ed25519Identity::generate(), MockClipboard::new());
+        let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+        ra.unwrap();
+        rb.unwrap();
+        assert!(!a.raw_file_chunks_enabled() && !b.raw_file_chunks_enabled());
+    }
+
+    #[tokio::test]
+    async fn large_clip_text_is_compressed_and_tiny_text_is_not() {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let new_session = |conn| Session::new(conn, Ed25519Identity::generate(), MockClipboard::new()).with_compression_threshold(1024);
+        let (a, b) = (new_session(conn_a), new_session(conn_b));
+        let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+        ra.unwrap();
+        rb.unwrap();
+        assert!(a.compression_enabled() && b.compression_enabled());
+
+        let big = "openclipboard ".repeat(1024 * 1024 / 14);
+        a.send_clip_text(big.clone()).await.unwrap();
+        let frame = b.conn.recv().await.unwrap();
+        assert_eq!(frame.msg_type, MsgType::ClipTextCompressed as u8);
+        assert!(frame.payload.len() < 16 * 1024, "{} bytes", frame.payload.len());
+        let Message::ClipTextCompressed { data_b64, .. } = b.codec().decode(&frame.payload).unwrap() else { panic!("expected ClipTextCompressed") };
+        assert_eq!(decompress_clip_text(&data_b64).unwrap(), big);
+
+        a.send_clip_text(big.clone()).await.unwrap();
+        assert!(matches!(b.recv_message().await.unwrap(), Message::ClipText { text, .. } if text == big));
+
+        a.send_clip_text("tiny".into()).await.unwrap();
+        let frame = b.conn.recv().await.unwrap();
+        assert_eq!(frame.msg_type, MsgType::ClipText as u8);
+
+        // Offered by one side only: off on both, and big text goes out plain.
+        let (conn_a, conn_b) = memory_connection_pair();
+        let a = Session::new(conn_a, Ed25519Identity::generate(), MockClipboard::new()).with_compression_threshold(1024);
+        let b = Session::new(conn_b, Ed25519Identity::generate(), MockClipboard::new());
+        let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+        ra.unwrap();
+        rb.unwrap();
+        assert!(!a.compression_enabled() && !b.compression_enabled());
+        a.send_clip_text("y".repeat(4096)).await.unwrap();
+        assert_eq!(b.conn.recv().await.unwrap().msg_type, MsgType::ClipText as u8);
+    }
+
+    #[test]
+    fn compressed_clip_text_may_not_expand_past_the_payload_cap() {
+        let bomb = zstd::bulk::compress(&vec![b'a'; MAX_PAYLOAD_LEN + 1], 0).unwrap();
+        let err = decompress_clip_text(&Base64Alphabet::Standard.encode(bomb)).unwrap_err();
+        assert!(err.to_string().contains("expands past"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn trust_acks_confirm_mutual_trust_and_flag_a_one_sided_one() {
+        let handshaken = || async {
+            let (conn_a, conn_b) = memory_connection_pair();
+            let new_session = |conn| Session::new(conn, Ed25519Identity::generate(), MockClipboard::new()).with_trust_ack();
+            let (a, b) = (new_session(conn_a), new_session(conn_b));
+            let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+            ra.unwrap();
+            rb.unwrap();
+            assert!(a.trust_ack_enabled() && b.trust_ack_enabled());
+            (a, b)
+        };
+        let timeout = Duration::from_secs(1);
+
+        let (a, b) = handshaken().await;
+        let (ra, rb) = tokio::join!(a.confirm_mutual_trust(timeout), b.confirm_mutual_trust(timeout));
+        ra.unwrap();
+        rb.unwrap();
+
+        // b doesn't trust a.
+        let (a, b) = handshaken().await;
+        let (ra, rb) = tokio::join!(a.confirm_mutual_trust(timeout), b.send_trust_ack(false));
+        rb.unwrap();
+        assert_eq!(HandshakeError::of(&ra.unwrap_err()), Some(HandshakeError::NotTrustedByPeer));
+        assert!(b.recv_trust_ack(timeout).await.unwrap());
+
+        // An ack is bound to the session it was signed for.
+        let (a, b) = handshaken().await;
+        let (c, d) = handshaken().await;
+        b.send_trust_ack(true).await.unwrap();
+        let Message::TrustAck { trusted, sig_b64 } = a.recv_message().await.unwrap() else { panic!("expected TrustAck") };
+        c.send_message(&Message::TrustAck { trusted, sig_b64 }).await.unwrap();
+        assert!(d.recv_trust_ack(timeout).await.is_err());
+
+        // Offered by one side only: no acks expected.
+        let (conn_a, conn_b) = memory_connection_pair();
+        let a = Session::new(conn_a, Ed25519Identity::generate(), MockClipboard::new()).with_trust_ack();
+        let b = Session::new(conn_b, Ed25519Identity::generate(), MockClipboard::new());
+        let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+        ra.unwrap();
+        rb.unwrap();
+        assert!(!a.trust_ack_enabled());
+        a.confirm_mutual_trust(timeout).await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn simultaneous_handshakes_verify_no_more_than_the_limit_at_once() {
+        let limiter = Arc::new(HandshakeLimiter::new(2));
+        let mut handshakes = tokio::task::JoinSet::new();
+        for _ in 0..32 {
+            let (conn_a, conn_b) = memory_connection_pair();
+            for conn in [conn_a, conn_b] {
+                let session = Session::new(conn, Ed25519Identity::generate(), MockClipboard::new())
+                    .with_trust_ack()
+                    .with_handshake_limiter(Arc::clone(&limiter));
+                handshakes.spawn(async move {
+                    session.handshake().await?;
+                    session.confirm_mutual_trust(Duration::from_secs(5)).await
+                });
+            }
+        }
+        while let Some(res) = handshakes.join_next().await {
+            res.unwrap().unwrap();
+        }
+        assert!((1..=2).contains(&limiter.peak()), "peak {}", limiter.peak());
+        assert_eq!(limiter.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn raising_the_handshake_limit_lets_waiters_in() {
+        let limiter = Arc::new(HandshakeLimiter::new(1));
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let held = tokio::spawn({
+            let limiter = Arc::clone(&limiter);
+            async move {
+                limiter
+                    .run(move || {
+                        started_tx.send(()).unwrap();
+                        release_rx.recv().unwrap();
+                    })
+                    .await
+            }
+        });
+        tokio::task::spawn_blocking(move || started_rx.recv().unwrap()).await.unwrap();
+
+        let waiting = limiter.run(|| 7);
+        tokio::pin!(waiting);
+        assert!(tokio::time::timeout(Duration::from_millis(50), waiting.as_mut()).await.is_err());
+        limiter.set_limit(2);
+        assert_eq!(tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap(), 7);
+
+        release_tx.send(()).unwrap();
+        held.await.unwrap();
+        assert_eq!((limiter.in_flight(), limiter.peak()), (0, 2));
+    }
+
+    #[tokio::test]
+    async fn describe_request_returns_message_types_and_negotiated_codec() {
+        let (a, b) = codec_handshake(true, true).await;
+        a.send_describe_request().await.unwrap();
+        assert_eq!(b.recv_message().await.unwrap(), Message::DescribeRequest);
+        b.send_describe().await.unwrap();
+        let Message::Describe { descriptor } = a.recv_message().await.unwrap() else { panic!("expected Describe") };
+
+        assert_eq!(descriptor.version, crate::protocol::PROTOCOL_VERSION);
+        assert_eq!(descriptor.codec, "msgpack");
+        assert_eq!(descriptor.codecs, ["json", "msgpack"]);
+        let names: Vec<_> = descriptor.message_types.iter().map(|t| t.name.as_str()).collect();
+        for known in ["Hello", "DescribeRequest", "Describe", "ClipText", "ClipTextEnd", "FileOffer", "TrustSync", "ClipAck"] {
+            assert!(names.contains(&known), "{known} missing from {names:?}");
+        }
+        let clip_text = descriptor.message_types.iter().find(|t| t.name == "ClipText").unwrap();
+        assert_eq!((clip_text.id, clip_text.stream_id), (MsgType::ClipText as u8, 2));
+        assert_eq!(descriptor.streams.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["control", "clipboard", "file"]);
+
+        let (a, _b) = codec_handshake(true, false).await;
+        assert_eq!(a.protocol_descriptor().codec, "json");
+    }
+
+    #[tokio::test]
+    async fn descriptor_lists_every_feature_both_hellos_offered() {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let new_session = |conn| {
+            Session::with_pairing_mode(conn, Ed25519Identity::generate(), MockClipboard::new(), Arc::new(MemoryTrustStore::new()))
+                .with_clip_ack()
+                .with_raw_file_chunks()
+                .with_compression_threshold(1024)
+                .with_trust_ack()
+                .with_encryption()
+        };
+        let (a, b) = (new_session(conn_a), new_session(conn_b));
+        let (ra, rb) = tokio::join!(a.handshake(), b.handshake());
+        ra.unwrap();
+        rb.unwrap();
+        assert_eq!(a.protocol_descriptor().features, FEATURES);
+        assert_eq!(b.protocol_descriptor().features, FEATURES);
+    }
+
+    #[tokio::test]
+    async fn json_only_peer_keeps_both_sides_on_json() {
+        let (a, b) = codec_handshake(true, false).await;
+        assert_eq!(a.codec(), Codec::Json);
+        assert_eq!(b.codec(), Codec::Json);
+
+        a.send_clip_text("plain hello".into()).await.unwrap();
+        assert!(matches!(b.recv_message().await.unwrap(), Message::ClipText { text, .. } if text == "plain hello"));
+        b.send_clip_text("plain back".into()).await.unwrap();
+        assert!(matches!(a.recv_message().await.unwrap(), Message::ClipText { text, .. } if text == "plain back"));
+    }
+
+    #[tokio::test]
+    async fn v0_compat_peer_syncs_clips_and_files_with_a_current_peer() {
+        let (conn_a, conn_b) = memory_connection_pair();
+        let new_session = |conn| Session::with_pairing_mode(conn, Ed25519Identity::generate(), MockClipboard::new(), Arc::new(MemoryTrustStore::new()));
+        let current = new_session(conn_a).with_binary_codec().with_clip_ack().with_origin("laptop");
+        let old = new_session(conn_b).with_binary_codec().with_v0_compat();
+        let (ra, rb) = tokio::join!(current.handshake(), old.handshake());
+        ra.unwrap();
+        rb.unwrap();
+        for s in [&current, &old] {
+            assert_eq!(s.codec(), Codec::Json);
+            assert!(!s.clip_ack_enabled());
+        }
+
+        current.send_clip_text("to v0".into()).await.unwrap();
+        assert!(matches!(old.recv_message().await.unwrap(), Message::ClipText { text, .. } if text == "to v0"));
+        old.send_clip_text("from v0".into()).await.unwrap();
+        assert!(matches!(current.recv_message().await.unwrap(), Message::ClipText { text, origin: None, .. } if text == "from v0"));
+
+        old.send_file_offer("f1", "a.txt", 5, "text/plain").await.unwrap();
+        assert!(matches!(current.recv_message().await.unwrap(), Message::FileOffer { file_id, size: 5, .. } if file_id == "f1"));
+        current.send_file_accept("f1").await.unwrap();
+        assert_eq!(old.recv_message().await.unwrap(), Message::FileAccept { file_id: "f1".into() });
+        old.send_file_chunk("f1", 0, b"hello").await.unwrap();
+        old.send_file_done("f1", "h").await.unwrap();
+        assert!(matches!(current.recv_message().await.unwrap(), Message::FileChunk { offset: 0, .. }));
+        assert!(matches!(current.recv_message().await.unwrap(), Message::FileDone { .. }));
+
+        // Newer messages are outside the common set: v0 can't send them, and
+        // the current peer, seeing a v0 HELLO, never sends them to it.
+        assert!(old.send_device_info("linux", "0.1").await.is_err());
+        assert!(current.peer_is_v0() && !old.peer_is_v0());
+        current.send_device_info("linux", "0.1").await.unwrap();
+        current.send_bye("closing").await.unwrap();
+        assert!(current.send_describe_request().await.is_err());
+        current.enqueue(Message::TrustSync { records: Vec::new(), revoked: Vec::new() }, Some(Box::new(|| panic!("sent TrustSync to v0"))));
+        current.enqueue_clip_text("still talking".into());
+        current.flush_send_queue().await.unwrap();
+        assert!(matches!(old.recv_message().await.unwrap(), Message::ClipText { text, .. } if text == "still talking"));
+    }
 }
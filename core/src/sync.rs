@@ -1,17 +1,21 @@
 //! Persistent peer connections + clipboard sync.
 
 use crate::clipboard::{ClipboardContent, ClipboardProvider};
+use crate::clock::{Clock, SystemClock};
 use crate::discovery::{Discovery, PeerInfo};
+use crate::file_log::{FileReceiveLog, ReceivedFile};
 use crate::history::ClipboardHistory;
 use crate::identity::Ed25519Identity;
 use crate::identity::IdentityProvider;
 use crate::mesh::PeerRegistry;
-use crate::quic_transport::{make_insecure_client_endpoint, make_server_endpoint, QuicListener, QuicTransport};
-use crate::replay::MemoryReplayProtector;
-use crate::session::Session;
-use crate::trust::TrustStore;
+use crate::quic_transport::{make_insecure_client_endpoint, make_server_endpoint, AcceptError, ListenError, QuicListener, QuicTransport, ServerEndpointFactory};
+use crate::rate_limit::{FileRateLimits, FileSendSlots};
+use crate::replay::ReplayProtector;
+use crate::session::{hash_file, HandshakeError, SendPriority, Session, FILE_OFFER_TIMEOUT};
+use crate::trust::{AsyncTrustStore, BlockingTrustStore, SyncDirection, TrustRecord, TrustRevocation, TrustStore};
 use crate::Message;
-use crate::transport::{Listener, Transport};
+use crate::protocol::{FileAssembler, TrustSyncRecord, TrustSyncRevocation, FILE_CHUNK_SIZE};
+use crate::transport::{box_listener, box_transport, BoxConnection, BoxListener, BoxTransport, Transport};
 use crate::transport::Connection;
 use anyhow::{Context, Result};
 use std::collections::{HashMap, VecDeque};
@@ -26,38 +30,531 @@ pub trait SyncHandler: Send + Sync {
     fn on_peer_connected(&self, peer_id: String);
     fn on_peer_disconnected(&self, peer_id: String);
     fn on_error(&self, message: String);
+
+    /// The same error as `on_error`, with what it concerns. The service calls
+    /// this rather than `on_error` and keeps the entries for
+    /// [`SyncService::recent_errors`]; the default passes the message on.
+    fn on_error_entry(&self, entry: ErrorEntry) {
+        self.on_error(entry.message);
+    }
+
+    /// A handshake was refused for a typed reason. Fired alongside the `on_error`
+    /// message (and rate-limited with it for dialed peers). `peer_id` is the remote
+    /// address when the peer's id isn't known yet (e.g. an incoming timeout).
+    fn on_handshake_failed(&self, _peer_id: String, _error: HandshakeError) {}
+
+    /// A long-running sync task (an accept loop or the dial loop) ended while the
+    /// service was still meant to be running, so that part of sync has stopped
+    /// until `stop`/`start`. Fired after an `on_error` starting with "sync stopped".
+    fn on_sync_stopped(&self, _task: String, _reason: String) {}
+
+    /// Whether to accept a file a connected peer offers. Rejected offers get a
+    /// `FileReject` with [`crate::REJECT_MIME_NOT_ALLOWED`].
+    fn allow_file(&self, _peer_id: &str, _name: &str, _mime: &str) -> bool {
+        true
+    }
+
+    /// A connected peer sent a file (see [`SyncService::send_file_to_peer`]), its
+    /// hash checked out, and it was saved at `path` and recorded in
+    /// [`SyncService::received_files`].
+    fn on_file_received(&self, _peer_id: String, _name: String, _path: String) {}
+
+    /// A connected peer copied an image. Images it sent that weren't valid
+    /// base64 are dropped with an `on_error` instead.
+    fn on_clipboard_image(&self, _peer_id: String, _mime: String, _width: u32, _height: u32, _bytes: Vec<u8>, _ts_ms: u64) {}
+
+    /// `peer_id` applied the clip with timestamp `ts_ms` that we sent it. Only
+    /// for peers that negotiated clip acks; a clip the peer's policy dropped
+    /// (direction, mute, size) is never confirmed.
+    fn on_clip_delivered(&self, _peer_id: String, _ts_ms: u64) {}
 }
 
 /// Track recent clipboard contents written due to remote updates.
 ///
 /// Used for echo suppression: if a remote write triggers a local clipboard-change event,
-/// the platform can check `should_ignore_local_change`.
-#[derive(Debug)]
+/// the platform can check `should_ignore_local_change`. Entries are evicted by count
+/// (`cap`) and, with [`EchoSuppressor::with_window`], also once they are older than the
+/// window, so suppression doesn't depend on how fast clips arrive.
 pub struct EchoSuppressor {
     cap: usize,
-    recent: VecDeque<String>,
+    window: Option<std::time::Duration>,
+    recent: VecDeque<(String, std::time::Instant)>,
+    /// Where remote writes land, see `read_back`.
+    read_back: Option<Arc<dyn ClipboardProvider>>,
+    note_after_write: bool,
+}
+
+impl std::fmt::Debug for EchoSuppressor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EchoSuppressor")
+            .field("cap", &self.cap)
+            .field("window", &self.window)
+            .field("recent", &self.recent)
+            .field("read_back", &self.read_back.is_some())
+            .field("note_after_write", &self.note_after_write)
+            .finish()
+    }
 }
 
 impl EchoSuppressor {
     pub fn new(cap: usize) -> Self {
         Self {
             cap: cap.max(1),
+            window: None,
             recent: VecDeque::new(),
+            read_back: None,
+            note_after_write: true,
         }
     }
 
-    pub fn note_remote_write(&mut self, text: &str) {
-        if self.recent.back().is_some_and(|t| t == text) {
-            return;
+    /// Also stop suppressing a write `window` after it was noted. Combine with a
+    /// large `cap` for a purely time-based suppressor.
+    pub fn with_window(mut self, window: std::time::Duration) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    /// Change (or with `None`, remove) the time window.
+    pub fn set_window(&mut self, window: Option<std::time::Duration>) {
+        self.window = window;
+    }
+
+    /// Suppress `text` as a local change for a while. Returns what was noted,
+    /// for [`Self::forget_remote_write`].
+    pub fn note_remote_write(&mut self, text: &str) -> NotedWrite {
+        self.note_remote_write_at(text, std::time::Instant::now())
+    }
+
+    /// [`Self::note_remote_write`] at an explicit time.
+    pub fn note_remote_write_at(&mut self, text: &str, now: std::time::Instant) -> NotedWrite {
+        if let Some(window) = self.window {
+            while self.recent.front().is_some_and(|(_, at)| now.saturating_duration_since(*at) >= window) {
+                self.recent.pop_front();
+            }
         }
-        self.recent.push_back(text.to_string());
+        if let Some((t, at)) = self.recent.back_mut()
+            && t == text
+        {
+            let previous = std::mem::replace(at, now);
+            return NotedWrite { text: text.to_string(), at: now, previous: Some(previous) };
+        }
+        self.recent.push_back((text.to_string(), now));
         while self.recent.len() > self.cap {
             self.recent.pop_front();
         }
+        NotedWrite { text: text.to_string(), at: now, previous: None }
+    }
+
+    /// Take back a write noted before it was made, e.g. because making it
+    /// failed, leaving anything noted since alone.
+    pub fn forget_remote_write(&mut self, noted: NotedWrite) {
+        let Some(pos) = self.recent.iter().rposition(|(t, at)| *t == noted.text && *at == noted.at) else { return };
+        match noted.previous {
+            Some(previous) => self.recent[pos].1 = previous,
+            None => {
+                self.recent.remove(pos);
+            }
+        }
     }
 
     pub fn should_ignore_local_change(&self, text: &str) -> bool {
-        self.recent.iter().any(|t| t == text)
+        self.should_ignore_local_change_at(text, std::time::Instant::now())
+    }
+
+    /// [`Self::note_remote_write`] for an image, remembered by its hash.
+    pub fn note_remote_image(&mut self, bytes: &[u8]) {
+        self.note_remote_write(&image_echo_key(bytes));
+    }
+
+    /// [`Self::should_ignore_local_change`] for an image.
+    pub fn should_ignore_local_image(&self, bytes: &[u8]) -> bool {
+        self.should_ignore_local_change(&image_echo_key(bytes))
+    }
+
+    /// [`Self::should_ignore_local_change`] at an explicit time.
+    pub fn should_ignore_local_change_at(&self, text: &str, now: std::time::Instant) -> bool {
+        self.recent.iter().any(|(t, at)| {
+            (t == text || (self.note_after_write && same_after_normalizing(t, text)))
+                && self.window.is_none_or(|w| now.saturating_duration_since(*at) < w)
+        })
+    }
+
+    /// Forget all recent remote writes.
+    pub fn clear(&mut self) {
+        self.recent.clear();
+    }
+
+    /// The clipboard remote writes are applied to, for `read_back`.
+    pub fn set_read_back(&mut self, provider: Option<Arc<dyn ClipboardProvider>>) {
+        self.read_back = provider;
+    }
+
+    /// Whether a noted write also suppresses its normalized forms and is
+    /// read back after writing (on by default).
+    pub fn set_note_after_write(&mut self, enabled: bool) {
+        self.note_after_write = enabled;
+    }
+
+    /// The clipboard to read once a remote write has been applied, noting
+    /// what it reads back as, so a provider that normalizes what it stores in
+    /// ways a noted write doesn't already cover isn't seen as a local change.
+    /// Read it without holding the suppressor's lock.
+    pub fn read_back(&self) -> Option<Arc<dyn ClipboardProvider>> {
+        self.read_back.clone().filter(|_| self.note_after_write)
+    }
+}
+
+/// Whether `a` and `b` differ only in what clipboards commonly normalize:
+/// surrounding whitespace and line endings. Lets a write noted before it is
+/// made cover what the clipboard turns it into, whenever the watcher looks.
+fn same_after_normalizing(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| s.trim().replace("\r\n", "\n");
+    normalize(a) == normalize(b)
+}
+
+/// A write noted by [`EchoSuppressor::note_remote_write`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotedWrite {
+    text: String,
+    at: std::time::Instant,
+    /// When the same text was noted last, if this only refreshed that entry.
+    previous: Option<std::time::Instant>,
+}
+
+/// Stands in for an image among the suppressor's texts; the leading NUL keeps
+/// it from matching copied text.
+fn image_echo_key(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("\0image:{:x}", Sha256::digest(bytes))
+}
+
+/// A recent connection attempt from a peer that is not in the trust store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingRequest {
+    pub peer_id: String,
+    /// Short fingerprint of the peer's identity key, for out-of-band comparison.
+    pub fingerprint: String,
+    pub identity_pk: Vec<u8>,
+    /// Remote socket address the attempt came from, if known.
+    pub addr: Option<String>,
+    pub timestamp_ms: u64,
+}
+
+/// Bounded list of untrusted connection attempts (one entry per peer, newest last).
+#[derive(Debug)]
+pub struct PendingRequests {
+    cap: usize,
+    entries: std::sync::Mutex<VecDeque<PendingRequest>>,
+}
+
+impl PendingRequests {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            cap: cap.max(1),
+            entries: std::sync::Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Record an attempt, replacing any earlier entry for the same peer.
+    pub fn record(&self, req: PendingRequest) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|r| r.peer_id != req.peer_id);
+        entries.push_back(req);
+        while entries.len() > self.cap {
+            entries.pop_front();
+        }
+    }
+
+    /// Remove and return the entry for `peer_id`, if any.
+    pub fn take(&self, peer_id: &str) -> Option<PendingRequest> {
+        let mut entries = self.entries.lock().unwrap();
+        let idx = entries.iter().position(|r| r.peer_id == peer_id)?;
+        entries.remove(idx)
+    }
+
+    /// All recorded attempts, newest first.
+    pub fn list(&self) -> Vec<PendingRequest> {
+        self.entries.lock().unwrap().iter().rev().cloned().collect()
+    }
+}
+
+/// Connection events kept for [`SyncService::events_since`].
+const CONNECTION_LOG_CAP: usize = 256;
+
+/// A peer connecting or disconnecting, as reported to the handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionEvent {
+    pub peer_id: String,
+    /// `false` for a disconnect.
+    pub connected: bool,
+    pub timestamp_ms: u64,
+}
+
+/// Errors kept for [`SyncService::recent_errors`].
+const ERROR_LOG_CAP: usize = 64;
+
+/// What part of sync an [`ErrorEntry`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    Discovery,
+    Accept,
+    Dial,
+    Handshake,
+    Send,
+    Recv,
+    File,
+    Trust,
+    Other,
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Discovery => "discovery",
+            Self::Accept => "accept",
+            Self::Dial => "dial",
+            Self::Handshake => "handshake",
+            Self::Send => "send",
+            Self::Recv => "recv",
+            Self::File => "file",
+            Self::Trust => "trust",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// An error reported to the handler, as kept by [`SyncService::recent_errors`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorEntry {
+    pub timestamp_ms: u64,
+    /// The peer it concerns, when known.
+    pub peer_id: Option<String>,
+    pub category: ErrorCategory,
+    pub message: String,
+}
+
+/// Report an error through `handler.on_error_entry`.
+fn report_error(handler: &dyn SyncHandler, category: ErrorCategory, peer_id: Option<&str>, message: String) {
+    handler.on_error_entry(ErrorEntry {
+        timestamp_ms: crate::session::now_ms(),
+        peer_id: peer_id.map(str::to_string),
+        category,
+        message,
+    });
+}
+
+/// What changed after a point in time, for a UI resuming from the background.
+/// See [`SyncService::events_since`].
+#[derive(Debug, Clone, Default)]
+pub struct SyncSummary {
+    /// History entries (local and received), oldest first.
+    pub clips: Vec<crate::history::ClipboardEntry>,
+    /// Files received, oldest first.
+    pub files: Vec<ReceivedFile>,
+    /// Connects and disconnects, oldest first; at most the newest 256 are kept.
+    pub connections: Vec<ConnectionEvent>,
+}
+
+/// How often a repeating per-peer error is re-reported as a "still failing" summary.
+const ERROR_SUMMARY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Coalesces identical consecutive errors per peer from the dial/retry path: the first
+/// occurrence is reported, repeats only as a periodic "still failing" summary.
+struct ErrorCoalescer {
+    interval: std::time::Duration,
+    /// peer_id -> (error key, attempts, last reported)
+    last: std::sync::Mutex<HashMap<String, (String, u32, std::time::Instant)>>,
+}
+
+impl ErrorCoalescer {
+    fn new(interval: std::time::Duration) -> Self {
+        Self { interval, last: std::sync::Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns the message to report, if any. `key` identifies "the same error"
+    /// (e.g. without the varying retry delay that `message` may include).
+    fn filter(&self, peer_id: &str, key: &str, message: String, now: std::time::Instant) -> Option<String> {
+        let mut last = self.last.lock().unwrap();
+        match last.get_mut(peer_id) {
+            Some((k, count, at)) if k == key => {
+                *count += 1;
+                if now.duration_since(*at) < self.interval {
+                    return None;
+                }
+                *at = now;
+                Some(format!("{message} (still failing, {count} attempts)"))
+            }
+            _ => {
+                last.insert(peer_id.to_string(), (key.to_string(), 1, now));
+                Some(message)
+            }
+        }
+    }
+
+    /// Report through the handler unless suppressed; returns whether it was reported.
+    fn report(&self, handler: &dyn SyncHandler, category: ErrorCategory, peer_id: &str, key: &str, message: String) -> bool {
+        match self.filter(peer_id, key, message, std::time::Instant::now()) {
+            Some(msg) => {
+                report_error(handler, category, Some(peer_id), msg);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget the peer's error streak (e.g. after a successful connection).
+    fn clear(&self, peer_id: &str) {
+        self.last.lock().unwrap().remove(peer_id);
+    }
+}
+
+/// Sits between the service and the caller's handler and holds back
+/// `on_peer_disconnected` for a grace period: if the peer is connected again
+/// by then (e.g. after WiFi roaming), neither the disconnect nor the reconnect
+/// reaches the handler. With no grace period events pass straight through.
+struct PresenceDebounce {
+    inner: Arc<dyn SyncHandler>,
+    peers: Arc<Mutex<HashMap<String, PeerHandle>>>,
+    grace_ms: std::sync::atomic::AtomicU64,
+    /// Peers the handler was last told are connected.
+    shown: std::sync::Mutex<std::collections::HashSet<String>>,
+    /// Peers whose disconnect is being held back, with the hold's id and the
+    /// timer that releases it.
+    held: std::sync::Mutex<HashMap<String, (u64, tokio::task::AbortHandle)>>,
+    next_hold: std::sync::atomic::AtomicU64,
+    /// What the handler was told, oldest first, at most `CONNECTION_LOG_CAP`.
+    log: std::sync::Mutex<VecDeque<ConnectionEvent>>,
+    /// Errors the handler was told, oldest first, at most `ERROR_LOG_CAP`.
+    errors: std::sync::Mutex<VecDeque<ErrorEntry>>,
+    this: std::sync::Weak<PresenceDebounce>,
+}
+
+impl PresenceDebounce {
+    fn new(inner: Arc<dyn SyncHandler>, peers: Arc<Mutex<HashMap<String, PeerHandle>>>) -> Arc<Self> {
+        Arc::new_cyclic(|this| Self {
+            inner,
+            peers,
+            grace_ms: std::sync::atomic::AtomicU64::new(0),
+            shown: std::sync::Mutex::new(std::collections::HashSet::new()),
+            held: std::sync::Mutex::new(HashMap::new()),
+            next_hold: std::sync::atomic::AtomicU64::new(0),
+            log: std::sync::Mutex::new(VecDeque::new()),
+            errors: std::sync::Mutex::new(VecDeque::new()),
+            this: this.clone(),
+        })
+    }
+
+    fn report_disconnected(&self, peer_id: String) {
+        self.shown.lock().unwrap().remove(&peer_id);
+        self.log_event(&peer_id, false);
+        self.inner.on_peer_disconnected(peer_id);
+    }
+
+    fn log_event(&self, peer_id: &str, connected: bool) {
+        let mut log = self.log.lock().unwrap();
+        log.push_back(ConnectionEvent { peer_id: peer_id.to_string(), connected, timestamp_ms: crate::session::now_ms() });
+        while log.len() > CONNECTION_LOG_CAP {
+            log.pop_front();
+        }
+    }
+
+    /// The hold ran out: report the disconnect unless the peer is back.
+    async fn release(&self, peer_id: String, hold: u64) {
+        if self.held.lock().unwrap().get(&peer_id).map(|(id, _)| *id) != Some(hold) {
+            return;
+        }
+        let connected = self.peers.lock().await.contains_key(&peer_id);
+        if self.held.lock().unwrap().remove(&peer_id).is_some() && !connected {
+            self.report_disconnected(peer_id);
+        }
+    }
+
+    /// Cancel every hold's timer and report its disconnect now, so none
+    /// reaches the handler after the service stopped.
+    fn release_all(&self) {
+        let held: Vec<_> = self.held.lock().unwrap().drain().collect();
+        for (peer_id, (_, timer)) in held {
+            timer.abort();
+            self.report_disconnected(peer_id);
+        }
+    }
+}
+
+impl SyncHandler for PresenceDebounce {
+    fn on_clipboard_text(&self, peer_id: String, text: String, ts_ms: u64) {
+        self.inner.on_clipboard_text(peer_id, text, ts_ms);
+    }
+
+    fn on_clipboard_image(&self, peer_id: String, mime: String, width: u32, height: u32, bytes: Vec<u8>, ts_ms: u64) {
+        self.inner.on_clipboard_image(peer_id, mime, width, height, bytes, ts_ms);
+    }
+
+    fn on_peer_connected(&self, peer_id: String) {
+        if let Some((_, timer)) = self.held.lock().unwrap().remove(&peer_id) {
+            timer.abort();
+            return;
+        }
+        self.shown.lock().unwrap().insert(peer_id.clone());
+        self.log_event(&peer_id, true);
+        self.inner.on_peer_connected(peer_id);
+    }
+
+    fn on_peer_disconnected(&self, peer_id: String) {
+        let grace = std::time::Duration::from_millis(self.grace_ms.load(std::sync::atomic::Ordering::SeqCst));
+        let holdable = !grace.is_zero() && self.shown.lock().unwrap().contains(&peer_id);
+        let (true, Some(this), Ok(runtime)) = (holdable, self.this.upgrade(), tokio::runtime::Handle::try_current()) else {
+            self.report_disconnected(peer_id);
+            return;
+        };
+        let hold = self.next_hold.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        // Holding the lock until the timer is recorded keeps `release` from
+        // seeing the hold before it is there.
+        let mut held = self.held.lock().unwrap();
+        let timer = runtime.spawn({
+            let peer_id = peer_id.clone();
+            async move {
+                tokio::time::sleep(grace).await;
+                this.release(peer_id, hold).await;
+            }
+        });
+        if let Some((_, earlier)) = held.insert(peer_id, (hold, timer.abort_handle())) {
+            earlier.abort();
+        }
+    }
+
+    fn on_error(&self, message: String) {
+        report_error(self, ErrorCategory::Other, None, message);
+    }
+
+    fn on_error_entry(&self, entry: ErrorEntry) {
+        {
+            let mut errors = self.errors.lock().unwrap();
+            errors.push_back(entry.clone());
+            while errors.len() > ERROR_LOG_CAP {
+                errors.pop_front();
+            }
+        }
+        self.inner.on_error_entry(entry);
+    }
+
+    fn on_handshake_failed(&self, peer_id: String, error: HandshakeError) {
+        self.inner.on_handshake_failed(peer_id, error);
+    }
+
+    fn on_sync_stopped(&self, task: String, reason: String) {
+        self.inner.on_sync_stopped(task, reason);
+    }
+
+    fn allow_file(&self, peer_id: &str, name: &str, mime: &str) -> bool {
+        self.inner.allow_file(peer_id, name, mime)
+    }
+
+    fn on_clip_delivered(&self, peer_id: String, ts_ms: u64) {
+        self.inner.on_clip_delivered(peer_id, ts_ms);
+    }
+
+    fn on_file_received(&self, peer_id: String, name: String, path: String) {
+        self.inner.on_file_received(peer_id, name, path);
     }
 }
 
@@ -83,53 +580,524 @@ impl Backoff {
     }
 }
 
-struct PeerHandle {
-    outbound_tx: mpsc::Sender<String>,
-}
-
+/// When an outbound connection gives up on a peer it can't reach; see
+/// [`SyncService::set_retry_budget`]. The default never gives up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryBudget {
+    /// Failed attempts in a row (dial, handshake or refused session) to allow.
+    pub max_attempts: Option<u32>,
+    /// How long to keep retrying after the first of those failures.
+    pub max_duration: Option<std::time::Duration>,
+}
+
+/// Liveness checks on open sessions; see [`SyncService::set_keepalive`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keepalive {
+    /// How long after the last answered ping (or the session opening) to send the next.
+    pub interval: std::time::Duration,
+    /// How long to wait for its `Pong` before dropping the session.
+    pub timeout: std::time::Duration,
+}
+
+impl Default for Keepalive {
+    /// A ping every 30 s, given 10 s to be answered.
+    fn default() -> Self {
+        Self { interval: std::time::Duration::from_secs(30), timeout: std::time::Duration::from_secs(10) }
+    }
+}
+
+/// Where a [`SyncService`] listens and keeps its state; see
+/// [`SyncService::with_options`].
+#[derive(Debug, Clone)]
+pub struct SyncServiceOptions {
+    pub local_listen: SocketAddr,
+    pub device_name: String,
+    /// Keep clipboard history in an encrypted file here across restarts
+    /// (see [`SyncService::with_history_file`]); `None` keeps it in memory.
+    pub history_file: Option<std::path::PathBuf>,
+}
+
+/// Consecutive failed attempts, counted against a [`RetryBudget`].
+struct RetryTracker {
+    budget: RetryBudget,
+    failures: u32,
+    failing_since: Option<std::time::Instant>,
+}
+
+impl RetryTracker {
+    fn new(budget: RetryBudget) -> Self {
+        Self { budget, failures: 0, failing_since: None }
+    }
+
+    /// Count a failure; true once the budget is spent.
+    fn fail(&mut self) -> bool {
+        self.failures += 1;
+        let since = *self.failing_since.get_or_insert_with(std::time::Instant::now);
+        self.budget.max_attempts.is_some_and(|n| self.failures >= n)
+            || self.budget.max_duration.is_some_and(|d| since.elapsed() >= d)
+    }
+
+    fn reset(&mut self) {
+        self.failures = 0;
+        self.failing_since = None;
+    }
+}
+
+/// A daily local-time range, start inclusive and end exclusive. An `end`
+/// before `start` wraps past midnight, e.g. 22:00–07:00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietRange {
+    pub start: chrono::NaiveTime,
+    pub end: chrono::NaiveTime,
+}
+
+impl QuietRange {
+    pub fn new(start: chrono::NaiveTime, end: chrono::NaiveTime) -> Self {
+        Self { start, end }
+    }
+
+    pub fn contains(&self, t: chrono::NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= t && t < self.end
+        } else {
+            t >= self.start || t < self.end
+        }
+    }
+}
+
+/// When sync pauses itself; see [`SyncService::set_quiet_hours`]. The
+/// default has no ranges, so it never pauses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuietHours {
+    pub ranges: Vec<QuietRange>,
+    /// Also drop clips peers send during quiet hours, instead of only not
+    /// broadcasting local ones.
+    pub pause_incoming: bool,
+}
+
+impl QuietHours {
+    pub fn is_quiet_at(&self, now: chrono::NaiveDateTime) -> bool {
+        self.ranges.iter().any(|r| r.contains(now.time()))
+    }
+}
+
+/// The quiet-hours schedule and the clock it's read against, shared with
+/// the watcher and every peer loop.
+struct QuietSchedule {
+    clock: std::sync::Mutex<Arc<dyn Clock>>,
+    hours: std::sync::Mutex<QuietHours>,
+}
+
+impl QuietSchedule {
+    fn is_quiet(&self) -> bool {
+        let now = self.clock.lock().unwrap().now();
+        self.hours.lock().unwrap().is_quiet_at(now)
+    }
+
+    fn pauses_outgoing(&self) -> bool {
+        self.is_quiet()
+    }
+
+    fn pauses_incoming(&self) -> bool {
+        self.hours.lock().unwrap().pause_incoming && self.is_quiet()
+    }
+}
+
+/// How far ahead of our wall clock a peer's clip timestamp may move our
+/// clock; a peer with a wildly wrong clock can't pin it in the future.
+const MAX_CLOCK_SKEW_MS: u64 = 60_000;
+
+/// The newest clip this device has, copied here or applied from a peer,
+/// for last-writer-wins reconciliation; see
+/// [`SyncService::set_reconcile_on_connect`].
+///
+/// Clips are ordered by a hybrid logical clock rather than bare wall-clock
+/// time: a clip copied here is stamped after every clip seen so far, so it
+/// wins over them even when a peer's clock runs ahead of ours.
+#[derive(Default)]
+struct LatestClip {
+    enabled: std::sync::atomic::AtomicBool,
+    clip: std::sync::Mutex<Option<(u64, String)>>,
+    /// The newest timestamp issued or seen.
+    clock: std::sync::atomic::AtomicU64,
+}
+
+impl LatestClip {
+    fn enabled(&self) -> bool {
+        self.enabled.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// A timestamp for a clip copied here: now, or just after the newest
+    /// timestamp issued or seen if that is later.
+    fn tick(&self) -> u64 {
+        let now = crate::session::now_ms();
+        let prev = self
+            .clock
+            .fetch_update(std::sync::atomic::Ordering::SeqCst, std::sync::atomic::Ordering::SeqCst, |last| Some(now.max(last + 1)))
+            .unwrap_or_default();
+        now.max(prev + 1)
+    }
+
+    /// Advance the clock past a peer's clip timestamp, by at most
+    /// `MAX_CLOCK_SKEW_MS` beyond our own clock.
+    fn observe(&self, ts_ms: u64) {
+        let bound = crate::session::now_ms().saturating_add(MAX_CLOCK_SKEW_MS);
+        self.clock.fetch_max(ts_ms.min(bound), std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Whether `(ts_ms, text)` beats the current clip: it's newer, or from the
+    /// same millisecond with greater text, so both sides agree on ties.
+    fn is_newer(&self, ts_ms: u64, text: &str) -> bool {
+        self.clip.lock().unwrap().as_ref().is_none_or(|(t, s)| (ts_ms, text) > (*t, s.as_str()))
+    }
+
+    /// Note a clip this device now has, unless it already has a newer one.
+    /// Noted even while reconciling is off, so turning it on offers the clip.
+    fn note(&self, ts_ms: u64, text: &str) {
+        let mut clip = self.clip.lock().unwrap();
+        if clip.as_ref().is_none_or(|(t, s)| (ts_ms, text) > (*t, s.as_str())) {
+            *clip = Some((ts_ms, text.to_string()));
+        }
+    }
+
+    /// The clip to offer a peer as a session opens, when reconciling.
+    fn to_offer(&self) -> Option<(u64, String)> {
+        if !self.enabled() {
+            return None;
+        }
+        self.clip.lock().unwrap().clone()
+    }
+}
+
+struct PeerHandle {
+    outbound_tx: mpsc::Sender<Outbound>,
+    info: SessionInfo,
+    /// Tells this session apart from a later one with the same peer.
+    session: u64,
+}
+
+static NEXT_SESSION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+impl PeerHandle {
+    fn new(outbound_tx: mpsc::Sender<Outbound>, peer_id: &str, addr: Option<String>, encrypted: bool, outbound: bool) -> Self {
+        Self {
+            outbound_tx,
+            info: SessionInfo {
+                peer_id: peer_id.to_string(),
+                addr,
+                connected_at_ms: crate::session::now_ms(),
+                encrypted,
+                outbound,
+            },
+            session: NEXT_SESSION.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+        }
+    }
+}
+
+/// An open session with a peer; see [`SyncService::active_sessions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub peer_id: String,
+    /// The peer's address, when known.
+    pub addr: Option<String>,
+    /// When the session opened (ms since the Unix epoch).
+    pub connected_at_ms: u64,
+    pub encrypted: bool,
+    /// `true` if this side dialed the peer, `false` if it dialed in.
+    pub outbound: bool,
+}
+
+/// Forget `peer_id`'s `session` once its message loop ends. Returns `false`,
+/// touching nothing, if another session has taken its place meanwhile (e.g.
+/// the peer redialed after `terminate_session`).
+async fn forget_session(peers: &Mutex<HashMap<String, PeerHandle>>, registry: &PeerRegistry, peer_id: &str, session: u64) -> bool {
+    let mut map = peers.lock().await;
+    if map.get(peer_id).is_some_and(|h| h.session != session) {
+        return false;
+    }
+    map.remove(peer_id);
+    registry.set_offline(peer_id).await;
+    true
+}
+
+/// Work queued for a connected peer's message loop.
+enum Outbound {
+    /// Clip text and when it was copied.
+    Text(String, u64),
+    /// A `Message::ClipImage`, encoded once for every peer.
+    Image(Message),
+    File(OutboundFile),
+    /// Send our trust list (linked devices only).
+    TrustSync,
+    /// Resolves once everything queued before it has reached the peer: a
+    /// `Ping` goes out behind it and the peer's `Pong` confirms receipt.
+    Drain(tokio::sync::oneshot::Sender<()>),
+    /// Send `Bye` with this reason and end the session; resolves once the
+    /// peer has hung up or stopped waiting for it.
+    Bye(&'static str, tokio::sync::oneshot::Sender<()>),
+}
+
+/// How a peer's message loop ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LoopEnd {
+    /// The connection failed or was closed from this side.
+    Dropped,
+    /// The peer said `Bye`, for this reason.
+    Bye(String),
+}
+
+struct OutboundFile {
+    name: String,
+    mime: String,
+    size: u64,
+    source: FileSource,
+    /// SHA-256 hex of the content, sent in the offer and in `FileDone`.
+    hash: String,
+    progress: Option<FileProgressFn>,
+    /// Resolves once the peer accepts the offer.
+    accepted: tokio::sync::oneshot::Sender<()>,
+    done: tokio::sync::oneshot::Sender<Result<()>>,
+}
+
+/// Resolves once the peer accepts an [`OutboundFile`]; dropped if it doesn't.
+type OutboundFileAccepted = tokio::sync::oneshot::Receiver<()>;
+/// Resolves with how an [`OutboundFile`]'s transfer ended.
+type OutboundFileDone = tokio::sync::oneshot::Receiver<Result<()>>;
+
+impl OutboundFile {
+    fn new(
+        name: String,
+        mime: String,
+        size: u64,
+        source: FileSource,
+        hash: String,
+        progress: Option<FileProgressFn>,
+    ) -> (Self, OutboundFileAccepted, OutboundFileDone) {
+        let (accepted, accepted_rx) = tokio::sync::oneshot::channel();
+        let (done, done_rx) = tokio::sync::oneshot::channel();
+        (Self { name, mime, size, source, hash, progress, accepted, done }, accepted_rx, done_rx)
+    }
+}
+
+/// Where an outbound file's content comes from.
+enum FileSource {
+    Bytes(Vec<u8>),
+    /// Read a chunk at a time as the transfer goes, never whole.
+    Path(std::path::PathBuf),
+}
+
+/// An accepted file whose chunks are queued a window at a time.
+struct SendingFile {
+    name: String,
+    reader: FileReader,
+    total: u64,
+    /// Bytes queued so far.
+    queued: u64,
+    /// Chunks queued but not yet sent.
+    in_flight: usize,
+    hash: String,
+    progress: Option<FileProgressFn>,
+    done: tokio::sync::oneshot::Sender<Result<()>>,
+}
+
+enum FileReader {
+    Bytes(Vec<u8>),
+    Disk(tokio::fs::File),
+}
+
+impl FileReader {
+    async fn open(source: FileSource) -> Result<Self> {
+        Ok(match source {
+            FileSource::Bytes(data) => Self::Bytes(data),
+            FileSource::Path(path) => {
+                Self::Disk(tokio::fs::File::open(&path).await.with_context(|| format!("open {}", path.display()))?)
+            }
+        })
+    }
+
+    /// Continue from `offset`, where the receiver said a resumed transfer
+    /// picks up.
+    async fn seek(&mut self, offset: u64) -> Result<()> {
+        if let Self::Disk(file) = self {
+            use tokio::io::AsyncSeekExt;
+            file.seek(std::io::SeekFrom::Start(offset)).await.context("seek to the resume offset")?;
+        }
+        Ok(())
+    }
+
+    /// The `len` bytes at `offset`; chunks are read in order, so a file on
+    /// disk is just read on from where the last chunk ended.
+    async fn read_chunk(&mut self, offset: u64, len: usize) -> Result<Vec<u8>> {
+        match self {
+            Self::Bytes(data) => Ok(data[offset as usize..offset as usize + len].to_vec()),
+            Self::Disk(file) => {
+                use tokio::io::AsyncReadExt;
+                let mut chunk = vec![0; len];
+                file.read_exact(&mut chunk).await.context("file shrank while being sent")?;
+                Ok(chunk)
+            }
+        }
+    }
+}
+
+/// File chunks queued per transfer ahead of the one being sent, so a large
+/// file is neither read nor queued whole.
+const FILE_SEND_WINDOW: usize = 8;
+
+/// Called with `(bytes_sent, total_bytes)` as a file's chunks go out.
+pub type FileProgressFn = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/// Entries kept in a service's clipboard history.
+const HISTORY_ENTRIES: usize = 100;
+
+/// Point-in-time view of a running service, for a UI that attaches late.
+/// See [`SyncService::snapshot`].
+#[derive(Debug, Clone)]
+pub struct SyncSnapshot {
+    /// Peers with an open session, sorted by peer_id.
+    pub connected: Vec<String>,
+    /// Registry entries (known peers with status, addresses and device info).
+    pub peers: Vec<crate::mesh::PeerEntry>,
+    /// Most recent history entries, newest first.
+    pub history: Vec<crate::history::ClipboardEntry>,
+    /// Total entries and content bytes held in history.
+    pub history_len: usize,
+    pub history_bytes: usize,
+    pub pending_requests: Vec<PendingRequest>,
+    pub suspended: Vec<String>,
+}
+
+/// Platform and app version this node reports to peers after each handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppInfo {
+    pub platform: String,
+    pub app_version: String,
+}
+
+impl Default for AppInfo {
+    fn default() -> Self {
+        Self {
+            platform: std::env::consts::OS.to_string(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
 /// Persistent sync service: listens for incoming peers, dials discovered trusted peers,
 /// and broadcasts clipboard text to all connected peers.
 ///
 /// This is a LAN prototype: QUIC cert validation is disabled and we rely on the
 /// application-layer session handshake + pinned public keys in the trust store.
+///
+/// The listener binds `local_listen` as given. Port 0 picks a fresh ephemeral port on
+/// every `start`, so any address peers remember for us (e.g. a `PeerEntry::last_addr`)
+/// goes stale across restarts; a fixed port keeps it valid. A fixed port that is already
+/// taken makes `start` fail instead of falling back to an ephemeral one.
 pub struct SyncService<D: Discovery + 'static> {
     identity: Ed25519Identity,
     trust_store: Arc<dyn TrustStore>,
-    replay: Arc<MemoryReplayProtector>,
+    replay: Arc<dyn ReplayProtector>,
     discovery: Arc<D>,
 
     local_listen: SocketAddr,
     device_name: String,
 
+    /// The caller's handler behind `presence`.
     handler: Arc<dyn SyncHandler>,
+    presence: Arc<PresenceDebounce>,
 
     peers: Arc<Mutex<HashMap<String, PeerHandle>>>,
     peer_registry: PeerRegistry,
     echo_suppressor: Arc<Mutex<EchoSuppressor>>,
     history: Arc<ClipboardHistory>,
+    received_files: Arc<FileReceiveLog>,
 
-    /// When true, the next clipboard write is a "silent recall" and should not trigger fanout.
-    silent_write: Arc<std::sync::atomic::AtomicBool>,
+    /// A "silent recall" write in flight: the watcher doesn't fan out a change
+    /// to this text seen before the deadline.
+    silent_write: Arc<std::sync::Mutex<Option<(String, std::time::Instant)>>>,
+
+    /// When true, empty or whitespace-only clipboard text is synced like any other text.
+    sync_whitespace: Arc<std::sync::atomic::AtomicBool>,
 
     /// Peers we're expecting to connect back (after QR scan pair).
     /// When a peer in this set connects, auto-trust them.
     pending_pair_peers: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
 
+    /// Recent connection attempts from untrusted peers (for a "pending requests" UI).
+    pending_requests: Arc<PendingRequests>,
+
+    /// Peers disconnected via `disconnect_peer`: never dialed or accepted until resumed.
+    suspended_peers: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+
+    /// Peers an outbound connection gave up on: not dialed again until
+    /// `reconnect_now` or until discovery loses and finds them again.
+    unreachable_peers: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+
+    /// Handed to each outbound connection the dial loop starts.
+    retry_budget: Arc<std::sync::Mutex<RetryBudget>>,
+
+    /// Read by each peer loop as its session opens.
+    keepalive: Arc<std::sync::Mutex<Option<Keepalive>>>,
+
+    /// Offered to each peer as its session opens, and guards which clips apply.
+    latest_clip: Arc<LatestClip>,
+
+    /// Global and per-peer caps on how fast files go out.
+    file_rate_limits: std::sync::Mutex<Arc<FileRateLimits>>,
+
+    /// Held by each `send_file_to_peer` until its file is sent or refused.
+    file_send_slots: std::sync::Mutex<Arc<FileSendSlots>>,
+
+    /// When the watcher stops broadcasting, and peer loops optionally stop applying clips.
+    quiet_hours: Arc<QuietSchedule>,
+
+    /// When true, sessions that aren't application-layer encrypted are dropped.
+    require_encryption: Arc<std::sync::atomic::AtomicBool>,
+
+    /// When true (the default), each handshake offers encrypted payloads.
+    offer_encryption: Arc<std::sync::atomic::AtomicBool>,
+
+    /// Rate-limits repeated dial/handshake errors per peer.
+    dial_errors: Arc<ErrorCoalescer>,
+
+    /// Held by an outbound connection attempt while it dials and handshakes;
+    /// replaced by `set_max_concurrent_dials`.
+    dial_permits: Arc<std::sync::Mutex<Arc<tokio::sync::Semaphore>>>,
+
+    /// Addresses the listeners are bound to while running.
+    bound_addrs: std::sync::Mutex<Vec<SocketAddr>>,
+
+    /// Sent to each peer as `Message::DeviceInfo`, together with `device_name`.
+    app_info: std::sync::Mutex<AppInfo>,
+
+    /// How long `stop` waits for queued sends to reach peers; `None` drops them.
+    drain_on_stop: std::sync::Mutex<Option<std::time::Duration>>,
+
+    /// Creates each QUIC listener endpoint in `start_on`.
+    endpoint_factory: std::sync::Mutex<ServerEndpointFactory>,
+
     stop_tx: watch::Sender<bool>,
     tasks: Mutex<Vec<JoinHandle<()>>>,
+    /// The mesh clipboard watcher. At most one runs: `start_mesh` aborts the
+    /// previous one, `stop` the current one.
+    watcher: std::sync::Mutex<Option<JoinHandle<()>>>,
+    /// One per `start`: owns the accept and dial loops and reports any that die.
+    supervisors: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl<D: Discovery + 'static> SyncService<D> {
     pub fn new(
         identity: Ed25519Identity,
         trust_store: Arc<dyn TrustStore>,
-        replay: Arc<MemoryReplayProtector>,
+        replay: Arc<dyn ReplayProtector>,
         discovery: Arc<D>,
         local_listen: SocketAddr,
         device_name: String,
         handler: Arc<dyn SyncHandler>,
     ) -> Result<Self> {
         let (stop_tx, _stop_rx) = watch::channel(false);
+        let peers = Arc::new(Mutex::new(HashMap::new()));
+        let presence = PresenceDebounce::new(handler, Arc::clone(&peers));
         Ok(Self {
             identity,
             trust_store,
@@ -137,183 +1105,851 @@ impl<D: Discovery + 'static> SyncService<D> {
             discovery,
             local_listen,
             device_name,
-            handler,
-            peers: Arc::new(Mutex::new(HashMap::new())),
+            handler: presence.clone(),
+            presence,
+            peers,
             peer_registry: PeerRegistry::new(),
             echo_suppressor: Arc::new(Mutex::new(EchoSuppressor::new(32))),
-            history: Arc::new(ClipboardHistory::new(100)),
-            silent_write: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            history: Arc::new(ClipboardHistory::new(HISTORY_ENTRIES)),
+            received_files: Arc::new(FileReceiveLog::new(100)),
+            silent_write: Arc::new(std::sync::Mutex::new(None)),
+            sync_whitespace: Arc::new(std::sync::atomic::AtomicBool::new(true)),
             pending_pair_peers: Arc::new(std::sync::Mutex::new(std::collections::HashSet::new())),
+            pending_requests: Arc::new(PendingRequests::new(16)),
+            suspended_peers: Arc::new(std::sync::Mutex::new(std::collections::HashSet::new())),
+            unreachable_peers: Arc::new(std::sync::Mutex::new(std::collections::HashSet::new())),
+            retry_budget: Arc::new(std::sync::Mutex::new(RetryBudget::default())),
+            keepalive: Arc::new(std::sync::Mutex::new(Some(Keepalive::default()))),
+            latest_clip: Arc::new(LatestClip::default()),
+            file_rate_limits: std::sync::Mutex::new(Arc::new(FileRateLimits::new())),
+            file_send_slots: std::sync::Mutex::new(Arc::new(FileSendSlots::default())),
+            quiet_hours: Arc::new(QuietSchedule {
+                clock: std::sync::Mutex::new(Arc::new(SystemClock)),
+                hours: std::sync::Mutex::new(QuietHours::default()),
+            }),
+            require_encryption: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            offer_encryption: Arc::new(std::sync::atomic::AtomicBool::new(true)),
+            dial_errors: Arc::new(ErrorCoalescer::new(ERROR_SUMMARY_INTERVAL)),
+            dial_permits: Arc::new(std::sync::Mutex::new(Arc::new(tokio::sync::Semaphore::new(tokio::sync::Semaphore::MAX_PERMITS)))),
+            bound_addrs: std::sync::Mutex::new(Vec::new()),
+            app_info: std::sync::Mutex::new(AppInfo::default()),
+            drain_on_stop: std::sync::Mutex::new(None),
+            endpoint_factory: std::sync::Mutex::new(Arc::new(make_server_endpoint)),
             stop_tx,
             tasks: Mutex::new(Vec::new()),
+            watcher: std::sync::Mutex::new(None),
+            supervisors: Mutex::new(Vec::new()),
         })
     }
 
+    /// Like `new`, with the listen address, device name and history file
+    /// given in `options`.
+    pub fn with_options(
+        identity: Ed25519Identity,
+        trust_store: Arc<dyn TrustStore>,
+        replay: Arc<dyn ReplayProtector>,
+        discovery: Arc<D>,
+        handler: Arc<dyn SyncHandler>,
+        options: SyncServiceOptions,
+    ) -> Result<Self> {
+        let service = Self::new(identity, trust_store, replay, discovery, options.local_listen, options.device_name, handler)?;
+        match options.history_file {
+            Some(path) => service.with_history_file(path),
+            None => Ok(service),
+        }
+    }
+
+    /// Keep clipboard history across restarts in an encrypted file at
+    /// `path`, readable only with this service's identity (see
+    /// [`ClipboardHistory::with_encrypted_persistence`]). Entries already there
+    /// are loaded, newest first up to the entry limit. Without it history is
+    /// in memory only.
+    pub fn with_history_file(mut self, path: impl Into<std::path::PathBuf>) -> Result<Self> {
+        let key = crate::history::HistoryKey::from_identity(&self.identity);
+        self.history = Arc::new(ClipboardHistory::new(HISTORY_ENTRIES).with_encrypted_persistence(path, key)?);
+        Ok(self)
+    }
+
+    /// Record received files in `log` instead of a log of this service's own,
+    /// e.g. one shared with other ways of receiving files.
+    pub fn with_file_log(mut self, log: Arc<FileReceiveLog>) -> Self {
+        self.received_files = log;
+        self
+    }
+
     pub async fn start(&self) -> Result<()> {
-        let (listener, _cert) = make_server_endpoint(self.local_listen)
-            .with_context(|| format!("bind listener {}", self.local_listen))?;
-        let listener = QuicListener::new(listener);
+        self.start_on(&[self.local_listen]).await
+    }
+
+    /// Like `start`, but listening on each of `addrs` (e.g. an IPv4 and an IPv6
+    /// address, or two ports) instead of `local_listen`. All listeners feed the
+    /// same peers; discovery advertises every bound address.
+    pub async fn start_on(&self, addrs: &[SocketAddr]) -> Result<()> {
+        anyhow::ensure!(!addrs.is_empty(), "no listen address given");
+        let mut listeners = Vec::with_capacity(addrs.len());
+        let mut bound = Vec::with_capacity(addrs.len());
+        let make_endpoint = Arc::clone(&self.endpoint_factory.lock().unwrap());
+        for &addr in addrs {
+            let (endpoint, _cert) = match make_endpoint(addr) {
+                Ok(v) => v,
+                Err(e) if addr.port() != 0 && is_addr_in_use(&e) => {
+                    return Err(ListenError::AddressInUse { port: addr.port() }.into());
+                }
+                Err(e) => return Err(ListenError::BindFailed { addr, reason: format!("{e:#}") }.into()),
+            };
+            let listener = QuicListener::new(endpoint);
+            let bound_addr = listener.local_addr()?;
+            listeners.push((box_listener(listener), bound_addr.to_string()));
+            bound.push(bound_addr);
+        }
+        let client = make_insecure_client_endpoint().context("client endpoint")?;
+
+        self.start_with_listeners(listeners, box_transport(QuicTransport::new(client))).await?;
+        *self.bound_addrs.lock().unwrap() = bound;
+        Ok(())
+    }
+
+    /// Like `start`, but accepting on `listener` and dialing through `transport`
+    /// instead of QUIC, e.g. to run over fault-injecting links in tests.
+    /// `advertise_addr` is what discovery publishes; `transport` must be able to
+    /// connect to it.
+    pub async fn start_with_transport(&self, listener: BoxListener, advertise_addr: String, transport: BoxTransport) -> Result<()> {
+        self.start_with_listeners(vec![(listener, advertise_addr)], transport).await
+    }
+
+    /// `start_with_transport` over several listeners, each paired with the address
+    /// discovery advertises for it. The first is the primary address.
+    pub async fn start_with_listeners(&self, listeners: Vec<(BoxListener, String)>, transport: BoxTransport) -> Result<()> {
+        let mut advertise_addrs = listeners.iter().map(|(_, addr)| addr.clone());
+        let Some(addr) = advertise_addrs.next() else {
+            anyhow::bail!("no listener given");
+        };
 
         // Advertising / discovery
         let peer_info = PeerInfo {
             peer_id: self.identity.peer_id().to_string(),
             name: self.device_name.clone(),
-            addr: listener.local_addr()?.to_string(),
+            addr,
+            alt_addrs: advertise_addrs.collect(),
         };
+        let own_addrs = peer_info.addresses();
         // best-effort: if advertise fails, we still can run with direct connects.
         if let Err(e) = self.discovery.start_discovery(peer_info).await {
-            self.handler.on_error(format!("discovery start failed: {e}"));
+            report_error(self.handler.as_ref(), ErrorCategory::Discovery, None, format!("discovery start failed: {e}"));
+        }
+
+        // Incoming accept loop, one per listener. These and the dial loop run under a
+        // supervisor that reports any of them ending before `stop`.
+        let mut critical = tokio::task::JoinSet::new();
+        for (listener, listen_addr) in listeners {
+            let mut stop_rx = self.stop_tx.subscribe();
+            let handler = Arc::clone(&self.handler);
+            let identity = self.identity.clone();
+            let trust_store = Arc::clone(&self.trust_store);
+            let replay = Arc::clone(&self.replay);
+            let peers = Arc::clone(&self.peers);
+            let echo_sup = Arc::clone(&self.echo_suppressor);
+            let registry = self.peer_registry.clone();
+            let history = Arc::clone(&self.history);
+            let received_files = Arc::clone(&self.received_files);
+            let pending_pairs = Arc::clone(&self.pending_pair_peers);
+            let pending_requests = Arc::clone(&self.pending_requests);
+            let suspended = Arc::clone(&self.suspended_peers);
+            let device_name = self.device_name.clone();
+            let app_info = self.app_info();
+            let require_encryption = Arc::clone(&self.require_encryption);
+            let offer_encryption = Arc::clone(&self.offer_encryption);
+            let quiet_hours = Arc::clone(&self.quiet_hours);
+            let keepalive = Arc::clone(&self.keepalive);
+            let latest_clip = Arc::clone(&self.latest_clip);
+            let file_rate_limits = Arc::clone(&self.file_rate_limits.lock().unwrap());
+
+            critical.spawn(async move {
+                let task = format!("accept loop on {listen_addr}");
+                // Errors that aren't about a single incoming connection, in a row.
+                let mut listener_errors = 0;
+                loop {
+                    tokio::select! {
+                        _ = stop_rx.changed() => { break; }
+                        conn = listener.accept() => {
+                            let conn = match conn {
+                                Ok(c) => {
+                                    listener_errors = 0;
+                                    c
+                                }
+                                Err(e) if AcceptError::of(&e) == Some(&AcceptError::Closed) => {
+                                    return (task, Err("listener closed".to_string()));
+                                }
+                                Err(e) => {
+                                    report_error(handler.as_ref(), ErrorCategory::Accept, None, format!("accept failed: {e}"));
+                                    if AcceptError::of(&e).is_none() {
+                                        listener_errors += 1;
+                                        if listener_errors >= MAX_CONSECUTIVE_LISTENER_ERRORS {
+                                            return (task, Err(format!("{listener_errors} accept errors in a row, last: {e}")));
+                                        }
+                                    }
+                                    continue;
+                                }
+                            };
+
+                            let handler2 = Arc::clone(&handler);
+                            let identity2 = identity.clone();
+                            let trust2 = Arc::clone(&trust_store);
+                            let replay2 = Arc::clone(&replay);
+                            let peers2 = Arc::clone(&peers);
+                            let echo2 = Arc::clone(&echo_sup);
+                            let registry2 = registry.clone();
+                            let history2 = Arc::clone(&history);
+                            let files2 = Arc::clone(&received_files);
+                            let pending2 = Arc::clone(&pending_pairs);
+                            let requests2 = Arc::clone(&pending_requests);
+                            let suspended2 = Arc::clone(&suspended);
+                            let device_name2 = device_name.clone();
+                            let app_info2 = app_info.clone();
+                            let require_encryption2 = Arc::clone(&require_encryption);
+                            let offer_encryption2 = Arc::clone(&offer_encryption);
+                            let quiet_hours2 = Arc::clone(&quiet_hours);
+                            let keepalive2 = Arc::clone(&keepalive);
+                            let latest_clip2 = Arc::clone(&latest_clip);
+                            let file_rate_limits2 = Arc::clone(&file_rate_limits);
+                            let stop_rx2 = stop_rx.clone();
+                            tokio::spawn(async move {
+                                if let Err(e) = handle_incoming_connection(conn, identity2, trust2, replay2, peers2, handler2, echo2, registry2, history2, files2, pending2, requests2, suspended2, device_name2, app_info2, require_encryption2, offer_encryption2, quiet_hours2, keepalive2, latest_clip2, file_rate_limits2, stop_rx2).await {
+                                    // already reported most errors
+                                    let _ = e;
+                                }
+                            });
+                        }
+                    }
+                }
+                (task, Ok(()))
+            });
+        }
+
+        // Outbound dial loop (poll discovery)
+        let mut stop_rx2 = self.stop_tx.subscribe();
+        let identity3 = self.identity.clone();
+        let trust3 = Arc::clone(&self.trust_store);
+        let async_trust3 = BlockingTrustStore::new(Arc::clone(&self.trust_store));
+        let replay3 = Arc::clone(&self.replay);
+        let discovery3 = Arc::clone(&self.discovery);
+        let peers3 = Arc::clone(&self.peers);
+        let handler3 = Arc::clone(&self.handler);
+        let echo3 = Arc::clone(&self.echo_suppressor);
+        let registry3 = self.peer_registry.clone();
+        let history3 = Arc::clone(&self.history);
+        let files3 = Arc::clone(&self.received_files);
+        let suspended3 = Arc::clone(&self.suspended_peers);
+        let device_name3 = self.device_name.clone();
+        let app_info3 = self.app_info();
+        let require_encryption3 = Arc::clone(&self.require_encryption);
+        let offer_encryption3 = Arc::clone(&self.offer_encryption);
+        let quiet_hours3 = Arc::clone(&self.quiet_hours);
+        let keepalive3 = Arc::clone(&self.keepalive);
+        let latest_clip3 = Arc::clone(&self.latest_clip);
+        let file_rate_limits3 = Arc::clone(&self.file_rate_limits.lock().unwrap());
+        let dial_errors3 = Arc::clone(&self.dial_errors);
+        let dial_permits3 = Arc::clone(&self.dial_permits);
+        let unreachable3 = Arc::clone(&self.unreachable_peers);
+        let retry_budget3 = Arc::clone(&self.retry_budget);
+        // Peers (by advertised addresses) with a connect loop running, so each
+        // gets one at a time; a peer that moves gets a new loop for its new address.
+        let dialing = Arc::new(std::sync::Mutex::new(std::collections::HashSet::<(String, Vec<String>)>::new()));
+        critical.spawn(async move {
+            let mut seen = std::collections::HashSet::new();
+            // Addresses advertising our own peer_id that have been probed for a duplicate identity.
+            let mut probed_own_id = std::collections::HashSet::new();
+            loop {
+                tokio::select! {
+                    _ = stop_rx2.changed() => { break; }
+                    _ = tokio::time::sleep(std::time::Duration::from_millis(300)) => {}
+                }
+
+                let scanned = match discovery3.scan().await {
+                    Ok(v) => v,
+                    Err(e) => {
+                        report_error(handler3.as_ref(), ErrorCategory::Discovery, None, format!("discovery scan failed: {e}"));
+                        continue;
+                    }
+                };
+                let previously_seen = std::mem::replace(&mut seen, scanned.iter().map(|p| p.peer_id.clone()).collect());
+
+                for peer in scanned {
+                    if peer.peer_id == identity3.peer_id().to_string() {
+                        // Our own advertisement, or another device using our identity?
+                        let addrs = peer.addresses();
+                        if !addrs.iter().all(|a| is_own_address(a, &own_addrs)) && probed_own_id.insert(addrs) {
+                            tokio::spawn(probe_duplicate_identity(peer, Arc::clone(&transport), identity3.clone(), Arc::clone(&handler3)));
+                        }
+                        continue;
+                    }
+                    // trust gate
+                    match async_trust3.is_trusted(&peer.peer_id).await {
+                        Ok(true) => {}
+                        Ok(false) => continue,
+                        Err(e) => {
+                            report_error(handler3.as_ref(), ErrorCategory::Trust, Some(peer.peer_id.as_str()), format!("trust check failed: {e}"));
+                            continue;
+                        }
+                    }
+                    registry3.set_addresses(&peer.peer_id, peer.addresses()).await;
+                    // dial rule
+                    if identity3.peer_id().to_string() >= peer.peer_id {
+                        continue;
+                    }
+                    // disconnected by the user?
+                    if suspended3.lock().unwrap().contains(&peer.peer_id) {
+                        continue;
+                    }
+                    // already connected? Then it's reachable, whichever side dialed.
+                    if peers3.lock().await.contains_key(&peer.peer_id) {
+                        unreachable3.lock().unwrap().remove(&peer.peer_id);
+                        continue;
+                    }
+                    // given up on? Only a fresh discovery record (or `reconnect_now`) revives it.
+                    if !previously_seen.contains(&peer.peer_id) && unreachable3.lock().unwrap().remove(&peer.peer_id) {
+                        registry3.clear_unreachable(&peer.peer_id).await;
+                    }
+                    if unreachable3.lock().unwrap().contains(&peer.peer_id) {
+                        continue;
+                    }
+                    let dial_key = (peer.peer_id.clone(), peer.addresses());
+                    if !dialing.lock().unwrap().insert(dial_key.clone()) {
+                        continue;
+                    }
+
+                    let identity4 = identity3.clone();
+                    let trust4 = Arc::clone(&trust3);
+                    let replay4 = Arc::clone(&replay3);
+                    let peers4 = Arc::clone(&peers3);
+                    let handler4 = Arc::clone(&handler3);
+                    let echo4 = Arc::clone(&echo3);
+                    let registry4 = registry3.clone();
+                    let history4 = Arc::clone(&history3);
+                    let files4 = Arc::clone(&files3);
+                    let suspended4 = Arc::clone(&suspended3);
+                    let device_name4 = device_name3.clone();
+                    let app_info4 = app_info3.clone();
+                    let require_encryption4 = Arc::clone(&require_encryption3);
+                    let offer_encryption4 = Arc::clone(&offer_encryption3);
+                    let quiet_hours4 = Arc::clone(&quiet_hours3);
+                    let keepalive4 = Arc::clone(&keepalive3);
+                    let latest_clip4 = Arc::clone(&latest_clip3);
+                    let file_rate_limits4 = Arc::clone(&file_rate_limits3);
+                    let dial_errors4 = Arc::clone(&dial_errors3);
+                    let dial_permits4 = Arc::clone(&dial_permits3.lock().unwrap());
+                    let unreachable4 = Arc::clone(&unreachable3);
+                    let retry_budget4 = *retry_budget3.lock().unwrap();
+                    let dialing2 = Arc::clone(&dialing);
+                    let transport2 = Arc::clone(&transport);
+                    let stop_rx4 = stop_rx2.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = connect_loop(peer, transport2, identity4, trust4, replay4, peers4, handler4, echo4, registry4, history4, files4, suspended4, unreachable4, device_name4, app_info4, require_encryption4, offer_encryption4, dial_errors4, dial_permits4, retry_budget4, quiet_hours4, keepalive4, latest_clip4, file_rate_limits4, stop_rx4).await {
+                            let _ = e;
+                        }
+                        dialing2.lock().unwrap().remove(&dial_key);
+                    });
+                }
+            }
+            ("dial loop".to_string(), Ok(()))
+        });
+
+        let supervisor = tokio::spawn(supervise(critical, Arc::clone(&self.handler), self.stop_tx.subscribe()));
+        self.supervisors.lock().await.push(supervisor);
+        Ok(())
+    }
+
+    pub async fn stop(&self) {
+        if let Some(timeout) = self.drain_on_stop() {
+            self.drain(timeout).await;
+        }
+        self.say_bye(BYE_STOPPING).await;
+        let _ = self.stop_tx.send(true);
+
+        // Stop discovery before returning, so it can't withdraw the advertisement
+        // of a `start` that follows right after.
+        let _ = self.discovery.stop_discovery().await;
+
+        // Supervisors shut their loops down (abort, then wait, so listeners are
+        // dropped) as soon as they see the stop signal.
+        for s in self.supervisors.lock().await.drain(..) {
+            let _ = s.await;
         }
 
-        let mut stop_rx = self.stop_tx.subscribe();
-        let handler = Arc::clone(&self.handler);
-        let identity = self.identity.clone();
-        let trust_store = Arc::clone(&self.trust_store);
-        let replay = Arc::clone(&self.replay);
-        let peers = Arc::clone(&self.peers);
-        let echo_sup = Arc::clone(&self.echo_suppressor);
-        let registry = self.peer_registry.clone();
-        let history = Arc::clone(&self.history);
-        let pending_pairs = Arc::clone(&self.pending_pair_peers);
+        let watcher = self.watcher.lock().unwrap().take();
+        if let Some(w) = watcher {
+            w.abort();
+            let _ = w.await;
+        }
+        self.echo_suppressor.lock().await.set_read_back(None);
+
+        let mut tasks = self.tasks.lock().await;
+        for t in tasks.iter() {
+            t.abort();
+        }
+        // Wait for the aborted tasks to drop what they own (notably the listener), then
+        // yield so the endpoint driver can release the socket before a restart rebinds it.
+        for t in tasks.drain(..) {
+            let _ = t.await;
+        }
+        drop(tasks);
+        self.bound_addrs.lock().unwrap().clear();
+        tokio::task::yield_now().await;
+
+        self.peers.lock().await.clear();
+        self.presence.release_all();
+    }
+
+    /// Have `stop` first wait up to `timeout` for clips already queued for
+    /// connected peers to reach them, so one copied right before quitting
+    /// isn't lost. `None` (the default) stops straight away.
+    pub fn set_drain_on_stop(&self, timeout: Option<std::time::Duration>) {
+        *self.drain_on_stop.lock().unwrap() = timeout;
+    }
+
+    pub fn drain_on_stop(&self) -> Option<std::time::Duration> {
+        *self.drain_on_stop.lock().unwrap()
+    }
+
+    /// Create listener endpoints with `factory` instead of
+    /// [`make_server_endpoint`] from the next `start` on. Its errors come back
+    /// from `start` as a [`ListenError`].
+    pub fn set_endpoint_factory(&self, factory: ServerEndpointFactory) {
+        *self.endpoint_factory.lock().unwrap() = factory;
+    }
+
+    /// Let at most `limit` outbound connections dial and handshake at once,
+    /// so a large trust set connects in waves instead of all at startup.
+    /// `None` (the default) doesn't limit them. Applies to attempts the dial
+    /// loop starts after the call.
+    pub fn set_max_concurrent_dials(&self, limit: Option<usize>) {
+        let permits = limit.map_or(tokio::sync::Semaphore::MAX_PERMITS, |n| n.max(1));
+        *self.dial_permits.lock().unwrap() = Arc::new(tokio::sync::Semaphore::new(permits));
+    }
+
+    /// Give up on a trusted peer that can't be reached once `budget` is spent,
+    /// instead of retrying it forever: it's marked `PeerStatus::Unreachable`
+    /// and left alone until `reconnect_now`, or until discovery loses and
+    /// finds it again. Applies to connection attempts started after the call.
+    pub fn set_retry_budget(&self, budget: RetryBudget) {
+        *self.retry_budget.lock().unwrap() = budget;
+    }
+
+    pub fn retry_budget(&self) -> RetryBudget {
+        *self.retry_budget.lock().unwrap()
+    }
+
+    /// Ping each connected peer every `interval` and drop its session when
+    /// the `Pong` doesn't come back within `timeout`, so a silently dead path
+    /// is noticed (and redialed) without waiting for a send to fail. `None`
+    /// never pings; [`Keepalive::default`] is used until this is called.
+    /// Applies to sessions opened after the call.
+    pub fn set_keepalive(&self, keepalive: Option<Keepalive>) {
+        *self.keepalive.lock().unwrap() = keepalive;
+    }
+
+    pub fn keepalive(&self) -> Option<Keepalive> {
+        *self.keepalive.lock().unwrap()
+    }
+
+    /// Reconcile clipboards last-writer-wins whenever a session opens, e.g.
+    /// after a network partition heals: each side offers its newest clip with
+    /// the time it was copied, and from then on a clip older than the one a
+    /// device already has is dropped rather than applied. Off by default.
+    pub fn set_reconcile_on_connect(&self, enabled: bool) {
+        self.latest_clip.enabled.store(enabled, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    pub fn reconciles_on_connect(&self) -> bool {
+        self.latest_clip.enabled()
+    }
+
+    /// Cap how fast files go out to all peers together, in bytes per second
+    /// (`None` or 0: unlimited, the default). Chunks are paced in the send
+    /// queue, so clipboard traffic isn't held up behind them. Applies to
+    /// transfers already running too.
+    pub fn set_file_rate_limit(&self, bytes_per_sec: Option<u64>) {
+        self.file_rate_limits.lock().unwrap().set_global(bytes_per_sec);
+    }
+
+    pub fn file_rate_limit(&self) -> Option<u64> {
+        self.file_rate_limits.lock().unwrap().global()
+    }
+
+    /// Cap how fast files go out to `peer_id`, in bytes per second, on top of
+    /// the global limit (`None` or 0: only the global limit applies).
+    pub fn set_peer_file_rate_limit(&self, peer_id: &str, bytes_per_sec: Option<u64>) {
+        self.file_rate_limits.lock().unwrap().set_peer(peer_id, bytes_per_sec);
+    }
+
+    pub fn peer_file_rate_limit(&self, peer_id: &str) -> Option<u64> {
+        self.file_rate_limits.lock().unwrap().peer(peer_id)
+    }
+
+    /// Pace files under `limits` instead of the service's own, from the next
+    /// `start` on; share them with transfers made outside the service so the
+    /// global limit covers those too.
+    pub fn set_file_rate_limits(&self, limits: Arc<FileRateLimits>) {
+        *self.file_rate_limits.lock().unwrap() = limits;
+    }
+
+    /// Let at most `limit` `send_file_to_peer` calls run at once, across all
+    /// peers; further calls wait for a free slot. `None` (the default) doesn't
+    /// limit them. Applies to sends started after the call.
+    pub fn set_max_concurrent_file_sends(&self, limit: Option<usize>) {
+        self.file_send_slots.lock().unwrap().set_limit(limit);
+    }
+
+    pub fn max_concurrent_file_sends(&self) -> Option<usize> {
+        self.file_send_slots.lock().unwrap().limit()
+    }
+
+    /// Take file send slots from `slots` instead of the service's own; share
+    /// them with transfers made outside the service so the cap covers those too.
+    pub fn set_file_send_slots(&self, slots: Arc<FileSendSlots>) {
+        *self.file_send_slots.lock().unwrap() = slots;
+    }
+
+    /// Pause sync during `hours`: local clipboard changes are still recorded in
+    /// history but not broadcast, and with `pause_incoming` clips from peers
+    /// are dropped too. Takes effect immediately, including on open sessions.
+    pub fn set_quiet_hours(&self, hours: QuietHours) {
+        *self.quiet_hours.hours.lock().unwrap() = hours;
+    }
+
+    pub fn quiet_hours(&self) -> QuietHours {
+        self.quiet_hours.hours.lock().unwrap().clone()
+    }
+
+    /// The clock quiet hours are checked against; the system's local time by default.
+    pub fn set_clock(&self, clock: Arc<dyn Clock>) {
+        *self.quiet_hours.clock.lock().unwrap() = clock;
+    }
+
+    /// Whether it's currently quiet hours.
+    pub fn is_quiet(&self) -> bool {
+        self.quiet_hours.is_quiet()
+    }
+
+    /// Dial `peer_id` again on the next discovery poll if its retry budget
+    /// ran out. Returns `false` if it wasn't given up on.
+    pub async fn reconnect_now(&self, peer_id: &str) -> bool {
+        if !self.unreachable_peers.lock().unwrap().remove(peer_id) {
+            return false;
+        }
+        self.peer_registry.clear_unreachable(peer_id).await;
+        true
+    }
+
+    /// Peers whose retry budget ran out, sorted by peer_id.
+    pub fn unreachable_peers(&self) -> Vec<String> {
+        let mut v: Vec<String> = self.unreachable_peers.lock().unwrap().iter().cloned().collect();
+        v.sort();
+        v
+    }
+
+    /// Wait, up to `timeout`, until every connected peer has received what is
+    /// queued for it. Peers that don't answer in time are given up on.
+    async fn drain(&self, timeout: std::time::Duration) {
+        let mut acks = Vec::new();
+        for h in self.peers.lock().await.values() {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            if h.outbound_tx.send(Outbound::Drain(tx)).await.is_ok() {
+                acks.push(rx);
+            }
+        }
+        let _ = tokio::time::timeout(timeout, async {
+            for ack in acks {
+                let _ = ack.await;
+            }
+        })
+        .await;
+    }
+
+    /// Send every connected peer `Bye` and end its session, waiting until
+    /// they have hung up (or `BYE_GRACE` has passed).
+    async fn say_bye(&self, reason: &'static str) {
+        let mut acks = Vec::new();
+        for h in self.peers.lock().await.values() {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            if h.outbound_tx.send(Outbound::Bye(reason, tx)).await.is_ok() {
+                acks.push(rx);
+            }
+        }
+        let _ = tokio::time::timeout(BYE_GRACE, async {
+            for ack in acks {
+                let _ = ack.await;
+            }
+        })
+        .await;
+    }
+
+    /// The address the listener is bound to, while the service is running.
+    ///
+    /// With a fixed port in `local_listen` this is stable across `stop`/`start`.
+    pub fn listen_addr(&self) -> Option<SocketAddr> {
+        self.bound_addrs.lock().unwrap().first().copied()
+    }
+
+    /// Every address the service is listening on (see `start_on`), while running.
+    pub fn listen_addrs(&self) -> Vec<SocketAddr> {
+        self.bound_addrs.lock().unwrap().clone()
+    }
+
+    pub async fn broadcast_clip_text(&self, text: String) {
+        let ts_ms = self.latest_clip.tick();
+        self.latest_clip.note(ts_ms, &text);
+        if self.quiet_hours.pauses_outgoing() {
+            return;
+        }
+        let peers = self.peers.lock().await;
+        for (peer_id, h) in peers.iter() {
+            let _ = h.outbound_tx.send(Outbound::Text(text.clone(), ts_ms)).await;
+            let _ = peer_id;
+        }
+    }
+
+    /// Send an image to every connected peer. One too large for a single
+    /// message is refused before anything is sent: the error goes to
+    /// `on_error` and is returned.
+    pub async fn broadcast_clip_image(&self, mime: String, width: u32, height: u32, bytes: Vec<u8>) -> Result<()> {
+        let msg = match clip_image_message(mime, width, height, &bytes, crate::session::now_ms()) {
+            Ok(msg) => msg,
+            Err(e) => {
+                report_error(self.handler.as_ref(), ErrorCategory::Send, None, e.to_string());
+                return Err(e);
+            }
+        };
+        if self.quiet_hours.pauses_outgoing() {
+            return Ok(());
+        }
+        let peers = self.peers.lock().await;
+        for h in peers.values() {
+            let _ = h.outbound_tx.send(Outbound::Image(msg.clone())).await;
+        }
+        Ok(())
+    }
+
+    /// Send a file to a connected peer over its existing session, reporting
+    /// progress as chunks go out. Resolves once the last chunk is sent; fails if
+    /// the peer isn't connected, we don't send it clips (see
+    /// [`SyncDirection`]), it doesn't answer the offer within 30 s, rejects
+    /// it, or disconnects first.
+    pub async fn send_file_to_peer(
+        &self,
+        peer_id: &str,
+        name: String,
+        mime: String,
+        data: Vec<u8>,
+        progress: Option<FileProgressFn>,
+    ) -> Result<()> {
+        use sha2::{Digest, Sha256};
+        let hash = format!("{:x}", Sha256::digest(&data));
+        let size = data.len() as u64;
+        self.send_outbound_file(peer_id, OutboundFile::new(name, mime, size, FileSource::Bytes(data), hash, progress)).await
+    }
+
+    /// Like [`Self::send_file_to_peer`] for the file at `path`, which is read
+    /// a chunk at a time as the transfer goes rather than loaded whole.
+    pub async fn send_file_path_to_peer(
+        &self,
+        peer_id: &str,
+        path: &std::path::Path,
+        mime: String,
+        progress: Option<FileProgressFn>,
+    ) -> Result<()> {
+        let name = path.file_name().map_or_else(|| "file".to_string(), |n| n.to_string_lossy().to_string());
+        let to_hash = path.to_path_buf();
+        let (size, hash) = tokio::task::spawn_blocking(move || hash_file(&to_hash)).await??;
+        self.send_outbound_file(peer_id, OutboundFile::new(name, mime, size, FileSource::Path(path.to_path_buf()), hash, progress)).await
+    }
+
+    async fn send_outbound_file(
+        &self,
+        peer_id: &str,
+        (file, accepted_rx, mut done_rx): (OutboundFile, OutboundFileAccepted, OutboundFileDone),
+    ) -> Result<()> {
+        let name = file.name.clone();
+        if !peer_sync_direction(Some(&self.trust_store), peer_id).await.sends() {
+            anyhow::bail!("not sending {name}: sync to {peer_id} is turned off");
+        }
+        let slots = Arc::clone(&self.file_send_slots.lock().unwrap());
+        let _slot = slots.acquire().await;
+        {
+            let peers = self.peers.lock().await;
+            let h = peers.get(peer_id).with_context(|| format!("peer {peer_id} is not connected"))?;
+            h.outbound_tx
+                .send(Outbound::File(file))
+                .await
+                .map_err(|_| anyhow::anyhow!("peer {peer_id} disconnected"))?;
+        }
+        // A rejection resolves `done` without an accept; past the accept, the
+        // session ending (e.g. on a dead connection) resolves it.
+        tokio::select! {
+            res = &mut done_rx => {
+                return res.map_err(|_| anyhow::anyhow!("peer {peer_id} disconnected before the file was sent"))?;
+            }
+            answered = tokio::time::timeout(FILE_OFFER_TIMEOUT, accepted_rx) => {
+                if answered.is_err() {
+                    anyhow::bail!("peer {peer_id} didn't answer the offer of {name} within {FILE_OFFER_TIMEOUT:?}");
+                }
+            }
+        }
+        done_rx.await.map_err(|_| anyhow::anyhow!("peer {peer_id} disconnected before the file was sent"))?
+    }
+
+    /// Mark (or unmark) a peer as another of this owner's devices. Linked
+    /// devices exchange trust lists on every connect and on `sync_trust`, so a
+    /// device paired on one shows up on the others; trust lists from any other
+    /// peer are ignored.
+    pub async fn set_linked_device(&self, peer_id: &str, linked: bool) -> Result<()> {
+        self.peer_registry.set_linked(peer_id, linked).await
+    }
+
+    /// Persist the linked devices at `path`, loading the ones already there.
+    /// `None` keeps them in memory only.
+    pub async fn set_linked_devices_file(&self, path: Option<std::path::PathBuf>) -> Result<()> {
+        self.peer_registry.set_linked_path(path).await
+    }
+
+    pub async fn linked_devices(&self) -> Vec<String> {
+        self.peer_registry.linked().await
+    }
+
+    /// Send our trust list to every connected linked device now, e.g. after
+    /// pairing. Returns how many devices it was queued for.
+    pub async fn sync_trust(&self) -> usize {
+        let peers = self.peers.lock().await;
+        let mut sent = 0;
+        for (peer_id, h) in peers.iter() {
+            if self.peer_registry.is_linked(peer_id).await && h.outbound_tx.send(Outbound::TrustSync).await.is_ok() {
+                sent += 1;
+            }
+        }
+        sent
+    }
+
+    /// Push the provider's current clipboard text to all connected peers, even if it
+    /// came from a peer and would be echo-suppressed. Returns `false` if the clipboard
+    /// doesn't hold text.
+    pub async fn resend_current(&self, provider: &dyn ClipboardProvider) -> Result<bool> {
+        let ClipboardContent::Text(text) = provider.read()? else {
+            return Ok(false);
+        };
+        self.broadcast_clip_text(text).await;
+        Ok(true)
+    }
 
-        // Incoming accept loop
-        let incoming_task = tokio::spawn(async move {
-            loop {
-                tokio::select! {
-                    _ = stop_rx.changed() => { break; }
-                    conn = listener.accept() => {
-                        let conn = match conn {
-                            Ok(c) => c,
-                            Err(e) => {
-                                handler.on_error(format!("accept failed: {e}"));
-                                continue;
-                            }
-                        };
+    /// Forget recently received clipboard contents, so copying one of them again
+    /// is broadcast like any local change.
+    pub async fn clear_echo_suppressor(&self) {
+        self.echo_suppressor.lock().await.clear();
+    }
 
-                        let handler2 = Arc::clone(&handler);
-                        let identity2 = identity.clone();
-                        let trust2 = Arc::clone(&trust_store);
-                        let replay2 = Arc::clone(&replay);
-                        let peers2 = Arc::clone(&peers);
-                        let echo2 = Arc::clone(&echo_sup);
-                        let registry2 = registry.clone();
-                        let history2 = Arc::clone(&history);
-                        let pending2 = Arc::clone(&pending_pairs);
-                        tokio::spawn(async move {
-                            if let Err(e) = handle_incoming_connection(conn, identity2, trust2, replay2, peers2, handler2, echo2, registry2, history2, pending2).await {
-                                // already reported most errors
-                                let _ = e;
-                            }
-                        });
-                    }
-                }
-            }
-        });
+    /// Stop suppressing a received clip as an echo once it is older than
+    /// `window` (`None`: only the count cap applies, the default).
+    pub async fn set_echo_suppression_window(&self, window: Option<std::time::Duration>) {
+        self.echo_suppressor.lock().await.set_window(window);
+    }
 
-        // Outbound dial loop (poll discovery)
-        let mut stop_rx2 = self.stop_tx.subscribe();
-        let identity3 = self.identity.clone();
-        let trust3 = Arc::clone(&self.trust_store);
-        let replay3 = Arc::clone(&self.replay);
-        let discovery3 = Arc::clone(&self.discovery);
-        let peers3 = Arc::clone(&self.peers);
-        let handler3 = Arc::clone(&self.handler);
-        let echo3 = Arc::clone(&self.echo_suppressor);
-        let registry3 = self.peer_registry.clone();
-        let history3 = Arc::clone(&self.history);
-        let dial_task = tokio::spawn(async move {
-            let endpoint = match make_insecure_client_endpoint() {
-                Ok(ep) => ep,
-                Err(e) => {
-                    handler3.on_error(format!("client endpoint failed: {e}"));
-                    return;
-                }
-            };
-            let endpoint = Arc::new(endpoint);
+    /// Also suppress a received clip's trimmed and line-ending-normalized
+    /// forms and, in mesh mode once the handler has applied it, what the
+    /// clipboard reads back as (on by default). Turn off for a
+    /// provider whose reads can lag its writes, where the read-back would be
+    /// the previous, locally copied text.
+    pub async fn set_note_after_write(&self, enabled: bool) {
+        self.echo_suppressor.lock().await.set_note_after_write(enabled);
+    }
 
-            loop {
-                tokio::select! {
-                    _ = stop_rx2.changed() => { break; }
-                    _ = tokio::time::sleep(std::time::Duration::from_millis(300)) => {}
-                }
+    /// The local peer id (derived from this service's identity).
+    pub fn local_peer_id(&self) -> String {
+        self.identity.peer_id().to_string()
+    }
 
-                let scanned = match discovery3.scan().await {
-                    Ok(v) => v,
-                    Err(e) => {
-                        handler3.on_error(format!("discovery scan failed: {e}"));
-                        continue;
-                    }
-                };
+    /// The device name this service advertises.
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
 
-                for peer in scanned {
-                    if peer.peer_id == identity3.peer_id().to_string() {
-                        continue;
-                    }
-                    // trust gate
-                    match trust3.is_trusted(&peer.peer_id) {
-                        Ok(true) => {}
-                        Ok(false) => continue,
-                        Err(e) => {
-                            handler3.on_error(format!("trust check failed: {e}"));
-                            continue;
-                        }
-                    }
-                    // dial rule
-                    if identity3.peer_id().to_string() >= peer.peer_id {
-                        continue;
-                    }
-                    // already connected?
-                    if peers3.lock().await.contains_key(&peer.peer_id) {
-                        continue;
-                    }
+    /// Drop the connection to `peer_id` and stop reconnecting to it (in either direction)
+    /// until `resume_peer` is called. Trust is left untouched.
+    ///
+    /// Returns `true` if the peer was connected.
+    pub async fn disconnect_peer(&self, peer_id: &str) -> bool {
+        self.suspended_peers.lock().unwrap().insert(peer_id.to_string());
+        self.terminate_session(peer_id).await
+    }
 
-                    let identity4 = identity3.clone();
-                    let trust4 = Arc::clone(&trust3);
-                    let replay4 = Arc::clone(&replay3);
-                    let peers4 = Arc::clone(&peers3);
-                    let handler4 = Arc::clone(&handler3);
-                    let echo4 = Arc::clone(&echo3);
-                    let registry4 = registry3.clone();
-                    let history4 = Arc::clone(&history3);
-                    let transport2 = QuicTransport::new((*endpoint).clone());
-                    tokio::spawn(async move {
-                        if let Err(e) = connect_loop(peer, transport2, identity4, trust4, replay4, peers4, handler4, echo4, registry4, history4).await {
-                            let _ = e;
-                        }
-                    });
-                }
-            }
-        });
+    /// Every open session, sorted by peer_id.
+    pub async fn active_sessions(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> = self.peers.lock().await.values().map(|h| h.info.clone()).collect();
+        sessions.sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+        sessions
+    }
 
-        let mut tasks = self.tasks.lock().await;
-        tasks.push(incoming_task);
-        tasks.push(dial_task);
-        Ok(())
+    /// Drop the current connection to `peer_id`, e.g. one that looks
+    /// suspicious. Unlike `disconnect_peer` the peer may connect again (the
+    /// usual redial applies); trust is left untouched either way.
+    ///
+    /// Returns `true` if the peer was connected.
+    pub async fn terminate_session(&self, peer_id: &str) -> bool {
+        // Dropping the outbound sender ends the peer's message loop, which says `Bye` and closes the session.
+        self.peers.lock().await.remove(peer_id).is_some()
     }
 
-    pub async fn stop(&self) {
-        let _ = self.stop_tx.send(true);
+    /// Allow `peer_id` to be dialed / accepted again after `disconnect_peer`.
+    pub fn resume_peer(&self, peer_id: &str) {
+        self.suspended_peers.lock().unwrap().remove(peer_id);
+    }
 
-        // stop discovery
-        let discovery = Arc::clone(&self.discovery);
-        tokio::spawn(async move {
-            let _ = discovery.stop_discovery().await;
-        });
+    /// Peers currently suspended via `disconnect_peer`, sorted by peer_id.
+    pub fn suspended_peers(&self) -> Vec<String> {
+        let mut v: Vec<String> = self.suspended_peers.lock().unwrap().iter().cloned().collect();
+        v.sort();
+        v
+    }
 
-        let mut tasks = self.tasks.lock().await;
-        for t in tasks.drain(..) {
-            t.abort();
+    /// Connected peers, registry, recent history and counters in one call.
+    ///
+    /// The session map is locked for the duration, so no peer connects or
+    /// disconnects between reading `connected` and `peers`; history and its
+    /// totals are read under the history lock.
+    pub async fn snapshot(&self, history_limit: usize) -> SyncSnapshot {
+        let sessions = self.peers.lock().await;
+        let mut connected: Vec<String> = sessions.keys().cloned().collect();
+        connected.sort();
+        let peers = self.peer_registry.list_all().await;
+        let (history, history_len, history_bytes) = self.history.recent_with_totals(history_limit);
+        drop(sessions);
+
+        SyncSnapshot {
+            connected,
+            peers,
+            history,
+            history_len,
+            history_bytes,
+            pending_requests: self.pending_requests(),
+            suspended: self.suspended_peers(),
         }
+    }
 
-        self.peers.lock().await.clear();
+    /// Clips recorded, files received and peers connected or disconnected
+    /// after `ts_ms`. Connection events are what the handler was told (so
+    /// after the disconnect grace period).
+    pub fn events_since(&self, ts_ms: u64) -> SyncSummary {
+        let mut files: Vec<ReceivedFile> =
+            self.received_files.list().into_iter().filter(|f| f.received_at_ms > ts_ms).collect();
+        files.reverse();
+        let connections =
+            self.presence.log.lock().unwrap().iter().filter(|e| e.timestamp_ms > ts_ms).cloned().collect();
+        SyncSummary { clips: self.history.since(ts_ms), files, connections }
     }
 
-    pub async fn broadcast_clip_text(&self, text: String) {
-        let peers = self.peers.lock().await;
-        for (peer_id, h) in peers.iter() {
-            let _ = h.outbound_tx.send(text.clone()).await;
-            let _ = peer_id;
-        }
+    /// Up to `limit` of the most recent errors reported to the handler, newest
+    /// first; at most the newest 64 are kept. Repeats the dial path holds back
+    /// (see the "still failing" summaries) aren't recorded.
+    pub fn recent_errors(&self, limit: usize) -> Vec<ErrorEntry> {
+        self.presence.errors.lock().unwrap().iter().rev().take(limit).cloned().collect()
     }
 
     /// Get a reference to the peer registry.
@@ -326,14 +1962,137 @@ impl<D: Discovery + 'static> SyncService<D> {
         &self.echo_suppressor
     }
 
+    /// Files received from peers, newest first.
+    pub fn received_files(&self) -> Vec<ReceivedFile> {
+        self.received_files.list()
+    }
+
+    /// Refuse file offers larger than `max` bytes (`None`: no limit);
+    /// [`crate::DEFAULT_MAX_RECEIVED_FILE_SIZE`] by default.
+    pub fn set_max_incoming_file_size(&self, max: Option<u64>) {
+        self.received_files.set_max_file_size(max);
+    }
+
+    pub fn max_incoming_file_size(&self) -> Option<u64> {
+        self.received_files.max_file_size()
+    }
+
+    /// The log behind `received_files`, e.g. to set its download directory or
+    /// persist it.
+    pub fn file_log(&self) -> &Arc<FileReceiveLog> {
+        &self.received_files
+    }
+
     /// Get a reference to the clipboard history.
     pub fn history(&self) -> &Arc<ClipboardHistory> {
         &self.history
     }
 
-    /// Get a reference to the silent-write flag.
-    pub fn silent_write_flag(&self) -> &Arc<std::sync::atomic::AtomicBool> {
-        &self.silent_write
+    /// Preview of the most recent clip received from `peer_id`, if any.
+    pub fn last_clip_for_peer(&self, peer_id: &str) -> Option<String> {
+        self.history
+            .last_for_peer(peer_id)
+            .map(|e| e.preview(crate::history::PREVIEW_MAX_CHARS))
+    }
+
+    /// Don't fan out the watcher's next change if it is `text` (a history
+    /// recall's own write). Any other change, or none within `timeout`, drops
+    /// the arm, so a write that never reached the clipboard can't swallow the
+    /// user's next copy.
+    pub fn arm_silent_write(&self, text: &str, timeout: std::time::Duration) {
+        *self.silent_write.lock().unwrap() = Some((text.to_string(), std::time::Instant::now() + timeout));
+    }
+
+    /// Drop a pending [`arm_silent_write`](Self::arm_silent_write).
+    pub fn disarm_silent_write(&self) {
+        *self.silent_write.lock().unwrap() = None;
+    }
+
+    /// Whether a silent write is armed and hasn't been seen or expired.
+    pub fn silent_write_armed(&self) -> bool {
+        self.silent_write
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|(_, deadline)| std::time::Instant::now() < *deadline)
+    }
+
+    /// Hold back `on_peer_disconnected` for `grace`; a peer that reconnects
+    /// within it produces neither that event nor a new `on_peer_connected`.
+    /// Zero (the default) reports disconnects immediately.
+    pub fn set_disconnect_grace(&self, grace: std::time::Duration) {
+        self.presence.grace_ms.store(grace.as_millis() as u64, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    pub fn disconnect_grace(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.presence.grace_ms.load(std::sync::atomic::Ordering::SeqCst))
+    }
+
+    /// Enable or disable syncing of empty / whitespace-only clipboard text in mesh mode.
+    ///
+    /// Enabled by default, like any other text; disable it to treat a blank
+    /// copy as "nothing to sync".
+    pub fn set_sync_whitespace(&self, enabled: bool) {
+        self.sync_whitespace.store(enabled, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Whether empty / whitespace-only clipboard text is synced in mesh mode.
+    pub fn sync_whitespace(&self) -> bool {
+        self.sync_whitespace.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Refuse to sync with peers whose session isn't application-layer encrypted.
+    ///
+    /// Off by default. Applies to sessions established after the change; each
+    /// peer's negotiated state is reported in `PeerEntry::encrypted`.
+    pub fn set_require_encryption(&self, required: bool) {
+        self.require_encryption.store(required, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Whether unencrypted sessions are refused.
+    pub fn requires_encryption(&self) -> bool {
+        self.require_encryption.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Offer application-layer encryption in each handshake (on by default);
+    /// a session is encrypted when both peers offer it. Turning it off makes
+    /// this device a plaintext peer, e.g. to test a "require encryption"
+    /// policy. Applies to sessions established after the change.
+    pub fn set_offer_encryption(&self, offer: bool) {
+        self.offer_encryption.store(offer, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Whether handshakes offer application-layer encryption.
+    pub fn offers_encryption(&self) -> bool {
+        self.offer_encryption.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Set the platform and app version reported to peers (`PeerEntry::platform`,
+    /// `PeerEntry::app_version` on their side). Applies from the next `start`.
+    pub fn set_app_info(&self, platform: impl Into<String>, app_version: impl Into<String>) {
+        *self.app_info.lock().unwrap() = AppInfo { platform: platform.into(), app_version: app_version.into() };
+    }
+
+    /// Platform and app version reported to peers.
+    pub fn app_info(&self) -> AppInfo {
+        self.app_info.lock().unwrap().clone()
+    }
+
+    /// Set which way clips flow with a trusted peer. Takes effect on open sessions.
+    ///
+    /// Returns `false` if the peer isn't trusted.
+    pub fn set_sync_direction(&self, peer_id: &str, direction: SyncDirection) -> Result<bool> {
+        let Some(mut record) = self.trust_store.get(peer_id)? else {
+            return Ok(false);
+        };
+        record.sync_direction = direction;
+        self.trust_store.save(record)?;
+        Ok(true)
+    }
+
+    /// The sync direction configured for a trusted peer.
+    pub fn sync_direction(&self, peer_id: &str) -> Result<Option<SyncDirection>> {
+        Ok(self.trust_store.get(peer_id)?.map(|r| r.sync_direction))
     }
 
     /// Get a reference to the pending pair peers set.
@@ -346,6 +2105,45 @@ impl<D: Discovery + 'static> SyncService<D> {
         self.pending_pair_peers.lock().unwrap().insert(peer_id.to_string());
     }
 
+    /// Recent connection attempts from untrusted peers, newest first.
+    pub fn pending_requests(&self) -> Vec<PendingRequest> {
+        self.pending_requests.list()
+    }
+
+    /// Trust a peer from the pending requests list so its next connection is accepted.
+    ///
+    /// Returns `false` if there is no pending request for `peer_id`.
+    pub async fn accept_pending_request(&self, peer_id: &str, display_name: &str) -> Result<bool> {
+        let Some(req) = self.pending_requests.take(peer_id) else {
+            return Ok(false);
+        };
+        BlockingTrustStore::new(Arc::clone(&self.trust_store))
+            .save(crate::trust::TrustRecord {
+                peer_id: req.peer_id,
+                identity_pk: req.identity_pk,
+                display_name: display_name.to_string(),
+                created_at: chrono::Utc::now(),
+                sync_direction: Default::default(),
+            })
+            .await?;
+        self.peer_registry.load_from_async_trust(&BlockingTrustStore::new(Arc::clone(&self.trust_store))).await?;
+        Ok(true)
+    }
+
+    /// Stop trusting `peer_id`: revoke its trust record, forget its replay
+    /// nonces, drop its connection and registry entry, and tell linked
+    /// devices about the revocation.
+    ///
+    /// Returns `false` if it wasn't trusted.
+    pub async fn remove_trusted_peer(&self, peer_id: &str) -> Result<bool> {
+        let removed = BlockingTrustStore::new(Arc::clone(&self.trust_store)).revoke(peer_id).await?;
+        self.replay.forget_peer(peer_id)?;
+        self.terminate_session(peer_id).await;
+        self.peer_registry.remove(peer_id).await;
+        self.sync_trust().await;
+        Ok(removed)
+    }
+
     /// Dial a specific address to initiate a pairing connection.
     /// Used after QR scan: we already trust them, now connect.
     pub async fn dial_peer_for_pair(&self, addr: &str) -> Result<()> {
@@ -355,41 +2153,65 @@ impl<D: Discovery + 'static> SyncService<D> {
         let conn = transport.connect(addr).await
             .with_context(|| format!("dial {addr} for pairing"))?;
 
-        let session = Session::with_trust_and_replay(
-            conn,
-            self.identity.clone(),
-            crate::clipboard::MockClipboard::new(),
-            self.trust_store.clone(),
-            self.replay.clone(),
+        let session = sync_session(
+            Session::with_trust_and_replay(
+                conn,
+                self.identity.clone(),
+                crate::clipboard::MockClipboard::new(),
+                self.trust_store.clone(),
+                self.replay.clone(),
+            ),
+            self.device_name.clone(),
+            self.offers_encryption(),
         );
 
-        let peer_id = session.handshake().await
+        let hs = session.handshake_full().await
             .with_context(|| format!("handshake with {addr} for pairing"))?;
+        session.confirm_mutual_trust(TRUST_ACK_TIMEOUT).await
+            .with_context(|| format!("trust ack from {addr} for pairing"))?;
+        let peer_id = hs.peer_id;
+        if !hs.encrypted && self.requires_encryption() {
+            session.conn.close();
+            anyhow::bail!("refusing unencrypted session with {peer_id}");
+        }
 
         // Set up the peer message loop
-        let (tx, rx) = mpsc::channel::<String>(32);
+        let (tx, rx) = mpsc::channel::<Outbound>(32);
+        let session_id;
         {
             let mut map = self.peers.lock().await;
             if map.contains_key(&peer_id) {
                 return Ok(());
             }
-            map.insert(peer_id.clone(), PeerHandle { outbound_tx: tx });
+            let handle = PeerHandle::new(tx, &peer_id, Some(addr.to_string()), hs.encrypted, true);
+            session_id = handle.session;
+            map.insert(peer_id.clone(), handle);
+            self.peer_registry.set_online(&peer_id, Some(addr.to_string())).await;
+            self.peer_registry.set_encrypted(&peer_id, hs.encrypted).await;
         }
 
-        self.peer_registry.set_online(&peer_id, Some(addr.to_string())).await;
         self.handler.on_peer_connected(peer_id.clone());
+        let app_info = self.app_info();
+        if let Err(e) = session.send_device_info(&app_info.platform, &app_info.app_version).await {
+            report_error(self.handler.as_ref(), ErrorCategory::Send, Some(peer_id.as_str()), format!("send device info to {peer_id} failed: {e}"));
+        }
 
         let peers = Arc::clone(&self.peers);
         let handler = Arc::clone(&self.handler);
         let echo_sup = Arc::clone(&self.echo_suppressor);
         let registry = self.peer_registry.clone();
         let history = Arc::clone(&self.history);
+        let received_files = Arc::clone(&self.received_files);
+        let quiet_hours = Arc::clone(&self.quiet_hours);
+        let keepalive = Arc::clone(&self.keepalive);
+        let latest_clip = Arc::clone(&self.latest_clip);
+        let file_rate_limits = Arc::clone(&self.file_rate_limits.lock().unwrap());
         let peer_id2 = peer_id.clone();
         let task = tokio::spawn(async move {
-            let _ = peer_message_loop(session, peer_id2.clone(), rx, handler.clone(), echo_sup, history).await;
-            peers.lock().await.remove(&peer_id2);
-            registry.set_offline(&peer_id2).await;
-            handler.on_peer_disconnected(peer_id2);
+            let _ = peer_message_loop(session, peer_id2.clone(), rx, handler.clone(), echo_sup, registry.clone(), history, received_files, quiet_hours, keepalive, latest_clip, file_rate_limits).await;
+            if forget_session(&peers, &registry, &peer_id2, session_id).await {
+                handler.on_peer_disconnected(peer_id2);
+            }
         });
 
         self.tasks.lock().await.push(task);
@@ -406,17 +2228,24 @@ impl<D: Discovery + 'static> SyncService<D> {
         poll_interval: std::time::Duration,
     ) -> Result<()> {
         // Load trust store into peer registry.
-        self.peer_registry.load_from_trust(self.trust_store.as_ref()).await?;
+        self.peer_registry.load_from_async_trust(&BlockingTrustStore::new(Arc::clone(&self.trust_store))).await?;
 
         // Start the normal sync (listener + discovery + dial).
         self.start().await?;
 
+        // The handler writes received clips to `provider`; see `EchoSuppressor::read_back`.
+        self.echo_suppressor.lock().await.set_read_back(Some(Arc::clone(&provider)));
+
         // Start clipboard watcher.
         let stop_rx = self.stop_tx.subscribe();
         let echo_sup = Arc::clone(&self.echo_suppressor);
         let peers = Arc::clone(&self.peers);
         let watcher_history = Arc::clone(&self.history);
-        let silent_flag = Arc::clone(&self.silent_write);
+        let silent_write = Arc::clone(&self.silent_write);
+        let sync_whitespace = Arc::clone(&self.sync_whitespace);
+        let quiet_hours = Arc::clone(&self.quiet_hours);
+        let latest_clip = Arc::clone(&self.latest_clip);
+        let watcher_handler = Arc::clone(&self.handler);
 
         let watcher = crate::mesh::start_clipboard_watcher(
             provider,
@@ -426,14 +2255,26 @@ impl<D: Discovery + 'static> SyncService<D> {
             move |content| {
                 if let ClipboardContent::Text(text) = content {
                     // Check if this is a silent recall write — skip fanout if so.
-                    if silent_flag.swap(false, std::sync::atomic::Ordering::SeqCst) {
+                    let silent = silent_write.lock().unwrap().take();
+                    if silent.is_some_and(|(t, deadline)| t == text && std::time::Instant::now() < deadline) {
                         // Still record in history as local.
                         watcher_history.record(text, "local".into());
                         return;
                     }
 
+                    // Blank text is skipped when whitespace sync is turned off.
+                    if text.trim().is_empty() && !sync_whitespace.load(std::sync::atomic::Ordering::SeqCst) {
+                        return;
+                    }
+
                     // Record local clipboard change in history.
                     watcher_history.record(text.clone(), "local".into());
+                    let ts_ms = latest_clip.tick();
+                    latest_clip.note(ts_ms, &text);
+
+                    if quiet_hours.pauses_outgoing() {
+                        return;
+                    }
 
                     // Fan out to all connected peers (fire-and-forget from the watcher's perspective).
                     let peers = peers.clone();
@@ -442,7 +2283,27 @@ impl<D: Discovery + 'static> SyncService<D> {
                         handle.spawn(async move {
                             let map = peers.lock().await;
                             for (_pid, h) in map.iter() {
-                                let _ = h.outbound_tx.send(text.clone()).await;
+                                let _ = h.outbound_tx.send(Outbound::Text(text.clone(), ts_ms)).await;
+                            }
+                        });
+                    }
+                } else if let ClipboardContent::Image { mime, width, height, bytes } = content {
+                    watcher_history.record_image(mime.clone(), width, height, bytes.clone(), "local".into());
+                    let msg = match clip_image_message(mime, width, height, &bytes, crate::session::now_ms()) {
+                        Ok(msg) => msg,
+                        Err(e) => {
+                            report_error(watcher_handler.as_ref(), ErrorCategory::Send, None, e.to_string());
+                            return;
+                        }
+                    };
+                    if quiet_hours.pauses_outgoing() {
+                        return;
+                    }
+                    let peers = peers.clone();
+                    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                        handle.spawn(async move {
+                            for h in peers.lock().await.values() {
+                                let _ = h.outbound_tx.send(Outbound::Image(msg.clone())).await;
                             }
                         });
                     }
@@ -450,235 +2311,1119 @@ impl<D: Discovery + 'static> SyncService<D> {
             },
         );
 
-        self.tasks.lock().await.push(watcher);
+        if let Some(previous) = self.watcher.lock().unwrap().replace(watcher) {
+            previous.abort();
+        }
         Ok(())
     }
 }
 
+/// A `ClipImage` carrying `bytes`, or an error if it wouldn't fit in one frame.
+fn clip_image_message(mime: String, width: u32, height: u32, bytes: &[u8], ts_ms: u64) -> Result<Message> {
+    let max = crate::protocol::MAX_CLIP_IMAGE_LEN;
+    if bytes.len() > max {
+        anyhow::bail!("{mime} image of {} bytes not sent: images over {max} bytes can't be synced", bytes.len());
+    }
+    let bytes_b64 = crate::b64::Base64Alphabet::Standard.encode(bytes);
+    Ok(Message::ClipImage { mime, width, height, bytes_b64, ts_ms })
+}
+
+/// Offer everything sync sessions use: device name, binary codec, clip and
+/// trust acks, raw file chunks, clip text compression and, when `encrypt`,
+/// encrypted payloads.
+fn sync_session<C: crate::transport::Connection, I: crate::identity::IdentityProvider, P: ClipboardProvider>(
+    session: Session<C, I, P>,
+    device_name: String,
+    encrypt: bool,
+) -> Session<C, I, P> {
+    let session = session.with_origin(device_name).with_binary_codec().with_clip_ack().with_raw_file_chunks().with_trust_ack()
+        .with_compression_threshold(CLIP_COMPRESSION_THRESHOLD);
+    if encrypt { session.with_encryption() } else { session }
+}
+
+fn is_addr_in_use(e: &anyhow::Error) -> bool {
+    e.chain()
+        .filter_map(|c| c.downcast_ref::<std::io::Error>())
+        .any(|io| io.kind() == std::io::ErrorKind::AddrInUse)
+}
+
 async fn handle_incoming_connection(
-    conn: crate::quic_transport::QuicConnection,
+    conn: BoxConnection,
     identity: Ed25519Identity,
     trust_store: Arc<dyn TrustStore>,
-    replay: Arc<MemoryReplayProtector>,
+    replay: Arc<dyn ReplayProtector>,
     peers: Arc<Mutex<HashMap<String, PeerHandle>>>,
     handler: Arc<dyn SyncHandler>,
     echo_suppressor: Arc<Mutex<EchoSuppressor>>,
     registry: PeerRegistry,
     history: Arc<ClipboardHistory>,
+    received_files: Arc<FileReceiveLog>,
     pending_pair_peers: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+    pending_requests: Arc<PendingRequests>,
+    suspended_peers: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+    device_name: String,
+    app_info: AppInfo,
+    require_encryption: Arc<std::sync::atomic::AtomicBool>,
+    offer_encryption: Arc<std::sync::atomic::AtomicBool>,
+    quiet_hours: Arc<QuietSchedule>,
+    keepalive: Arc<std::sync::Mutex<Option<Keepalive>>>,
+    latest_clip: Arc<LatestClip>,
+    file_rate_limits: Arc<FileRateLimits>,
+    stop_rx: watch::Receiver<bool>,
 ) -> Result<()> {
-    // Check if we have pending pair peers — if so, use pairing mode
-    let has_pending = !pending_pair_peers.lock().unwrap().is_empty();
+    let remote_addr = conn.remote_addr();
 
-    let session = if has_pending {
+    // Always handshake in pairing mode: trust is checked below so that untrusted
+    // attempts can be recorded as pending requests instead of just dropped.
+    let session = sync_session(
         Session::with_pairing_mode_and_replay(
             conn,
             identity.clone(),
             crate::clipboard::MockClipboard::new(),
             trust_store.clone(),
             replay.clone(),
-        )
-    } else {
-        Session::with_trust_and_replay(
-            conn,
-            identity.clone(),
-            crate::clipboard::MockClipboard::new(),
-            trust_store.clone(),
-            replay.clone(),
-        )
-    };
+        ),
+        device_name,
+        offer_encryption.load(std::sync::atomic::Ordering::SeqCst),
+    );
 
     let hs = match session.handshake_full().await {
         Ok(r) => r,
         Err(e) => {
-            handler.on_error(format!("incoming handshake failed: {e}"));
+            report_error(handler.as_ref(), ErrorCategory::Handshake, None, format!("incoming handshake failed: {e}"));
+            if let Some(reason) = HandshakeError::of(&e) {
+                let from = remote_addr.map(|a| a.to_string()).unwrap_or_default();
+                handler.on_handshake_failed(from, reason);
+            }
+            session.conn.close();
             return Ok(());
         }
     };
     let peer_id = hs.peer_id;
+    let encrypted = hs.encrypted;
 
-    // If pairing mode was used, check if peer is pending or trusted
-    if has_pending {
-        let is_trusted = trust_store.is_trusted(&peer_id)?;
-        let was_pending = {
-            let mut set = pending_pair_peers.lock().unwrap();
-            // Check for specific peer_id or wildcard "*"
-            set.remove(&peer_id) || set.contains("*")
-        };
+    if peer_id == identity.peer_id() {
+        let from = remote_addr.map(|a| a.to_string()).unwrap_or_default();
+        report_duplicate_identity(handler.as_ref(), &from);
+        // Let the other device finish reading our HELLO so it notices too.
+        let _ = tokio::time::timeout(TRUST_ACK_TIMEOUT, async { while session.recv_message().await.is_ok() {} }).await;
+        session.conn.close();
+        return Ok(());
+    }
 
-        if !is_trusted && !was_pending {
-            // Unknown peer, not pending — reject
-            handler.on_error(format!("rejecting untrusted peer {}", peer_id));
+    let async_trust = BlockingTrustStore::new(Arc::clone(&trust_store));
+    let is_trusted = match async_trust.get(&peer_id).await? {
+        Some(rec) if rec.identity_pk == hs.identity_pk => true,
+        Some(_) => {
+            report_error(handler.as_ref(), ErrorCategory::Handshake, Some(peer_id.as_str()), format!("incoming handshake failed: trusted peer public key mismatch: {peer_id}"));
+            handler.on_handshake_failed(peer_id, HandshakeError::KeyChanged);
+            refuse_trust(&session).await;
             session.conn.close();
             return Ok(());
         }
+        None => false,
+    };
+    let was_pending = {
+        let mut set = pending_pair_peers.lock().unwrap();
+        // Check for specific peer_id or wildcard "*"
+        set.remove(&peer_id) || set.contains("*")
+    };
 
-        if was_pending && !is_trusted {
-            // Auto-trust this peer back — they scanned our QR and connected
-            use crate::trust::TrustRecord as CoreTrustRecord;
-            let record = CoreTrustRecord {
-                peer_id: peer_id.clone(),
-                identity_pk: hs.identity_pk,
-                display_name: peer_id.clone(), // We don't know their name yet
-                created_at: chrono::Utc::now(),
-            };
-            trust_store.save(record)?;
-            // Also add to peer registry
-            registry.load_from_trust(trust_store.as_ref()).await?;
+    if !is_trusted && !was_pending {
+        // Unknown peer, not pending — remember the attempt, then reject.
+        pending_requests.record(PendingRequest {
+            peer_id: peer_id.clone(),
+            fingerprint: Ed25519Identity::fingerprint_from_public_key(&hs.identity_pk),
+            identity_pk: hs.identity_pk,
+            addr: remote_addr.map(|a| a.to_string()),
+            timestamp_ms: crate::session::now_ms(),
+        });
+        report_error(handler.as_ref(), ErrorCategory::Handshake, Some(peer_id.as_str()), format!("rejecting untrusted peer {}", peer_id));
+        handler.on_handshake_failed(peer_id, HandshakeError::Untrusted);
+        refuse_trust(&session).await;
+        session.conn.close();
+        return Ok(());
+    }
+
+    if was_pending && !is_trusted {
+        // Auto-trust this peer back — they scanned our QR and connected
+        use crate::trust::TrustRecord as CoreTrustRecord;
+        let record = CoreTrustRecord {
+            peer_id: peer_id.clone(),
+            identity_pk: hs.identity_pk,
+            display_name: peer_id.clone(), // We don't know their name yet
+            created_at: chrono::Utc::now(),
+            sync_direction: Default::default(),
+        };
+        async_trust.save(record).await?;
+        pending_requests.take(&peer_id);
+        // Also add to peer registry
+        registry.load_from_async_trust(&async_trust).await?;
+    }
+
+    if let Err(e) = session.confirm_mutual_trust(TRUST_ACK_TIMEOUT).await {
+        report_error(handler.as_ref(), ErrorCategory::Handshake, Some(peer_id.as_str()), format!("incoming handshake failed: {peer_id}: {e}"));
+        if let Some(reason) = HandshakeError::of(&e) {
+            handler.on_handshake_failed(peer_id, reason);
+        }
+        session.conn.close();
+        return Ok(());
+    }
+
+    if suspended_peers.lock().unwrap().contains(&peer_id) {
+        session.conn.close();
+        return Ok(());
+    }
+
+    if !encrypted && require_encryption.load(std::sync::atomic::Ordering::SeqCst) {
+        report_error(handler.as_ref(), ErrorCategory::Handshake, Some(peer_id.as_str()), format!("refusing unencrypted session with {peer_id}"));
+        session.conn.close();
+        return Ok(());
+    }
+
+    // dedupe: if we're the dialer, prefer outbound
+    let local_id = identity.peer_id().to_string();
+    if local_id < peer_id {
+        // We should be dialing; reject inbound to avoid duplicates.
+        handler.on_peer_disconnected(peer_id);
+        session.conn.close();
+        return Ok(());
+    }
+
+    let (tx, rx) = mpsc::channel::<Outbound>(32);
+    let handle = PeerHandle::new(tx, &peer_id, remote_addr.map(|a| a.to_string()), encrypted, false);
+    let session_id = handle.session;
+    {
+        let mut map = peers.lock().await;
+        // Also give up if the service stopped mid-handshake: `stop` has
+        // already cleared the map, so nothing would end this session.
+        if map.contains_key(&peer_id) || stop_rx.has_changed().unwrap_or(true) {
+            session.conn.close();
+            return Ok(());
         }
+        map.insert(peer_id.clone(), handle);
+        // Under the map lock so `snapshot` sees the map and registry agree.
+        registry.set_online(&peer_id, None).await;
+        registry.set_encrypted(&peer_id, encrypted).await;
+    }
+
+    handler.on_peer_connected(peer_id.clone());
+    if let Err(e) = session.send_device_info(&app_info.platform, &app_info.app_version).await {
+        report_error(handler.as_ref(), ErrorCategory::Send, Some(peer_id.as_str()), format!("send device info to {peer_id} failed: {e}"));
+    }
+
+    let res = peer_message_loop(session, peer_id.clone(), rx, Arc::clone(&handler), Arc::clone(&echo_suppressor), registry.clone(), Arc::clone(&history), Arc::clone(&received_files), Arc::clone(&quiet_hours), Arc::clone(&keepalive), Arc::clone(&latest_clip), Arc::clone(&file_rate_limits)).await;
+
+    if forget_session(&peers, &registry, &peer_id, session_id).await {
+        handler.on_peer_disconnected(peer_id);
+    }
+
+    res.map(|_| ())
+}
+
+/// Listener errors in a row (ones not tied to a single incoming connection)
+/// after which an accept loop treats its listener as dead.
+const MAX_CONSECUTIVE_LISTENER_ERRORS: u32 = 10;
+
+/// How long to wait for the peer's `TrustAck` after the handshake.
+const TRUST_ACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Clip text longer than this goes out zstd-compressed to peers that offer it.
+const CLIP_COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Tell a peer we don't trust it, when trust acks were negotiated, and give
+/// it a moment to read that (until it hangs up) before the session goes.
+async fn refuse_trust<C: crate::transport::Connection, I: crate::identity::IdentityProvider, P: ClipboardProvider>(
+    session: &Session<C, I, P>,
+) {
+    if !session.trust_ack_enabled() || session.send_trust_ack(false).await.is_err() {
+        return;
+    }
+    let _ = tokio::time::timeout(TRUST_ACK_TIMEOUT, async { while session.recv_message().await.is_ok() {} }).await;
+}
+
+/// `Bye` reasons this service sends.
+const BYE_STOPPING: &str = "stopping";
+const BYE_TERMINATED: &str = "terminated";
+
+/// How long to wait for a peer to hang up after `Bye`, so the frame isn't lost
+/// with the connection.
+const BYE_GRACE: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How long `connect_loop` waits before redialing a peer that said it is stopping.
+const BYE_QUARANTINE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Say `Bye` and wait (up to `BYE_GRACE`) for the peer to hang up.
+/// Queue a `Bye` and, keeping the session's `send_task` going, give it up to
+/// `BYE_GRACE` to go out and the peer to hang up; then close the connection.
+async fn say_bye<C: crate::transport::Connection, I: crate::identity::IdentityProvider, P: ClipboardProvider>(
+    session: &Session<C, I, P>,
+    mut send_task: std::pin::Pin<&mut impl std::future::Future<Output = Result<()>>>,
+    reason: &str,
+) {
+    let (sent_tx, sent_rx) = tokio::sync::oneshot::channel();
+    if session.enqueue_bye(reason, Some(Box::new(move || { let _ = sent_tx.send(()); }))) {
+        let _ = tokio::time::timeout(BYE_GRACE, async {
+            tokio::select! {
+                sent = sent_rx => if sent.is_err() { return },
+                _ = &mut send_task => return,
+            }
+            loop {
+                tokio::select! {
+                    res = session.recv_message() => if res.is_err() { return },
+                    _ = &mut send_task => return,
+                }
+            }
+        })
+        .await;
     }
+    session.conn.close();
+}
 
-    // dedupe: if we're the dialer, prefer outbound
-    let local_id = identity.peer_id().to_string();
-    if local_id < peer_id {
-        // We should be dialing; reject inbound to avoid duplicates.
-        handler.on_peer_disconnected(peer_id);
-        session.conn.close();
-        return Ok(());
+/// Whether `addr` is one this device listens on: one of `own_addrs`, or the
+/// port of one of them on a local interface, as discovery may report our own
+/// advertisement under a LAN address rather than the one we bound.
+fn is_own_address(addr: &str, own_addrs: &[String]) -> bool {
+    if own_addrs.iter().any(|a| a == addr) {
+        return true;
     }
+    let Ok(addr) = addr.parse::<SocketAddr>() else { return false };
+    let own_port = own_addrs.iter().filter_map(|a| a.parse::<SocketAddr>().ok()).any(|a| a.port() == addr.port());
+    let local_ip = addr.ip().is_loopback()
+        || addr.ip().is_unspecified()
+        || crate::pairing::get_local_ip_addresses().contains(&addr.ip().to_string());
+    own_port && local_ip
+}
 
-    let (tx, rx) = mpsc::channel::<String>(32);
+/// Handshake with `peer`, which advertises our own peer_id from elsewhere, and
+/// warn if it proves it holds our key: the identity file was copied to
+/// another device.
+async fn probe_duplicate_identity(peer: PeerInfo, transport: BoxTransport, identity: Ed25519Identity, handler: Arc<dyn SyncHandler>) {
+    let Ok((conn, addr)) = dial_any(transport.as_ref(), &peer).await else { return };
+    let session = Session::new(conn, identity.clone(), crate::clipboard::MockClipboard::new());
+    if let Ok(hs) = session.handshake_full().await
+        && hs.identity_pk == identity.public_key_bytes()
     {
-        let mut map = peers.lock().await;
-        if map.contains_key(&peer_id) {
-            return Ok(());
-        }
-        map.insert(peer_id.clone(), PeerHandle { outbound_tx: tx });
+        report_duplicate_identity(handler.as_ref(), &addr);
     }
+    session.conn.close();
+}
 
-    registry.set_online(&peer_id, None).await;
-    handler.on_peer_connected(peer_id.clone());
+/// Tell the user another device at `addr` presented this device's identity.
+fn report_duplicate_identity(handler: &dyn SyncHandler, addr: &str) {
+    report_error(handler, ErrorCategory::Handshake, None, format!("another device ({addr}) is using this device's identity; generate a new identity on one of them"));
+    handler.on_handshake_failed(addr.to_string(), HandshakeError::DuplicateIdentity);
+}
 
-    let res = peer_message_loop(session, peer_id.clone(), rx, Arc::clone(&handler), Arc::clone(&echo_suppressor), Arc::clone(&history)).await;
+/// A supervised loop's name and, if it ended on its own, why.
+type TaskExit = (String, std::result::Result<(), String>);
 
-    peers.lock().await.remove(&peer_id);
-    registry.set_offline(&peer_id).await;
-    handler.on_peer_disconnected(peer_id);
+/// Run until `stop_rx` fires, reporting each critical task that ends early
+/// (returns an error or panics); then abort the rest and wait for them.
+async fn supervise(mut critical: tokio::task::JoinSet<TaskExit>, handler: Arc<dyn SyncHandler>, mut stop_rx: watch::Receiver<bool>) {
+    loop {
+        tokio::select! {
+            biased;
+            _ = stop_rx.changed() => break,
+            next = critical.join_next() => {
+                let (task, reason) = match next {
+                    None => break,
+                    Some(Ok((_, Ok(())))) => continue,
+                    Some(Ok((task, Err(reason)))) => (task, reason),
+                    Some(Err(e)) if e.is_panic() => ("sync task".to_string(), format!("panicked: {e}")),
+                    Some(Err(_)) => continue,
+                };
+                report_error(handler.as_ref(), ErrorCategory::Other, None, format!("sync stopped: {task} ended: {reason}"));
+                handler.on_sync_stopped(task, reason);
+            }
+        }
+    }
+    critical.shutdown().await;
+}
 
-    res
+/// Dial `peer.addr`, then each of `peer.alt_addrs`; returns the first connection
+/// with the address it was made to, or the last error.
+async fn dial_any(transport: &dyn Transport<Conn = BoxConnection>, peer: &PeerInfo) -> Result<(BoxConnection, String)> {
+    let mut last_err = None;
+    for addr in peer.addresses() {
+        match transport.connect(&addr).await {
+            Ok(conn) => return Ok((conn, addr)),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no address to dial")))
 }
 
 async fn connect_loop(
     peer: PeerInfo,
-    transport: QuicTransport,
+    transport: BoxTransport,
     identity: Ed25519Identity,
     trust_store: Arc<dyn TrustStore>,
-    replay: Arc<MemoryReplayProtector>,
+    replay: Arc<dyn ReplayProtector>,
     peers: Arc<Mutex<HashMap<String, PeerHandle>>>,
     handler: Arc<dyn SyncHandler>,
     echo_suppressor: Arc<Mutex<EchoSuppressor>>,
     registry: PeerRegistry,
     history: Arc<ClipboardHistory>,
+    received_files: Arc<FileReceiveLog>,
+    suspended_peers: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+    unreachable_peers: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+    device_name: String,
+    app_info: AppInfo,
+    require_encryption: Arc<std::sync::atomic::AtomicBool>,
+    offer_encryption: Arc<std::sync::atomic::AtomicBool>,
+    dial_errors: Arc<ErrorCoalescer>,
+    dial_permits: Arc<tokio::sync::Semaphore>,
+    retry_budget: RetryBudget,
+    quiet_hours: Arc<QuietSchedule>,
+    keepalive: Arc<std::sync::Mutex<Option<Keepalive>>>,
+    latest_clip: Arc<LatestClip>,
+    file_rate_limits: Arc<FileRateLimits>,
+    stop_rx: watch::Receiver<bool>,
 ) -> Result<()> {
     let mut backoff = Backoff::new();
+    let mut retries = RetryTracker::new(retry_budget);
+    // Out of budget: park the peer until it's revived and end this loop.
+    let give_up = |failures: u32, last_error: String| {
+        let (peer, registry, handler) = (&peer, &registry, &handler);
+        let (unreachable_peers, dial_errors) = (&unreachable_peers, &dial_errors);
+        async move {
+            unreachable_peers.lock().unwrap().insert(peer.peer_id.clone());
+            registry.set_unreachable(&peer.peer_id).await;
+            dial_errors.clear(&peer.peer_id);
+            report_error(handler.as_ref(), ErrorCategory::Dial, Some(peer.peer_id.as_str()), format!("giving up on {} after {failures} failed attempts, last: {last_error}", peer.peer_id));
+        }
+    };
 
     loop {
-        // If already connected (race), stop.
+        // Held through dial and handshake only, never while backing off.
+        let permit = dial_permits.clone().acquire_owned().await?;
+
+        // If the service stopped, already connected (race) or disconnected by the user, stop.
+        if stop_rx.has_changed().unwrap_or(true) {
+            return Ok(());
+        }
         if peers.lock().await.contains_key(&peer.peer_id) {
             return Ok(());
         }
+        if suspended_peers.lock().unwrap().contains(&peer.peer_id) {
+            return Ok(());
+        }
 
-        let conn = match transport.connect(&peer.addr).await {
+        let (conn, dialed_addr) = match dial_any(transport.as_ref(), &peer).await {
             Ok(c) => c,
             Err(e) => {
+                drop(permit);
+                if retries.fail() {
+                    give_up(retries.failures, format!("dial: {e}")).await;
+                    return Ok(());
+                }
                 let d = backoff.next_delay();
-                handler.on_error(format!("dial {} failed: {e}; retrying in {:?}", peer.peer_id, d));
+                let key = format!("dial: {e}");
+                dial_errors.report(handler.as_ref(), ErrorCategory::Dial, &peer.peer_id, &key, format!("dial {} failed: {e}; retrying in {:?}", peer.peer_id, d));
                 tokio::time::sleep(d).await;
                 continue;
             }
         };
 
-        let session = Session::with_trust_and_replay(
-            conn,
-            identity.clone(),
-            crate::clipboard::MockClipboard::new(),
-            trust_store.clone(),
-            replay.clone(),
+        let session = sync_session(
+            Session::with_trust_and_replay(
+                conn,
+                identity.clone(),
+                crate::clipboard::MockClipboard::new(),
+                trust_store.clone(),
+                replay.clone(),
+            ),
+            device_name.clone(),
+            offer_encryption.load(std::sync::atomic::Ordering::SeqCst),
         );
 
-        let peer_id = match session.handshake().await {
-            Ok(p) => p,
+        let handshake = match session.handshake_full().await {
+            Ok(hs) => session.confirm_mutual_trust(TRUST_ACK_TIMEOUT).await.map(|()| hs),
+            Err(e) => Err(e),
+        };
+        drop(permit);
+        let (peer_id, encrypted) = match handshake {
+            Ok(hs) => (hs.peer_id, hs.encrypted),
             Err(e) => {
+                if retries.fail() {
+                    give_up(retries.failures, format!("handshake: {e}")).await;
+                    return Ok(());
+                }
                 let d = backoff.next_delay();
-                handler.on_error(format!("handshake {} failed: {e}; retrying in {:?}", peer.peer_id, d));
+                let key = format!("handshake: {e}");
+                if dial_errors.report(handler.as_ref(), ErrorCategory::Handshake, &peer.peer_id, &key, format!("handshake {} failed: {e}; retrying in {:?}", peer.peer_id, d))
+                    && let Some(reason) = HandshakeError::of(&e)
+                {
+                    handler.on_handshake_failed(peer.peer_id.clone(), reason);
+                }
                 tokio::time::sleep(d).await;
                 continue;
             }
         };
 
+        if !encrypted && require_encryption.load(std::sync::atomic::Ordering::SeqCst) {
+            session.conn.close();
+            if retries.fail() {
+                give_up(retries.failures, "session not encrypted".into()).await;
+                return Ok(());
+            }
+            let d = backoff.next_delay();
+            dial_errors.report(handler.as_ref(), ErrorCategory::Handshake, &peer.peer_id, "unencrypted", format!("refusing unencrypted session with {}; retrying in {:?}", peer.peer_id, d));
+            tokio::time::sleep(d).await;
+            continue;
+        }
+
         if peer_id != peer.peer_id {
-            handler.on_error(format!("dialed {}, but handshake reported peer_id {}", peer.peer_id, peer_id));
+            report_error(handler.as_ref(), ErrorCategory::Handshake, Some(peer.peer_id.as_str()), format!("dialed {}, but handshake reported peer_id {}", peer.peer_id, peer_id));
         }
 
         backoff.reset();
+        retries.reset();
+        dial_errors.clear(&peer.peer_id);
 
-        let (tx, rx) = mpsc::channel::<String>(32);
+        let (tx, rx) = mpsc::channel::<Outbound>(32);
+        let handle = PeerHandle::new(tx, &peer.peer_id, Some(dialed_addr.clone()), encrypted, true);
+        let session_id = handle.session;
         {
             let mut map = peers.lock().await;
-            if map.contains_key(&peer.peer_id) {
-                // someone else connected while we were handshaking
+            if map.contains_key(&peer.peer_id) || stop_rx.has_changed().unwrap_or(true) {
+                // someone else connected while we were handshaking, or the service stopped
                 return Ok(());
             }
-            map.insert(peer.peer_id.clone(), PeerHandle { outbound_tx: tx });
+            map.insert(peer.peer_id.clone(), handle);
+            registry.set_online(&peer.peer_id, Some(dialed_addr)).await;
+            registry.set_encrypted(&peer.peer_id, encrypted).await;
         }
 
-        registry.set_online(&peer.peer_id, Some(peer.addr.clone())).await;
         handler.on_peer_connected(peer.peer_id.clone());
+        if let Err(e) = session.send_device_info(&app_info.platform, &app_info.app_version).await {
+            report_error(handler.as_ref(), ErrorCategory::Send, Some(peer.peer_id.as_str()), format!("send device info to {} failed: {e}", peer.peer_id));
+        }
 
-        let loop_res = peer_message_loop(session, peer.peer_id.clone(), rx, Arc::clone(&handler), Arc::clone(&echo_suppressor), Arc::clone(&history)).await;
+        let loop_res = peer_message_loop(session, peer.peer_id.clone(), rx, Arc::clone(&handler), Arc::clone(&echo_suppressor), registry.clone(), Arc::clone(&history), Arc::clone(&received_files), Arc::clone(&quiet_hours), Arc::clone(&keepalive), Arc::clone(&latest_clip), Arc::clone(&file_rate_limits)).await;
 
-        peers.lock().await.remove(&peer.peer_id);
-        registry.set_offline(&peer.peer_id).await;
-        handler.on_peer_disconnected(peer.peer_id.clone());
+        if forget_session(&peers, &registry, &peer.peer_id, session_id).await {
+            handler.on_peer_disconnected(peer.peer_id.clone());
+        }
 
-        let _ = loop_res;
-        let d = backoff.next_delay();
+        // A peer that is stopping won't be back right away: give it a while
+        // before redialing instead of the usual retry churn.
+        let d = match loop_res {
+            Ok(LoopEnd::Bye(reason)) if reason == BYE_STOPPING => BYE_QUARANTINE,
+            _ => backoff.next_delay(),
+        };
         tokio::time::sleep(d).await;
     }
 }
 
+/// The direction configured for `peer_id`, read per message so changes apply
+/// to open sessions. Peers without a trust record sync both ways.
+async fn peer_sync_direction(trust_store: Option<&Arc<dyn TrustStore>>, peer_id: &str) -> SyncDirection {
+    let Some(store) = trust_store else { return SyncDirection::default() };
+    BlockingTrustStore::new(Arc::clone(store))
+        .get(peer_id)
+        .await
+        .ok()
+        .flatten()
+        .map(|r| r.sync_direction)
+        .unwrap_or_default()
+}
+
 async fn peer_message_loop<C: crate::transport::Connection, I: crate::identity::IdentityProvider, P: ClipboardProvider>(
     session: Session<C, I, P>,
     peer_id: String,
-    mut outbound_rx: mpsc::Receiver<String>,
+    mut outbound_rx: mpsc::Receiver<Outbound>,
     handler: Arc<dyn SyncHandler>,
     echo_suppressor: Arc<Mutex<EchoSuppressor>>,
+    registry: PeerRegistry,
     history: Arc<ClipboardHistory>,
-) -> Result<()> {
+    received_files: Arc<FileReceiveLog>,
+    quiet_hours: Arc<QuietSchedule>,
+    keepalive: Arc<std::sync::Mutex<Option<Keepalive>>>,
+    latest_clip: Arc<LatestClip>,
+    file_rate_limits: Arc<FileRateLimits>,
+) -> Result<LoopEnd> {
+    // Files we offered, waiting on the peer's accept/reject; files it is sending us.
+    let mut offered: HashMap<String, OutboundFile> = HashMap::new();
+    let mut incoming = IncomingFiles::new(peer_id.clone(), &received_files, Arc::clone(&handler));
+    // Accepted files waiting on the peer's `FileResumeOffset`; files being
+    // sent, and the ids of their chunks as each goes out.
+    let mut awaiting_offset: HashMap<String, SendingFile> = HashMap::new();
+    let mut sending: HashMap<String, SendingFile> = HashMap::new();
+    let (chunk_sent_tx, mut chunk_sent_rx) = mpsc::unbounded_channel::<String>();
+    // Drain requests waiting on the `Pong` to the `Ping` (by its `ts_ms`) sent behind them.
+    let mut draining: Vec<(u64, tokio::sync::oneshot::Sender<()>)> = Vec::new();
+    // Keepalive: the `ts_ms` of our unanswered ping, if any, and when to send
+    // the next one or, while one is unanswered, give up on the peer.
+    let keepalive = *keepalive.lock().unwrap();
+    let mut unanswered_ping: Option<u64> = None;
+    let keepalive_due = tokio::time::sleep(keepalive.map_or(std::time::Duration::ZERO, |k| k.interval));
+    tokio::pin!(keepalive_due);
+    session.set_file_rate_limiters(file_rate_limits.for_peer(&peer_id));
+    // The single task writing to the connection; kept across iterations so a
+    // frame is never abandoned half-written. Everything this loop sends goes
+    // through the queue: a direct send here would wait for the connection
+    // while the queue, only polled by this loop, holds it.
+    let send_task = session.run_send_queue();
+    tokio::pin!(send_task);
+
+    // Every (re)connect to a linked device starts a trust sync round, so one
+    // cut short by a disconnect is simply redone.
+    if registry.is_linked(&peer_id).await
+        && let Err(e) = enqueue_trust_sync(&session).await
+    {
+        report_error(handler.as_ref(), ErrorCategory::Trust, Some(peer_id.as_str()), format!("trust sync to {peer_id} failed: {e}"));
+        return Ok(LoopEnd::Dropped);
+    }
+
+    // Offer our newest clip so clips copied while apart (e.g. during a
+    // partition) converge; the peer keeps whichever is newer.
+    if let Some((ts_ms, text)) = latest_clip.to_offer()
+        && peer_sync_direction(session.trust_store(), &peer_id).await.sends()
+        && !quiet_hours.pauses_outgoing()
+    {
+        session.enqueue_clip_text_at(text, ts_ms);
+    }
+
     loop {
         tokio::select! {
-            maybe_text = outbound_rx.recv() => {
-                let Some(text) = maybe_text else { return Ok(()); };
-                if let Err(e) = session.clipboard.write(crate::clipboard::ClipboardContent::Text(text)) {
-                    handler.on_error(format!("clipboard write (for send to {peer_id}) failed: {e}"));
-                    return Ok(());
+            maybe_out = outbound_rx.recv() => {
+                // Our handle is gone (`terminate_session`): hang up politely.
+                let Some(out) = maybe_out else {
+                    say_bye(&session, send_task.as_mut(), BYE_TERMINATED).await;
+                    return Ok(LoopEnd::Dropped);
+                };
+                let (text, ts_ms) = match out {
+                    Outbound::Text(text, ts_ms) => (text, ts_ms),
+                    Outbound::Image(msg) => {
+                        if peer_sync_direction(session.trust_store(), &peer_id).await.sends() {
+                            session.enqueue(msg, None);
+                        }
+                        continue;
+                    }
+                    Outbound::File(file) => {
+                        // Keyed on the content, so a receiver that saved part
+                        // of it before a disconnect can resume it.
+                        let file_id = file.hash.clone();
+                        if offered.contains_key(&file_id) || awaiting_offset.contains_key(&file_id) || sending.contains_key(&file_id) {
+                            let _ = file.done.send(Err(anyhow::anyhow!("{} is already being sent to {peer_id}", file.name)));
+                            continue;
+                        }
+                        session.enqueue(
+                            Message::FileOffer {
+                                file_id: file_id.clone(),
+                                name: file.name.clone(),
+                                size: file.size,
+                                mime: file.mime.clone(),
+                                hash: Some(file.hash.clone()),
+                            },
+                            None,
+                        );
+                        offered.insert(file_id, file);
+                        continue;
+                    }
+                    Outbound::TrustSync => {
+                        if let Err(e) = enqueue_trust_sync(&session).await {
+                            report_error(handler.as_ref(), ErrorCategory::Trust, Some(peer_id.as_str()), format!("trust sync to {peer_id} failed: {e}"));
+                            return Ok(LoopEnd::Dropped);
+                        }
+                        continue;
+                    }
+                    Outbound::Bye(reason, done) => {
+                        say_bye(&session, send_task.as_mut(), reason).await;
+                        let _ = done.send(());
+                        return Ok(LoopEnd::Dropped);
+                    }
+                    Outbound::Drain(done) => {
+                        // Behind everything queued, file chunks included.
+                        let ts_ms = crate::session::now_ms();
+                        session.enqueue_with_priority(Message::Ping { ts_ms }, SendPriority::Bulk, None);
+                        draining.push((ts_ms, done));
+                        continue;
+                    }
+                };
+                if !peer_sync_direction(session.trust_store(), &peer_id).await.sends() {
+                    continue;
                 }
-                if let Err(e) = session.send_clipboard().await {
-                    handler.on_error(format!("send to {peer_id} failed: {e}"));
-                    return Ok(());
+                // Queued ahead of any file chunks still waiting to go out.
+                session.enqueue_clip_text_at(text, ts_ms);
+            }
+            () = &mut keepalive_due, if keepalive.is_some() => {
+                let Some(k) = keepalive else { continue };
+                if unanswered_ping.is_some() {
+                    report_error(handler.as_ref(), ErrorCategory::Recv, Some(peer_id.as_str()), format!("no pong from {peer_id} within {:?}; dropping the session", k.timeout));
+                    return Ok(LoopEnd::Dropped);
+                }
+                let ts_ms = crate::session::now_ms();
+                session.enqueue(Message::Ping { ts_ms }, None);
+                unanswered_ping = Some(ts_ms);
+                keepalive_due.as_mut().reset(tokio::time::Instant::now() + k.timeout);
+            }
+            Some(file_id) = chunk_sent_rx.recv() => {
+                let Some(file) = sending.get_mut(&file_id) else { continue };
+                file.in_flight -= 1;
+                queue_file_chunks(&session, &mut sending, &file_id, &chunk_sent_tx, handler.as_ref(), &peer_id).await;
+            }
+            res = &mut send_task => {
+                if let Err(e) = res {
+                    report_error(handler.as_ref(), ErrorCategory::Send, Some(peer_id.as_str()), format!("send to {peer_id} failed: {e}"));
                 }
+                return Ok(LoopEnd::Dropped);
             }
-            msg = session.recv_message() => {
-                let msg = match msg {
+            msg = session.recv_message_with_seq() => {
+                let (msg, seq) = match msg {
                     Ok(m) => m,
                     Err(e) => {
-                        handler.on_error(format!("recv from {peer_id} failed: {e}"));
-                        return Ok(());
+                        report_error(handler.as_ref(), ErrorCategory::Recv, Some(peer_id.as_str()), format!("recv from {peer_id} failed: {e}"));
+                        return Ok(LoopEnd::Dropped);
                     }
                 };
 
-                if let Message::ClipText { text, ts_ms, .. } = msg {
-                    // Note in echo suppressor so the clipboard watcher won't re-broadcast.
-                    echo_suppressor.lock().await.note_remote_write(&text);
-                    // Record in history.
-                    history.record(text.clone(), peer_id.clone());
-                    handler.on_clipboard_text(peer_id.clone(), text, ts_ms);
+                match msg {
+                    Message::Bye { reason } => return Ok(LoopEnd::Bye(reason)),
+                    Message::ClipText { text, ts_ms, origin, .. } => {
+                        latest_clip.observe(ts_ms);
+                        if !peer_sync_direction(session.trust_store(), &peer_id).await.receives() || quiet_hours.pauses_incoming() {
+                            continue;
+                        }
+                        if latest_clip.enabled() && !latest_clip.is_newer(ts_ms, &text) {
+                            continue;
+                        }
+                        let content = crate::clipboard::ClipboardContent::Text(text.clone());
+                        if !registry.should_accept(&peer_id, &content).await {
+                            continue;
+                        }
+                        latest_clip.note(ts_ms, &text);
+                        // Note in echo suppressor so the clipboard watcher won't re-broadcast.
+                        echo_suppressor.lock().await.note_remote_write(&text);
+                        // Record in history, with the sender's device name when it sent one.
+                        history.record_with_origin(text.clone(), peer_id.clone(), origin);
+                        handler.on_clipboard_text(peer_id.clone(), text, ts_ms);
+                        let read_back = echo_suppressor.lock().await.read_back();
+                        if let Some(provider) = read_back
+                            && let Ok(Ok(ClipboardContent::Text(read))) = tokio::task::spawn_blocking(move || provider.read()).await
+                        {
+                            echo_suppressor.lock().await.note_remote_write(&read);
+                        }
+                        if session.clip_ack_enabled() {
+                            session.enqueue(Message::ClipAck { ack_of_seq: seq }, None);
+                        }
+                    }
+                    Message::ClipImage { mime, width, height, bytes_b64, ts_ms } => {
+                        if !peer_sync_direction(session.trust_store(), &peer_id).await.receives() || quiet_hours.pauses_incoming() {
+                            continue;
+                        }
+                        let bytes = match crate::b64::Base64Alphabet::Standard.decode(&bytes_b64) {
+                            Ok(bytes) => bytes,
+                            Err(e) => {
+                                report_error(handler.as_ref(), ErrorCategory::Recv, Some(peer_id.as_str()), format!("dropped an image from {peer_id}: bad base64: {e}"));
+                                continue;
+                            }
+                        };
+                        let content = crate::clipboard::ClipboardContent::Image { mime, width, height, bytes };
+                        if !registry.should_accept(&peer_id, &content).await {
+                            continue;
+                        }
+                        let crate::clipboard::ClipboardContent::Image { mime, width, height, bytes } = content else { continue };
+                        echo_suppressor.lock().await.note_remote_image(&bytes);
+                        history.record_image(mime.clone(), width, height, bytes.clone(), peer_id.clone());
+                        handler.on_clipboard_image(peer_id.clone(), mime, width, height, bytes, ts_ms);
+                    }
+                    Message::ClipAck { ack_of_seq } => {
+                        if let Some(ts_ms) = session.take_acked_clip(ack_of_seq) {
+                            handler.on_clip_delivered(peer_id.clone(), ts_ms);
+                        }
+                    }
+                    Message::DeviceInfo { name, platform, app_version } => {
+                        registry.set_device_info(&peer_id, name, platform, app_version).await;
+                    }
+                    Message::DescribeRequest => {
+                        session.enqueue(Message::Describe { descriptor: session.protocol_descriptor() }, None);
+                    }
+                    Message::Ping { ts_ms } => {
+                        session.enqueue(Message::Pong { ts_ms }, None);
+                    }
+                    Message::Pong { ts_ms } if unanswered_ping == Some(ts_ms) => {
+                        unanswered_ping = None;
+                        if let Some(k) = keepalive {
+                            keepalive_due.as_mut().reset(tokio::time::Instant::now() + k.interval);
+                        }
+                    }
+                    Message::Pong { ts_ms } => {
+                        // The peer has read everything sent before the matching ping.
+                        for (_, done) in draining.extract_if(.., |(sent, _)| *sent <= ts_ms) {
+                            let _ = done.send(());
+                        }
+                    }
+                    Message::FileAccept { file_id } => {
+                        let Some(file) = offered.remove(&file_id) else { continue };
+                        let _ = file.accepted.send(());
+                        let reader = match FileReader::open(file.source).await {
+                            Ok(reader) => reader,
+                            Err(e) => {
+                                report_error(handler.as_ref(), ErrorCategory::File, Some(peer_id.as_str()), format!("sending {} to {peer_id} failed: {e}", file.name));
+                                let _ = file.done.send(Err(e));
+                                continue;
+                            }
+                        };
+                        let file = SendingFile {
+                            name: file.name,
+                            reader,
+                            total: file.size,
+                            queued: 0,
+                            in_flight: 0,
+                            hash: file.hash,
+                            progress: file.progress,
+                            done: file.done,
+                        };
+                        // A v0 peer can't resume, so it always gets the whole file.
+                        if session.peer_is_v0() {
+                            sending.insert(file_id.clone(), file);
+                            queue_file_chunks(&session, &mut sending, &file_id, &chunk_sent_tx, handler.as_ref(), &peer_id).await;
+                        } else {
+                            session.enqueue_with_priority(Message::FileResumeQuery { file_id: file_id.clone() }, SendPriority::Control, None);
+                            awaiting_offset.insert(file_id, file);
+                        }
+                    }
+                    Message::FileResumeOffset { file_id, offset } => {
+                        let Some(mut file) = awaiting_offset.remove(&file_id) else { continue };
+                        let offset = offset.min(file.total);
+                        if let Err(e) = file.reader.seek(offset).await {
+                            report_error(handler.as_ref(), ErrorCategory::File, Some(peer_id.as_str()), format!("sending {} to {peer_id} failed: {e}", file.name));
+                            let _ = file.done.send(Err(e));
+                            continue;
+                        }
+                        file.queued = offset;
+                        sending.insert(file_id.clone(), file);
+                        queue_file_chunks(&session, &mut sending, &file_id, &chunk_sent_tx, handler.as_ref(), &peer_id).await;
+                    }
+                    Message::FileReject { file_id, reason } => {
+                        if let Some(file) = offered.remove(&file_id) {
+                            let res = if reason == crate::REJECT_ALREADY_RECEIVED {
+                                Ok(())
+                            } else {
+                                Err(crate::FileRejected { name: file.name.clone(), reason }.into())
+                            };
+                            let _ = file.done.send(res);
+                        } else if let Some(file) = awaiting_offset.remove(&file_id).or_else(|| sending.remove(&file_id)) {
+                            // Dropped mid-transfer: chunks already queued still go out, but no more.
+                            let _ = file.done.send(Err(crate::FileRejected { name: file.name, reason }.into()));
+                        }
+                    }
+                    Message::FileOffer { file_id, name, size, mime, hash } => {
+                        // Answers go ahead of our own queued chunks.
+                        let reject = |reason: &str| {
+                            let msg = Message::FileReject { file_id: file_id.clone(), reason: reason.into() };
+                            session.enqueue_with_priority(msg, SendPriority::Control, None);
+                        };
+                        if !peer_sync_direction(session.trust_store(), &peer_id).await.receives() {
+                            reject(crate::REJECT_NOT_ACCEPTING);
+                            continue;
+                        }
+                        if received_files.max_file_size().is_some_and(|max| size > max) {
+                            reject(crate::REJECT_TOO_LARGE);
+                            continue;
+                        }
+                        if !handler.allow_file(&peer_id, &name, &mime) {
+                            reject(crate::REJECT_MIME_NOT_ALLOWED);
+                            continue;
+                        }
+                        // Same content as a file this peer already sent: point at that one instead.
+                        if let Some(existing) = hash.as_deref().and_then(|h| received_files.find_by_hash(&peer_id, h)) {
+                            reject(crate::REJECT_ALREADY_RECEIVED);
+                            history.record_file(name.clone(), existing.size, existing.path.clone(), peer_id.clone());
+                            handler.on_file_received(peer_id.clone(), name, existing.path);
+                            continue;
+                        }
+                        let file = incoming.resume(&file_id, FileAssembler::new(name, size).with_hash(hash)).await;
+                        incoming.insert(file_id.clone(), file);
+                        session.enqueue_with_priority(Message::FileAccept { file_id }, SendPriority::Control, None);
+                    }
+                    Message::FileChunk { file_id, offset, data_b64 } => {
+                        let data = crate::b64::Base64Alphabet::Standard.decode(data_b64);
+                        if place_file_chunk(&mut incoming, &file_id, offset, data, handler.as_ref(), &peer_id) {
+                            let msg = Message::FileReject { file_id, reason: crate::REJECT_CORRUPTED.into() };
+                            session.enqueue_with_priority(msg, SendPriority::Control, None);
+                        }
+                    }
+                    Message::FileChunkRaw { file_id, offset, data } => {
+                        if place_file_chunk(&mut incoming, &file_id, offset, Ok(data), handler.as_ref(), &peer_id) {
+                            let msg = Message::FileReject { file_id, reason: crate::REJECT_CORRUPTED.into() };
+                            session.enqueue_with_priority(msg, SendPriority::Control, None);
+                        }
+                    }
+                    Message::FileResumeQuery { file_id } => {
+                        let offset = incoming.resume_offset(&file_id);
+                        session.enqueue_with_priority(Message::FileResumeOffset { file_id, offset }, SendPriority::Control, None);
+                    }
+                    Message::FileDone { file_id, hash } => {
+                        use sha2::{Digest, Sha256};
+                        let Some(f) = incoming.remove(&file_id) else { continue };
+                        incoming.discard_partial(&file_id);
+                        let name = f.name.clone();
+                        let data = match f.finish() {
+                            Ok(data) if format!("{:x}", Sha256::digest(&data)) == hash => data,
+                            _ => {
+                                report_error(handler.as_ref(), ErrorCategory::File, Some(peer_id.as_str()), format!("{name} from {peer_id} arrived corrupted; dropping it"));
+                                continue;
+                            }
+                        };
+                        match received_files.save(&peer_id, &name, &data, &hash) {
+                            Ok(file) => {
+                                history.record_file(file.name.clone(), file.size, file.path.clone(), peer_id.clone());
+                                handler.on_file_received(peer_id.clone(), file.name, file.path);
+                            }
+                            Err(e) => report_error(handler.as_ref(), ErrorCategory::File, Some(peer_id.as_str()), format!("saving {name} from {peer_id} failed: {e}")),
+                        }
+                    }
+                    Message::TrustSync { records, revoked } => {
+                        if !registry.is_linked(&peer_id).await {
+                            report_error(handler.as_ref(), ErrorCategory::Trust, Some(peer_id.as_str()), format!("ignored trust records from {peer_id}: not a linked device"));
+                            continue;
+                        }
+                        let Some(store) = session.trust_store().cloned() else { continue };
+                        let (records, revoked) = trust_records_from_sync(records, revoked, session.identity.peer_id());
+                        let merge_into = Arc::clone(&store);
+                        let scheme = session.identity.peer_id_scheme();
+                        let merged = tokio::task::spawn_blocking(move || crate::trust::merge_trust_records(merge_into.as_ref(), &records, &revoked, scheme.as_ref()))
+                            .await
+                            .unwrap_or_else(|e| Err(e.into()));
+                        match merged {
+                            Ok(changed) if !changed.is_empty() => {
+                                let store = BlockingTrustStore::new(store);
+                                for id in &changed {
+                                    if !store.is_trusted(id).await.unwrap_or(true) {
+                                        registry.remove(id).await;
+                                    }
+                                }
+                                let _ = registry.load_from_async_trust(&store).await;
+                            }
+                            Ok(_) => {}
+                            Err(e) => report_error(handler.as_ref(), ErrorCategory::Trust, Some(peer_id.as_str()), format!("merging trust records from {peer_id} failed: {e}")),
+                        }
+                    }
+                    _ => {}
                 }
             }
         }
     }
 }
 
+/// Queue our trust list and revocations for a linked device.
+async fn enqueue_trust_sync<C: crate::transport::Connection, I: crate::identity::IdentityProvider, P: ClipboardProvider>(
+    session: &Session<C, I, P>,
+) -> Result<()> {
+    let Some(store) = session.trust_store() else { return Ok(()) };
+    let store = BlockingTrustStore::new(Arc::clone(store));
+    let records = store
+        .list()
+        .await?
+        .into_iter()
+        .map(|r| TrustSyncRecord {
+            peer_id: r.peer_id,
+            identity_pk_b64: crate::b64::Base64Alphabet::Standard.encode(&r.identity_pk),
+            display_name: r.display_name,
+            created_at_ms: r.created_at.timestamp_millis(),
+        })
+        .collect();
+    let revoked = store
+        .revocations()
+        .await?
+        .into_iter()
+        .map(|r| TrustSyncRevocation { peer_id: r.peer_id, revoked_at_ms: r.revoked_at.timestamp_millis() })
+        .collect();
+    session.enqueue(Message::TrustSync { records, revoked }, None);
+    Ok(())
+}
+
+/// Decode a linked device's trust list and revocations, dropping undecodable
+/// entries and any about this device itself.
+fn trust_records_from_sync(records: Vec<TrustSyncRecord>, revoked: Vec<TrustSyncRevocation>, local_peer_id: &str) -> (Vec<TrustRecord>, Vec<TrustRevocation>) {
+    let revoked = revoked
+        .into_iter()
+        .filter(|r| r.peer_id != local_peer_id)
+        .filter_map(|r| Some(TrustRevocation { revoked_at: chrono::DateTime::from_timestamp_millis(r.revoked_at_ms)?, peer_id: r.peer_id }))
+        .collect();
+    let records = records
+        .into_iter()
+        .filter(|r| r.peer_id != local_peer_id)
+        .filter_map(|r| {
+            Some(TrustRecord {
+                identity_pk: crate::b64::decode_any(&r.identity_pk_b64).ok()?,
+                created_at: chrono::DateTime::from_timestamp_millis(r.created_at_ms)?,
+                peer_id: r.peer_id,
+                display_name: r.display_name,
+                sync_direction: SyncDirection::default(),
+            })
+        })
+        .collect();
+    (records, revoked)
+}
+
+/// Files a peer is sending us, by file id. Whatever is still incomplete when
+/// the session ends is saved to the [`FileReceiveLog`]'s partial files, so
+/// the sender can resume it after reconnecting.
+struct IncomingFiles {
+    files: HashMap<String, FileAssembler>,
+    peer_id: String,
+    partials: Option<Arc<crate::PartialFiles>>,
+    handler: Arc<dyn SyncHandler>,
+}
+
+impl IncomingFiles {
+    fn new(peer_id: String, received_files: &FileReceiveLog, handler: Arc<dyn SyncHandler>) -> Self {
+        Self { files: HashMap::new(), peer_id, partials: received_files.partial_files().map(Arc::new), handler }
+    }
+
+    /// `file` continued from what an earlier session with this peer saved of
+    /// `file_id`; a saved part that doesn't fit is dropped and reported.
+    async fn resume(&self, file_id: &str, file: FileAssembler) -> FileAssembler {
+        let Some(partials) = self.partials.clone() else { return file };
+        let (name, size) = (file.name.clone(), file.expected());
+        let hash = file.hash().map(str::to_string);
+        let (peer_id, id) = (self.peer_id.clone(), file_id.to_string());
+        let resumed = tokio::task::spawn_blocking(move || partials.resume(&peer_id, &id, file)).await;
+        match resumed {
+            Ok(Ok(file)) => file,
+            Ok(Err(e)) => {
+                report_error(self.handler.as_ref(), ErrorCategory::File, Some(self.peer_id.as_str()), format!("resuming {name} from {} failed: {e:#}", self.peer_id));
+                FileAssembler::new(name, size).with_hash(hash)
+            }
+            Err(e) => {
+                report_error(self.handler.as_ref(), ErrorCategory::File, Some(self.peer_id.as_str()), format!("resuming {name} from {} failed: {e}", self.peer_id));
+                FileAssembler::new(name, size).with_hash(hash)
+            }
+        }
+    }
+
+    /// Bytes of `file_id` received without a gap, in this session or saved
+    /// from an earlier one.
+    fn resume_offset(&self, file_id: &str) -> u64 {
+        match self.files.get(file_id) {
+            Some(file) => file.next_offset(),
+            None => self.partials.as_ref().map_or(0, |p| p.saved_len(&self.peer_id, file_id)),
+        }
+    }
+
+    /// Forget what was saved of `file_id`, once it completed or went bad.
+    fn discard_partial(&self, file_id: &str) {
+        let Some(partials) = self.partials.clone() else { return };
+        let (peer_id, id) = (self.peer_id.clone(), file_id.to_string());
+        tokio::task::spawn_blocking(move || partials.discard(&peer_id, &id));
+    }
+}
+
+impl std::ops::Deref for IncomingFiles {
+    type Target = HashMap<String, FileAssembler>;
+    fn deref(&self) -> &Self::Target {
+        &self.files
+    }
+}
+
+impl std::ops::DerefMut for IncomingFiles {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.files
+    }
+}
+
+impl Drop for IncomingFiles {
+    fn drop(&mut self) {
+        let Some(partials) = self.partials.take() else { return };
+        if self.files.is_empty() {
+            return;
+        }
+        let mut files = std::mem::take(&mut self.files);
+        let peer_id = self.peer_id.clone();
+        let handler = Arc::clone(&self.handler);
+        let mut save = move || {
+            if let Err(e) = partials.save_all(&peer_id, &mut files) {
+                report_error(handler.as_ref(), ErrorCategory::File, Some(peer_id.as_str()), format!("saving partial files from {peer_id} failed: {e:#}"));
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(rt) => drop(rt.spawn_blocking(save)),
+            Err(_) => save(),
+        }
+    }
+}
+
+/// Place a received chunk in the file it belongs to; a bad one (undecodable,
+/// out of range, overlapping, completing the file with the wrong hash) drops
+/// that file's transfer. Returns whether it did, so the sender can be told.
+fn place_file_chunk(
+    incoming: &mut IncomingFiles,
+    file_id: &str,
+    offset: u64,
+    data: Result<Vec<u8>>,
+    handler: &dyn SyncHandler,
+    peer_id: &str,
+) -> bool {
+    let Some(f) = incoming.get_mut(file_id) else { return false };
+    if let Err(e) = data.and_then(|data| f.insert(offset, &data)) {
+        report_error(handler, ErrorCategory::File, Some(peer_id), format!("bad chunk for {} from {peer_id} ({e}); dropping it", f.name));
+        incoming.remove(file_id);
+        incoming.discard_partial(file_id);
+        return true;
+    }
+    false
+}
+
+/// Queue more of an accepted file's chunks in the bulk lane, up to
+/// `FILE_SEND_WINDOW` not yet sent, so clipboard and control traffic still go
+/// out ahead of them; once all are queued, queue its `FileDone` (SHA-256 hex).
+/// Chunks are `FileChunkRaw` when the session negotiated it. Each chunk sent
+/// reports progress and posts the file's id to `chunk_sent`, which calls
+/// this again. `done` resolves once `FileDone` is sent, fails if the file
+/// can't be read, and is dropped if the session ends first.
+async fn queue_file_chunks<C: crate::transport::Connection, I: crate::identity::IdentityProvider, P: ClipboardProvider>(
+    session: &Session<C, I, P>,
+    sending: &mut HashMap<String, SendingFile>,
+    file_id: &str,
+    chunk_sent: &mpsc::UnboundedSender<String>,
+    handler: &dyn SyncHandler,
+    peer_id: &str,
+) {
+    let Some(file) = sending.get_mut(file_id) else { return };
+    while file.in_flight < FILE_SEND_WINDOW && file.queued < file.total {
+        let offset = file.queued;
+        let len = (file.total - offset).min(FILE_CHUNK_SIZE as u64) as usize;
+        let chunk = match file.reader.read_chunk(offset, len).await {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                report_error(handler, ErrorCategory::File, Some(peer_id), format!("sending {file_id} to {peer_id} failed: {e}"));
+                if let Some(file) = sending.remove(file_id) {
+                    let _ = file.done.send(Err(e));
+                }
+                return;
+            }
+        };
+        file.queued += len as u64;
+        file.in_flight += 1;
+        let msg = if session.raw_file_chunks_enabled() {
+            Message::FileChunkRaw { file_id: file_id.into(), offset, data: chunk }
+        } else {
+            Message::FileChunk { file_id: file_id.into(), offset, data_b64: crate::b64::Base64Alphabet::Standard.encode(chunk) }
+        };
+        let (sent, total, progress) = (file.queued, file.total, file.progress.clone());
+        let (chunk_sent, id) = (chunk_sent.clone(), file_id.to_string());
+        session.enqueue(msg, Some(Box::new(move || {
+            if let Some(progress) = progress {
+                progress(sent, total);
+            }
+            let _ = chunk_sent.send(id);
+        })));
+    }
+    if file.queued < file.total {
+        return;
+    }
+    let Some(file) = sending.remove(file_id) else { return };
+    let done = Message::FileDone { file_id: file_id.into(), hash: file.hash };
+    let notify = file.done;
+    session.enqueue(done, Some(Box::new(move || {
+        let _ = notify.send(Ok(()));
+    })));
+}
 
 #[cfg(test)]
 mod tests {
@@ -700,6 +3445,103 @@ mod tests {
         assert!(s.should_ignore_local_change("d"));
     }
 
+    #[test]
+    fn windowed_echo_suppressor_expires_by_age_and_count() {
+        let window = std::time::Duration::from_millis(500);
+        let mut s = EchoSuppressor::new(2).with_window(window);
+        let t0 = std::time::Instant::now();
+        let at = |ms| t0 + std::time::Duration::from_millis(ms);
+
+        s.note_remote_write_at("a", at(0));
+        assert!(s.should_ignore_local_change_at("a", at(499)));
+        assert!(!s.should_ignore_local_change_at("a", at(500)));
+
+        // Noting the same text again restarts its window.
+        s.note_remote_write_at("a", at(400));
+        assert!(s.should_ignore_local_change_at("a", at(800)));
+
+        // The count cap still applies inside the window.
+        s.note_remote_write_at("b", at(810));
+        s.note_remote_write_at("c", at(820));
+        assert!(!s.should_ignore_local_change_at("a", at(830)));
+        assert!(s.should_ignore_local_change_at("b", at(830)));
+
+        s.set_window(None);
+        assert!(s.should_ignore_local_change_at("c", at(60_000)));
+    }
+
+    #[test]
+    fn noted_write_covers_its_normalized_forms_unless_turned_off() {
+        let mut s = EchoSuppressor::new(4);
+        s.note_remote_write("  line one\r\nline two\n");
+        assert!(s.should_ignore_local_change("line one\nline two"));
+        assert!(!s.should_ignore_local_change("line one line two"));
+
+        s.set_note_after_write(false);
+        assert!(!s.should_ignore_local_change("line one\nline two"));
+        assert!(s.should_ignore_local_change("  line one\r\nline two\n"));
+    }
+
+    #[test]
+    fn forgetting_a_noted_write_undoes_only_that_note() {
+        let window = std::time::Duration::from_millis(500);
+        let mut s = EchoSuppressor::new(8).with_window(window);
+        let t0 = std::time::Instant::now();
+        let at = |ms| t0 + std::time::Duration::from_millis(ms);
+
+        let noted = s.note_remote_write_at("a", at(0));
+        s.forget_remote_write(noted);
+        assert!(!s.should_ignore_local_change_at("a", at(1)));
+
+        // Re-noting the latest write and then forgetting it restores its old window.
+        s.note_remote_write_at("b", at(0));
+        let noted = s.note_remote_write_at("b", at(400));
+        s.forget_remote_write(noted);
+        assert!(s.should_ignore_local_change_at("b", at(499)));
+        assert!(!s.should_ignore_local_change_at("b", at(800)));
+
+        // A note made since is left alone.
+        let noted = s.note_remote_write_at("c", at(100));
+        s.note_remote_write_at("d", at(110));
+        s.note_remote_write_at("c", at(120));
+        s.forget_remote_write(noted);
+        assert!(s.should_ignore_local_change_at("c", at(130)));
+        assert!(s.should_ignore_local_change_at("d", at(130)));
+    }
+
+    #[test]
+    fn error_coalescer_reports_first_then_periodic_summary() {
+        let c = ErrorCoalescer::new(std::time::Duration::from_secs(10));
+        let t0 = std::time::Instant::now();
+        let at = |ms| t0 + std::time::Duration::from_millis(ms);
+
+        assert_eq!(c.filter("p", "dial: refused", "dial p failed".into(), at(0)).as_deref(), Some("dial p failed"));
+        for ms in [200, 600, 1400, 3000] {
+            assert_eq!(c.filter("p", "dial: refused", "dial p failed".into(), at(ms)), None);
+        }
+        assert_eq!(
+            c.filter("p", "dial: refused", "dial p failed".into(), at(10_000)).as_deref(),
+            Some("dial p failed (still failing, 6 attempts)")
+        );
+        // A different error, or another peer, is reported straight away.
+        assert!(c.filter("p", "handshake: timeout", "handshake p failed".into(), at(10_100)).is_some());
+        assert!(c.filter("q", "dial: refused", "dial q failed".into(), at(10_100)).is_some());
+        // After a success the streak starts over.
+        c.clear("q");
+        assert!(c.filter("q", "dial: refused", "dial q failed".into(), at(10_200)).is_some());
+    }
+
+    #[test]
+    fn own_address_matches_advertised_or_our_port_on_this_machine() {
+        let own = ["0.0.0.0:5000".to_string(), "mem://a".to_string()];
+        assert!(is_own_address("mem://a", &own));
+        assert!(is_own_address("127.0.0.1:5000", &own));
+        // Same port, another machine; another port here: not us.
+        assert!(!is_own_address("203.0.113.7:5000", &own));
+        assert!(!is_own_address("127.0.0.1:5001", &own));
+        assert!(!is_own_address("mem://b", &own));
+    }
+
     #[test]
     fn backoff_grows_and_caps() {
         let mut b = Backoff { cur_ms: 200, max_ms: 500 };
@@ -710,4 +3552,184 @@ mod tests {
         b.reset();
         assert_eq!(b.next_delay(), std::time::Duration::from_millis(200));
     }
+
+    #[test]
+    fn quiet_ranges_wrap_past_midnight() {
+        let t = |h, m| chrono::NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let lunch = QuietRange::new(t(12, 0), t(13, 0));
+        assert!(lunch.contains(t(12, 0)));
+        assert!(lunch.contains(t(12, 59)));
+        assert!(!lunch.contains(t(13, 0)));
+
+        let night = QuietRange::new(t(22, 0), t(7, 0));
+        assert!(night.contains(t(23, 30)));
+        assert!(night.contains(t(0, 0)));
+        assert!(night.contains(t(6, 59)));
+        assert!(!night.contains(t(7, 0)));
+        assert!(!night.contains(t(21, 59)));
+
+        let day = chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let hours = QuietHours { ranges: vec![lunch, night], pause_incoming: false };
+        assert!(hours.is_quiet_at(day.and_time(t(12, 30))));
+        assert!(hours.is_quiet_at(day.and_time(t(3, 0))));
+        assert!(!hours.is_quiet_at(day.and_time(t(9, 0))));
+        assert!(!QuietHours::default().is_quiet_at(day.and_time(t(3, 0))));
+    }
+
+    struct NoopHandler;
+    impl SyncHandler for NoopHandler {
+        fn on_clipboard_text(&self, _: String, _: String, _: u64) {}
+        fn on_peer_connected(&self, _: String) {}
+        fn on_peer_disconnected(&self, _: String) {}
+        fn on_error(&self, _: String) {}
+    }
+
+    fn test_service(identity: Ed25519Identity, device_name: &str) -> SyncService<crate::discovery::MockDiscovery> {
+        SyncService::new(
+            identity,
+            Arc::new(crate::trust::MemoryTrustStore::new()),
+            Arc::new(crate::replay::MemoryReplayProtector::new(16)),
+            Arc::new(crate::discovery::MockDiscovery::new_shared()),
+            "127.0.0.1:0".parse().unwrap(),
+            device_name.into(),
+            Arc::new(NoopHandler),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn a_clip_copied_after_one_from_a_fast_clock_still_wins() {
+        let latest = LatestClip::default();
+        let ahead = crate::session::now_ms() + 10_000;
+        latest.observe(ahead);
+        latest.note(ahead, "from the peer");
+
+        let ts_ms = latest.tick();
+        assert!(ts_ms > ahead);
+        assert!(latest.is_newer(ts_ms, "copied here"));
+        assert!(latest.tick() > ts_ms);
+
+        // A clock too far ahead only moves ours by the allowed skew.
+        latest.observe(u64::MAX);
+        assert!(latest.tick() <= crate::session::now_ms() + MAX_CLOCK_SKEW_MS + 1);
+    }
+
+    #[tokio::test]
+    async fn sync_sessions_negotiate_clip_text_compression() {
+        let (ours, theirs) = crate::transport::memory_connection_pair();
+        let session = sync_session(Session::new(ours, Ed25519Identity::generate(), crate::clipboard::MockClipboard::new()), "a".into(), false);
+        let peer = sync_session(Session::new(theirs, Ed25519Identity::generate(), crate::clipboard::MockClipboard::new()), "b".into(), false);
+        let (ours, theirs) = tokio::join!(session.handshake(), peer.handshake());
+        ours.unwrap();
+        theirs.unwrap();
+        assert!(session.compression_enabled() && peer.compression_enabled());
+    }
+
+    #[tokio::test]
+    async fn peer_loop_keeps_reading_while_its_replies_wait_for_the_connection() {
+        let service = test_service(Ed25519Identity::generate(), "me");
+        let (ours, theirs) = crate::transport::memory_connection_pair();
+        let session = Session::new(ours, Ed25519Identity::generate(), crate::clipboard::MockClipboard::new());
+        let peer = Session::new(theirs, Ed25519Identity::generate(), crate::clipboard::MockClipboard::new());
+        let (_outbound_tx, outbound_rx) = mpsc::channel(8);
+        let peer_loop = tokio::spawn(peer_message_loop(
+            session,
+            "peer".into(),
+            outbound_rx,
+            Arc::clone(&service.handler),
+            Arc::clone(&service.echo_suppressor),
+            service.peer_registry.clone(),
+            Arc::clone(&service.history),
+            Arc::clone(&service.received_files),
+            Arc::clone(&service.quiet_hours),
+            Arc::clone(&service.keepalive),
+            Arc::clone(&service.latest_clip),
+            Arc::clone(&service.file_rate_limits.lock().unwrap()),
+        ));
+
+        // Far more pings than the connection buffers, without reading the
+        // pongs: the loop has to keep reading while its pongs can't go out.
+        let pings = 500;
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            for ts_ms in 0..pings {
+                peer.send_ping(ts_ms).await.unwrap();
+            }
+        })
+        .await
+        .expect("the loop stopped reading");
+        for ts_ms in 0..pings {
+            assert_eq!(peer.recv_message().await.unwrap(), Message::Pong { ts_ms });
+        }
+        peer_loop.abort();
+    }
+
+    #[test]
+    fn events_since_returns_only_later_activity() {
+        let service = test_service(Ed25519Identity::generate(), "me");
+        let file = |name: &str| ReceivedFile {
+            peer_id: "peer-a".into(),
+            name: name.into(),
+            size: 1,
+            hash: String::new(),
+            path: String::new(),
+            received_at_ms: crate::session::now_ms(),
+        };
+        let tick = || std::thread::sleep(std::time::Duration::from_millis(5));
+
+        service.history().record("before".into(), "local".into());
+        service.file_log().record(file("before.txt")).unwrap();
+        service.handler.on_peer_connected("peer-a".into());
+        tick();
+        let mid = crate::session::now_ms();
+        tick();
+        service.handler.on_peer_disconnected("peer-a".into());
+        service.handler.on_peer_connected("peer-b".into());
+        service.history().record("after".into(), "peer-b".into());
+        service.file_log().record(file("after.txt")).unwrap();
+
+        let summary = service.events_since(mid);
+        assert_eq!(summary.clips.iter().map(|e| e.content.as_str()).collect::<Vec<_>>(), ["after"]);
+        assert_eq!(summary.files.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), ["after.txt"]);
+        let connections: Vec<_> = summary.connections.iter().map(|e| (e.peer_id.as_str(), e.connected)).collect();
+        assert_eq!(connections, [("peer-a", false), ("peer-b", true)]);
+
+        assert_eq!(service.events_since(0).connections.len(), 3);
+        let later = service.events_since(crate::session::now_ms() + 1);
+        assert!(later.clips.is_empty() && later.files.is_empty() && later.connections.is_empty());
+    }
+
+    #[test]
+    fn service_exposes_local_peer_id_and_device_name() {
+        let identity = Ed25519Identity::generate();
+        let expected_id = identity.peer_id().to_string();
+        let service = test_service(identity, "my-laptop");
+
+        assert_eq!(service.local_peer_id(), expected_id);
+        assert_eq!(service.device_name(), "my-laptop");
+    }
+
+    #[test]
+    fn last_clip_for_peer_is_per_peer_and_newest() {
+        let service = test_service(Ed25519Identity::generate(), "dev");
+        assert_eq!(service.last_clip_for_peer("phone"), None);
+
+        service.history().record("first from phone".into(), "phone".into());
+        service.history().record("from laptop".into(), "laptop".into());
+        service.history().record("meeting notes\nline two".into(), "phone".into());
+        service.history().record("local copy".into(), "local".into());
+
+        assert_eq!(service.last_clip_for_peer("phone").as_deref(), Some("meeting notes line two"));
+        assert_eq!(service.last_clip_for_peer("laptop").as_deref(), Some("from laptop"));
+        assert_eq!(service.last_clip_for_peer("tablet"), None);
+    }
+
+    #[tokio::test]
+    async fn clear_echo_suppressor_lets_remote_content_through_again() {
+        let service = test_service(Ed25519Identity::generate(), "dev");
+        service.echo_suppressor().lock().await.note_remote_write("from phone");
+        assert!(service.echo_suppressor().lock().await.should_ignore_local_change("from phone"));
+
+        service.clear_echo_suppressor().await;
+        assert!(!service.echo_suppressor().lock().await.should_ignore_local_change("from phone"));
+    }
 }
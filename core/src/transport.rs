@@ -3,6 +3,7 @@
 use crate::protocol::Frame;
 use anyhow::Result;
 use async_trait::async_trait;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 
@@ -13,6 +14,10 @@ pub trait Connection: Send + Sync {
     /// Close the connection.
     fn close(&self);
     fn is_closed(&self) -> bool;
+    /// Remote socket address, if the transport has one.
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        None
+    }
 }
 
 #[async_trait]
@@ -27,6 +32,69 @@ pub trait Listener: Send + Sync {
     async fn accept(&self) -> Result<Self::Conn>;
 }
 
+// ── Type erasure ──
+
+/// A connection of any transport, so one code path can run over QUIC, memory,
+/// or test links (see `SyncService::start_with_transport`).
+pub type BoxConnection = Box<dyn Connection>;
+
+/// A listener yielding [`BoxConnection`]s.
+pub type BoxListener = Box<dyn Listener<Conn = BoxConnection>>;
+
+/// A transport yielding [`BoxConnection`]s.
+pub type BoxTransport = Arc<dyn Transport<Conn = BoxConnection>>;
+
+#[async_trait]
+impl Connection for BoxConnection {
+    async fn send(&self, frame: Frame) -> Result<()> {
+        (**self).send(frame).await
+    }
+
+    async fn recv(&self) -> Result<Frame> {
+        (**self).recv().await
+    }
+
+    fn close(&self) {
+        (**self).close()
+    }
+
+    fn is_closed(&self) -> bool {
+        (**self).is_closed()
+    }
+
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        (**self).remote_addr()
+    }
+}
+
+struct Erased<T>(T);
+
+#[async_trait]
+impl<L: Listener + 'static> Listener for Erased<L> {
+    type Conn = BoxConnection;
+    async fn accept(&self) -> Result<BoxConnection> {
+        Ok(Box::new(self.0.accept().await?))
+    }
+}
+
+#[async_trait]
+impl<T: Transport + 'static> Transport for Erased<T> {
+    type Conn = BoxConnection;
+    async fn connect(&self, addr: &str) -> Result<BoxConnection> {
+        Ok(Box::new(self.0.connect(addr).await?))
+    }
+}
+
+/// Erase a listener's connection type.
+pub fn box_listener<L: Listener + 'static>(listener: L) -> BoxListener {
+    Box::new(Erased(listener))
+}
+
+/// Erase a transport's connection type.
+pub fn box_transport<T: Transport + 'static>(transport: T) -> BoxTransport {
+    Arc::new(Erased(transport))
+}
+
 // ── MemoryTransport ──
 
 /// Create a pair of connected in-memory connections.
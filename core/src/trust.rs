@@ -1,9 +1,10 @@
 //! Trust store: manage trusted peers (paired devices).
 
+use crate::identity::{Blake3HexPeerId, PeerIdScheme};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
@@ -13,6 +14,32 @@ pub fn default_trust_store_path() -> PathBuf {
     PathBuf::from(home).join(".openclipboard").join("trust.json")
 }
 
+/// Which way clipboard content flows between this device and a trusted peer.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncDirection {
+    #[default]
+    Bidirectional,
+    /// We send to the peer but ignore clips it sends us.
+    SendOnly,
+    /// We accept the peer's clips but never send ours.
+    ReceiveOnly,
+    /// Stay connected, but exchange no clips either way.
+    Off,
+}
+
+impl SyncDirection {
+    /// Whether our clips are sent to the peer.
+    pub fn sends(self) -> bool {
+        matches!(self, Self::Bidirectional | Self::SendOnly)
+    }
+
+    /// Whether clips from the peer are accepted.
+    pub fn receives(self) -> bool {
+        matches!(self, Self::Bidirectional | Self::ReceiveOnly)
+    }
+}
+
 /// A record of a trusted peer.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TrustRecord {
@@ -20,24 +47,256 @@ pub struct TrustRecord {
     pub identity_pk: Vec<u8>,
     pub display_name: String,
     pub created_at: DateTime<Utc>,
+    /// Absent in stores written before directions existed; defaults to bidirectional.
+    #[serde(default)]
+    pub sync_direction: SyncDirection,
+}
+
+/// A peer removed from trust, kept so the removal reaches linked devices
+/// instead of their copy of the record bringing the peer back.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrustRevocation {
+    pub peer_id: String,
+    /// A record created (re-paired) after this outlives the revocation.
+    pub revoked_at: DateTime<Utc>,
+}
+
+/// A problem found by [`TrustStore::verify_integrity`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityIssue {
+    /// `identity_pk` is not a valid 32-byte Ed25519 public key.
+    MalformedKey,
+    /// `peer_id` does not equal `blake3(identity_pk)`; `expected` is the recomputed id.
+    PeerIdMismatch { expected: String },
 }
 
 /// Persistent trust storage.
 pub trait TrustStore: Send + Sync {
     fn save(&self, record: TrustRecord) -> Result<()>;
     fn get(&self, peer_id: &str) -> Result<Option<TrustRecord>>;
+    /// All trusted peers, sorted by `peer_id` (stable across calls).
     fn list(&self) -> Result<Vec<TrustRecord>>;
     fn remove(&self, peer_id: &str) -> Result<bool>;
 
     fn is_trusted(&self, peer_id: &str) -> Result<bool> {
         Ok(self.get(peer_id)?.is_some())
     }
+
+    /// Revocations, sorted by `peer_id`. Stores that don't keep them have none.
+    fn revocations(&self) -> Result<Vec<TrustRevocation>> {
+        Ok(Vec::new())
+    }
+
+    /// Record a revocation, replacing any earlier one for the same peer.
+    /// Stores that don't keep them ignore it.
+    fn save_revocation(&self, _revocation: TrustRevocation) -> Result<()> {
+        Ok(())
+    }
+
+    /// Remove a peer as the user's decision (unlike [`Self::remove`], which
+    /// also undoes partial writes), recording the revocation first.
+    fn revoke(&self, peer_id: &str) -> Result<bool> {
+        self.save_revocation(TrustRevocation { peer_id: peer_id.to_string(), revoked_at: Utc::now() })?;
+        self.remove(peer_id)
+    }
+
+    /// Save several records all-or-nothing, e.g. both sides of a pairing.
+    ///
+    /// The default saves one by one and, if a save fails, restores every
+    /// record touched so far before returning the error. Stores that can
+    /// write a batch in one go should override it.
+    fn save_many(&self, records: &[TrustRecord]) -> Result<()> {
+        let mut previous = Vec::with_capacity(records.len());
+        for rec in records {
+            let prior = match self.get(&rec.peer_id) {
+                Ok(p) => p,
+                Err(e) => {
+                    restore_records(self, previous);
+                    return Err(e);
+                }
+            };
+            previous.push((rec.peer_id.clone(), prior));
+            if let Err(e) = self.save(rec.clone()) {
+                restore_records(self, previous);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Recompute each record's peer id from its public key and report the
+    /// records that don't match (e.g. after importing or migrating a store).
+    fn verify_integrity(&self) -> Result<Vec<(String, IntegrityIssue)>> {
+        self.verify_integrity_with(&Blake3HexPeerId)
+    }
+
+    /// [`Self::verify_integrity`] for peer ids derived with `scheme`.
+    fn verify_integrity_with(&self, scheme: &dyn PeerIdScheme) -> Result<Vec<(String, IntegrityIssue)>> {
+        let mut issues = Vec::new();
+        for rec in self.list()? {
+            let valid_key = <[u8; 32]>::try_from(rec.identity_pk.as_slice())
+                .ok()
+                .is_some_and(|pk| ed25519_dalek::VerifyingKey::from_bytes(&pk).is_ok());
+            if !valid_key {
+                issues.push((rec.peer_id, IntegrityIssue::MalformedKey));
+                continue;
+            }
+            if !scheme.verify(&rec.peer_id, &rec.identity_pk) {
+                let expected = scheme.derive(&rec.identity_pk);
+                issues.push((rec.peer_id, IntegrityIssue::PeerIdMismatch { expected }));
+            }
+        }
+        Ok(issues)
+    }
+}
+
+/// Undo a partial `save_many`, newest first. Best effort: errors while
+/// restoring are ignored in favour of the one that triggered it.
+fn restore_records<S: TrustStore + ?Sized>(store: &S, previous: Vec<(String, Option<TrustRecord>)>) {
+    for (peer_id, prior) in previous.into_iter().rev() {
+        let _ = match prior {
+            Some(rec) => store.save(rec),
+            None => store.remove(&peer_id).map(|_| ()),
+        };
+    }
+}
+
+/// Merge trust records and revocations received from a linked device.
+///
+/// A revocation newer than the store's own (if any) is kept, and removes the
+/// local record if that was created before it. A record is taken if the store
+/// has none for that peer or an older one (by `created_at`) and no revocation
+/// at or after it, keeping the local sync direction. Records whose peer id
+/// doesn't match their key under `scheme` are skipped. Records are saved all-or-nothing and
+/// revocations are idempotent, so an interrupted merge can simply be redone;
+/// returns the peer ids added, replaced or removed.
+pub fn merge_trust_records<S: TrustStore + ?Sized>(
+    store: &S,
+    incoming: &[TrustRecord],
+    revoked: &[TrustRevocation],
+    scheme: &dyn PeerIdScheme,
+) -> Result<Vec<String>> {
+    let mut revocations: BTreeMap<String, DateTime<Utc>> =
+        store.revocations()?.into_iter().map(|r| (r.peer_id, r.revoked_at)).collect();
+    let mut removed = Vec::new();
+    for rev in revoked {
+        if revocations.get(&rev.peer_id).is_some_and(|at| *at >= rev.revoked_at) {
+            continue;
+        }
+        store.save_revocation(rev.clone())?;
+        revocations.insert(rev.peer_id.clone(), rev.revoked_at);
+        if store.get(&rev.peer_id)?.is_some_and(|cur| cur.created_at < rev.revoked_at) && store.remove(&rev.peer_id)? {
+            removed.push(rev.peer_id.clone());
+        }
+    }
+
+    let mut newer: Vec<TrustRecord> = Vec::new();
+    for rec in incoming {
+        if !scheme.verify(&rec.peer_id, &rec.identity_pk) {
+            continue;
+        }
+        if revocations.get(&rec.peer_id).is_some_and(|at| *at >= rec.created_at) {
+            continue;
+        }
+        let current = newer.iter().find(|r| r.peer_id == rec.peer_id).cloned().or(store.get(&rec.peer_id)?);
+        let mut rec = rec.clone();
+        match current {
+            Some(cur) if cur.created_at >= rec.created_at => continue,
+            Some(cur) => rec.sync_direction = cur.sync_direction,
+            None => rec.sync_direction = SyncDirection::default(),
+        }
+        newer.retain(|r| r.peer_id != rec.peer_id);
+        newer.push(rec);
+    }
+    store.save_many(&newer)?;
+    Ok(removed.into_iter().chain(newer.into_iter().map(|r| r.peer_id)).collect())
+}
+
+/// Async view of a trust store, for callers running on the tokio runtime.
+#[cfg(feature = "net")]
+#[async_trait::async_trait]
+pub trait AsyncTrustStore: Send + Sync {
+    async fn save(&self, record: TrustRecord) -> Result<()>;
+    async fn get(&self, peer_id: &str) -> Result<Option<TrustRecord>>;
+    /// All trusted peers, sorted by `peer_id` (stable across calls).
+    async fn list(&self) -> Result<Vec<TrustRecord>>;
+    async fn remove(&self, peer_id: &str) -> Result<bool>;
+
+    async fn is_trusted(&self, peer_id: &str) -> Result<bool> {
+        Ok(self.get(peer_id).await?.is_some())
+    }
+
+    /// See [`TrustStore::revocations`].
+    async fn revocations(&self) -> Result<Vec<TrustRevocation>> {
+        Ok(Vec::new())
+    }
+
+    /// See [`TrustStore::revoke`].
+    async fn revoke(&self, peer_id: &str) -> Result<bool> {
+        self.remove(peer_id).await
+    }
+}
+
+/// Runs a synchronous [`TrustStore`] on tokio's blocking pool, so stores that
+/// touch the disk (like [`FileTrustStore`]) don't stall runtime worker threads.
+#[cfg(feature = "net")]
+#[derive(Clone)]
+pub struct BlockingTrustStore {
+    inner: std::sync::Arc<dyn TrustStore>,
+}
+
+#[cfg(feature = "net")]
+impl BlockingTrustStore {
+    pub fn new(inner: std::sync::Arc<dyn TrustStore>) -> Self {
+        Self { inner }
+    }
+
+    async fn run<R, F>(&self, f: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&dyn TrustStore) -> Result<R> + Send + 'static,
+    {
+        let inner = std::sync::Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || f(inner.as_ref())).await?
+    }
+}
+
+#[cfg(feature = "net")]
+#[async_trait::async_trait]
+impl AsyncTrustStore for BlockingTrustStore {
+    async fn save(&self, record: TrustRecord) -> Result<()> {
+        self.run(move |s| s.save(record)).await
+    }
+
+    async fn get(&self, peer_id: &str) -> Result<Option<TrustRecord>> {
+        let peer_id = peer_id.to_string();
+        self.run(move |s| s.get(&peer_id)).await
+    }
+
+    async fn list(&self) -> Result<Vec<TrustRecord>> {
+        self.run(|s| s.list()).await
+    }
+
+    async fn remove(&self, peer_id: &str) -> Result<bool> {
+        let peer_id = peer_id.to_string();
+        self.run(move |s| s.remove(&peer_id)).await
+    }
+
+    async fn revocations(&self) -> Result<Vec<TrustRevocation>> {
+        self.run(|s| s.revocations()).await
+    }
+
+    async fn revoke(&self, peer_id: &str) -> Result<bool> {
+        let peer_id = peer_id.to_string();
+        self.run(move |s| s.revoke(&peer_id)).await
+    }
 }
 
 /// In-memory trust store (useful for tests).
 #[derive(Default)]
 pub struct MemoryTrustStore {
-    records: Mutex<HashMap<String, TrustRecord>>,
+    records: Mutex<BTreeMap<String, TrustRecord>>,
+    revocations: Mutex<BTreeMap<String, TrustRevocation>>,
 }
 
 impl MemoryTrustStore {
@@ -63,14 +322,33 @@ impl TrustStore for MemoryTrustStore {
     fn remove(&self, peer_id: &str) -> Result<bool> {
         Ok(self.records.lock().unwrap().remove(peer_id).is_some())
     }
+
+    fn revocations(&self) -> Result<Vec<TrustRevocation>> {
+        Ok(self.revocations.lock().unwrap().values().cloned().collect())
+    }
+
+    fn save_revocation(&self, revocation: TrustRevocation) -> Result<()> {
+        self.revocations.lock().unwrap().insert(revocation.peer_id.clone(), revocation);
+        Ok(())
+    }
+
+    fn save_many(&self, records: &[TrustRecord]) -> Result<()> {
+        let mut map = self.records.lock().unwrap();
+        for rec in records {
+            map.insert(rec.peer_id.clone(), rec.clone());
+        }
+        Ok(())
+    }
 }
 
 /// File-backed trust store (JSON file).
 ///
-/// Format: an array of `TrustRecord`.
+/// Format: an array of `TrustRecord`. Revocations are kept next to it, as an
+/// array of `TrustRevocation` in `<stem>.revocations.json`.
 pub struct FileTrustStore {
     path: PathBuf,
-    cache: Mutex<HashMap<String, TrustRecord>>,
+    cache: Mutex<BTreeMap<String, TrustRecord>>,
+    revocations: Mutex<BTreeMap<String, TrustRevocation>>,
 }
 
 impl FileTrustStore {
@@ -80,24 +358,49 @@ impl FileTrustStore {
             let records: Vec<TrustRecord> = serde_json::from_str(&data)?;
             records.into_iter().map(|r| (r.peer_id.clone(), r)).collect()
         } else {
-            HashMap::new()
+            BTreeMap::new()
+        };
+        let revocations_path = revocations_path(&path);
+        let revocations = if revocations_path.exists() {
+            let data = std::fs::read_to_string(&revocations_path)?;
+            let revocations: Vec<TrustRevocation> = serde_json::from_str(&data)?;
+            revocations.into_iter().map(|r| (r.peer_id.clone(), r)).collect()
+        } else {
+            BTreeMap::new()
         };
 
         Ok(Self {
             path,
             cache: Mutex::new(cache),
+            revocations: Mutex::new(revocations),
         })
     }
 
     fn flush(&self) -> Result<()> {
         let records: Vec<TrustRecord> = self.cache.lock().unwrap().values().cloned().collect();
-        let data = serde_json::to_string_pretty(&records)?;
-        if let Some(parent) = self.path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::write(&self.path, data)?;
-        Ok(())
+        self.write_records(&records)
+    }
+
+    fn write_records(&self, records: &[TrustRecord]) -> Result<()> {
+        write_json_atomically(&self.path, records)
+    }
+}
+
+fn revocations_path(path: &std::path::Path) -> PathBuf {
+    path.with_extension("revocations.json")
+}
+
+/// Write via a temp file and rename, so a failed write never leaves a
+/// truncated file behind.
+pub(crate) fn write_json_atomically<T: Serialize + ?Sized>(path: &std::path::Path, value: &T) -> Result<()> {
+    let data = serde_json::to_string_pretty(value)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
     }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
 }
 
 impl TrustStore for FileTrustStore {
@@ -121,11 +424,127 @@ impl TrustStore for FileTrustStore {
         }
         Ok(removed)
     }
+
+    fn revocations(&self) -> Result<Vec<TrustRevocation>> {
+        Ok(self.revocations.lock().unwrap().values().cloned().collect())
+    }
+
+    /// The in-memory list only changes if the write succeeds.
+    fn save_revocation(&self, revocation: TrustRevocation) -> Result<()> {
+        let mut revocations = self.revocations.lock().unwrap();
+        let mut next = revocations.clone();
+        next.insert(revocation.peer_id.clone(), revocation);
+        write_json_atomically(&revocations_path(&self.path), &next.values().collect::<Vec<_>>())?;
+        *revocations = next;
+        Ok(())
+    }
+
+    /// One write for the whole batch; the cache only changes if it succeeds.
+    fn save_many(&self, records: &[TrustRecord]) -> Result<()> {
+        let mut cache = self.cache.lock().unwrap();
+        let mut next = cache.clone();
+        for rec in records {
+            next.insert(rec.peer_id.clone(), rec.clone());
+        }
+        self.write_records(&next.values().cloned().collect::<Vec<_>>())?;
+        *cache = next;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::identity::{Ed25519Identity, IdentityProvider};
+
+    fn keyed_record(id: &Ed25519Identity, name: &str, created_at: DateTime<Utc>) -> TrustRecord {
+        TrustRecord {
+            peer_id: id.peer_id().to_string(),
+            identity_pk: id.public_key_bytes(),
+            display_name: name.into(),
+            created_at,
+            sync_direction: Default::default(),
+        }
+    }
+
+    #[test]
+    fn merge_takes_new_and_newer_records_and_keeps_local_direction() {
+        let (phone, tablet) = (Ed25519Identity::generate(), Ed25519Identity::generate());
+        let t0 = Utc::now();
+        let later = t0 + chrono::Duration::seconds(10);
+
+        let store = MemoryTrustStore::new();
+        let mut local = keyed_record(&phone, "Phone", t0);
+        local.sync_direction = SyncDirection::ReceiveOnly;
+        store.save(local).unwrap();
+
+        let mut forged = keyed_record(&tablet, "Forged", later);
+        forged.peer_id = "not-its-key".into();
+        let stale = keyed_record(&tablet, "Tablet (old)", t0);
+        let incoming = [
+            keyed_record(&phone, "Phone (renamed)", later),
+            stale,
+            keyed_record(&tablet, "Tablet", later),
+            forged,
+        ];
+
+        let mut changed = merge_trust_records(&store, &incoming, &[], &Blake3HexPeerId).unwrap();
+        changed.sort();
+        let mut expected = vec![phone.peer_id().to_string(), tablet.peer_id().to_string()];
+        expected.sort();
+        assert_eq!(changed, expected);
+
+        let phone_rec = store.get(phone.peer_id()).unwrap().unwrap();
+        assert_eq!(phone_rec.display_name, "Phone (renamed)");
+        assert_eq!(phone_rec.sync_direction, SyncDirection::ReceiveOnly);
+        assert_eq!(store.get(tablet.peer_id()).unwrap().unwrap().display_name, "Tablet");
+        assert!(!store.is_trusted("not-its-key").unwrap());
+
+        // Redoing the same merge is a no-op.
+        assert!(merge_trust_records(&store, &incoming, &[], &Blake3HexPeerId).unwrap().is_empty());
+    }
+
+    #[test]
+    fn merge_applies_revocations_newer_than_the_record() {
+        let (phone, tablet) = (Ed25519Identity::generate(), Ed25519Identity::generate());
+        let t0 = Utc::now();
+        let later = t0 + chrono::Duration::seconds(10);
+        let revoke = |id: &Ed25519Identity, at| TrustRevocation { peer_id: id.peer_id().to_string(), revoked_at: at };
+
+        let store = MemoryTrustStore::new();
+        store.save(keyed_record(&phone, "Phone", t0)).unwrap();
+        store.save(keyed_record(&tablet, "Tablet", later)).unwrap();
+
+        // The phone was revoked after it was paired; the tablet was re-paired
+        // after its revocation.
+        let changed = merge_trust_records(&store, &[], &[revoke(&phone, later), revoke(&tablet, t0)], &Blake3HexPeerId).unwrap();
+        assert_eq!(changed, [phone.peer_id().to_string()]);
+        assert!(!store.is_trusted(phone.peer_id()).unwrap());
+        assert!(store.is_trusted(tablet.peer_id()).unwrap());
+        assert_eq!(store.revocations().unwrap().len(), 2);
+
+        // A linked device still holding the old phone record can't bring it back,
+        // but a pairing after the revocation can.
+        assert!(merge_trust_records(&store, &[keyed_record(&phone, "Phone", t0)], &[], &Blake3HexPeerId).unwrap().is_empty());
+        let repaired = later + chrono::Duration::seconds(1);
+        assert_eq!(merge_trust_records(&store, &[keyed_record(&phone, "Phone", repaired)], &[], &Blake3HexPeerId).unwrap(), [phone.peer_id().to_string()]);
+        assert!(store.is_trusted(phone.peer_id()).unwrap());
+    }
+
+    #[test]
+    fn file_store_keeps_revocations_across_reopen() {
+        let base = std::env::temp_dir().join(format!("openclipboard_trust_revoke_{}", rand::random::<u64>()));
+        let path = base.join("trust.json");
+        let store = FileTrustStore::new(path.clone()).unwrap();
+        store.save(record("peer-a", "A")).unwrap();
+        assert!(store.revoke("peer-a").unwrap());
+
+        let reopened = FileTrustStore::new(path).unwrap();
+        assert!(!reopened.is_trusted("peer-a").unwrap());
+        assert_eq!(reopened.revocations().unwrap().iter().map(|r| r.peer_id.as_str()).collect::<Vec<_>>(), ["peer-a"]);
+
+        let _ = std::fs::remove_dir_all(base);
+    }
 
     #[test]
     fn memory_store_crud() {
@@ -135,6 +554,7 @@ mod tests {
             identity_pk: vec![1, 2, 3],
             display_name: "Alice".into(),
             created_at: Utc::now(),
+            sync_direction: Default::default(),
         };
 
         store.save(record.clone()).unwrap();
@@ -152,6 +572,217 @@ mod tests {
         assert!(!store.is_trusted("peer-a").unwrap());
     }
 
+    #[test]
+    fn list_is_sorted_by_peer_id() {
+        let store = MemoryTrustStore::new();
+        for id in ["peer-c", "peer-a", "peer-b"] {
+            store
+                .save(TrustRecord {
+                    peer_id: id.into(),
+                    identity_pk: vec![1],
+                    display_name: id.to_uppercase(),
+                    created_at: Utc::now(),
+                    sync_direction: Default::default(),
+                })
+                .unwrap();
+        }
+        let ids: Vec<_> = store.list().unwrap().into_iter().map(|r| r.peer_id).collect();
+        assert_eq!(ids, ["peer-a", "peer-b", "peer-c"]);
+    }
+
+    #[test]
+    fn verify_integrity_flags_only_tampered_records() {
+        use crate::identity::IdentityProvider;
+
+        let store = MemoryTrustStore::new();
+        let good = Ed25519Identity::generate();
+        store
+            .save(TrustRecord {
+                peer_id: good.peer_id().into(),
+                identity_pk: good.public_key_bytes(),
+                display_name: "Good".into(),
+                created_at: Utc::now(),
+                sync_direction: Default::default(),
+            })
+            .unwrap();
+        // Claims another device's key under a forged peer id.
+        let other = Ed25519Identity::generate();
+        store
+            .save(TrustRecord {
+                peer_id: "forged".into(),
+                identity_pk: other.public_key_bytes(),
+                display_name: "Tampered".into(),
+                created_at: Utc::now(),
+                sync_direction: Default::default(),
+            })
+            .unwrap();
+        assert_eq!(
+            store.verify_integrity().unwrap(),
+            vec![(
+                "forged".to_string(),
+                IntegrityIssue::PeerIdMismatch { expected: other.peer_id().into() },
+            )]
+        );
+
+        store.save(TrustRecord {
+            peer_id: "short-key".into(),
+            identity_pk: vec![1, 2, 3],
+            display_name: "Broken".into(),
+            created_at: Utc::now(),
+            sync_direction: Default::default(),
+        }).unwrap();
+        let flagged: Vec<_> = store.verify_integrity().unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(flagged, ["forged", "short-key"]);
+    }
+
+    /// Blake3 hex in upper case: a scheme under which default ids don't verify.
+    struct UpperHexPeerId;
+
+    impl PeerIdScheme for UpperHexPeerId {
+        fn derive(&self, public_key: &[u8]) -> String {
+            Blake3HexPeerId.derive(public_key).to_uppercase()
+        }
+    }
+
+    #[test]
+    fn merge_and_integrity_check_use_the_given_peer_id_scheme() {
+        let phone = Ed25519Identity::generate().with_peer_id_scheme(std::sync::Arc::new(UpperHexPeerId));
+        let tablet = Ed25519Identity::generate();
+        let store = MemoryTrustStore::new();
+        let incoming = [keyed_record(&phone, "Phone", Utc::now()), keyed_record(&tablet, "Tablet", Utc::now())];
+
+        assert_eq!(merge_trust_records(&store, &incoming, &[], &UpperHexPeerId).unwrap(), [phone.peer_id().to_string()]);
+        assert!(store.verify_integrity_with(&UpperHexPeerId).unwrap().is_empty());
+        let issues = store.verify_integrity().unwrap();
+        assert_eq!(issues, [(phone.peer_id().to_string(), IntegrityIssue::PeerIdMismatch { expected: phone.peer_id().to_lowercase() })]);
+    }
+
+    fn record(peer_id: &str, display_name: &str) -> TrustRecord {
+        TrustRecord {
+            peer_id: peer_id.into(),
+            identity_pk: vec![1],
+            display_name: display_name.into(),
+            created_at: Utc::now(),
+            sync_direction: Default::default(),
+        }
+    }
+
+    #[test]
+    fn save_many_rolls_back_when_a_later_save_fails() {
+        /// A store whose saves fail for one peer, like a disk error mid-batch.
+        struct FailingStore(MemoryTrustStore, &'static str);
+
+        impl TrustStore for FailingStore {
+            fn save(&self, record: TrustRecord) -> Result<()> {
+                anyhow::ensure!(record.peer_id != self.1, "disk full");
+                self.0.save(record)
+            }
+            fn get(&self, peer_id: &str) -> Result<Option<TrustRecord>> {
+                self.0.get(peer_id)
+            }
+            fn list(&self) -> Result<Vec<TrustRecord>> {
+                self.0.list()
+            }
+            fn remove(&self, peer_id: &str) -> Result<bool> {
+                self.0.remove(peer_id)
+            }
+        }
+
+        let store = FailingStore(MemoryTrustStore::new(), "resp");
+        store.save(record("other", "Old name")).unwrap();
+
+        let err = store.save_many(&[record("init", "Init"), record("other", "New name"), record("resp", "Resp")]);
+        assert!(err.is_err());
+        assert!(!store.is_trusted("init").unwrap());
+        assert!(!store.is_trusted("resp").unwrap());
+        assert_eq!(store.get("other").unwrap().unwrap().display_name, "Old name");
+
+        store.save_many(&[record("init", "Init"), record("other", "New name")]).unwrap();
+        assert_eq!(store.list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn file_store_save_many_persists_nothing_when_the_write_fails() {
+        let base = std::env::temp_dir().join(format!(
+            "openclipboard_trust_many_{}_{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&base).unwrap();
+        let path = base.join("trust.json");
+
+        let store = FileTrustStore::new(path.clone()).unwrap();
+        store.save_many(&[record("init", "Init"), record("resp", "Resp")]).unwrap();
+        assert_eq!(FileTrustStore::new(path.clone()).unwrap().list().unwrap().len(), 2);
+
+        // Make the store's directory unwritable by replacing it with a file.
+        std::fs::remove_dir_all(&base).unwrap();
+        std::fs::write(&base, b"not a directory").unwrap();
+        assert!(store.save_many(&[record("third", "Third"), record("fourth", "Fourth")]).is_err());
+        assert!(!store.is_trusted("third").unwrap());
+        assert!(!store.is_trusted("fourth").unwrap());
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        let _ = std::fs::remove_file(&base);
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn blocking_trust_store_lookup_does_not_stall_runtime() {
+        /// A store whose lookups block like slow disk I/O.
+        struct SlowStore(MemoryTrustStore);
+
+        impl TrustStore for SlowStore {
+            fn save(&self, record: TrustRecord) -> Result<()> {
+                self.0.save(record)
+            }
+            fn get(&self, peer_id: &str) -> Result<Option<TrustRecord>> {
+                std::thread::sleep(std::time::Duration::from_millis(500));
+                self.0.get(peer_id)
+            }
+            fn list(&self) -> Result<Vec<TrustRecord>> {
+                self.0.list()
+            }
+            fn remove(&self, peer_id: &str) -> Result<bool> {
+                self.0.remove(peer_id)
+            }
+        }
+
+        let inner = SlowStore(MemoryTrustStore::new());
+        inner
+            .save(TrustRecord {
+                peer_id: "peer-a".into(),
+                identity_pk: vec![1],
+                display_name: "A".into(),
+                created_at: Utc::now(),
+                sync_direction: Default::default(),
+            })
+            .unwrap();
+        let store = BlockingTrustStore::new(std::sync::Arc::new(inner));
+
+        // Single-threaded runtime: a blocking lookup would delay the timer by ~500ms.
+        let started = std::time::Instant::now();
+        let timer = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            started.elapsed()
+        });
+        assert!(store.is_trusted("peer-a").await.unwrap());
+        let fired_after = timer.await.unwrap();
+        assert!(fired_after < std::time::Duration::from_millis(250), "timer fired after {fired_after:?}");
+    }
+
+    #[test]
+    fn record_without_sync_direction_defaults_to_bidirectional() {
+        let json = r#"{"peer_id":"p","identity_pk":[1,2],"display_name":"P","created_at":"2024-01-01T00:00:00Z"}"#;
+        let rec: TrustRecord = serde_json::from_str(json).unwrap();
+        assert_eq!(rec.sync_direction, SyncDirection::Bidirectional);
+        assert!(rec.sync_direction.sends() && rec.sync_direction.receives());
+        assert!(!SyncDirection::ReceiveOnly.sends() && SyncDirection::ReceiveOnly.receives());
+    }
+
     #[test]
     fn file_store_persist_roundtrip() {
         let base = std::env::temp_dir().join(format!(
@@ -173,6 +804,7 @@ mod tests {
                     identity_pk: vec![9, 8, 7],
                     display_name: "Xavier".into(),
                     created_at: Utc::now(),
+                    sync_direction: Default::default(),
                 })
                 .unwrap();
             assert!(store.is_trusted("peer-x").unwrap());
@@ -183,6 +815,18 @@ mod tests {
             let store = FileTrustStore::new(path.clone()).unwrap();
             let rec = store.get("peer-x").unwrap().unwrap();
             assert_eq!(rec.display_name, "Xavier");
+
+            store
+                .save(TrustRecord {
+                    peer_id: "peer-m".into(),
+                    identity_pk: vec![1],
+                    display_name: "Mia".into(),
+                    created_at: Utc::now(),
+                    sync_direction: Default::default(),
+                })
+                .unwrap();
+            let ids: Vec<_> = store.list().unwrap().into_iter().map(|r| r.peer_id).collect();
+            assert_eq!(ids, ["peer-m", "peer-x"]);
         }
 
         let _ = std::fs::remove_dir_all(base);
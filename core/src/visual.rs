@@ -0,0 +1,80 @@
+//! Deterministic per-device visuals (color, initials, identicon seed) derived
+//! from a peer id, so every platform draws the same device the same way.
+
+/// How to draw a device in a peer list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceVisual {
+    /// `#RRGGBB`; a fixed saturation and lightness keep every hue readable
+    /// behind white text.
+    pub color_hex: String,
+    /// Two upper-case characters for an avatar badge.
+    pub initials: String,
+    /// Seed for a platform's identicon generator.
+    pub identicon_seed: u64,
+}
+
+/// Derive a [`DeviceVisual`] from `blake3(peer_id)`. Pure: the same id always
+/// yields the same visual.
+pub fn peer_visual(peer_id: &str) -> DeviceVisual {
+    let hash = blake3::hash(peer_id.as_bytes());
+    let b = hash.as_bytes();
+
+    let hue = u16::from_le_bytes([b[0], b[1]]) % 360;
+    let (r, g, bl) = hsl_to_rgb(hue as f32, 0.65, 0.45);
+
+    let initials: String = peer_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(2)
+        .collect::<String>()
+        .to_ascii_uppercase();
+    let initials = if initials.is_empty() { hash.to_hex()[..2].to_ascii_uppercase() } else { initials };
+
+    DeviceVisual {
+        color_hex: format!("#{r:02X}{g:02X}{bl:02X}"),
+        initials,
+        identicon_seed: u64::from_le_bytes(b[8..16].try_into().expect("8 bytes")),
+    }
+}
+
+fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+    let (r, g, b) = match h as u32 {
+        0..=59 => (c, x, 0.0),
+        60..=119 => (x, c, 0.0),
+        120..=179 => (0.0, c, x),
+        180..=239 => (0.0, x, c),
+        240..=299 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let to_u8 = |v: f32| ((v + m) * 255.0).round() as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_peer_id_same_visual_different_ids_differ() {
+        let a = peer_visual("3f9a0c7e51d2");
+        assert_eq!(a, peer_visual("3f9a0c7e51d2"));
+        assert_eq!(a.initials, "3F");
+        assert_eq!(a.color_hex.len(), 7);
+        assert!(a.color_hex.starts_with('#'));
+
+        let b = peer_visual("b41e77d09a6c");
+        assert_ne!(a.color_hex, b.color_hex);
+        assert_ne!(a.identicon_seed, b.identicon_seed);
+        assert_ne!(a.initials, b.initials);
+    }
+
+    #[test]
+    fn hsl_primaries() {
+        assert_eq!(hsl_to_rgb(0.0, 1.0, 0.5), (255, 0, 0));
+        assert_eq!(hsl_to_rgb(120.0, 1.0, 0.5), (0, 255, 0));
+        assert_eq!(hsl_to_rgb(240.0, 1.0, 0.5), (0, 0, 255));
+    }
+}
@@ -1,16 +1,21 @@
-use openclipboard_core::{Ed25519Identity, MockClipboard, Session};
+#![cfg(feature = "net")]
+
+use openclipboard_core::{
+    box_listener, box_transport, Ed25519Identity, IdentityProvider, MemoryReplayProtector, MemoryTrustStore,
+    MockClipboard, MockDiscovery, Session, SyncHandler, SyncService, TrustRecord, TrustStore,
+};
 use openclipboard_core::protocol::Frame;
-use openclipboard_core::transport::Connection;
+use openclipboard_core::transport::{Connection, Listener, Transport};
 
 use anyhow::Result;
 use async_trait::async_trait;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
-use std::collections::VecDeque;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, watch, Mutex};
 
 #[derive(Clone, Debug)]
 struct ChaosConfig {
@@ -92,11 +97,13 @@ impl ChaosLink {
             tx: a_out_tx,
             rx: Mutex::new(a_in_rx),
             closed: closed.clone(),
+            partitioned: None,
         };
         let b = ChaosConn {
             tx: b_out_tx,
             rx: Mutex::new(b_in_rx),
             closed,
+            partitioned: None,
         };
         (a, b)
     }
@@ -157,6 +164,8 @@ struct ChaosConn {
     tx: mpsc::Sender<Frame>,
     rx: Mutex<mpsc::Receiver<Frame>>,
     closed: Arc<AtomicBool>,
+    /// Set while the `ChaosNet` this link belongs to is partitioned.
+    partitioned: Option<watch::Receiver<bool>>,
 }
 
 #[async_trait]
@@ -165,13 +174,22 @@ impl Connection for ChaosConn {
         if self.is_closed() {
             anyhow::bail!("connection closed");
         }
+        if self.partitioned.as_ref().is_some_and(|p| *p.borrow()) {
+            anyhow::bail!("network partitioned");
+        }
         self.tx.send(frame).await.map_err(|_| anyhow::anyhow!("send failed"))?;
         Ok(())
     }
 
     async fn recv(&self) -> Result<Frame> {
         let mut rx = self.rx.lock().await;
-        rx.recv().await.ok_or_else(|| anyhow::anyhow!("connection closed"))
+        let Some(mut partitioned) = self.partitioned.clone() else {
+            return rx.recv().await.ok_or_else(|| anyhow::anyhow!("connection closed"));
+        };
+        tokio::select! {
+            frame = rx.recv() => frame.ok_or_else(|| anyhow::anyhow!("connection closed")),
+            _ = partitioned.wait_for(|p| *p) => anyhow::bail!("network partitioned"),
+        }
     }
 
     fn close(&self) {
@@ -252,3 +270,219 @@ async fn chaos_drop_causes_handshake_timeout_not_hang() {
     assert!(ra.is_err());
     assert!(rb.is_err());
 }
+
+/// In-process network of `ChaosLink`s. Link `n` is seeded with `seed + n`, so a
+/// run's fault pattern depends only on the seed and the order links are opened.
+#[derive(Clone)]
+struct ChaosNet {
+    seed: u64,
+    cfg: ChaosConfig,
+    links: Arc<AtomicU64>,
+    listeners: Arc<std::sync::Mutex<HashMap<String, mpsc::Sender<ChaosConn>>>>,
+    /// While true, every link errors out and no new one can be opened, though
+    /// both ends stay up.
+    partitioned: Arc<watch::Sender<bool>>,
+}
+
+impl ChaosNet {
+    fn new(seed: u64, cfg: ChaosConfig) -> Self {
+        Self {
+            seed,
+            cfg,
+            links: Arc::new(AtomicU64::new(0)),
+            listeners: Default::default(),
+            partitioned: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    fn set_partitioned(&self, partitioned: bool) {
+        self.partitioned.send_replace(partitioned);
+    }
+
+    fn listen(&self, addr: &str) -> ChaosListener {
+        let (tx, rx) = mpsc::channel(16);
+        self.listeners.lock().unwrap().insert(addr.to_string(), tx);
+        ChaosListener { rx: Mutex::new(rx) }
+    }
+}
+
+#[async_trait]
+impl Transport for ChaosNet {
+    type Conn = ChaosConn;
+
+    async fn connect(&self, addr: &str) -> Result<ChaosConn> {
+        let listener = self.listeners.lock().unwrap().get(addr).cloned();
+        let listener = listener.ok_or_else(|| anyhow::anyhow!("no chaos listener at {addr}"))?;
+        if *self.partitioned.borrow() {
+            anyhow::bail!("network partitioned");
+        }
+        let n = self.links.fetch_add(1, Ordering::SeqCst);
+        let (mut local, mut remote) = ChaosLink::pair(self.seed.wrapping_add(n), self.cfg.clone());
+        local.partitioned = Some(self.partitioned.subscribe());
+        remote.partitioned = Some(self.partitioned.subscribe());
+        listener.send(remote).await.map_err(|_| anyhow::anyhow!("chaos listener at {addr} closed"))?;
+        Ok(local)
+    }
+}
+
+struct ChaosListener {
+    rx: Mutex<mpsc::Receiver<ChaosConn>>,
+}
+
+#[async_trait]
+impl Listener for ChaosListener {
+    type Conn = ChaosConn;
+
+    async fn accept(&self) -> Result<ChaosConn> {
+        self.rx.lock().await.recv().await.ok_or_else(|| anyhow::anyhow!("chaos listener closed"))
+    }
+}
+
+#[derive(Default)]
+struct TextRecorder {
+    texts: std::sync::Mutex<Vec<String>>,
+    /// Peers currently connected.
+    online: std::sync::atomic::AtomicUsize,
+}
+
+impl SyncHandler for TextRecorder {
+    fn on_clipboard_text(&self, _peer_id: String, text: String, _ts_ms: u64) {
+        self.texts.lock().unwrap().push(text);
+    }
+    fn on_peer_connected(&self, _peer_id: String) {
+        self.online.fetch_add(1, Ordering::SeqCst);
+    }
+    fn on_peer_disconnected(&self, _peer_id: String) {
+        self.online.fetch_sub(1, Ordering::SeqCst);
+    }
+    fn on_error(&self, _message: String) {}
+}
+
+fn chaos_sync_node(
+    identity: &Ed25519Identity,
+    peer: &Ed25519Identity,
+    discovery: MockDiscovery,
+    handler: Arc<TextRecorder>,
+) -> SyncService<MockDiscovery> {
+    let trust = Arc::new(MemoryTrustStore::new());
+    trust
+        .save(TrustRecord {
+            peer_id: peer.peer_id().to_string(),
+            identity_pk: peer.public_key_bytes(),
+            display_name: "peer".into(),
+            created_at: chrono::Utc::now(),
+            sync_direction: Default::default(),
+        })
+        .unwrap();
+    SyncService::new(
+        identity.clone(),
+        trust,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(discovery),
+        "127.0.0.1:0".parse().unwrap(),
+        identity.peer_id().to_string(),
+        handler,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn chaos_sync_service_broadcast_converges_under_loss_and_reorder() {
+    const MAX_BROADCASTS: usize = 40;
+
+    let cfg = ChaosConfig {
+        drop_rate: 0.1,
+        reorder_rate: 0.2,
+        max_delay: Duration::from_millis(2),
+        jitter: Duration::from_millis(5),
+        ..Default::default()
+    };
+    let net = ChaosNet::new(0xC1A0, cfg);
+    let shared = MockDiscovery::new_shared();
+
+    let id_a = Ed25519Identity::generate();
+    let id_b = Ed25519Identity::generate();
+    let h_a = Arc::new(TextRecorder::default());
+    let h_b = Arc::new(TextRecorder::default());
+    let a = chaos_sync_node(&id_a, &id_b, shared.clone_shared(), h_a.clone());
+    let b = chaos_sync_node(&id_b, &id_a, shared.clone_shared(), h_b.clone());
+
+    a.start_with_transport(box_listener(net.listen("chaos-a")), "chaos-a".into(), box_transport(net.clone()))
+        .await
+        .unwrap();
+    b.start_with_transport(box_listener(net.listen("chaos-b")), "chaos-b".into(), box_transport(net.clone()))
+        .await
+        .unwrap();
+
+    // Frames may be lost, so keep re-broadcasting (fresh text each time) until one lands.
+    let mut delivered_after = None;
+    for i in 0..MAX_BROADCASTS {
+        a.broadcast_clip_text(format!("chaos-{i}")).await;
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        if h_b.texts.lock().unwrap().iter().any(|t| t.starts_with("chaos-")) {
+            delivered_after = Some(i + 1);
+            break;
+        }
+    }
+
+    a.stop().await;
+    b.stop().await;
+
+    assert!(
+        delivered_after.is_some(),
+        "no broadcast arrived in {MAX_BROADCASTS} attempts over a lossy link"
+    );
+}
+
+#[tokio::test]
+async fn clips_copied_on_both_sides_of_a_partition_converge_to_the_newest_on_heal() {
+    let net = ChaosNet::new(0x5EA1, ChaosConfig::default());
+    let shared = MockDiscovery::new_shared();
+
+    let id_a = Ed25519Identity::generate();
+    let id_b = Ed25519Identity::generate();
+    let h_a = Arc::new(TextRecorder::default());
+    let h_b = Arc::new(TextRecorder::default());
+    let a = chaos_sync_node(&id_a, &id_b, shared.clone_shared(), h_a.clone());
+    let b = chaos_sync_node(&id_b, &id_a, shared.clone_shared(), h_b.clone());
+    for node in [&a, &b] {
+        node.set_reconcile_on_connect(true);
+    }
+    a.start_with_transport(box_listener(net.listen("chaos-a")), "chaos-a".into(), box_transport(net.clone()))
+        .await
+        .unwrap();
+    b.start_with_transport(box_listener(net.listen("chaos-b")), "chaos-b".into(), box_transport(net.clone()))
+        .await
+        .unwrap();
+
+    async fn wait_until(what: &str, done: impl Fn() -> bool) {
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while !done() {
+            assert!(std::time::Instant::now() < deadline, "timed out waiting for {what}");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+    let online = |h: &TextRecorder| h.online.load(Ordering::SeqCst);
+
+    wait_until("the nodes to connect", || online(&h_a) == 1 && online(&h_b) == 1).await;
+    a.broadcast_clip_text("before".into()).await;
+    wait_until("the pre-partition clip", || h_b.texts.lock().unwrap().iter().any(|t| t == "before")).await;
+
+    // Both stay up but can't reach each other; each copies something.
+    net.set_partitioned(true);
+    wait_until("the partition to drop the session", || online(&h_a) == 0 && online(&h_b) == 0).await;
+    b.broadcast_clip_text("older, from b".into()).await;
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    a.broadcast_clip_text("newer, from a".into()).await;
+
+    // On heal the newer clip wins on both sides; the older one is not applied.
+    net.set_partitioned(false);
+    wait_until("b to take a's newer clip", || h_b.texts.lock().unwrap().last().is_some_and(|t| t == "newer, from a")).await;
+    wait_until("the nodes to reconnect", || online(&h_a) == 1 && online(&h_b) == 1).await;
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(h_a.texts.lock().unwrap().is_empty(), "{:?}", h_a.texts.lock().unwrap());
+    assert_eq!(h_b.texts.lock().unwrap().as_slice(), ["before", "newer, from a"]);
+
+    a.stop().await;
+    b.stop().await;
+}
@@ -302,3 +302,58 @@ fn peer_filter_with_limit() {
     assert_eq!(alice_3[1].content, "a8");
     assert_eq!(alice_3[2].content, "a7");
 }
+
+#[test]
+fn peer_summary_counts_per_peer_ordered_by_recency() {
+    let h = ClipboardHistory::new(100);
+    h.record("a1".into(), "alice".into());
+    h.record("b1".into(), "bob".into());
+    h.record("a2".into(), "alice".into());
+    h.record("c1".into(), "carol".into());
+    h.record("b2".into(), "bob".into());
+    h.record("b3".into(), "bob".into());
+
+    let summary = h.peer_summary();
+    let peers: Vec<(&str, usize)> = summary.iter().map(|(p, n, _)| (p.as_str(), *n)).collect();
+    assert_eq!(peers, [("bob", 3), ("carol", 1), ("alice", 2)]);
+
+    for (peer, _, last_ts) in &summary {
+        assert_eq!(*last_ts, h.last_for_peer(peer).unwrap().timestamp);
+    }
+    assert!(summary.windows(2).all(|w| w[0].2 >= w[1].2));
+}
+
+#[test]
+fn byte_budget_evicts_oldest_until_under_limit() {
+    let h = ClipboardHistory::new(100);
+    h.set_max_bytes(Some(1000));
+    for i in 0..10 {
+        h.record(format!("{i}").repeat(300), "p".into());
+        assert!(h.total_bytes() <= 1000, "total {} after entry {i}", h.total_bytes());
+    }
+    // Only the newest three 300-byte entries fit.
+    let kept: Vec<String> = h.get_recent(10).into_iter().map(|e| e.content[..1].to_string()).collect();
+    assert_eq!(kept, ["9", "8", "7"]);
+    assert_eq!(h.total_bytes(), 900);
+
+    // Tightening the budget evicts straight away; the count cap still binds too.
+    h.set_max_bytes(Some(400));
+    assert_eq!(h.len(), 1);
+    let h = ClipboardHistory::new(2);
+    h.set_max_bytes(Some(1000));
+    for c in ["a", "b", "c"] {
+        h.record(c.into(), "p".into());
+    }
+    assert_eq!(h.len(), 2);
+    assert_eq!(h.total_bytes(), 2);
+}
+
+#[test]
+fn byte_budget_keeps_a_single_oversized_entry() {
+    let h = ClipboardHistory::new(100);
+    h.set_max_bytes(Some(10));
+    h.record("small".into(), "p".into());
+    h.record("x".repeat(50), "p".into());
+    assert_eq!(h.len(), 1);
+    assert_eq!(h.get_recent(1)[0].content.len(), 50);
+}
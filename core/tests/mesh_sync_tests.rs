@@ -1,5 +1,7 @@
 //! Tests for mesh sync: EchoSuppressor, PeerRegistry, clipboard watcher behavior.
 
+#![cfg(feature = "net")]
+
 use openclipboard_core::{
     ClipboardHistory, ClipboardContent, ClipboardProvider, EchoSuppressor,
     PeerRegistry, PeerStatus, PeerEntry,
@@ -87,6 +89,7 @@ async fn peer_registry_load_from_trust() {
         identity_pk: vec![1],
         display_name: "Peer1".into(),
         created_at: chrono::Utc::now(),
+        sync_direction: Default::default(),
     }).unwrap();
 
     reg.load_from_trust(&store).await.unwrap();
@@ -106,6 +109,7 @@ async fn peer_registry_online_offline_toggle() {
         identity_pk: vec![1],
         display_name: "P1".into(),
         created_at: chrono::Utc::now(),
+        sync_direction: Default::default(),
     }).unwrap();
     reg.load_from_trust(&store).await.unwrap();
 
@@ -140,6 +144,7 @@ async fn peer_registry_multiple_peers() {
             identity_pk: vec![i as u8],
             display_name: format!("Peer{i}"),
             created_at: chrono::Utc::now(),
+            sync_direction: Default::default(),
         }).unwrap();
     }
     reg.load_from_trust(&store).await.unwrap();
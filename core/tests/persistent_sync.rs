@@ -1,4 +1,6 @@
-use openclipboard_core::{Ed25519Identity, IdentityProvider, MemoryReplayProtector, MemoryTrustStore, SyncHandler, SyncService, TrustRecord, TrustStore, MockDiscovery};
+#![cfg(feature = "net")]
+
+use openclipboard_core::{Ed25519Identity, HandshakeError, IdentityProvider, MemoryReplayProtector, MemoryTrustStore, SyncDirection, SyncHandler, SyncService, TrustRecord, TrustStore, MockDiscovery, ErrorCategory};
 use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
 
@@ -8,6 +10,11 @@ struct TestHandler {
     connected: Mutex<Vec<String>>,
     disconnected: Mutex<Vec<String>>,
     errors: Mutex<Vec<String>>,
+    handshake_failures: Mutex<Vec<(String, HandshakeError)>>,
+    sync_stopped: Mutex<Vec<(String, String)>>,
+    files: Mutex<Vec<(String, String, String)>>,
+    delivered: Mutex<Vec<(String, u64)>>,
+    images: Mutex<Vec<(String, String, Vec<u8>)>>,
 }
 
 impl SyncHandler for TestHandler {
@@ -26,6 +33,26 @@ impl SyncHandler for TestHandler {
     fn on_error(&self, message: String) {
         self.errors.lock().unwrap().push(message);
     }
+
+    fn on_handshake_failed(&self, peer_id: String, error: HandshakeError) {
+        self.handshake_failures.lock().unwrap().push((peer_id, error));
+    }
+
+    fn on_sync_stopped(&self, task: String, reason: String) {
+        self.sync_stopped.lock().unwrap().push((task, reason));
+    }
+
+    fn on_file_received(&self, peer_id: String, name: String, path: String) {
+        self.files.lock().unwrap().push((peer_id, name, path));
+    }
+
+    fn on_clip_delivered(&self, peer_id: String, ts_ms: u64) {
+        self.delivered.lock().unwrap().push((peer_id, ts_ms));
+    }
+
+    fn on_clipboard_image(&self, peer_id: String, mime: String, _width: u32, _height: u32, bytes: Vec<u8>, _ts_ms: u64) {
+        self.images.lock().unwrap().push((peer_id, mime, bytes));
+    }
 }
 
 fn trust_each_other(a: &Ed25519Identity, b: &Ed25519Identity, store: &MemoryTrustStore, name: &str) {
@@ -34,6 +61,7 @@ fn trust_each_other(a: &Ed25519Identity, b: &Ed25519Identity, store: &MemoryTrus
         identity_pk: b.public_key_bytes(),
         display_name: name.to_string(),
         created_at: chrono::Utc::now(),
+        sync_direction: Default::default(),
     }).unwrap();
 }
 
@@ -316,3 +344,2723 @@ async fn quic_persistent_reconnects_after_remote_restart() {
 
     assert!(got, "expected cliptext after reconnect");
 }
+
+#[tokio::test]
+async fn untrusted_connection_attempt_is_listed_in_pending_requests() {
+    use openclipboard_core::quic_transport::{make_insecure_client_endpoint, QuicTransport};
+    use openclipboard_core::{Discovery, Session, Transport};
+
+    let disc = MockDiscovery::new_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let stranger = Ed25519Identity::generate();
+
+    let h1 = Arc::new(TestHandler::default());
+    let s1 = SyncService::new(
+        id1.clone(),
+        Arc::new(MemoryTrustStore::new()),
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev1".into(),
+        h1.clone(),
+    ).unwrap();
+    s1.start().await.unwrap();
+
+    let addr = disc.scan().await.unwrap()
+        .into_iter()
+        .find(|p| p.peer_id == id1.peer_id())
+        .unwrap()
+        .addr;
+
+    let transport = QuicTransport::new(make_insecure_client_endpoint().unwrap());
+    let conn = transport.connect(&addr).await.unwrap();
+    let session = Session::new(conn, stranger.clone(), openclipboard_core::MockClipboard::new());
+    let _ = session.handshake().await;
+
+    let start = std::time::Instant::now();
+    while s1.pending_requests().is_empty() && start.elapsed() < std::time::Duration::from_secs(2) {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let pending = s1.pending_requests();
+    assert_eq!(pending.len(), 1, "errors={:?}", h1.errors.lock().unwrap());
+    assert_eq!(pending[0].peer_id, stranger.peer_id());
+    assert_eq!(
+        pending[0].fingerprint,
+        Ed25519Identity::fingerprint_from_public_key(&stranger.public_key_bytes())
+    );
+    assert!(pending[0].addr.is_some());
+    assert!(h1.connected.lock().unwrap().is_empty());
+
+    s1.stop().await;
+}
+
+#[tokio::test]
+async fn quic_persistent_syncs_10mib_text_in_chunks() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+
+    let s1 = SyncService::new(
+        id1.clone(),
+        trust1,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc1),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev1".into(),
+        h1.clone(),
+    ).unwrap();
+    let s2 = SyncService::new(
+        id2.clone(),
+        trust2,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc2),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev2".into(),
+        h2.clone(),
+    ).unwrap();
+
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(2) {
+        if !h1.connected.lock().unwrap().is_empty() && !h2.connected.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    // 10 MiB of non-ASCII text: well over MAX_PAYLOAD_LEN, with multi-byte chars at chunk edges.
+    let big: String = "clipboard-ü-€-".chars().cycle().take(10 * 1024 * 1024).collect();
+    s1.broadcast_clip_text(big.clone()).await;
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(10) {
+        if !h2.texts.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let texts = h2.texts.lock().unwrap().clone();
+    assert_eq!(texts.len(), 1, "errors={:?}", h2.errors.lock().unwrap());
+    assert_eq!(texts[0].0, id1.peer_id());
+    assert!(texts[0].1 == big, "reassembled text differs from original");
+    assert_eq!(s2.history().get_recent(1)[0].content.len(), big.len());
+
+    s1.stop().await;
+    s2.stop().await;
+}
+
+#[tokio::test]
+async fn disconnect_peer_drops_connection_and_suppresses_redial() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+
+    let s1 = SyncService::new(
+        id1.clone(),
+        trust1,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc1),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev1".into(),
+        h1.clone(),
+    ).unwrap();
+    let s2 = SyncService::new(
+        id2.clone(),
+        trust2,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc2),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev2".into(),
+        h2.clone(),
+    ).unwrap();
+
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while h1.connected.lock().unwrap().is_empty() && start.elapsed() < std::time::Duration::from_secs(2) {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(h1.connected.lock().unwrap().len(), 1);
+
+    assert!(s1.disconnect_peer(id2.peer_id()).await);
+    assert_eq!(s1.suspended_peers(), vec![id2.peer_id().to_string()]);
+
+    // Well past the initial redial backoff: s1 must not have reconnected.
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    assert_eq!(h1.connected.lock().unwrap().len(), 1, "peer reconnected after disconnect_peer");
+    assert!(!h1.disconnected.lock().unwrap().is_empty());
+
+    // Re-enabling lets it connect again.
+    s1.resume_peer(id2.peer_id());
+    let start = std::time::Instant::now();
+    while h1.connected.lock().unwrap().len() < 2 && start.elapsed() < std::time::Duration::from_secs(6) {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert_eq!(h1.connected.lock().unwrap().len(), 2, "errors={:?}", h1.errors.lock().unwrap());
+
+    s1.stop().await;
+    s2.stop().await;
+}
+
+#[tokio::test]
+async fn removed_peer_is_revoked_disconnected_and_dropped_from_the_registry() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust: &Arc<MemoryTrustStore>, disc, name: &str, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust.clone(),
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            name.into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, &trust1, disc1, "dev1", &h1);
+    let s2 = service(&id2, &trust2, disc2, "dev2", &h2);
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while s1.active_sessions().await.is_empty() && start.elapsed() < std::time::Duration::from_secs(2) {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(s1.active_sessions().await.len(), 1);
+
+    assert!(s1.remove_trusted_peer(id2.peer_id()).await.unwrap());
+    assert!(s1.active_sessions().await.is_empty());
+    assert!(s1.peer_registry().get(id2.peer_id()).await.is_none());
+    assert!(trust1.get(id2.peer_id()).unwrap().is_none());
+    let revoked: Vec<String> = trust1.revocations().unwrap().into_iter().map(|r| r.peer_id).collect();
+    assert_eq!(revoked, [id2.peer_id().to_string()]);
+
+    // Redials from either side now fail the trust check.
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    assert!(s1.active_sessions().await.is_empty());
+    assert!(s1.peer_registry().get(id2.peer_id()).await.is_none());
+    assert!(!s1.remove_trusted_peer(id2.peer_id()).await.unwrap());
+
+    s1.stop().await;
+    s2.stop().await;
+}
+
+#[tokio::test]
+async fn received_clip_history_entry_carries_sender_device_name() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+
+    let s1 = SyncService::new(
+        id1.clone(),
+        trust1,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc1),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "Alice's MacBook".into(),
+        h1.clone(),
+    ).unwrap();
+
+    let s2 = SyncService::new(
+        id2.clone(),
+        trust2,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc2),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "Bob's Phone".into(),
+        h2.clone(),
+    ).unwrap();
+
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(2) {
+        if !h1.connected.lock().unwrap().is_empty() && !h2.connected.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    s1.broadcast_clip_text("labelled".to_string()).await;
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3) {
+        if h2.texts.lock().unwrap().iter().any(|(_, t)| t == "labelled") {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    let entry = s2.history().get_recent(1).into_iter().next().expect("history entry");
+
+    s1.stop().await;
+    s2.stop().await;
+
+    assert_eq!(entry.content, "labelled");
+    assert_eq!(entry.source_peer, id1.peer_id());
+    assert_eq!(entry.origin_name.as_deref(), Some("Alice's MacBook"));
+}
+
+#[tokio::test]
+async fn plaintext_peer_is_flagged_and_refused_when_encryption_required() {
+    async fn pair(require_on_first: bool) -> (SyncService<MockDiscovery>, SyncService<MockDiscovery>, Arc<TestHandler>, Ed25519Identity) {
+        let disc1 = MockDiscovery::new_shared();
+        let disc2 = disc1.clone_shared();
+        let id1 = Ed25519Identity::generate();
+        let id2 = Ed25519Identity::generate();
+        let trust1 = Arc::new(MemoryTrustStore::new());
+        let trust2 = Arc::new(MemoryTrustStore::new());
+        trust_each_other(&id1, &id2, &trust1, "peer2");
+        trust_each_other(&id2, &id1, &trust2, "peer1");
+        let h1 = Arc::new(TestHandler::default());
+        let s1 = SyncService::new(
+            id1,
+            trust1.clone(),
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc1),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev1".into(),
+            h1.clone(),
+        ).unwrap();
+        let s2 = SyncService::new(
+            id2.clone(),
+            trust2,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc2),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev2".into(),
+            Arc::new(TestHandler::default()),
+        ).unwrap();
+        s1.set_require_encryption(require_on_first);
+        // The second device doesn't offer encryption, so the session stays plaintext.
+        s2.set_offer_encryption(false);
+        s1.peer_registry().load_from_trust(trust1.as_ref()).await.unwrap();
+        s1.start().await.unwrap();
+        s2.start().await.unwrap();
+        (s1, s2, h1, id2)
+    }
+
+    // Default policy: the plaintext peer connects and is reported as unencrypted.
+    let (s1, s2, h1, id2) = pair(false).await;
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(2) && h1.connected.lock().unwrap().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let entry = s1.peer_registry().get(id2.peer_id()).await.unwrap();
+    s1.stop().await;
+    s2.stop().await;
+    assert_eq!(h1.connected.lock().unwrap().as_slice(), [id2.peer_id().to_string()]);
+    assert!(!entry.encrypted);
+
+    // "Require encryption": the same plaintext peer is dropped after the handshake.
+    let (s1, s2, h1, _id2) = pair(true).await;
+    assert!(s1.requires_encryption());
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    s1.stop().await;
+    s2.stop().await;
+    assert!(h1.connected.lock().unwrap().is_empty());
+    assert!(
+        h1.errors.lock().unwrap().iter().any(|e| e.contains("refusing unencrypted session")),
+        "errors={:?}",
+        h1.errors.lock().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn services_encrypt_sessions_by_default_and_satisfy_required_encryption() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let s1 = SyncService::new(
+        id1.clone(),
+        trust1.clone(),
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc1),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev1".into(),
+        h1.clone(),
+    ).unwrap();
+    let s2 = SyncService::new(
+        id2.clone(),
+        trust2,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc2),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev2".into(),
+        h2.clone(),
+    ).unwrap();
+    assert!(s1.offers_encryption());
+    s1.set_require_encryption(true);
+    s2.set_require_encryption(true);
+    s1.peer_registry().load_from_trust(trust1.as_ref()).await.unwrap();
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3)
+        && (h1.connected.lock().unwrap().is_empty() || h2.connected.lock().unwrap().is_empty())
+    {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let entry = s1.peer_registry().get(id2.peer_id()).await.unwrap();
+    let sessions = s1.active_sessions().await;
+
+    // Clips still flow over the encrypted session.
+    s2.broadcast_clip_text("sealed".into()).await;
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(2) && h1.texts.lock().unwrap().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    s1.stop().await;
+    s2.stop().await;
+
+    assert!(entry.encrypted);
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].encrypted);
+    assert_eq!(h1.texts.lock().unwrap().iter().map(|(_, t)| t.as_str()).collect::<Vec<_>>(), ["sealed"]);
+}
+
+#[tokio::test]
+async fn permanently_failing_dial_errors_are_coalesced() {
+    use openclipboard_core::{Discovery, PeerInfo};
+
+    let disc = MockDiscovery::new_shared();
+    let id = Ed25519Identity::generate();
+    // Lexicographically after any hex peer id, so we are the dialer.
+    let unreachable = "zz-unreachable-peer";
+    let trust = Arc::new(MemoryTrustStore::new());
+    trust.save(TrustRecord {
+        peer_id: unreachable.into(),
+        identity_pk: vec![0; 32],
+        display_name: "offline".into(),
+        created_at: chrono::Utc::now(),
+        sync_direction: Default::default(),
+    }).unwrap();
+    disc.clone_shared().advertise(PeerInfo {
+        peer_id: unreachable.into(),
+        name: "offline".into(),
+        addr: "not-a-socket-addr".into(),
+        alt_addrs: Vec::new(),
+    }).await.unwrap();
+
+    let h = Arc::new(TestHandler::default());
+    let s = SyncService::new(
+        id,
+        trust,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev".into(),
+        h.clone(),
+    ).unwrap();
+    s.start().await.unwrap();
+    // Backoff starts at 200ms, so uncoalesced this would be several reports.
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    s.stop().await;
+
+    let dial_errors: Vec<_> = h.errors.lock().unwrap().iter().filter(|e| e.contains(unreachable)).cloned().collect();
+    assert_eq!(dial_errors.len(), 1, "{dial_errors:?}");
+    assert!(dial_errors[0].starts_with(&format!("dial {unreachable} failed")));
+}
+
+#[tokio::test]
+async fn fixed_listen_port_is_reused_across_restarts_and_never_falls_back() {
+    let port = std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let fixed = SocketAddr::from(([127, 0, 0, 1], port));
+    let disc = MockDiscovery::new_shared();
+
+    let new_service = |disc: MockDiscovery| {
+        let id = Ed25519Identity::generate();
+        SyncService::new(
+            id,
+            Arc::new(MemoryTrustStore::new()),
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            fixed,
+            "fixed".into(),
+            Arc::new(TestHandler::default()),
+        )
+        .unwrap()
+    };
+
+    let s = new_service(disc.clone_shared());
+    assert_eq!(s.listen_addr(), None);
+    s.start().await.unwrap();
+    let first = s.listen_addr().unwrap();
+    assert_eq!(first, fixed);
+
+    // A second service on the same port must fail loudly rather than pick another port.
+    let other = new_service(MockDiscovery::new_shared());
+    let err = other.start().await.unwrap_err();
+    assert!(err.to_string().contains(&format!("listen port {port} is already in use")), "{err}");
+    assert_eq!(other.listen_addr(), None);
+
+    s.stop().await;
+    assert_eq!(s.listen_addr(), None);
+
+    s.start().await.unwrap();
+    assert_eq!(s.listen_addr(), Some(first));
+    let advertised = openclipboard_core::Discovery::scan(&disc).await.unwrap();
+    assert!(advertised.iter().any(|p| p.addr == first.to_string()));
+    s.stop().await;
+}
+
+#[tokio::test]
+async fn untrusted_incoming_peer_reports_typed_handshake_failure() {
+    let shared = MockDiscovery::new_shared();
+
+    // The dialer (smaller peer_id) trusts the acceptor, which trusts nobody.
+    let id_a = Ed25519Identity::generate();
+    let id_b = Ed25519Identity::generate();
+    let (dialer_id, acceptor_id) = if id_a.peer_id() <= id_b.peer_id() {
+        (id_a, id_b)
+    } else {
+        (id_b, id_a)
+    };
+    let dialer_trust = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&dialer_id, &acceptor_id, &dialer_trust, "acceptor");
+
+    let acceptor_h = Arc::new(TestHandler::default());
+    let acceptor = SyncService::new(
+        acceptor_id,
+        Arc::new(MemoryTrustStore::new()),
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(shared.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "acceptor".into(),
+        acceptor_h.clone(),
+    )
+    .unwrap();
+    let dialer = SyncService::new(
+        dialer_id.clone(),
+        dialer_trust,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(shared.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dialer".into(),
+        Arc::new(TestHandler::default()),
+    )
+    .unwrap();
+
+    acceptor.start().await.unwrap();
+    dialer.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3) && acceptor_h.handshake_failures.lock().unwrap().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    dialer.stop().await;
+    acceptor.stop().await;
+
+    let failures = acceptor_h.handshake_failures.lock().unwrap();
+    assert!(
+        failures.first() == Some(&(dialer_id.peer_id().to_string(), HandshakeError::Untrusted)),
+        "failures={failures:?} errors={:?}",
+        acceptor_h.errors.lock().unwrap()
+    );
+    assert!(acceptor_h.connected.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn connected_peers_exchange_device_info_into_the_registry() {
+    let shared = MockDiscovery::new_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let service = |id: &Ed25519Identity, trust: &Arc<MemoryTrustStore>, name: &str| {
+        SyncService::new(
+            id.clone(),
+            trust.clone(),
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(shared.clone_shared()),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            name.into(),
+            Arc::new(TestHandler::default()),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, &trust1, "Pixel 8");
+    let s2 = service(&id2, &trust2, "Work Laptop");
+    s1.set_app_info("android", "1.4.0");
+    s2.set_app_info("linux", "1.3.2");
+    s1.peer_registry().load_from_trust(trust1.as_ref()).await.unwrap();
+    s2.peer_registry().load_from_trust(trust2.as_ref()).await.unwrap();
+
+    let id1_str = id1.peer_id().to_string();
+    let id2_str = id2.peer_id().to_string();
+    let before = s2.peer_registry().get(&id1_str).await.unwrap();
+    assert_eq!(before.platform, None);
+
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    loop {
+        let seen_by_2 = s2.peer_registry().get(&id1_str).await.unwrap();
+        let seen_by_1 = s1.peer_registry().get(&id2_str).await.unwrap();
+        if seen_by_2.app_version.is_some() && seen_by_1.app_version.is_some() {
+            assert_eq!(seen_by_2.device_name.as_deref(), Some("Pixel 8"));
+            assert_eq!(seen_by_2.platform.as_deref(), Some("android"));
+            assert_eq!(seen_by_2.app_version.as_deref(), Some("1.4.0"));
+            assert_eq!(seen_by_1.device_name.as_deref(), Some("Work Laptop"));
+            assert_eq!(seen_by_1.platform.as_deref(), Some("linux"));
+            assert_eq!(seen_by_1.app_version.as_deref(), Some("1.3.2"));
+            break;
+        }
+        assert!(start.elapsed() < std::time::Duration::from_secs(3), "device info not exchanged");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    s1.stop().await;
+    s2.stop().await;
+}
+
+#[tokio::test]
+async fn receive_only_peer_gets_clips_but_is_never_sent_ours() {
+    let shared = MockDiscovery::new_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "laptop");
+    trust_each_other(&id2, &id1, &trust2, "phone");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust: &Arc<MemoryTrustStore>, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust.clone(),
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(shared.clone_shared()),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let phone = service(&id1, &trust1, &h1);
+    let laptop = service(&id2, &trust2, &h2);
+
+    // The phone only receives from the laptop.
+    let laptop_id = id2.peer_id().to_string();
+    assert!(phone.set_sync_direction(&laptop_id, SyncDirection::ReceiveOnly).unwrap());
+    assert_eq!(phone.sync_direction(&laptop_id).unwrap(), Some(SyncDirection::ReceiveOnly));
+
+    phone.start().await.unwrap();
+    laptop.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3)
+        && (h1.connected.lock().unwrap().is_empty() || h2.connected.lock().unwrap().is_empty())
+    {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    phone.broadcast_clip_text("from phone".into()).await;
+    laptop.broadcast_clip_text("from laptop".into()).await;
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3) && h1.texts.lock().unwrap().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    // Give a stray "from phone" time to arrive as well.
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    phone.stop().await;
+    laptop.stop().await;
+
+    assert_eq!(*h1.texts.lock().unwrap(), vec![(laptop_id, "from laptop".to_string())]);
+    assert!(h2.texts.lock().unwrap().is_empty(), "laptop received {:?}", h2.texts.lock().unwrap());
+}
+
+#[tokio::test]
+async fn muted_peer_clips_are_dropped_while_others_pass() {
+    let shared = MockDiscovery::new_shared();
+
+    let desk = Ed25519Identity::generate();
+    let noisy = Ed25519Identity::generate();
+    let quiet = Ed25519Identity::generate();
+    let desk_trust = Arc::new(MemoryTrustStore::new());
+    let noisy_trust = Arc::new(MemoryTrustStore::new());
+    let quiet_trust = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&desk, &noisy, &desk_trust, "noisy");
+    trust_each_other(&desk, &quiet, &desk_trust, "quiet");
+    trust_each_other(&noisy, &desk, &noisy_trust, "desk");
+    trust_each_other(&quiet, &desk, &quiet_trust, "desk");
+
+    let service = |id: &Ed25519Identity, trust: &Arc<MemoryTrustStore>, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust.clone(),
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(shared.clone_shared()),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let hd = Arc::new(TestHandler::default());
+    let hn = Arc::new(TestHandler::default());
+    let hq = Arc::new(TestHandler::default());
+    let desk_svc = service(&desk, &desk_trust, &hd);
+    let noisy_svc = service(&noisy, &noisy_trust, &hn);
+    let quiet_svc = service(&quiet, &quiet_trust, &hq);
+
+    let noisy_id = noisy.peer_id().to_string();
+    let quiet_id = quiet.peer_id().to_string();
+    desk_svc.peer_registry().set_muted(&noisy_id, true).await;
+    assert!(desk_svc.peer_registry().is_muted(&noisy_id).await);
+
+    desk_svc.start().await.unwrap();
+    noisy_svc.start().await.unwrap();
+    quiet_svc.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3)
+        && (hd.connected.lock().unwrap().len() < 2
+            || hn.connected.lock().unwrap().is_empty()
+            || hq.connected.lock().unwrap().is_empty())
+    {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(hd.connected.lock().unwrap().len(), 2, "errors={:?}", hd.errors.lock().unwrap());
+
+    noisy_svc.broadcast_clip_text("spam".into()).await;
+    quiet_svc.broadcast_clip_text("hello".into()).await;
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3) && hd.texts.lock().unwrap().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    // Give a stray "spam" time to arrive as well.
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    desk_svc.stop().await;
+    noisy_svc.stop().await;
+    quiet_svc.stop().await;
+
+    assert_eq!(*hd.texts.lock().unwrap(), vec![(quiet_id.clone(), "hello".to_string())]);
+    let history = desk_svc.history().get_recent(10);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].source_peer, quiet_id);
+    assert!(desk_svc.history().get_for_peer(&noisy_id, 10).is_empty());
+}
+
+#[tokio::test]
+async fn service_listening_on_two_ports_accepts_on_both_and_advertises_both() {
+    use openclipboard_core::quic_transport::{make_insecure_client_endpoint, QuicTransport};
+    use openclipboard_core::{Discovery, Session, Transport};
+
+    let disc = MockDiscovery::new_shared();
+    let id = Ed25519Identity::generate();
+    let h = Arc::new(TestHandler::default());
+    let s = SyncService::new(
+        id.clone(),
+        Arc::new(MemoryTrustStore::new()),
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev".into(),
+        h.clone(),
+    )
+    .unwrap();
+    s.start_on(&[SocketAddr::from(([127, 0, 0, 1], 0)), SocketAddr::from(([127, 0, 0, 1], 0))])
+        .await
+        .unwrap();
+
+    let bound = s.listen_addrs();
+    assert_eq!(bound.len(), 2);
+    assert_ne!(bound[0], bound[1]);
+    let advertised = disc.scan().await.unwrap().into_iter().find(|p| p.peer_id == id.peer_id()).unwrap();
+    assert_eq!(advertised.addr, bound[0].to_string());
+    assert_eq!(advertised.alt_addrs, vec![bound[1].to_string()]);
+
+    // A stranger on each port ends up in the same pending-requests list.
+    let transport = QuicTransport::new(make_insecure_client_endpoint().unwrap());
+    let mut strangers = Vec::new();
+    for addr in &bound {
+        let stranger = Ed25519Identity::generate();
+        let conn = transport.connect(&addr.to_string()).await.unwrap();
+        let session = Session::new(conn, stranger.clone(), openclipboard_core::MockClipboard::new());
+        let _ = session.handshake().await;
+        strangers.push(stranger.peer_id().to_string());
+    }
+
+    let start = std::time::Instant::now();
+    while s.pending_requests().len() < 2 && start.elapsed() < std::time::Duration::from_secs(3) {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let mut pending: Vec<String> = s.pending_requests().into_iter().map(|r| r.peer_id).collect();
+    pending.sort();
+    strangers.sort();
+    assert_eq!(pending, strangers, "errors={:?}", h.errors.lock().unwrap());
+
+    s.stop().await;
+    assert!(s.listen_addrs().is_empty());
+}
+
+#[tokio::test]
+async fn registry_lists_every_address_a_peer_advertises() {
+    use openclipboard_core::{Discovery, PeerInfo};
+
+    let disc = MockDiscovery::new_shared();
+    let me = Ed25519Identity::generate();
+    let other = Ed25519Identity::generate();
+    let trust = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&me, &other, &trust, "laptop");
+
+    let h = Arc::new(TestHandler::default());
+    let s = SyncService::new(
+        me.clone(),
+        trust.clone(),
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev".into(),
+        h.clone(),
+    )
+    .unwrap();
+    s.peer_registry().load_from_trust(trust.as_ref()).await.unwrap();
+
+    // Nothing listens there; only the advertisement matters.
+    let other_id = other.peer_id().to_string();
+    let addresses = vec!["192.0.2.10:18455".to_string(), "198.51.100.7:18455".to_string()];
+    disc.advertise(PeerInfo {
+        peer_id: other_id.clone(),
+        name: "laptop".into(),
+        addr: addresses[0].clone(),
+        alt_addrs: vec![addresses[1].clone(), addresses[0].clone()],
+    })
+    .await
+    .unwrap();
+    s.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    let mut listed = Vec::new();
+    while start.elapsed() < std::time::Duration::from_secs(3) {
+        listed = s.peer_registry().get(&other_id).await.unwrap().addresses;
+        if !listed.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    s.stop().await;
+
+    assert_eq!(listed, addresses);
+}
+
+#[tokio::test]
+async fn snapshot_peers_and_history_stay_consistent_through_connect_and_disconnect() {
+    use openclipboard_core::{PeerStatus, SyncSnapshot};
+
+    let shared = MockDiscovery::new_shared();
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust: &Arc<MemoryTrustStore>, h: &Arc<TestHandler>| {
+        Arc::new(
+            SyncService::new(
+                id.clone(),
+                trust.clone(),
+                Arc::new(MemoryReplayProtector::new(1024)),
+                Arc::new(shared.clone_shared()),
+                SocketAddr::from(([127, 0, 0, 1], 0)),
+                "dev".into(),
+                h.clone(),
+            )
+            .unwrap(),
+        )
+    };
+    let s1 = service(&id1, &trust1, &h1);
+    let s2 = service(&id2, &trust2, &h2);
+    s1.peer_registry().load_from_trust(trust1.as_ref()).await.unwrap();
+
+    let check = |snap: &SyncSnapshot| {
+        let online: Vec<String> = snap
+            .peers
+            .iter()
+            .filter(|p| p.status == PeerStatus::Online)
+            .map(|p| p.peer_id.clone())
+            .collect();
+        assert_eq!(snap.connected, online);
+        assert_eq!(snap.history.len(), snap.history_len);
+        assert_eq!(snap.history_bytes, snap.history.iter().map(|e| e.content.len()).sum::<usize>());
+    };
+
+    // Poll snapshots for the whole connect / clip / disconnect cycle.
+    let watcher = {
+        let s1 = Arc::clone(&s1);
+        let done = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let done2 = Arc::clone(&done);
+        let task = tokio::spawn(async move {
+            let mut taken = 0;
+            while !done2.load(std::sync::atomic::Ordering::SeqCst) {
+                check(&s1.snapshot(usize::MAX).await);
+                taken += 1;
+                tokio::task::yield_now().await;
+            }
+            taken
+        });
+        (done, task)
+    };
+
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3)
+        && (h1.connected.lock().unwrap().is_empty() || h2.connected.lock().unwrap().is_empty())
+    {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    s2.broadcast_clip_text("hello".into()).await;
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3) && h1.texts.lock().unwrap().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let snap = s1.snapshot(10).await;
+    assert_eq!(snap.connected, vec![id2.peer_id().to_string()]);
+    assert_eq!(snap.history.len(), 1);
+    assert_eq!(snap.history[0].content, "hello");
+    assert_eq!(snap.history_bytes, 5);
+
+    s2.stop().await;
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3) && !s1.snapshot(0).await.connected.is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    watcher.0.store(true, std::sync::atomic::Ordering::SeqCst);
+    assert!(watcher.1.await.unwrap() > 0);
+    let snap = s1.snapshot(10).await;
+    check(&snap);
+    assert!(snap.connected.is_empty());
+    assert_eq!(snap.history_len, 1);
+    s1.stop().await;
+}
+
+#[tokio::test]
+async fn supervisor_reports_an_accept_loop_whose_listener_died() {
+    use openclipboard_core::quic_transport::{make_insecure_client_endpoint, QuicTransport};
+    use openclipboard_core::transport::memory_transport_pair;
+    use openclipboard_core::{box_listener, box_transport};
+
+    let service = |h: &Arc<TestHandler>| {
+        SyncService::new(
+            Ed25519Identity::generate(),
+            Arc::new(MemoryTrustStore::new()),
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(MockDiscovery::new_shared()),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let transport = || box_transport(QuicTransport::new(make_insecure_client_endpoint().unwrap()));
+
+    // Dropping the sender kills the listener: every accept now fails.
+    let h = Arc::new(TestHandler::default());
+    let s = service(&h);
+    let (tx, listener) = memory_transport_pair();
+    s.start_with_transport(box_listener(listener), "mem://dead".into(), transport()).await.unwrap();
+    drop(tx);
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3) && h.sync_stopped.lock().unwrap().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    s.stop().await;
+
+    let stopped = h.sync_stopped.lock().unwrap().clone();
+    assert_eq!(stopped.len(), 1, "{stopped:?}");
+    assert_eq!(stopped[0].0, "accept loop on mem://dead");
+    assert!(stopped[0].1.contains("listener closed"), "{}", stopped[0].1);
+    assert!(h.errors.lock().unwrap().iter().any(|e| e.starts_with("sync stopped: accept loop on mem://dead")));
+
+    // A plain stop is not reported.
+    let h = Arc::new(TestHandler::default());
+    let s = service(&h);
+    let (_tx, listener) = memory_transport_pair();
+    s.start_with_transport(box_listener(listener), "mem://ok".into(), transport()).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    s.stop().await;
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(h.sync_stopped.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn received_files_are_logged_with_their_metadata() {
+    use sha2::{Digest, Sha256};
+
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust, disc, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, trust1, disc1, &h1);
+    let s2 = service(&id2, trust2, disc2, &h2);
+
+    let download_dir = std::env::temp_dir().join(format!("oc-received-{}", rand::random::<u64>()));
+    s2.file_log().set_download_dir(download_dir.clone());
+
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while s1.snapshot(0).await.connected.is_empty() || s2.snapshot(0).await.connected.is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let sent = [("notes.txt", b"first file".to_vec()), ("photo.png", vec![7u8; 100_000])];
+    for (name, data) in &sent {
+        s1.send_file_to_peer(id2.peer_id(), name.to_string(), "application/octet-stream".into(), data.clone(), None)
+            .await
+            .unwrap();
+    }
+
+    let start = std::time::Instant::now();
+    while h2.files.lock().unwrap().len() < 2 && start.elapsed() < std::time::Duration::from_secs(3) {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let log = s2.received_files();
+
+    s1.stop().await;
+    s2.stop().await;
+
+    assert_eq!(log.len(), 2, "errors: {:?}", h2.errors.lock().unwrap());
+    // Newest first.
+    for (file, (name, data)) in log.iter().rev().zip(&sent) {
+        assert_eq!(file.peer_id, id1.peer_id().to_string());
+        assert_eq!(file.name, *name);
+        assert_eq!(file.size, data.len() as u64);
+        assert_eq!(file.hash, format!("{:x}", Sha256::digest(data)));
+        assert_eq!(std::path::Path::new(&file.path), download_dir.join(name));
+        assert_eq!(&std::fs::read(&file.path).unwrap(), data);
+        assert!(file.received_at_ms > 0);
+    }
+    let paths: Vec<_> = h2.files.lock().unwrap().iter().map(|(_, _, p)| p.clone()).collect();
+    assert_eq!(paths, log.iter().rev().map(|f| f.path.clone()).collect::<Vec<_>>());
+
+    let _ = std::fs::remove_dir_all(download_dir);
+}
+
+#[tokio::test]
+async fn file_sends_stream_from_disk_within_the_size_cap_and_sync_direction() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust, disc, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, trust1, disc1, &h1);
+    let s2 = service(&id2, trust2, disc2, &h2);
+
+    let dir = std::env::temp_dir().join(format!("oc-send-path-{}", rand::random::<u64>()));
+    std::fs::create_dir_all(&dir).unwrap();
+    s2.file_log().set_download_dir(dir.join("downloads"));
+    assert_eq!(s2.max_incoming_file_size(), Some(openclipboard_core::DEFAULT_MAX_RECEIVED_FILE_SIZE));
+
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+    let start = std::time::Instant::now();
+    while s1.snapshot(0).await.connected.is_empty() || s2.snapshot(0).await.connected.is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    // Many chunks' worth, read off disk as it goes.
+    let data: Vec<u8> = (0..1_000_000u32).map(|i| (i % 251) as u8).collect();
+    let path = dir.join("big.bin");
+    std::fs::write(&path, &data).unwrap();
+    let progress = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = Arc::clone(&progress);
+    s1.send_file_path_to_peer(id2.peer_id(), &path, "application/octet-stream".into(), Some(Arc::new(move |sent, _| seen.lock().unwrap().push(sent))))
+        .await
+        .unwrap();
+    assert_eq!(progress.lock().unwrap().last(), Some(&(data.len() as u64)));
+    let start = std::time::Instant::now();
+    while h2.files.lock().unwrap().is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "file never arrived: {:?}", h2.errors.lock().unwrap());
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let (_, name, saved) = h2.files.lock().unwrap()[0].clone();
+    assert_eq!(name, "big.bin");
+    assert_eq!(std::fs::read(saved).unwrap(), data);
+
+    // Past the receiver's cap, the offer is refused.
+    s2.set_max_incoming_file_size(Some(1000));
+    let err = s1
+        .send_file_to_peer(id2.peer_id(), "too-big.bin".into(), "application/octet-stream".into(), vec![0; 1001], None)
+        .await
+        .unwrap_err();
+    assert_eq!(openclipboard_core::FileRejected::of(&err).map(|r| r.reason.as_str()), Some(openclipboard_core::REJECT_TOO_LARGE));
+
+    // The receiver doesn't take anything from this sender.
+    s2.set_max_incoming_file_size(None);
+    assert!(s2.set_sync_direction(id1.peer_id(), SyncDirection::SendOnly).unwrap());
+    let err = s1
+        .send_file_to_peer(id2.peer_id(), "unwanted.txt".into(), "text/plain".into(), b"no".to_vec(), None)
+        .await
+        .unwrap_err();
+    assert_eq!(openclipboard_core::FileRejected::of(&err).map(|r| r.reason.as_str()), Some(openclipboard_core::REJECT_NOT_ACCEPTING));
+
+    // Nor does the sender send to that receiver.
+    assert!(s1.set_sync_direction(id2.peer_id(), SyncDirection::ReceiveOnly).unwrap());
+    assert!(s1.send_file_to_peer(id2.peer_id(), "held.txt".into(), "text/plain".into(), b"no".to_vec(), None).await.is_err());
+    assert_eq!(h2.files.lock().unwrap().len(), 1);
+
+    s1.stop().await;
+    s2.stop().await;
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn reconnect_within_disconnect_grace_is_not_reported() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust, disc, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, trust1, disc1, &h1);
+    let s2 = service(&id2, trust2, disc2, &h2);
+    let grace = std::time::Duration::from_millis(2000);
+    s1.set_disconnect_grace(grace);
+    s2.set_disconnect_grace(grace);
+
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let both_connected = || async { !s1.snapshot(0).await.connected.is_empty() && !s2.snapshot(0).await.connected.is_empty() };
+    let start = std::time::Instant::now();
+    while !both_connected().await {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let counts = |h: &TestHandler| (h.connected.lock().unwrap().len(), h.disconnected.lock().unwrap().len());
+    let (before1, before2) = (counts(&h1), counts(&h2));
+
+    // A blip: drop the session and let it come straight back.
+    let dropped_at = std::time::Instant::now();
+    assert!(s1.disconnect_peer(id2.peer_id()).await);
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    s1.resume_peer(id2.peer_id());
+    while !both_connected().await {
+        assert!(dropped_at.elapsed() < grace, "didn't reconnect within the grace period");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    // Past the grace period, neither side has seen the blip.
+    tokio::time::sleep(grace + std::time::Duration::from_millis(500) - dropped_at.elapsed().min(grace)).await;
+    assert_eq!(counts(&h1), before1);
+    assert_eq!(counts(&h2), before2);
+
+    // A disconnect that lasts is still reported, once the grace period is up.
+    assert!(s1.disconnect_peer(id2.peer_id()).await);
+    tokio::time::sleep(grace / 2).await;
+    assert_eq!(h1.disconnected.lock().unwrap().len(), before1.1);
+    let start = std::time::Instant::now();
+    while h1.disconnected.lock().unwrap().len() == before1.1 && start.elapsed() < grace * 2 {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert_eq!(h1.disconnected.lock().unwrap().len(), before1.1 + 1);
+    assert_eq!(h1.disconnected.lock().unwrap().last().map(String::as_str), Some(id2.peer_id()));
+
+    // A disconnect still held back when the service stops is reported by
+    // `stop`, and no timer fires after it.
+    s1.stop().await;
+    s2.stop().await;
+    let after_stop = counts(&h2);
+    tokio::time::sleep(grace + std::time::Duration::from_millis(500)).await;
+    assert_eq!(counts(&h2), after_stop);
+}
+
+#[tokio::test]
+async fn trust_records_sync_between_linked_devices_only() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+    let new_device = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "laptop");
+    trust_each_other(&id2, &id1, &trust2, "phone");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust, disc, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, trust1.clone(), disc1, &h1);
+    let s2 = service(&id2, trust2.clone(), disc2, &h2);
+
+    // Only the phone treats the laptop as its own device so far.
+    let linked_file = std::env::temp_dir().join(format!("oc-linked-{}.json", rand::random::<u64>()));
+    s1.set_linked_devices_file(Some(linked_file.clone())).await.unwrap();
+    s1.set_linked_device(id2.peer_id(), true).await.unwrap();
+    assert_eq!(s1.linked_devices().await, vec![id2.peer_id().to_string()]);
+
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while s1.snapshot(0).await.connected.is_empty() || s2.snapshot(0).await.connected.is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    // Pair a new device on the phone.
+    trust_each_other(&id1, &new_device, &trust1, "tablet");
+    let not_linked = |h: &TestHandler| h.errors.lock().unwrap().iter().filter(|e| e.contains("not a linked device")).count();
+    let before = not_linked(&h2);
+    assert_eq!(s1.sync_trust().await, 1);
+    let start = std::time::Instant::now();
+    while not_linked(&h2) == before {
+        assert!(start.elapsed() < std::time::Duration::from_secs(3), "trust sync never arrived");
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert!(!trust2.is_trusted(new_device.peer_id()).unwrap(), "unlinked peer's records were merged");
+
+    // Once the laptop links the phone back, the next round carries the tablet over.
+    s2.set_linked_device(id1.peer_id(), true).await.unwrap();
+    assert_eq!(s1.sync_trust().await, 1);
+    let start = std::time::Instant::now();
+    while !trust2.is_trusted(new_device.peer_id()).unwrap() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(3), "tablet never reached the laptop");
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let tablet = trust2.get(new_device.peer_id()).unwrap().unwrap();
+    assert_eq!(tablet.display_name, "tablet");
+    assert_eq!(tablet.identity_pk, new_device.public_key_bytes());
+    assert!(!trust2.is_trusted(id2.peer_id()).unwrap(), "a device never trusts itself");
+    assert!(s2.peer_registry().get(new_device.peer_id()).await.is_some());
+
+    // Removing the tablet on the phone removes it on the laptop too, rather
+    // than the laptop's copy bringing it back.
+    assert!(trust1.revoke(new_device.peer_id()).unwrap());
+    assert_eq!(s2.sync_trust().await, 1);
+    assert_eq!(s1.sync_trust().await, 1);
+    let start = std::time::Instant::now();
+    while trust2.is_trusted(new_device.peer_id()).unwrap() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(3), "revocation never reached the laptop");
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(!trust1.is_trusted(new_device.peer_id()).unwrap());
+    assert!(s2.peer_registry().get(new_device.peer_id()).await.is_none());
+
+    s1.stop().await;
+    s2.stop().await;
+
+    // The phone's linked devices outlive the service.
+    let reopened = service(&id1, trust1.clone(), MockDiscovery::new_shared(), &h1);
+    reopened.set_linked_devices_file(Some(linked_file.clone())).await.unwrap();
+    assert_eq!(reopened.linked_devices().await, vec![id2.peer_id().to_string()]);
+    let _ = std::fs::remove_file(linked_file);
+}
+
+#[tokio::test]
+async fn stop_with_drain_delivers_a_clip_queued_right_before_it() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust, disc, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, trust1, disc1, &h1);
+    let s2 = service(&id2, trust2, disc2, &h2);
+    s1.set_drain_on_stop(Some(std::time::Duration::from_secs(3)));
+
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while s1.snapshot(0).await.connected.is_empty() || s2.snapshot(0).await.connected.is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    s1.broadcast_clip_text("copied right before quitting".into()).await;
+    s1.stop().await;
+
+    // `stop` returned only after the peer acknowledged everything queued before it.
+    let texts = h2.texts.lock().unwrap().clone();
+    s2.stop().await;
+    assert_eq!(texts, vec![(id1.peer_id().to_string(), "copied right before quitting".to_string())]);
+}
+
+#[tokio::test]
+async fn dial_limit_caps_concurrent_handshakes_and_everyone_still_connects() {
+    use openclipboard_core::quic_transport::{make_insecure_client_endpoint, make_server_endpoint, QuicConnection, QuicListener, QuicTransport};
+    use openclipboard_core::transport::Transport;
+    use openclipboard_core::{box_listener, box_transport};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// QUIC dials that take a while and record how many overlap.
+    struct CountingTransport {
+        inner: QuicTransport,
+        in_flight: AtomicUsize,
+        max_in_flight: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for CountingTransport {
+        type Conn = QuicConnection;
+        async fn connect(&self, addr: &str) -> anyhow::Result<QuicConnection> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            let res = self.inner.connect(addr).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            res
+        }
+    }
+
+    const PEERS: usize = 8;
+    const LIMIT: usize = 3;
+
+    // The lowest peer id dials everyone else.
+    let mut ids: Vec<_> = (0..=PEERS).map(|_| Ed25519Identity::generate()).collect();
+    ids.sort_by_key(|id| id.peer_id().to_string());
+    let hub_id = ids.remove(0);
+
+    let disc = MockDiscovery::new_shared();
+    let service = |id: &Ed25519Identity, trust: Arc<MemoryTrustStore>, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc.clone_shared()),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+
+    let hub_trust = Arc::new(MemoryTrustStore::new());
+    let mut spokes = Vec::new();
+    for id in &ids {
+        trust_each_other(&hub_id, id, &hub_trust, "spoke");
+        let trust = Arc::new(MemoryTrustStore::new());
+        trust_each_other(id, &hub_id, &trust, "hub");
+        let s = service(id, trust, &Arc::new(TestHandler::default()));
+        s.start().await.unwrap();
+        spokes.push(s);
+    }
+
+    let hub_handler = Arc::new(TestHandler::default());
+    let hub = service(&hub_id, hub_trust, &hub_handler);
+    hub.set_max_concurrent_dials(Some(LIMIT));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let transport = CountingTransport {
+        inner: QuicTransport::new(make_insecure_client_endpoint().unwrap()),
+        in_flight: AtomicUsize::new(0),
+        max_in_flight: Arc::clone(&max_in_flight),
+    };
+    let (endpoint, _cert) = make_server_endpoint(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+    let listener = QuicListener::new(endpoint);
+    let addr = listener.local_addr().unwrap().to_string();
+    hub.start_with_transport(box_listener(listener), addr, box_transport(transport)).await.unwrap();
+
+    let start = std::time::Instant::now();
+    while hub.snapshot(0).await.connected.len() < PEERS {
+        assert!(start.elapsed() < std::time::Duration::from_secs(10), "only {:?} connected", hub.snapshot(0).await.connected);
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let max = max_in_flight.load(Ordering::SeqCst);
+    assert!((1..=LIMIT).contains(&max), "{max} dials ran at once");
+
+    hub.stop().await;
+    for s in spokes {
+        s.stop().await;
+    }
+}
+
+#[tokio::test]
+async fn applied_clips_are_acked_to_the_sender_and_dropped_ones_are_not() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust, disc, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, trust1, disc1, &h1);
+    let s2 = service(&id2, trust2, disc2, &h2);
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while s1.snapshot(0).await.connected.is_empty() || s2.snapshot(0).await.connected.is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let peer1 = id1.peer_id().to_string();
+    let peer2 = id2.peer_id().to_string();
+    let wait_delivered = |n: usize| {
+        let h1 = Arc::clone(&h1);
+        async move {
+            let start = std::time::Instant::now();
+            while h1.delivered.lock().unwrap().len() < n {
+                assert!(start.elapsed() < std::time::Duration::from_secs(3), "no ack; errors={:?}", h1.errors.lock().unwrap());
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        }
+    };
+
+    s1.broadcast_clip_text("applied".into()).await;
+    wait_delivered(1).await;
+    assert_eq!(h1.delivered.lock().unwrap()[0].0, peer2);
+
+    // Muted on the receiving side: dropped, so never acked. The clip after it
+    // is, and the session is ordered, so a stray ack would have come first.
+    s2.peer_registry().set_muted(&peer1, true).await;
+    s1.broadcast_clip_text("dropped".into()).await;
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    s2.peer_registry().set_muted(&peer1, false).await;
+    s1.broadcast_clip_text("applied again".into()).await;
+    wait_delivered(2).await;
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    s1.stop().await;
+    s2.stop().await;
+
+    let delivered = h1.delivered.lock().unwrap().clone();
+    assert_eq!(delivered.len(), 2, "{delivered:?}");
+    assert!(delivered.iter().all(|(peer, _)| *peer == peer2));
+    assert!(delivered[0].1 <= delivered[1].1);
+    let texts: Vec<_> = h2.texts.lock().unwrap().iter().map(|(_, t)| t.clone()).collect();
+    assert_eq!(texts, ["applied", "applied again"]);
+    // The receiver sent nothing, so it was confirmed nothing.
+    assert!(h2.delivered.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn retry_budget_gives_up_on_an_unreachable_peer_until_revived() {
+    use openclipboard_core::quic_transport::{make_server_endpoint, QuicConnection, QuicListener};
+    use openclipboard_core::transport::Transport;
+    use openclipboard_core::{box_listener, box_transport, Discovery, PeerInfo, PeerStatus, RetryBudget};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Every dial fails, as for a decommissioned device.
+    struct DeadTransport(Arc<AtomicUsize>);
+
+    #[async_trait::async_trait]
+    impl Transport for DeadTransport {
+        type Conn = QuicConnection;
+        async fn connect(&self, addr: &str) -> anyhow::Result<QuicConnection> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            anyhow::bail!("no route to {addr}")
+        }
+    }
+
+    // The lower peer id dials, so that's us.
+    let mut ids = [Ed25519Identity::generate(), Ed25519Identity::generate()];
+    ids.sort_by_key(|id| id.peer_id().to_string());
+    let [me, gone] = ids;
+    let gone_id = gone.peer_id().to_string();
+
+    let disc = MockDiscovery::new_shared();
+    let gone_disc = disc.clone_shared();
+    let gone_info = PeerInfo { peer_id: gone_id.clone(), name: "gone".into(), addr: "127.0.0.1:9".into(), alt_addrs: Vec::new() };
+    gone_disc.start_discovery(gone_info.clone()).await.unwrap();
+
+    let trust = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&me, &gone, &trust, "gone");
+    let h = Arc::new(TestHandler::default());
+    let svc = SyncService::new(
+        me.clone(),
+        trust.clone(),
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "me".into(),
+        h.clone(),
+    )
+    .unwrap();
+    svc.peer_registry().load_from_trust(trust.as_ref()).await.unwrap();
+    svc.set_retry_budget(RetryBudget { max_attempts: Some(3), max_duration: None });
+
+    let dials = Arc::new(AtomicUsize::new(0));
+    let (endpoint, _cert) = make_server_endpoint(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+    let listener = QuicListener::new(endpoint);
+    let addr = listener.local_addr().unwrap().to_string();
+    svc.start_with_transport(box_listener(listener), addr, box_transport(DeadTransport(Arc::clone(&dials)))).await.unwrap();
+
+    let given_up = || async { svc.unreachable_peers() == [gone_id.clone()] };
+    let wait_given_up = || async {
+        let start = std::time::Instant::now();
+        while !given_up().await {
+            assert!(start.elapsed() < std::time::Duration::from_secs(5), "never gave up after {} dials", dials.load(Ordering::SeqCst));
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+    };
+
+    wait_given_up().await;
+    assert_eq!(svc.peer_registry().get(&gone_id).await.unwrap().status, PeerStatus::Unreachable);
+    assert!(h.errors.lock().unwrap().iter().any(|e| e.starts_with(&format!("giving up on {gone_id} after 3 failed attempts"))));
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    assert_eq!(dials.load(Ordering::SeqCst), 3, "kept dialing after giving up");
+
+    // `reconnect_now` spends a fresh budget.
+    assert!(svc.reconnect_now(&gone_id).await);
+    assert_ne!(svc.peer_registry().get(&gone_id).await.unwrap().status, PeerStatus::Unreachable);
+    wait_given_up().await;
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert_eq!(dials.load(Ordering::SeqCst), 6);
+
+    // So does dropping out of discovery and coming back.
+    gone_disc.stop_discovery().await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(700)).await;
+    assert_eq!(dials.load(Ordering::SeqCst), 6);
+    gone_disc.start_discovery(gone_info).await.unwrap();
+    let start = std::time::Instant::now();
+    while dials.load(Ordering::SeqCst) < 9 {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "rediscovery didn't revive the peer");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    wait_given_up().await;
+
+    svc.stop().await;
+}
+
+#[tokio::test]
+async fn offered_file_hash_is_checked_and_known_content_is_not_resent() {
+    use openclipboard_core::quic_transport::{make_insecure_client_endpoint, QuicTransport};
+    use openclipboard_core::{Discovery, Message, Session, Transport};
+    use sha2::{Digest, Sha256};
+
+    let disc = MockDiscovery::new_shared();
+    let id1 = Ed25519Identity::generate();
+    // The service only keeps inbound sessions from peers that sort before it.
+    let sender = std::iter::repeat_with(Ed25519Identity::generate).find(|s| s.peer_id() < id1.peer_id()).unwrap();
+    let other = std::iter::repeat_with(Ed25519Identity::generate).find(|s| s.peer_id() < id1.peer_id()).unwrap();
+    let trust = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &sender, &trust, "sender");
+    trust_each_other(&id1, &other, &trust, "other");
+
+    let h1 = Arc::new(TestHandler::default());
+    let s1 = SyncService::new(
+        id1.clone(),
+        trust,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev1".into(),
+        h1.clone(),
+    ).unwrap();
+    s1.file_log().set_download_dir(std::env::temp_dir().join(format!("oc-offer-hash-{}", rand::random::<u64>())));
+    s1.start().await.unwrap();
+
+    let addr = disc.scan().await.unwrap().into_iter().find(|p| p.peer_id == id1.peer_id()).unwrap().addr;
+    let transport = QuicTransport::new(make_insecure_client_endpoint().unwrap());
+    let session = Session::new(transport.connect(&addr).await.unwrap(), sender, openclipboard_core::MockClipboard::new());
+    session.handshake().await.unwrap();
+    let file_reply = || async {
+        loop {
+            match session.recv_message().await.unwrap() {
+                m @ (Message::FileAccept { .. } | Message::FileReject { .. }) => return m,
+                _ => continue,
+            }
+        }
+    };
+
+    let promised = b"promised".to_vec();
+    let hash = format!("{:x}", Sha256::digest(&promised));
+
+    // The offer's hash and the bytes sent disagree: the transfer is dropped
+    // as soon as the chunk completing it arrives, and the sender told to stop.
+    session.send_file_offer_with_hash("f1", "a.txt", 8, "text/plain", Some(&hash)).await.unwrap();
+    assert!(matches!(file_reply().await, Message::FileAccept { .. }));
+    session.send_file_chunk("f1", 0, b"tampered").await.unwrap();
+    let Message::FileReject { file_id, reason } = file_reply().await else { panic!("expected a reject") };
+    assert_eq!((file_id.as_str(), reason.as_str()), ("f1", openclipboard_core::REJECT_CORRUPTED));
+    session.send_file_done("f1", &hash).await.unwrap();
+
+    // The same content, honestly sent, arrives.
+    session.send_file_offer_with_hash("f2", "a.txt", 8, "text/plain", Some(&hash)).await.unwrap();
+    assert!(matches!(file_reply().await, Message::FileAccept { .. }));
+    session.send_file_chunk("f2", 0, &promised).await.unwrap();
+    session.send_file_done("f2", &hash).await.unwrap();
+
+    let start = std::time::Instant::now();
+    while h1.files.lock().unwrap().is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(3), "file never arrived");
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let errors = h1.errors.lock().unwrap().clone();
+    assert!(errors.iter().any(|e| e.contains("doesn't match the hash it was offered with")), "errors: {errors:?}");
+    let log = s1.received_files();
+    assert_eq!(log.len(), 1);
+    assert_eq!(std::fs::read(&log[0].path).unwrap(), promised);
+
+    // Offered again, it is already here: no transfer, and the existing copy is reported.
+    session.send_file_offer_with_hash("f3", "copy.txt", 8, "text/plain", Some(&hash)).await.unwrap();
+    let Message::FileReject { reason, .. } = file_reply().await else { panic!("expected a reject") };
+    assert_eq!(reason, openclipboard_core::REJECT_ALREADY_RECEIVED);
+    let files = h1.files.lock().unwrap().clone();
+    assert_eq!(files.len(), 2);
+    assert_eq!((files[1].1.as_str(), files[1].2.as_str()), ("copy.txt", log[0].path.as_str()));
+    assert_eq!(s1.received_files().len(), 1);
+
+    // Another peer offering the same content learns nothing: it sends it in full.
+    let other_session = Session::new(transport.connect(&addr).await.unwrap(), other, openclipboard_core::MockClipboard::new());
+    other_session.handshake().await.unwrap();
+    other_session.send_file_offer_with_hash("g1", "mine.txt", 8, "text/plain", Some(&hash)).await.unwrap();
+    loop {
+        match other_session.recv_message().await.unwrap() {
+            Message::FileAccept { file_id } => break assert_eq!(file_id, "g1"),
+            Message::FileReject { reason, .. } => panic!("other peer's offer rejected: {reason}"),
+            _ => continue,
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(std::path::Path::new(&log[0].path).parent().unwrap());
+    s1.stop().await;
+}
+
+/// A clipboard that trims what it stores, like some platform clipboards.
+#[derive(Default)]
+struct TrimmingClipboard(Mutex<Option<String>>);
+
+impl openclipboard_core::ClipboardProvider for TrimmingClipboard {
+    fn read(&self) -> anyhow::Result<openclipboard_core::ClipboardContent> {
+        Ok(match self.0.lock().unwrap().clone() {
+            Some(text) => openclipboard_core::ClipboardContent::Text(text),
+            None => openclipboard_core::ClipboardContent::Empty,
+        })
+    }
+    fn write(&self, content: openclipboard_core::ClipboardContent) -> anyhow::Result<()> {
+        if let openclipboard_core::ClipboardContent::Text(text) = content {
+            *self.0.lock().unwrap() = Some(text.trim().to_string());
+        }
+        Ok(())
+    }
+    fn on_change(&self, _: Box<dyn Fn(openclipboard_core::ClipboardContent) + Send + Sync>) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Applies received clips to its clipboard, as a mesh-mode app does.
+struct WriteBackHandler {
+    inner: Arc<TestHandler>,
+    clipboard: Arc<TrimmingClipboard>,
+}
+
+impl SyncHandler for WriteBackHandler {
+    fn on_clipboard_text(&self, peer_id: String, text: String, ts_ms: u64) {
+        use openclipboard_core::ClipboardProvider;
+        let _ = self.clipboard.write(openclipboard_core::ClipboardContent::Text(text.clone()));
+        self.inner.on_clipboard_text(peer_id, text, ts_ms);
+    }
+    fn on_peer_connected(&self, peer_id: String) {
+        self.inner.on_peer_connected(peer_id);
+    }
+    fn on_peer_disconnected(&self, peer_id: String) {
+        self.inner.on_peer_disconnected(peer_id);
+    }
+    fn on_error(&self, message: String) {
+        self.inner.on_error(message);
+    }
+}
+
+#[tokio::test]
+async fn normalized_write_back_is_not_rebroadcast() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let handlers = [Arc::new(TestHandler::default()), Arc::new(TestHandler::default())];
+    let clipboards = [Arc::new(TrimmingClipboard::default()), Arc::new(TrimmingClipboard::default())];
+    let service = |id: &Ed25519Identity, trust, disc, i: usize| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            format!("dev{i}"),
+            Arc::new(WriteBackHandler { inner: handlers[i].clone(), clipboard: clipboards[i].clone() }),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, trust1, disc1, 0);
+    let s2 = service(&id2, trust2, disc2, 1);
+    let poll = std::time::Duration::from_millis(20);
+    s1.start_mesh(clipboards[0].clone(), poll).await.unwrap();
+    s2.start_mesh(clipboards[1].clone(), poll).await.unwrap();
+
+    let start = std::time::Instant::now();
+    while s1.snapshot(0).await.connected.is_empty() || s2.snapshot(0).await.connected.is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    // Written by the "user" directly, so it keeps its padding on this side.
+    *clipboards[0].0.lock().unwrap() = Some("  padded clip\n".into());
+    let start = std::time::Instant::now();
+    while handlers[1].texts.lock().unwrap().is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(3), "clip never arrived");
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(clipboards[1].0.lock().unwrap().as_deref(), Some("padded clip"));
+
+    // Several polls later, the trimmed read-back still hasn't gone back out.
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert!(handlers[0].texts.lock().unwrap().is_empty(), "rebroadcast: {:?}", handlers[0].texts.lock().unwrap());
+    assert!(s2.history().get_recent(10).iter().all(|e| e.source_peer != "local"));
+
+    s1.stop().await;
+    s2.stop().await;
+}
+
+#[tokio::test]
+async fn quiet_hours_pause_broadcasts_and_optionally_incoming_clips() {
+    use openclipboard_core::{MockClock, QuietHours, QuietRange};
+
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let handlers = [Arc::new(TestHandler::default()), Arc::new(TestHandler::default())];
+    let service = |id: &Ed25519Identity, trust, disc, i: usize| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            format!("dev{i}"),
+            handlers[i].clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, trust1, disc1, 0);
+    let s2 = service(&id2, trust2, disc2, 1);
+
+    let at = |h| chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(h, 0, 0).unwrap();
+    let night = QuietHours {
+        ranges: vec![QuietRange::new(chrono::NaiveTime::from_hms_opt(22, 0, 0).unwrap(), chrono::NaiveTime::from_hms_opt(7, 0, 0).unwrap())],
+        pause_incoming: false,
+    };
+    let clock1 = Arc::new(MockClock::new(at(23)));
+    s1.set_clock(clock1.clone());
+    s1.set_quiet_hours(night.clone());
+    assert!(s1.is_quiet());
+
+    let clipboard = Arc::new(TrimmingClipboard::default());
+    let poll = std::time::Duration::from_millis(20);
+    s1.start_mesh(clipboard.clone(), poll).await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while s1.snapshot(0).await.connected.is_empty() || s2.snapshot(0).await.connected.is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let wait_for_text = |text: &'static str| {
+        let handler = handlers[1].clone();
+        async move {
+            let start = std::time::Instant::now();
+            while !handler.texts.lock().unwrap().iter().any(|(_, t)| t == text) {
+                assert!(start.elapsed() < std::time::Duration::from_secs(3), "{text} never arrived");
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        }
+    };
+
+    // Inside quiet hours: kept locally, not sent.
+    *clipboard.0.lock().unwrap() = Some("copied at night".into());
+    s1.broadcast_clip_text("sent at night".into()).await;
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert!(handlers[1].texts.lock().unwrap().is_empty(), "sent during quiet hours: {:?}", handlers[1].texts.lock().unwrap());
+    assert!(s1.history().get_recent(10).iter().any(|e| e.content == "copied at night" && e.source_peer == "local"));
+
+    // Morning: broadcasts resume without a restart.
+    clock1.set(at(8));
+    assert!(!s1.is_quiet());
+    *clipboard.0.lock().unwrap() = Some("copied in the morning".into());
+    wait_for_text("copied in the morning").await;
+
+    // The receiver's own quiet hours only drop incoming clips when asked to.
+    let clock2 = Arc::new(MockClock::new(at(23)));
+    s2.set_clock(clock2.clone());
+    s2.set_quiet_hours(night.clone());
+    s1.broadcast_clip_text("received at night".into()).await;
+    wait_for_text("received at night").await;
+
+    s2.set_quiet_hours(QuietHours { pause_incoming: true, ..night });
+    s1.broadcast_clip_text("dropped at night".into()).await;
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert!(!handlers[1].texts.lock().unwrap().iter().any(|(_, t)| t == "dropped at night"));
+
+    clock2.advance(chrono::Duration::hours(9));
+    s1.broadcast_clip_text("received in the morning".into()).await;
+    wait_for_text("received in the morning").await;
+
+    s1.stop().await;
+    s2.stop().await;
+}
+
+#[tokio::test]
+async fn handshake_failure_is_kept_in_recent_errors_with_its_category() {
+    let shared = MockDiscovery::new_shared();
+
+    // As above: the dialer trusts the acceptor, which trusts nobody.
+    let id_a = Ed25519Identity::generate();
+    let id_b = Ed25519Identity::generate();
+    let (dialer_id, acceptor_id) = if id_a.peer_id() <= id_b.peer_id() {
+        (id_a, id_b)
+    } else {
+        (id_b, id_a)
+    };
+    let dialer_trust = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&dialer_id, &acceptor_id, &dialer_trust, "acceptor");
+
+    let acceptor_h = Arc::new(TestHandler::default());
+    let acceptor = SyncService::new(
+        acceptor_id,
+        Arc::new(MemoryTrustStore::new()),
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(shared.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "acceptor".into(),
+        acceptor_h.clone(),
+    )
+    .unwrap();
+    let dialer = SyncService::new(
+        dialer_id.clone(),
+        dialer_trust,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(shared.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dialer".into(),
+        Arc::new(TestHandler::default()),
+    )
+    .unwrap();
+    assert!(acceptor.recent_errors(10).is_empty());
+
+    acceptor.start().await.unwrap();
+    dialer.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3) && acceptor.recent_errors(10).is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    dialer.stop().await;
+    acceptor.stop().await;
+
+    let errors = acceptor.recent_errors(10);
+    let entry = errors.first().expect("no error recorded");
+    assert_eq!(entry.category, ErrorCategory::Handshake);
+    assert_eq!(entry.peer_id.as_deref(), Some(dialer_id.peer_id()));
+    assert!(entry.timestamp_ms > 0);
+    // The handler still gets the plain message.
+    assert!(acceptor_h.errors.lock().unwrap().contains(&entry.message), "{entry:?}");
+    assert!(acceptor.recent_errors(0).is_empty());
+}
+
+#[tokio::test]
+async fn keepalive_drops_a_peer_that_stops_answering_pings() {
+    use openclipboard_core::quic_transport::{make_insecure_client_endpoint, QuicTransport};
+    use openclipboard_core::{Discovery, Keepalive, Session, Transport};
+
+    let disc = MockDiscovery::new_shared();
+
+    // The stalled peer dials, so it must sort first for its session to be kept.
+    let id_a = Ed25519Identity::generate();
+    let id_b = Ed25519Identity::generate();
+    let (stalled, id1) = if id_a.peer_id() < id_b.peer_id() { (id_a, id_b) } else { (id_b, id_a) };
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &stalled, &trust1, "stalled");
+
+    let h1 = Arc::new(TestHandler::default());
+    let s1 = SyncService::new(
+        id1.clone(),
+        trust1,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev1".into(),
+        h1.clone(),
+    ).unwrap();
+    assert_eq!(s1.keepalive(), Some(Keepalive::default()));
+    let keepalive = Keepalive {
+        interval: std::time::Duration::from_millis(200),
+        timeout: std::time::Duration::from_millis(300),
+    };
+    s1.set_keepalive(Some(keepalive));
+    assert_eq!(s1.keepalive(), Some(keepalive));
+    s1.start().await.unwrap();
+
+    let addr = disc.scan().await.unwrap()
+        .into_iter()
+        .find(|p| p.peer_id == id1.peer_id())
+        .unwrap()
+        .addr;
+
+    // Handshake, then never read again: pings go unanswered.
+    let transport = QuicTransport::new(make_insecure_client_endpoint().unwrap());
+    let conn = transport.connect(&addr).await.unwrap();
+    let session = Session::new(conn, stalled.clone(), openclipboard_core::MockClipboard::new());
+    session.handshake().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while h1.connected.lock().unwrap().is_empty() && start.elapsed() < std::time::Duration::from_secs(2) {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(*h1.connected.lock().unwrap(), vec![stalled.peer_id().to_string()], "errors={:?}", h1.errors.lock().unwrap());
+    let connected_at = std::time::Instant::now();
+
+    while h1.disconnected.lock().unwrap().is_empty() && connected_at.elapsed() < std::time::Duration::from_secs(2) {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let window = keepalive.interval + keepalive.timeout;
+    assert_eq!(*h1.disconnected.lock().unwrap(), vec![stalled.peer_id().to_string()]);
+    assert!(connected_at.elapsed() < window + std::time::Duration::from_millis(500), "took {:?}", connected_at.elapsed());
+    assert!(h1.errors.lock().unwrap().iter().any(|e| e.contains("no pong")), "errors={:?}", h1.errors.lock().unwrap());
+
+    drop(session);
+    s1.stop().await;
+}
+
+#[tokio::test]
+async fn keepalive_leaves_peers_that_answer_connected() {
+    let disc = MockDiscovery::new_shared();
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let services: Vec<_> = [(id1, trust1, h1.clone()), (id2, trust2, h2.clone())]
+        .into_iter()
+        .map(|(id, trust, h)| {
+            let s = SyncService::new(
+                id,
+                trust,
+                Arc::new(MemoryReplayProtector::new(1024)),
+                Arc::new(disc.clone_shared()),
+                SocketAddr::from(([127, 0, 0, 1], 0)),
+                "dev".into(),
+                h,
+            )
+            .unwrap();
+            s.set_keepalive(Some(openclipboard_core::Keepalive {
+                interval: std::time::Duration::from_millis(50),
+                timeout: std::time::Duration::from_millis(200),
+            }));
+            s
+        })
+        .collect();
+    for s in &services {
+        s.start().await.unwrap();
+    }
+
+    let start = std::time::Instant::now();
+    while (h1.connected.lock().unwrap().is_empty() || h2.connected.lock().unwrap().is_empty())
+        && start.elapsed() < std::time::Duration::from_secs(2)
+    {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    // Many ping rounds.
+    tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+
+    for h in [&h1, &h2] {
+        assert_eq!(h.connected.lock().unwrap().len(), 1);
+        assert!(h.disconnected.lock().unwrap().is_empty(), "errors={:?}", h.errors.lock().unwrap());
+    }
+    for s in &services {
+        s.stop().await;
+    }
+}
+
+#[tokio::test]
+async fn one_sided_trust_is_flagged_to_the_trusting_side() {
+    let shared = MockDiscovery::new_shared();
+
+    // The dialer (smaller peer_id) trusts the acceptor, which trusts nobody.
+    let id_a = Ed25519Identity::generate();
+    let id_b = Ed25519Identity::generate();
+    let (dialer_id, acceptor_id) = if id_a.peer_id() <= id_b.peer_id() {
+        (id_a, id_b)
+    } else {
+        (id_b, id_a)
+    };
+    let dialer_trust = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&dialer_id, &acceptor_id, &dialer_trust, "acceptor");
+
+    let acceptor = SyncService::new(
+        acceptor_id.clone(),
+        Arc::new(MemoryTrustStore::new()),
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(shared.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "acceptor".into(),
+        Arc::new(TestHandler::default()),
+    )
+    .unwrap();
+    let dialer_h = Arc::new(TestHandler::default());
+    let dialer = SyncService::new(
+        dialer_id,
+        dialer_trust,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(shared.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dialer".into(),
+        dialer_h.clone(),
+    )
+    .unwrap();
+
+    acceptor.start().await.unwrap();
+    dialer.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(3) && dialer_h.handshake_failures.lock().unwrap().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    dialer.stop().await;
+    acceptor.stop().await;
+
+    let failures = dialer_h.handshake_failures.lock().unwrap();
+    assert_eq!(
+        failures.first(),
+        Some(&(acceptor_id.peer_id().to_string(), HandshakeError::NotTrustedByPeer)),
+        "errors={:?}",
+        dialer_h.errors.lock().unwrap()
+    );
+    // The session never counts as established on the trusting side.
+    assert!(dialer_h.connected.lock().unwrap().is_empty());
+    let errors = dialer.recent_errors(10);
+    assert!(errors.iter().any(|e| e.category == ErrorCategory::Handshake && e.message.contains("does not trust this device")), "{errors:?}");
+}
+
+#[tokio::test]
+async fn received_clips_are_still_in_history_after_a_restart_with_a_history_file() {
+    let shared = MockDiscovery::new_shared();
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+    let history_path = std::env::temp_dir().join(format!("oc-history-{}", rand::random::<u64>())).join("history.bin");
+
+    let service = |id: &Ed25519Identity, trust: &Arc<MemoryTrustStore>, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust.clone(),
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(shared.clone_shared()),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let s1 = service(&id1, &trust1, &h1);
+    let s2 = service(&id2, &trust2, &h2).with_history_file(&history_path).unwrap();
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while h1.connected.lock().unwrap().is_empty() || h2.connected.lock().unwrap().is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    s1.broadcast_clip_text("kept across restarts".into()).await;
+    while s2.history().len() == 0 {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "clip never arrived");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    s1.stop().await;
+    s2.stop().await;
+    drop(s2);
+
+    let restarted = SyncService::with_options(
+        id2.clone(),
+        trust2.clone(),
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(shared.clone_shared()),
+        Arc::new(TestHandler::default()),
+        openclipboard_core::SyncServiceOptions {
+            local_listen: SocketAddr::from(([127, 0, 0, 1], 0)),
+            device_name: "dev".into(),
+            history_file: Some(history_path.clone()),
+        },
+    )
+    .unwrap();
+    let entries = restarted.history().get_recent(10);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].content, "kept across restarts");
+    assert_eq!(entries[0].source_peer, id1.peer_id());
+
+    // Without a history file a restart starts empty.
+    assert_eq!(service(&id2, &trust2, &Arc::new(TestHandler::default())).history().len(), 0);
+
+    let _ = std::fs::remove_dir_all(history_path.parent().unwrap());
+}
+
+#[tokio::test]
+async fn undecodable_image_is_reported_and_the_session_keeps_going() {
+    use openclipboard_core::quic_transport::{make_insecure_client_endpoint, QuicTransport};
+    use openclipboard_core::{Discovery, Message, Session, Transport};
+
+    let disc = MockDiscovery::new_shared();
+    let id1 = Ed25519Identity::generate();
+    // The service only keeps inbound sessions from peers that sort before it.
+    let sender = std::iter::repeat_with(Ed25519Identity::generate).find(|s| s.peer_id() < id1.peer_id()).unwrap();
+    let other = std::iter::repeat_with(Ed25519Identity::generate).find(|s| s.peer_id() < id1.peer_id()).unwrap();
+    let trust = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &sender, &trust, "sender");
+    trust_each_other(&id1, &other, &trust, "other");
+
+    let h1 = Arc::new(TestHandler::default());
+    let s1 = SyncService::new(
+        id1.clone(),
+        trust,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(disc.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev1".into(),
+        h1.clone(),
+    ).unwrap();
+    s1.start().await.unwrap();
+
+    let addr = disc.scan().await.unwrap().into_iter().find(|p| p.peer_id == id1.peer_id()).unwrap().addr;
+    let transport = QuicTransport::new(make_insecure_client_endpoint().unwrap());
+    let session = Session::new(transport.connect(&addr).await.unwrap(), sender.clone(), openclipboard_core::MockClipboard::new());
+    session.handshake().await.unwrap();
+
+    let image = |bytes_b64: &str| Message::ClipImage { mime: "image/png".into(), width: 1, height: 1, bytes_b64: bytes_b64.into(), ts_ms: 1 };
+    session.enqueue(image("not base64!"), None);
+    session.enqueue(image("iVBORw=="), None);
+    session.enqueue_clip_text_at("after the images".into(), 2);
+    tokio::select! {
+        res = session.run_send_queue() => panic!("send queue ended: {res:?}"),
+        () = async {
+            let start = std::time::Instant::now();
+            while h1.texts.lock().unwrap().is_empty() {
+                assert!(start.elapsed() < std::time::Duration::from_secs(3), "session died; errors: {:?}", h1.errors.lock().unwrap());
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        } => {}
+    }
+
+    assert_eq!(*h1.images.lock().unwrap(), vec![(sender.peer_id().to_string(), "image/png".to_string(), vec![0x89, 0x50, 0x4e, 0x47])]);
+    let errors = h1.errors.lock().unwrap().clone();
+    assert!(errors.iter().any(|e| e.contains("bad base64")), "errors: {errors:?}");
+    assert!(h1.disconnected.lock().unwrap().is_empty());
+    assert_eq!(s1.history().get_recent(10).len(), 2);
+
+    s1.stop().await;
+}
+
+#[tokio::test]
+async fn bind_failures_from_the_endpoint_factory_surface_as_typed_errors() {
+    use openclipboard_core::quic_transport::ListenError;
+
+    let fixed = SocketAddr::from(([127, 0, 0, 1], 4242));
+    let service = SyncService::new(
+        Ed25519Identity::generate(),
+        Arc::new(MemoryTrustStore::new()),
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(MockDiscovery::new_shared()),
+        fixed,
+        "bind".into(),
+        Arc::new(TestHandler::default()),
+    )
+    .unwrap();
+
+    // Nothing is bound: the factory fails the way a taken port does.
+    service.set_endpoint_factory(Arc::new(|_| Err(std::io::Error::from(std::io::ErrorKind::AddrInUse).into())));
+    let err = service.start().await.unwrap_err();
+    assert_eq!(ListenError::of(&err), Some(&ListenError::AddressInUse { port: 4242 }), "{err:#}");
+    assert_eq!(err.to_string(), "listen port 4242 is already in use");
+    assert_eq!(service.listen_addr(), None);
+
+    service.set_endpoint_factory(Arc::new(|_| Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied).into())));
+    let err = service.start().await.unwrap_err();
+    let Some(ListenError::BindFailed { addr, reason }) = ListenError::of(&err) else { panic!("{err:#}") };
+    assert_eq!(*addr, fixed);
+    assert!(reason.contains("permission denied"), "{reason}");
+    assert_eq!(service.listen_addr(), None);
+}
+
+#[tokio::test]
+async fn file_transfers_are_paced_to_the_rate_limit() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust, disc, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, trust1, disc1, &h1);
+    let s2 = service(&id2, trust2, disc2, &h2);
+
+    let download_dir = std::env::temp_dir().join(format!("oc-paced-{}", rand::random::<u64>()));
+    s2.file_log().set_download_dir(download_dir.clone());
+
+    // The per-peer limit is the stricter one here.
+    const RATE: u64 = 64 * 1024;
+    s1.set_file_rate_limit(Some(4 * RATE));
+    s1.set_peer_file_rate_limit(id2.peer_id(), Some(RATE));
+    assert_eq!(s1.peer_file_rate_limit(id2.peer_id()), Some(RATE));
+
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while s1.snapshot(0).await.connected.is_empty() || s2.snapshot(0).await.connected.is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    // A one-second burst, then two more seconds' worth at the limit.
+    let data: Vec<u8> = (0..3 * RATE).map(|i| (i % 251) as u8).collect();
+    let start = std::time::Instant::now();
+    s1.send_file_to_peer(id2.peer_id(), "paced.bin".into(), "application/octet-stream".into(), data.clone(), None)
+        .await
+        .unwrap();
+    let elapsed = start.elapsed();
+
+    while h2.files.lock().unwrap().is_empty() && start.elapsed() < std::time::Duration::from_secs(5) {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let received = h2.files.lock().unwrap().first().map(|(_, _, path)| path.clone());
+
+    s1.stop().await;
+    s2.stop().await;
+
+    assert!(
+        elapsed >= std::time::Duration::from_millis(1800) && elapsed < std::time::Duration::from_secs(4),
+        "took {elapsed:?} at {RATE} B/s"
+    );
+    let path = received.unwrap_or_else(|| panic!("file never arrived; errors: {:?}", h2.errors.lock().unwrap()));
+    assert_eq!(std::fs::read(path).unwrap(), data);
+
+    let _ = std::fs::remove_dir_all(download_dir);
+}
+
+#[tokio::test]
+async fn a_file_cut_off_by_a_restart_resumes_where_it_stopped() {
+    use sha2::{Digest, Sha256};
+    let shared = MockDiscovery::new_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let dir = std::env::temp_dir().join(format!("oc-resume-{}", rand::random::<u64>()));
+    let h1 = Arc::new(TestHandler::default());
+    let receiver = |h: &Arc<TestHandler>| {
+        let s = SyncService::new(
+            id2.clone(),
+            trust2.clone(),
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(shared.clone_shared()),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap();
+        s.file_log().set_download_dir(dir.join("downloads"));
+        s.file_log().set_partial_dir(Some(dir.join("partial")));
+        s
+    };
+    let s1 = SyncService::new(
+        id1.clone(),
+        trust1,
+        Arc::new(MemoryReplayProtector::new(1024)),
+        Arc::new(shared.clone_shared()),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        "dev".into(),
+        h1.clone(),
+    )
+    .unwrap();
+    let h2 = Arc::new(TestHandler::default());
+    let s2 = receiver(&h2);
+    let connected = |h: Arc<TestHandler>| async move {
+        let start = std::time::Instant::now();
+        while h.connected.lock().unwrap().is_empty() {
+            assert!(start.elapsed() < std::time::Duration::from_secs(6), "never connected");
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+    };
+
+    const RATE: u64 = 64 * 1024;
+    s1.set_peer_file_rate_limit(id2.peer_id(), Some(RATE));
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+    connected(h2.clone()).await;
+    connected(h1.clone()).await;
+
+    // Slow enough to cut off partway: stop the receiver once a few chunks are out.
+    let data: Vec<u8> = (0..8 * RATE).map(|i| (i % 251) as u8).collect();
+    let sent = Arc::new(std::sync::atomic::AtomicU64::new(0));
+    let progress = Arc::clone(&sent);
+    let send = s1.send_file_to_peer(
+        id2.peer_id(),
+        "big.bin".into(),
+        "application/octet-stream".into(),
+        data.clone(),
+        Some(Arc::new(move |n, _| progress.store(n, std::sync::atomic::Ordering::SeqCst))),
+    );
+    let cut_off = async {
+        let start = std::time::Instant::now();
+        while sent.load(std::sync::atomic::Ordering::SeqCst) < 2 * RATE {
+            assert!(start.elapsed() < std::time::Duration::from_secs(10), "too little sent: {:?}", h1.errors.lock().unwrap());
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        s2.stop().await;
+    };
+    let (res, ()) = tokio::join!(send, cut_off);
+    assert!(res.is_err(), "the transfer should have been cut off");
+
+    let partials = openclipboard_core::PartialFiles::new(dir.join("partial"));
+    let file_id = format!("{:x}", Sha256::digest(&data));
+    let start = std::time::Instant::now();
+    while partials.saved_len(id1.peer_id(), &file_id) == 0 {
+        assert!(start.elapsed() < std::time::Duration::from_secs(3), "nothing saved: {:?}", h2.errors.lock().unwrap());
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let saved = partials.saved_len(id1.peer_id(), &file_id);
+
+    // The restarted receiver only needs the rest.
+    h1.connected.lock().unwrap().clear();
+    let h2 = Arc::new(TestHandler::default());
+    let s2 = receiver(&h2);
+    s2.start().await.unwrap();
+    connected(h2.clone()).await;
+    connected(h1.clone()).await;
+    s1.set_peer_file_rate_limit(id2.peer_id(), None);
+    let first = Arc::new(Mutex::new(None));
+    let seen = Arc::clone(&first);
+    s1.send_file_to_peer(
+        id2.peer_id(),
+        "big.bin".into(),
+        "application/octet-stream".into(),
+        data.clone(),
+        Some(Arc::new(move |n, _| {
+            seen.lock().unwrap().get_or_insert(n);
+        })),
+    )
+    .await
+    .unwrap();
+    let start = std::time::Instant::now();
+    while h2.files.lock().unwrap().is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "file never arrived: {:?}", h2.errors.lock().unwrap());
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    s1.stop().await;
+    s2.stop().await;
+
+    assert!(first.lock().unwrap().unwrap() > saved, "resent from the start");
+    let (_, _, path) = h2.files.lock().unwrap()[0].clone();
+    assert_eq!(std::fs::read(path).unwrap(), data);
+    let start = std::time::Instant::now();
+    while partials.saved_len(id1.peer_id(), &file_id) > 0 {
+        assert!(start.elapsed() < std::time::Duration::from_secs(1), "the partial outlived the transfer");
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn terminated_session_leaves_the_active_list_but_not_the_trust_store() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust: Arc<MemoryTrustStore>, disc, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, Arc::clone(&trust1), disc1, &h1);
+    let s2 = service(&id2, trust2, disc2, &h2);
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while s1.active_sessions().await.is_empty() || s2.active_sessions().await.is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let sessions1 = s1.active_sessions().await;
+    let sessions2 = s2.active_sessions().await;
+    assert_eq!(sessions1.len(), 1);
+    let session = &sessions1[0];
+    assert_eq!(session.peer_id, id2.peer_id());
+    assert_eq!(session.encrypted, sessions2[0].encrypted);
+    assert!(session.connected_at_ms > 0);
+    // One side dialed, the other accepted; the dialer knows the address it used.
+    assert_ne!(session.outbound, sessions2[0].outbound);
+    let dialer = if session.outbound { session } else { &sessions2[0] };
+    assert!(dialer.addr.as_deref().is_some_and(|a| a.starts_with("127.0.0.1:")), "{dialer:?}");
+
+    assert!(s1.terminate_session(id2.peer_id()).await);
+    assert!(s1.active_sessions().await.is_empty());
+    assert!(!s1.terminate_session(id2.peer_id()).await);
+
+    // Still trusted and not suspended: the peers find each other again.
+    assert!(trust1.get(id2.peer_id()).unwrap().is_some());
+    assert!(s1.suspended_peers().is_empty());
+    let start = std::time::Instant::now();
+    while h1.connected.lock().unwrap().len() < 2 {
+        assert!(start.elapsed() < std::time::Duration::from_secs(6), "never reconnected; errors={:?}", h1.errors.lock().unwrap());
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert_eq!(h1.disconnected.lock().unwrap().len(), 1);
+
+    s1.stop().await;
+    s2.stop().await;
+}
+
+#[tokio::test]
+async fn stopping_a_node_is_a_clean_disconnect_for_its_peer() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    let id1 = Ed25519Identity::generate();
+    let id2 = Ed25519Identity::generate();
+
+    let trust1 = Arc::new(MemoryTrustStore::new());
+    let trust2 = Arc::new(MemoryTrustStore::new());
+    trust_each_other(&id1, &id2, &trust1, "peer2");
+    trust_each_other(&id2, &id1, &trust2, "peer1");
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |id: &Ed25519Identity, trust: Arc<MemoryTrustStore>, disc, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            trust,
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(&id1, trust1, disc1, &h1);
+    let s2 = service(&id2, trust2, disc2, &h2);
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let start = std::time::Instant::now();
+    while s1.active_sessions().await.is_empty() || s2.active_sessions().await.is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "never connected");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    s1.stop().await;
+
+    let start = std::time::Instant::now();
+    while h2.disconnected.lock().unwrap().is_empty() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(2), "peer never saw the disconnect");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    // Well inside the redial quarantine, so nothing else should be reported either.
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert_eq!(*h2.disconnected.lock().unwrap(), [id1.peer_id().to_string()]);
+    assert!(s2.active_sessions().await.is_empty());
+    assert!(h2.errors.lock().unwrap().is_empty(), "errors={:?}", h2.errors.lock().unwrap());
+
+    s2.stop().await;
+}
+
+#[tokio::test]
+async fn two_nodes_sharing_an_identity_are_warned_about_it() {
+    let disc1 = MockDiscovery::new_shared();
+    let disc2 = disc1.clone_shared();
+
+    // The same identity file on two devices.
+    let id = Ed25519Identity::generate();
+
+    let h1 = Arc::new(TestHandler::default());
+    let h2 = Arc::new(TestHandler::default());
+    let service = |disc, h: &Arc<TestHandler>| {
+        SyncService::new(
+            id.clone(),
+            Arc::new(MemoryTrustStore::new()),
+            Arc::new(MemoryReplayProtector::new(1024)),
+            Arc::new(disc),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            "dev".into(),
+            h.clone(),
+        )
+        .unwrap()
+    };
+    let s1 = service(disc1, &h1);
+    let s2 = service(disc2, &h2);
+    s1.start().await.unwrap();
+    s2.start().await.unwrap();
+
+    let warned = |h: &TestHandler| {
+        h.handshake_failures.lock().unwrap().iter().any(|(_, e)| *e == HandshakeError::DuplicateIdentity)
+    };
+    let start = std::time::Instant::now();
+    while !(warned(&h1) && warned(&h2)) {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(5),
+            "no duplicate identity warning; failures1={:?} failures2={:?}",
+            h1.handshake_failures.lock().unwrap(),
+            h2.handshake_failures.lock().unwrap()
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(h1.errors.lock().unwrap().iter().chain(h2.errors.lock().unwrap().iter()).any(|e| e.contains("using this device's identity")));
+    assert!(h1.connected.lock().unwrap().is_empty() && h2.connected.lock().unwrap().is_empty());
+
+    // Warned once, not on every discovery scan.
+    tokio::time::sleep(std::time::Duration::from_millis(700)).await;
+    let count = |h: &TestHandler| h.handshake_failures.lock().unwrap().iter().filter(|(_, e)| *e == HandshakeError::DuplicateIdentity).count();
+    assert_eq!(count(&h1) + count(&h2), 2);
+
+    s1.stop().await;
+    s2.stop().await;
+}
@@ -1,4 +1,4 @@
-use openclipboard_core::protocol::{decode_frame, decode_message, encode_message, Message};
+use openclipboard_core::protocol::{decode_file_chunk_raw, decode_frame, decode_message, encode_message, protocol_descriptor, Codec, Message, TrustSyncRecord, TrustSyncRevocation};
 
 use proptest::prelude::*;
 use std::panic::catch_unwind;
@@ -11,6 +11,13 @@ proptest! {
         }).expect("decode_frame panicked");
     }
 
+    #[test]
+    fn decode_file_chunk_raw_never_panics(data in proptest::collection::vec(any::<u8>(), 0..256)) {
+        catch_unwind(|| {
+            let _ = decode_file_chunk_raw(&data);
+        }).expect("decode_file_chunk_raw panicked");
+    }
+
     #[test]
     fn message_encode_decode_roundtrip(msg in arb_message()) {
         let enc = encode_message(&msg, 123).expect("encode_message");
@@ -18,35 +25,101 @@ proptest! {
         prop_assert_eq!(seq, 123);
         prop_assert_eq!(dec, msg);
     }
+
+    #[test]
+    fn message_msgpack_roundtrip(msg in arb_message()) {
+        let enc = Codec::MessagePack.encode(&msg).expect("encode");
+        prop_assert_eq!(Codec::MessagePack.decode(&enc).expect("decode"), msg);
+    }
 }
 
 fn arb_message() -> impl Strategy<Value = Message> {
     let small_string = "[ -~]{0,128}"; // printable ASCII, small
 
     prop_oneof![
-        (small_string, any::<u8>(), small_string, small_string, small_string).prop_map(
-            |(peer_id, version, identity_pk_b64, nonce_b64, sig_b64)| Message::Hello {
+        (
+            small_string,
+            any::<u8>(),
+            small_string,
+            small_string,
+            small_string,
+            any::<bool>(),
+            proptest::collection::vec(small_string, 0..3),
+            proptest::collection::vec(small_string, 0..3),
+            proptest::option::of(small_string),
+        ).prop_map(
+            |(peer_id, version, identity_pk_b64, nonce_b64, sig_b64, psk, codecs, features, x25519_pk_b64)| Message::Hello {
                 peer_id,
                 version,
                 identity_pk_b64,
                 nonce_b64,
                 sig_b64,
+                psk,
+                codecs,
+                features,
+                x25519_pk_b64,
             }
         ),
         any::<u64>().prop_map(|ts_ms| Message::Ping { ts_ms }),
         any::<u64>().prop_map(|ts_ms| Message::Pong { ts_ms }),
-        (small_string, small_string, any::<u64>()).prop_map(|(mime, text, ts_ms)| Message::ClipText { mime, text, ts_ms }),
+        (small_string, small_string, small_string).prop_map(
+            |(name, platform, app_version)| Message::DeviceInfo { name, platform, app_version }
+        ),
+        (small_string, small_string, any::<u64>(), proptest::option::of(small_string)).prop_map(
+            |(mime, text, ts_ms, origin)| Message::ClipText { mime, text, ts_ms, origin }
+        ),
         (small_string, any::<u32>(), any::<u32>(), small_string, any::<u64>()).prop_map(
             |(mime, width, height, bytes_b64, ts_ms)| Message::ClipImage { mime, width, height, bytes_b64, ts_ms }
         ),
-        (small_string, small_string, any::<u64>(), small_string).prop_map(
-            |(file_id, name, size, mime)| Message::FileOffer { file_id, name, size, mime }
+        (small_string, small_string, any::<u64>(), any::<u64>(), proptest::option::of(small_string)).prop_map(
+            |(clip_id, mime, total_len, ts_ms, origin)| Message::ClipTextBegin { clip_id, mime, total_len, ts_ms, origin }
+        ),
+        (small_string, any::<u64>(), small_string).prop_map(
+            |(clip_id, offset, text)| Message::ClipTextChunk { clip_id, offset, text }
+        ),
+        small_string.prop_map(|clip_id| Message::ClipTextEnd { clip_id }),
+        (small_string, small_string, any::<u64>(), proptest::option::of(small_string)).prop_map(
+            |(mime, data_b64, ts_ms, origin)| Message::ClipTextCompressed { mime, data_b64, ts_ms, origin }
+        ),
+        (small_string, small_string, any::<u64>(), small_string, proptest::option::of(small_string)).prop_map(
+            |(file_id, name, size, mime, hash)| Message::FileOffer { file_id, name, size, mime, hash }
         ),
         small_string.prop_map(|file_id| Message::FileAccept { file_id }),
         (small_string, small_string).prop_map(|(file_id, reason)| Message::FileReject { file_id, reason }),
         (small_string, any::<u64>(), small_string).prop_map(
             |(file_id, offset, data_b64)| Message::FileChunk { file_id, offset, data_b64 }
         ),
+        (small_string, any::<u64>(), proptest::collection::vec(any::<u8>(), 0..64)).prop_map(
+            |(file_id, offset, data)| Message::FileChunkRaw { file_id, offset, data }
+        ),
         (small_string, small_string).prop_map(|(file_id, hash)| Message::FileDone { file_id, hash }),
+        small_string.prop_map(|file_id| Message::FileResumeQuery { file_id }),
+        (small_string, any::<u64>()).prop_map(|(file_id, offset)| Message::FileResumeOffset { file_id, offset }),
+        any::<u64>().prop_map(|ack_of_seq| Message::ClipAck { ack_of_seq }),
+        Just(Message::DescribeRequest),
+        small_string.prop_map(|reason| Message::Bye { reason }),
+        (any::<bool>(), small_string).prop_map(|(trusted, sig_b64)| Message::TrustAck { trusted, sig_b64 }),
+        proptest::collection::vec(small_string, 0..3).prop_map(|features| Message::Describe {
+            descriptor: protocol_descriptor(Codec::MessagePack, &features),
+        }),
+        (
+            proptest::collection::vec((small_string, small_string, small_string, any::<i64>()), 0..3),
+            proptest::collection::vec((small_string, any::<i64>()), 0..3),
+        )
+            .prop_map(|(records, revoked)| Message::TrustSync {
+                records: records
+                    .into_iter()
+                    .map(|(peer_id, identity_pk_b64, display_name, created_at_ms)| TrustSyncRecord {
+                        peer_id,
+                        identity_pk_b64,
+                        display_name,
+                        created_at_ms,
+                    })
+                    .collect(),
+                revoked: revoked
+                    .into_iter()
+                    .map(|(peer_id, revoked_at_ms)| TrustSyncRevocation { peer_id, revoked_at_ms })
+                    .collect(),
+            }),
     ]
 }
@@ -1,8 +1,12 @@
 //! QUIC loopback integration tests.
 
+#![cfg(feature = "net")]
+
 use openclipboard_core::protocol::{Frame, MsgType, StreamId, Message};
 use openclipboard_core::transport::{Connection, Listener, Transport};
-use openclipboard_core::quic_transport::{QuicListener, QuicTransport, make_server_endpoint, make_client_endpoint};
+use openclipboard_core::quic_transport::{
+    QuicListener, QuicTransport, default_alpn, make_client_endpoint, make_client_endpoint_with_alpn, make_server_endpoint,
+};
 
 async fn setup() -> (QuicListener, QuicTransport, String) {
     let bind: std::net::SocketAddr = "127.0.0.1:0".parse().unwrap();
@@ -59,7 +63,7 @@ async fn quic_clipboard_text_sync() {
     });
 
     let conn = transport.connect(&addr).await.unwrap();
-    let msg = Message::ClipText { mime: "text/plain".into(), text: "Hello from QUIC!".into(), ts_ms: 1 };
+    let msg = Message::ClipText { mime: "text/plain".into(), text: "Hello from QUIC!".into(), ts_ms: 1, origin: None };
     conn.send(msg_to_frame(&msg, 1)).await.unwrap();
 
     server.await.unwrap();
@@ -68,7 +72,7 @@ async fn quic_clipboard_text_sync() {
 #[tokio::test]
 async fn quic_clipboard_image_sync() {
     let (listener, transport, addr) = setup().await;
-    let fake_png = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]);
+    let fake_png = openclipboard_core::Base64Alphabet::Standard.encode([0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]);
 
     let expected = fake_png.clone();
     let server = tokio::spawn(async move {
@@ -96,7 +100,7 @@ async fn quic_clipboard_image_sync() {
 async fn quic_file_transfer() {
     let (listener, transport, addr) = setup().await;
     let file_data = b"Hello file content over QUIC";
-    let data_b64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, file_data);
+    let data_b64 = openclipboard_core::Base64Alphabet::Standard.encode(file_data);
     let hash = blake3::hash(file_data).to_hex().to_string();
 
     let expected_b64 = data_b64.clone();
@@ -126,7 +130,7 @@ async fn quic_file_transfer() {
     });
 
     let conn = transport.connect(&addr).await.unwrap();
-    let offer = Message::FileOffer { file_id: "f1".into(), name: "test.txt".into(), size: file_data.len() as u64, mime: "text/plain".into() };
+    let offer = Message::FileOffer { file_id: "f1".into(), name: "test.txt".into(), size: file_data.len() as u64, mime: "text/plain".into(), hash: None };
     conn.send(msg_to_frame(&offer, 1)).await.unwrap();
     let chunk = Message::FileChunk { file_id: "f1".into(), offset: 0, data_b64 };
     conn.send(msg_to_frame(&chunk, 2)).await.unwrap();
@@ -143,7 +147,7 @@ async fn quic_bidirectional() {
     let server = tokio::spawn(async move {
         let conn = listener.accept().await.unwrap();
         // Send clip from server side
-        let msg = Message::ClipText { mime: "text/plain".into(), text: "from server".into(), ts_ms: 10 };
+        let msg = Message::ClipText { mime: "text/plain".into(), text: "from server".into(), ts_ms: 10, origin: None };
         conn.send(msg_to_frame(&msg, 1)).await.unwrap();
         // Receive clip from client
         let f = conn.recv().await.unwrap();
@@ -158,7 +162,7 @@ async fn quic_bidirectional() {
 
     let conn = transport.connect(&addr).await.unwrap();
     // Send clip from client side
-    let msg = Message::ClipText { mime: "text/plain".into(), text: "from client".into(), ts_ms: 20 };
+    let msg = Message::ClipText { mime: "text/plain".into(), text: "from client".into(), ts_ms: 20, origin: None };
     conn.send(msg_to_frame(&msg, 1)).await.unwrap();
     // Receive clip from server
     let f = conn.recv().await.unwrap();
@@ -171,3 +175,100 @@ async fn quic_bidirectional() {
 
     let _ = server.await;
 }
+
+#[tokio::test]
+async fn quic_rejects_client_with_wrong_alpn() {
+    assert_eq!(default_alpn(), b"oclip/0");
+
+    let bind: std::net::SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let (endpoint, cert) = make_server_endpoint(bind).unwrap();
+    let addr = endpoint.local_addr().unwrap().to_string();
+    let listener = QuicListener::new(endpoint);
+
+    let server = tokio::spawn(async move {
+        // The wrong-ALPN attempt surfaces as an accept error; only the
+        // correctly-identified client yields a connection.
+        let mut failed_accepts = 0;
+        let conn = loop {
+            match listener.accept().await {
+                Ok(c) => break c,
+                Err(_) => failed_accepts += 1,
+            }
+        };
+        assert_eq!(failed_accepts, 1);
+        let frame = conn.recv().await.unwrap();
+        assert_eq!(frame.payload, b"hi");
+    });
+
+    let wrong = QuicTransport::new(make_client_endpoint_with_alpn(cert.clone(), vec![b"h3".to_vec()]).unwrap());
+    let err = tokio::time::timeout(std::time::Duration::from_secs(5), wrong.connect(&addr))
+        .await
+        .expect("wrong-ALPN connect should fail, not hang");
+    assert!(err.is_err(), "client offering h3 should be rejected");
+
+    let right = QuicTransport::new(make_client_endpoint(cert).unwrap());
+    let conn = right.connect(&addr).await.unwrap();
+    conn.send(Frame::new(MsgType::Ping, StreamId::Control, 1, b"hi".to_vec())).await.unwrap();
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), server).await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn quic_accept_times_out_client_that_opens_no_stream() {
+    use openclipboard_core::quic_transport::AcceptError;
+
+    let bind: std::net::SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let (endpoint, cert) = make_server_endpoint(bind).unwrap();
+    let addr = endpoint.local_addr().unwrap();
+    let listener = QuicListener::new(endpoint).with_stream_open_timeout(std::time::Duration::from_millis(200));
+
+    // Completes the QUIC handshake, then sits idle without opening a stream.
+    let idle_ep = make_client_endpoint(cert.clone()).unwrap();
+    let idle = tokio::spawn(async move { idle_ep.connect(addr, "localhost").unwrap().await.unwrap() });
+
+    let err = tokio::time::timeout(std::time::Duration::from_secs(5), listener.accept())
+        .await
+        .expect("accept should give up on the idle client, not hang")
+        .err()
+        .expect("idle client should not yield a connection");
+    assert_eq!(AcceptError::of(&err), Some(&AcceptError::StreamTimeout));
+    let _idle = idle.await.unwrap();
+
+    // The listener still serves well-behaved clients afterwards.
+    let server = tokio::spawn(async move {
+        let conn = listener.accept().await.unwrap();
+        conn.recv().await.unwrap()
+    });
+    let transport = QuicTransport::new(make_client_endpoint(cert).unwrap());
+    let conn = transport.connect(&addr.to_string()).await.unwrap();
+    conn.send(Frame::new(MsgType::Ping, StreamId::Control, 1, b"hi".to_vec())).await.unwrap();
+    let frame = tokio::time::timeout(std::time::Duration::from_secs(5), server).await.unwrap().unwrap();
+    assert_eq!(frame.payload, b"hi");
+}
+
+#[tokio::test]
+async fn quic_accept_does_not_wait_behind_an_idle_client() {
+    let bind: std::net::SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let (endpoint, cert) = make_server_endpoint(bind).unwrap();
+    let addr = endpoint.local_addr().unwrap();
+    // Long enough that waiting it out would fail the test.
+    let listener = QuicListener::new(endpoint).with_stream_open_timeout(std::time::Duration::from_secs(30));
+    let server = tokio::spawn(async move {
+        let conn = listener.accept().await.unwrap();
+        conn.recv().await.unwrap()
+    });
+
+    // Connects first, then never opens a stream.
+    let idle_ep = make_client_endpoint(cert.clone()).unwrap();
+    let _idle = idle_ep.connect(addr, "localhost").unwrap().await.unwrap();
+
+    let transport = QuicTransport::new(make_client_endpoint(cert).unwrap());
+    let conn = transport.connect(&addr.to_string()).await.unwrap();
+    conn.send(Frame::new(MsgType::Ping, StreamId::Control, 1, b"hi".to_vec())).await.unwrap();
+
+    let frame = tokio::time::timeout(std::time::Duration::from_secs(5), server)
+        .await
+        .expect("the second client should be accepted while the first idles")
+        .unwrap();
+    assert_eq!(frame.payload, b"hi");
+}
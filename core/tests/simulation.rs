@@ -1,3 +1,5 @@
+#![cfg(feature = "net")]
+
 use openclipboard_core::{
     derive_confirmation_code,
     pairing::PairingPayload,
@@ -54,6 +56,7 @@ async fn full_pairing_and_trusted_handshake_flow() {
             identity_pk: bob_payload.identity_pk.clone(),
             display_name: bob_payload.name.clone(),
             created_at: Utc::now(),
+            sync_direction: Default::default(),
         })
         .unwrap();
 
@@ -64,6 +67,7 @@ async fn full_pairing_and_trusted_handshake_flow() {
             identity_pk: alice_payload.identity_pk.clone(),
             display_name: alice_payload.name.clone(),
             created_at: Utc::now(),
+            sync_direction: Default::default(),
         })
         .unwrap();
 
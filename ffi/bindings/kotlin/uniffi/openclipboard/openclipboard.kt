@@ -375,7 +375,7 @@ private fun findLibraryName(componentName: String): String {
     if (libOverride != null) {
         return libOverride
     }
-    return "uniffi_openclipboard"
+    return "openclipboard_ffi"
 }
 
 private inline fun <reified Lib : Library> loadIndirect(
@@ -660,6 +660,12 @@ internal interface UniffiCallbackInterfaceClipboardCallbackMethod0 : com.sun.jna
 internal interface UniffiCallbackInterfaceClipboardCallbackMethod1 : com.sun.jna.Callback {
     fun callback(`uniffiHandle`: Long,`text`: RustBuffer.ByValue,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,)
 }
+internal interface UniffiCallbackInterfaceClipboardCallbackMethod2 : com.sun.jna.Callback {
+    fun callback(`uniffiHandle`: Long,`uniffiOutReturn`: RustBuffer,uniffiCallStatus: UniffiRustCallStatus,)
+}
+internal interface UniffiCallbackInterfaceClipboardCallbackMethod3 : com.sun.jna.Callback {
+    fun callback(`uniffiHandle`: Long,`image`: RustBuffer.ByValue,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,)
+}
 internal interface UniffiCallbackInterfaceDiscoveryHandlerMethod0 : com.sun.jna.Callback {
     fun callback(`uniffiHandle`: Long,`peerId`: RustBuffer.ByValue,`name`: RustBuffer.ByValue,`addr`: RustBuffer.ByValue,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,)
 }
@@ -681,21 +687,33 @@ internal interface UniffiCallbackInterfaceEventHandlerMethod3 : com.sun.jna.Call
 internal interface UniffiCallbackInterfaceEventHandlerMethod4 : com.sun.jna.Callback {
     fun callback(`uniffiHandle`: Long,`message`: RustBuffer.ByValue,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,)
 }
-@Structure.FieldOrder("readText", "writeText", "uniffiFree")
+internal interface UniffiCallbackInterfaceEventHandlerMethod5 : com.sun.jna.Callback {
+    fun callback(`uniffiHandle`: Long,`peerId`: RustBuffer.ByValue,`mime`: RustBuffer.ByValue,`width`: Int,`height`: Int,`bytes`: RustBuffer.ByValue,`tsMs`: Long,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,)
+}
+internal interface UniffiCallbackInterfaceFileProgressHandlerMethod0 : com.sun.jna.Callback {
+    fun callback(`uniffiHandle`: Long,`sent`: Long,`total`: Long,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,)
+}
+@Structure.FieldOrder("readText", "writeText", "readImage", "writeImage", "uniffiFree")
 internal open class UniffiVTableCallbackInterfaceClipboardCallback(
     @JvmField internal var `readText`: UniffiCallbackInterfaceClipboardCallbackMethod0? = null,
     @JvmField internal var `writeText`: UniffiCallbackInterfaceClipboardCallbackMethod1? = null,
+    @JvmField internal var `readImage`: UniffiCallbackInterfaceClipboardCallbackMethod2? = null,
+    @JvmField internal var `writeImage`: UniffiCallbackInterfaceClipboardCallbackMethod3? = null,
     @JvmField internal var `uniffiFree`: UniffiCallbackInterfaceFree? = null,
 ) : Structure() {
     class UniffiByValue(
         `readText`: UniffiCallbackInterfaceClipboardCallbackMethod0? = null,
         `writeText`: UniffiCallbackInterfaceClipboardCallbackMethod1? = null,
+        `readImage`: UniffiCallbackInterfaceClipboardCallbackMethod2? = null,
+        `writeImage`: UniffiCallbackInterfaceClipboardCallbackMethod3? = null,
         `uniffiFree`: UniffiCallbackInterfaceFree? = null,
-    ): UniffiVTableCallbackInterfaceClipboardCallback(`readText`,`writeText`,`uniffiFree`,), Structure.ByValue
+    ): UniffiVTableCallbackInterfaceClipboardCallback(`readText`,`writeText`,`readImage`,`writeImage`,`uniffiFree`,), Structure.ByValue
 
    internal fun uniffiSetValue(other: UniffiVTableCallbackInterfaceClipboardCallback) {
         `readText` = other.`readText`
         `writeText` = other.`writeText`
+        `readImage` = other.`readImage`
+        `writeImage` = other.`writeImage`
         `uniffiFree` = other.`uniffiFree`
     }
 
@@ -719,13 +737,14 @@ internal open class UniffiVTableCallbackInterfaceDiscoveryHandler(
     }
 
 }
-@Structure.FieldOrder("onClipboardText", "onFileReceived", "onPeerConnected", "onPeerDisconnected", "onError", "uniffiFree")
+@Structure.FieldOrder("onClipboardText", "onFileReceived", "onPeerConnected", "onPeerDisconnected", "onError", "onClipboardImage", "uniffiFree")
 internal open class UniffiVTableCallbackInterfaceEventHandler(
     @JvmField internal var `onClipboardText`: UniffiCallbackInterfaceEventHandlerMethod0? = null,
     @JvmField internal var `onFileReceived`: UniffiCallbackInterfaceEventHandlerMethod1? = null,
     @JvmField internal var `onPeerConnected`: UniffiCallbackInterfaceEventHandlerMethod2? = null,
     @JvmField internal var `onPeerDisconnected`: UniffiCallbackInterfaceEventHandlerMethod3? = null,
     @JvmField internal var `onError`: UniffiCallbackInterfaceEventHandlerMethod4? = null,
+    @JvmField internal var `onClipboardImage`: UniffiCallbackInterfaceEventHandlerMethod5? = null,
     @JvmField internal var `uniffiFree`: UniffiCallbackInterfaceFree? = null,
 ) : Structure() {
     class UniffiByValue(
@@ -734,8 +753,9 @@ internal open class UniffiVTableCallbackInterfaceEventHandler(
         `onPeerConnected`: UniffiCallbackInterfaceEventHandlerMethod2? = null,
         `onPeerDisconnected`: UniffiCallbackInterfaceEventHandlerMethod3? = null,
         `onError`: UniffiCallbackInterfaceEventHandlerMethod4? = null,
+        `onClipboardImage`: UniffiCallbackInterfaceEventHandlerMethod5? = null,
         `uniffiFree`: UniffiCallbackInterfaceFree? = null,
-    ): UniffiVTableCallbackInterfaceEventHandler(`onClipboardText`,`onFileReceived`,`onPeerConnected`,`onPeerDisconnected`,`onError`,`uniffiFree`,), Structure.ByValue
+    ): UniffiVTableCallbackInterfaceEventHandler(`onClipboardText`,`onFileReceived`,`onPeerConnected`,`onPeerDisconnected`,`onError`,`onClipboardImage`,`uniffiFree`,), Structure.ByValue
 
    internal fun uniffiSetValue(other: UniffiVTableCallbackInterfaceEventHandler) {
         `onClipboardText` = other.`onClipboardText`
@@ -743,6 +763,23 @@ internal open class UniffiVTableCallbackInterfaceEventHandler(
         `onPeerConnected` = other.`onPeerConnected`
         `onPeerDisconnected` = other.`onPeerDisconnected`
         `onError` = other.`onError`
+        `onClipboardImage` = other.`onClipboardImage`
+        `uniffiFree` = other.`uniffiFree`
+    }
+
+}
+@Structure.FieldOrder("onProgress", "uniffiFree")
+internal open class UniffiVTableCallbackInterfaceFileProgressHandler(
+    @JvmField internal var `onProgress`: UniffiCallbackInterfaceFileProgressHandlerMethod0? = null,
+    @JvmField internal var `uniffiFree`: UniffiCallbackInterfaceFree? = null,
+) : Structure() {
+    class UniffiByValue(
+        `onProgress`: UniffiCallbackInterfaceFileProgressHandlerMethod0? = null,
+        `uniffiFree`: UniffiCallbackInterfaceFree? = null,
+    ): UniffiVTableCallbackInterfaceFileProgressHandler(`onProgress`,`uniffiFree`,), Structure.ByValue
+
+   internal fun uniffiSetValue(other: UniffiVTableCallbackInterfaceFileProgressHandler) {
+        `onProgress` = other.`onProgress`
         `uniffiFree` = other.`uniffiFree`
     }
 
@@ -890,6 +927,111 @@ internal open class UniffiVTableCallbackInterfaceEventHandler(
 
 
 
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
 
 
 
@@ -928,6 +1070,8 @@ internal interface IntegrityCheckingUniffiLib : Library {
     // Integrity check functions only
     fun uniffi_openclipboard_ffi_checksum_func_clipboard_node_new(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_func_confirmation_transcript(
+): Short
 fun uniffi_openclipboard_ffi_checksum_func_default_identity_path(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_func_derive_confirmation_code(
@@ -942,30 +1086,118 @@ fun uniffi_openclipboard_ffi_checksum_func_pairing_payload_create(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_func_pairing_payload_from_qr_string(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_func_peer_visual(
+): Short
 fun uniffi_openclipboard_ffi_checksum_func_trust_store_default_path(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_func_trust_store_open(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_func_verify_confirmation_code(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_cancellationtoken_cancel(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_cancellationtoken_is_cancelled(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_accept_pending_request(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_active_sessions(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_clear_echo_suppressor(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_clear_peer_file_type_rules(
+): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_connect_and_send_file(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_connect_and_send_text(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_diagnostics(
+): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_disable_qr_pairing_listener(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_disconnect_peer(
+): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_enable_qr_pairing_listener(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_history(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_history_for_peer(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_peer_summary(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_is_discovering(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_last_clip_for_peer(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_linked_devices(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_list_peers(
+): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_pair_via_qr(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_peer_id(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_pending_requests(
+): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_recall_from_history(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_received_files(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_recent_errors(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_remove_trusted_peer(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_resend_current(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_resume_peer(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_clipboard_image(
+): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_clipboard_text(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_file_to_peer(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_disconnect_grace_ms(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_download_dir(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_drain_on_stop_ms(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_rate_limit(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_transfer_config(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_type_rules(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_history_file(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_history_max_bytes(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_keepalive_ms(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_linked_device(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_linked_devices_file(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_max_concurrent_file_sends(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_max_incoming_file_size(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_partial_files_dir(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_file_rate_limit(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_file_type_rules(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_sync_direction(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_profile(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_received_files_log(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_sync_whitespace(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_snapshot(
+): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_discovery(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_listener(
@@ -980,6 +1212,10 @@ fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_stop_discovery(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_stop_sync(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_sync_trust(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardnode_terminate_session(
+): Short
 fun uniffi_openclipboard_ffi_checksum_method_identity_info(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_identity_peer_id(
@@ -1002,6 +1238,8 @@ fun uniffi_openclipboard_ffi_checksum_method_pairingpayload_peer_id(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_pairingpayload_to_qr_string(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_method_pairingpayload_to_qr_string_compact(
+): Short
 fun uniffi_openclipboard_ffi_checksum_method_pairingpayload_version(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_truststore_add(
@@ -1012,10 +1250,16 @@ fun uniffi_openclipboard_ffi_checksum_method_truststore_list(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_truststore_remove(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_constructor_cancellationtoken_new(
+): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardcallback_read_text(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_clipboardcallback_write_text(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardcallback_read_image(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_clipboardcallback_write_image(
+): Short
 fun uniffi_openclipboard_ffi_checksum_method_discoveryhandler_on_peer_discovered(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_discoveryhandler_on_peer_lost(
@@ -1030,6 +1274,10 @@ fun uniffi_openclipboard_ffi_checksum_method_eventhandler_on_peer_disconnected(
 ): Short
 fun uniffi_openclipboard_ffi_checksum_method_eventhandler_on_error(
 ): Short
+fun uniffi_openclipboard_ffi_checksum_method_eventhandler_on_clipboard_image(
+): Short
+fun uniffi_openclipboard_ffi_checksum_method_fileprogresshandler_on_progress(
+): Short
 fun ffi_openclipboard_ffi_uniffi_contract_version(
 ): Int
 
@@ -1071,6 +1319,7 @@ internal interface UniffiLib : Library {
             uniffiCallbackInterfaceClipboardCallback.register(lib)
             uniffiCallbackInterfaceDiscoveryHandler.register(lib)
             uniffiCallbackInterfaceEventHandler.register(lib)
+            uniffiCallbackInterfaceFileProgressHandler.register(lib)
             // Loading of library with integrity check done.
             lib
         }
@@ -1082,35 +1331,125 @@ internal interface UniffiLib : Library {
     }
 
     // FFI functions
-    fun uniffi_openclipboard_ffi_fn_clone_clipboardnode(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+    fun uniffi_openclipboard_ffi_fn_clone_cancellationtoken(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): Pointer
+fun uniffi_openclipboard_ffi_fn_free_cancellationtoken(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_constructor_cancellationtoken_new(uniffi_out_err: UniffiRustCallStatus, 
+): Pointer
+fun uniffi_openclipboard_ffi_fn_method_cancellationtoken_cancel(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_cancellationtoken_is_cancelled(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): Byte
+fun uniffi_openclipboard_ffi_fn_clone_clipboardnode(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
 ): Pointer
 fun uniffi_openclipboard_ffi_fn_free_clipboardnode(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
 ): Unit
-fun uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_file(`ptr`: Pointer,`addr`: RustBuffer.ByValue,`filePath`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_accept_pending_request(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,`displayName`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Byte
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_active_sessions(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_clear_echo_suppressor(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_clear_peer_file_type_rules(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_file(`ptr`: Pointer,`addr`: RustBuffer.ByValue,`filePath`: RustBuffer.ByValue,`cancel`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
 ): Unit
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_text(`ptr`: Pointer,`addr`: RustBuffer.ByValue,`text`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
 ): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_diagnostics(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_disable_qr_pairing_listener(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
 ): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_disconnect_peer(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Byte
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_enable_qr_pairing_listener(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
 ): Unit
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_history(`ptr`: Pointer,`limit`: Int,uniffi_out_err: UniffiRustCallStatus, 
 ): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_history_for_peer(`ptr`: Pointer,`peerName`: RustBuffer.ByValue,`limit`: Int,uniffi_out_err: UniffiRustCallStatus, 
 ): RustBuffer.ByValue
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_peer_summary(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_is_discovering(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): Byte
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_last_clip_for_peer(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_linked_devices(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_list_peers(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_pair_via_qr(`ptr`: Pointer,`qrString`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
 ): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_peer_id(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
 ): RustBuffer.ByValue
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_pending_requests(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_recall_from_history(`ptr`: Pointer,`entryId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
 ): RustBuffer.ByValue
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_received_files(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_recent_errors(`ptr`: Pointer,`limit`: Int,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_remove_trusted_peer(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Byte
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_resend_current(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): Byte
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_resume_peer(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_send_clipboard_image(`ptr`: Pointer,`mime`: RustBuffer.ByValue,`width`: Int,`height`: Int,`bytes`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_send_clipboard_text(`ptr`: Pointer,`text`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
 ): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_send_file_to_peer(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,`filePath`: RustBuffer.ByValue,`progress`: Long,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_disconnect_grace_ms(`ptr`: Pointer,`graceMs`: Long,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_download_dir(`ptr`: Pointer,`dir`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_drain_on_stop_ms(`ptr`: Pointer,`timeoutMs`: Long,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_rate_limit(`ptr`: Pointer,`bytesPerSec`: Long,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_transfer_config(`ptr`: Pointer,`chunkSize`: Int,`window`: Int,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_type_rules(`ptr`: Pointer,`allow`: RustBuffer.ByValue,`deny`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_history_file(`ptr`: Pointer,`path`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_history_max_bytes(`ptr`: Pointer,`maxBytes`: Long,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_keepalive_ms(`ptr`: Pointer,`intervalMs`: Long,`timeoutMs`: Long,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_linked_device(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,`linked`: Byte,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_linked_devices_file(`ptr`: Pointer,`path`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_max_concurrent_file_sends(`ptr`: Pointer,`limit`: Int,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_max_incoming_file_size(`ptr`: Pointer,`maxBytes`: Long,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_partial_files_dir(`ptr`: Pointer,`dir`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_file_rate_limit(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,`bytesPerSec`: Long,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_file_type_rules(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,`allow`: RustBuffer.ByValue,`deny`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_sync_direction(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,`direction`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Byte
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_profile(`ptr`: Pointer,`trustPath`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_received_files_log(`ptr`: Pointer,`path`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_set_sync_whitespace(`ptr`: Pointer,`enabled`: Byte,uniffi_out_err: UniffiRustCallStatus, 
+): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_snapshot(`ptr`: Pointer,`historyLimit`: Int,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_start_discovery(`ptr`: Pointer,`deviceName`: RustBuffer.ByValue,`handler`: Long,uniffi_out_err: UniffiRustCallStatus, 
 ): Unit
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_start_listener(`ptr`: Pointer,`port`: Short,`handler`: Long,uniffi_out_err: UniffiRustCallStatus, 
 ): Unit
-fun uniffi_openclipboard_ffi_fn_method_clipboardnode_start_mesh(`ptr`: Pointer,`port`: Short,`deviceName`: RustBuffer.ByValue,`handler`: Long,`provider`: Long,`pollIntervalMs`: Long,uniffi_out_err: UniffiRustCallStatus, 
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_start_mesh(`ptr`: Pointer,`port`: Short,`deviceName`: RustBuffer.ByValue,`handler`: Long,`provider`: Long,`pollIntervalMs`: Long,`cancel`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
 ): Unit
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_start_sync(`ptr`: Pointer,`port`: Short,`deviceName`: RustBuffer.ByValue,`handler`: Long,uniffi_out_err: UniffiRustCallStatus, 
 ): Unit
@@ -1120,6 +1459,10 @@ fun uniffi_openclipboard_ffi_fn_method_clipboardnode_stop_discovery(`ptr`: Point
 ): Unit
 fun uniffi_openclipboard_ffi_fn_method_clipboardnode_stop_sync(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
 ): Unit
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_sync_trust(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): Int
+fun uniffi_openclipboard_ffi_fn_method_clipboardnode_terminate_session(`ptr`: Pointer,`peerId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Byte
 fun uniffi_openclipboard_ffi_fn_clone_identity(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
 ): Pointer
 fun uniffi_openclipboard_ffi_fn_free_identity(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
@@ -1150,6 +1493,8 @@ fun uniffi_openclipboard_ffi_fn_method_pairingpayload_peer_id(`ptr`: Pointer,uni
 ): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_method_pairingpayload_to_qr_string(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
 ): RustBuffer.ByValue
+fun uniffi_openclipboard_ffi_fn_method_pairingpayload_to_qr_string_compact(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_method_pairingpayload_version(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
 ): Byte
 fun uniffi_openclipboard_ffi_fn_clone_truststore(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
@@ -1170,8 +1515,12 @@ fun uniffi_openclipboard_ffi_fn_init_callback_vtable_discoveryhandler(`vtable`:
 ): Unit
 fun uniffi_openclipboard_ffi_fn_init_callback_vtable_eventhandler(`vtable`: UniffiVTableCallbackInterfaceEventHandler,
 ): Unit
+fun uniffi_openclipboard_ffi_fn_init_callback_vtable_fileprogresshandler(`vtable`: UniffiVTableCallbackInterfaceFileProgressHandler,
+): Unit
 fun uniffi_openclipboard_ffi_fn_func_clipboard_node_new(`identityPath`: RustBuffer.ByValue,`trustPath`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
 ): Pointer
+fun uniffi_openclipboard_ffi_fn_func_confirmation_transcript(`nonce`: RustBuffer.ByValue,`peerAId`: RustBuffer.ByValue,`peerBId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_func_default_identity_path(uniffi_out_err: UniffiRustCallStatus, 
 ): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_func_derive_confirmation_code(`nonce`: RustBuffer.ByValue,`peerAId`: RustBuffer.ByValue,`peerBId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
@@ -1186,10 +1535,14 @@ fun uniffi_openclipboard_ffi_fn_func_pairing_payload_create(`version`: Byte,`pee
 ): Pointer
 fun uniffi_openclipboard_ffi_fn_func_pairing_payload_from_qr_string(`s`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
 ): Pointer
+fun uniffi_openclipboard_ffi_fn_func_peer_visual(`peerId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_func_trust_store_default_path(uniffi_out_err: UniffiRustCallStatus, 
 ): RustBuffer.ByValue
 fun uniffi_openclipboard_ffi_fn_func_trust_store_open(`path`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
 ): Pointer
+fun uniffi_openclipboard_ffi_fn_func_verify_confirmation_code(`transcript`: RustBuffer.ByValue,`code`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+): Byte
 fun ffi_openclipboard_ffi_rustbuffer_alloc(`size`: Long,uniffi_out_err: UniffiRustCallStatus, 
 ): RustBuffer.ByValue
 fun ffi_openclipboard_ffi_rustbuffer_from_bytes(`bytes`: ForeignBytes.ByValue,uniffi_out_err: UniffiRustCallStatus, 
@@ -1319,6 +1672,9 @@ private fun uniffiCheckApiChecksums(lib: IntegrityCheckingUniffiLib) {
     if (lib.uniffi_openclipboard_ffi_checksum_func_clipboard_node_new() != 34405.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
+    if (lib.uniffi_openclipboard_ffi_checksum_func_confirmation_transcript() != 17304.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
     if (lib.uniffi_openclipboard_ffi_checksum_func_default_identity_path() != 36700.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
@@ -1340,79 +1696,217 @@ private fun uniffiCheckApiChecksums(lib: IntegrityCheckingUniffiLib) {
     if (lib.uniffi_openclipboard_ffi_checksum_func_pairing_payload_from_qr_string() != 47962.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
+    if (lib.uniffi_openclipboard_ffi_checksum_func_peer_visual() != 57004.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
     if (lib.uniffi_openclipboard_ffi_checksum_func_trust_store_default_path() != 22940.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
     if (lib.uniffi_openclipboard_ffi_checksum_func_trust_store_open() != 31823.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_connect_and_send_file() != 14460.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_func_verify_confirmation_code() != 12607.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_connect_and_send_text() != 48151.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_cancellationtoken_cancel() != 27502.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_disable_qr_pairing_listener() != 2416.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_cancellationtoken_is_cancelled() != 41445.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_enable_qr_pairing_listener() != 5068.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_accept_pending_request() != 708.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_history() != 16113.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_active_sessions() != 62813.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_history_for_peer() != 25297.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_clear_echo_suppressor() != 59991.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_pair_via_qr() != 39585.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_clear_peer_file_type_rules() != 44714.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_peer_id() != 3503.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_connect_and_send_file() != 47892.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_recall_from_history() != 13525.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_connect_and_send_text() != 48151.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_clipboard_text() != 29125.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_diagnostics() != 10731.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_discovery() != 28153.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_disable_qr_pairing_listener() != 2416.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_listener() != 7176.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_disconnect_peer() != 16645.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_mesh() != 46513.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_enable_qr_pairing_listener() != 5068.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_sync() != 58968.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_history() != 16113.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_stop() != 10276.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_history_for_peer() != 25297.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_stop_discovery() != 35689.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_peer_summary() != 63904.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_stop_sync() != 62029.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_is_discovering() != 28190.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_identity_info() != 2247.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_last_clip_for_peer() != 38905.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_identity_peer_id() != 44450.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_linked_devices() != 34477.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_identity_pubkey_b64() != 60910.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_list_peers() != 37860.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_identity_save() != 46504.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_pair_via_qr() != 39585.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_pairingpayload_identity_pk() != 27340.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_peer_id() != 3503.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_openclipboard_ffi_checksum_method_pairingpayload_lan_addrs() != 29153.toShort()) {
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_pending_requests() != 25529.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_recall_from_history() != 13525.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_received_files() != 20857.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_recent_errors() != 57195.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_remove_trusted_peer() != 20100.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_resend_current() != 31955.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_resume_peer() != 49290.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_clipboard_image() != 28572.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_clipboard_text() != 29125.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_file_to_peer() != 35416.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_disconnect_grace_ms() != 21211.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_download_dir() != 31085.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_drain_on_stop_ms() != 63620.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_rate_limit() != 28079.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_transfer_config() != 59733.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_type_rules() != 9513.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_history_file() != 57996.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_history_max_bytes() != 49098.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_keepalive_ms() != 24892.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_linked_device() != 10605.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_linked_devices_file() != 30814.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_max_concurrent_file_sends() != 25926.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_max_incoming_file_size() != 55629.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_partial_files_dir() != 20457.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_file_rate_limit() != 21982.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_file_type_rules() != 64971.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_sync_direction() != 40363.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_profile() != 30629.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_received_files_log() != 12204.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_sync_whitespace() != 34296.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_snapshot() != 64857.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_discovery() != 28153.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_listener() != 7176.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_mesh() != 62031.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_sync() != 58968.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_stop() != 10276.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_stop_discovery() != 35689.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_stop_sync() != 62029.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_sync_trust() != 16158.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardnode_terminate_session() != 39279.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_identity_info() != 2247.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_identity_peer_id() != 44450.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_identity_pubkey_b64() != 60910.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_identity_save() != 46504.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_pairingpayload_identity_pk() != 27340.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_pairingpayload_lan_addrs() != 29153.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
     if (lib.uniffi_openclipboard_ffi_checksum_method_pairingpayload_lan_port() != 37867.toShort()) {
@@ -1430,6 +1924,9 @@ private fun uniffiCheckApiChecksums(lib: IntegrityCheckingUniffiLib) {
     if (lib.uniffi_openclipboard_ffi_checksum_method_pairingpayload_to_qr_string() != 16160.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_pairingpayload_to_qr_string_compact() != 51408.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
     if (lib.uniffi_openclipboard_ffi_checksum_method_pairingpayload_version() != 57585.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
@@ -1445,12 +1942,21 @@ private fun uniffiCheckApiChecksums(lib: IntegrityCheckingUniffiLib) {
     if (lib.uniffi_openclipboard_ffi_checksum_method_truststore_remove() != 28281.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
+    if (lib.uniffi_openclipboard_ffi_checksum_constructor_cancellationtoken_new() != 62734.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
     if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardcallback_read_text() != 18804.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
     if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardcallback_write_text() != 24337.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardcallback_read_image() != 47649.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_clipboardcallback_write_image() != 35844.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
     if (lib.uniffi_openclipboard_ffi_checksum_method_discoveryhandler_on_peer_discovered() != 5960.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
@@ -1472,6 +1978,12 @@ private fun uniffiCheckApiChecksums(lib: IntegrityCheckingUniffiLib) {
     if (lib.uniffi_openclipboard_ffi_checksum_method_eventhandler_on_error() != 400.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_eventhandler_on_clipboard_image() != 37218.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_openclipboard_ffi_checksum_method_fileprogresshandler_on_progress() != 1648.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
 }
 
 /**
@@ -1917,46 +2429,16 @@ public object FfiConverterString: FfiConverter<String, RustBuffer.ByValue> {
 //
 
 
-public interface ClipboardNodeInterface {
-    
-    fun `connectAndSendFile`(`addr`: kotlin.String, `filePath`: kotlin.String)
-    
-    fun `connectAndSendText`(`addr`: kotlin.String, `text`: kotlin.String)
-    
-    fun `disableQrPairingListener`()
-    
-    fun `enableQrPairingListener`()
-    
-    fun `getClipboardHistory`(`limit`: kotlin.UInt): List<ClipboardHistoryEntry>
-    
-    fun `getClipboardHistoryForPeer`(`peerName`: kotlin.String, `limit`: kotlin.UInt): List<ClipboardHistoryEntry>
+public interface CancellationTokenInterface {
     
-    fun `pairViaQr`(`qrString`: kotlin.String): kotlin.String
-    
-    fun `peerId`(): kotlin.String
-    
-    fun `recallFromHistory`(`entryId`: kotlin.String): ClipboardHistoryEntry
-    
-    fun `sendClipboardText`(`text`: kotlin.String)
-    
-    fun `startDiscovery`(`deviceName`: kotlin.String, `handler`: DiscoveryHandler)
-    
-    fun `startListener`(`port`: kotlin.UShort, `handler`: EventHandler)
-    
-    fun `startMesh`(`port`: kotlin.UShort, `deviceName`: kotlin.String, `handler`: EventHandler, `provider`: ClipboardCallback, `pollIntervalMs`: kotlin.ULong)
-    
-    fun `startSync`(`port`: kotlin.UShort, `deviceName`: kotlin.String, `handler`: EventHandler)
-    
-    fun `stop`()
+    fun `cancel`()
     
-    fun `stopDiscovery`()
-    
-    fun `stopSync`()
+    fun `isCancelled`(): kotlin.Boolean
     
     companion object
 }
 
-open class ClipboardNode: Disposable, AutoCloseable, ClipboardNodeInterface
+open class CancellationToken: Disposable, AutoCloseable, CancellationTokenInterface
 {
 
     constructor(pointer: Pointer) {
@@ -1974,6 +2456,13 @@ open class ClipboardNode: Disposable, AutoCloseable, ClipboardNodeInterface
         this.pointer = null
         this.cleanable = UniffiLib.CLEANER.register(this, UniffiCleanAction(pointer))
     }
+    constructor() :
+        this(
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_constructor_cancellationtoken_new(
+        _status)
+}
+    )
 
     protected val pointer: Pointer?
     protected val cleanable: UniffiCleaner.Cleanable
@@ -2026,7 +2515,7 @@ open class ClipboardNode: Disposable, AutoCloseable, ClipboardNodeInterface
         override fun run() {
             pointer?.let { ptr ->
                 uniffiRustCall { status ->
-                    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_free_clipboardnode(ptr, status)
+                    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_free_cancellationtoken(ptr, status)
                 }
             }
         }
@@ -2034,132 +2523,961 @@ open class ClipboardNode: Disposable, AutoCloseable, ClipboardNodeInterface
 
     fun uniffiClonePointer(): Pointer {
         return uniffiRustCall() { status ->
-            UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_clone_clipboardnode(pointer!!, status)
+            UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_clone_cancellationtoken(pointer!!, status)
         }
     }
 
-    
-    @Throws(OpenClipboardException::class)override fun `connectAndSendFile`(`addr`: kotlin.String, `filePath`: kotlin.String)
-        = 
-    callWithPointer {
-    uniffiRustCallWithError(OpenClipboardException) { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_file(
-        it, FfiConverterString.lower(`addr`),FfiConverterString.lower(`filePath`),_status)
-}
-    }
-    
-    
-
-    
-    @Throws(OpenClipboardException::class)override fun `connectAndSendText`(`addr`: kotlin.String, `text`: kotlin.String)
-        = 
-    callWithPointer {
-    uniffiRustCallWithError(OpenClipboardException) { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_text(
-        it, FfiConverterString.lower(`addr`),FfiConverterString.lower(`text`),_status)
-}
-    }
-    
-    
-
-    
-    @Throws(OpenClipboardException::class)override fun `disableQrPairingListener`()
-        = 
-    callWithPointer {
-    uniffiRustCallWithError(OpenClipboardException) { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_disable_qr_pairing_listener(
-        it, _status)
-}
-    }
-    
-    
-
-    
-    @Throws(OpenClipboardException::class)override fun `enableQrPairingListener`()
+    override fun `cancel`()
         = 
     callWithPointer {
-    uniffiRustCallWithError(OpenClipboardException) { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_enable_qr_pairing_listener(
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_cancellationtoken_cancel(
         it, _status)
 }
     }
     
     
 
-    override fun `getClipboardHistory`(`limit`: kotlin.UInt): List<ClipboardHistoryEntry> {
-            return FfiConverterSequenceTypeClipboardHistoryEntry.lift(
+    override fun `isCancelled`(): kotlin.Boolean {
+            return FfiConverterBoolean.lift(
     callWithPointer {
     uniffiRustCall() { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_history(
-        it, FfiConverterUInt.lower(`limit`),_status)
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_cancellationtoken_is_cancelled(
+        it, _status)
 }
     }
     )
     }
     
 
-    override fun `getClipboardHistoryForPeer`(`peerName`: kotlin.String, `limit`: kotlin.UInt): List<ClipboardHistoryEntry> {
-            return FfiConverterSequenceTypeClipboardHistoryEntry.lift(
-    callWithPointer {
-    uniffiRustCall() { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_history_for_peer(
-        it, FfiConverterString.lower(`peerName`),FfiConverterUInt.lower(`limit`),_status)
-}
-    }
-    )
-    }
     
 
     
-    @Throws(OpenClipboardException::class)override fun `pairViaQr`(`qrString`: kotlin.String): kotlin.String {
-            return FfiConverterString.lift(
-    callWithPointer {
-    uniffiRustCallWithError(OpenClipboardException) { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_pair_via_qr(
-        it, FfiConverterString.lower(`qrString`),_status)
-}
-    }
-    )
-    }
     
-
-    override fun `peerId`(): kotlin.String {
-            return FfiConverterString.lift(
-    callWithPointer {
-    uniffiRustCall() { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_peer_id(
-        it, _status)
+    companion object
+    
 }
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeCancellationToken: FfiConverter<CancellationToken, Pointer> {
+
+    override fun lower(value: CancellationToken): Pointer {
+        return value.uniffiClonePointer()
     }
-    )
+
+    override fun lift(value: Pointer): CancellationToken {
+        return CancellationToken(value)
     }
-    
 
-    
-    @Throws(OpenClipboardException::class)override fun `recallFromHistory`(`entryId`: kotlin.String): ClipboardHistoryEntry {
-            return FfiConverterTypeClipboardHistoryEntry.lift(
-    callWithPointer {
-    uniffiRustCallWithError(OpenClipboardException) { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_recall_from_history(
-        it, FfiConverterString.lower(`entryId`),_status)
-}
+    override fun read(buf: ByteBuffer): CancellationToken {
+        // The Rust code always writes pointers as 8 bytes, and will
+        // fail to compile if they don't fit.
+        return lift(Pointer(buf.getLong()))
     }
-    )
+
+    override fun allocationSize(value: CancellationToken) = 8UL
+
+    override fun write(value: CancellationToken, buf: ByteBuffer) {
+        // The Rust code always expects pointers written as 8 bytes,
+        // and will fail to compile if they don't fit.
+        buf.putLong(Pointer.nativeValue(lower(value)))
     }
-    
+}
 
+
+// This template implements a class for working with a Rust struct via a Pointer/Arc<T>
+// to the live Rust struct on the other side of the FFI.
+//
+// Each instance implements core operations for working with the Rust `Arc<T>` and the
+// Kotlin Pointer to work with the live Rust struct on the other side of the FFI.
+//
+// There's some subtlety here, because we have to be careful not to operate on a Rust
+// struct after it has been dropped, and because we must expose a public API for freeing
+// theq Kotlin wrapper object in lieu of reliable finalizers. The core requirements are:
+//
+//   * Each instance holds an opaque pointer to the underlying Rust struct.
+//     Method calls need to read this pointer from the object's state and pass it in to
+//     the Rust FFI.
+//
+//   * When an instance is no longer needed, its pointer should be passed to a
+//     special destructor function provided by the Rust FFI, which will drop the
+//     underlying Rust struct.
+//
+//   * Given an instance, calling code is expected to call the special
+//     `destroy` method in order to free it after use, either by calling it explicitly
+//     or by using a higher-level helper like the `use` method. Failing to do so risks
+//     leaking the underlying Rust struct.
+//
+//   * We can't assume that calling code will do the right thing, and must be prepared
+//     to handle Kotlin method calls executing concurrently with or even after a call to
+//     `destroy`, and to handle multiple (possibly concurrent!) calls to `destroy`.
+//
+//   * We must never allow Rust code to operate on the underlying Rust struct after
+//     the destructor has been called, and must never call the destructor more than once.
+//     Doing so may trigger memory unsafety.
+//
+//   * To mitigate many of the risks of leaking memory and use-after-free unsafety, a `Cleaner`
+//     is implemented to call the destructor when the Kotlin object becomes unreachable.
+//     This is done in a background thread. This is not a panacea, and client code should be aware that
+//      1. the thread may starve if some there are objects that have poorly performing
+//     `drop` methods or do significant work in their `drop` methods.
+//      2. the thread is shared across the whole library. This can be tuned by using `android_cleaner = true`,
+//         or `android = true` in the [`kotlin` section of the `uniffi.toml` file](https://mozilla.github.io/uniffi-rs/kotlin/configuration.html).
+//
+// If we try to implement this with mutual exclusion on access to the pointer, there is the
+// possibility of a race between a method call and a concurrent call to `destroy`:
+//
+//    * Thread A starts a method call, reads the value of the pointer, but is interrupted
+//      before it can pass the pointer over the FFI to Rust.
+//    * Thread B calls `destroy` and frees the underlying Rust struct.
+//    * Thread A resumes, passing the already-read pointer value to Rust and triggering
+//      a use-after-free.
+//
+// One possible solution would be to use a `ReadWriteLock`, with each method call taking
+// a read lock (and thus allowed to run concurrently) and the special `destroy` method
+// taking a write lock (and thus blocking on live method calls). However, we aim not to
+// generate methods with any hidden blocking semantics, and a `destroy` method that might
+// block if called incorrectly seems to meet that bar.
+//
+// So, we achieve our goals by giving each instance an associated `AtomicLong` counter to track
+// the number of in-flight method calls, and an `AtomicBoolean` flag to indicate whether `destroy`
+// has been called. These are updated according to the following rules:
+//
+//    * The initial value of the counter is 1, indicating a live object with no in-flight calls.
+//      The initial value for the flag is false.
+//
+//    * At the start of each method call, we atomically check the counter.
+//      If it is 0 then the underlying Rust struct has already been destroyed and the call is aborted.
+//      If it is nonzero them we atomically increment it by 1 and proceed with the method call.
+//
+//    * At the end of each method call, we atomically decrement and check the counter.
+//      If it has reached zero then we destroy the underlying Rust struct.
+//
+//    * When `destroy` is called, we atomically flip the flag from false to true.
+//      If the flag was already true we silently fail.
+//      Otherwise we atomically decrement and check the counter.
+//      If it has reached zero then we destroy the underlying Rust struct.
+//
+// Astute readers may observe that this all sounds very similar to the way that Rust's `Arc<T>` works,
+// and indeed it is, with the addition of a flag to guard against multiple calls to `destroy`.
+//
+// The overall effect is that the underlying Rust struct is destroyed only when `destroy` has been
+// called *and* all in-flight method calls have completed, avoiding violating any of the expectations
+// of the underlying Rust code.
+//
+// This makes a cleaner a better alternative to _not_ calling `destroy()` as
+// and when the object is finished with, but the abstraction is not perfect: if the Rust object's `drop`
+// method is slow, and/or there are many objects to cleanup, and it's on a low end Android device, then the cleaner
+// thread may be starved, and the app will leak memory.
+//
+// In this case, `destroy`ing manually may be a better solution.
+//
+// The cleaner can live side by side with the manual calling of `destroy`. In the order of responsiveness, uniffi objects
+// with Rust peers are reclaimed:
+//
+// 1. By calling the `destroy` method of the object, which calls `rustObject.free()`. If that doesn't happen:
+// 2. When the object becomes unreachable, AND the Cleaner thread gets to call `rustObject.free()`. If the thread is starved then:
+// 3. The memory is reclaimed when the process terminates.
+//
+// [1] https://stackoverflow.com/questions/24376768/can-java-finalize-an-object-when-it-is-still-in-scope/24380219
+//
+
+
+public interface ClipboardNodeInterface {
+    
+    fun `acceptPendingRequest`(`peerId`: kotlin.String, `displayName`: kotlin.String): kotlin.Boolean
+    
+    fun `activeSessions`(): List<SessionInfo>
+    
+    fun `clearEchoSuppressor`()
+    
+    fun `clearPeerFileTypeRules`(`peerId`: kotlin.String)
+    
+    fun `connectAndSendFile`(`addr`: kotlin.String, `filePath`: kotlin.String, `cancel`: CancellationToken? = null)
+    
+    fun `connectAndSendText`(`addr`: kotlin.String, `text`: kotlin.String)
+    
+    fun `diagnostics`(): kotlin.String
+    
+    fun `disableQrPairingListener`()
+    
+    fun `disconnectPeer`(`peerId`: kotlin.String): kotlin.Boolean
+    
+    fun `enableQrPairingListener`()
+    
+    fun `getClipboardHistory`(`limit`: kotlin.UInt): List<ClipboardHistoryEntry>
+    
+    fun `getClipboardHistoryForPeer`(`peerName`: kotlin.String, `limit`: kotlin.UInt): List<ClipboardHistoryEntry>
+    
+    fun `getClipboardPeerSummary`(): List<PeerClipSummary>
+    
+    fun `isDiscovering`(): kotlin.Boolean
+    
+    fun `lastClipForPeer`(`peerId`: kotlin.String): kotlin.String?
+    
+    fun `linkedDevices`(): List<kotlin.String>
+    
+    fun `listPeers`(): List<PeerEntry>
+    
+    fun `pairViaQr`(`qrString`: kotlin.String): kotlin.String
+    
+    fun `peerId`(): kotlin.String
+    
+    fun `pendingRequests`(): List<PendingRequest>
+    
+    fun `recallFromHistory`(`entryId`: kotlin.String): ClipboardHistoryEntry
+    
+    fun `receivedFiles`(): List<ReceivedFileEntry>
+    
+    fun `recentErrors`(`limit`: kotlin.UInt): List<ErrorEntry>
+    
+    fun `removeTrustedPeer`(`peerId`: kotlin.String): kotlin.Boolean
+    
+    fun `resendCurrent`(): kotlin.Boolean
+    
+    fun `resumePeer`(`peerId`: kotlin.String)
+    
+    fun `sendClipboardImage`(`mime`: kotlin.String, `width`: kotlin.UInt, `height`: kotlin.UInt, `bytes`: List<kotlin.UByte>)
+    
+    fun `sendClipboardText`(`text`: kotlin.String)
+    
+    fun `sendFileToPeer`(`peerId`: kotlin.String, `filePath`: kotlin.String, `progress`: FileProgressHandler)
+    
+    fun `setDisconnectGraceMs`(`graceMs`: kotlin.ULong)
+    
+    fun `setDownloadDir`(`dir`: kotlin.String)
+    
+    fun `setDrainOnStopMs`(`timeoutMs`: kotlin.ULong)
+    
+    fun `setFileRateLimit`(`bytesPerSec`: kotlin.ULong)
+    
+    fun `setFileTransferConfig`(`chunkSize`: kotlin.UInt, `window`: kotlin.UInt)
+    
+    fun `setFileTypeRules`(`allow`: List<kotlin.String>, `deny`: List<kotlin.String>)
+    
+    fun `setHistoryFile`(`path`: kotlin.String?)
+    
+    fun `setHistoryMaxBytes`(`maxBytes`: kotlin.ULong)
+    
+    fun `setKeepaliveMs`(`intervalMs`: kotlin.ULong, `timeoutMs`: kotlin.ULong)
+    
+    fun `setLinkedDevice`(`peerId`: kotlin.String, `linked`: kotlin.Boolean)
+    
+    fun `setLinkedDevicesFile`(`path`: kotlin.String?)
+    
+    fun `setMaxConcurrentFileSends`(`limit`: kotlin.UInt)
+    
+    fun `setMaxIncomingFileSize`(`maxBytes`: kotlin.ULong)
+    
+    fun `setPartialFilesDir`(`dir`: kotlin.String?)
+    
+    fun `setPeerFileRateLimit`(`peerId`: kotlin.String, `bytesPerSec`: kotlin.ULong)
+    
+    fun `setPeerFileTypeRules`(`peerId`: kotlin.String, `allow`: List<kotlin.String>, `deny`: List<kotlin.String>)
+    
+    fun `setPeerSyncDirection`(`peerId`: kotlin.String, `direction`: SyncDirection): kotlin.Boolean
+    
+    fun `setProfile`(`trustPath`: kotlin.String)
+    
+    fun `setReceivedFilesLog`(`path`: kotlin.String?)
+    
+    fun `setSyncWhitespace`(`enabled`: kotlin.Boolean)
+    
+    fun `snapshot`(`historyLimit`: kotlin.UInt): NodeSnapshot
+    
+    fun `startDiscovery`(`deviceName`: kotlin.String, `handler`: DiscoveryHandler)
+    
+    fun `startListener`(`port`: kotlin.UShort, `handler`: EventHandler)
+    
+    fun `startMesh`(`port`: kotlin.UShort, `deviceName`: kotlin.String, `handler`: EventHandler, `provider`: ClipboardCallback, `pollIntervalMs`: kotlin.ULong, `cancel`: CancellationToken? = null)
+    
+    fun `startSync`(`port`: kotlin.UShort, `deviceName`: kotlin.String, `handler`: EventHandler)
+    
+    fun `stop`()
+    
+    fun `stopDiscovery`()
+    
+    fun `stopSync`()
+    
+    fun `syncTrust`(): kotlin.UInt
+    
+    fun `terminateSession`(`peerId`: kotlin.String): kotlin.Boolean
+    
+    companion object
+}
+
+open class ClipboardNode: Disposable, AutoCloseable, ClipboardNodeInterface
+{
+
+    constructor(pointer: Pointer) {
+        this.pointer = pointer
+        this.cleanable = UniffiLib.CLEANER.register(this, UniffiCleanAction(pointer))
+    }
+
+    /**
+     * This constructor can be used to instantiate a fake object. Only used for tests. Any
+     * attempt to actually use an object constructed this way will fail as there is no
+     * connected Rust object.
+     */
+    @Suppress("UNUSED_PARAMETER")
+    constructor(noPointer: NoPointer) {
+        this.pointer = null
+        this.cleanable = UniffiLib.CLEANER.register(this, UniffiCleanAction(pointer))
+    }
+
+    protected val pointer: Pointer?
+    protected val cleanable: UniffiCleaner.Cleanable
+
+    private val wasDestroyed = AtomicBoolean(false)
+    private val callCounter = AtomicLong(1)
+
+    override fun destroy() {
+        // Only allow a single call to this method.
+        // TODO: maybe we should log a warning if called more than once?
+        if (this.wasDestroyed.compareAndSet(false, true)) {
+            // This decrement always matches the initial count of 1 given at creation time.
+            if (this.callCounter.decrementAndGet() == 0L) {
+                cleanable.clean()
+            }
+        }
+    }
+
+    @Synchronized
+    override fun close() {
+        this.destroy()
+    }
+
+    internal inline fun <R> callWithPointer(block: (ptr: Pointer) -> R): R {
+        // Check and increment the call counter, to keep the object alive.
+        // This needs a compare-and-set retry loop in case of concurrent updates.
+        do {
+            val c = this.callCounter.get()
+            if (c == 0L) {
+                throw IllegalStateException("${this.javaClass.simpleName} object has already been destroyed")
+            }
+            if (c == Long.MAX_VALUE) {
+                throw IllegalStateException("${this.javaClass.simpleName} call counter would overflow")
+            }
+        } while (! this.callCounter.compareAndSet(c, c + 1L))
+        // Now we can safely do the method call without the pointer being freed concurrently.
+        try {
+            return block(this.uniffiClonePointer())
+        } finally {
+            // This decrement always matches the increment we performed above.
+            if (this.callCounter.decrementAndGet() == 0L) {
+                cleanable.clean()
+            }
+        }
+    }
+
+    // Use a static inner class instead of a closure so as not to accidentally
+    // capture `this` as part of the cleanable's action.
+    private class UniffiCleanAction(private val pointer: Pointer?) : Runnable {
+        override fun run() {
+            pointer?.let { ptr ->
+                uniffiRustCall { status ->
+                    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_free_clipboardnode(ptr, status)
+                }
+            }
+        }
+    }
+
+    fun uniffiClonePointer(): Pointer {
+        return uniffiRustCall() { status ->
+            UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_clone_clipboardnode(pointer!!, status)
+        }
+    }
+
+    
+    @Throws(OpenClipboardException::class)override fun `acceptPendingRequest`(`peerId`: kotlin.String, `displayName`: kotlin.String): kotlin.Boolean {
+            return FfiConverterBoolean.lift(
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_accept_pending_request(
+        it, FfiConverterString.lower(`peerId`),FfiConverterString.lower(`displayName`),_status)
+}
+    }
+    )
+    }
+    
+
+    override fun `activeSessions`(): List<SessionInfo> {
+            return FfiConverterSequenceTypeSessionInfo.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_active_sessions(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    override fun `clearEchoSuppressor`()
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_clear_echo_suppressor(
+        it, _status)
+}
+    }
+    
+    
+
+    override fun `clearPeerFileTypeRules`(`peerId`: kotlin.String)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_clear_peer_file_type_rules(
+        it, FfiConverterString.lower(`peerId`),_status)
+}
+    }
+    
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `connectAndSendFile`(`addr`: kotlin.String, `filePath`: kotlin.String, `cancel`: CancellationToken?)
+        = 
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_file(
+        it, FfiConverterString.lower(`addr`),FfiConverterString.lower(`filePath`),FfiConverterOptionalTypeCancellationToken.lower(`cancel`),_status)
+}
+    }
+    
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `connectAndSendText`(`addr`: kotlin.String, `text`: kotlin.String)
+        = 
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_text(
+        it, FfiConverterString.lower(`addr`),FfiConverterString.lower(`text`),_status)
+}
+    }
+    
+    
+
+    override fun `diagnostics`(): kotlin.String {
+            return FfiConverterString.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_diagnostics(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `disableQrPairingListener`()
+        = 
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_disable_qr_pairing_listener(
+        it, _status)
+}
+    }
+    
+    
+
+    override fun `disconnectPeer`(`peerId`: kotlin.String): kotlin.Boolean {
+            return FfiConverterBoolean.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_disconnect_peer(
+        it, FfiConverterString.lower(`peerId`),_status)
+}
+    }
+    )
+    }
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `enableQrPairingListener`()
+        = 
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_enable_qr_pairing_listener(
+        it, _status)
+}
+    }
+    
+    
+
+    override fun `getClipboardHistory`(`limit`: kotlin.UInt): List<ClipboardHistoryEntry> {
+            return FfiConverterSequenceTypeClipboardHistoryEntry.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_history(
+        it, FfiConverterUInt.lower(`limit`),_status)
+}
+    }
+    )
+    }
+    
+
+    override fun `getClipboardHistoryForPeer`(`peerName`: kotlin.String, `limit`: kotlin.UInt): List<ClipboardHistoryEntry> {
+            return FfiConverterSequenceTypeClipboardHistoryEntry.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_history_for_peer(
+        it, FfiConverterString.lower(`peerName`),FfiConverterUInt.lower(`limit`),_status)
+}
+    }
+    )
+    }
+    
+
+    override fun `getClipboardPeerSummary`(): List<PeerClipSummary> {
+            return FfiConverterSequenceTypePeerClipSummary.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_peer_summary(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    override fun `isDiscovering`(): kotlin.Boolean {
+            return FfiConverterBoolean.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_is_discovering(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    override fun `lastClipForPeer`(`peerId`: kotlin.String): kotlin.String? {
+            return FfiConverterOptionalString.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_last_clip_for_peer(
+        it, FfiConverterString.lower(`peerId`),_status)
+}
+    }
+    )
+    }
+    
+
+    override fun `linkedDevices`(): List<kotlin.String> {
+            return FfiConverterSequenceString.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_linked_devices(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    override fun `listPeers`(): List<PeerEntry> {
+            return FfiConverterSequenceTypePeerEntry.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_list_peers(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `pairViaQr`(`qrString`: kotlin.String): kotlin.String {
+            return FfiConverterString.lift(
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_pair_via_qr(
+        it, FfiConverterString.lower(`qrString`),_status)
+}
+    }
+    )
+    }
+    
+
+    override fun `peerId`(): kotlin.String {
+            return FfiConverterString.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_peer_id(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    override fun `pendingRequests`(): List<PendingRequest> {
+            return FfiConverterSequenceTypePendingRequest.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_pending_requests(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `recallFromHistory`(`entryId`: kotlin.String): ClipboardHistoryEntry {
+            return FfiConverterTypeClipboardHistoryEntry.lift(
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_recall_from_history(
+        it, FfiConverterString.lower(`entryId`),_status)
+}
+    }
+    )
+    }
+    
+
+    override fun `receivedFiles`(): List<ReceivedFileEntry> {
+            return FfiConverterSequenceTypeReceivedFileEntry.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_received_files(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    override fun `recentErrors`(`limit`: kotlin.UInt): List<ErrorEntry> {
+            return FfiConverterSequenceTypeErrorEntry.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_recent_errors(
+        it, FfiConverterUInt.lower(`limit`),_status)
+}
+    }
+    )
+    }
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `removeTrustedPeer`(`peerId`: kotlin.String): kotlin.Boolean {
+            return FfiConverterBoolean.lift(
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_remove_trusted_peer(
+        it, FfiConverterString.lower(`peerId`),_status)
+}
+    }
+    )
+    }
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `resendCurrent`(): kotlin.Boolean {
+            return FfiConverterBoolean.lift(
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_resend_current(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    override fun `resumePeer`(`peerId`: kotlin.String)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_resume_peer(
+        it, FfiConverterString.lower(`peerId`),_status)
+}
+    }
+    
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `sendClipboardImage`(`mime`: kotlin.String, `width`: kotlin.UInt, `height`: kotlin.UInt, `bytes`: List<kotlin.UByte>)
+        = 
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_send_clipboard_image(
+        it, FfiConverterString.lower(`mime`),FfiConverterUInt.lower(`width`),FfiConverterUInt.lower(`height`),FfiConverterSequenceUByte.lower(`bytes`),_status)
+}
+    }
+    
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `sendClipboardText`(`text`: kotlin.String)
+        = 
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_send_clipboard_text(
+        it, FfiConverterString.lower(`text`),_status)
+}
+    }
+    
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `sendFileToPeer`(`peerId`: kotlin.String, `filePath`: kotlin.String, `progress`: FileProgressHandler)
+        = 
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_send_file_to_peer(
+        it, FfiConverterString.lower(`peerId`),FfiConverterString.lower(`filePath`),FfiConverterTypeFileProgressHandler.lower(`progress`),_status)
+}
+    }
+    
+    
+
+    override fun `setDisconnectGraceMs`(`graceMs`: kotlin.ULong)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_disconnect_grace_ms(
+        it, FfiConverterULong.lower(`graceMs`),_status)
+}
+    }
+    
+    
+
+    override fun `setDownloadDir`(`dir`: kotlin.String)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_download_dir(
+        it, FfiConverterString.lower(`dir`),_status)
+}
+    }
+    
+    
+
+    override fun `setDrainOnStopMs`(`timeoutMs`: kotlin.ULong)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_drain_on_stop_ms(
+        it, FfiConverterULong.lower(`timeoutMs`),_status)
+}
+    }
+    
+    
+
+    override fun `setFileRateLimit`(`bytesPerSec`: kotlin.ULong)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_rate_limit(
+        it, FfiConverterULong.lower(`bytesPerSec`),_status)
+}
+    }
+    
+    
+
+    override fun `setFileTransferConfig`(`chunkSize`: kotlin.UInt, `window`: kotlin.UInt)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_transfer_config(
+        it, FfiConverterUInt.lower(`chunkSize`),FfiConverterUInt.lower(`window`),_status)
+}
+    }
+    
+    
+
+    override fun `setFileTypeRules`(`allow`: List<kotlin.String>, `deny`: List<kotlin.String>)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_type_rules(
+        it, FfiConverterSequenceString.lower(`allow`),FfiConverterSequenceString.lower(`deny`),_status)
+}
+    }
+    
+    
+
+    override fun `setHistoryFile`(`path`: kotlin.String?)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_history_file(
+        it, FfiConverterOptionalString.lower(`path`),_status)
+}
+    }
+    
+    
+
+    override fun `setHistoryMaxBytes`(`maxBytes`: kotlin.ULong)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_history_max_bytes(
+        it, FfiConverterULong.lower(`maxBytes`),_status)
+}
+    }
+    
+    
+
+    override fun `setKeepaliveMs`(`intervalMs`: kotlin.ULong, `timeoutMs`: kotlin.ULong)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_keepalive_ms(
+        it, FfiConverterULong.lower(`intervalMs`),FfiConverterULong.lower(`timeoutMs`),_status)
+}
+    }
+    
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `setLinkedDevice`(`peerId`: kotlin.String, `linked`: kotlin.Boolean)
+        = 
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_linked_device(
+        it, FfiConverterString.lower(`peerId`),FfiConverterBoolean.lower(`linked`),_status)
+}
+    }
+    
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `setLinkedDevicesFile`(`path`: kotlin.String?)
+        = 
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_linked_devices_file(
+        it, FfiConverterOptionalString.lower(`path`),_status)
+}
+    }
+    
+    
+
+    override fun `setMaxConcurrentFileSends`(`limit`: kotlin.UInt)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_max_concurrent_file_sends(
+        it, FfiConverterUInt.lower(`limit`),_status)
+}
+    }
+    
+    
+
+    override fun `setMaxIncomingFileSize`(`maxBytes`: kotlin.ULong)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_max_incoming_file_size(
+        it, FfiConverterULong.lower(`maxBytes`),_status)
+}
+    }
+    
+    
+
+    override fun `setPartialFilesDir`(`dir`: kotlin.String?)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_partial_files_dir(
+        it, FfiConverterOptionalString.lower(`dir`),_status)
+}
+    }
+    
+    
+
+    override fun `setPeerFileRateLimit`(`peerId`: kotlin.String, `bytesPerSec`: kotlin.ULong)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_file_rate_limit(
+        it, FfiConverterString.lower(`peerId`),FfiConverterULong.lower(`bytesPerSec`),_status)
+}
+    }
+    
+    
+
+    override fun `setPeerFileTypeRules`(`peerId`: kotlin.String, `allow`: List<kotlin.String>, `deny`: List<kotlin.String>)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_file_type_rules(
+        it, FfiConverterString.lower(`peerId`),FfiConverterSequenceString.lower(`allow`),FfiConverterSequenceString.lower(`deny`),_status)
+}
+    }
+    
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `setPeerSyncDirection`(`peerId`: kotlin.String, `direction`: SyncDirection): kotlin.Boolean {
+            return FfiConverterBoolean.lift(
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_sync_direction(
+        it, FfiConverterString.lower(`peerId`),FfiConverterTypeSyncDirection.lower(`direction`),_status)
+}
+    }
+    )
+    }
     
-    @Throws(OpenClipboardException::class)override fun `sendClipboardText`(`text`: kotlin.String)
+
+    
+    @Throws(OpenClipboardException::class)override fun `setProfile`(`trustPath`: kotlin.String)
         = 
     callWithPointer {
     uniffiRustCallWithError(OpenClipboardException) { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_send_clipboard_text(
-        it, FfiConverterString.lower(`text`),_status)
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_profile(
+        it, FfiConverterString.lower(`trustPath`),_status)
+}
+    }
+    
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `setReceivedFilesLog`(`path`: kotlin.String?)
+        = 
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_received_files_log(
+        it, FfiConverterOptionalString.lower(`path`),_status)
+}
+    }
+    
+    
+
+    override fun `setSyncWhitespace`(`enabled`: kotlin.Boolean)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_set_sync_whitespace(
+        it, FfiConverterBoolean.lower(`enabled`),_status)
 }
     }
     
     
 
+    override fun `snapshot`(`historyLimit`: kotlin.UInt): NodeSnapshot {
+            return FfiConverterTypeNodeSnapshot.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_snapshot(
+        it, FfiConverterUInt.lower(`historyLimit`),_status)
+}
+    }
+    )
+    }
+    
+
     
     @Throws(OpenClipboardException::class)override fun `startDiscovery`(`deviceName`: kotlin.String, `handler`: DiscoveryHandler)
         = 
@@ -2185,12 +3503,12 @@ open class ClipboardNode: Disposable, AutoCloseable, ClipboardNodeInterface
     
 
     
-    @Throws(OpenClipboardException::class)override fun `startMesh`(`port`: kotlin.UShort, `deviceName`: kotlin.String, `handler`: EventHandler, `provider`: ClipboardCallback, `pollIntervalMs`: kotlin.ULong)
+    @Throws(OpenClipboardException::class)override fun `startMesh`(`port`: kotlin.UShort, `deviceName`: kotlin.String, `handler`: EventHandler, `provider`: ClipboardCallback, `pollIntervalMs`: kotlin.ULong, `cancel`: CancellationToken?)
         = 
     callWithPointer {
     uniffiRustCallWithError(OpenClipboardException) { _status ->
     UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_start_mesh(
-        it, FfiConverterUShort.lower(`port`),FfiConverterString.lower(`deviceName`),FfiConverterTypeEventHandler.lower(`handler`),FfiConverterTypeClipboardCallback.lower(`provider`),FfiConverterULong.lower(`pollIntervalMs`),_status)
+        it, FfiConverterUShort.lower(`port`),FfiConverterString.lower(`deviceName`),FfiConverterTypeEventHandler.lower(`handler`),FfiConverterTypeClipboardCallback.lower(`provider`),FfiConverterULong.lower(`pollIntervalMs`),FfiConverterOptionalTypeCancellationToken.lower(`cancel`),_status)
 }
     }
     
@@ -2241,6 +3559,30 @@ open class ClipboardNode: Disposable, AutoCloseable, ClipboardNodeInterface
     
     
 
+    override fun `syncTrust`(): kotlin.UInt {
+            return FfiConverterUInt.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_sync_trust(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    override fun `terminateSession`(`peerId`: kotlin.String): kotlin.Boolean {
+            return FfiConverterBoolean.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_clipboardnode_terminate_session(
+        it, FfiConverterString.lower(`peerId`),_status)
+}
+    }
+    )
+    }
+    
+
     
 
     
@@ -2670,6 +4012,8 @@ public interface PairingPayloadInterface {
     
     fun `toQrString`(): kotlin.String
     
+    fun `toQrStringCompact`(): kotlin.String
+    
     fun `version`(): kotlin.UByte
     
     companion object
@@ -2842,6 +4186,19 @@ open class PairingPayload: Disposable, AutoCloseable, PairingPayloadInterface
     }
     
 
+    
+    @Throws(OpenClipboardException::class)override fun `toQrStringCompact`(): kotlin.String {
+            return FfiConverterString.lift(
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_pairingpayload_to_qr_string_compact(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
     override fun `version`(): kotlin.UByte {
             return FfiConverterUByte.lift(
     callWithPointer {
@@ -3093,91 +4450,521 @@ open class TrustStore: Disposable, AutoCloseable, TrustStoreInterface
         it, FfiConverterString.lower(`peerId`),FfiConverterString.lower(`identityPkB64`),FfiConverterString.lower(`displayName`),_status)
 }
     }
-    
-    
+    
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `get`(`peerId`: kotlin.String): TrustRecord? {
+            return FfiConverterOptionalTypeTrustRecord.lift(
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_truststore_get(
+        it, FfiConverterString.lower(`peerId`),_status)
+}
+    }
+    )
+    }
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `list`(): List<TrustRecord> {
+            return FfiConverterSequenceTypeTrustRecord.lift(
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_truststore_list(
+        it, _status)
+}
+    }
+    )
+    }
+    
+
+    
+    @Throws(OpenClipboardException::class)override fun `remove`(`peerId`: kotlin.String): kotlin.Boolean {
+            return FfiConverterBoolean.lift(
+    callWithPointer {
+    uniffiRustCallWithError(OpenClipboardException) { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_truststore_remove(
+        it, FfiConverterString.lower(`peerId`),_status)
+}
+    }
+    )
+    }
+    
+
+    
+
+    
+    
+    companion object
+    
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeTrustStore: FfiConverter<TrustStore, Pointer> {
+
+    override fun lower(value: TrustStore): Pointer {
+        return value.uniffiClonePointer()
+    }
+
+    override fun lift(value: Pointer): TrustStore {
+        return TrustStore(value)
+    }
+
+    override fun read(buf: ByteBuffer): TrustStore {
+        // The Rust code always writes pointers as 8 bytes, and will
+        // fail to compile if they don't fit.
+        return lift(Pointer(buf.getLong()))
+    }
+
+    override fun allocationSize(value: TrustStore) = 8UL
+
+    override fun write(value: TrustStore, buf: ByteBuffer) {
+        // The Rust code always expects pointers written as 8 bytes,
+        // and will fail to compile if they don't fit.
+        buf.putLong(Pointer.nativeValue(lower(value)))
+    }
+}
+
+
+
+data class ClipboardHistoryEntry (
+    var `id`: kotlin.String, 
+    var `content`: kotlin.String, 
+    var `sourcePeer`: kotlin.String, 
+    var `originName`: kotlin.String? = null, 
+    var `timestamp`: kotlin.ULong, 
+    var `kind`: ClipboardHistoryKind = ClipboardHistoryKind.TEXT, 
+    var `image`: ClipboardImage? = null, 
+    var `filePath`: kotlin.String? = null, 
+    var `fileSize`: kotlin.ULong? = null
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeClipboardHistoryEntry: FfiConverterRustBuffer<ClipboardHistoryEntry> {
+    override fun read(buf: ByteBuffer): ClipboardHistoryEntry {
+        return ClipboardHistoryEntry(
+            FfiConverterString.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterOptionalString.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterTypeClipboardHistoryKind.read(buf),
+            FfiConverterOptionalTypeClipboardImage.read(buf),
+            FfiConverterOptionalString.read(buf),
+            FfiConverterOptionalULong.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: ClipboardHistoryEntry) = (
+            FfiConverterString.allocationSize(value.`id`) +
+            FfiConverterString.allocationSize(value.`content`) +
+            FfiConverterString.allocationSize(value.`sourcePeer`) +
+            FfiConverterOptionalString.allocationSize(value.`originName`) +
+            FfiConverterULong.allocationSize(value.`timestamp`) +
+            FfiConverterTypeClipboardHistoryKind.allocationSize(value.`kind`) +
+            FfiConverterOptionalTypeClipboardImage.allocationSize(value.`image`) +
+            FfiConverterOptionalString.allocationSize(value.`filePath`) +
+            FfiConverterOptionalULong.allocationSize(value.`fileSize`)
+    )
+
+    override fun write(value: ClipboardHistoryEntry, buf: ByteBuffer) {
+            FfiConverterString.write(value.`id`, buf)
+            FfiConverterString.write(value.`content`, buf)
+            FfiConverterString.write(value.`sourcePeer`, buf)
+            FfiConverterOptionalString.write(value.`originName`, buf)
+            FfiConverterULong.write(value.`timestamp`, buf)
+            FfiConverterTypeClipboardHistoryKind.write(value.`kind`, buf)
+            FfiConverterOptionalTypeClipboardImage.write(value.`image`, buf)
+            FfiConverterOptionalString.write(value.`filePath`, buf)
+            FfiConverterOptionalULong.write(value.`fileSize`, buf)
+    }
+}
+
+
+
+data class ClipboardImage (
+    var `mime`: kotlin.String, 
+    var `width`: kotlin.UInt, 
+    var `height`: kotlin.UInt, 
+    var `bytes`: List<kotlin.UByte>
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeClipboardImage: FfiConverterRustBuffer<ClipboardImage> {
+    override fun read(buf: ByteBuffer): ClipboardImage {
+        return ClipboardImage(
+            FfiConverterString.read(buf),
+            FfiConverterUInt.read(buf),
+            FfiConverterUInt.read(buf),
+            FfiConverterSequenceUByte.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: ClipboardImage) = (
+            FfiConverterString.allocationSize(value.`mime`) +
+            FfiConverterUInt.allocationSize(value.`width`) +
+            FfiConverterUInt.allocationSize(value.`height`) +
+            FfiConverterSequenceUByte.allocationSize(value.`bytes`)
+    )
+
+    override fun write(value: ClipboardImage, buf: ByteBuffer) {
+            FfiConverterString.write(value.`mime`, buf)
+            FfiConverterUInt.write(value.`width`, buf)
+            FfiConverterUInt.write(value.`height`, buf)
+            FfiConverterSequenceUByte.write(value.`bytes`, buf)
+    }
+}
+
+
+
+data class ConfirmationTranscript (
+    var `nonce`: List<kotlin.UByte>, 
+    var `peerAId`: kotlin.String, 
+    var `peerBId`: kotlin.String
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeConfirmationTranscript: FfiConverterRustBuffer<ConfirmationTranscript> {
+    override fun read(buf: ByteBuffer): ConfirmationTranscript {
+        return ConfirmationTranscript(
+            FfiConverterSequenceUByte.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterString.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: ConfirmationTranscript) = (
+            FfiConverterSequenceUByte.allocationSize(value.`nonce`) +
+            FfiConverterString.allocationSize(value.`peerAId`) +
+            FfiConverterString.allocationSize(value.`peerBId`)
+    )
+
+    override fun write(value: ConfirmationTranscript, buf: ByteBuffer) {
+            FfiConverterSequenceUByte.write(value.`nonce`, buf)
+            FfiConverterString.write(value.`peerAId`, buf)
+            FfiConverterString.write(value.`peerBId`, buf)
+    }
+}
+
+
+
+data class DeviceVisual (
+    var `colorHex`: kotlin.String, 
+    var `initials`: kotlin.String, 
+    var `identiconSeed`: kotlin.ULong
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeDeviceVisual: FfiConverterRustBuffer<DeviceVisual> {
+    override fun read(buf: ByteBuffer): DeviceVisual {
+        return DeviceVisual(
+            FfiConverterString.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterULong.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: DeviceVisual) = (
+            FfiConverterString.allocationSize(value.`colorHex`) +
+            FfiConverterString.allocationSize(value.`initials`) +
+            FfiConverterULong.allocationSize(value.`identiconSeed`)
+    )
+
+    override fun write(value: DeviceVisual, buf: ByteBuffer) {
+            FfiConverterString.write(value.`colorHex`, buf)
+            FfiConverterString.write(value.`initials`, buf)
+            FfiConverterULong.write(value.`identiconSeed`, buf)
+    }
+}
+
+
+
+data class ErrorEntry (
+    var `timestampMs`: kotlin.ULong, 
+    var `peerId`: kotlin.String?, 
+    var `category`: kotlin.String, 
+    var `message`: kotlin.String
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeErrorEntry: FfiConverterRustBuffer<ErrorEntry> {
+    override fun read(buf: ByteBuffer): ErrorEntry {
+        return ErrorEntry(
+            FfiConverterULong.read(buf),
+            FfiConverterOptionalString.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterString.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: ErrorEntry) = (
+            FfiConverterULong.allocationSize(value.`timestampMs`) +
+            FfiConverterOptionalString.allocationSize(value.`peerId`) +
+            FfiConverterString.allocationSize(value.`category`) +
+            FfiConverterString.allocationSize(value.`message`)
+    )
+
+    override fun write(value: ErrorEntry, buf: ByteBuffer) {
+            FfiConverterULong.write(value.`timestampMs`, buf)
+            FfiConverterOptionalString.write(value.`peerId`, buf)
+            FfiConverterString.write(value.`category`, buf)
+            FfiConverterString.write(value.`message`, buf)
+    }
+}
+
+
 
+data class IdentityInfo (
+    var `peerId`: kotlin.String, 
+    var `pubkeyB64`: kotlin.String
+) {
     
-    @Throws(OpenClipboardException::class)override fun `get`(`peerId`: kotlin.String): TrustRecord? {
-            return FfiConverterOptionalTypeTrustRecord.lift(
-    callWithPointer {
-    uniffiRustCallWithError(OpenClipboardException) { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_truststore_get(
-        it, FfiConverterString.lower(`peerId`),_status)
+    companion object
 }
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeIdentityInfo: FfiConverterRustBuffer<IdentityInfo> {
+    override fun read(buf: ByteBuffer): IdentityInfo {
+        return IdentityInfo(
+            FfiConverterString.read(buf),
+            FfiConverterString.read(buf),
+        )
     }
+
+    override fun allocationSize(value: IdentityInfo) = (
+            FfiConverterString.allocationSize(value.`peerId`) +
+            FfiConverterString.allocationSize(value.`pubkeyB64`)
     )
+
+    override fun write(value: IdentityInfo, buf: ByteBuffer) {
+            FfiConverterString.write(value.`peerId`, buf)
+            FfiConverterString.write(value.`pubkeyB64`, buf)
     }
-    
+}
+
 
+
+data class NodeSnapshot (
+    var `connected`: List<kotlin.String>, 
+    var `peers`: List<PeerEntry>, 
+    var `history`: List<ClipboardHistoryEntry>, 
+    var `historyLen`: kotlin.ULong, 
+    var `historyBytes`: kotlin.ULong, 
+    var `pendingRequests`: List<PendingRequest>, 
+    var `suspended`: List<kotlin.String>
+) {
     
-    @Throws(OpenClipboardException::class)override fun `list`(): List<TrustRecord> {
-            return FfiConverterSequenceTypeTrustRecord.lift(
-    callWithPointer {
-    uniffiRustCallWithError(OpenClipboardException) { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_truststore_list(
-        it, _status)
+    companion object
 }
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeNodeSnapshot: FfiConverterRustBuffer<NodeSnapshot> {
+    override fun read(buf: ByteBuffer): NodeSnapshot {
+        return NodeSnapshot(
+            FfiConverterSequenceString.read(buf),
+            FfiConverterSequenceTypePeerEntry.read(buf),
+            FfiConverterSequenceTypeClipboardHistoryEntry.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterSequenceTypePendingRequest.read(buf),
+            FfiConverterSequenceString.read(buf),
+        )
     }
+
+    override fun allocationSize(value: NodeSnapshot) = (
+            FfiConverterSequenceString.allocationSize(value.`connected`) +
+            FfiConverterSequenceTypePeerEntry.allocationSize(value.`peers`) +
+            FfiConverterSequenceTypeClipboardHistoryEntry.allocationSize(value.`history`) +
+            FfiConverterULong.allocationSize(value.`historyLen`) +
+            FfiConverterULong.allocationSize(value.`historyBytes`) +
+            FfiConverterSequenceTypePendingRequest.allocationSize(value.`pendingRequests`) +
+            FfiConverterSequenceString.allocationSize(value.`suspended`)
     )
+
+    override fun write(value: NodeSnapshot, buf: ByteBuffer) {
+            FfiConverterSequenceString.write(value.`connected`, buf)
+            FfiConverterSequenceTypePeerEntry.write(value.`peers`, buf)
+            FfiConverterSequenceTypeClipboardHistoryEntry.write(value.`history`, buf)
+            FfiConverterULong.write(value.`historyLen`, buf)
+            FfiConverterULong.write(value.`historyBytes`, buf)
+            FfiConverterSequenceTypePendingRequest.write(value.`pendingRequests`, buf)
+            FfiConverterSequenceString.write(value.`suspended`, buf)
     }
-    
+}
 
+
+
+data class PeerClipSummary (
+    var `peerId`: kotlin.String, 
+    var `count`: kotlin.UInt, 
+    var `lastTimestamp`: kotlin.ULong
+) {
     
-    @Throws(OpenClipboardException::class)override fun `remove`(`peerId`: kotlin.String): kotlin.Boolean {
-            return FfiConverterBoolean.lift(
-    callWithPointer {
-    uniffiRustCallWithError(OpenClipboardException) { _status ->
-    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_method_truststore_remove(
-        it, FfiConverterString.lower(`peerId`),_status)
+    companion object
 }
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypePeerClipSummary: FfiConverterRustBuffer<PeerClipSummary> {
+    override fun read(buf: ByteBuffer): PeerClipSummary {
+        return PeerClipSummary(
+            FfiConverterString.read(buf),
+            FfiConverterUInt.read(buf),
+            FfiConverterULong.read(buf),
+        )
     }
+
+    override fun allocationSize(value: PeerClipSummary) = (
+            FfiConverterString.allocationSize(value.`peerId`) +
+            FfiConverterUInt.allocationSize(value.`count`) +
+            FfiConverterULong.allocationSize(value.`lastTimestamp`)
     )
+
+    override fun write(value: PeerClipSummary, buf: ByteBuffer) {
+            FfiConverterString.write(value.`peerId`, buf)
+            FfiConverterUInt.write(value.`count`, buf)
+            FfiConverterULong.write(value.`lastTimestamp`, buf)
     }
-    
+}
 
-    
 
-    
+
+data class PeerEntry (
+    var `peerId`: kotlin.String, 
+    var `displayName`: kotlin.String, 
+    var `label`: kotlin.String, 
+    var `online`: kotlin.Boolean, 
+    var `lastAddr`: kotlin.String?, 
+    var `addresses`: List<kotlin.String>, 
+    var `deviceName`: kotlin.String?
+) {
     
     companion object
-    
 }
 
 /**
  * @suppress
  */
-public object FfiConverterTypeTrustStore: FfiConverter<TrustStore, Pointer> {
-
-    override fun lower(value: TrustStore): Pointer {
-        return value.uniffiClonePointer()
+public object FfiConverterTypePeerEntry: FfiConverterRustBuffer<PeerEntry> {
+    override fun read(buf: ByteBuffer): PeerEntry {
+        return PeerEntry(
+            FfiConverterString.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterBoolean.read(buf),
+            FfiConverterOptionalString.read(buf),
+            FfiConverterSequenceString.read(buf),
+            FfiConverterOptionalString.read(buf),
+        )
     }
 
-    override fun lift(value: Pointer): TrustStore {
-        return TrustStore(value)
+    override fun allocationSize(value: PeerEntry) = (
+            FfiConverterString.allocationSize(value.`peerId`) +
+            FfiConverterString.allocationSize(value.`displayName`) +
+            FfiConverterString.allocationSize(value.`label`) +
+            FfiConverterBoolean.allocationSize(value.`online`) +
+            FfiConverterOptionalString.allocationSize(value.`lastAddr`) +
+            FfiConverterSequenceString.allocationSize(value.`addresses`) +
+            FfiConverterOptionalString.allocationSize(value.`deviceName`)
+    )
+
+    override fun write(value: PeerEntry, buf: ByteBuffer) {
+            FfiConverterString.write(value.`peerId`, buf)
+            FfiConverterString.write(value.`displayName`, buf)
+            FfiConverterString.write(value.`label`, buf)
+            FfiConverterBoolean.write(value.`online`, buf)
+            FfiConverterOptionalString.write(value.`lastAddr`, buf)
+            FfiConverterSequenceString.write(value.`addresses`, buf)
+            FfiConverterOptionalString.write(value.`deviceName`, buf)
     }
+}
 
-    override fun read(buf: ByteBuffer): TrustStore {
-        // The Rust code always writes pointers as 8 bytes, and will
-        // fail to compile if they don't fit.
-        return lift(Pointer(buf.getLong()))
+
+
+data class PendingRequest (
+    var `peerId`: kotlin.String, 
+    var `fingerprint`: kotlin.String, 
+    var `identityPkB64`: kotlin.String, 
+    var `addr`: kotlin.String?, 
+    var `timestampMs`: kotlin.ULong
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypePendingRequest: FfiConverterRustBuffer<PendingRequest> {
+    override fun read(buf: ByteBuffer): PendingRequest {
+        return PendingRequest(
+            FfiConverterString.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterOptionalString.read(buf),
+            FfiConverterULong.read(buf),
+        )
     }
 
-    override fun allocationSize(value: TrustStore) = 8UL
+    override fun allocationSize(value: PendingRequest) = (
+            FfiConverterString.allocationSize(value.`peerId`) +
+            FfiConverterString.allocationSize(value.`fingerprint`) +
+            FfiConverterString.allocationSize(value.`identityPkB64`) +
+            FfiConverterOptionalString.allocationSize(value.`addr`) +
+            FfiConverterULong.allocationSize(value.`timestampMs`)
+    )
 
-    override fun write(value: TrustStore, buf: ByteBuffer) {
-        // The Rust code always expects pointers written as 8 bytes,
-        // and will fail to compile if they don't fit.
-        buf.putLong(Pointer.nativeValue(lower(value)))
+    override fun write(value: PendingRequest, buf: ByteBuffer) {
+            FfiConverterString.write(value.`peerId`, buf)
+            FfiConverterString.write(value.`fingerprint`, buf)
+            FfiConverterString.write(value.`identityPkB64`, buf)
+            FfiConverterOptionalString.write(value.`addr`, buf)
+            FfiConverterULong.write(value.`timestampMs`, buf)
     }
 }
 
 
 
-data class ClipboardHistoryEntry (
-    var `id`: kotlin.String, 
-    var `content`: kotlin.String, 
-    var `sourcePeer`: kotlin.String, 
-    var `timestamp`: kotlin.ULong
+data class ReceivedFileEntry (
+    var `peerId`: kotlin.String, 
+    var `name`: kotlin.String, 
+    var `size`: kotlin.ULong, 
+    var `hash`: kotlin.String, 
+    var `path`: kotlin.String, 
+    var `receivedAtMs`: kotlin.ULong
 ) {
     
     companion object
@@ -3186,36 +4973,45 @@ data class ClipboardHistoryEntry (
 /**
  * @suppress
  */
-public object FfiConverterTypeClipboardHistoryEntry: FfiConverterRustBuffer<ClipboardHistoryEntry> {
-    override fun read(buf: ByteBuffer): ClipboardHistoryEntry {
-        return ClipboardHistoryEntry(
+public object FfiConverterTypeReceivedFileEntry: FfiConverterRustBuffer<ReceivedFileEntry> {
+    override fun read(buf: ByteBuffer): ReceivedFileEntry {
+        return ReceivedFileEntry(
+            FfiConverterString.read(buf),
             FfiConverterString.read(buf),
+            FfiConverterULong.read(buf),
             FfiConverterString.read(buf),
             FfiConverterString.read(buf),
             FfiConverterULong.read(buf),
         )
     }
 
-    override fun allocationSize(value: ClipboardHistoryEntry) = (
-            FfiConverterString.allocationSize(value.`id`) +
-            FfiConverterString.allocationSize(value.`content`) +
-            FfiConverterString.allocationSize(value.`sourcePeer`) +
-            FfiConverterULong.allocationSize(value.`timestamp`)
+    override fun allocationSize(value: ReceivedFileEntry) = (
+            FfiConverterString.allocationSize(value.`peerId`) +
+            FfiConverterString.allocationSize(value.`name`) +
+            FfiConverterULong.allocationSize(value.`size`) +
+            FfiConverterString.allocationSize(value.`hash`) +
+            FfiConverterString.allocationSize(value.`path`) +
+            FfiConverterULong.allocationSize(value.`receivedAtMs`)
     )
 
-    override fun write(value: ClipboardHistoryEntry, buf: ByteBuffer) {
-            FfiConverterString.write(value.`id`, buf)
-            FfiConverterString.write(value.`content`, buf)
-            FfiConverterString.write(value.`sourcePeer`, buf)
-            FfiConverterULong.write(value.`timestamp`, buf)
+    override fun write(value: ReceivedFileEntry, buf: ByteBuffer) {
+            FfiConverterString.write(value.`peerId`, buf)
+            FfiConverterString.write(value.`name`, buf)
+            FfiConverterULong.write(value.`size`, buf)
+            FfiConverterString.write(value.`hash`, buf)
+            FfiConverterString.write(value.`path`, buf)
+            FfiConverterULong.write(value.`receivedAtMs`, buf)
     }
 }
 
 
 
-data class IdentityInfo (
+data class SessionInfo (
     var `peerId`: kotlin.String, 
-    var `pubkeyB64`: kotlin.String
+    var `addr`: kotlin.String?, 
+    var `connectedAtMs`: kotlin.ULong, 
+    var `encrypted`: kotlin.Boolean, 
+    var `outbound`: kotlin.Boolean
 ) {
     
     companion object
@@ -3224,22 +5020,31 @@ data class IdentityInfo (
 /**
  * @suppress
  */
-public object FfiConverterTypeIdentityInfo: FfiConverterRustBuffer<IdentityInfo> {
-    override fun read(buf: ByteBuffer): IdentityInfo {
-        return IdentityInfo(
-            FfiConverterString.read(buf),
+public object FfiConverterTypeSessionInfo: FfiConverterRustBuffer<SessionInfo> {
+    override fun read(buf: ByteBuffer): SessionInfo {
+        return SessionInfo(
             FfiConverterString.read(buf),
+            FfiConverterOptionalString.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterBoolean.read(buf),
+            FfiConverterBoolean.read(buf),
         )
     }
 
-    override fun allocationSize(value: IdentityInfo) = (
+    override fun allocationSize(value: SessionInfo) = (
             FfiConverterString.allocationSize(value.`peerId`) +
-            FfiConverterString.allocationSize(value.`pubkeyB64`)
+            FfiConverterOptionalString.allocationSize(value.`addr`) +
+            FfiConverterULong.allocationSize(value.`connectedAtMs`) +
+            FfiConverterBoolean.allocationSize(value.`encrypted`) +
+            FfiConverterBoolean.allocationSize(value.`outbound`)
     )
 
-    override fun write(value: IdentityInfo, buf: ByteBuffer) {
+    override fun write(value: SessionInfo, buf: ByteBuffer) {
             FfiConverterString.write(value.`peerId`, buf)
-            FfiConverterString.write(value.`pubkeyB64`, buf)
+            FfiConverterOptionalString.write(value.`addr`, buf)
+            FfiConverterULong.write(value.`connectedAtMs`, buf)
+            FfiConverterBoolean.write(value.`encrypted`, buf)
+            FfiConverterBoolean.write(value.`outbound`, buf)
     }
 }
 
@@ -3249,7 +5054,8 @@ data class TrustRecord (
     var `peerId`: kotlin.String, 
     var `identityPkB64`: kotlin.String, 
     var `displayName`: kotlin.String, 
-    var `createdAtMs`: kotlin.ULong
+    var `createdAtMs`: kotlin.ULong, 
+    var `syncDirection`: SyncDirection
 ) {
     
     companion object
@@ -3265,6 +5071,7 @@ public object FfiConverterTypeTrustRecord: FfiConverterRustBuffer<TrustRecord> {
             FfiConverterString.read(buf),
             FfiConverterString.read(buf),
             FfiConverterULong.read(buf),
+            FfiConverterTypeSyncDirection.read(buf),
         )
     }
 
@@ -3272,7 +5079,8 @@ public object FfiConverterTypeTrustRecord: FfiConverterRustBuffer<TrustRecord> {
             FfiConverterString.allocationSize(value.`peerId`) +
             FfiConverterString.allocationSize(value.`identityPkB64`) +
             FfiConverterString.allocationSize(value.`displayName`) +
-            FfiConverterULong.allocationSize(value.`createdAtMs`)
+            FfiConverterULong.allocationSize(value.`createdAtMs`) +
+            FfiConverterTypeSyncDirection.allocationSize(value.`syncDirection`)
     )
 
     override fun write(value: TrustRecord, buf: ByteBuffer) {
@@ -3280,6 +5088,36 @@ public object FfiConverterTypeTrustRecord: FfiConverterRustBuffer<TrustRecord> {
             FfiConverterString.write(value.`identityPkB64`, buf)
             FfiConverterString.write(value.`displayName`, buf)
             FfiConverterULong.write(value.`createdAtMs`, buf)
+            FfiConverterTypeSyncDirection.write(value.`syncDirection`, buf)
+    }
+}
+
+
+
+
+enum class ClipboardHistoryKind {
+    
+    TEXT,
+    IMAGE,
+    FILE;
+    companion object
+}
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeClipboardHistoryKind: FfiConverterRustBuffer<ClipboardHistoryKind> {
+    override fun read(buf: ByteBuffer) = try {
+        ClipboardHistoryKind.values()[buf.getInt() - 1]
+    } catch (e: IndexOutOfBoundsException) {
+        throw RuntimeException("invalid enum value, something is very wrong!!", e)
+    }
+
+    override fun allocationSize(value: ClipboardHistoryKind) = 4UL
+
+    override fun write(value: ClipboardHistoryKind, buf: ByteBuffer) {
+        buf.putInt(value.ordinal + 1)
     }
 }
 
@@ -3287,10 +5125,24 @@ public object FfiConverterTypeTrustRecord: FfiConverterRustBuffer<TrustRecord> {
 
 
 
+
+
 sealed class OpenClipboardException(message: String): kotlin.Exception(message) {
         
         class Other(message: String) : OpenClipboardException(message)
         
+        class NotAnOpenClipboardCode(message: String) : OpenClipboardException(message)
+        
+        class NodeStopped(message: String) : OpenClipboardException(message)
+        
+        class Cancelled(message: String) : OpenClipboardException(message)
+        
+        class AddressInUse(message: String) : OpenClipboardException(message)
+        
+        class BindFailed(message: String) : OpenClipboardException(message)
+        
+        class FileRejected(message: String) : OpenClipboardException(message)
+        
 
     companion object ErrorHandler : UniffiRustCallStatusErrorHandler<OpenClipboardException> {
         override fun lift(error_buf: RustBuffer.ByValue): OpenClipboardException = FfiConverterTypeOpenClipboardError.lift(error_buf)
@@ -3305,6 +5157,12 @@ public object FfiConverterTypeOpenClipboardError : FfiConverterRustBuffer<OpenCl
         
             return when(buf.getInt()) {
             1 -> OpenClipboardException.Other(FfiConverterString.read(buf))
+            2 -> OpenClipboardException.NotAnOpenClipboardCode(FfiConverterString.read(buf))
+            3 -> OpenClipboardException.NodeStopped(FfiConverterString.read(buf))
+            4 -> OpenClipboardException.Cancelled(FfiConverterString.read(buf))
+            5 -> OpenClipboardException.AddressInUse(FfiConverterString.read(buf))
+            6 -> OpenClipboardException.BindFailed(FfiConverterString.read(buf))
+            7 -> OpenClipboardException.FileRejected(FfiConverterString.read(buf))
             else -> throw RuntimeException("invalid error enum value, something is very wrong!!")
         }
         
@@ -3320,6 +5178,30 @@ public object FfiConverterTypeOpenClipboardError : FfiConverterRustBuffer<OpenCl
                 buf.putInt(1)
                 Unit
             }
+            is OpenClipboardException.NotAnOpenClipboardCode -> {
+                buf.putInt(2)
+                Unit
+            }
+            is OpenClipboardException.NodeStopped -> {
+                buf.putInt(3)
+                Unit
+            }
+            is OpenClipboardException.Cancelled -> {
+                buf.putInt(4)
+                Unit
+            }
+            is OpenClipboardException.AddressInUse -> {
+                buf.putInt(5)
+                Unit
+            }
+            is OpenClipboardException.BindFailed -> {
+                buf.putInt(6)
+                Unit
+            }
+            is OpenClipboardException.FileRejected -> {
+                buf.putInt(7)
+                Unit
+            }
         }.let { /* this makes the `when` an expression, which ensures it is exhaustive */ }
     }
 
@@ -3328,6 +5210,38 @@ public object FfiConverterTypeOpenClipboardError : FfiConverterRustBuffer<OpenCl
 
 
 
+enum class SyncDirection {
+    
+    BIDIRECTIONAL,
+    SEND_ONLY,
+    RECEIVE_ONLY,
+    OFF;
+    companion object
+}
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeSyncDirection: FfiConverterRustBuffer<SyncDirection> {
+    override fun read(buf: ByteBuffer) = try {
+        SyncDirection.values()[buf.getInt() - 1]
+    } catch (e: IndexOutOfBoundsException) {
+        throw RuntimeException("invalid enum value, something is very wrong!!", e)
+    }
+
+    override fun allocationSize(value: SyncDirection) = 4UL
+
+    override fun write(value: SyncDirection, buf: ByteBuffer) {
+        buf.putInt(value.ordinal + 1)
+    }
+}
+
+
+
+
+
+
 
 public interface ClipboardCallback {
     
@@ -3335,6 +5249,10 @@ public interface ClipboardCallback {
     
     fun `writeText`(`text`: kotlin.String)
     
+    fun `readImage`(): ClipboardImage?
+    
+    fun `writeImage`(`image`: ClipboardImage)
+    
     companion object
 }
 
@@ -3365,6 +5283,29 @@ internal object uniffiCallbackInterfaceClipboardCallback {
             uniffiTraitInterfaceCall(uniffiCallStatus, makeCall, writeReturn)
         }
     }
+    internal object `readImage`: UniffiCallbackInterfaceClipboardCallbackMethod2 {
+        override fun callback(`uniffiHandle`: Long,`uniffiOutReturn`: RustBuffer,uniffiCallStatus: UniffiRustCallStatus,) {
+            val uniffiObj = FfiConverterTypeClipboardCallback.handleMap.get(uniffiHandle)
+            val makeCall = { ->
+                uniffiObj.`readImage`(
+                )
+            }
+            val writeReturn = { value: ClipboardImage? -> uniffiOutReturn.setValue(FfiConverterOptionalTypeClipboardImage.lower(value)) }
+            uniffiTraitInterfaceCall(uniffiCallStatus, makeCall, writeReturn)
+        }
+    }
+    internal object `writeImage`: UniffiCallbackInterfaceClipboardCallbackMethod3 {
+        override fun callback(`uniffiHandle`: Long,`image`: RustBuffer.ByValue,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,) {
+            val uniffiObj = FfiConverterTypeClipboardCallback.handleMap.get(uniffiHandle)
+            val makeCall = { ->
+                uniffiObj.`writeImage`(
+                    FfiConverterTypeClipboardImage.lift(`image`),
+                )
+            }
+            val writeReturn = { _: Unit -> Unit }
+            uniffiTraitInterfaceCall(uniffiCallStatus, makeCall, writeReturn)
+        }
+    }
 
     internal object uniffiFree: UniffiCallbackInterfaceFree {
         override fun callback(handle: Long) {
@@ -3375,6 +5316,8 @@ internal object uniffiCallbackInterfaceClipboardCallback {
     internal var vtable = UniffiVTableCallbackInterfaceClipboardCallback.UniffiByValue(
         `readText`,
         `writeText`,
+        `readImage`,
+        `writeImage`,
         uniffiFree,
     )
 
@@ -3478,6 +5421,8 @@ public interface EventHandler {
     
     fun `onError`(`message`: kotlin.String)
     
+    fun `onClipboardImage`(`peerId`: kotlin.String, `mime`: kotlin.String, `width`: kotlin.UInt, `height`: kotlin.UInt, `bytes`: List<kotlin.UByte>, `tsMs`: kotlin.ULong)
+    
     companion object
 }
 
@@ -3541,8 +5486,25 @@ internal object uniffiCallbackInterfaceEventHandler {
         override fun callback(`uniffiHandle`: Long,`message`: RustBuffer.ByValue,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,) {
             val uniffiObj = FfiConverterTypeEventHandler.handleMap.get(uniffiHandle)
             val makeCall = { ->
-                uniffiObj.`onError`(
-                    FfiConverterString.lift(`message`),
+                uniffiObj.`onError`(
+                    FfiConverterString.lift(`message`),
+                )
+            }
+            val writeReturn = { _: Unit -> Unit }
+            uniffiTraitInterfaceCall(uniffiCallStatus, makeCall, writeReturn)
+        }
+    }
+    internal object `onClipboardImage`: UniffiCallbackInterfaceEventHandlerMethod5 {
+        override fun callback(`uniffiHandle`: Long,`peerId`: RustBuffer.ByValue,`mime`: RustBuffer.ByValue,`width`: Int,`height`: Int,`bytes`: RustBuffer.ByValue,`tsMs`: Long,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,) {
+            val uniffiObj = FfiConverterTypeEventHandler.handleMap.get(uniffiHandle)
+            val makeCall = { ->
+                uniffiObj.`onClipboardImage`(
+                    FfiConverterString.lift(`peerId`),
+                    FfiConverterString.lift(`mime`),
+                    FfiConverterUInt.lift(`width`),
+                    FfiConverterUInt.lift(`height`),
+                    FfiConverterSequenceUByte.lift(`bytes`),
+                    FfiConverterULong.lift(`tsMs`),
                 )
             }
             val writeReturn = { _: Unit -> Unit }
@@ -3562,6 +5524,7 @@ internal object uniffiCallbackInterfaceEventHandler {
         `onPeerConnected`,
         `onPeerDisconnected`,
         `onError`,
+        `onClipboardImage`,
         uniffiFree,
     )
 
@@ -3582,6 +5545,92 @@ public object FfiConverterTypeEventHandler: FfiConverterCallbackInterface<EventH
 
 
 
+
+public interface FileProgressHandler {
+    
+    fun `onProgress`(`sent`: kotlin.ULong, `total`: kotlin.ULong)
+    
+    companion object
+}
+
+
+
+// Put the implementation in an object so we don't pollute the top-level namespace
+internal object uniffiCallbackInterfaceFileProgressHandler {
+    internal object `onProgress`: UniffiCallbackInterfaceFileProgressHandlerMethod0 {
+        override fun callback(`uniffiHandle`: Long,`sent`: Long,`total`: Long,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,) {
+            val uniffiObj = FfiConverterTypeFileProgressHandler.handleMap.get(uniffiHandle)
+            val makeCall = { ->
+                uniffiObj.`onProgress`(
+                    FfiConverterULong.lift(`sent`),
+                    FfiConverterULong.lift(`total`),
+                )
+            }
+            val writeReturn = { _: Unit -> Unit }
+            uniffiTraitInterfaceCall(uniffiCallStatus, makeCall, writeReturn)
+        }
+    }
+
+    internal object uniffiFree: UniffiCallbackInterfaceFree {
+        override fun callback(handle: Long) {
+            FfiConverterTypeFileProgressHandler.handleMap.remove(handle)
+        }
+    }
+
+    internal var vtable = UniffiVTableCallbackInterfaceFileProgressHandler.UniffiByValue(
+        `onProgress`,
+        uniffiFree,
+    )
+
+    // Registers the foreign callback with the Rust side.
+    // This method is generated for each callback interface.
+    internal fun register(lib: UniffiLib) {
+        lib.uniffi_openclipboard_ffi_fn_init_callback_vtable_fileprogresshandler(vtable)
+    }
+}
+
+/**
+ * The ffiConverter which transforms the Callbacks in to handles to pass to Rust.
+ *
+ * @suppress
+ */
+public object FfiConverterTypeFileProgressHandler: FfiConverterCallbackInterface<FileProgressHandler>()
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterOptionalULong: FfiConverterRustBuffer<kotlin.ULong?> {
+    override fun read(buf: ByteBuffer): kotlin.ULong? {
+        if (buf.get().toInt() == 0) {
+            return null
+        }
+        return FfiConverterULong.read(buf)
+    }
+
+    override fun allocationSize(value: kotlin.ULong?): ULong {
+        if (value == null) {
+            return 1UL
+        } else {
+            return 1UL + FfiConverterULong.allocationSize(value)
+        }
+    }
+
+    override fun write(value: kotlin.ULong?, buf: ByteBuffer) {
+        if (value == null) {
+            buf.put(0)
+        } else {
+            buf.put(1)
+            FfiConverterULong.write(value, buf)
+        }
+    }
+}
+
+
+
+
 /**
  * @suppress
  */
@@ -3614,6 +5663,70 @@ public object FfiConverterOptionalString: FfiConverterRustBuffer<kotlin.String?>
 
 
 
+/**
+ * @suppress
+ */
+public object FfiConverterOptionalTypeCancellationToken: FfiConverterRustBuffer<CancellationToken?> {
+    override fun read(buf: ByteBuffer): CancellationToken? {
+        if (buf.get().toInt() == 0) {
+            return null
+        }
+        return FfiConverterTypeCancellationToken.read(buf)
+    }
+
+    override fun allocationSize(value: CancellationToken?): ULong {
+        if (value == null) {
+            return 1UL
+        } else {
+            return 1UL + FfiConverterTypeCancellationToken.allocationSize(value)
+        }
+    }
+
+    override fun write(value: CancellationToken?, buf: ByteBuffer) {
+        if (value == null) {
+            buf.put(0)
+        } else {
+            buf.put(1)
+            FfiConverterTypeCancellationToken.write(value, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterOptionalTypeClipboardImage: FfiConverterRustBuffer<ClipboardImage?> {
+    override fun read(buf: ByteBuffer): ClipboardImage? {
+        if (buf.get().toInt() == 0) {
+            return null
+        }
+        return FfiConverterTypeClipboardImage.read(buf)
+    }
+
+    override fun allocationSize(value: ClipboardImage?): ULong {
+        if (value == null) {
+            return 1UL
+        } else {
+            return 1UL + FfiConverterTypeClipboardImage.allocationSize(value)
+        }
+    }
+
+    override fun write(value: ClipboardImage?, buf: ByteBuffer) {
+        if (value == null) {
+            buf.put(0)
+        } else {
+            buf.put(1)
+            FfiConverterTypeClipboardImage.write(value, buf)
+        }
+    }
+}
+
+
+
+
 /**
  * @suppress
  */
@@ -3730,6 +5843,174 @@ public object FfiConverterSequenceTypeClipboardHistoryEntry: FfiConverterRustBuf
 
 
 
+/**
+ * @suppress
+ */
+public object FfiConverterSequenceTypeErrorEntry: FfiConverterRustBuffer<List<ErrorEntry>> {
+    override fun read(buf: ByteBuffer): List<ErrorEntry> {
+        val len = buf.getInt()
+        return List<ErrorEntry>(len) {
+            FfiConverterTypeErrorEntry.read(buf)
+        }
+    }
+
+    override fun allocationSize(value: List<ErrorEntry>): ULong {
+        val sizeForLength = 4UL
+        val sizeForItems = value.map { FfiConverterTypeErrorEntry.allocationSize(it) }.sum()
+        return sizeForLength + sizeForItems
+    }
+
+    override fun write(value: List<ErrorEntry>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        value.iterator().forEach {
+            FfiConverterTypeErrorEntry.write(it, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterSequenceTypePeerClipSummary: FfiConverterRustBuffer<List<PeerClipSummary>> {
+    override fun read(buf: ByteBuffer): List<PeerClipSummary> {
+        val len = buf.getInt()
+        return List<PeerClipSummary>(len) {
+            FfiConverterTypePeerClipSummary.read(buf)
+        }
+    }
+
+    override fun allocationSize(value: List<PeerClipSummary>): ULong {
+        val sizeForLength = 4UL
+        val sizeForItems = value.map { FfiConverterTypePeerClipSummary.allocationSize(it) }.sum()
+        return sizeForLength + sizeForItems
+    }
+
+    override fun write(value: List<PeerClipSummary>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        value.iterator().forEach {
+            FfiConverterTypePeerClipSummary.write(it, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterSequenceTypePeerEntry: FfiConverterRustBuffer<List<PeerEntry>> {
+    override fun read(buf: ByteBuffer): List<PeerEntry> {
+        val len = buf.getInt()
+        return List<PeerEntry>(len) {
+            FfiConverterTypePeerEntry.read(buf)
+        }
+    }
+
+    override fun allocationSize(value: List<PeerEntry>): ULong {
+        val sizeForLength = 4UL
+        val sizeForItems = value.map { FfiConverterTypePeerEntry.allocationSize(it) }.sum()
+        return sizeForLength + sizeForItems
+    }
+
+    override fun write(value: List<PeerEntry>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        value.iterator().forEach {
+            FfiConverterTypePeerEntry.write(it, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterSequenceTypePendingRequest: FfiConverterRustBuffer<List<PendingRequest>> {
+    override fun read(buf: ByteBuffer): List<PendingRequest> {
+        val len = buf.getInt()
+        return List<PendingRequest>(len) {
+            FfiConverterTypePendingRequest.read(buf)
+        }
+    }
+
+    override fun allocationSize(value: List<PendingRequest>): ULong {
+        val sizeForLength = 4UL
+        val sizeForItems = value.map { FfiConverterTypePendingRequest.allocationSize(it) }.sum()
+        return sizeForLength + sizeForItems
+    }
+
+    override fun write(value: List<PendingRequest>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        value.iterator().forEach {
+            FfiConverterTypePendingRequest.write(it, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterSequenceTypeReceivedFileEntry: FfiConverterRustBuffer<List<ReceivedFileEntry>> {
+    override fun read(buf: ByteBuffer): List<ReceivedFileEntry> {
+        val len = buf.getInt()
+        return List<ReceivedFileEntry>(len) {
+            FfiConverterTypeReceivedFileEntry.read(buf)
+        }
+    }
+
+    override fun allocationSize(value: List<ReceivedFileEntry>): ULong {
+        val sizeForLength = 4UL
+        val sizeForItems = value.map { FfiConverterTypeReceivedFileEntry.allocationSize(it) }.sum()
+        return sizeForLength + sizeForItems
+    }
+
+    override fun write(value: List<ReceivedFileEntry>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        value.iterator().forEach {
+            FfiConverterTypeReceivedFileEntry.write(it, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterSequenceTypeSessionInfo: FfiConverterRustBuffer<List<SessionInfo>> {
+    override fun read(buf: ByteBuffer): List<SessionInfo> {
+        val len = buf.getInt()
+        return List<SessionInfo>(len) {
+            FfiConverterTypeSessionInfo.read(buf)
+        }
+    }
+
+    override fun allocationSize(value: List<SessionInfo>): ULong {
+        val sizeForLength = 4UL
+        val sizeForItems = value.map { FfiConverterTypeSessionInfo.allocationSize(it) }.sum()
+        return sizeForLength + sizeForItems
+    }
+
+    override fun write(value: List<SessionInfo>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        value.iterator().forEach {
+            FfiConverterTypeSessionInfo.write(it, buf)
+        }
+    }
+}
+
+
+
+
 /**
  * @suppress
  */
@@ -3763,6 +6044,15 @@ public object FfiConverterSequenceTypeTrustRecord: FfiConverterRustBuffer<List<T
     )
     }
     
+ fun `confirmationTranscript`(`nonce`: List<kotlin.UByte>, `peerAId`: kotlin.String, `peerBId`: kotlin.String): ConfirmationTranscript {
+            return FfiConverterTypeConfirmationTranscript.lift(
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_func_confirmation_transcript(
+        FfiConverterSequenceUByte.lower(`nonce`),FfiConverterString.lower(`peerAId`),FfiConverterString.lower(`peerBId`),_status)
+}
+    )
+    }
+    
  fun `defaultIdentityPath`(): kotlin.String {
             return FfiConverterString.lift(
     uniffiRustCall() { _status ->
@@ -3828,6 +6118,15 @@ public object FfiConverterSequenceTypeTrustRecord: FfiConverterRustBuffer<List<T
     )
     }
     
+ fun `peerVisual`(`peerId`: kotlin.String): DeviceVisual {
+            return FfiConverterTypeDeviceVisual.lift(
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_func_peer_visual(
+        FfiConverterString.lower(`peerId`),_status)
+}
+    )
+    }
+    
  fun `trustStoreDefaultPath`(): kotlin.String {
             return FfiConverterString.lift(
     uniffiRustCall() { _status ->
@@ -3847,5 +6146,14 @@ public object FfiConverterSequenceTypeTrustRecord: FfiConverterRustBuffer<List<T
     )
     }
     
+ fun `verifyConfirmationCode`(`transcript`: ConfirmationTranscript, `code`: kotlin.String): kotlin.Boolean {
+            return FfiConverterBoolean.lift(
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_openclipboard_ffi_fn_func_verify_confirmation_code(
+        FfiConverterTypeConfirmationTranscript.lower(`transcript`),FfiConverterString.lower(`code`),_status)
+}
+    )
+    }
+    
 
 
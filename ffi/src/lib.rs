@@ -1,5 +1,4 @@
 use anyhow::Context as _;
-use base64::Engine as _;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::collections::HashMap;
@@ -7,15 +6,17 @@ use std::collections::HashMap;
 use openclipboard_core::{
     derive_confirmation_code as core_derive_confirmation_code,
     Ed25519Identity,
+    FileAssembler,
     IdentityProvider,
     TrustStore as CoreTrustStore,
     Session,
-    MemoryReplayProtector,
+    FileReplayProtector,
+    ReplayProtector,
     FileTrustStore,
     ClipboardProvider,
     ClipboardContent,
     clipboard::MockClipboard,
-    quic_transport::{make_server_endpoint, make_insecure_client_endpoint, QuicListener, QuicTransport},
+    quic_transport::{make_server_endpoint, make_insecure_client_endpoint, AcceptError, ListenError, QuicListener, QuicTransport, ServerEndpointFactory},
     Listener,
     Transport,
     Message,
@@ -36,12 +37,30 @@ use openclipboard_core::{
 #[derive(Debug, Clone)]
 pub enum OpenClipboardError {
     Other,
+    /// A scanned QR string is not an OpenClipboard pairing code.
+    NotAnOpenClipboardCode,
+    /// The `ClipboardNode` has been stopped and can no longer be used.
+    NodeStopped,
+    /// The call's `CancellationToken` was cancelled before it finished.
+    Cancelled,
+    /// Sync couldn't listen: its fixed port is taken.
+    AddressInUse,
+    /// Sync couldn't listen for any other reason.
+    BindFailed,
+    /// The receiver declined the file (e.g. too large or a blocked type).
+    FileRejected,
 }
 
 impl std::fmt::Display for OpenClipboardError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             OpenClipboardError::Other => write!(f, "OpenClipboardError::Other"),
+            OpenClipboardError::NotAnOpenClipboardCode => write!(f, "OpenClipboardError::NotAnOpenClipboardCode"),
+            OpenClipboardError::NodeStopped => write!(f, "OpenClipboardError::NodeStopped"),
+            OpenClipboardError::Cancelled => write!(f, "OpenClipboardError::Cancelled"),
+            OpenClipboardError::AddressInUse => write!(f, "OpenClipboardError::AddressInUse"),
+            OpenClipboardError::BindFailed => write!(f, "OpenClipboardError::BindFailed"),
+            OpenClipboardError::FileRejected => write!(f, "OpenClipboardError::FileRejected"),
         }
     }
 }
@@ -49,8 +68,18 @@ impl std::fmt::Display for OpenClipboardError {
 impl std::error::Error for OpenClipboardError {}
 
 impl From<anyhow::Error> for OpenClipboardError {
-    fn from(_: anyhow::Error) -> Self {
-        Self::Other
+    fn from(e: anyhow::Error) -> Self {
+        if e.downcast_ref::<openclipboard_core::NotAnOpenClipboardCode>().is_some() {
+            return Self::NotAnOpenClipboardCode;
+        }
+        if openclipboard_core::FileRejected::of(&e).is_some() {
+            return Self::FileRejected;
+        }
+        match ListenError::of(&e) {
+            Some(ListenError::AddressInUse { .. }) => Self::AddressInUse,
+            Some(ListenError::BindFailed { .. }) => Self::BindFailed,
+            None => Self::Other,
+        }
     }
 }
 
@@ -60,37 +89,285 @@ pub type Result<T> = std::result::Result<T, OpenClipboardError>;
 // Dictionaries (UDL `dictionary`)
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// What a history entry holds; `content` is the text, or a label for the rest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipboardHistoryKind {
+    Text,
+    Image,
+    File,
+}
+
 #[derive(Clone, Debug)]
 pub struct ClipboardHistoryEntry {
     pub id: String,
     pub content: String,
     pub source_peer: String,
+    pub origin_name: Option<String>,
     pub timestamp: u64,
+    pub kind: ClipboardHistoryKind,
+    /// Set for `Image` entries.
+    pub image: Option<ClipboardImage>,
+    /// Set for `File` entries: where the file was saved, and its size.
+    pub file_path: Option<String>,
+    pub file_size: Option<u64>,
+}
+
+/// Clip activity from one peer, as in `ClipboardHistory::peer_summary`.
+#[derive(Clone, Debug)]
+pub struct PeerClipSummary {
+    pub peer_id: String,
+    pub count: u32,
+    pub last_timestamp: u64,
 }
 
 impl From<openclipboard_core::ClipboardEntry> for ClipboardHistoryEntry {
     fn from(e: openclipboard_core::ClipboardEntry) -> Self {
+        use openclipboard_core::HistoryItemKind;
+        let (kind, image, file_path, file_size) = match e.kind {
+            HistoryItemKind::Text => (ClipboardHistoryKind::Text, None, None, None),
+            HistoryItemKind::Image { mime, width, height, bytes } => {
+                (ClipboardHistoryKind::Image, Some(ClipboardImage { mime, width, height, bytes }), None, None)
+            }
+            HistoryItemKind::File { size, path, .. } => (ClipboardHistoryKind::File, None, Some(path), Some(size)),
+        };
         Self {
             id: e.id,
             content: e.content,
             source_peer: e.source_peer,
+            origin_name: e.origin_name,
             timestamp: e.timestamp,
+            kind,
+            image,
+            file_path,
+            file_size,
+        }
+    }
+}
+
+/// A file received over sync, as in `SyncService::received_files`.
+#[derive(Clone, Debug)]
+pub struct ReceivedFileEntry {
+    pub peer_id: String,
+    pub name: String,
+    pub size: u64,
+    pub hash: String,
+    pub path: String,
+    pub received_at_ms: u64,
+}
+
+impl From<openclipboard_core::ReceivedFile> for ReceivedFileEntry {
+    fn from(f: openclipboard_core::ReceivedFile) -> Self {
+        Self {
+            peer_id: f.peer_id,
+            name: f.name,
+            size: f.size,
+            hash: f.hash,
+            path: f.path,
+            received_at_ms: f.received_at_ms,
+        }
+    }
+}
+
+/// An image on the platform clipboard, as raw encoded bytes (e.g. a PNG).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipboardImage {
+    pub mime: String,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ErrorEntry {
+    pub timestamp_ms: u64,
+    pub peer_id: Option<String>,
+    /// "handshake", "dial", "send", "recv", "file", "trust", ...
+    pub category: String,
+    pub message: String,
+}
+
+impl From<openclipboard_core::ErrorEntry> for ErrorEntry {
+    fn from(e: openclipboard_core::ErrorEntry) -> Self {
+        Self {
+            timestamp_ms: e.timestamp_ms,
+            peer_id: e.peer_id,
+            category: e.category.as_str().to_string(),
+            message: e.message,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PendingRequest {
+    pub peer_id: String,
+    pub fingerprint: String,
+    pub identity_pk_b64: String,
+    pub addr: Option<String>,
+    pub timestamp_ms: u64,
+}
+
+impl From<openclipboard_core::PendingRequest> for PendingRequest {
+    fn from(r: openclipboard_core::PendingRequest) -> Self {
+        Self {
+            peer_id: r.peer_id,
+            fingerprint: r.fingerprint,
+            identity_pk_b64: openclipboard_core::Base64Alphabet::Standard.encode(r.identity_pk),
+            addr: r.addr,
+            timestamp_ms: r.timestamp_ms,
+        }
+    }
+}
+
+/// An open sync session, as in `SyncService::active_sessions`.
+#[derive(Clone, Debug)]
+pub struct SessionInfo {
+    pub peer_id: String,
+    pub addr: Option<String>,
+    pub connected_at_ms: u64,
+    pub encrypted: bool,
+    /// `true` if this node dialed the peer.
+    pub outbound: bool,
+}
+
+impl From<openclipboard_core::SessionInfo> for SessionInfo {
+    fn from(s: openclipboard_core::SessionInfo) -> Self {
+        Self {
+            peer_id: s.peer_id,
+            addr: s.addr,
+            connected_at_ms: s.connected_at_ms,
+            encrypted: s.encrypted,
+            outbound: s.outbound,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PeerEntry {
+    pub peer_id: String,
+    pub display_name: String,
+    /// `display_name`, suffixed (`"MacBook (2)"`) when another peer shares it.
+    pub label: String,
+    pub online: bool,
+    pub last_addr: Option<String>,
+    pub addresses: Vec<String>,
+    pub device_name: Option<String>,
+}
+
+impl From<openclipboard_core::PeerEntry> for PeerEntry {
+    fn from(e: openclipboard_core::PeerEntry) -> Self {
+        Self {
+            peer_id: e.peer_id,
+            display_name: e.display_name,
+            label: e.label,
+            online: e.status == openclipboard_core::PeerStatus::Online,
+            last_addr: e.last_addr,
+            addresses: e.addresses,
+            device_name: e.device_name,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NodeSnapshot {
+    pub connected: Vec<String>,
+    pub peers: Vec<PeerEntry>,
+    pub history: Vec<ClipboardHistoryEntry>,
+    pub history_len: u64,
+    pub history_bytes: u64,
+    pub pending_requests: Vec<PendingRequest>,
+    pub suspended: Vec<String>,
+}
+
+impl From<openclipboard_core::SyncSnapshot> for NodeSnapshot {
+    fn from(s: openclipboard_core::SyncSnapshot) -> Self {
+        Self {
+            connected: s.connected,
+            peers: s.peers.into_iter().map(Into::into).collect(),
+            history: s.history.into_iter().map(Into::into).collect(),
+            history_len: s.history_len as u64,
+            history_bytes: s.history_bytes as u64,
+            pending_requests: s.pending_requests.into_iter().map(Into::into).collect(),
+            suspended: s.suspended,
         }
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceVisual {
+    pub color_hex: String,
+    pub initials: String,
+    pub identicon_seed: u64,
+}
+
+impl From<openclipboard_core::DeviceVisual> for DeviceVisual {
+    fn from(v: openclipboard_core::DeviceVisual) -> Self {
+        Self { color_hex: v.color_hex, initials: v.initials, identicon_seed: v.identicon_seed }
+    }
+}
+
+/// The inputs hashed into a pairing confirmation code; see the core type.
+#[derive(Clone, Debug)]
+pub struct ConfirmationTranscript {
+    pub nonce: Vec<u8>,
+    pub peer_a_id: String,
+    pub peer_b_id: String,
+}
+
+impl From<openclipboard_core::ConfirmationTranscript> for ConfirmationTranscript {
+    fn from(t: openclipboard_core::ConfirmationTranscript) -> Self {
+        Self { nonce: t.nonce, peer_a_id: t.peer_a_id, peer_b_id: t.peer_b_id }
+    }
+}
+
+impl From<ConfirmationTranscript> for openclipboard_core::ConfirmationTranscript {
+    fn from(t: ConfirmationTranscript) -> Self {
+        Self { nonce: t.nonce, peer_a_id: t.peer_a_id, peer_b_id: t.peer_b_id }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct IdentityInfo {
     pub peer_id: String,
     pub pubkey_b64: String,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncDirection {
+    Bidirectional,
+    SendOnly,
+    ReceiveOnly,
+    Off,
+}
+
+impl From<openclipboard_core::SyncDirection> for SyncDirection {
+    fn from(d: openclipboard_core::SyncDirection) -> Self {
+        match d {
+            openclipboard_core::SyncDirection::Bidirectional => Self::Bidirectional,
+            openclipboard_core::SyncDirection::SendOnly => Self::SendOnly,
+            openclipboard_core::SyncDirection::ReceiveOnly => Self::ReceiveOnly,
+            openclipboard_core::SyncDirection::Off => Self::Off,
+        }
+    }
+}
+
+impl From<SyncDirection> for openclipboard_core::SyncDirection {
+    fn from(d: SyncDirection) -> Self {
+        match d {
+            SyncDirection::Bidirectional => Self::Bidirectional,
+            SyncDirection::SendOnly => Self::SendOnly,
+            SyncDirection::ReceiveOnly => Self::ReceiveOnly,
+            SyncDirection::Off => Self::Off,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TrustRecord {
     pub peer_id: String,
     pub identity_pk_b64: String,
     pub display_name: String,
     pub created_at_ms: u64,
+    pub sync_direction: SyncDirection,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -113,7 +390,7 @@ impl Identity {
     }
 
     pub fn pubkey_b64(&self) -> String {
-        base64::engine::general_purpose::STANDARD.encode(self.inner.public_key_bytes())
+        openclipboard_core::Base64Alphabet::Standard.encode(self.inner.public_key_bytes())
     }
 
     pub fn info(&self) -> IdentityInfo {
@@ -126,7 +403,7 @@ impl Identity {
     pub fn save(&self, path: String) -> Result<()> {
         let sk_bytes = self.inner.signing_key_seed_bytes();
         let file = IdentityFile {
-            signing_key_b64: base64::engine::general_purpose::STANDARD.encode(sk_bytes),
+            signing_key_b64: openclipboard_core::Base64Alphabet::Standard.encode(sk_bytes),
         };
 
         let path = std::path::PathBuf::from(path);
@@ -150,7 +427,7 @@ pub fn identity_load(path: String) -> Result<Arc<Identity>> {
     let path = std::path::PathBuf::from(path);
     let s = std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
     let file: IdentityFile = serde_json::from_str(&s).context("parse identity json")?;
-    let sk_bytes = base64::engine::general_purpose::STANDARD
+    let sk_bytes = openclipboard_core::Base64Alphabet::Standard
         .decode(file.signing_key_b64)
         .context("decode signing_key_b64")?;
     let sk_arr: [u8; 32] = sk_bytes
@@ -198,6 +475,10 @@ impl PairingPayload {
     pub fn to_qr_string(&self) -> Result<String> {
         Ok(self.inner.to_qr_string())
     }
+
+    pub fn to_qr_string_compact(&self) -> Result<String> {
+        Ok(self.inner.to_qr_string_compact()?)
+    }
 }
 
 pub fn pairing_payload_create(
@@ -236,6 +517,20 @@ pub fn derive_confirmation_code(nonce: Vec<u8>, peer_a_id: String, peer_b_id: St
     core_derive_confirmation_code(&nonce, &peer_a_id, &peer_b_id)
 }
 
+/// The inputs `derive_confirmation_code` hashes, for showing or auditing them.
+pub fn confirmation_transcript(nonce: Vec<u8>, peer_a_id: String, peer_b_id: String) -> ConfirmationTranscript {
+    openclipboard_core::ConfirmationTranscript::new(&nonce, &peer_a_id, &peer_b_id).into()
+}
+
+pub fn verify_confirmation_code(transcript: ConfirmationTranscript, code: String) -> bool {
+    openclipboard_core::verify_confirmation_code(&transcript.into(), &code)
+}
+
+/// Stable color, initials and identicon seed for drawing a device.
+pub fn peer_visual(peer_id: String) -> DeviceVisual {
+    openclipboard_core::peer_visual(&peer_id).into()
+}
+
 pub fn default_identity_path() -> String {
     let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
     std::path::PathBuf::from(home)
@@ -248,9 +543,10 @@ pub fn default_identity_path() -> String {
 fn trust_record_from_core(r: openclipboard_core::TrustRecord) -> TrustRecord {
     TrustRecord {
         peer_id: r.peer_id,
-        identity_pk_b64: base64::engine::general_purpose::STANDARD.encode(r.identity_pk),
+        identity_pk_b64: openclipboard_core::Base64Alphabet::Standard.encode(r.identity_pk),
         display_name: r.display_name,
         created_at_ms: r.created_at.timestamp_millis().max(0) as u64,
+        sync_direction: r.sync_direction.into(),
     }
 }
 
@@ -272,15 +568,14 @@ pub fn trust_store_default_path() -> String {
 
 impl TrustStore {
     pub fn add(&self, peer_id: String, identity_pk_b64: String, display_name: String) -> Result<()> {
-        let pk = base64::engine::general_purpose::STANDARD
-            .decode(identity_pk_b64)
-            .context("decode identity_pk_b64")?;
+        let pk = openclipboard_core::base64_decode_any(identity_pk_b64).context("decode identity_pk_b64")?;
 
         let record = openclipboard_core::TrustRecord {
             peer_id,
             identity_pk: pk,
             display_name,
             created_at: chrono::Utc::now(),
+            sync_direction: Default::default(),
         };
         self.inner.save(record)?;
         Ok(())
@@ -300,8 +595,42 @@ impl TrustStore {
             .collect())
     }
 
+    /// Stop trusting `peer_id`. The removal is kept as a revocation, so
+    /// linked devices drop the peer too on their next trust sync.
     pub fn remove(&self, peer_id: String) -> Result<bool> {
-        Ok(self.inner.remove(&peer_id)?)
+        Ok(self.inner.revoke(&peer_id)?)
+    }
+}
+
+/// Handle a host passes to a long-running call and later cancels (e.g. the
+/// user hits "stop") to abort it; the call then fails with `Cancelled`.
+pub struct CancellationToken {
+    cancelled: tokio::sync::watch::Sender<bool>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self { cancelled: tokio::sync::watch::Sender::new(false) }
+    }
+
+    /// Abort the call(s) this token was passed to. Idempotent.
+    pub fn cancel(&self) {
+        self.cancelled.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    async fn wait(&self) {
+        let mut rx = self.cancelled.subscribe();
+        let _ = rx.wait_for(|c| *c).await;
     }
 }
 
@@ -313,6 +642,12 @@ impl TrustStore {
 pub trait ClipboardCallback: Send + Sync {
     fn read_text(&self) -> Option<String>;
     fn write_text(&self, text: String);
+    /// The clipboard's image, when it holds no text. Platforms without image
+    /// support can leave this out.
+    fn read_image(&self) -> Option<ClipboardImage> {
+        None
+    }
+    fn write_image(&self, _image: ClipboardImage) {}
 }
 
 /// Adapter from `ClipboardCallback` (UniFFI) to `ClipboardProvider` (core).
@@ -322,15 +657,24 @@ struct ClipboardCallbackAdapter {
 
 impl ClipboardProvider for ClipboardCallbackAdapter {
     fn read(&self) -> anyhow::Result<ClipboardContent> {
-        match self.inner.read_text() {
-            Some(t) if !t.is_empty() => Ok(ClipboardContent::Text(t)),
-            _ => Ok(ClipboardContent::Empty),
+        // `None` means the platform has no text on the clipboard; an empty or
+        // whitespace-only string is still text (mesh mode decides whether to sync it).
+        if let Some(t) = self.inner.read_text() {
+            return Ok(ClipboardContent::Text(t));
+        }
+        match self.inner.read_image() {
+            Some(ClipboardImage { mime, width, height, bytes }) => Ok(ClipboardContent::Image { mime, width, height, bytes }),
+            None => Ok(ClipboardContent::Empty),
         }
     }
 
     fn write(&self, content: ClipboardContent) -> anyhow::Result<()> {
-        if let ClipboardContent::Text(t) = content {
-            self.inner.write_text(t);
+        match content {
+            ClipboardContent::Text(t) => self.inner.write_text(t),
+            ClipboardContent::Image { mime, width, height, bytes } => {
+                self.inner.write_image(ClipboardImage { mime, width, height, bytes });
+            }
+            ClipboardContent::Empty => {}
         }
         Ok(())
     }
@@ -347,6 +691,14 @@ pub trait EventHandler: Send + Sync {
     fn on_peer_connected(&self, peer_id: String);
     fn on_peer_disconnected(&self, peer_id: String);
     fn on_error(&self, message: String);
+    /// A peer copied an image. Mesh mode has already written it to the
+    /// `ClipboardCallback`.
+    fn on_clipboard_image(&self, _peer_id: String, _mime: String, _width: u32, _height: u32, _bytes: Vec<u8>, _ts_ms: u64) {}
+}
+
+/// Progress of a `send_file_to_peer` call.
+pub trait FileProgressHandler: Send + Sync {
+    fn on_progress(&self, sent: u64, total: u64);
 }
 
 pub trait DiscoveryHandler: Send + Sync {
@@ -354,16 +706,38 @@ pub trait DiscoveryHandler: Send + Sync {
     fn on_peer_lost(&self, peer_id: String);
 }
 
-struct IncomingFile {
-    name: String,
-    expected: u64,
-    buf: Vec<u8>,
-}
+/// Default cap on concurrent file sends per node.
+const DEFAULT_MAX_CONCURRENT_FILE_SENDS: usize = 2;
+
+/// How long a direct send waits for the receiver to confirm it read everything
+/// before the connection is dropped.
+const DELIVERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How long a recall's clipboard write stays exempt from fanout if the
+/// watcher never sees it.
+const RECALL_SILENT_WRITE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How many of the service's recent errors `diagnostics` includes.
+const DIAGNOSTICS_ERRORS: usize = 32;
+
+/// Newest handshake nonces remembered across all peers.
+const REPLAY_NONCES: usize = 4096;
+
+/// Newest received files kept in the node's log.
+const RECEIVED_FILES: usize = 100;
+
+/// How long a direct file send waits for the receiver to say how much of the
+/// file it already has.
+const RESUME_QUERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
 
 pub struct ClipboardNode {
     identity: Ed25519Identity,
-    trust_store: Arc<FileTrustStore>,
-    replay_protector: Arc<MemoryReplayProtector>,
+    // Active profile's trust store; swapped by `set_profile`.
+    trust_store: Mutex<Arc<FileTrustStore>>,
+    // Kept beside the trust store, so replayed handshakes are caught across restarts.
+    replay_protector: Arc<dyn ReplayProtector>,
+    // Files received over sync and the direct listener alike.
+    received_files: Arc<openclipboard_core::FileReceiveLog>,
     runtime: tokio::runtime::Runtime,
 
     // Legacy (Phase 1/2)
@@ -375,9 +749,66 @@ pub struct ClipboardNode {
     sync_discovery: Arc<BoxDiscovery>,
     sync_bind_ip: std::net::IpAddr,
     sync_service: Mutex<Option<Arc<openclipboard_core::SyncService<BoxDiscovery>>>>,
+    // Held through `start_sync` / `start_mesh`, so concurrent starts can't
+    // leave an earlier service (and its clipboard watcher) running unowned.
+    sync_start: Mutex<()>,
 
     // Clipboard provider for recall (set when start_mesh is called)
     mesh_provider: Mutex<Option<Arc<dyn ClipboardProvider>>>,
+
+    // Whether mesh mode syncs empty / whitespace-only text (applied on start_mesh)
+    sync_whitespace: std::sync::atomic::AtomicBool,
+
+    // Clipboard history byte budget, 0 = unlimited (applied on start_sync / start_mesh)
+    history_max_bytes: std::sync::atomic::AtomicU64,
+
+    // Grace period before reporting a peer disconnect, 0 = immediate (applied on start_sync / start_mesh)
+    disconnect_grace_ms: std::sync::atomic::AtomicU64,
+
+    // How long stopping sync waits for queued clips to reach peers, 0 = not at all (applied on start_sync / start_mesh)
+    drain_on_stop_ms: std::sync::atomic::AtomicU64,
+
+    // Keepalive ping interval and pong timeout, interval 0 = no pings (applied on start_sync / start_mesh)
+    keepalive_interval_ms: std::sync::atomic::AtomicU64,
+    keepalive_timeout_ms: std::sync::atomic::AtomicU64,
+
+    // Where clipboard history is persisted, encrypted, if anywhere (used by start_sync / start_mesh)
+    history_file: Mutex<Option<String>>,
+
+    // Where the received-files log is persisted, if anywhere (applied on start_sync / start_mesh)
+    received_files_log: Mutex<Option<String>>,
+
+    // Where the linked devices are persisted, if anywhere (applied on start_sync / start_mesh)
+    linked_devices_file: Mutex<Option<String>>,
+
+    // Where cut-off incoming files are kept for resuming, None = not kept; read by
+    // the legacy listener per connection (applied on start_sync / start_mesh)
+    partial_files_dir: Arc<Mutex<Option<std::path::PathBuf>>>,
+
+    // Caps on how fast files go out, shared by sync and direct sends (applied on start_sync / start_mesh)
+    file_rate_limits: Arc<openclipboard_core::FileRateLimits>,
+
+    // Replaces how sync creates its listener endpoint (applied on start_sync / start_mesh)
+    endpoint_factory: Mutex<Option<ServerEndpointFactory>>,
+
+    // Set by `stop()`; once set, every public method returning a `Result`
+    // fails with `NodeStopped`, and setters returning nothing are ignored.
+    stopped: std::sync::atomic::AtomicBool,
+    // Read-held by each public method from `ensure_running` to its return and
+    // write-held by `stop()`, so teardown never starts under a running call.
+    running: std::sync::RwLock<()>,
+
+    // Which incoming file types the legacy listener accepts.
+    file_filter: Arc<Mutex<openclipboard_core::FileTypeFilter>>,
+
+    // Caps concurrent file sends, shared by sync and direct sends (applied on start_sync / start_mesh)
+    file_send_slots: Arc<openclipboard_core::FileSendSlots>,
+
+    // Chunk size and pipelining window for `connect_and_send_file`.
+    file_transfer: Mutex<openclipboard_core::FileTransferConfig>,
+
+    // Largest incoming file taken, by sync sessions and the legacy listener; 0 for no limit.
+    max_incoming_file_size: Arc<std::sync::atomic::AtomicU64>,
 }
 
 impl ClipboardNode {
@@ -390,7 +821,7 @@ impl ClipboardNode {
             let s = std::fs::read_to_string(&identity_path)
                 .with_context(|| format!("read identity from {}", identity_path.display()))?;
             let file: IdentityFile = serde_json::from_str(&s).context("parse identity json")?;
-            let sk_bytes = base64::engine::general_purpose::STANDARD
+            let sk_bytes = openclipboard_core::Base64Alphabet::Standard
                 .decode(file.signing_key_b64)
                 .context("decode signing_key_b64")?;
             let sk_arr: [u8; 32] = sk_bytes
@@ -402,7 +833,7 @@ impl ClipboardNode {
             let identity = Ed25519Identity::generate();
             let sk_bytes = identity.signing_key_seed_bytes();
             let file = IdentityFile {
-                signing_key_b64: base64::engine::general_purpose::STANDARD.encode(sk_bytes),
+                signing_key_b64: openclipboard_core::Base64Alphabet::Standard.encode(sk_bytes),
             };
             if let Some(parent) = identity_path.parent() {
                 std::fs::create_dir_all(parent)
@@ -414,8 +845,8 @@ impl ClipboardNode {
             identity
         };
 
+        let replay_protector = Arc::new(FileReplayProtector::new(openclipboard_core::replay_path_for(&trust_path), REPLAY_NONCES)?);
         let trust_store = Arc::new(FileTrustStore::new(trust_path)?);
-        let replay_protector = Arc::new(MemoryReplayProtector::new(1024));
         let runtime = tokio::runtime::Runtime::new()
             .context("create tokio runtime")?;
 
@@ -424,8 +855,9 @@ impl ClipboardNode {
 
         Ok(Self {
             identity,
-            trust_store,
+            trust_store: Mutex::new(trust_store),
             replay_protector,
+            received_files: Arc::new(openclipboard_core::FileReceiveLog::new(RECEIVED_FILES)),
             runtime,
             listener_handle: Mutex::new(None),
             discovery: Arc::clone(&mdns),
@@ -433,7 +865,26 @@ impl ClipboardNode {
             sync_discovery: Arc::new(BoxDiscovery::new(mdns_dyn)),
             sync_bind_ip: std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
             sync_service: Mutex::new(None),
+            sync_start: Mutex::new(()),
             mesh_provider: Mutex::new(None),
+            sync_whitespace: std::sync::atomic::AtomicBool::new(true),
+            history_max_bytes: std::sync::atomic::AtomicU64::new(0),
+            disconnect_grace_ms: std::sync::atomic::AtomicU64::new(0),
+            drain_on_stop_ms: std::sync::atomic::AtomicU64::new(0),
+            keepalive_interval_ms: std::sync::atomic::AtomicU64::new(openclipboard_core::Keepalive::default().interval.as_millis() as u64),
+            keepalive_timeout_ms: std::sync::atomic::AtomicU64::new(openclipboard_core::Keepalive::default().timeout.as_millis() as u64),
+            history_file: Mutex::new(None),
+            received_files_log: Mutex::new(None),
+            linked_devices_file: Mutex::new(None),
+            partial_files_dir: Arc::new(Mutex::new(Some(openclipboard_core::default_partial_dir()))),
+            file_rate_limits: Arc::new(openclipboard_core::FileRateLimits::new()),
+            endpoint_factory: Mutex::new(None),
+            stopped: std::sync::atomic::AtomicBool::new(false),
+            running: std::sync::RwLock::new(()),
+            file_filter: Arc::new(Mutex::new(openclipboard_core::FileTypeFilter::new())),
+            file_send_slots: Arc::new(openclipboard_core::FileSendSlots::new(Some(DEFAULT_MAX_CONCURRENT_FILE_SENDS))),
+            file_transfer: Mutex::new(openclipboard_core::FileTransferConfig::default()),
+            max_incoming_file_size: Arc::new(std::sync::atomic::AtomicU64::new(openclipboard_core::DEFAULT_MAX_RECEIVED_FILE_SIZE)),
         })
     }
 
@@ -448,6 +899,43 @@ impl ClipboardNode {
         node.sync_bind_ip = sync_bind_ip;
         Ok(node)
     }
+
+    fn trust_store(&self) -> Arc<FileTrustStore> {
+        Arc::clone(&self.trust_store.lock().unwrap())
+    }
+
+    /// Fails with `NodeStopped` once `stop` has begun; otherwise returns a guard
+    /// to hold for the rest of the call, which `stop` waits on. The flag is
+    /// checked before taking the lock so a call made while `stop` waits (e.g.
+    /// from a handler callback) fails instead of blocking.
+    fn ensure_running(&self) -> Result<std::sync::RwLockReadGuard<'_, ()>> {
+        if self.stopped.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(OpenClipboardError::NodeStopped);
+        }
+        let guard = self.running.read().unwrap();
+        if self.stopped.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(OpenClipboardError::NodeStopped);
+        }
+        Ok(guard)
+    }
+
+    /// `block_on(fut)`, unless `cancel` fires first: then `fut` is dropped,
+    /// which closes whatever connection it holds, and `Cancelled` is returned.
+    fn block_on_cancellable<T>(
+        &self,
+        cancel: Option<&CancellationToken>,
+        fut: impl std::future::Future<Output = anyhow::Result<T>>,
+    ) -> Result<T> {
+        let Some(cancel) = cancel else {
+            return Ok(self.runtime.block_on(fut)?);
+        };
+        self.runtime.block_on(async {
+            tokio::select! {
+                res = fut => Ok(res?),
+                _ = cancel.wait() => Err(OpenClipboardError::Cancelled),
+            }
+        })
+    }
 }
 
 impl ClipboardNode {
@@ -466,18 +954,22 @@ impl ClipboardNode {
         handler: Box<dyn EventHandler>,
         provider: Box<dyn ClipboardCallback>,
         poll_interval_ms: u64,
+        cancel: Option<Arc<CancellationToken>>,
     ) -> Result<()> {
+        let _running = self.ensure_running()?;
+        let _starting = self.sync_start.lock().unwrap();
         self.stop_sync();
 
         let bind: std::net::SocketAddr = format!("{}:{}", self.sync_bind_ip, port).parse().unwrap();
         let identity = self.identity.clone();
-        let trust_store: Arc<dyn openclipboard_core::TrustStore> = self.trust_store.clone();
+        let trust_store: Arc<dyn openclipboard_core::TrustStore> = self.trust_store();
         let replay = self.replay_protector.clone();
         let discovery = Arc::clone(&self.sync_discovery);
 
         struct MeshHandlerShim {
             inner: Arc<dyn EventHandler>,
             provider: Arc<dyn ClipboardProvider>,
+            file_filter: Arc<Mutex<openclipboard_core::FileTypeFilter>>,
         }
         impl openclipboard_core::SyncHandler for MeshHandlerShim {
             fn on_clipboard_text(&self, peer_id: String, text: String, ts_ms: u64) {
@@ -485,6 +977,10 @@ impl ClipboardNode {
                 let _ = self.provider.write(ClipboardContent::Text(text.clone()));
                 self.inner.on_clipboard_text(peer_id, text, ts_ms);
             }
+            fn on_clipboard_image(&self, peer_id: String, mime: String, width: u32, height: u32, bytes: Vec<u8>, ts_ms: u64) {
+                let _ = self.provider.write(ClipboardContent::Image { mime: mime.clone(), width, height, bytes: bytes.clone() });
+                self.inner.on_clipboard_image(peer_id, mime, width, height, bytes, ts_ms);
+            }
             fn on_peer_connected(&self, peer_id: String) {
                 self.inner.on_peer_connected(peer_id);
             }
@@ -494,6 +990,12 @@ impl ClipboardNode {
             fn on_error(&self, message: String) {
                 self.inner.on_error(message);
             }
+            fn allow_file(&self, peer_id: &str, name: &str, mime: &str) -> bool {
+                self.file_filter.lock().unwrap().allows(peer_id, name, mime)
+            }
+            fn on_file_received(&self, peer_id: String, name: String, path: String) {
+                self.inner.on_file_received(peer_id, name, path);
+            }
         }
 
         let adapter = ClipboardCallbackAdapter { inner: provider };
@@ -505,47 +1007,312 @@ impl ClipboardNode {
         let shim: Arc<dyn openclipboard_core::SyncHandler> = Arc::new(MeshHandlerShim {
             inner: handler_arc,
             provider: Arc::clone(&provider_arc),
+            file_filter: Arc::clone(&self.file_filter),
         });
 
-        let service = Arc::new(openclipboard_core::SyncService::new(
+        let options = openclipboard_core::SyncServiceOptions {
+            local_listen: bind,
+            device_name,
+            history_file: self.history_file.lock().unwrap().clone().map(std::path::PathBuf::from),
+        };
+        let service = Arc::new(openclipboard_core::SyncService::with_options(
             identity,
             trust_store,
             replay,
             discovery,
-            bind,
-            device_name,
             shim,
-        ).map_err(|_| OpenClipboardError::Other)?);
+            options,
+        ).map_err(|_| OpenClipboardError::Other)?.with_file_log(Arc::clone(&self.received_files)));
+        service.set_sync_whitespace(self.sync_whitespace.load(std::sync::atomic::Ordering::SeqCst));
+        self.apply_history_max_bytes(&service);
+        self.apply_disconnect_grace(&service);
+        self.apply_drain_on_stop(&service);
+        self.apply_keepalive(&service);
+        self.apply_max_incoming_file_size(&service);
+        self.apply_partial_files_dir(&service);
+        self.apply_linked_devices_file(&service)?;
+        service.set_file_rate_limits(Arc::clone(&self.file_rate_limits));
+        service.set_file_send_slots(Arc::clone(&self.file_send_slots));
+        self.apply_endpoint_factory(&service);
 
         let poll_interval = std::time::Duration::from_millis(poll_interval_ms);
-        self.runtime.block_on(async {
-            service.start_mesh(provider_arc, poll_interval).await
-        }).map_err(|e| {
-            eprintln!("start_mesh failed: {e}");
-            OpenClipboardError::Other
-        })?;
+        let started = self.block_on_cancellable(cancel.as_deref(), async {
+            service.start_mesh(provider_arc, poll_interval).await.inspect_err(|e| eprintln!("start_mesh failed: {e}"))
+        });
+        match started {
+            Ok(()) => {}
+            Err(OpenClipboardError::Cancelled) => {
+                // Tear down whatever started before the cancellation.
+                self.runtime.block_on(service.stop());
+                return Err(OpenClipboardError::Cancelled);
+            }
+            Err(e) => return Err(e),
+        }
 
         *self.sync_service.lock().unwrap() = Some(service);
         Ok(())
     }
 
+    /// Enable or disable syncing of empty / whitespace-only clipboard text in mesh mode
+    /// (enabled by default). Takes effect immediately if mesh is running and persists across restarts.
+    pub fn set_sync_whitespace(&self, enabled: bool) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.sync_whitespace.store(enabled, std::sync::atomic::Ordering::SeqCst);
+        if let Some(service) = self.sync_service.lock().unwrap().as_ref() {
+            service.set_sync_whitespace(enabled);
+        }
+    }
+
+    /// Cap the bytes of clipboard text kept in history (0 = no cap); the
+    /// oldest entries are evicted first. Takes effect immediately if sync is
+    /// running and persists across restarts.
+    pub fn set_history_max_bytes(&self, max_bytes: u64) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.history_max_bytes.store(max_bytes, std::sync::atomic::Ordering::SeqCst);
+        if let Some(service) = self.sync_service.lock().unwrap().as_ref() {
+            self.apply_history_max_bytes(service);
+        }
+    }
+
+    /// Wait `grace_ms` before reporting a peer as disconnected; a peer that
+    /// reconnects within it fires neither `on_peer_disconnected` nor another
+    /// `on_peer_connected`. 0 (the default) reports immediately. Takes effect
+    /// immediately if sync is running and persists across restarts.
+    pub fn set_disconnect_grace_ms(&self, grace_ms: u64) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.disconnect_grace_ms.store(grace_ms, std::sync::atomic::Ordering::SeqCst);
+        if let Some(service) = self.sync_service.lock().unwrap().as_ref() {
+            self.apply_disconnect_grace(service);
+        }
+    }
+
+    fn apply_disconnect_grace(&self, service: &openclipboard_core::SyncService<BoxDiscovery>) {
+        let grace_ms = self.disconnect_grace_ms.load(std::sync::atomic::Ordering::SeqCst);
+        service.set_disconnect_grace(std::time::Duration::from_millis(grace_ms));
+    }
+
+    /// When sync stops, wait up to `timeout_ms` for clips already queued for
+    /// connected peers to reach them, so one copied right before quitting isn't
+    /// lost. 0 (the default) stops immediately. Takes effect immediately if sync
+    /// is running and persists across restarts.
+    pub fn set_drain_on_stop_ms(&self, timeout_ms: u64) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.drain_on_stop_ms.store(timeout_ms, std::sync::atomic::Ordering::SeqCst);
+        if let Some(service) = self.sync_service.lock().unwrap().as_ref() {
+            self.apply_drain_on_stop(service);
+        }
+    }
+
+    fn apply_drain_on_stop(&self, service: &openclipboard_core::SyncService<BoxDiscovery>) {
+        let timeout_ms = self.drain_on_stop_ms.load(std::sync::atomic::Ordering::SeqCst);
+        service.set_drain_on_stop((timeout_ms > 0).then(|| std::time::Duration::from_millis(timeout_ms)));
+    }
+
+    /// Ping each connected peer every `interval_ms` and drop (and redial) one
+    /// whose pong doesn't come back within `timeout_ms`. An interval of 0
+    /// turns the pings off; every 30 s with a 10 s timeout by default. Applies
+    /// to sessions opened afterwards and persists across restarts.
+    pub fn set_keepalive_ms(&self, interval_ms: u64, timeout_ms: u64) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.keepalive_interval_ms.store(interval_ms, std::sync::atomic::Ordering::SeqCst);
+        self.keepalive_timeout_ms.store(timeout_ms, std::sync::atomic::Ordering::SeqCst);
+        if let Some(service) = self.sync_service.lock().unwrap().as_ref() {
+            self.apply_keepalive(service);
+        }
+    }
+
+    fn apply_keepalive(&self, service: &openclipboard_core::SyncService<BoxDiscovery>) {
+        let interval_ms = self.keepalive_interval_ms.load(std::sync::atomic::Ordering::SeqCst);
+        let timeout_ms = self.keepalive_timeout_ms.load(std::sync::atomic::Ordering::SeqCst);
+        service.set_keepalive((interval_ms > 0).then(|| openclipboard_core::Keepalive {
+            interval: std::time::Duration::from_millis(interval_ms),
+            timeout: std::time::Duration::from_millis(timeout_ms),
+        }));
+    }
+
+    /// Refuse incoming files larger than `max_bytes`, from sync peers and
+    /// through the legacy listener; 0 removes the limit. 256 MiB by default.
+    /// Takes effect immediately if sync is running and persists across restarts.
+    pub fn set_max_incoming_file_size(&self, max_bytes: u64) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.max_incoming_file_size.store(max_bytes, std::sync::atomic::Ordering::SeqCst);
+        if let Some(service) = self.sync_service.lock().unwrap().as_ref() {
+            self.apply_max_incoming_file_size(service);
+        }
+    }
+
+    fn apply_max_incoming_file_size(&self, service: &openclipboard_core::SyncService<BoxDiscovery>) {
+        let max_bytes = self.max_incoming_file_size.load(std::sync::atomic::Ordering::SeqCst);
+        service.set_max_incoming_file_size((max_bytes > 0).then_some(max_bytes));
+    }
+
+    /// Keep clipboard history across restarts in a file at `path`, encrypted
+    /// with this node's identity (`None` keeps it in memory only). Entries
+    /// already there are loaded. Takes effect the next time sync starts.
+    pub fn set_history_file(&self, path: Option<String>) {
+        let Ok(_running) = self.ensure_running() else { return };
+        *self.history_file.lock().unwrap() = path;
+    }
+
+    /// Persist the received-files log at `path` (`None` keeps it in memory),
+    /// loading the records already there. Takes effect immediately and
+    /// persists across restarts.
+    pub fn set_received_files_log(&self, path: Option<String>) -> Result<()> {
+        let _running = self.ensure_running()?;
+        self.received_files.set_persist_path(path.clone().map(std::path::PathBuf::from))?;
+        *self.received_files_log.lock().unwrap() = path;
+        Ok(())
+    }
+
+    /// Save received files in `dir`, created if missing. Defaults to an
+    /// `openclipboard` directory under the system temp dir; apps should point
+    /// it at their own storage.
+    pub fn set_download_dir(&self, dir: String) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.received_files.set_download_dir(std::path::PathBuf::from(dir));
+    }
+
+    /// Keep files cut off by a dropped connection in `dir`, so their senders
+    /// can resume them after reconnecting; parts older than a week are
+    /// removed. `None` drops them instead. Defaults to
+    /// `~/.openclipboard/partial`; apps should point it at their data dir.
+    pub fn set_partial_files_dir(&self, dir: Option<String>) {
+        let Ok(_running) = self.ensure_running() else { return };
+        let dir = dir.map(std::path::PathBuf::from);
+        if let Some(dir) = &dir {
+            openclipboard_core::PartialFiles::new(dir.clone()).expire(openclipboard_core::PARTIAL_FILE_MAX_AGE);
+        }
+        *self.partial_files_dir.lock().unwrap() = dir;
+        if let Some(service) = self.sync_service.lock().unwrap().as_ref() {
+            self.apply_partial_files_dir(service);
+        }
+    }
+
+    fn apply_partial_files_dir(&self, service: &openclipboard_core::SyncService<BoxDiscovery>) {
+        service.file_log().set_partial_dir(self.partial_files_dir.lock().unwrap().clone());
+    }
+
+    /// Persist which trusted peers are this owner's own devices at `path`,
+    /// loading the ones already there. `None` keeps them in memory only.
+    pub fn set_linked_devices_file(&self, path: Option<String>) -> Result<()> {
+        let _running = self.ensure_running()?;
+        *self.linked_devices_file.lock().unwrap() = path;
+        if let Some(service) = self.sync_service.lock().unwrap().clone() {
+            self.apply_linked_devices_file(&service)?;
+        }
+        Ok(())
+    }
+
+    fn apply_linked_devices_file(&self, service: &openclipboard_core::SyncService<BoxDiscovery>) -> Result<()> {
+        let path = self.linked_devices_file.lock().unwrap().clone();
+        self.runtime.block_on(service.set_linked_devices_file(path.map(std::path::PathBuf::from)))?;
+        Ok(())
+    }
+
+    /// Mark (or unmark) a trusted peer as another of this owner's devices;
+    /// linked devices exchange trust lists, including removals. Requires sync
+    /// to be running.
+    pub fn set_linked_device(&self, peer_id: String, linked: bool) -> Result<()> {
+        let _running = self.ensure_running()?;
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return Err(OpenClipboardError::Other);
+        };
+        self.runtime.block_on(service.set_linked_device(&peer_id, linked))?;
+        Ok(())
+    }
+
+    /// Linked devices, sorted. Empty while sync isn't running.
+    pub fn linked_devices(&self) -> Vec<String> {
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return Vec::new();
+        };
+        self.runtime.block_on(service.linked_devices())
+    }
+
+    /// Send the trust list to every connected linked device now, e.g. after
+    /// pairing or removing a peer. Returns how many it was queued for.
+    pub fn sync_trust(&self) -> u32 {
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return 0;
+        };
+        self.runtime.block_on(service.sync_trust()) as u32
+    }
+
+    /// Stop trusting `peer_id`. While sync runs this also drops its session,
+    /// replay nonces and peer entry and tells linked devices; otherwise the
+    /// revocation is only written to the trust store. Returns `false` if it
+    /// wasn't trusted.
+    pub fn remove_trusted_peer(&self, peer_id: String) -> Result<bool> {
+        let _running = self.ensure_running()?;
+        let service = self.sync_service.lock().unwrap().clone();
+        match service {
+            Some(service) => Ok(self.runtime.block_on(service.remove_trusted_peer(&peer_id))?),
+            None => Ok(self.trust_store().revoke(&peer_id)?),
+        }
+    }
+
+    /// Files received over sync or `start_listener`, newest first.
+    pub fn received_files(&self) -> Vec<ReceivedFileEntry> {
+        self.received_files.list().into_iter().map(Into::into).collect()
+    }
+
+    /// Cap how fast files go out to all peers together, in bytes per second; 0
+    /// (the default) is unlimited. Covers `send_file_to_peer` and
+    /// `connect_and_send_file`, and applies to transfers already running.
+    pub fn set_file_rate_limit(&self, bytes_per_sec: u64) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.file_rate_limits.set_global(Some(bytes_per_sec));
+    }
+
+    /// Cap how fast files go out to `peer_id`, in bytes per second, on top of
+    /// the global limit; 0 leaves only the global limit.
+    pub fn set_peer_file_rate_limit(&self, peer_id: String, bytes_per_sec: u64) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.file_rate_limits.set_peer(&peer_id, Some(bytes_per_sec));
+    }
+
+    /// Create sync's listener endpoint with `factory` (`None` restores the
+    /// default) from the next `start_sync` / `start_mesh` on. Rust only, not
+    /// part of the UniFFI interface: lets tests fail the bind on demand.
+    pub fn set_server_endpoint_factory(&self, factory: Option<ServerEndpointFactory>) {
+        let Ok(_running) = self.ensure_running() else { return };
+        *self.endpoint_factory.lock().unwrap() = factory;
+    }
+
+    fn apply_endpoint_factory(&self, service: &openclipboard_core::SyncService<BoxDiscovery>) {
+        if let Some(factory) = self.endpoint_factory.lock().unwrap().clone() {
+            service.set_endpoint_factory(factory);
+        }
+    }
+
+    fn apply_history_max_bytes(&self, service: &openclipboard_core::SyncService<BoxDiscovery>) {
+        let max_bytes = self.history_max_bytes.load(std::sync::atomic::Ordering::SeqCst);
+        service.history().set_max_bytes((max_bytes > 0).then_some(max_bytes as usize));
+    }
+
     pub fn start_sync(&self, port: u16, device_name: String, handler: Box<dyn EventHandler>) -> Result<()> {
+        let _running = self.ensure_running()?;
+        let _starting = self.sync_start.lock().unwrap();
         // Stop any previous sync instance.
         self.stop_sync();
 
         let bind: std::net::SocketAddr = format!("{}:{}", self.sync_bind_ip, port).parse().unwrap();
         let identity = self.identity.clone();
-        let trust_store: Arc<dyn openclipboard_core::TrustStore> = self.trust_store.clone();
+        let trust_store: Arc<dyn openclipboard_core::TrustStore> = self.trust_store();
         let replay = self.replay_protector.clone();
         let discovery = Arc::clone(&self.sync_discovery);
 
         struct HandlerShim {
             inner: Arc<dyn EventHandler>,
+            file_filter: Arc<Mutex<openclipboard_core::FileTypeFilter>>,
         }
         impl openclipboard_core::SyncHandler for HandlerShim {
             fn on_clipboard_text(&self, peer_id: String, text: String, ts_ms: u64) {
                 self.inner.on_clipboard_text(peer_id, text, ts_ms);
             }
+            fn on_clipboard_image(&self, peer_id: String, mime: String, width: u32, height: u32, bytes: Vec<u8>, ts_ms: u64) {
+                self.inner.on_clipboard_image(peer_id, mime, width, height, bytes, ts_ms);
+            }
             fn on_peer_connected(&self, peer_id: String) {
                 self.inner.on_peer_connected(peer_id);
             }
@@ -555,26 +1322,49 @@ impl ClipboardNode {
             fn on_error(&self, message: String) {
                 self.inner.on_error(message);
             }
+            fn allow_file(&self, peer_id: &str, name: &str, mime: &str) -> bool {
+                self.file_filter.lock().unwrap().allows(peer_id, name, mime)
+            }
+            fn on_file_received(&self, peer_id: String, name: String, path: String) {
+                self.inner.on_file_received(peer_id, name, path);
+            }
         }
 
         let handler_arc: Arc<dyn EventHandler> = handler.into();
-        let shim: Arc<dyn openclipboard_core::SyncHandler> = Arc::new(HandlerShim { inner: handler_arc });
+        let shim: Arc<dyn openclipboard_core::SyncHandler> = Arc::new(HandlerShim {
+            inner: handler_arc,
+            file_filter: Arc::clone(&self.file_filter),
+        });
 
-        let service = Arc::new(openclipboard_core::SyncService::new(
+        let options = openclipboard_core::SyncServiceOptions {
+            local_listen: bind,
+            device_name,
+            history_file: self.history_file.lock().unwrap().clone().map(std::path::PathBuf::from),
+        };
+        let service = Arc::new(openclipboard_core::SyncService::with_options(
             identity,
             trust_store,
             replay,
             discovery,
-            bind,
-            device_name,
             shim,
-        ).map_err(|_| OpenClipboardError::Other)?);
+            options,
+        ).map_err(|_| OpenClipboardError::Other)?.with_file_log(Arc::clone(&self.received_files)));
+        self.apply_history_max_bytes(&service);
+        self.apply_disconnect_grace(&service);
+        self.apply_drain_on_stop(&service);
+        self.apply_keepalive(&service);
+        self.apply_max_incoming_file_size(&service);
+        self.apply_partial_files_dir(&service);
+        self.apply_linked_devices_file(&service)?;
+        service.set_file_rate_limits(Arc::clone(&self.file_rate_limits));
+        service.set_file_send_slots(Arc::clone(&self.file_send_slots));
+        self.apply_endpoint_factory(&service);
 
         self.runtime.block_on(async {
             service.start().await
         }).map_err(|e| {
             eprintln!("start_sync failed: {e}");
-            OpenClipboardError::Other
+            OpenClipboardError::from(e)
         })?;
 
         *self.sync_service.lock().unwrap() = Some(service);
@@ -589,7 +1379,21 @@ impl ClipboardNode {
         }
     }
 
+    /// Switch the active trust store ("profile") to the one at `trust_path`.
+    ///
+    /// Stops sync so no session outlives the old trust set; call `start_sync` /
+    /// `start_mesh` again to sync with the new profile's peers. The identity is
+    /// shared across profiles.
+    pub fn set_profile(&self, trust_path: String) -> Result<()> {
+        let _running = self.ensure_running()?;
+        let store = Arc::new(FileTrustStore::new(std::path::PathBuf::from(trust_path))?);
+        self.stop_sync();
+        *self.trust_store.lock().unwrap() = store;
+        Ok(())
+    }
+
     pub fn send_clipboard_text(&self, text: String) -> Result<()> {
+        let _running = self.ensure_running()?;
         let Some(service) = self.sync_service.lock().unwrap().clone() else {
             return Err(OpenClipboardError::Other);
         };
@@ -599,10 +1403,89 @@ impl ClipboardNode {
         Ok(())
     }
 
+    /// Send an image to every connected peer. Images too large to sync are
+    /// refused before sending, with the reason passed to `on_error`.
+    pub fn send_clipboard_image(&self, mime: String, width: u32, height: u32, bytes: Vec<u8>) -> Result<()> {
+        let _running = self.ensure_running()?;
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return Err(OpenClipboardError::Other);
+        };
+        self.runtime.block_on(service.broadcast_clip_image(mime, width, height, bytes))?;
+        Ok(())
+    }
+
+    /// Send a file to a peer connected through `start_sync` / `start_mesh`, over
+    /// the existing session rather than a new connection, reading it from disk
+    /// as it goes; its MIME type comes from its extension. Blocks until the last
+    /// chunk is sent; fails if the peer isn't connected, we don't send to it,
+    /// or it rejects the file.
+    pub fn send_file_to_peer(
+        &self,
+        peer_id: String,
+        file_path: String,
+        progress: Box<dyn FileProgressHandler>,
+    ) -> Result<()> {
+        let _running = self.ensure_running()?;
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return Err(OpenClipboardError::Other);
+        };
+        let path = std::path::PathBuf::from(file_path);
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let mime = openclipboard_core::mime_for_name(&name).to_string();
+        let progress: Arc<dyn FileProgressHandler> = progress.into();
+        let progress: openclipboard_core::FileProgressFn = Arc::new(move |sent, total| progress.on_progress(sent, total));
+
+        self.runtime.block_on(async {
+            service.send_file_path_to_peer(&peer_id, &path, mime, Some(progress)).await
+        })?;
+        Ok(())
+    }
+
+    /// Set which way clips flow with a trusted peer; applies to open sessions too.
+    /// Returns false if the peer isn't trusted.
+    pub fn set_peer_sync_direction(&self, peer_id: String, direction: SyncDirection) -> Result<bool> {
+        let _running = self.ensure_running()?;
+        let trust_store = self.trust_store();
+        let Some(mut record) = trust_store.get(&peer_id)? else {
+            return Ok(false);
+        };
+        record.sync_direction = direction.into();
+        trust_store.save(record)?;
+        Ok(true)
+    }
+
+    /// Re-send the current clipboard text to all peers, bypassing echo suppression.
+    /// Requires mesh mode; returns false if the clipboard holds no text.
+    pub fn resend_current(&self) -> Result<bool> {
+        let _running = self.ensure_running()?;
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return Err(OpenClipboardError::Other);
+        };
+        let Some(provider) = self.mesh_provider.lock().unwrap().clone() else {
+            return Err(OpenClipboardError::Other);
+        };
+        self.runtime
+            .block_on(async { service.resend_current(provider.as_ref()).await })
+            .map_err(|_| OpenClipboardError::Other)
+    }
+
+    /// Forget recently received clips so re-copying one of them syncs it onward.
+    pub fn clear_echo_suppressor(&self) {
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return;
+        };
+        self.runtime.block_on(async { service.clear_echo_suppressor().await });
+    }
+
     pub fn start_listener(&self, port: u16, handler: Box<dyn EventHandler>) -> Result<()> {
+        let _running = self.ensure_running()?;
         let identity = self.identity.clone();
-        let trust_store = self.trust_store.clone();
+        let trust_store = self.trust_store();
         let replay_protector = self.replay_protector.clone();
+        let received_files = Arc::clone(&self.received_files);
+        let file_filter = Arc::clone(&self.file_filter);
+        let max_incoming_file_size = Arc::clone(&self.max_incoming_file_size);
+        let partial_files_dir = Arc::clone(&self.partial_files_dir);
 
         // Bind synchronously so callers can connect immediately after this returns.
         // (The previous implementation raced: connect could happen before the endpoint was bound.)
@@ -628,6 +1511,7 @@ impl ClipboardNode {
             loop {
                 let conn = match listener.accept().await {
                     Ok(conn) => conn,
+                    Err(e) if AcceptError::of(&e) == Some(&AcceptError::Closed) => break,
                     Err(e) => {
                         handler.on_error(format!("Failed to accept connection: {}", e));
                         continue;
@@ -640,7 +1524,8 @@ impl ClipboardNode {
                     MockClipboard::new(),
                     trust_store.clone(),
                     replay_protector.clone(),
-                );
+                )
+                .with_raw_file_chunks();
 
                 let peer_id = match session.handshake().await {
                     Ok(peer_id) => {
@@ -653,12 +1538,17 @@ impl ClipboardNode {
                     }
                 };
 
-                let mut files: HashMap<String, IncomingFile> = HashMap::new();
+                let mut files: HashMap<String, FileAssembler> = HashMap::new();
+                // Files cut off by a disconnect are kept here for the sender to resume.
+                let partials = partial_files_dir.lock().unwrap().clone().map(openclipboard_core::PartialFiles::new);
 
                 loop {
                     let msg = match session.recv_message().await {
                         Ok(m) => m,
                         Err(_) => {
+                            if let Some(Err(e)) = partials.as_ref().map(|p| p.save_all(&peer_id, &mut files)) {
+                                handler.on_error(format!("Failed to save partial files: {e:#}"));
+                            }
                             handler.on_peer_disconnected(peer_id.clone());
                             break;
                         }
@@ -668,37 +1558,88 @@ impl ClipboardNode {
                         Message::ClipText { text, ts_ms, .. } => {
                             handler.on_clipboard_text(peer_id.clone(), text, ts_ms);
                         }
-                        Message::FileOffer { file_id, name, size, .. } => {
-                            files.insert(
-                                file_id.clone(),
-                                IncomingFile { name: name.clone(), expected: size, buf: Vec::new() },
-                            );
+                        Message::Ping { ts_ms } => {
+                            // The sender is checking we've read everything before it hangs up.
+                            let _ = session.send_pong(ts_ms).await;
+                        }
+                        Message::FileOffer { file_id, name, size, mime, hash } => {
+                            if !file_filter.lock().unwrap().allows(&peer_id, &name, &mime) {
+                                let reason = openclipboard_core::REJECT_MIME_NOT_ALLOWED;
+                                if session.send_file_reject(&file_id, reason).await.is_err() {
+                                    handler.on_error("Failed to send file reject".to_string());
+                                }
+                                continue;
+                            }
+                            let max_size = max_incoming_file_size.load(std::sync::atomic::Ordering::SeqCst);
+                            if max_size > 0 && size > max_size {
+                                if session.send_file_reject(&file_id, openclipboard_core::REJECT_TOO_LARGE).await.is_err() {
+                                    handler.on_error("Failed to send file reject".to_string());
+                                }
+                                continue;
+                            }
+                            let mut file = FileAssembler::new(name.clone(), size).with_hash(hash.clone());
+                            if let Some(partials) = &partials {
+                                file = partials.resume(&peer_id, &file_id, file).unwrap_or_else(|e| {
+                                    handler.on_error(format!("Failed to resume file: {e:#}"));
+                                    FileAssembler::new(name, size).with_hash(hash)
+                                });
+                            }
+                            files.insert(file_id.clone(), file);
                             if session.send_file_accept(&file_id).await.is_err() {
                                 handler.on_error("Failed to send file accept".to_string());
                             }
                         }
-                        Message::FileChunk { file_id, data_b64, .. } => {
-                            if let Ok(data) = base64::engine::general_purpose::STANDARD.decode(data_b64) {
-                                if let Some(f) = files.get_mut(&file_id) {
-                                    f.buf.extend_from_slice(&data);
+                        Message::FileChunk { file_id, offset, data_b64 } => {
+                            let Some(f) = files.get_mut(&file_id) else { continue };
+                            let placed = openclipboard_core::Base64Alphabet::Standard
+                                .decode(data_b64)
+                                .and_then(|data| f.insert(offset, &data));
+                            if let Err(e) = placed {
+                                handler.on_error(format!("Bad file chunk: {e}"));
+                                files.remove(&file_id);
+                                if let Some(partials) = &partials {
+                                    partials.discard(&peer_id, &file_id);
+                                }
+                            }
+                        }
+                        Message::FileChunkRaw { file_id, offset, data } => {
+                            let Some(f) = files.get_mut(&file_id) else { continue };
+                            if let Err(e) = f.insert(offset, &data) {
+                                handler.on_error(format!("Bad file chunk: {e}"));
+                                files.remove(&file_id);
+                                if let Some(partials) = &partials {
+                                    partials.discard(&peer_id, &file_id);
                                 }
                             }
                         }
-                        Message::FileDone { file_id, .. } => {
+                        Message::FileResumeQuery { file_id } => {
+                            let offset = match files.get(&file_id) {
+                                Some(f) => f.next_offset(),
+                                None => partials.as_ref().map_or(0, |p| p.saved_len(&peer_id, &file_id)),
+                            };
+                            if session.send_file_resume_offset(&file_id, offset).await.is_err() {
+                                handler.on_error("Failed to send file resume offset".to_string());
+                            }
+                        }
+                        Message::FileDone { file_id, hash } => {
                             if let Some(f) = files.remove(&file_id) {
-                                // Save file to temp directory
-                                let temp_dir = std::env::temp_dir().join("openclipboard");
-                                let _ = std::fs::create_dir_all(&temp_dir);
-                                let safe_name = f.name.chars()
-                                    .map(|c| if c.is_alphanumeric() || c == '.' || c == '-' || c == '_' { c } else { '_' })
-                                    .collect::<String>();
-                                let temp_path = temp_dir.join(safe_name);
-                                if std::fs::write(&temp_path, &f.buf).is_ok() {
-                                    handler.on_file_received(
-                                        peer_id.clone(),
-                                        f.name,
-                                        temp_path.to_string_lossy().to_string(),
-                                    );
+                                if let Some(partials) = &partials {
+                                    partials.discard(&peer_id, &file_id);
+                                }
+                                let name = f.name.clone();
+                                match f.finish() {
+                                    Ok(data) => {
+                                        // Same content as a file this peer already sent: point at that one instead.
+                                        let saved = match received_files.find_by_hash(&peer_id, &hash) {
+                                            Some(existing) => Ok(existing),
+                                            None => received_files.save(&peer_id, &name, &data, &hash),
+                                        };
+                                        match saved {
+                                            Ok(file) => handler.on_file_received(peer_id.clone(), name, file.path),
+                                            Err(e) => handler.on_error(format!("Failed to save {name}: {e:#}")),
+                                        }
+                                    }
+                                    Err(e) => handler.on_error(format!("Dropping file: {e}")),
                                 }
                             }
                         }
@@ -713,8 +1654,9 @@ impl ClipboardNode {
     }
 
     pub fn connect_and_send_text(&self, addr: String, text: String) -> Result<()> {
+        let _running = self.ensure_running()?;
         let identity = self.identity.clone();
-        let trust_store = self.trust_store.clone();
+        let trust_store = self.trust_store();
         let replay_protector = self.replay_protector.clone();
 
         self.runtime.block_on(async move {
@@ -736,8 +1678,8 @@ impl ClipboardNode {
             session.clipboard.write(ClipboardContent::Text(text))?;
             session.send_clipboard().await?;
 
-            // Give the receiver a beat to process before we drop the connection (CI can be slow).
-            tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+            // Don't drop the connection before the receiver has read the clip.
+            session.confirm_delivery(DELIVERY_TIMEOUT).await?;
 
             Ok::<_, anyhow::Error>(())
         })?;
@@ -745,13 +1687,70 @@ impl ClipboardNode {
         Ok(())
     }
 
-    pub fn connect_and_send_file(&self, addr: String, file_path: String) -> Result<()> {
+    /// Incoming file types accepted from every peer without rules of its own;
+    /// see `FileTypeRules` for the pattern syntax. Empty lists accept anything.
+    pub fn set_file_type_rules(&self, allow: Vec<String>, deny: Vec<String>) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.file_filter
+            .lock()
+            .unwrap()
+            .set_global(openclipboard_core::FileTypeRules { allow, deny });
+    }
+
+    /// Incoming file types accepted from `peer_id`, replacing the global rules for it.
+    pub fn set_peer_file_type_rules(&self, peer_id: String, allow: Vec<String>, deny: Vec<String>) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.file_filter
+            .lock()
+            .unwrap()
+            .set_peer(&peer_id, Some(openclipboard_core::FileTypeRules { allow, deny }));
+    }
+
+    /// Judge `peer_id`'s files by the global rules again.
+    pub fn clear_peer_file_type_rules(&self, peer_id: String) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.file_filter.lock().unwrap().set_peer(&peer_id, None);
+    }
+
+    /// Limit how many `connect_and_send_file` and `send_file_to_peer` calls run
+    /// at once (minimum 1); further calls wait for a free slot. Applies to sends
+    /// started after the change.
+    pub fn set_max_concurrent_file_sends(&self, limit: u32) {
+        let Ok(_running) = self.ensure_running() else { return };
+        self.file_send_slots.set_limit(Some(limit as usize));
+    }
+
+    /// Cut files sent by `connect_and_send_file` into `chunk_size`-byte chunks,
+    /// preparing up to `window` ahead of the one being sent. 0 keeps the
+    /// default for either. Applies to sends started after the change.
+    pub fn set_file_transfer_config(&self, chunk_size: u32, window: u32) {
+        let Ok(_running) = self.ensure_running() else { return };
+        let default = openclipboard_core::FileTransferConfig::default();
+        *self.file_transfer.lock().unwrap() = openclipboard_core::FileTransferConfig {
+            chunk_size: if chunk_size == 0 { default.chunk_size } else { chunk_size as usize },
+            window: if window == 0 { default.window } else { window as usize },
+        };
+    }
+
+    /// Connect to `addr` and send the file at `file_path`. Cancelling `cancel`
+    /// aborts the transfer, closing the connection, and fails with `Cancelled`.
+    pub fn connect_and_send_file(
+        &self,
+        addr: String,
+        file_path: String,
+        cancel: Option<Arc<CancellationToken>>,
+    ) -> Result<()> {
+        let _running = self.ensure_running()?;
         let identity = self.identity.clone();
-        let trust_store = self.trust_store.clone();
+        let trust_store = self.trust_store();
         let replay_protector = self.replay_protector.clone();
         let file_path = std::path::PathBuf::from(file_path);
+        let slots = Arc::clone(&self.file_send_slots);
+        let rate_limits = Arc::clone(&self.file_rate_limits);
+        let config = *self.file_transfer.lock().unwrap();
 
-        self.runtime.block_on(async move {
+        self.block_on_cancellable(cancel.as_deref(), async move {
+            let _slot = slots.acquire().await;
             let endpoint = make_insecure_client_endpoint()?;
             let transport = QuicTransport::new(endpoint);
             let conn = transport.connect(&addr).await?;
@@ -762,12 +1761,17 @@ impl ClipboardNode {
                 MockClipboard::new(),
                 trust_store,
                 replay_protector,
-            );
+            )
+            .with_raw_file_chunks();
 
-            session.handshake().await?;
+            let peer_id = session.handshake().await?;
+            session.set_file_rate_limiters(rate_limits.for_peer(&peer_id));
 
             // Use the send_file helper from cli
-            Self::send_file_internal(&session, &file_path).await?;
+            Self::send_file_internal(&session, &file_path, &config).await?;
+
+            // Let the receiver read the last chunks before the connection drops.
+            session.confirm_delivery(DELIVERY_TIMEOUT).await?;
 
             Ok::<_, anyhow::Error>(())
         })?;
@@ -776,6 +1780,7 @@ impl ClipboardNode {
     }
 
     pub fn start_discovery(&self, device_name: String, handler: Box<dyn DiscoveryHandler>) -> Result<()> {
+        let _running = self.ensure_running()?;
         let identity = self.identity.clone();
         let discovery = Arc::clone(&self.discovery);
 
@@ -785,6 +1790,7 @@ impl ClipboardNode {
                 peer_id: identity.peer_id().to_string(),
                 name: device_name,
                 addr: "127.0.0.1:7651".to_string(), // Default port, should be configurable
+                alt_addrs: Vec::new(),
             };
 
             match discovery.start_discovery(peer_info).await {
@@ -814,17 +1820,25 @@ impl ClipboardNode {
         Ok(())
     }
 
+    /// Stop discovery, returning once the event task is gone and the mDNS
+    /// service is unregistered.
     pub fn stop_discovery(&self) {
-        if let Some(handle) = self.discovery_handle.lock().unwrap().take() {
-            handle.abort();
-        }
-
+        let handle = self.discovery_handle.lock().unwrap().take();
         let discovery = Arc::clone(&self.discovery);
-        self.runtime.spawn(async move {
+        self.runtime.block_on(async move {
+            if let Some(handle) = handle {
+                handle.abort();
+                let _ = handle.await;
+            }
             let _ = discovery.stop_discovery().await;
         });
     }
 
+    /// Whether `start_discovery` is in effect (until `stop_discovery` or `stop`).
+    pub fn is_discovering(&self) -> bool {
+        self.discovery_handle.lock().unwrap().is_some() || self.discovery.is_running()
+    }
+
     pub fn get_clipboard_history(&self, limit: u32) -> Vec<ClipboardHistoryEntry> {
         let service = self.sync_service.lock().unwrap();
         match service.as_ref() {
@@ -841,44 +1855,266 @@ impl ClipboardNode {
         }
     }
 
+    /// Peers that sent clips still in history, with counts; most recent first.
+    pub fn get_clipboard_peer_summary(&self) -> Vec<PeerClipSummary> {
+        let service = self.sync_service.lock().unwrap();
+        match service.as_ref() {
+            Some(s) => s
+                .history()
+                .peer_summary()
+                .into_iter()
+                .map(|(peer_id, count, last_timestamp)| PeerClipSummary { peer_id, count: count as u32, last_timestamp })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Preview of the last clip received from `peer_id` (for a devices list).
+    pub fn last_clip_for_peer(&self, peer_id: String) -> Option<String> {
+        let service = self.sync_service.lock().unwrap();
+        service.as_ref().and_then(|s| s.last_clip_for_peer(&peer_id))
+    }
+
     pub fn recall_from_history(&self, entry_id: String) -> Result<ClipboardHistoryEntry> {
+        let _running = self.ensure_running()?;
         let service = self.sync_service.lock().unwrap();
         let service = service.as_ref().ok_or(OpenClipboardError::Other)?;
 
         let entry = service.history().get_by_id(&entry_id).ok_or(OpenClipboardError::Other)?;
+        // Only text can be written back through the text clipboard callback.
+        if entry.kind != openclipboard_core::HistoryItemKind::Text {
+            return Err(OpenClipboardError::Other);
+        }
 
         let provider = self.mesh_provider.lock().unwrap();
         let provider = provider.as_ref().ok_or(OpenClipboardError::Other)?;
 
-        // Set silent flag so the watcher won't broadcast this write.
-        service.silent_write_flag().store(true, std::sync::atomic::Ordering::SeqCst);
-
-        // Also note in echo suppressor as extra safety.
-        self.runtime.block_on(async {
-            service.echo_suppressor().lock().await.note_remote_write(&entry.content);
+        // Note in the echo suppressor so the watcher won't broadcast this write.
+        let noted = self.runtime.block_on(async {
+            service.echo_suppressor().lock().await.note_remote_write(&entry.content)
         });
+        // A write that didn't land mustn't keep the user copying the same text
+        // later from being broadcast.
+        let forget_noted = || {
+            self.runtime.block_on(async {
+                service.echo_suppressor().lock().await.forget_remote_write(noted.clone());
+            });
+        };
 
-        provider.write(ClipboardContent::Text(entry.content.clone()))
-            .map_err(|_| OpenClipboardError::Other)?;
+        if provider.write(ClipboardContent::Text(entry.content.clone())).is_err() {
+            forget_noted();
+            return Err(OpenClipboardError::Other);
+        }
+
+        // Platform callbacks can't report a failed write, so read it back and
+        // only then mark it silent as extra safety.
+        let written = matches!(provider.read(), Ok(ClipboardContent::Text(t)) if t == entry.content);
+        if !written {
+            forget_noted();
+            service.disarm_silent_write();
+            return Err(OpenClipboardError::Other);
+        }
+        service.arm_silent_write(&entry.content, RECALL_SILENT_WRITE_TIMEOUT);
 
         Ok(entry.into())
     }
 
+    /// Disconnect from a peer and stop reconnecting to it until `resume_peer`.
+    /// Returns true if the peer was connected.
+    pub fn disconnect_peer(&self, peer_id: String) -> bool {
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return false;
+        };
+        self.runtime.block_on(async { service.disconnect_peer(&peer_id).await })
+    }
+
+    /// Every open sync session, sorted by peer_id. Empty when sync isn't running.
+    pub fn active_sessions(&self) -> Vec<SessionInfo> {
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return Vec::new();
+        };
+        self.runtime.block_on(service.active_sessions()).into_iter().map(Into::into).collect()
+    }
+
+    /// Drop the current connection to a peer without untrusting it or
+    /// stopping it from reconnecting (see `disconnect_peer` for that).
+    /// Returns true if the peer was connected.
+    pub fn terminate_session(&self, peer_id: String) -> bool {
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return false;
+        };
+        self.runtime.block_on(async { service.terminate_session(&peer_id).await })
+    }
+
+    /// Allow a peer disconnected via `disconnect_peer` to reconnect.
+    pub fn resume_peer(&self, peer_id: String) {
+        if let Some(service) = self.sync_service.lock().unwrap().as_ref() {
+            service.resume_peer(&peer_id);
+        }
+    }
+
+    /// Untrusted peers that recently tried to connect, newest first.
+    pub fn pending_requests(&self) -> Vec<PendingRequest> {
+        let service = self.sync_service.lock().unwrap();
+        match service.as_ref() {
+            Some(s) => s.pending_requests().into_iter().map(Into::into).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Up to `limit` of the running service's most recent errors, newest
+    /// first. Empty when sync isn't running.
+    pub fn recent_errors(&self, limit: u32) -> Vec<ErrorEntry> {
+        let service = self.sync_service.lock().unwrap();
+        match service.as_ref() {
+            Some(s) => s.recent_errors(limit as usize).into_iter().map(Into::into).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Peers in the running service's registry, with every address each one
+    /// advertised. Empty when sync isn't running.
+    pub fn list_peers(&self) -> Vec<PeerEntry> {
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return Vec::new();
+        };
+        self.runtime
+            .block_on(service.peer_registry().list_all())
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Everything a freshly attached UI needs to render, read in one go so the
+    /// pieces agree. Empty when sync isn't running.
+    pub fn snapshot(&self, history_limit: u32) -> NodeSnapshot {
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return NodeSnapshot::default();
+        };
+        self.runtime.block_on(service.snapshot(history_limit as usize)).into()
+    }
+
+    /// A JSON bundle for support: this node's peer_id, trusted peers (ids and
+    /// names), known peers and their state, recent sync errors, counters and
+    /// settings. Never includes the signing key or any clipboard content, so
+    /// it is safe to attach to a bug report.
+    pub fn diagnostics(&self) -> String {
+        let service = self.sync_service.lock().unwrap().clone();
+        let trusted: Vec<_> = self
+            .trust_store()
+            .list()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|r| serde_json::json!({ "peer_id": r.peer_id, "display_name": r.display_name }))
+            .collect();
+        let errors: Vec<_> = service
+            .iter()
+            .flat_map(|s| s.recent_errors(DIAGNOSTICS_ERRORS))
+            .map(|e| {
+                serde_json::json!({
+                    "timestamp_ms": e.timestamp_ms,
+                    "peer_id": e.peer_id,
+                    "category": e.category.as_str(),
+                    "message": e.message,
+                })
+            })
+            .collect();
+
+        let mut bundle = serde_json::json!({
+            "peer_id": self.peer_id(),
+            "stopped": self.stopped.load(std::sync::atomic::Ordering::SeqCst),
+            "sync_running": service.is_some(),
+            "mesh": self.mesh_provider.lock().unwrap().is_some(),
+            "trusted_peers": trusted,
+            "recent_errors": errors,
+            "config": {
+                "sync_whitespace": self.sync_whitespace.load(std::sync::atomic::Ordering::SeqCst),
+                "history_max_bytes": self.history_max_bytes.load(std::sync::atomic::Ordering::SeqCst),
+                "disconnect_grace_ms": self.disconnect_grace_ms.load(std::sync::atomic::Ordering::SeqCst),
+                "drain_on_stop_ms": self.drain_on_stop_ms.load(std::sync::atomic::Ordering::SeqCst),
+                "keepalive_interval_ms": self.keepalive_interval_ms.load(std::sync::atomic::Ordering::SeqCst),
+                "keepalive_timeout_ms": self.keepalive_timeout_ms.load(std::sync::atomic::Ordering::SeqCst),
+                "history_file": self.history_file.lock().unwrap().is_some(),
+                "received_files_log": self.received_files_log.lock().unwrap().is_some(),
+                "partial_files_dir": self.partial_files_dir.lock().unwrap().is_some(),
+            },
+        });
+
+        if let Some(service) = service {
+            let snapshot = self.runtime.block_on(service.snapshot(0));
+            let unreachable = service.unreachable_peers();
+            let mut peers: Vec<_> = snapshot
+                .peers
+                .iter()
+                .map(|p| {
+                    serde_json::json!({
+                        "peer_id": p.peer_id,
+                        "label": p.label,
+                        "status": format!("{:?}", p.status).to_lowercase(),
+                        "connected": snapshot.connected.contains(&p.peer_id),
+                        "suspended": snapshot.suspended.contains(&p.peer_id),
+                        "encrypted": p.encrypted,
+                        "addresses": p.addresses,
+                        "platform": p.platform,
+                        "app_version": p.app_version,
+                    })
+                })
+                .collect();
+            // Outside mesh mode the registry isn't loaded, so sessions may have no entry.
+            for peer_id in &snapshot.connected {
+                if !snapshot.peers.iter().any(|p| &p.peer_id == peer_id) {
+                    peers.push(serde_json::json!({ "peer_id": peer_id, "status": "online", "connected": true }));
+                }
+            }
+            bundle["device_name"] = service.device_name().into();
+            bundle["listen_addrs"] = service.listen_addrs().iter().map(|a| a.to_string()).collect();
+            bundle["peers"] = peers.into();
+            bundle["metrics"] = serde_json::json!({
+                "connected_peers": snapshot.connected.len(),
+                "known_peers": snapshot.peers.len(),
+                "unreachable_peers": unreachable.len(),
+                "pending_requests": snapshot.pending_requests.len(),
+                "history_entries": snapshot.history_len,
+                "history_bytes": snapshot.history_bytes,
+                "received_files": service.received_files().len(),
+                "connection_events": service.events_since(0).connections.len(),
+            });
+            bundle["config"]["require_encryption"] = service.requires_encryption().into();
+            bundle["config"]["quiet_now"] = service.is_quiet().into();
+        }
+        serde_json::to_string_pretty(&bundle).expect("diagnostics serialize")
+    }
+
+    /// Trust a peer from `pending_requests` so its next connection attempt succeeds.
+    /// Returns false if no such request is pending.
+    pub fn accept_pending_request(&self, peer_id: String, display_name: String) -> Result<bool> {
+        let _running = self.ensure_running()?;
+        let Some(service) = self.sync_service.lock().unwrap().clone() else {
+            return Ok(false);
+        };
+        let accepted = self.runtime.block_on(async {
+            service.accept_pending_request(&peer_id, &display_name).await
+        })?;
+        Ok(accepted)
+    }
+
     /// Pair with a remote device by processing its QR string.
     /// Parses the QR payload, adds the remote peer to the local trust store,
     /// and initiates a connection.
     pub fn pair_via_qr(&self, qr_string: String) -> Result<String> {
+        let _running = self.ensure_running()?;
         let payload = openclipboard_core::PairingPayload::from_qr_string(&qr_string)?;
 
         // Add the remote peer to our trust store
-        let pk_b64 = base64::engine::general_purpose::STANDARD.encode(&payload.identity_pk);
+        let pk_b64 = openclipboard_core::Base64Alphabet::Standard.encode(&payload.identity_pk);
         let record = openclipboard_core::TrustRecord {
             peer_id: payload.peer_id.clone(),
             identity_pk: payload.identity_pk.clone(),
             display_name: payload.name.clone(),
             created_at: chrono::Utc::now(),
+            sync_direction: Default::default(),
         };
-        self.trust_store.save(record)?;
+        self.trust_store().save(record)?;
 
         // If sync service is running, tell it to expect this peer to auto-trust back,
         // reload the registry, and dial them.
@@ -902,7 +2138,7 @@ impl ClipboardNode {
             let peer_id = payload.peer_id.clone();
             self.runtime.block_on(async {
                 // Reload trust store into peer registry
-                let _ = service.peer_registry().load_from_trust(self.trust_store.as_ref()).await;
+                let _ = service.peer_registry().load_from_trust(self.trust_store().as_ref()).await;
 
                 for addr in &addrs {
                     match service.dial_peer_for_pair(addr).await {
@@ -922,6 +2158,7 @@ impl ClipboardNode {
     /// Enable auto-trust mode: any peer that connects and completes the handshake
     /// while we're showing our QR code will be auto-trusted.
     pub fn enable_qr_pairing_listener(&self) -> Result<()> {
+        let _running = self.ensure_running()?;
         let service = self.sync_service.lock().unwrap().clone();
         if let Some(service) = service {
             // Use a wildcard: any unknown peer that connects will be auto-trusted.
@@ -933,6 +2170,7 @@ impl ClipboardNode {
 
     /// Disable auto-trust mode.
     pub fn disable_qr_pairing_listener(&self) -> Result<()> {
+        let _running = self.ensure_running()?;
         let service = self.sync_service.lock().unwrap().clone();
         if let Some(service) = service {
             service.pending_pair_peers().lock().unwrap().remove("*");
@@ -940,11 +2178,21 @@ impl ClipboardNode {
         Ok(())
     }
 
+    /// Stop everything, returning once all teardown has finished, so the node
+    /// can be dropped right after. The node cannot be restarted; later calls
+    /// fail with `NodeStopped` (setters without a result are ignored), and a
+    /// second `stop` is a no-op.
     pub fn stop(&self) {
+        if self.stopped.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            return;
+        }
+        // Let calls already past `ensure_running` finish first.
+        let _running = self.running.write().unwrap();
         self.stop_sync();
 
         if let Some(handle) = self.listener_handle.lock().unwrap().take() {
             handle.abort();
+            let _ = self.runtime.block_on(handle);
         }
         self.stop_discovery();
     }
@@ -953,6 +2201,7 @@ impl ClipboardNode {
     async fn send_file_internal(
         session: &Session<impl openclipboard_core::transport::Connection, Ed25519Identity, MockClipboard>,
         path: &std::path::Path,
+        config: &openclipboard_core::FileTransferConfig,
     ) -> anyhow::Result<()> {
         use sha2::{Digest, Sha256};
 
@@ -964,29 +2213,32 @@ impl ClipboardNode {
             .unwrap_or("unknown")
             .to_string();
 
-        let file_id = format!("file-{}", rand_core::RngCore::next_u32(&mut rand_core::OsRng));
         let size = data.len() as u64;
-        let mime = "application/octet-stream".to_string();
-
-        session.send_file_offer(&file_id, &name, size, &mime).await?;
-
-        // Wait for accept
-        let msg = session.recv_message().await?;
-        if !matches!(msg, Message::FileAccept { .. }) {
-            anyhow::bail!("expected file accept");
+        let mime = openclipboard_core::mime_for_name(&name).to_string();
+        let hash = format!("{:x}", Sha256::digest(&data));
+        // Keyed on the content, so a receiver that kept part of it can resume.
+        let file_id = hash.clone();
+
+        session.send_file_offer_with_hash(&file_id, &name, size, &mime, Some(&hash)).await?;
+
+        // Wait for the receiver's answer
+        match session.recv_message().await? {
+            Message::FileAccept { .. } => {}
+            Message::FileReject { reason, .. } => {
+                return Err(openclipboard_core::FileRejected { name, reason }.into());
+            }
+            _ => anyhow::bail!("expected file accept"),
         }
 
-        // Send chunks
-        const CHUNK_SIZE: usize = 64 * 1024;
-        for (i, chunk) in data.chunks(CHUNK_SIZE).enumerate() {
-            let offset = (i * CHUNK_SIZE) as u64;
-            session.send_file_chunk(&file_id, offset, chunk).await?;
-        }
+        // Pick up where a cut-off earlier send stopped; a v0 peer, or one that
+        // doesn't answer, gets the whole file.
+        let offset = if session.peer_is_v0() {
+            0
+        } else {
+            session.query_resume_offset(&file_id, RESUME_QUERY_TIMEOUT).await.unwrap_or(0).min(size)
+        };
+        session.send_file_data(&file_id, offset, &data[offset as usize..], config).await?;
 
-        // Send done with hash
-        let mut hasher = Sha256::new();
-        hasher.update(&data);
-        let hash = format!("{:x}", hasher.finalize());
         session.send_file_done(&file_id, &hash).await?;
 
         Ok(())
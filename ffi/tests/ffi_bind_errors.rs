@@ -0,0 +1,60 @@
+//! A sync listener that can't bind fails `start_sync` / `start_mesh` with a
+//! typed error. The endpoint factory is swapped for one that fails, so no real
+//! port has to be fought over.
+
+use openclipboard_ffi::{
+    clipboard_node_new_with_sync_discovery, ClipboardCallback, EventHandler, OpenClipboardError,
+};
+use openclipboard_core::MockDiscovery;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+struct Handler;
+
+impl EventHandler for Handler {
+    fn on_clipboard_text(&self, _: String, _: String, _: u64) {}
+    fn on_file_received(&self, _: String, _: String, _: String) {}
+    fn on_peer_connected(&self, _: String) {}
+    fn on_peer_disconnected(&self, _: String) {}
+    fn on_error(&self, _: String) {}
+}
+
+struct NoClipboard;
+
+impl ClipboardCallback for NoClipboard {
+    fn read_text(&self) -> Option<String> {
+        None
+    }
+    fn write_text(&self, _text: String) {}
+}
+
+fn failing_with(kind: std::io::ErrorKind) -> Option<openclipboard_core::quic_transport::ServerEndpointFactory> {
+    Some(Arc::new(move |_| Err(std::io::Error::from(kind).into())))
+}
+
+#[test]
+fn bind_failures_reach_the_caller_as_typed_errors() {
+    let td = TempDir::new().unwrap();
+    let node = clipboard_node_new_with_sync_discovery(
+        td.path().join("id.json").to_string_lossy().to_string(),
+        td.path().join("trust.json").to_string_lossy().to_string(),
+        Arc::new(MockDiscovery::new_shared()),
+        std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+    )
+    .unwrap();
+
+    node.set_server_endpoint_factory(failing_with(std::io::ErrorKind::AddrInUse));
+    let err = node.start_sync(4242, "node".into(), Box::new(Handler)).unwrap_err();
+    assert!(matches!(err, OpenClipboardError::AddressInUse), "{err}");
+    let err = node.start_mesh(4242, "node".into(), Box::new(Handler), Box::new(NoClipboard), 20, None).unwrap_err();
+    assert!(matches!(err, OpenClipboardError::AddressInUse), "{err}");
+
+    node.set_server_endpoint_factory(failing_with(std::io::ErrorKind::PermissionDenied));
+    let err = node.start_sync(4242, "node".into(), Box::new(Handler)).unwrap_err();
+    assert!(matches!(err, OpenClipboardError::BindFailed), "{err}");
+
+    // The default factory binds again.
+    node.set_server_endpoint_factory(None);
+    node.start_sync(0, "node".into(), Box::new(Handler)).unwrap();
+    node.stop_sync();
+}
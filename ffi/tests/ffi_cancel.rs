@@ -0,0 +1,94 @@
+//! E2E test: cancelling an in-flight `connect_and_send_file` from the host.
+
+use openclipboard_core::quic_transport::{make_server_endpoint, QuicListener};
+use openclipboard_core::{Ed25519Identity, IdentityProvider, Listener, Message, MockClipboard, MockDiscovery, Session};
+use openclipboard_ffi::{clipboard_node_new_with_sync_discovery, trust_store_open, CancellationToken, OpenClipboardError};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+#[test]
+fn cancelling_a_file_send_aborts_it_and_closes_the_connection() {
+    let dir = tempfile::tempdir().unwrap();
+    let trust_path = dir.path().join("trust.json").to_string_lossy().to_string();
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let receiver_id = Ed25519Identity::generate();
+    let (endpoint, _cert) = rt.block_on(async { make_server_endpoint("127.0.0.1:0".parse().unwrap()) }).unwrap();
+    let addr = endpoint.local_addr().unwrap().to_string();
+    let listener = QuicListener::new(endpoint);
+
+    // Accept the offer, then stall after the first chunk so the sender is
+    // still mid-transfer when the host cancels. Once resumed, drain until the
+    // connection ends and report whether the file ever completed.
+    let (first_chunk_tx, first_chunk_rx) = mpsc::channel();
+    let (ended_tx, ended_rx) = mpsc::channel();
+    let resume = Arc::new(AtomicBool::new(false));
+    let resume_rx = Arc::clone(&resume);
+    let id = receiver_id.clone();
+    rt.spawn(async move {
+        let conn = listener.accept().await.unwrap();
+        let session = Session::new(conn, id, MockClipboard::new());
+        session.handshake().await.unwrap();
+        let mut completed = false;
+        let mut first = true;
+        while let Ok(msg) = session.recv_message().await {
+            match msg {
+                Message::FileOffer { file_id, .. } => session.send_file_accept(&file_id).await.unwrap(),
+                Message::FileChunk { .. } if first => {
+                    first = false;
+                    let _ = first_chunk_tx.send(());
+                    while !resume_rx.load(Ordering::SeqCst) {
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                    }
+                }
+                Message::FileDone { .. } => completed = true,
+                _ => {}
+            }
+        }
+        let _ = ended_tx.send(completed);
+    });
+
+    trust_store_open(trust_path.clone())
+        .unwrap()
+        .add(
+            receiver_id.peer_id().to_string(),
+            openclipboard_core::Base64Alphabet::Standard.encode(receiver_id.public_key_bytes()),
+            "receiver".into(),
+        )
+        .unwrap();
+
+    let node = clipboard_node_new_with_sync_discovery(
+        dir.path().join("id.json").to_string_lossy().to_string(),
+        trust_path,
+        Arc::new(MockDiscovery::new_shared()),
+        "127.0.0.1".parse().unwrap(),
+    )
+    .unwrap();
+
+    // Far more than the connection's flow-control window, so the send can't
+    // finish while the receiver is stalled.
+    let path = dir.path().join("big.bin");
+    std::fs::write(&path, vec![0x5a; 32 * 1024 * 1024]).unwrap();
+
+    let token = Arc::new(CancellationToken::new());
+    let sender = {
+        let node = Arc::clone(&node);
+        let token = Arc::clone(&token);
+        std::thread::spawn(move || node.connect_and_send_file(addr, path.to_string_lossy().to_string(), Some(token)))
+    };
+
+    first_chunk_rx.recv_timeout(Duration::from_secs(10)).expect("transfer never started");
+    assert!(!token.is_cancelled());
+    token.cancel();
+    assert!(token.is_cancelled());
+
+    let res = sender.join().unwrap();
+    assert!(matches!(res, Err(OpenClipboardError::Cancelled)), "expected Cancelled, got {res:?}");
+
+    resume.store(true, Ordering::SeqCst);
+    let completed = ended_rx.recv_timeout(Duration::from_secs(10)).expect("connection was left open");
+    assert!(!completed, "the cancelled file still completed");
+
+    node.stop();
+}
@@ -0,0 +1,96 @@
+//! E2E test: the diagnostics bundle describes peers and counters without
+//! leaking the signing key or clipboard content.
+
+use openclipboard_ffi::{clipboard_node_new_with_sync_discovery, identity_generate, trust_store_open, EventHandler};
+use openclipboard_core::MockDiscovery;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+struct TextHandler(Mutex<mpsc::Sender<String>>);
+
+impl EventHandler for TextHandler {
+    fn on_clipboard_text(&self, _peer_id: String, text: String, _ts_ms: u64) {
+        let _ = self.0.lock().unwrap().send(text);
+    }
+    fn on_file_received(&self, _: String, _: String, _: String) {}
+    fn on_peer_connected(&self, _: String) {}
+    fn on_peer_disconnected(&self, _: String) {}
+    fn on_error(&self, _: String) {}
+}
+
+#[test]
+fn diagnostics_bundle_has_peers_and_metrics_but_no_secrets_or_clips() {
+    let td = TempDir::new().unwrap();
+    let id_paths: Vec<String> = (0..2).map(|i| td.path().join(format!("id_{i}.json")).to_string_lossy().to_string()).collect();
+    let ids: Vec<_> = id_paths
+        .iter()
+        .map(|path| {
+            let id = identity_generate();
+            id.save(path.clone()).unwrap();
+            id
+        })
+        .collect();
+    let trust_paths: Vec<String> = (0..2)
+        .map(|i| td.path().join(format!("trust_{i}.json")).to_string_lossy().to_string())
+        .collect();
+    for (i, trust_path) in trust_paths.iter().enumerate() {
+        let j = 1 - i;
+        let store = trust_store_open(trust_path.clone()).unwrap();
+        store.add(ids[j].peer_id(), ids[j].pubkey_b64(), format!("node_{j}")).unwrap();
+    }
+
+    let shared_disc = Arc::new(MockDiscovery::new_shared());
+    let nodes: Vec<_> = (0..2)
+        .map(|i| {
+            clipboard_node_new_with_sync_discovery(
+                id_paths[i].clone(),
+                trust_paths[i].clone(),
+                Arc::new(shared_disc.clone_shared()),
+                std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    // Before sync starts there are no peers or metrics, but the bundle is still valid.
+    let idle: serde_json::Value = serde_json::from_str(&nodes[1].diagnostics()).unwrap();
+    assert_eq!(idle["sync_running"], false);
+    assert_eq!(idle["trusted_peers"][0]["display_name"], "node_0");
+
+    let (texts_tx, texts_rx) = mpsc::channel();
+    let (unused_tx, _unused_rx) = mpsc::channel();
+    nodes[0].start_sync(0, "A".into(), Box::new(TextHandler(Mutex::new(unused_tx)))).unwrap();
+    nodes[1].start_sync(0, "B".into(), Box::new(TextHandler(Mutex::new(texts_tx)))).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while nodes[0].snapshot(0).connected.is_empty() || nodes[1].snapshot(0).connected.is_empty() {
+        assert!(Instant::now() < deadline, "nodes never connected");
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let secret_clip = "correct horse battery staple";
+    nodes[0].send_clipboard_text(secret_clip.into()).unwrap();
+    assert_eq!(texts_rx.recv_timeout(Duration::from_secs(5)).unwrap(), secret_clip);
+
+    let raw = nodes[1].diagnostics();
+    let bundle: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    assert_eq!(bundle["peer_id"], nodes[1].peer_id());
+    assert_eq!(bundle["device_name"], "B");
+    assert_eq!(bundle["trusted_peers"][0]["peer_id"], nodes[0].peer_id());
+    let peer = &bundle["peers"][0];
+    assert_eq!(peer["peer_id"], nodes[0].peer_id());
+    assert_eq!(peer["status"], "online");
+    assert_eq!(peer["connected"], true);
+    assert_eq!(bundle["metrics"]["connected_peers"], 1);
+    assert_eq!(bundle["metrics"]["history_entries"], 1);
+    assert!(bundle["recent_errors"].is_array());
+
+    let identity_file: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&id_paths[1]).unwrap()).unwrap();
+    let seed = identity_file["signing_key_b64"].as_str().unwrap();
+    assert!(!raw.contains(seed), "signing seed leaked");
+    assert!(!raw.contains(secret_clip), "clipboard text leaked");
+
+    for n in &nodes {
+        n.stop_sync();
+    }
+}
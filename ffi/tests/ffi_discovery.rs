@@ -99,4 +99,25 @@ fn test_discovery_roundtrip() {
     
     // The test passes if we get here without panicking
     println!("Discovery roundtrip test completed successfully");
-}
\ No newline at end of file
+}
+
+#[test]
+fn stop_twice_then_drop_tears_down_discovery() {
+    let dir = tempfile::tempdir().unwrap();
+    let node = clipboard_node_new(
+        dir.path().join("id.json").to_string_lossy().to_string(),
+        dir.path().join("trust.json").to_string_lossy().to_string(),
+    )
+    .unwrap();
+
+    node.start_discovery("Test Device".to_string(), Box::new(TestDiscoveryHandler::new())).unwrap();
+    assert!(node.is_discovering());
+
+    node.stop();
+    assert!(!node.is_discovering());
+    node.stop();
+    assert!(!node.is_discovering());
+
+    // Nothing is left running on the runtime to outlive the node.
+    drop(node);
+}
@@ -1,11 +1,15 @@
 use base64::Engine as _;
 
 use openclipboard_ffi::{
+    clipboard_node_new,
     identity_generate,
     identity_load,
     pairing_payload_from_qr_string,
     trust_store_default_path,
     trust_store_open,
+    ClipboardNode,
+    OpenClipboardError,
+    SyncDirection,
 };
 
 #[test]
@@ -33,6 +37,12 @@ fn pairing_payload_from_qr_string_invalid_errors() {
     assert!(pairing_payload_from_qr_string("not-a-qr".into()).is_err());
 }
 
+#[test]
+fn pairing_payload_from_foreign_qr_is_not_an_openclipboard_code() {
+    let err = pairing_payload_from_qr_string("https://example.com".into()).err().unwrap();
+    assert!(matches!(err, OpenClipboardError::NotAnOpenClipboardCode));
+}
+
 #[test]
 fn trust_store_open_and_add_creates_parent_dirs() {
     let dir = tempfile::tempdir().unwrap();
@@ -62,3 +72,77 @@ fn trust_store_default_path_returns_something() {
     assert!(!p.is_empty());
     assert!(p.contains("trust"));
 }
+
+#[test]
+fn node_methods_after_stop_return_node_stopped() {
+    let dir = tempfile::tempdir().unwrap();
+    let node = clipboard_node_new(
+        dir.path().join("id.json").to_string_lossy().to_string(),
+        dir.path().join("trust.json").to_string_lossy().to_string(),
+    )
+    .unwrap();
+
+    node.stop();
+    // A second stop is a no-op.
+    node.stop();
+
+    let err = node.send_clipboard_text("after stop".into()).err().unwrap();
+    assert!(matches!(err, OpenClipboardError::NodeStopped));
+    let err = node.recall_from_history("x".into()).err().unwrap();
+    assert!(matches!(err, OpenClipboardError::NodeStopped));
+    let err = node.set_peer_sync_direction("peer".into(), SyncDirection::Bidirectional).err().unwrap();
+    assert!(matches!(err, OpenClipboardError::NodeStopped));
+}
+
+#[test]
+fn setters_after_stop_fail_or_are_ignored() {
+    let dir = tempfile::tempdir().unwrap();
+    let node = clipboard_node_new(
+        dir.path().join("id.json").to_string_lossy().to_string(),
+        dir.path().join("trust.json").to_string_lossy().to_string(),
+    )
+    .unwrap();
+    let config = |node: &ClipboardNode| serde_json::from_str::<serde_json::Value>(&node.diagnostics()).unwrap()["config"].clone();
+    node.set_history_max_bytes(1000);
+    let before = config(&node);
+
+    node.stop();
+    let log = dir.path().join("received.json").to_string_lossy().to_string();
+    assert!(matches!(node.set_received_files_log(Some(log)), Err(OpenClipboardError::NodeStopped)));
+    assert!(matches!(node.set_linked_devices_file(None), Err(OpenClipboardError::NodeStopped)));
+
+    node.set_history_max_bytes(5);
+    node.set_keepalive_ms(1, 1);
+    node.set_sync_whitespace(false);
+    node.set_history_file(Some(dir.path().join("history.bin").to_string_lossy().to_string()));
+    assert_eq!(config(&node), before);
+}
+
+#[test]
+fn calls_racing_stop_finish_or_fail_with_node_stopped() {
+    let dir = tempfile::tempdir().unwrap();
+    let node = clipboard_node_new(
+        dir.path().join("id.json").to_string_lossy().to_string(),
+        dir.path().join("trust.json").to_string_lossy().to_string(),
+    )
+    .unwrap();
+
+    let callers: Vec<_> = (0..4)
+        .map(|_| {
+            let node = node.clone();
+            std::thread::spawn(move || loop {
+                // Not syncing, so a call that gets in before `stop` fails with `Other`.
+                match node.send_clipboard_text("racing".into()) {
+                    Err(OpenClipboardError::NodeStopped) => break,
+                    Err(OpenClipboardError::Other) => {}
+                    other => panic!("unexpected result: {other:?}"),
+                }
+            })
+        })
+        .collect();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    node.stop();
+    for caller in callers {
+        caller.join().unwrap();
+    }
+}
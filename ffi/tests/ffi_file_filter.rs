@@ -0,0 +1,62 @@
+//! Incoming file offers filtered by MIME type on the node's listener.
+
+use openclipboard_core::quic_transport::{make_insecure_client_endpoint, QuicTransport};
+use openclipboard_core::{Ed25519Identity, IdentityProvider, Message, MockClipboard, Session, Transport};
+use openclipboard_ffi::{clipboard_node_new, trust_store_open, EventHandler};
+
+struct NoopHandler;
+
+impl EventHandler for NoopHandler {
+    fn on_clipboard_text(&self, _: String, _: String, _: u64) {}
+    fn on_file_received(&self, _: String, _: String, _: String) {}
+    fn on_peer_connected(&self, _: String) {}
+    fn on_peer_disconnected(&self, _: String) {}
+    fn on_error(&self, _: String) {}
+}
+
+#[test]
+fn denied_mime_is_rejected_while_allowed_one_is_accepted() {
+    let dir = tempfile::tempdir().unwrap();
+    let trust_path = dir.path().join("trust.json").to_string_lossy().to_string();
+
+    let sender = Ed25519Identity::generate();
+    trust_store_open(trust_path.clone())
+        .unwrap()
+        .add(
+            sender.peer_id().to_string(),
+            openclipboard_core::Base64Alphabet::Standard.encode(sender.public_key_bytes()),
+            "sender".into(),
+        )
+        .unwrap();
+
+    let node = clipboard_node_new(dir.path().join("id.json").to_string_lossy().to_string(), trust_path).unwrap();
+    node.set_file_type_rules(Vec::new(), vec!["application/x-executable".into()]);
+
+    // Grab a free port for the listener.
+    let port = std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    node.start_listener(port, Box::new(NoopHandler)).unwrap();
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let replies = rt.block_on(async {
+        let transport = QuicTransport::new(make_insecure_client_endpoint().unwrap());
+        let conn = transport.connect(&format!("127.0.0.1:{port}")).await.unwrap();
+        let session = Session::new(conn, sender.clone(), MockClipboard::new());
+        session.handshake().await.unwrap();
+
+        let mut replies = Vec::new();
+        for (file_id, name, mime) in [("f1", "tool", "application/x-executable"), ("f2", "photo.png", "image/png")] {
+            session.send_file_offer(file_id, name, 4, mime).await.unwrap();
+            replies.push(session.recv_message().await.unwrap());
+        }
+        replies
+    });
+
+    assert!(
+        matches!(&replies[0], Message::FileReject { file_id, reason } if file_id == "f1" && reason == "mime-not-allowed"),
+        "{:?}",
+        replies[0]
+    );
+    assert!(matches!(&replies[1], Message::FileAccept { file_id } if file_id == "f2"), "{:?}", replies[1]);
+
+    node.stop();
+}
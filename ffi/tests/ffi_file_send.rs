@@ -0,0 +1,175 @@
+use openclipboard_core::quic_transport::{make_insecure_client_endpoint, make_server_endpoint, QuicListener, QuicTransport};
+use openclipboard_core::{Ed25519Identity, IdentityProvider, Listener, Message, MockClipboard, MockDiscovery, Session, Transport};
+use openclipboard_ffi::{clipboard_node_new, clipboard_node_new_with_sync_discovery, trust_store_open, EventHandler};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+
+#[derive(Default)]
+struct ReceiverStats {
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+    files: Mutex<Vec<(String, Vec<u8>)>>,
+}
+
+/// Accept connections concurrently and record how many file transfers overlap.
+fn spawn_receiver(rt: &tokio::runtime::Runtime, identity: Ed25519Identity, stats: Arc<ReceiverStats>) -> String {
+    let (endpoint, _cert) = rt.block_on(async { make_server_endpoint("127.0.0.1:0".parse().unwrap()) }).unwrap();
+    let addr = endpoint.local_addr().unwrap().to_string();
+    let listener = QuicListener::new(endpoint);
+    rt.spawn(async move {
+        while let Ok(conn) = listener.accept().await {
+            let identity = identity.clone();
+            let stats = Arc::clone(&stats);
+            tokio::spawn(async move {
+                let session = Session::new(conn, identity, MockClipboard::new());
+                session.handshake().await.unwrap();
+                let mut name = String::new();
+                let mut data = Vec::new();
+                while let Ok(msg) = session.recv_message().await {
+                    match msg {
+                        Message::FileOffer { file_id, name: n, .. } => {
+                            let now = stats.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                            stats.max_in_flight.fetch_max(now, Ordering::SeqCst);
+                            name = n;
+                            // Hold the transfer open so overlapping sends would be visible.
+                            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                            session.send_file_accept(&file_id).await.unwrap();
+                        }
+                        Message::FileChunk { data_b64, .. } => {
+                            data.extend(openclipboard_core::Base64Alphabet::Standard.decode(data_b64).unwrap());
+                        }
+                        Message::FileDone { .. } => {
+                            stats.in_flight.fetch_sub(1, Ordering::SeqCst);
+                            stats.files.lock().unwrap().push((std::mem::take(&mut name), std::mem::take(&mut data)));
+                        }
+                        // The sender confirms delivery before hanging up.
+                        Message::Ping { ts_ms } => session.send_pong(ts_ms).await.unwrap(),
+                        _ => {}
+                    }
+                }
+            });
+        }
+    });
+    addr
+}
+
+#[test]
+fn file_sends_with_limit_one_run_sequentially() {
+    let dir = tempfile::tempdir().unwrap();
+    let trust_path = dir.path().join("trust.json").to_string_lossy().to_string();
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let receiver_id = Ed25519Identity::generate();
+    let stats = Arc::new(ReceiverStats::default());
+    let addr = spawn_receiver(&rt, receiver_id.clone(), Arc::clone(&stats));
+
+    trust_store_open(trust_path.clone())
+        .unwrap()
+        .add(
+            receiver_id.peer_id().to_string(),
+            openclipboard_core::Base64Alphabet::Standard.encode(receiver_id.public_key_bytes()),
+            "receiver".into(),
+        )
+        .unwrap();
+
+    let node = clipboard_node_new_with_sync_discovery(
+        dir.path().join("id.json").to_string_lossy().to_string(),
+        trust_path,
+        Arc::new(MockDiscovery::new_shared()),
+        "127.0.0.1".parse().unwrap(),
+    )
+    .unwrap();
+    node.set_max_concurrent_file_sends(1);
+
+    let mut expected = Vec::new();
+    let senders: Vec<_> = (0..3)
+        .map(|i| {
+            let name = format!("file{i}.bin");
+            let body = vec![i as u8; 100 * 1024 + i];
+            let path = dir.path().join(&name);
+            std::fs::write(&path, &body).unwrap();
+            expected.push((name, body));
+
+            let node = Arc::clone(&node);
+            let addr = addr.clone();
+            std::thread::spawn(move || node.connect_and_send_file(addr, path.to_string_lossy().to_string(), None))
+        })
+        .collect();
+    for s in senders {
+        s.join().unwrap().unwrap();
+    }
+
+    let mut files = stats.files.lock().unwrap().clone();
+    files.sort();
+    assert_eq!(files, expected);
+    assert_eq!(stats.max_in_flight.load(Ordering::SeqCst), 1);
+
+    node.stop();
+}
+
+struct FileHandler(Mutex<mpsc::Sender<String>>);
+
+impl EventHandler for FileHandler {
+    fn on_clipboard_text(&self, _: String, _: String, _: u64) {}
+    fn on_file_received(&self, _: String, _: String, data_path: String) {
+        let _ = self.0.lock().unwrap().send(data_path);
+    }
+    fn on_peer_connected(&self, _: String) {}
+    fn on_peer_disconnected(&self, _: String) {}
+    fn on_error(&self, _: String) {}
+}
+
+#[test]
+fn listener_files_are_logged_and_a_resent_one_is_not_saved_again() {
+    let dir = tempfile::tempdir().unwrap();
+    let trust_path = dir.path().join("trust.json").to_string_lossy().to_string();
+
+    let sender = Ed25519Identity::generate();
+    trust_store_open(trust_path.clone())
+        .unwrap()
+        .add(
+            sender.peer_id().to_string(),
+            openclipboard_core::Base64Alphabet::Standard.encode(sender.public_key_bytes()),
+            "sender".into(),
+        )
+        .unwrap();
+
+    let node = clipboard_node_new(dir.path().join("id.json").to_string_lossy().to_string(), trust_path).unwrap();
+    let downloads = dir.path().join("downloads");
+    node.set_download_dir(downloads.to_string_lossy().to_string());
+    let (tx, rx) = mpsc::channel();
+    let port = std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    node.start_listener(port, Box::new(FileHandler(Mutex::new(tx)))).unwrap();
+
+    let body = format!("listener file {}", sender.peer_id()).into_bytes();
+    let hash = format!("{:x}", Sha256::digest(&body));
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let mut paths = Vec::new();
+    for file_id in ["f1", "f2"] {
+        rt.block_on(async {
+            let transport = QuicTransport::new(make_insecure_client_endpoint().unwrap());
+            let conn = transport.connect(&format!("127.0.0.1:{port}")).await.unwrap();
+            let session = Session::new(conn, sender.clone(), MockClipboard::new());
+            session.handshake().await.unwrap();
+            session.send_file_offer_with_hash(file_id, "note.txt", body.len() as u64, "text/plain", Some(&hash)).await.unwrap();
+            assert!(matches!(session.recv_message().await.unwrap(), Message::FileAccept { .. }));
+            session.send_file_chunk(file_id, 0, &body).await.unwrap();
+            session.send_file_done(file_id, &hash).await.unwrap();
+            // Hang up only once the listener has read everything.
+            session.send_ping(1).await.unwrap();
+            assert!(matches!(session.recv_message().await.unwrap(), Message::Pong { .. }));
+        });
+        paths.push(rx.recv_timeout(std::time::Duration::from_secs(10)).unwrap());
+    }
+
+    assert_eq!(paths[0], paths[1]);
+    assert!(std::path::Path::new(&paths[0]).starts_with(&downloads), "{}", paths[0]);
+    assert_eq!(std::fs::read(&paths[0]).unwrap(), body);
+    let files = node.received_files();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, paths[0]);
+    assert_eq!(files[0].peer_id, sender.peer_id());
+
+    node.stop();
+}
@@ -61,6 +61,7 @@ fn history_and_recall_via_mesh() {
         Box::new(NoopHandler),
         Box::new(cb),
         50,
+        None,
     ).unwrap();
 
     // Give it a moment to start
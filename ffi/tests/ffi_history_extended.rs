@@ -46,7 +46,7 @@ fn make_mesh_node(dir: &std::path::Path) -> (Arc<ClipboardNode>, Arc<TestClipboa
     // We need to use a struct that implements ClipboardCallback, not the Arc
     let cb_for_node = TestClipboard::new();
 
-    node.start_mesh(0, "test".into(), Box::new(NoopHandler), Box::new(cb_for_node), 50).unwrap();
+    node.start_mesh(0, "test".into(), Box::new(NoopHandler), Box::new(cb_for_node), 50, None).unwrap();
     std::thread::sleep(std::time::Duration::from_millis(100));
     (node, cb)
 }
@@ -64,6 +64,7 @@ fn history_empty_without_mesh() {
     assert!(node.get_clipboard_history(10).is_empty());
     assert!(node.get_clipboard_history(0).is_empty());
     assert!(node.get_clipboard_history_for_peer("any".into(), 10).is_empty());
+    assert!(node.get_clipboard_peer_summary().is_empty());
 }
 
 #[test]
@@ -225,3 +226,64 @@ fn mesh_history_recorded_on_receive() {
         n.stop_sync();
     }
 }
+
+// ─── Whitespace-only clipboard content ───────────────────────────────────────
+
+#[derive(Clone)]
+struct SharedClipboard {
+    content: Arc<Mutex<Option<String>>>,
+}
+
+impl ClipboardCallback for SharedClipboard {
+    fn read_text(&self) -> Option<String> {
+        self.content.lock().unwrap().clone()
+    }
+    fn write_text(&self, text: String) {
+        *self.content.lock().unwrap() = Some(text);
+    }
+}
+
+fn make_mock_discovery_node(dir: &std::path::Path) -> Arc<ClipboardNode> {
+    let id_path = dir.join("id.json").to_string_lossy().to_string();
+    let trust_path = dir.join("trust").to_string_lossy().to_string();
+    clipboard_node_new_with_sync_discovery(
+        id_path, trust_path, Arc::new(MockDiscovery::new_shared()),
+        std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+    ).unwrap()
+}
+
+#[test]
+fn mesh_whitespace_copy_is_synced_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let node = make_mock_discovery_node(dir.path());
+    let cb = SharedClipboard { content: Arc::new(Mutex::new(None)) };
+
+    node.start_mesh(0, "test".into(), Box::new(NoopHandler), Box::new(cb.clone()), 20, None).unwrap();
+
+    // Copy whitespace only: the watcher must see text, not Empty, and record it.
+    *cb.content.lock().unwrap() = Some("  \n".into());
+    std::thread::sleep(std::time::Duration::from_millis(300));
+
+    let h = node.get_clipboard_history(10);
+    assert_eq!(h.len(), 1);
+    assert_eq!(h[0].content, "  \n");
+    assert_eq!(h[0].source_peer, "local");
+
+    node.stop();
+}
+
+#[test]
+fn mesh_whitespace_copy_is_ignored_when_disabled() {
+    let dir = tempfile::tempdir().unwrap();
+    let node = make_mock_discovery_node(dir.path());
+    let cb = SharedClipboard { content: Arc::new(Mutex::new(None)) };
+
+    node.set_sync_whitespace(false);
+    node.start_mesh(0, "test".into(), Box::new(NoopHandler), Box::new(cb.clone()), 20, None).unwrap();
+
+    *cb.content.lock().unwrap() = Some("  \n".into());
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    assert!(node.get_clipboard_history(10).is_empty());
+
+    node.stop();
+}
@@ -0,0 +1,148 @@
+//! E2E test: sending a file to a connected mesh peer over its existing session.
+
+use openclipboard_ffi::{
+    clipboard_node_new_with_sync_discovery, identity_generate, trust_store_open, ClipboardCallback,
+    ClipboardHistoryKind, EventHandler, FileProgressHandler,
+};
+use openclipboard_core::MockDiscovery;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+#[derive(Default)]
+struct TestClipboard {
+    content: Mutex<Option<String>>,
+}
+
+impl ClipboardCallback for TestClipboard {
+    fn read_text(&self) -> Option<String> {
+        self.content.lock().unwrap().clone()
+    }
+    fn write_text(&self, text: String) {
+        *self.content.lock().unwrap() = Some(text);
+    }
+}
+
+struct FileHandler {
+    files_tx: Mutex<mpsc::Sender<(String, String, String)>>,
+    errors: Arc<Mutex<Vec<String>>>,
+}
+
+impl EventHandler for FileHandler {
+    fn on_clipboard_text(&self, _peer_id: String, _text: String, _ts_ms: u64) {}
+    fn on_file_received(&self, peer_id: String, name: String, data_path: String) {
+        let _ = self.files_tx.lock().unwrap().send((peer_id, name, data_path));
+    }
+    fn on_peer_connected(&self, _peer_id: String) {}
+    fn on_peer_disconnected(&self, _peer_id: String) {}
+    fn on_error(&self, message: String) {
+        self.errors.lock().unwrap().push(message);
+    }
+}
+
+struct Progress(Arc<Mutex<Vec<(u64, u64)>>>);
+
+impl FileProgressHandler for Progress {
+    fn on_progress(&self, sent: u64, total: u64) {
+        self.0.lock().unwrap().push((sent, total));
+    }
+}
+
+#[test]
+fn mesh_send_file_to_connected_peer() {
+    let td = TempDir::new().unwrap();
+
+    let ids: Vec<_> = (0..2)
+        .map(|i| {
+            let id = identity_generate();
+            id.save(td.path().join(format!("id_{i}.json")).to_string_lossy().to_string()).unwrap();
+            id
+        })
+        .collect();
+    let trust_paths: Vec<String> = (0..2)
+        .map(|i| td.path().join(format!("trust_{i}.json")).to_string_lossy().to_string())
+        .collect();
+    for (i, trust_path) in trust_paths.iter().enumerate() {
+        let j = 1 - i;
+        let store = trust_store_open(trust_path.clone()).unwrap();
+        store.add(ids[j].peer_id(), ids[j].pubkey_b64(), format!("node_{j}")).unwrap();
+    }
+
+    let shared_disc = Arc::new(MockDiscovery::new_shared());
+    let nodes: Vec<_> = (0..2)
+        .map(|i| {
+            clipboard_node_new_with_sync_discovery(
+                td.path().join(format!("id_{i}.json")).to_string_lossy().to_string(),
+                trust_paths[i].clone(),
+                Arc::new(shared_disc.clone_shared()),
+                std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let (files_tx, files_rx) = mpsc::channel();
+    let errors_b = Arc::new(Mutex::new(Vec::new()));
+    let (unused_tx, _unused_rx) = mpsc::channel();
+    let handler_a = FileHandler { files_tx: Mutex::new(unused_tx), errors: Arc::new(Mutex::new(Vec::new())) };
+    let handler_b = FileHandler { files_tx: Mutex::new(files_tx), errors: Arc::clone(&errors_b) };
+
+    nodes[0]
+        .start_mesh(0, "A".into(), Box::new(handler_a), Box::new(TestClipboard::default()), 50, None)
+        .unwrap();
+    nodes[1]
+        .start_mesh(0, "B".into(), Box::new(handler_b), Box::new(TestClipboard::default()), 50, None)
+        .unwrap();
+
+    // Wait until both sides hold the (deduplicated) session.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while nodes[0].snapshot(0).connected != vec![nodes[1].peer_id()]
+        || nodes[1].snapshot(0).connected != vec![nodes[0].peer_id()]
+    {
+        assert!(Instant::now() < deadline, "nodes never connected; errors_b={:?}", errors_b.lock().unwrap());
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    // Larger than one chunk so progress is reported more than once.
+    let data: Vec<u8> = (0..150_000u32).map(|i| (i % 251) as u8).collect();
+    let name = format!("mesh-file-{}.bin", std::process::id());
+    let path = td.path().join(&name);
+    std::fs::write(&path, &data).unwrap();
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    nodes[0]
+        .send_file_to_peer(nodes[1].peer_id(), path.to_string_lossy().to_string(), Box::new(Progress(Arc::clone(&progress))))
+        .unwrap();
+
+    let (from, got_name, saved) = files_rx
+        .recv_timeout(Duration::from_secs(5))
+        .unwrap_or_else(|_| panic!("B didn't receive the file; errors={:?}", errors_b.lock().unwrap()));
+    assert_eq!(from, nodes[0].peer_id());
+    assert_eq!(got_name, name);
+    assert_eq!(std::fs::read(&saved).unwrap(), data);
+    let received = nodes[1].received_files();
+    assert_eq!(received.len(), 1);
+    assert_eq!((received[0].name.as_str(), received[0].size), (name.as_str(), data.len() as u64));
+    assert_eq!(received[0].path, saved);
+    // History keeps it as a file entry pointing at the saved copy.
+    let history = nodes[1].get_clipboard_history(10);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].kind, ClipboardHistoryKind::File);
+    assert_eq!(history[0].content, name);
+    assert_eq!((history[0].file_path.as_deref(), history[0].file_size), (Some(saved.as_str()), Some(data.len() as u64)));
+    assert!(history[0].image.is_none());
+    let _ = std::fs::remove_file(saved);
+
+    let progress = progress.lock().unwrap();
+    assert_eq!(progress.len(), 3);
+    assert_eq!(progress.last(), Some(&(data.len() as u64, data.len() as u64)));
+
+    // Unknown peers fail instead of dialing.
+    assert!(nodes[0]
+        .send_file_to_peer("not-connected".into(), path.to_string_lossy().to_string(), Box::new(Progress(Arc::default())))
+        .is_err());
+
+    for n in &nodes {
+        n.stop_sync();
+    }
+}
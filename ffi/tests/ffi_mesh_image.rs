@@ -0,0 +1,133 @@
+//! E2E test: an image copied on one mesh node reaches the other two, is written
+//! to their clipboards, and isn't echoed back; oversized images are refused.
+
+use openclipboard_ffi::{
+    clipboard_node_new_with_sync_discovery, identity_generate, trust_store_open, ClipboardCallback,
+    ClipboardHistoryKind, ClipboardImage, EventHandler,
+};
+use openclipboard_core::MockDiscovery;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+#[derive(Clone, Default)]
+struct ImageClipboard(Arc<Mutex<Option<ClipboardImage>>>);
+
+impl ClipboardCallback for ImageClipboard {
+    fn read_text(&self) -> Option<String> {
+        None
+    }
+    fn write_text(&self, _text: String) {}
+    fn read_image(&self) -> Option<ClipboardImage> {
+        self.0.lock().unwrap().clone()
+    }
+    fn write_image(&self, image: ClipboardImage) {
+        *self.0.lock().unwrap() = Some(image);
+    }
+}
+
+struct ImageHandler {
+    images: Mutex<mpsc::Sender<(String, ClipboardImage)>>,
+    errors: Arc<Mutex<Vec<String>>>,
+}
+
+impl EventHandler for ImageHandler {
+    fn on_clipboard_text(&self, _: String, _: String, _: u64) {}
+    fn on_file_received(&self, _: String, _: String, _: String) {}
+    fn on_peer_connected(&self, _: String) {}
+    fn on_peer_disconnected(&self, _: String) {}
+    fn on_error(&self, message: String) {
+        self.errors.lock().unwrap().push(message);
+    }
+    fn on_clipboard_image(&self, peer_id: String, mime: String, width: u32, height: u32, bytes: Vec<u8>, _ts_ms: u64) {
+        let _ = self.images.lock().unwrap().send((peer_id, ClipboardImage { mime, width, height, bytes }));
+    }
+}
+
+#[test]
+fn mesh_fans_an_image_out_to_two_peers() {
+    let td = TempDir::new().unwrap();
+
+    let ids: Vec<_> = (0..3)
+        .map(|i| {
+            let id = identity_generate();
+            id.save(td.path().join(format!("id_{i}.json")).to_string_lossy().to_string()).unwrap();
+            id
+        })
+        .collect();
+    let trust_paths: Vec<String> = (0..3)
+        .map(|i| td.path().join(format!("trust_{i}.json")).to_string_lossy().to_string())
+        .collect();
+    for (i, trust_path) in trust_paths.iter().enumerate() {
+        let store = trust_store_open(trust_path.clone()).unwrap();
+        for (j, id) in ids.iter().enumerate().filter(|(j, _)| *j != i) {
+            store.add(id.peer_id(), id.pubkey_b64(), format!("node_{j}")).unwrap();
+        }
+    }
+
+    let shared_disc = Arc::new(MockDiscovery::new_shared());
+    let nodes: Vec<_> = (0..3)
+        .map(|i| {
+            clipboard_node_new_with_sync_discovery(
+                td.path().join(format!("id_{i}.json")).to_string_lossy().to_string(),
+                trust_paths[i].clone(),
+                Arc::new(shared_disc.clone_shared()),
+                std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let clipboards: Vec<ImageClipboard> = (0..3).map(|_| ImageClipboard::default()).collect();
+    let errors: Vec<Arc<Mutex<Vec<String>>>> = (0..3).map(|_| Arc::default()).collect();
+    let receivers: Vec<_> = (0..3)
+        .map(|i| {
+            let (tx, rx) = mpsc::channel();
+            let handler = ImageHandler { images: Mutex::new(tx), errors: Arc::clone(&errors[i]) };
+            nodes[i]
+                .start_mesh(0, format!("node_{i}"), Box::new(handler), Box::new(clipboards[i].clone()), 20, None)
+                .unwrap();
+            rx
+        })
+        .collect();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while nodes.iter().any(|n| n.snapshot(0).connected.len() < 2) {
+        assert!(Instant::now() < deadline, "mesh never fully connected; errors={errors:?}");
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    let image = ClipboardImage { mime: "image/png".into(), width: 4, height: 2, bytes: (0..=255).collect() };
+    *clipboards[0].0.lock().unwrap() = Some(image.clone());
+
+    for i in [1, 2] {
+        let (from, got) = receivers[i]
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap_or_else(|_| panic!("node_{i} got no image; errors={:?}", errors[i].lock().unwrap()));
+        assert_eq!(from, nodes[0].peer_id());
+        assert_eq!(got, image);
+        assert_eq!(clipboards[i].0.lock().unwrap().as_ref(), Some(&image), "node_{i} didn't write the image");
+        let history = nodes[i].get_clipboard_history(10);
+        assert_eq!(history.len(), 1, "node_{i} history");
+        assert_eq!((history[0].kind, history[0].image.as_ref()), (ClipboardHistoryKind::Image, Some(&image)));
+        assert_eq!(history[0].source_peer, nodes[0].peer_id());
+    }
+    // Writing it on B and C must not send it around again.
+    assert!(receivers[0].recv_timeout(Duration::from_millis(300)).is_err(), "image echoed back to A");
+
+    // Too large for one message: refused up front, with the reason on A's on_error.
+    let huge = vec![0u8; openclipboard_core::protocol::MAX_CLIP_IMAGE_LEN + 1];
+    assert!(nodes[0].send_clipboard_image("image/png".into(), 4096, 4096, huge).is_err());
+    assert!(
+        errors[0].lock().unwrap().iter().any(|e| e.contains("can't be synced")),
+        "errors={:?}",
+        errors[0].lock().unwrap()
+    );
+    for i in [1, 2] {
+        assert!(receivers[i].recv_timeout(Duration::from_millis(300)).is_err(), "node_{i} got the oversized image");
+    }
+
+    for n in &nodes {
+        n.stop_sync();
+    }
+}
@@ -0,0 +1,105 @@
+//! E2E test: restarting mesh mode leaves exactly one clipboard watcher, so
+//! each local copy is broadcast once.
+
+use openclipboard_ffi::{
+    clipboard_node_new_with_sync_discovery, identity_generate, trust_store_open, ClipboardCallback,
+    EventHandler,
+};
+use openclipboard_core::MockDiscovery;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+struct SharedClipboard(Arc<Mutex<Option<String>>>);
+
+impl ClipboardCallback for SharedClipboard {
+    fn read_text(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+    fn write_text(&self, text: String) {
+        *self.0.lock().unwrap() = Some(text);
+    }
+}
+
+struct TextHandler(Mutex<mpsc::Sender<String>>);
+
+impl EventHandler for TextHandler {
+    fn on_clipboard_text(&self, _peer_id: String, text: String, _ts_ms: u64) {
+        let _ = self.0.lock().unwrap().send(text);
+    }
+    fn on_file_received(&self, _: String, _: String, _: String) {}
+    fn on_peer_connected(&self, _: String) {}
+    fn on_peer_disconnected(&self, _: String) {}
+    fn on_error(&self, _: String) {}
+}
+
+#[test]
+fn restarting_mesh_keeps_a_single_clipboard_watcher() {
+    let td = TempDir::new().unwrap();
+
+    let ids: Vec<_> = (0..2)
+        .map(|i| {
+            let id = identity_generate();
+            id.save(td.path().join(format!("id_{i}.json")).to_string_lossy().to_string()).unwrap();
+            id
+        })
+        .collect();
+    let trust_paths: Vec<String> = (0..2)
+        .map(|i| td.path().join(format!("trust_{i}.json")).to_string_lossy().to_string())
+        .collect();
+    for (i, trust_path) in trust_paths.iter().enumerate() {
+        let j = 1 - i;
+        let store = trust_store_open(trust_path.clone()).unwrap();
+        store.add(ids[j].peer_id(), ids[j].pubkey_b64(), format!("node_{j}")).unwrap();
+    }
+
+    let shared_disc = Arc::new(MockDiscovery::new_shared());
+    let nodes: Vec<_> = (0..2)
+        .map(|i| {
+            clipboard_node_new_with_sync_discovery(
+                td.path().join(format!("id_{i}.json")).to_string_lossy().to_string(),
+                trust_paths[i].clone(),
+                Arc::new(shared_disc.clone_shared()),
+                std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let clipboard_a = Arc::new(Mutex::new(None));
+    let start_a = |node: &openclipboard_ffi::ClipboardNode| {
+        let (unused_tx, _unused_rx) = mpsc::channel();
+        node.start_mesh(0, "A".into(), Box::new(TextHandler(Mutex::new(unused_tx))), Box::new(SharedClipboard(Arc::clone(&clipboard_a))), 20, None)
+            .unwrap();
+    };
+    let (texts_tx, texts_rx) = mpsc::channel();
+    nodes[1]
+        .start_mesh(0, "B".into(), Box::new(TextHandler(Mutex::new(texts_tx))), Box::new(SharedClipboard(Arc::default())), 20, None)
+        .unwrap();
+
+    // Back to back, then two racing restarts.
+    start_a(&nodes[0]);
+    start_a(&nodes[0]);
+    std::thread::scope(|s| {
+        s.spawn(|| start_a(&nodes[0]));
+        s.spawn(|| start_a(&nodes[0]));
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while nodes[0].snapshot(0).connected != vec![nodes[1].peer_id()]
+        || nodes[1].snapshot(0).connected != vec![nodes[0].peer_id()]
+    {
+        assert!(Instant::now() < deadline, "nodes never connected");
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    for text in ["first", "second"] {
+        *clipboard_a.lock().unwrap() = Some(text.to_string());
+        assert_eq!(texts_rx.recv_timeout(Duration::from_secs(5)).expect("B got no clip"), text);
+        assert!(texts_rx.recv_timeout(Duration::from_millis(300)).is_err(), "{text} was broadcast more than once");
+    }
+
+    for n in &nodes {
+        n.stop_sync();
+    }
+}
@@ -0,0 +1,148 @@
+//! Switching the node's active trust store ("profile") at runtime.
+
+use openclipboard_core::MockDiscovery;
+use openclipboard_ffi::{
+    clipboard_node_new_with_sync_discovery, identity_generate, trust_store_open, ClipboardNode, EventHandler,
+};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Tracks the set of currently connected peers.
+#[derive(Clone, Default)]
+struct PeerSetHandler {
+    connected: Arc<Mutex<BTreeSet<String>>>,
+}
+
+impl EventHandler for PeerSetHandler {
+    fn on_clipboard_text(&self, _: String, _: String, _: u64) {}
+    fn on_file_received(&self, _: String, _: String, _: String) {}
+    fn on_peer_connected(&self, peer_id: String) {
+        self.connected.lock().unwrap().insert(peer_id);
+    }
+    fn on_peer_disconnected(&self, peer_id: String) {
+        self.connected.lock().unwrap().remove(&peer_id);
+    }
+    fn on_error(&self, _: String) {}
+}
+
+fn wait_for_peers(handler: &PeerSetHandler, expected: &BTreeSet<String>) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if *handler.connected.lock().unwrap() == *expected {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+/// Create a node whose identity and trust file are written up front.
+fn make_node(
+    td: &TempDir,
+    name: &str,
+    trusted: &[(String, String)],
+    shared: &MockDiscovery,
+) -> (Arc<ClipboardNode>, String) {
+    let id = identity_generate();
+    let id_path = td.path().join(format!("{name}_identity.json")).to_string_lossy().to_string();
+    id.save(id_path.clone()).unwrap();
+    let trust_path = td.path().join(format!("{name}_trust.json")).to_string_lossy().to_string();
+    let trust = trust_store_open(trust_path.clone()).unwrap();
+    for (peer_id, pubkey_b64) in trusted {
+        trust.add(peer_id.clone(), pubkey_b64.clone(), "peer".into()).unwrap();
+    }
+    let node = clipboard_node_new_with_sync_discovery(
+        id_path,
+        trust_path,
+        Arc::new(shared.clone_shared()),
+        std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+    )
+    .unwrap();
+    (node, id.pubkey_b64())
+}
+
+#[test]
+fn switching_profile_limits_connections_to_the_active_trust_set() {
+    let td = TempDir::new().unwrap();
+    let shared = MockDiscovery::new_shared();
+
+    // The multi-profile node; both peers trust it, but each profile trusts only one peer.
+    let main_id = identity_generate();
+    let main_id_path = td.path().join("main_identity.json").to_string_lossy().to_string();
+    main_id.save(main_id_path.clone()).unwrap();
+    let trusts_main = [(main_id.peer_id(), main_id.pubkey_b64())];
+    let (work_peer, work_pk) = make_node(&td, "laptop", &trusts_main, &shared);
+    let (personal_peer, personal_pk) = make_node(&td, "phone", &trusts_main, &shared);
+
+    let work_path = td.path().join("work_trust.json").to_string_lossy().to_string();
+    let personal_path = td.path().join("personal_trust.json").to_string_lossy().to_string();
+    trust_store_open(work_path.clone())
+        .unwrap()
+        .add(work_peer.peer_id(), work_pk, "laptop".into())
+        .unwrap();
+    trust_store_open(personal_path.clone())
+        .unwrap()
+        .add(personal_peer.peer_id(), personal_pk, "phone".into())
+        .unwrap();
+
+    let main = clipboard_node_new_with_sync_discovery(
+        main_id_path,
+        work_path,
+        Arc::new(shared.clone_shared()),
+        std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+    )
+    .unwrap();
+
+    work_peer.start_sync(0, "laptop".into(), Box::new(PeerSetHandler::default())).unwrap();
+    personal_peer.start_sync(0, "phone".into(), Box::new(PeerSetHandler::default())).unwrap();
+
+    let work_handler = PeerSetHandler::default();
+    main.start_sync(0, "main".into(), Box::new(work_handler.clone())).unwrap();
+    assert!(
+        wait_for_peers(&work_handler, &BTreeSet::from([work_peer.peer_id()])),
+        "work profile connected to {:?}",
+        work_handler.connected.lock().unwrap()
+    );
+
+    main.set_profile(personal_path).unwrap();
+    let personal_handler = PeerSetHandler::default();
+    main.start_sync(0, "main".into(), Box::new(personal_handler.clone())).unwrap();
+    assert!(
+        wait_for_peers(&personal_handler, &BTreeSet::from([personal_peer.peer_id()])),
+        "personal profile connected to {:?}",
+        personal_handler.connected.lock().unwrap()
+    );
+
+    // The work peer keeps retrying, but stays out while the personal profile is active.
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(*personal_handler.connected.lock().unwrap(), BTreeSet::from([personal_peer.peer_id()]));
+
+    main.stop();
+    work_peer.stop();
+    personal_peer.stop();
+}
+
+#[test]
+fn linked_devices_persist_across_sync_restarts() {
+    let td = TempDir::new().unwrap();
+    let shared = MockDiscovery::new_shared();
+    let (node, _) = make_node(&td, "phone", &[], &shared);
+    let linked_path = td.path().join("linked.json").to_string_lossy().to_string();
+    node.set_linked_devices_file(Some(linked_path)).unwrap();
+
+    // Linking needs a running service; listing is empty without one.
+    assert!(node.set_linked_device("laptop".into(), true).is_err());
+    assert!(node.linked_devices().is_empty());
+
+    node.start_sync(0, "phone".into(), Box::new(PeerSetHandler::default())).unwrap();
+    node.set_linked_device("laptop".into(), true).unwrap();
+    assert_eq!(node.linked_devices(), ["laptop"]);
+    assert_eq!(node.sync_trust(), 0, "the laptop isn't connected");
+    node.stop_sync();
+
+    node.start_sync(0, "phone".into(), Box::new(PeerSetHandler::default())).unwrap();
+    assert_eq!(node.linked_devices(), ["laptop"]);
+    node.stop();
+}
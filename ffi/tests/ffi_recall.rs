@@ -0,0 +1,131 @@
+//! E2E test: a history recall whose clipboard write never lands must not
+//! keep the user's next copy from being broadcast.
+
+use openclipboard_ffi::{
+    clipboard_node_new_with_sync_discovery, identity_generate, trust_store_open, ClipboardCallback,
+    EventHandler,
+};
+use openclipboard_core::MockDiscovery;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// A platform clipboard whose writes can be made to silently do nothing.
+#[derive(Default)]
+struct ClipboardState {
+    content: Mutex<Option<String>>,
+    drop_writes: AtomicBool,
+}
+
+struct FlakyClipboard(Arc<ClipboardState>);
+
+impl ClipboardCallback for FlakyClipboard {
+    fn read_text(&self) -> Option<String> {
+        self.0.content.lock().unwrap().clone()
+    }
+    fn write_text(&self, text: String) {
+        if !self.0.drop_writes.load(Ordering::SeqCst) {
+            *self.0.content.lock().unwrap() = Some(text);
+        }
+    }
+}
+
+struct TextHandler(Mutex<mpsc::Sender<String>>);
+
+impl EventHandler for TextHandler {
+    fn on_clipboard_text(&self, _peer_id: String, text: String, _ts_ms: u64) {
+        let _ = self.0.lock().unwrap().send(text);
+    }
+    fn on_file_received(&self, _: String, _: String, _: String) {}
+    fn on_peer_connected(&self, _: String) {}
+    fn on_peer_disconnected(&self, _: String) {}
+    fn on_error(&self, _: String) {}
+}
+
+#[test]
+fn failed_recall_write_does_not_swallow_the_next_local_copy() {
+    let td = TempDir::new().unwrap();
+
+    let ids: Vec<_> = (0..2)
+        .map(|i| {
+            let id = identity_generate();
+            id.save(td.path().join(format!("id_{i}.json")).to_string_lossy().to_string()).unwrap();
+            id
+        })
+        .collect();
+    let trust_paths: Vec<String> = (0..2)
+        .map(|i| td.path().join(format!("trust_{i}.json")).to_string_lossy().to_string())
+        .collect();
+    for (i, trust_path) in trust_paths.iter().enumerate() {
+        let j = 1 - i;
+        let store = trust_store_open(trust_path.clone()).unwrap();
+        store.add(ids[j].peer_id(), ids[j].pubkey_b64(), format!("node_{j}")).unwrap();
+    }
+
+    let shared_disc = Arc::new(MockDiscovery::new_shared());
+    let nodes: Vec<_> = (0..2)
+        .map(|i| {
+            clipboard_node_new_with_sync_discovery(
+                td.path().join(format!("id_{i}.json")).to_string_lossy().to_string(),
+                trust_paths[i].clone(),
+                Arc::new(shared_disc.clone_shared()),
+                std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let clipboard_a = Arc::new(ClipboardState::default());
+    let (unused_tx, _unused_rx) = mpsc::channel();
+    let (texts_tx, texts_rx) = mpsc::channel();
+    nodes[0]
+        .start_mesh(0, "A".into(), Box::new(TextHandler(Mutex::new(unused_tx))), Box::new(FlakyClipboard(Arc::clone(&clipboard_a))), 20, None)
+        .unwrap();
+    nodes[1]
+        .start_mesh(0, "B".into(), Box::new(TextHandler(Mutex::new(texts_tx))), Box::new(FlakyClipboard(Arc::default())), 20, None)
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while nodes[0].snapshot(0).connected != vec![nodes[1].peer_id()]
+        || nodes[1].snapshot(0).connected != vec![nodes[0].peer_id()]
+    {
+        assert!(Instant::now() < deadline, "nodes never connected");
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    let copy = |text: &str| *clipboard_a.content.lock().unwrap() = Some(text.to_string());
+    let next_on_b = || texts_rx.recv_timeout(Duration::from_secs(5)).expect("B got no clip");
+
+    copy("first");
+    assert_eq!(next_on_b(), "first");
+    copy("second");
+    assert_eq!(next_on_b(), "second");
+    let first = nodes[0]
+        .get_clipboard_history(10)
+        .into_iter()
+        .find(|e| e.content == "first")
+        .expect("local copy recorded");
+
+    // The platform drops the write: recall reports it, and the user's next
+    // copy, even of the very text recalled, still goes out.
+    clipboard_a.drop_writes.store(true, Ordering::SeqCst);
+    assert!(nodes[0].recall_from_history(first.id.clone()).is_err());
+    assert_eq!(clipboard_a.content.lock().unwrap().as_deref(), Some("second"));
+    copy("first");
+    assert_eq!(next_on_b(), "first");
+    copy("third");
+    assert_eq!(next_on_b(), "third");
+
+    // A recall that lands stays local, and doesn't hold back the copy after it.
+    clipboard_a.drop_writes.store(false, Ordering::SeqCst);
+    assert_eq!(nodes[0].recall_from_history(first.id).unwrap().content, "first");
+    assert_eq!(clipboard_a.content.lock().unwrap().as_deref(), Some("first"));
+    std::thread::sleep(Duration::from_millis(200));
+    copy("fourth");
+    assert_eq!(next_on_b(), "fourth");
+
+    for n in &nodes {
+        n.stop_sync();
+    }
+}
@@ -1,13 +1,16 @@
 use base64::Engine as _;
 
 use openclipboard_ffi::{
+    confirmation_transcript,
     default_identity_path,
     derive_confirmation_code,
     identity_generate,
     identity_load,
     pairing_payload_create,
     pairing_payload_from_qr_string,
+    peer_visual,
     trust_store_open,
+    verify_confirmation_code,
 };
 
 #[test]
@@ -61,6 +64,19 @@ fn derive_confirmation_code_deterministic() {
     assert_eq!(c1.len(), 6);
 }
 
+#[test]
+fn confirmation_transcript_verifies_its_code() {
+    let nonce = vec![42u8; 32];
+    let code = derive_confirmation_code(nonce.clone(), "peer-a".into(), "peer-b".into());
+    let transcript = confirmation_transcript(nonce.clone(), "peer-a".into(), "peer-b".into());
+    assert_eq!(transcript.nonce, nonce);
+    assert_eq!((transcript.peer_a_id.as_str(), transcript.peer_b_id.as_str()), ("peer-a", "peer-b"));
+
+    assert!(verify_confirmation_code(transcript.clone(), code.clone()));
+    let other = format!("{:06}", (code.parse::<u32>().unwrap() + 1) % 1_000_000);
+    assert!(!verify_confirmation_code(transcript, other));
+}
+
 #[test]
 fn trust_store_add_list_get_remove_roundtrip() {
     let dir = tempfile::tempdir().unwrap();
@@ -96,3 +112,11 @@ fn default_identity_path_returns_something() {
     assert!(!p.is_empty());
     assert!(p.contains("identity"));
 }
+
+#[test]
+fn peer_visual_is_stable_per_identity() {
+    let id = identity_generate();
+    let v = peer_visual(id.peer_id());
+    assert_eq!(v, peer_visual(id.peer_id()));
+    assert_ne!(v.identicon_seed, peer_visual(identity_generate().peer_id()).identicon_seed);
+}
@@ -535,33 +535,250 @@ fileprivate struct FfiConverterString: FfiConverter {
 
 
 
+public protocol CancellationTokenProtocol: AnyObject, Sendable {
+    
+    func cancel() 
+    
+    func isCancelled()  -> Bool
+    
+}
+open class CancellationToken: CancellationTokenProtocol, @unchecked Sendable {
+    fileprivate let pointer: UnsafeMutableRawPointer!
+
+    /// Used to instantiate a [FFIObject] without an actual pointer, for fakes in tests, mostly.
+#if swift(>=5.8)
+    @_documentation(visibility: private)
+#endif
+    public struct NoPointer {
+        public init() {}
+    }
+
+    // TODO: We'd like this to be `private` but for Swifty reasons,
+    // we can't implement `FfiConverter` without making this `required` and we can't
+    // make it `required` without making it `public`.
+#if swift(>=5.8)
+    @_documentation(visibility: private)
+#endif
+    required public init(unsafeFromRawPointer pointer: UnsafeMutableRawPointer) {
+        self.pointer = pointer
+    }
+
+    // This constructor can be used to instantiate a fake object.
+    // - Parameter noPointer: Placeholder value so we can have a constructor separate from the default empty one that may be implemented for classes extending [FFIObject].
+    //
+    // - Warning:
+    //     Any object instantiated with this constructor cannot be passed to an actual Rust-backed object. Since there isn't a backing [Pointer] the FFI lower functions will crash.
+#if swift(>=5.8)
+    @_documentation(visibility: private)
+#endif
+    public init(noPointer: NoPointer) {
+        self.pointer = nil
+    }
+
+#if swift(>=5.8)
+    @_documentation(visibility: private)
+#endif
+    public func uniffiClonePointer() -> UnsafeMutableRawPointer {
+        return try! rustCall { uniffi_openclipboard_ffi_fn_clone_cancellationtoken(self.pointer, $0) }
+    }
+public convenience init() {
+    let pointer =
+        try! rustCall() {
+    uniffi_openclipboard_ffi_fn_constructor_cancellationtoken_new($0
+    )
+}
+    self.init(unsafeFromRawPointer: pointer)
+}
+
+    deinit {
+        guard let pointer = pointer else {
+            return
+        }
+
+        try! rustCall { uniffi_openclipboard_ffi_fn_free_cancellationtoken(pointer, $0) }
+    }
+
+    
+
+    
+open func cancel()  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_cancellationtoken_cancel(self.uniffiClonePointer(),$0
+    )
+}
+}
+    
+open func isCancelled() -> Bool  {
+    return try!  FfiConverterBool.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_cancellationtoken_is_cancelled(self.uniffiClonePointer(),$0
+    )
+})
+}
+    
+
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeCancellationToken: FfiConverter {
+
+    typealias FfiType = UnsafeMutableRawPointer
+    typealias SwiftType = CancellationToken
+
+    public static func lift(_ pointer: UnsafeMutableRawPointer) throws -> CancellationToken {
+        return CancellationToken(unsafeFromRawPointer: pointer)
+    }
+
+    public static func lower(_ value: CancellationToken) -> UnsafeMutableRawPointer {
+        return value.uniffiClonePointer()
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> CancellationToken {
+        let v: UInt64 = try readInt(&buf)
+        // The Rust code won't compile if a pointer won't fit in a UInt64.
+        // We have to go via `UInt` because that's the thing that's the size of a pointer.
+        let ptr = UnsafeMutableRawPointer(bitPattern: UInt(truncatingIfNeeded: v))
+        if (ptr == nil) {
+            throw UniffiInternalError.unexpectedNullPointer
+        }
+        return try lift(ptr!)
+    }
+
+    public static func write(_ value: CancellationToken, into buf: inout [UInt8]) {
+        // This fiddling is because `Int` is the thing that's the same size as a pointer.
+        // The Rust code won't compile if a pointer won't fit in a `UInt64`.
+        writeInt(&buf, UInt64(bitPattern: Int64(Int(bitPattern: lower(value)))))
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeCancellationToken_lift(_ pointer: UnsafeMutableRawPointer) throws -> CancellationToken {
+    return try FfiConverterTypeCancellationToken.lift(pointer)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeCancellationToken_lower(_ value: CancellationToken) -> UnsafeMutableRawPointer {
+    return FfiConverterTypeCancellationToken.lower(value)
+}
+
+
+
+
+
+
 public protocol ClipboardNodeProtocol: AnyObject, Sendable {
     
-    func connectAndSendFile(addr: String, filePath: String) throws 
+    func acceptPendingRequest(peerId: String, displayName: String) throws  -> Bool
+    
+    func activeSessions()  -> [SessionInfo]
+    
+    func clearEchoSuppressor() 
+    
+    func clearPeerFileTypeRules(peerId: String) 
+    
+    func connectAndSendFile(addr: String, filePath: String, cancel: CancellationToken?) throws 
     
     func connectAndSendText(addr: String, text: String) throws 
     
+    func diagnostics()  -> String
+    
     func disableQrPairingListener() throws 
     
+    func disconnectPeer(peerId: String)  -> Bool
+    
     func enableQrPairingListener() throws 
     
     func getClipboardHistory(limit: UInt32)  -> [ClipboardHistoryEntry]
     
     func getClipboardHistoryForPeer(peerName: String, limit: UInt32)  -> [ClipboardHistoryEntry]
     
+    func getClipboardPeerSummary()  -> [PeerClipSummary]
+    
+    func isDiscovering()  -> Bool
+    
+    func lastClipForPeer(peerId: String)  -> String?
+    
+    func linkedDevices()  -> [String]
+    
+    func listPeers()  -> [PeerEntry]
+    
     func pairViaQr(qrString: String) throws  -> String
     
     func peerId()  -> String
     
+    func pendingRequests()  -> [PendingRequest]
+    
     func recallFromHistory(entryId: String) throws  -> ClipboardHistoryEntry
     
+    func receivedFiles()  -> [ReceivedFileEntry]
+    
+    func recentErrors(limit: UInt32)  -> [ErrorEntry]
+    
+    func removeTrustedPeer(peerId: String) throws  -> Bool
+    
+    func resendCurrent() throws  -> Bool
+    
+    func resumePeer(peerId: String) 
+    
+    func sendClipboardImage(mime: String, width: UInt32, height: UInt32, bytes: [UInt8]) throws 
+    
     func sendClipboardText(text: String) throws 
     
+    func sendFileToPeer(peerId: String, filePath: String, progress: FileProgressHandler) throws 
+    
+    func setDisconnectGraceMs(graceMs: UInt64) 
+    
+    func setDownloadDir(dir: String) 
+    
+    func setDrainOnStopMs(timeoutMs: UInt64) 
+    
+    func setFileRateLimit(bytesPerSec: UInt64) 
+    
+    func setFileTransferConfig(chunkSize: UInt32, window: UInt32) 
+    
+    func setFileTypeRules(allow: [String], deny: [String]) 
+    
+    func setHistoryFile(path: String?) 
+    
+    func setHistoryMaxBytes(maxBytes: UInt64) 
+    
+    func setKeepaliveMs(intervalMs: UInt64, timeoutMs: UInt64) 
+    
+    func setLinkedDevice(peerId: String, linked: Bool) throws 
+    
+    func setLinkedDevicesFile(path: String?) throws 
+    
+    func setMaxConcurrentFileSends(limit: UInt32) 
+    
+    func setMaxIncomingFileSize(maxBytes: UInt64) 
+    
+    func setPartialFilesDir(dir: String?) 
+    
+    func setPeerFileRateLimit(peerId: String, bytesPerSec: UInt64) 
+    
+    func setPeerFileTypeRules(peerId: String, allow: [String], deny: [String]) 
+    
+    func setPeerSyncDirection(peerId: String, direction: SyncDirection) throws  -> Bool
+    
+    func setProfile(trustPath: String) throws 
+    
+    func setReceivedFilesLog(path: String?) throws 
+    
+    func setSyncWhitespace(enabled: Bool) 
+    
+    func snapshot(historyLimit: UInt32)  -> NodeSnapshot
+    
     func startDiscovery(deviceName: String, handler: DiscoveryHandler) throws 
     
     func startListener(port: UInt16, handler: EventHandler) throws 
     
-    func startMesh(port: UInt16, deviceName: String, handler: EventHandler, provider: ClipboardCallback, pollIntervalMs: UInt64) throws 
+    func startMesh(port: UInt16, deviceName: String, handler: EventHandler, provider: ClipboardCallback, pollIntervalMs: UInt64, cancel: CancellationToken?) throws 
     
     func startSync(port: UInt16, deviceName: String, handler: EventHandler) throws 
     
@@ -571,6 +788,10 @@ public protocol ClipboardNodeProtocol: AnyObject, Sendable {
     
     func stopSync() 
     
+    func syncTrust()  -> UInt32
+    
+    func terminateSession(peerId: String)  -> Bool
+    
 }
 open class ClipboardNode: ClipboardNodeProtocol, @unchecked Sendable {
     fileprivate let pointer: UnsafeMutableRawPointer!
@@ -624,10 +845,40 @@ open class ClipboardNode: ClipboardNodeProtocol, @unchecked Sendable {
     
 
     
-open func connectAndSendFile(addr: String, filePath: String)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+open func acceptPendingRequest(peerId: String, displayName: String)throws  -> Bool  {
+    return try  FfiConverterBool.lift(try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_accept_pending_request(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),
+        FfiConverterString.lower(displayName),$0
+    )
+})
+}
+    
+open func activeSessions() -> [SessionInfo]  {
+    return try!  FfiConverterSequenceTypeSessionInfo.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_active_sessions(self.uniffiClonePointer(),$0
+    )
+})
+}
+    
+open func clearEchoSuppressor()  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_clear_echo_suppressor(self.uniffiClonePointer(),$0
+    )
+}
+}
+    
+open func clearPeerFileTypeRules(peerId: String)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_clear_peer_file_type_rules(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),$0
+    )
+}
+}
+    
+open func connectAndSendFile(addr: String, filePath: String, cancel: CancellationToken? = nil)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
     uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_file(self.uniffiClonePointer(),
         FfiConverterString.lower(addr),
-        FfiConverterString.lower(filePath),$0
+        FfiConverterString.lower(filePath),
+        FfiConverterOptionTypeCancellationToken.lower(cancel),$0
     )
 }
 }
@@ -640,12 +891,27 @@ open func connectAndSendText(addr: String, text: String)throws   {try rustCallWi
 }
 }
     
+open func diagnostics() -> String  {
+    return try!  FfiConverterString.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_diagnostics(self.uniffiClonePointer(),$0
+    )
+})
+}
+    
 open func disableQrPairingListener()throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
     uniffi_openclipboard_ffi_fn_method_clipboardnode_disable_qr_pairing_listener(self.uniffiClonePointer(),$0
     )
 }
 }
     
+open func disconnectPeer(peerId: String) -> Bool  {
+    return try!  FfiConverterBool.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_disconnect_peer(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),$0
+    )
+})
+}
+    
 open func enableQrPairingListener()throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
     uniffi_openclipboard_ffi_fn_method_clipboardnode_enable_qr_pairing_listener(self.uniffiClonePointer(),$0
     )
@@ -669,6 +935,42 @@ open func getClipboardHistoryForPeer(peerName: String, limit: UInt32) -> [Clipbo
 })
 }
     
+open func getClipboardPeerSummary() -> [PeerClipSummary]  {
+    return try!  FfiConverterSequenceTypePeerClipSummary.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_peer_summary(self.uniffiClonePointer(),$0
+    )
+})
+}
+    
+open func isDiscovering() -> Bool  {
+    return try!  FfiConverterBool.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_is_discovering(self.uniffiClonePointer(),$0
+    )
+})
+}
+    
+open func lastClipForPeer(peerId: String) -> String?  {
+    return try!  FfiConverterOptionString.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_last_clip_for_peer(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),$0
+    )
+})
+}
+    
+open func linkedDevices() -> [String]  {
+    return try!  FfiConverterSequenceString.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_linked_devices(self.uniffiClonePointer(),$0
+    )
+})
+}
+    
+open func listPeers() -> [PeerEntry]  {
+    return try!  FfiConverterSequenceTypePeerEntry.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_list_peers(self.uniffiClonePointer(),$0
+    )
+})
+}
+    
 open func pairViaQr(qrString: String)throws  -> String  {
     return try  FfiConverterString.lift(try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
     uniffi_openclipboard_ffi_fn_method_clipboardnode_pair_via_qr(self.uniffiClonePointer(),
@@ -684,6 +986,13 @@ open func peerId() -> String  {
 })
 }
     
+open func pendingRequests() -> [PendingRequest]  {
+    return try!  FfiConverterSequenceTypePendingRequest.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_pending_requests(self.uniffiClonePointer(),$0
+    )
+})
+}
+    
 open func recallFromHistory(entryId: String)throws  -> ClipboardHistoryEntry  {
     return try  FfiConverterTypeClipboardHistoryEntry_lift(try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
     uniffi_openclipboard_ffi_fn_method_clipboardnode_recall_from_history(self.uniffiClonePointer(),
@@ -692,71 +1001,300 @@ open func recallFromHistory(entryId: String)throws  -> ClipboardHistoryEntry  {
 })
 }
     
-open func sendClipboardText(text: String)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
-    uniffi_openclipboard_ffi_fn_method_clipboardnode_send_clipboard_text(self.uniffiClonePointer(),
-        FfiConverterString.lower(text),$0
+open func receivedFiles() -> [ReceivedFileEntry]  {
+    return try!  FfiConverterSequenceTypeReceivedFileEntry.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_received_files(self.uniffiClonePointer(),$0
     )
+})
 }
+    
+open func recentErrors(limit: UInt32) -> [ErrorEntry]  {
+    return try!  FfiConverterSequenceTypeErrorEntry.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_recent_errors(self.uniffiClonePointer(),
+        FfiConverterUInt32.lower(limit),$0
+    )
+})
 }
     
-open func startDiscovery(deviceName: String, handler: DiscoveryHandler)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
-    uniffi_openclipboard_ffi_fn_method_clipboardnode_start_discovery(self.uniffiClonePointer(),
-        FfiConverterString.lower(deviceName),
-        FfiConverterCallbackInterfaceDiscoveryHandler_lower(handler),$0
+open func removeTrustedPeer(peerId: String)throws  -> Bool  {
+    return try  FfiConverterBool.lift(try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_remove_trusted_peer(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),$0
     )
+})
 }
+    
+open func resendCurrent()throws  -> Bool  {
+    return try  FfiConverterBool.lift(try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_resend_current(self.uniffiClonePointer(),$0
+    )
+})
 }
     
-open func startListener(port: UInt16, handler: EventHandler)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
-    uniffi_openclipboard_ffi_fn_method_clipboardnode_start_listener(self.uniffiClonePointer(),
-        FfiConverterUInt16.lower(port),
-        FfiConverterCallbackInterfaceEventHandler_lower(handler),$0
+open func resumePeer(peerId: String)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_resume_peer(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),$0
     )
 }
 }
     
-open func startMesh(port: UInt16, deviceName: String, handler: EventHandler, provider: ClipboardCallback, pollIntervalMs: UInt64)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
-    uniffi_openclipboard_ffi_fn_method_clipboardnode_start_mesh(self.uniffiClonePointer(),
-        FfiConverterUInt16.lower(port),
-        FfiConverterString.lower(deviceName),
-        FfiConverterCallbackInterfaceEventHandler_lower(handler),
-        FfiConverterCallbackInterfaceClipboardCallback_lower(provider),
-        FfiConverterUInt64.lower(pollIntervalMs),$0
+open func sendClipboardImage(mime: String, width: UInt32, height: UInt32, bytes: [UInt8])throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_send_clipboard_image(self.uniffiClonePointer(),
+        FfiConverterString.lower(mime),
+        FfiConverterUInt32.lower(width),
+        FfiConverterUInt32.lower(height),
+        FfiConverterSequenceUInt8.lower(bytes),$0
     )
 }
 }
     
-open func startSync(port: UInt16, deviceName: String, handler: EventHandler)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
-    uniffi_openclipboard_ffi_fn_method_clipboardnode_start_sync(self.uniffiClonePointer(),
-        FfiConverterUInt16.lower(port),
-        FfiConverterString.lower(deviceName),
-        FfiConverterCallbackInterfaceEventHandler_lower(handler),$0
+open func sendClipboardText(text: String)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_send_clipboard_text(self.uniffiClonePointer(),
+        FfiConverterString.lower(text),$0
     )
 }
 }
     
-open func stop()  {try! rustCall() {
-    uniffi_openclipboard_ffi_fn_method_clipboardnode_stop(self.uniffiClonePointer(),$0
+open func sendFileToPeer(peerId: String, filePath: String, progress: FileProgressHandler)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_send_file_to_peer(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),
+        FfiConverterString.lower(filePath),
+        FfiConverterCallbackInterfaceFileProgressHandler_lower(progress),$0
     )
 }
 }
     
-open func stopDiscovery()  {try! rustCall() {
-    uniffi_openclipboard_ffi_fn_method_clipboardnode_stop_discovery(self.uniffiClonePointer(),$0
+open func setDisconnectGraceMs(graceMs: UInt64)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_disconnect_grace_ms(self.uniffiClonePointer(),
+        FfiConverterUInt64.lower(graceMs),$0
     )
 }
 }
     
-open func stopSync()  {try! rustCall() {
-    uniffi_openclipboard_ffi_fn_method_clipboardnode_stop_sync(self.uniffiClonePointer(),$0
+open func setDownloadDir(dir: String)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_download_dir(self.uniffiClonePointer(),
+        FfiConverterString.lower(dir),$0
     )
 }
 }
     
-
+open func setDrainOnStopMs(timeoutMs: UInt64)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_drain_on_stop_ms(self.uniffiClonePointer(),
+        FfiConverterUInt64.lower(timeoutMs),$0
+    )
 }
-
-
+}
+    
+open func setFileRateLimit(bytesPerSec: UInt64)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_rate_limit(self.uniffiClonePointer(),
+        FfiConverterUInt64.lower(bytesPerSec),$0
+    )
+}
+}
+    
+open func setFileTransferConfig(chunkSize: UInt32, window: UInt32)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_transfer_config(self.uniffiClonePointer(),
+        FfiConverterUInt32.lower(chunkSize),
+        FfiConverterUInt32.lower(window),$0
+    )
+}
+}
+    
+open func setFileTypeRules(allow: [String], deny: [String])  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_type_rules(self.uniffiClonePointer(),
+        FfiConverterSequenceString.lower(allow),
+        FfiConverterSequenceString.lower(deny),$0
+    )
+}
+}
+    
+open func setHistoryFile(path: String?)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_history_file(self.uniffiClonePointer(),
+        FfiConverterOptionString.lower(path),$0
+    )
+}
+}
+    
+open func setHistoryMaxBytes(maxBytes: UInt64)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_history_max_bytes(self.uniffiClonePointer(),
+        FfiConverterUInt64.lower(maxBytes),$0
+    )
+}
+}
+    
+open func setKeepaliveMs(intervalMs: UInt64, timeoutMs: UInt64)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_keepalive_ms(self.uniffiClonePointer(),
+        FfiConverterUInt64.lower(intervalMs),
+        FfiConverterUInt64.lower(timeoutMs),$0
+    )
+}
+}
+    
+open func setLinkedDevice(peerId: String, linked: Bool)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_linked_device(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),
+        FfiConverterBool.lower(linked),$0
+    )
+}
+}
+    
+open func setLinkedDevicesFile(path: String?)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_linked_devices_file(self.uniffiClonePointer(),
+        FfiConverterOptionString.lower(path),$0
+    )
+}
+}
+    
+open func setMaxConcurrentFileSends(limit: UInt32)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_max_concurrent_file_sends(self.uniffiClonePointer(),
+        FfiConverterUInt32.lower(limit),$0
+    )
+}
+}
+    
+open func setMaxIncomingFileSize(maxBytes: UInt64)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_max_incoming_file_size(self.uniffiClonePointer(),
+        FfiConverterUInt64.lower(maxBytes),$0
+    )
+}
+}
+    
+open func setPartialFilesDir(dir: String?)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_partial_files_dir(self.uniffiClonePointer(),
+        FfiConverterOptionString.lower(dir),$0
+    )
+}
+}
+    
+open func setPeerFileRateLimit(peerId: String, bytesPerSec: UInt64)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_file_rate_limit(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),
+        FfiConverterUInt64.lower(bytesPerSec),$0
+    )
+}
+}
+    
+open func setPeerFileTypeRules(peerId: String, allow: [String], deny: [String])  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_file_type_rules(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),
+        FfiConverterSequenceString.lower(allow),
+        FfiConverterSequenceString.lower(deny),$0
+    )
+}
+}
+    
+open func setPeerSyncDirection(peerId: String, direction: SyncDirection)throws  -> Bool  {
+    return try  FfiConverterBool.lift(try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_sync_direction(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),
+        FfiConverterTypeSyncDirection_lower(direction),$0
+    )
+})
+}
+    
+open func setProfile(trustPath: String)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_profile(self.uniffiClonePointer(),
+        FfiConverterString.lower(trustPath),$0
+    )
+}
+}
+    
+open func setReceivedFilesLog(path: String?)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_received_files_log(self.uniffiClonePointer(),
+        FfiConverterOptionString.lower(path),$0
+    )
+}
+}
+    
+open func setSyncWhitespace(enabled: Bool)  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_set_sync_whitespace(self.uniffiClonePointer(),
+        FfiConverterBool.lower(enabled),$0
+    )
+}
+}
+    
+open func snapshot(historyLimit: UInt32) -> NodeSnapshot  {
+    return try!  FfiConverterTypeNodeSnapshot_lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_snapshot(self.uniffiClonePointer(),
+        FfiConverterUInt32.lower(historyLimit),$0
+    )
+})
+}
+    
+open func startDiscovery(deviceName: String, handler: DiscoveryHandler)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_start_discovery(self.uniffiClonePointer(),
+        FfiConverterString.lower(deviceName),
+        FfiConverterCallbackInterfaceDiscoveryHandler_lower(handler),$0
+    )
+}
+}
+    
+open func startListener(port: UInt16, handler: EventHandler)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_start_listener(self.uniffiClonePointer(),
+        FfiConverterUInt16.lower(port),
+        FfiConverterCallbackInterfaceEventHandler_lower(handler),$0
+    )
+}
+}
+    
+open func startMesh(port: UInt16, deviceName: String, handler: EventHandler, provider: ClipboardCallback, pollIntervalMs: UInt64, cancel: CancellationToken? = nil)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_start_mesh(self.uniffiClonePointer(),
+        FfiConverterUInt16.lower(port),
+        FfiConverterString.lower(deviceName),
+        FfiConverterCallbackInterfaceEventHandler_lower(handler),
+        FfiConverterCallbackInterfaceClipboardCallback_lower(provider),
+        FfiConverterUInt64.lower(pollIntervalMs),
+        FfiConverterOptionTypeCancellationToken.lower(cancel),$0
+    )
+}
+}
+    
+open func startSync(port: UInt16, deviceName: String, handler: EventHandler)throws   {try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_start_sync(self.uniffiClonePointer(),
+        FfiConverterUInt16.lower(port),
+        FfiConverterString.lower(deviceName),
+        FfiConverterCallbackInterfaceEventHandler_lower(handler),$0
+    )
+}
+}
+    
+open func stop()  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_stop(self.uniffiClonePointer(),$0
+    )
+}
+}
+    
+open func stopDiscovery()  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_stop_discovery(self.uniffiClonePointer(),$0
+    )
+}
+}
+    
+open func stopSync()  {try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_stop_sync(self.uniffiClonePointer(),$0
+    )
+}
+}
+    
+open func syncTrust() -> UInt32  {
+    return try!  FfiConverterUInt32.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_sync_trust(self.uniffiClonePointer(),$0
+    )
+})
+}
+    
+open func terminateSession(peerId: String) -> Bool  {
+    return try!  FfiConverterBool.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_method_clipboardnode_terminate_session(self.uniffiClonePointer(),
+        FfiConverterString.lower(peerId),$0
+    )
+})
+}
+    
+
+}
+
+
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
@@ -976,6 +1514,8 @@ public protocol PairingPayloadProtocol: AnyObject, Sendable {
     
     func toQrString() throws  -> String
     
+    func toQrStringCompact() throws  -> String
+    
     func version()  -> UInt8
     
 }
@@ -1080,6 +1620,13 @@ open func toQrString()throws  -> String  {
 })
 }
     
+open func toQrStringCompact()throws  -> String  {
+    return try  FfiConverterString.lift(try rustCallWithError(FfiConverterTypeOpenClipboardError_lift) {
+    uniffi_openclipboard_ffi_fn_method_pairingpayload_to_qr_string_compact(self.uniffiClonePointer(),$0
+    )
+})
+}
+    
 open func version() -> UInt8  {
     return try!  FfiConverterUInt8.lift(try! rustCall() {
     uniffi_openclipboard_ffi_fn_method_pairingpayload_version(self.uniffiClonePointer(),$0
@@ -1247,34 +1794,952 @@ open func remove(peerId: String)throws  -> Bool  {
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public struct FfiConverterTypeTrustStore: FfiConverter {
-
-    typealias FfiType = UnsafeMutableRawPointer
-    typealias SwiftType = TrustStore
-
-    public static func lift(_ pointer: UnsafeMutableRawPointer) throws -> TrustStore {
-        return TrustStore(unsafeFromRawPointer: pointer)
-    }
-
-    public static func lower(_ value: TrustStore) -> UnsafeMutableRawPointer {
-        return value.uniffiClonePointer()
-    }
-
-    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> TrustStore {
-        let v: UInt64 = try readInt(&buf)
-        // The Rust code won't compile if a pointer won't fit in a UInt64.
-        // We have to go via `UInt` because that's the thing that's the size of a pointer.
-        let ptr = UnsafeMutableRawPointer(bitPattern: UInt(truncatingIfNeeded: v))
-        if (ptr == nil) {
-            throw UniffiInternalError.unexpectedNullPointer
-        }
-        return try lift(ptr!)
+public struct FfiConverterTypeTrustStore: FfiConverter {
+
+    typealias FfiType = UnsafeMutableRawPointer
+    typealias SwiftType = TrustStore
+
+    public static func lift(_ pointer: UnsafeMutableRawPointer) throws -> TrustStore {
+        return TrustStore(unsafeFromRawPointer: pointer)
+    }
+
+    public static func lower(_ value: TrustStore) -> UnsafeMutableRawPointer {
+        return value.uniffiClonePointer()
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> TrustStore {
+        let v: UInt64 = try readInt(&buf)
+        // The Rust code won't compile if a pointer won't fit in a UInt64.
+        // We have to go via `UInt` because that's the thing that's the size of a pointer.
+        let ptr = UnsafeMutableRawPointer(bitPattern: UInt(truncatingIfNeeded: v))
+        if (ptr == nil) {
+            throw UniffiInternalError.unexpectedNullPointer
+        }
+        return try lift(ptr!)
+    }
+
+    public static func write(_ value: TrustStore, into buf: inout [UInt8]) {
+        // This fiddling is because `Int` is the thing that's the same size as a pointer.
+        // The Rust code won't compile if a pointer won't fit in a `UInt64`.
+        writeInt(&buf, UInt64(bitPattern: Int64(Int(bitPattern: lower(value)))))
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeTrustStore_lift(_ pointer: UnsafeMutableRawPointer) throws -> TrustStore {
+    return try FfiConverterTypeTrustStore.lift(pointer)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeTrustStore_lower(_ value: TrustStore) -> UnsafeMutableRawPointer {
+    return FfiConverterTypeTrustStore.lower(value)
+}
+
+
+
+
+public struct ClipboardHistoryEntry {
+    public var id: String
+    public var content: String
+    public var sourcePeer: String
+    public var originName: String?
+    public var timestamp: UInt64
+    public var kind: ClipboardHistoryKind
+    public var image: ClipboardImage?
+    public var filePath: String?
+    public var fileSize: UInt64?
+
+    // Default memberwise initializers are never public by default, so we
+    // declare one manually.
+    public init(id: String, content: String, sourcePeer: String, originName: String? = nil, timestamp: UInt64, kind: ClipboardHistoryKind = .text, image: ClipboardImage? = nil, filePath: String? = nil, fileSize: UInt64? = nil) {
+        self.id = id
+        self.content = content
+        self.sourcePeer = sourcePeer
+        self.originName = originName
+        self.timestamp = timestamp
+        self.kind = kind
+        self.image = image
+        self.filePath = filePath
+        self.fileSize = fileSize
+    }
+}
+
+#if compiler(>=6)
+extension ClipboardHistoryEntry: Sendable {}
+#endif
+
+
+extension ClipboardHistoryEntry: Equatable, Hashable {
+    public static func ==(lhs: ClipboardHistoryEntry, rhs: ClipboardHistoryEntry) -> Bool {
+        if lhs.id != rhs.id {
+            return false
+        }
+        if lhs.content != rhs.content {
+            return false
+        }
+        if lhs.sourcePeer != rhs.sourcePeer {
+            return false
+        }
+        if lhs.originName != rhs.originName {
+            return false
+        }
+        if lhs.timestamp != rhs.timestamp {
+            return false
+        }
+        if lhs.kind != rhs.kind {
+            return false
+        }
+        if lhs.image != rhs.image {
+            return false
+        }
+        if lhs.filePath != rhs.filePath {
+            return false
+        }
+        if lhs.fileSize != rhs.fileSize {
+            return false
+        }
+        return true
+    }
+
+    public func hash(into hasher: inout Hasher) {
+        hasher.combine(id)
+        hasher.combine(content)
+        hasher.combine(sourcePeer)
+        hasher.combine(originName)
+        hasher.combine(timestamp)
+        hasher.combine(kind)
+        hasher.combine(image)
+        hasher.combine(filePath)
+        hasher.combine(fileSize)
+    }
+}
+
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeClipboardHistoryEntry: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> ClipboardHistoryEntry {
+        return
+            try ClipboardHistoryEntry(
+                id: FfiConverterString.read(from: &buf), 
+                content: FfiConverterString.read(from: &buf), 
+                sourcePeer: FfiConverterString.read(from: &buf), 
+                originName: FfiConverterOptionString.read(from: &buf), 
+                timestamp: FfiConverterUInt64.read(from: &buf), 
+                kind: FfiConverterTypeClipboardHistoryKind.read(from: &buf), 
+                image: FfiConverterOptionTypeClipboardImage.read(from: &buf), 
+                filePath: FfiConverterOptionString.read(from: &buf), 
+                fileSize: FfiConverterOptionUInt64.read(from: &buf)
+        )
+    }
+
+    public static func write(_ value: ClipboardHistoryEntry, into buf: inout [UInt8]) {
+        FfiConverterString.write(value.id, into: &buf)
+        FfiConverterString.write(value.content, into: &buf)
+        FfiConverterString.write(value.sourcePeer, into: &buf)
+        FfiConverterOptionString.write(value.originName, into: &buf)
+        FfiConverterUInt64.write(value.timestamp, into: &buf)
+        FfiConverterTypeClipboardHistoryKind.write(value.kind, into: &buf)
+        FfiConverterOptionTypeClipboardImage.write(value.image, into: &buf)
+        FfiConverterOptionString.write(value.filePath, into: &buf)
+        FfiConverterOptionUInt64.write(value.fileSize, into: &buf)
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeClipboardHistoryEntry_lift(_ buf: RustBuffer) throws -> ClipboardHistoryEntry {
+    return try FfiConverterTypeClipboardHistoryEntry.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeClipboardHistoryEntry_lower(_ value: ClipboardHistoryEntry) -> RustBuffer {
+    return FfiConverterTypeClipboardHistoryEntry.lower(value)
+}
+
+
+public struct ClipboardImage {
+    public var mime: String
+    public var width: UInt32
+    public var height: UInt32
+    public var bytes: [UInt8]
+
+    // Default memberwise initializers are never public by default, so we
+    // declare one manually.
+    public init(mime: String, width: UInt32, height: UInt32, bytes: [UInt8]) {
+        self.mime = mime
+        self.width = width
+        self.height = height
+        self.bytes = bytes
+    }
+}
+
+#if compiler(>=6)
+extension ClipboardImage: Sendable {}
+#endif
+
+
+extension ClipboardImage: Equatable, Hashable {
+    public static func ==(lhs: ClipboardImage, rhs: ClipboardImage) -> Bool {
+        if lhs.mime != rhs.mime {
+            return false
+        }
+        if lhs.width != rhs.width {
+            return false
+        }
+        if lhs.height != rhs.height {
+            return false
+        }
+        if lhs.bytes != rhs.bytes {
+            return false
+        }
+        return true
+    }
+
+    public func hash(into hasher: inout Hasher) {
+        hasher.combine(mime)
+        hasher.combine(width)
+        hasher.combine(height)
+        hasher.combine(bytes)
+    }
+}
+
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeClipboardImage: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> ClipboardImage {
+        return
+            try ClipboardImage(
+                mime: FfiConverterString.read(from: &buf), 
+                width: FfiConverterUInt32.read(from: &buf), 
+                height: FfiConverterUInt32.read(from: &buf), 
+                bytes: FfiConverterSequenceUInt8.read(from: &buf)
+        )
+    }
+
+    public static func write(_ value: ClipboardImage, into buf: inout [UInt8]) {
+        FfiConverterString.write(value.mime, into: &buf)
+        FfiConverterUInt32.write(value.width, into: &buf)
+        FfiConverterUInt32.write(value.height, into: &buf)
+        FfiConverterSequenceUInt8.write(value.bytes, into: &buf)
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeClipboardImage_lift(_ buf: RustBuffer) throws -> ClipboardImage {
+    return try FfiConverterTypeClipboardImage.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeClipboardImage_lower(_ value: ClipboardImage) -> RustBuffer {
+    return FfiConverterTypeClipboardImage.lower(value)
+}
+
+
+public struct ConfirmationTranscript {
+    public var nonce: [UInt8]
+    public var peerAId: String
+    public var peerBId: String
+
+    // Default memberwise initializers are never public by default, so we
+    // declare one manually.
+    public init(nonce: [UInt8], peerAId: String, peerBId: String) {
+        self.nonce = nonce
+        self.peerAId = peerAId
+        self.peerBId = peerBId
+    }
+}
+
+#if compiler(>=6)
+extension ConfirmationTranscript: Sendable {}
+#endif
+
+
+extension ConfirmationTranscript: Equatable, Hashable {
+    public static func ==(lhs: ConfirmationTranscript, rhs: ConfirmationTranscript) -> Bool {
+        if lhs.nonce != rhs.nonce {
+            return false
+        }
+        if lhs.peerAId != rhs.peerAId {
+            return false
+        }
+        if lhs.peerBId != rhs.peerBId {
+            return false
+        }
+        return true
+    }
+
+    public func hash(into hasher: inout Hasher) {
+        hasher.combine(nonce)
+        hasher.combine(peerAId)
+        hasher.combine(peerBId)
+    }
+}
+
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeConfirmationTranscript: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> ConfirmationTranscript {
+        return
+            try ConfirmationTranscript(
+                nonce: FfiConverterSequenceUInt8.read(from: &buf), 
+                peerAId: FfiConverterString.read(from: &buf), 
+                peerBId: FfiConverterString.read(from: &buf)
+        )
+    }
+
+    public static func write(_ value: ConfirmationTranscript, into buf: inout [UInt8]) {
+        FfiConverterSequenceUInt8.write(value.nonce, into: &buf)
+        FfiConverterString.write(value.peerAId, into: &buf)
+        FfiConverterString.write(value.peerBId, into: &buf)
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeConfirmationTranscript_lift(_ buf: RustBuffer) throws -> ConfirmationTranscript {
+    return try FfiConverterTypeConfirmationTranscript.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeConfirmationTranscript_lower(_ value: ConfirmationTranscript) -> RustBuffer {
+    return FfiConverterTypeConfirmationTranscript.lower(value)
+}
+
+
+public struct DeviceVisual {
+    public var colorHex: String
+    public var initials: String
+    public var identiconSeed: UInt64
+
+    // Default memberwise initializers are never public by default, so we
+    // declare one manually.
+    public init(colorHex: String, initials: String, identiconSeed: UInt64) {
+        self.colorHex = colorHex
+        self.initials = initials
+        self.identiconSeed = identiconSeed
+    }
+}
+
+#if compiler(>=6)
+extension DeviceVisual: Sendable {}
+#endif
+
+
+extension DeviceVisual: Equatable, Hashable {
+    public static func ==(lhs: DeviceVisual, rhs: DeviceVisual) -> Bool {
+        if lhs.colorHex != rhs.colorHex {
+            return false
+        }
+        if lhs.initials != rhs.initials {
+            return false
+        }
+        if lhs.identiconSeed != rhs.identiconSeed {
+            return false
+        }
+        return true
+    }
+
+    public func hash(into hasher: inout Hasher) {
+        hasher.combine(colorHex)
+        hasher.combine(initials)
+        hasher.combine(identiconSeed)
+    }
+}
+
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeDeviceVisual: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> DeviceVisual {
+        return
+            try DeviceVisual(
+                colorHex: FfiConverterString.read(from: &buf), 
+                initials: FfiConverterString.read(from: &buf), 
+                identiconSeed: FfiConverterUInt64.read(from: &buf)
+        )
+    }
+
+    public static func write(_ value: DeviceVisual, into buf: inout [UInt8]) {
+        FfiConverterString.write(value.colorHex, into: &buf)
+        FfiConverterString.write(value.initials, into: &buf)
+        FfiConverterUInt64.write(value.identiconSeed, into: &buf)
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeDeviceVisual_lift(_ buf: RustBuffer) throws -> DeviceVisual {
+    return try FfiConverterTypeDeviceVisual.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeDeviceVisual_lower(_ value: DeviceVisual) -> RustBuffer {
+    return FfiConverterTypeDeviceVisual.lower(value)
+}
+
+
+public struct ErrorEntry {
+    public var timestampMs: UInt64
+    public var peerId: String?
+    public var category: String
+    public var message: String
+
+    // Default memberwise initializers are never public by default, so we
+    // declare one manually.
+    public init(timestampMs: UInt64, peerId: String?, category: String, message: String) {
+        self.timestampMs = timestampMs
+        self.peerId = peerId
+        self.category = category
+        self.message = message
+    }
+}
+
+#if compiler(>=6)
+extension ErrorEntry: Sendable {}
+#endif
+
+
+extension ErrorEntry: Equatable, Hashable {
+    public static func ==(lhs: ErrorEntry, rhs: ErrorEntry) -> Bool {
+        if lhs.timestampMs != rhs.timestampMs {
+            return false
+        }
+        if lhs.peerId != rhs.peerId {
+            return false
+        }
+        if lhs.category != rhs.category {
+            return false
+        }
+        if lhs.message != rhs.message {
+            return false
+        }
+        return true
+    }
+
+    public func hash(into hasher: inout Hasher) {
+        hasher.combine(timestampMs)
+        hasher.combine(peerId)
+        hasher.combine(category)
+        hasher.combine(message)
+    }
+}
+
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeErrorEntry: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> ErrorEntry {
+        return
+            try ErrorEntry(
+                timestampMs: FfiConverterUInt64.read(from: &buf), 
+                peerId: FfiConverterOptionString.read(from: &buf), 
+                category: FfiConverterString.read(from: &buf), 
+                message: FfiConverterString.read(from: &buf)
+        )
+    }
+
+    public static func write(_ value: ErrorEntry, into buf: inout [UInt8]) {
+        FfiConverterUInt64.write(value.timestampMs, into: &buf)
+        FfiConverterOptionString.write(value.peerId, into: &buf)
+        FfiConverterString.write(value.category, into: &buf)
+        FfiConverterString.write(value.message, into: &buf)
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeErrorEntry_lift(_ buf: RustBuffer) throws -> ErrorEntry {
+    return try FfiConverterTypeErrorEntry.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeErrorEntry_lower(_ value: ErrorEntry) -> RustBuffer {
+    return FfiConverterTypeErrorEntry.lower(value)
+}
+
+
+public struct IdentityInfo {
+    public var peerId: String
+    public var pubkeyB64: String
+
+    // Default memberwise initializers are never public by default, so we
+    // declare one manually.
+    public init(peerId: String, pubkeyB64: String) {
+        self.peerId = peerId
+        self.pubkeyB64 = pubkeyB64
+    }
+}
+
+#if compiler(>=6)
+extension IdentityInfo: Sendable {}
+#endif
+
+
+extension IdentityInfo: Equatable, Hashable {
+    public static func ==(lhs: IdentityInfo, rhs: IdentityInfo) -> Bool {
+        if lhs.peerId != rhs.peerId {
+            return false
+        }
+        if lhs.pubkeyB64 != rhs.pubkeyB64 {
+            return false
+        }
+        return true
+    }
+
+    public func hash(into hasher: inout Hasher) {
+        hasher.combine(peerId)
+        hasher.combine(pubkeyB64)
+    }
+}
+
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeIdentityInfo: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> IdentityInfo {
+        return
+            try IdentityInfo(
+                peerId: FfiConverterString.read(from: &buf), 
+                pubkeyB64: FfiConverterString.read(from: &buf)
+        )
+    }
+
+    public static func write(_ value: IdentityInfo, into buf: inout [UInt8]) {
+        FfiConverterString.write(value.peerId, into: &buf)
+        FfiConverterString.write(value.pubkeyB64, into: &buf)
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeIdentityInfo_lift(_ buf: RustBuffer) throws -> IdentityInfo {
+    return try FfiConverterTypeIdentityInfo.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeIdentityInfo_lower(_ value: IdentityInfo) -> RustBuffer {
+    return FfiConverterTypeIdentityInfo.lower(value)
+}
+
+
+public struct NodeSnapshot {
+    public var connected: [String]
+    public var peers: [PeerEntry]
+    public var history: [ClipboardHistoryEntry]
+    public var historyLen: UInt64
+    public var historyBytes: UInt64
+    public var pendingRequests: [PendingRequest]
+    public var suspended: [String]
+
+    // Default memberwise initializers are never public by default, so we
+    // declare one manually.
+    public init(connected: [String], peers: [PeerEntry], history: [ClipboardHistoryEntry], historyLen: UInt64, historyBytes: UInt64, pendingRequests: [PendingRequest], suspended: [String]) {
+        self.connected = connected
+        self.peers = peers
+        self.history = history
+        self.historyLen = historyLen
+        self.historyBytes = historyBytes
+        self.pendingRequests = pendingRequests
+        self.suspended = suspended
+    }
+}
+
+#if compiler(>=6)
+extension NodeSnapshot: Sendable {}
+#endif
+
+
+extension NodeSnapshot: Equatable, Hashable {
+    public static func ==(lhs: NodeSnapshot, rhs: NodeSnapshot) -> Bool {
+        if lhs.connected != rhs.connected {
+            return false
+        }
+        if lhs.peers != rhs.peers {
+            return false
+        }
+        if lhs.history != rhs.history {
+            return false
+        }
+        if lhs.historyLen != rhs.historyLen {
+            return false
+        }
+        if lhs.historyBytes != rhs.historyBytes {
+            return false
+        }
+        if lhs.pendingRequests != rhs.pendingRequests {
+            return false
+        }
+        if lhs.suspended != rhs.suspended {
+            return false
+        }
+        return true
+    }
+
+    public func hash(into hasher: inout Hasher) {
+        hasher.combine(connected)
+        hasher.combine(peers)
+        hasher.combine(history)
+        hasher.combine(historyLen)
+        hasher.combine(historyBytes)
+        hasher.combine(pendingRequests)
+        hasher.combine(suspended)
+    }
+}
+
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeNodeSnapshot: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> NodeSnapshot {
+        return
+            try NodeSnapshot(
+                connected: FfiConverterSequenceString.read(from: &buf), 
+                peers: FfiConverterSequenceTypePeerEntry.read(from: &buf), 
+                history: FfiConverterSequenceTypeClipboardHistoryEntry.read(from: &buf), 
+                historyLen: FfiConverterUInt64.read(from: &buf), 
+                historyBytes: FfiConverterUInt64.read(from: &buf), 
+                pendingRequests: FfiConverterSequenceTypePendingRequest.read(from: &buf), 
+                suspended: FfiConverterSequenceString.read(from: &buf)
+        )
+    }
+
+    public static func write(_ value: NodeSnapshot, into buf: inout [UInt8]) {
+        FfiConverterSequenceString.write(value.connected, into: &buf)
+        FfiConverterSequenceTypePeerEntry.write(value.peers, into: &buf)
+        FfiConverterSequenceTypeClipboardHistoryEntry.write(value.history, into: &buf)
+        FfiConverterUInt64.write(value.historyLen, into: &buf)
+        FfiConverterUInt64.write(value.historyBytes, into: &buf)
+        FfiConverterSequenceTypePendingRequest.write(value.pendingRequests, into: &buf)
+        FfiConverterSequenceString.write(value.suspended, into: &buf)
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeNodeSnapshot_lift(_ buf: RustBuffer) throws -> NodeSnapshot {
+    return try FfiConverterTypeNodeSnapshot.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeNodeSnapshot_lower(_ value: NodeSnapshot) -> RustBuffer {
+    return FfiConverterTypeNodeSnapshot.lower(value)
+}
+
+
+public struct PeerClipSummary {
+    public var peerId: String
+    public var count: UInt32
+    public var lastTimestamp: UInt64
+
+    // Default memberwise initializers are never public by default, so we
+    // declare one manually.
+    public init(peerId: String, count: UInt32, lastTimestamp: UInt64) {
+        self.peerId = peerId
+        self.count = count
+        self.lastTimestamp = lastTimestamp
+    }
+}
+
+#if compiler(>=6)
+extension PeerClipSummary: Sendable {}
+#endif
+
+
+extension PeerClipSummary: Equatable, Hashable {
+    public static func ==(lhs: PeerClipSummary, rhs: PeerClipSummary) -> Bool {
+        if lhs.peerId != rhs.peerId {
+            return false
+        }
+        if lhs.count != rhs.count {
+            return false
+        }
+        if lhs.lastTimestamp != rhs.lastTimestamp {
+            return false
+        }
+        return true
+    }
+
+    public func hash(into hasher: inout Hasher) {
+        hasher.combine(peerId)
+        hasher.combine(count)
+        hasher.combine(lastTimestamp)
+    }
+}
+
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypePeerClipSummary: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> PeerClipSummary {
+        return
+            try PeerClipSummary(
+                peerId: FfiConverterString.read(from: &buf), 
+                count: FfiConverterUInt32.read(from: &buf), 
+                lastTimestamp: FfiConverterUInt64.read(from: &buf)
+        )
+    }
+
+    public static func write(_ value: PeerClipSummary, into buf: inout [UInt8]) {
+        FfiConverterString.write(value.peerId, into: &buf)
+        FfiConverterUInt32.write(value.count, into: &buf)
+        FfiConverterUInt64.write(value.lastTimestamp, into: &buf)
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypePeerClipSummary_lift(_ buf: RustBuffer) throws -> PeerClipSummary {
+    return try FfiConverterTypePeerClipSummary.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypePeerClipSummary_lower(_ value: PeerClipSummary) -> RustBuffer {
+    return FfiConverterTypePeerClipSummary.lower(value)
+}
+
+
+public struct PeerEntry {
+    public var peerId: String
+    public var displayName: String
+    public var label: String
+    public var online: Bool
+    public var lastAddr: String?
+    public var addresses: [String]
+    public var deviceName: String?
+
+    // Default memberwise initializers are never public by default, so we
+    // declare one manually.
+    public init(peerId: String, displayName: String, label: String, online: Bool, lastAddr: String?, addresses: [String], deviceName: String?) {
+        self.peerId = peerId
+        self.displayName = displayName
+        self.label = label
+        self.online = online
+        self.lastAddr = lastAddr
+        self.addresses = addresses
+        self.deviceName = deviceName
+    }
+}
+
+#if compiler(>=6)
+extension PeerEntry: Sendable {}
+#endif
+
+
+extension PeerEntry: Equatable, Hashable {
+    public static func ==(lhs: PeerEntry, rhs: PeerEntry) -> Bool {
+        if lhs.peerId != rhs.peerId {
+            return false
+        }
+        if lhs.displayName != rhs.displayName {
+            return false
+        }
+        if lhs.label != rhs.label {
+            return false
+        }
+        if lhs.online != rhs.online {
+            return false
+        }
+        if lhs.lastAddr != rhs.lastAddr {
+            return false
+        }
+        if lhs.addresses != rhs.addresses {
+            return false
+        }
+        if lhs.deviceName != rhs.deviceName {
+            return false
+        }
+        return true
+    }
+
+    public func hash(into hasher: inout Hasher) {
+        hasher.combine(peerId)
+        hasher.combine(displayName)
+        hasher.combine(label)
+        hasher.combine(online)
+        hasher.combine(lastAddr)
+        hasher.combine(addresses)
+        hasher.combine(deviceName)
+    }
+}
+
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypePeerEntry: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> PeerEntry {
+        return
+            try PeerEntry(
+                peerId: FfiConverterString.read(from: &buf), 
+                displayName: FfiConverterString.read(from: &buf), 
+                label: FfiConverterString.read(from: &buf), 
+                online: FfiConverterBool.read(from: &buf), 
+                lastAddr: FfiConverterOptionString.read(from: &buf), 
+                addresses: FfiConverterSequenceString.read(from: &buf), 
+                deviceName: FfiConverterOptionString.read(from: &buf)
+        )
+    }
+
+    public static func write(_ value: PeerEntry, into buf: inout [UInt8]) {
+        FfiConverterString.write(value.peerId, into: &buf)
+        FfiConverterString.write(value.displayName, into: &buf)
+        FfiConverterString.write(value.label, into: &buf)
+        FfiConverterBool.write(value.online, into: &buf)
+        FfiConverterOptionString.write(value.lastAddr, into: &buf)
+        FfiConverterSequenceString.write(value.addresses, into: &buf)
+        FfiConverterOptionString.write(value.deviceName, into: &buf)
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypePeerEntry_lift(_ buf: RustBuffer) throws -> PeerEntry {
+    return try FfiConverterTypePeerEntry.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypePeerEntry_lower(_ value: PeerEntry) -> RustBuffer {
+    return FfiConverterTypePeerEntry.lower(value)
+}
+
+
+public struct PendingRequest {
+    public var peerId: String
+    public var fingerprint: String
+    public var identityPkB64: String
+    public var addr: String?
+    public var timestampMs: UInt64
+
+    // Default memberwise initializers are never public by default, so we
+    // declare one manually.
+    public init(peerId: String, fingerprint: String, identityPkB64: String, addr: String?, timestampMs: UInt64) {
+        self.peerId = peerId
+        self.fingerprint = fingerprint
+        self.identityPkB64 = identityPkB64
+        self.addr = addr
+        self.timestampMs = timestampMs
+    }
+}
+
+#if compiler(>=6)
+extension PendingRequest: Sendable {}
+#endif
+
+
+extension PendingRequest: Equatable, Hashable {
+    public static func ==(lhs: PendingRequest, rhs: PendingRequest) -> Bool {
+        if lhs.peerId != rhs.peerId {
+            return false
+        }
+        if lhs.fingerprint != rhs.fingerprint {
+            return false
+        }
+        if lhs.identityPkB64 != rhs.identityPkB64 {
+            return false
+        }
+        if lhs.addr != rhs.addr {
+            return false
+        }
+        if lhs.timestampMs != rhs.timestampMs {
+            return false
+        }
+        return true
+    }
+
+    public func hash(into hasher: inout Hasher) {
+        hasher.combine(peerId)
+        hasher.combine(fingerprint)
+        hasher.combine(identityPkB64)
+        hasher.combine(addr)
+        hasher.combine(timestampMs)
+    }
+}
+
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypePendingRequest: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> PendingRequest {
+        return
+            try PendingRequest(
+                peerId: FfiConverterString.read(from: &buf), 
+                fingerprint: FfiConverterString.read(from: &buf), 
+                identityPkB64: FfiConverterString.read(from: &buf), 
+                addr: FfiConverterOptionString.read(from: &buf), 
+                timestampMs: FfiConverterUInt64.read(from: &buf)
+        )
     }
 
-    public static func write(_ value: TrustStore, into buf: inout [UInt8]) {
-        // This fiddling is because `Int` is the thing that's the same size as a pointer.
-        // The Rust code won't compile if a pointer won't fit in a `UInt64`.
-        writeInt(&buf, UInt64(bitPattern: Int64(Int(bitPattern: lower(value)))))
+    public static func write(_ value: PendingRequest, into buf: inout [UInt8]) {
+        FfiConverterString.write(value.peerId, into: &buf)
+        FfiConverterString.write(value.fingerprint, into: &buf)
+        FfiConverterString.write(value.identityPkB64, into: &buf)
+        FfiConverterOptionString.write(value.addr, into: &buf)
+        FfiConverterUInt64.write(value.timestampMs, into: &buf)
     }
 }
 
@@ -1282,63 +2747,73 @@ public struct FfiConverterTypeTrustStore: FfiConverter {
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public func FfiConverterTypeTrustStore_lift(_ pointer: UnsafeMutableRawPointer) throws -> TrustStore {
-    return try FfiConverterTypeTrustStore.lift(pointer)
+public func FfiConverterTypePendingRequest_lift(_ buf: RustBuffer) throws -> PendingRequest {
+    return try FfiConverterTypePendingRequest.lift(buf)
 }
 
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public func FfiConverterTypeTrustStore_lower(_ value: TrustStore) -> UnsafeMutableRawPointer {
-    return FfiConverterTypeTrustStore.lower(value)
+public func FfiConverterTypePendingRequest_lower(_ value: PendingRequest) -> RustBuffer {
+    return FfiConverterTypePendingRequest.lower(value)
 }
 
 
-
-
-public struct ClipboardHistoryEntry {
-    public var id: String
-    public var content: String
-    public var sourcePeer: String
-    public var timestamp: UInt64
+public struct ReceivedFileEntry {
+    public var peerId: String
+    public var name: String
+    public var size: UInt64
+    public var hash: String
+    public var path: String
+    public var receivedAtMs: UInt64
 
     // Default memberwise initializers are never public by default, so we
     // declare one manually.
-    public init(id: String, content: String, sourcePeer: String, timestamp: UInt64) {
-        self.id = id
-        self.content = content
-        self.sourcePeer = sourcePeer
-        self.timestamp = timestamp
+    public init(peerId: String, name: String, size: UInt64, hash: String, path: String, receivedAtMs: UInt64) {
+        self.peerId = peerId
+        self.name = name
+        self.size = size
+        self.hash = hash
+        self.path = path
+        self.receivedAtMs = receivedAtMs
     }
 }
 
 #if compiler(>=6)
-extension ClipboardHistoryEntry: Sendable {}
+extension ReceivedFileEntry: Sendable {}
 #endif
 
 
-extension ClipboardHistoryEntry: Equatable, Hashable {
-    public static func ==(lhs: ClipboardHistoryEntry, rhs: ClipboardHistoryEntry) -> Bool {
-        if lhs.id != rhs.id {
+extension ReceivedFileEntry: Equatable, Hashable {
+    public static func ==(lhs: ReceivedFileEntry, rhs: ReceivedFileEntry) -> Bool {
+        if lhs.peerId != rhs.peerId {
             return false
         }
-        if lhs.content != rhs.content {
+        if lhs.name != rhs.name {
             return false
         }
-        if lhs.sourcePeer != rhs.sourcePeer {
+        if lhs.size != rhs.size {
             return false
         }
-        if lhs.timestamp != rhs.timestamp {
+        if lhs.hash != rhs.hash {
+            return false
+        }
+        if lhs.path != rhs.path {
+            return false
+        }
+        if lhs.receivedAtMs != rhs.receivedAtMs {
             return false
         }
         return true
     }
 
     public func hash(into hasher: inout Hasher) {
-        hasher.combine(id)
-        hasher.combine(content)
-        hasher.combine(sourcePeer)
-        hasher.combine(timestamp)
+        hasher.combine(peerId)
+        hasher.combine(name)
+        hasher.combine(size)
+        hasher.combine(hash)
+        hasher.combine(path)
+        hasher.combine(receivedAtMs)
     }
 }
 
@@ -1347,22 +2822,26 @@ extension ClipboardHistoryEntry: Equatable, Hashable {
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public struct FfiConverterTypeClipboardHistoryEntry: FfiConverterRustBuffer {
-    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> ClipboardHistoryEntry {
+public struct FfiConverterTypeReceivedFileEntry: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> ReceivedFileEntry {
         return
-            try ClipboardHistoryEntry(
-                id: FfiConverterString.read(from: &buf), 
-                content: FfiConverterString.read(from: &buf), 
-                sourcePeer: FfiConverterString.read(from: &buf), 
-                timestamp: FfiConverterUInt64.read(from: &buf)
+            try ReceivedFileEntry(
+                peerId: FfiConverterString.read(from: &buf), 
+                name: FfiConverterString.read(from: &buf), 
+                size: FfiConverterUInt64.read(from: &buf), 
+                hash: FfiConverterString.read(from: &buf), 
+                path: FfiConverterString.read(from: &buf), 
+                receivedAtMs: FfiConverterUInt64.read(from: &buf)
         )
     }
 
-    public static func write(_ value: ClipboardHistoryEntry, into buf: inout [UInt8]) {
-        FfiConverterString.write(value.id, into: &buf)
-        FfiConverterString.write(value.content, into: &buf)
-        FfiConverterString.write(value.sourcePeer, into: &buf)
-        FfiConverterUInt64.write(value.timestamp, into: &buf)
+    public static func write(_ value: ReceivedFileEntry, into buf: inout [UInt8]) {
+        FfiConverterString.write(value.peerId, into: &buf)
+        FfiConverterString.write(value.name, into: &buf)
+        FfiConverterUInt64.write(value.size, into: &buf)
+        FfiConverterString.write(value.hash, into: &buf)
+        FfiConverterString.write(value.path, into: &buf)
+        FfiConverterUInt64.write(value.receivedAtMs, into: &buf)
     }
 }
 
@@ -1370,41 +2849,56 @@ public struct FfiConverterTypeClipboardHistoryEntry: FfiConverterRustBuffer {
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public func FfiConverterTypeClipboardHistoryEntry_lift(_ buf: RustBuffer) throws -> ClipboardHistoryEntry {
-    return try FfiConverterTypeClipboardHistoryEntry.lift(buf)
+public func FfiConverterTypeReceivedFileEntry_lift(_ buf: RustBuffer) throws -> ReceivedFileEntry {
+    return try FfiConverterTypeReceivedFileEntry.lift(buf)
 }
 
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public func FfiConverterTypeClipboardHistoryEntry_lower(_ value: ClipboardHistoryEntry) -> RustBuffer {
-    return FfiConverterTypeClipboardHistoryEntry.lower(value)
+public func FfiConverterTypeReceivedFileEntry_lower(_ value: ReceivedFileEntry) -> RustBuffer {
+    return FfiConverterTypeReceivedFileEntry.lower(value)
 }
 
 
-public struct IdentityInfo {
+public struct SessionInfo {
     public var peerId: String
-    public var pubkeyB64: String
+    public var addr: String?
+    public var connectedAtMs: UInt64
+    public var encrypted: Bool
+    public var outbound: Bool
 
     // Default memberwise initializers are never public by default, so we
     // declare one manually.
-    public init(peerId: String, pubkeyB64: String) {
+    public init(peerId: String, addr: String?, connectedAtMs: UInt64, encrypted: Bool, outbound: Bool) {
         self.peerId = peerId
-        self.pubkeyB64 = pubkeyB64
+        self.addr = addr
+        self.connectedAtMs = connectedAtMs
+        self.encrypted = encrypted
+        self.outbound = outbound
     }
 }
 
 #if compiler(>=6)
-extension IdentityInfo: Sendable {}
+extension SessionInfo: Sendable {}
 #endif
 
 
-extension IdentityInfo: Equatable, Hashable {
-    public static func ==(lhs: IdentityInfo, rhs: IdentityInfo) -> Bool {
+extension SessionInfo: Equatable, Hashable {
+    public static func ==(lhs: SessionInfo, rhs: SessionInfo) -> Bool {
         if lhs.peerId != rhs.peerId {
             return false
         }
-        if lhs.pubkeyB64 != rhs.pubkeyB64 {
+        if lhs.addr != rhs.addr {
+            return false
+        }
+        if lhs.connectedAtMs != rhs.connectedAtMs {
+            return false
+        }
+        if lhs.encrypted != rhs.encrypted {
+            return false
+        }
+        if lhs.outbound != rhs.outbound {
             return false
         }
         return true
@@ -1412,7 +2906,10 @@ extension IdentityInfo: Equatable, Hashable {
 
     public func hash(into hasher: inout Hasher) {
         hasher.combine(peerId)
-        hasher.combine(pubkeyB64)
+        hasher.combine(addr)
+        hasher.combine(connectedAtMs)
+        hasher.combine(encrypted)
+        hasher.combine(outbound)
     }
 }
 
@@ -1421,18 +2918,24 @@ extension IdentityInfo: Equatable, Hashable {
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public struct FfiConverterTypeIdentityInfo: FfiConverterRustBuffer {
-    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> IdentityInfo {
+public struct FfiConverterTypeSessionInfo: FfiConverterRustBuffer {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SessionInfo {
         return
-            try IdentityInfo(
+            try SessionInfo(
                 peerId: FfiConverterString.read(from: &buf), 
-                pubkeyB64: FfiConverterString.read(from: &buf)
+                addr: FfiConverterOptionString.read(from: &buf), 
+                connectedAtMs: FfiConverterUInt64.read(from: &buf), 
+                encrypted: FfiConverterBool.read(from: &buf), 
+                outbound: FfiConverterBool.read(from: &buf)
         )
     }
 
-    public static func write(_ value: IdentityInfo, into buf: inout [UInt8]) {
+    public static func write(_ value: SessionInfo, into buf: inout [UInt8]) {
         FfiConverterString.write(value.peerId, into: &buf)
-        FfiConverterString.write(value.pubkeyB64, into: &buf)
+        FfiConverterOptionString.write(value.addr, into: &buf)
+        FfiConverterUInt64.write(value.connectedAtMs, into: &buf)
+        FfiConverterBool.write(value.encrypted, into: &buf)
+        FfiConverterBool.write(value.outbound, into: &buf)
     }
 }
 
@@ -1440,15 +2943,15 @@ public struct FfiConverterTypeIdentityInfo: FfiConverterRustBuffer {
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public func FfiConverterTypeIdentityInfo_lift(_ buf: RustBuffer) throws -> IdentityInfo {
-    return try FfiConverterTypeIdentityInfo.lift(buf)
+public func FfiConverterTypeSessionInfo_lift(_ buf: RustBuffer) throws -> SessionInfo {
+    return try FfiConverterTypeSessionInfo.lift(buf)
 }
 
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public func FfiConverterTypeIdentityInfo_lower(_ value: IdentityInfo) -> RustBuffer {
-    return FfiConverterTypeIdentityInfo.lower(value)
+public func FfiConverterTypeSessionInfo_lower(_ value: SessionInfo) -> RustBuffer {
+    return FfiConverterTypeSessionInfo.lower(value)
 }
 
 
@@ -1457,14 +2960,16 @@ public struct TrustRecord {
     public var identityPkB64: String
     public var displayName: String
     public var createdAtMs: UInt64
+    public var syncDirection: SyncDirection
 
     // Default memberwise initializers are never public by default, so we
     // declare one manually.
-    public init(peerId: String, identityPkB64: String, displayName: String, createdAtMs: UInt64) {
+    public init(peerId: String, identityPkB64: String, displayName: String, createdAtMs: UInt64, syncDirection: SyncDirection) {
         self.peerId = peerId
         self.identityPkB64 = identityPkB64
         self.displayName = displayName
         self.createdAtMs = createdAtMs
+        self.syncDirection = syncDirection
     }
 }
 
@@ -1487,6 +2992,9 @@ extension TrustRecord: Equatable, Hashable {
         if lhs.createdAtMs != rhs.createdAtMs {
             return false
         }
+        if lhs.syncDirection != rhs.syncDirection {
+            return false
+        }
         return true
     }
 
@@ -1495,6 +3003,7 @@ extension TrustRecord: Equatable, Hashable {
         hasher.combine(identityPkB64)
         hasher.combine(displayName)
         hasher.combine(createdAtMs)
+        hasher.combine(syncDirection)
     }
 }
 
@@ -1510,7 +3019,8 @@ public struct FfiConverterTypeTrustRecord: FfiConverterRustBuffer {
                 peerId: FfiConverterString.read(from: &buf), 
                 identityPkB64: FfiConverterString.read(from: &buf), 
                 displayName: FfiConverterString.read(from: &buf), 
-                createdAtMs: FfiConverterUInt64.read(from: &buf)
+                createdAtMs: FfiConverterUInt64.read(from: &buf), 
+                syncDirection: FfiConverterTypeSyncDirection.read(from: &buf)
         )
     }
 
@@ -1519,6 +3029,7 @@ public struct FfiConverterTypeTrustRecord: FfiConverterRustBuffer {
         FfiConverterString.write(value.identityPkB64, into: &buf)
         FfiConverterString.write(value.displayName, into: &buf)
         FfiConverterUInt64.write(value.createdAtMs, into: &buf)
+        FfiConverterTypeSyncDirection.write(value.syncDirection, into: &buf)
     }
 }
 
@@ -1537,6 +3048,83 @@ public func FfiConverterTypeTrustRecord_lower(_ value: TrustRecord) -> RustBuffe
     return FfiConverterTypeTrustRecord.lower(value)
 }
 
+// Note that we don't yet support `indirect` for enums.
+// See https://github.com/mozilla/uniffi-rs/issues/396 for further discussion.
+
+public enum ClipboardHistoryKind {
+    
+    case text
+    case image
+    case file
+}
+
+
+#if compiler(>=6)
+extension ClipboardHistoryKind: Sendable {}
+#endif
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeClipboardHistoryKind: FfiConverterRustBuffer {
+    typealias SwiftType = ClipboardHistoryKind
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> ClipboardHistoryKind {
+        let variant: Int32 = try readInt(&buf)
+        switch variant {
+        
+        case 1: return .text
+        
+        case 2: return .image
+        
+        case 3: return .file
+        
+        default: throw UniffiInternalError.unexpectedEnumCase
+        }
+    }
+
+    public static func write(_ value: ClipboardHistoryKind, into buf: inout [UInt8]) {
+        switch value {
+        
+        
+        case .text:
+            writeInt(&buf, Int32(1))
+        
+        
+        case .image:
+            writeInt(&buf, Int32(2))
+        
+        
+        case .file:
+            writeInt(&buf, Int32(3))
+        
+        }
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeClipboardHistoryKind_lift(_ buf: RustBuffer) throws -> ClipboardHistoryKind {
+    return try FfiConverterTypeClipboardHistoryKind.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeClipboardHistoryKind_lower(_ value: ClipboardHistoryKind) -> RustBuffer {
+    return FfiConverterTypeClipboardHistoryKind.lower(value)
+}
+
+
+extension ClipboardHistoryKind: Equatable, Hashable {}
+
+
+
+
+
+
 
 public enum OpenClipboardError: Swift.Error {
 
@@ -1544,40 +3132,179 @@ public enum OpenClipboardError: Swift.Error {
     
     case Other(message: String)
     
+    case NotAnOpenClipboardCode(message: String)
+    
+    case NodeStopped(message: String)
+    
+    case Cancelled(message: String)
+    
+    case AddressInUse(message: String)
+    
+    case BindFailed(message: String)
+    
+    case FileRejected(message: String)
+    
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public struct FfiConverterTypeOpenClipboardError: FfiConverterRustBuffer {
+    typealias SwiftType = OpenClipboardError
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> OpenClipboardError {
+        let variant: Int32 = try readInt(&buf)
+        switch variant {
+
+        
+
+        
+        case 1: return .Other(
+            message: try FfiConverterString.read(from: &buf)
+        )
+        
+        case 2: return .NotAnOpenClipboardCode(
+            message: try FfiConverterString.read(from: &buf)
+        )
+        
+        case 3: return .NodeStopped(
+            message: try FfiConverterString.read(from: &buf)
+        )
+        
+        case 4: return .Cancelled(
+            message: try FfiConverterString.read(from: &buf)
+        )
+        
+        case 5: return .AddressInUse(
+            message: try FfiConverterString.read(from: &buf)
+        )
+        
+        case 6: return .BindFailed(
+            message: try FfiConverterString.read(from: &buf)
+        )
+        
+        case 7: return .FileRejected(
+            message: try FfiConverterString.read(from: &buf)
+        )
+        
+
+        default: throw UniffiInternalError.unexpectedEnumCase
+        }
+    }
+
+    public static func write(_ value: OpenClipboardError, into buf: inout [UInt8]) {
+        switch value {
+
+        
+
+        
+        case .Other(_ /* message is ignored*/):
+            writeInt(&buf, Int32(1))
+        case .NotAnOpenClipboardCode(_ /* message is ignored*/):
+            writeInt(&buf, Int32(2))
+        case .NodeStopped(_ /* message is ignored*/):
+            writeInt(&buf, Int32(3))
+        case .Cancelled(_ /* message is ignored*/):
+            writeInt(&buf, Int32(4))
+        case .AddressInUse(_ /* message is ignored*/):
+            writeInt(&buf, Int32(5))
+        case .BindFailed(_ /* message is ignored*/):
+            writeInt(&buf, Int32(6))
+        case .FileRejected(_ /* message is ignored*/):
+            writeInt(&buf, Int32(7))
+
+        
+        }
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeOpenClipboardError_lift(_ buf: RustBuffer) throws -> OpenClipboardError {
+    return try FfiConverterTypeOpenClipboardError.lift(buf)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterTypeOpenClipboardError_lower(_ value: OpenClipboardError) -> RustBuffer {
+    return FfiConverterTypeOpenClipboardError.lower(value)
+}
+
+
+extension OpenClipboardError: Equatable, Hashable {}
+
+
+
+
+extension OpenClipboardError: Foundation.LocalizedError {
+    public var errorDescription: String? {
+        String(reflecting: self)
+    }
+}
+
+
+
+
+// Note that we don't yet support `indirect` for enums.
+// See https://github.com/mozilla/uniffi-rs/issues/396 for further discussion.
+
+public enum SyncDirection {
+    
+    case bidirectional
+    case sendOnly
+    case receiveOnly
+    case off
 }
 
 
+#if compiler(>=6)
+extension SyncDirection: Sendable {}
+#endif
+
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public struct FfiConverterTypeOpenClipboardError: FfiConverterRustBuffer {
-    typealias SwiftType = OpenClipboardError
+public struct FfiConverterTypeSyncDirection: FfiConverterRustBuffer {
+    typealias SwiftType = SyncDirection
 
-    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> OpenClipboardError {
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SyncDirection {
         let variant: Int32 = try readInt(&buf)
         switch variant {
-
         
-
+        case 1: return .bidirectional
         
-        case 1: return .Other(
-            message: try FfiConverterString.read(from: &buf)
-        )
+        case 2: return .sendOnly
+        
+        case 3: return .receiveOnly
+        
+        case 4: return .off
         
-
         default: throw UniffiInternalError.unexpectedEnumCase
         }
     }
 
-    public static func write(_ value: OpenClipboardError, into buf: inout [UInt8]) {
+    public static func write(_ value: SyncDirection, into buf: inout [UInt8]) {
         switch value {
-
         
-
         
-        case .Other(_ /* message is ignored*/):
+        case .bidirectional:
             writeInt(&buf, Int32(1))
-
+        
+        
+        case .sendOnly:
+            writeInt(&buf, Int32(2))
+        
+        
+        case .receiveOnly:
+            writeInt(&buf, Int32(3))
+        
+        
+        case .off:
+            writeInt(&buf, Int32(4))
         
         }
     }
@@ -1587,28 +3314,21 @@ public struct FfiConverterTypeOpenClipboardError: FfiConverterRustBuffer {
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public func FfiConverterTypeOpenClipboardError_lift(_ buf: RustBuffer) throws -> OpenClipboardError {
-    return try FfiConverterTypeOpenClipboardError.lift(buf)
+public func FfiConverterTypeSyncDirection_lift(_ buf: RustBuffer) throws -> SyncDirection {
+    return try FfiConverterTypeSyncDirection.lift(buf)
 }
 
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public func FfiConverterTypeOpenClipboardError_lower(_ value: OpenClipboardError) -> RustBuffer {
-    return FfiConverterTypeOpenClipboardError.lower(value)
+public func FfiConverterTypeSyncDirection_lower(_ value: SyncDirection) -> RustBuffer {
+    return FfiConverterTypeSyncDirection.lower(value)
 }
 
 
-extension OpenClipboardError: Equatable, Hashable {}
-
-
+extension SyncDirection: Equatable, Hashable {}
 
 
-extension OpenClipboardError: Foundation.LocalizedError {
-    public var errorDescription: String? {
-        String(reflecting: self)
-    }
-}
 
 
 
@@ -1622,6 +3342,10 @@ public protocol ClipboardCallback: AnyObject, Sendable {
     
     func writeText(text: String) 
     
+    func readImage()  -> ClipboardImage?
+    
+    func writeImage(image: ClipboardImage) 
+    
 }
 
 
@@ -1673,6 +3397,52 @@ fileprivate struct UniffiCallbackInterfaceClipboardCallback {
             }
 
             
+            let writeReturn = { () }
+            uniffiTraitInterfaceCall(
+                callStatus: uniffiCallStatus,
+                makeCall: makeCall,
+                writeReturn: writeReturn
+            )
+        },
+        readImage: { (
+            uniffiHandle: UInt64,
+            uniffiOutReturn: UnsafeMutablePointer<RustBuffer>,
+            uniffiCallStatus: UnsafeMutablePointer<RustCallStatus>
+        ) in
+            let makeCall = {
+                () throws -> ClipboardImage? in
+                guard let uniffiObj = try? FfiConverterCallbackInterfaceClipboardCallback.handleMap.get(handle: uniffiHandle) else {
+                    throw UniffiInternalError.unexpectedStaleHandle
+                }
+                return uniffiObj.readImage(
+                )
+            }
+
+            
+            let writeReturn = { uniffiOutReturn.pointee = FfiConverterOptionTypeClipboardImage.lower($0) }
+            uniffiTraitInterfaceCall(
+                callStatus: uniffiCallStatus,
+                makeCall: makeCall,
+                writeReturn: writeReturn
+            )
+        },
+        writeImage: { (
+            uniffiHandle: UInt64,
+            image: RustBuffer,
+            uniffiOutReturn: UnsafeMutableRawPointer,
+            uniffiCallStatus: UnsafeMutablePointer<RustCallStatus>
+        ) in
+            let makeCall = {
+                () throws -> () in
+                guard let uniffiObj = try? FfiConverterCallbackInterfaceClipboardCallback.handleMap.get(handle: uniffiHandle) else {
+                    throw UniffiInternalError.unexpectedStaleHandle
+                }
+                return uniffiObj.writeImage(
+                     image: try FfiConverterTypeClipboardImage_lift(image)
+                )
+            }
+
+            
             let writeReturn = { () }
             uniffiTraitInterfaceCall(
                 callStatus: uniffiCallStatus,
@@ -1914,6 +3684,8 @@ public protocol EventHandler: AnyObject, Sendable {
     
     func onError(message: String) 
     
+    func onClipboardImage(peerId: String, mime: String, width: UInt32, height: UInt32, bytes: [UInt8], tsMs: UInt64) 
+    
 }
 
 
@@ -2047,6 +3819,40 @@ fileprivate struct UniffiCallbackInterfaceEventHandler {
             }
 
             
+            let writeReturn = { () }
+            uniffiTraitInterfaceCall(
+                callStatus: uniffiCallStatus,
+                makeCall: makeCall,
+                writeReturn: writeReturn
+            )
+        },
+        onClipboardImage: { (
+            uniffiHandle: UInt64,
+            peerId: RustBuffer,
+            mime: RustBuffer,
+            width: UInt32,
+            height: UInt32,
+            bytes: RustBuffer,
+            tsMs: UInt64,
+            uniffiOutReturn: UnsafeMutableRawPointer,
+            uniffiCallStatus: UnsafeMutablePointer<RustCallStatus>
+        ) in
+            let makeCall = {
+                () throws -> () in
+                guard let uniffiObj = try? FfiConverterCallbackInterfaceEventHandler.handleMap.get(handle: uniffiHandle) else {
+                    throw UniffiInternalError.unexpectedStaleHandle
+                }
+                return uniffiObj.onClipboardImage(
+                     peerId: try FfiConverterString.lift(peerId),
+                     mime: try FfiConverterString.lift(mime),
+                     width: try FfiConverterUInt32.lift(width),
+                     height: try FfiConverterUInt32.lift(height),
+                     bytes: try FfiConverterSequenceUInt8.lift(bytes),
+                     tsMs: try FfiConverterUInt64.lift(tsMs)
+                )
+            }
+
+            
             let writeReturn = { () }
             uniffiTraitInterfaceCall(
                 callStatus: uniffiCallStatus,
@@ -2112,26 +3918,216 @@ extension FfiConverterCallbackInterfaceEventHandler : FfiConverter {
     }
 }
 
-
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterCallbackInterfaceEventHandler_lift(_ handle: UInt64) throws -> EventHandler {
+    return try FfiConverterCallbackInterfaceEventHandler.lift(handle)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterCallbackInterfaceEventHandler_lower(_ v: EventHandler) -> UInt64 {
+    return FfiConverterCallbackInterfaceEventHandler.lower(v)
+}
+
+
+
+
+public protocol FileProgressHandler: AnyObject, Sendable {
+    
+    func onProgress(sent: UInt64, total: UInt64) 
+    
+}
+
+
+// Put the implementation in a struct so we don't pollute the top-level namespace
+fileprivate struct UniffiCallbackInterfaceFileProgressHandler {
+
+    // Create the VTable using a series of closures.
+    // Swift automatically converts these into C callback functions.
+    //
+    // This creates 1-element array, since this seems to be the only way to construct a const
+    // pointer that we can pass to the Rust code.
+    static let vtable: [UniffiVTableCallbackInterfaceFileProgressHandler] = [UniffiVTableCallbackInterfaceFileProgressHandler(
+        onProgress: { (
+            uniffiHandle: UInt64,
+            sent: UInt64,
+            total: UInt64,
+            uniffiOutReturn: UnsafeMutableRawPointer,
+            uniffiCallStatus: UnsafeMutablePointer<RustCallStatus>
+        ) in
+            let makeCall = {
+                () throws -> () in
+                guard let uniffiObj = try? FfiConverterCallbackInterfaceFileProgressHandler.handleMap.get(handle: uniffiHandle) else {
+                    throw UniffiInternalError.unexpectedStaleHandle
+                }
+                return uniffiObj.onProgress(
+                     sent: try FfiConverterUInt64.lift(sent),
+                     total: try FfiConverterUInt64.lift(total)
+                )
+            }
+
+            
+            let writeReturn = { () }
+            uniffiTraitInterfaceCall(
+                callStatus: uniffiCallStatus,
+                makeCall: makeCall,
+                writeReturn: writeReturn
+            )
+        },
+        uniffiFree: { (uniffiHandle: UInt64) -> () in
+            let result = try? FfiConverterCallbackInterfaceFileProgressHandler.handleMap.remove(handle: uniffiHandle)
+            if result == nil {
+                print("Uniffi callback interface FileProgressHandler: handle missing in uniffiFree")
+            }
+        }
+    )]
+}
+
+private func uniffiCallbackInitFileProgressHandler() {
+    uniffi_openclipboard_ffi_fn_init_callback_vtable_fileprogresshandler(UniffiCallbackInterfaceFileProgressHandler.vtable)
+}
+
+// FfiConverter protocol for callback interfaces
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterCallbackInterfaceFileProgressHandler {
+    fileprivate static let handleMap = UniffiHandleMap<FileProgressHandler>()
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+extension FfiConverterCallbackInterfaceFileProgressHandler : FfiConverter {
+    typealias SwiftType = FileProgressHandler
+    typealias FfiType = UInt64
+
+#if swift(>=5.8)
+    @_documentation(visibility: private)
+#endif
+    public static func lift(_ handle: UInt64) throws -> SwiftType {
+        try handleMap.get(handle: handle)
+    }
+
+#if swift(>=5.8)
+    @_documentation(visibility: private)
+#endif
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SwiftType {
+        let handle: UInt64 = try readInt(&buf)
+        return try lift(handle)
+    }
+
+#if swift(>=5.8)
+    @_documentation(visibility: private)
+#endif
+    public static func lower(_ v: SwiftType) -> UInt64 {
+        return handleMap.insert(obj: v)
+    }
+
+#if swift(>=5.8)
+    @_documentation(visibility: private)
+#endif
+    public static func write(_ v: SwiftType, into buf: inout [UInt8]) {
+        writeInt(&buf, lower(v))
+    }
+}
+
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterCallbackInterfaceFileProgressHandler_lift(_ handle: UInt64) throws -> FileProgressHandler {
+    return try FfiConverterCallbackInterfaceFileProgressHandler.lift(handle)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+public func FfiConverterCallbackInterfaceFileProgressHandler_lower(_ v: FileProgressHandler) -> UInt64 {
+    return FfiConverterCallbackInterfaceFileProgressHandler.lower(v)
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterOptionUInt64: FfiConverterRustBuffer {
+    typealias SwiftType = UInt64?
+
+    public static func write(_ value: SwiftType, into buf: inout [UInt8]) {
+        guard let value = value else {
+            writeInt(&buf, Int8(0))
+            return
+        }
+        writeInt(&buf, Int8(1))
+        FfiConverterUInt64.write(value, into: &buf)
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SwiftType {
+        switch try readInt(&buf) as Int8 {
+        case 0: return nil
+        case 1: return try FfiConverterUInt64.read(from: &buf)
+        default: throw UniffiInternalError.unexpectedOptionalTag
+        }
+    }
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterOptionString: FfiConverterRustBuffer {
+    typealias SwiftType = String?
+
+    public static func write(_ value: SwiftType, into buf: inout [UInt8]) {
+        guard let value = value else {
+            writeInt(&buf, Int8(0))
+            return
+        }
+        writeInt(&buf, Int8(1))
+        FfiConverterString.write(value, into: &buf)
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SwiftType {
+        switch try readInt(&buf) as Int8 {
+        case 0: return nil
+        case 1: return try FfiConverterString.read(from: &buf)
+        default: throw UniffiInternalError.unexpectedOptionalTag
+        }
+    }
+}
+
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-public func FfiConverterCallbackInterfaceEventHandler_lift(_ handle: UInt64) throws -> EventHandler {
-    return try FfiConverterCallbackInterfaceEventHandler.lift(handle)
-}
+fileprivate struct FfiConverterOptionTypeCancellationToken: FfiConverterRustBuffer {
+    typealias SwiftType = CancellationToken?
 
-#if swift(>=5.8)
-@_documentation(visibility: private)
-#endif
-public func FfiConverterCallbackInterfaceEventHandler_lower(_ v: EventHandler) -> UInt64 {
-    return FfiConverterCallbackInterfaceEventHandler.lower(v)
+    public static func write(_ value: SwiftType, into buf: inout [UInt8]) {
+        guard let value = value else {
+            writeInt(&buf, Int8(0))
+            return
+        }
+        writeInt(&buf, Int8(1))
+        FfiConverterTypeCancellationToken.write(value, into: &buf)
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SwiftType {
+        switch try readInt(&buf) as Int8 {
+        case 0: return nil
+        case 1: return try FfiConverterTypeCancellationToken.read(from: &buf)
+        default: throw UniffiInternalError.unexpectedOptionalTag
+        }
+    }
 }
 
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
-fileprivate struct FfiConverterOptionString: FfiConverterRustBuffer {
-    typealias SwiftType = String?
+fileprivate struct FfiConverterOptionTypeClipboardImage: FfiConverterRustBuffer {
+    typealias SwiftType = ClipboardImage?
 
     public static func write(_ value: SwiftType, into buf: inout [UInt8]) {
         guard let value = value else {
@@ -2139,13 +4135,13 @@ fileprivate struct FfiConverterOptionString: FfiConverterRustBuffer {
             return
         }
         writeInt(&buf, Int8(1))
-        FfiConverterString.write(value, into: &buf)
+        FfiConverterTypeClipboardImage.write(value, into: &buf)
     }
 
     public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> SwiftType {
         switch try readInt(&buf) as Int8 {
         case 0: return nil
-        case 1: return try FfiConverterString.read(from: &buf)
+        case 1: return try FfiConverterTypeClipboardImage.read(from: &buf)
         default: throw UniffiInternalError.unexpectedOptionalTag
         }
     }
@@ -2250,6 +4246,156 @@ fileprivate struct FfiConverterSequenceTypeClipboardHistoryEntry: FfiConverterRu
     }
 }
 
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterSequenceTypeErrorEntry: FfiConverterRustBuffer {
+    typealias SwiftType = [ErrorEntry]
+
+    public static func write(_ value: [ErrorEntry], into buf: inout [UInt8]) {
+        let len = Int32(value.count)
+        writeInt(&buf, len)
+        for item in value {
+            FfiConverterTypeErrorEntry.write(item, into: &buf)
+        }
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> [ErrorEntry] {
+        let len: Int32 = try readInt(&buf)
+        var seq = [ErrorEntry]()
+        seq.reserveCapacity(Int(len))
+        for _ in 0 ..< len {
+            seq.append(try FfiConverterTypeErrorEntry.read(from: &buf))
+        }
+        return seq
+    }
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterSequenceTypePeerClipSummary: FfiConverterRustBuffer {
+    typealias SwiftType = [PeerClipSummary]
+
+    public static func write(_ value: [PeerClipSummary], into buf: inout [UInt8]) {
+        let len = Int32(value.count)
+        writeInt(&buf, len)
+        for item in value {
+            FfiConverterTypePeerClipSummary.write(item, into: &buf)
+        }
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> [PeerClipSummary] {
+        let len: Int32 = try readInt(&buf)
+        var seq = [PeerClipSummary]()
+        seq.reserveCapacity(Int(len))
+        for _ in 0 ..< len {
+            seq.append(try FfiConverterTypePeerClipSummary.read(from: &buf))
+        }
+        return seq
+    }
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterSequenceTypePeerEntry: FfiConverterRustBuffer {
+    typealias SwiftType = [PeerEntry]
+
+    public static func write(_ value: [PeerEntry], into buf: inout [UInt8]) {
+        let len = Int32(value.count)
+        writeInt(&buf, len)
+        for item in value {
+            FfiConverterTypePeerEntry.write(item, into: &buf)
+        }
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> [PeerEntry] {
+        let len: Int32 = try readInt(&buf)
+        var seq = [PeerEntry]()
+        seq.reserveCapacity(Int(len))
+        for _ in 0 ..< len {
+            seq.append(try FfiConverterTypePeerEntry.read(from: &buf))
+        }
+        return seq
+    }
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterSequenceTypePendingRequest: FfiConverterRustBuffer {
+    typealias SwiftType = [PendingRequest]
+
+    public static func write(_ value: [PendingRequest], into buf: inout [UInt8]) {
+        let len = Int32(value.count)
+        writeInt(&buf, len)
+        for item in value {
+            FfiConverterTypePendingRequest.write(item, into: &buf)
+        }
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> [PendingRequest] {
+        let len: Int32 = try readInt(&buf)
+        var seq = [PendingRequest]()
+        seq.reserveCapacity(Int(len))
+        for _ in 0 ..< len {
+            seq.append(try FfiConverterTypePendingRequest.read(from: &buf))
+        }
+        return seq
+    }
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterSequenceTypeReceivedFileEntry: FfiConverterRustBuffer {
+    typealias SwiftType = [ReceivedFileEntry]
+
+    public static func write(_ value: [ReceivedFileEntry], into buf: inout [UInt8]) {
+        let len = Int32(value.count)
+        writeInt(&buf, len)
+        for item in value {
+            FfiConverterTypeReceivedFileEntry.write(item, into: &buf)
+        }
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> [ReceivedFileEntry] {
+        let len: Int32 = try readInt(&buf)
+        var seq = [ReceivedFileEntry]()
+        seq.reserveCapacity(Int(len))
+        for _ in 0 ..< len {
+            seq.append(try FfiConverterTypeReceivedFileEntry.read(from: &buf))
+        }
+        return seq
+    }
+}
+
+#if swift(>=5.8)
+@_documentation(visibility: private)
+#endif
+fileprivate struct FfiConverterSequenceTypeSessionInfo: FfiConverterRustBuffer {
+    typealias SwiftType = [SessionInfo]
+
+    public static func write(_ value: [SessionInfo], into buf: inout [UInt8]) {
+        let len = Int32(value.count)
+        writeInt(&buf, len)
+        for item in value {
+            FfiConverterTypeSessionInfo.write(item, into: &buf)
+        }
+    }
+
+    public static func read(from buf: inout (data: Data, offset: Data.Index)) throws -> [SessionInfo] {
+        let len: Int32 = try readInt(&buf)
+        var seq = [SessionInfo]()
+        seq.reserveCapacity(Int(len))
+        for _ in 0 ..< len {
+            seq.append(try FfiConverterTypeSessionInfo.read(from: &buf))
+        }
+        return seq
+    }
+}
+
 #if swift(>=5.8)
 @_documentation(visibility: private)
 #endif
@@ -2282,6 +4428,15 @@ public func clipboardNodeNew(identityPath: String, trustPath: String)throws  ->
     )
 })
 }
+public func confirmationTranscript(nonce: [UInt8], peerAId: String, peerBId: String) -> ConfirmationTranscript  {
+    return try!  FfiConverterTypeConfirmationTranscript_lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_func_confirmation_transcript(
+        FfiConverterSequenceUInt8.lower(nonce),
+        FfiConverterString.lower(peerAId),
+        FfiConverterString.lower(peerBId),$0
+    )
+})
+}
 public func defaultIdentityPath() -> String  {
     return try!  FfiConverterString.lift(try! rustCall() {
     uniffi_openclipboard_ffi_fn_func_default_identity_path($0
@@ -2336,6 +4491,13 @@ public func pairingPayloadFromQrString(s: String)throws  -> PairingPayload  {
     )
 })
 }
+public func peerVisual(peerId: String) -> DeviceVisual  {
+    return try!  FfiConverterTypeDeviceVisual_lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_func_peer_visual(
+        FfiConverterString.lower(peerId),$0
+    )
+})
+}
 public func trustStoreDefaultPath() -> String  {
     return try!  FfiConverterString.lift(try! rustCall() {
     uniffi_openclipboard_ffi_fn_func_trust_store_default_path($0
@@ -2349,6 +4511,14 @@ public func trustStoreOpen(path: String)throws  -> TrustStore  {
     )
 })
 }
+public func verifyConfirmationCode(transcript: ConfirmationTranscript, code: String) -> Bool  {
+    return try!  FfiConverterBool.lift(try! rustCall() {
+    uniffi_openclipboard_ffi_fn_func_verify_confirmation_code(
+        FfiConverterTypeConfirmationTranscript_lower(transcript),
+        FfiConverterString.lower(code),$0
+    )
+})
+}
 
 private enum InitializationResult {
     case ok
@@ -2368,6 +4538,9 @@ private let initializationResult: InitializationResult = {
     if (uniffi_openclipboard_ffi_checksum_func_clipboard_node_new() != 34405) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_func_confirmation_transcript() != 17304) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_func_default_identity_path() != 36700) {
         return InitializationResult.apiChecksumMismatch
     }
@@ -2389,21 +4562,51 @@ private let initializationResult: InitializationResult = {
     if (uniffi_openclipboard_ffi_checksum_func_pairing_payload_from_qr_string() != 47962) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_func_peer_visual() != 57004) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_func_trust_store_default_path() != 22940) {
         return InitializationResult.apiChecksumMismatch
     }
     if (uniffi_openclipboard_ffi_checksum_func_trust_store_open() != 31823) {
         return InitializationResult.apiChecksumMismatch
     }
-    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_connect_and_send_file() != 14460) {
+    if (uniffi_openclipboard_ffi_checksum_func_verify_confirmation_code() != 12607) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_cancellationtoken_cancel() != 27502) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_cancellationtoken_is_cancelled() != 41445) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_accept_pending_request() != 708) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_active_sessions() != 62813) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_clear_echo_suppressor() != 59991) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_clear_peer_file_type_rules() != 44714) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_connect_and_send_file() != 47892) {
         return InitializationResult.apiChecksumMismatch
     }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_connect_and_send_text() != 48151) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_diagnostics() != 10731) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_disable_qr_pairing_listener() != 2416) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_disconnect_peer() != 16645) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_enable_qr_pairing_listener() != 5068) {
         return InitializationResult.apiChecksumMismatch
     }
@@ -2413,25 +4616,127 @@ private let initializationResult: InitializationResult = {
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_history_for_peer() != 25297) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_peer_summary() != 63904) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_is_discovering() != 28190) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_last_clip_for_peer() != 38905) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_linked_devices() != 34477) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_list_peers() != 37860) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_pair_via_qr() != 39585) {
         return InitializationResult.apiChecksumMismatch
     }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_peer_id() != 3503) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_pending_requests() != 25529) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_recall_from_history() != 13525) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_received_files() != 20857) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_recent_errors() != 57195) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_remove_trusted_peer() != 20100) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_resend_current() != 31955) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_resume_peer() != 49290) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_clipboard_image() != 28572) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_clipboard_text() != 29125) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_file_to_peer() != 35416) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_disconnect_grace_ms() != 21211) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_download_dir() != 31085) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_drain_on_stop_ms() != 63620) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_rate_limit() != 28079) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_transfer_config() != 59733) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_type_rules() != 9513) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_history_file() != 57996) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_history_max_bytes() != 49098) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_keepalive_ms() != 24892) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_linked_device() != 10605) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_linked_devices_file() != 30814) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_max_concurrent_file_sends() != 25926) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_max_incoming_file_size() != 55629) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_partial_files_dir() != 20457) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_file_rate_limit() != 21982) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_file_type_rules() != 64971) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_sync_direction() != 40363) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_profile() != 30629) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_received_files_log() != 12204) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_sync_whitespace() != 34296) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_snapshot() != 64857) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_discovery() != 28153) {
         return InitializationResult.apiChecksumMismatch
     }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_listener() != 7176) {
         return InitializationResult.apiChecksumMismatch
     }
-    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_mesh() != 46513) {
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_mesh() != 62031) {
         return InitializationResult.apiChecksumMismatch
     }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_sync() != 58968) {
@@ -2446,6 +4751,12 @@ private let initializationResult: InitializationResult = {
     if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_stop_sync() != 62029) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_sync_trust() != 16158) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardnode_terminate_session() != 39279) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_method_identity_info() != 2247) {
         return InitializationResult.apiChecksumMismatch
     }
@@ -2479,6 +4790,9 @@ private let initializationResult: InitializationResult = {
     if (uniffi_openclipboard_ffi_checksum_method_pairingpayload_to_qr_string() != 16160) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_method_pairingpayload_to_qr_string_compact() != 51408) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_method_pairingpayload_version() != 57585) {
         return InitializationResult.apiChecksumMismatch
     }
@@ -2494,12 +4808,21 @@ private let initializationResult: InitializationResult = {
     if (uniffi_openclipboard_ffi_checksum_method_truststore_remove() != 28281) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_constructor_cancellationtoken_new() != 62734) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardcallback_read_text() != 18804) {
         return InitializationResult.apiChecksumMismatch
     }
     if (uniffi_openclipboard_ffi_checksum_method_clipboardcallback_write_text() != 24337) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardcallback_read_image() != 47649) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_clipboardcallback_write_image() != 35844) {
+        return InitializationResult.apiChecksumMismatch
+    }
     if (uniffi_openclipboard_ffi_checksum_method_discoveryhandler_on_peer_discovered() != 5960) {
         return InitializationResult.apiChecksumMismatch
     }
@@ -2521,10 +4844,17 @@ private let initializationResult: InitializationResult = {
     if (uniffi_openclipboard_ffi_checksum_method_eventhandler_on_error() != 400) {
         return InitializationResult.apiChecksumMismatch
     }
+    if (uniffi_openclipboard_ffi_checksum_method_eventhandler_on_clipboard_image() != 37218) {
+        return InitializationResult.apiChecksumMismatch
+    }
+    if (uniffi_openclipboard_ffi_checksum_method_fileprogresshandler_on_progress() != 1648) {
+        return InitializationResult.apiChecksumMismatch
+    }
 
     uniffiCallbackInitClipboardCallback()
     uniffiCallbackInitDiscoveryHandler()
     uniffiCallbackInitEventHandler()
+    uniffiCallbackInitFileProgressHandler()
     return InitializationResult.ok
 }()
 
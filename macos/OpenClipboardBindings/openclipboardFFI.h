@@ -250,6 +250,34 @@ typedef struct UniffiForeignFutureStructVoid {
 typedef void (*UniffiForeignFutureCompleteVoid)(uint64_t, UniffiForeignFutureStructVoid
     );
 
+#endif
+#ifndef UNIFFI_FFIDEF_CALLBACK_INTERFACE_CLIPBOARD_CALLBACK_METHOD0
+#define UNIFFI_FFIDEF_CALLBACK_INTERFACE_CLIPBOARD_CALLBACK_METHOD0
+typedef void (*UniffiCallbackInterfaceClipboardCallbackMethod0)(uint64_t, RustBuffer* _Nonnull, 
+        RustCallStatus *_Nonnull uniffiCallStatus
+    );
+
+#endif
+#ifndef UNIFFI_FFIDEF_CALLBACK_INTERFACE_CLIPBOARD_CALLBACK_METHOD1
+#define UNIFFI_FFIDEF_CALLBACK_INTERFACE_CLIPBOARD_CALLBACK_METHOD1
+typedef void (*UniffiCallbackInterfaceClipboardCallbackMethod1)(uint64_t, RustBuffer, void* _Nonnull, 
+        RustCallStatus *_Nonnull uniffiCallStatus
+    );
+
+#endif
+#ifndef UNIFFI_FFIDEF_CALLBACK_INTERFACE_CLIPBOARD_CALLBACK_METHOD2
+#define UNIFFI_FFIDEF_CALLBACK_INTERFACE_CLIPBOARD_CALLBACK_METHOD2
+typedef void (*UniffiCallbackInterfaceClipboardCallbackMethod2)(uint64_t, RustBuffer* _Nonnull, 
+        RustCallStatus *_Nonnull uniffiCallStatus
+    );
+
+#endif
+#ifndef UNIFFI_FFIDEF_CALLBACK_INTERFACE_CLIPBOARD_CALLBACK_METHOD3
+#define UNIFFI_FFIDEF_CALLBACK_INTERFACE_CLIPBOARD_CALLBACK_METHOD3
+typedef void (*UniffiCallbackInterfaceClipboardCallbackMethod3)(uint64_t, RustBuffer, void* _Nonnull, 
+        RustCallStatus *_Nonnull uniffiCallStatus
+    );
+
 #endif
 #ifndef UNIFFI_FFIDEF_CALLBACK_INTERFACE_DISCOVERY_HANDLER_METHOD0
 #define UNIFFI_FFIDEF_CALLBACK_INTERFACE_DISCOVERY_HANDLER_METHOD0
@@ -299,6 +327,31 @@ typedef void (*UniffiCallbackInterfaceEventHandlerMethod4)(uint64_t, RustBuffer,
         RustCallStatus *_Nonnull uniffiCallStatus
     );
 
+#endif
+#ifndef UNIFFI_FFIDEF_CALLBACK_INTERFACE_EVENT_HANDLER_METHOD5
+#define UNIFFI_FFIDEF_CALLBACK_INTERFACE_EVENT_HANDLER_METHOD5
+typedef void (*UniffiCallbackInterfaceEventHandlerMethod5)(uint64_t, RustBuffer, RustBuffer, uint32_t, uint32_t, RustBuffer, uint64_t, void* _Nonnull, 
+        RustCallStatus *_Nonnull uniffiCallStatus
+    );
+
+#endif
+#ifndef UNIFFI_FFIDEF_CALLBACK_INTERFACE_FILE_PROGRESS_HANDLER_METHOD0
+#define UNIFFI_FFIDEF_CALLBACK_INTERFACE_FILE_PROGRESS_HANDLER_METHOD0
+typedef void (*UniffiCallbackInterfaceFileProgressHandlerMethod0)(uint64_t, uint64_t, uint64_t, void* _Nonnull, 
+        RustCallStatus *_Nonnull uniffiCallStatus
+    );
+
+#endif
+#ifndef UNIFFI_FFIDEF_V_TABLE_CALLBACK_INTERFACE_CLIPBOARD_CALLBACK
+#define UNIFFI_FFIDEF_V_TABLE_CALLBACK_INTERFACE_CLIPBOARD_CALLBACK
+typedef struct UniffiVTableCallbackInterfaceClipboardCallback {
+    UniffiCallbackInterfaceClipboardCallbackMethod0 _Nonnull readText;
+    UniffiCallbackInterfaceClipboardCallbackMethod1 _Nonnull writeText;
+    UniffiCallbackInterfaceClipboardCallbackMethod2 _Nonnull readImage;
+    UniffiCallbackInterfaceClipboardCallbackMethod3 _Nonnull writeImage;
+    UniffiCallbackInterfaceFree _Nonnull uniffiFree;
+} UniffiVTableCallbackInterfaceClipboardCallback;
+
 #endif
 #ifndef UNIFFI_FFIDEF_V_TABLE_CALLBACK_INTERFACE_DISCOVERY_HANDLER
 #define UNIFFI_FFIDEF_V_TABLE_CALLBACK_INTERFACE_DISCOVERY_HANDLER
@@ -317,9 +370,44 @@ typedef struct UniffiVTableCallbackInterfaceEventHandler {
     UniffiCallbackInterfaceEventHandlerMethod2 _Nonnull onPeerConnected;
     UniffiCallbackInterfaceEventHandlerMethod3 _Nonnull onPeerDisconnected;
     UniffiCallbackInterfaceEventHandlerMethod4 _Nonnull onError;
+    UniffiCallbackInterfaceEventHandlerMethod5 _Nonnull onClipboardImage;
     UniffiCallbackInterfaceFree _Nonnull uniffiFree;
 } UniffiVTableCallbackInterfaceEventHandler;
 
+#endif
+#ifndef UNIFFI_FFIDEF_V_TABLE_CALLBACK_INTERFACE_FILE_PROGRESS_HANDLER
+#define UNIFFI_FFIDEF_V_TABLE_CALLBACK_INTERFACE_FILE_PROGRESS_HANDLER
+typedef struct UniffiVTableCallbackInterfaceFileProgressHandler {
+    UniffiCallbackInterfaceFileProgressHandlerMethod0 _Nonnull onProgress;
+    UniffiCallbackInterfaceFree _Nonnull uniffiFree;
+} UniffiVTableCallbackInterfaceFileProgressHandler;
+
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_CLONE_CANCELLATIONTOKEN
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_CLONE_CANCELLATIONTOKEN
+void*_Nonnull uniffi_openclipboard_ffi_fn_clone_cancellationtoken(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FREE_CANCELLATIONTOKEN
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FREE_CANCELLATIONTOKEN
+void uniffi_openclipboard_ffi_fn_free_cancellationtoken(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_CONSTRUCTOR_CANCELLATIONTOKEN_NEW
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_CONSTRUCTOR_CANCELLATIONTOKEN_NEW
+void*_Nonnull uniffi_openclipboard_ffi_fn_constructor_cancellationtoken_new(RustCallStatus *_Nonnull out_status
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CANCELLATIONTOKEN_CANCEL
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CANCELLATIONTOKEN_CANCEL
+void uniffi_openclipboard_ffi_fn_method_cancellationtoken_cancel(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CANCELLATIONTOKEN_IS_CANCELLED
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CANCELLATIONTOKEN_IS_CANCELLED
+int8_t uniffi_openclipboard_ffi_fn_method_cancellationtoken_is_cancelled(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_CLONE_CLIPBOARDNODE
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_CLONE_CLIPBOARDNODE
@@ -331,9 +419,29 @@ void*_Nonnull uniffi_openclipboard_ffi_fn_clone_clipboardnode(void*_Nonnull ptr,
 void uniffi_openclipboard_ffi_fn_free_clipboardnode(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_ACCEPT_PENDING_REQUEST
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_ACCEPT_PENDING_REQUEST
+int8_t uniffi_openclipboard_ffi_fn_method_clipboardnode_accept_pending_request(void*_Nonnull ptr, RustBuffer peer_id, RustBuffer display_name, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_ACTIVE_SESSIONS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_ACTIVE_SESSIONS
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_active_sessions(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_CLEAR_ECHO_SUPPRESSOR
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_CLEAR_ECHO_SUPPRESSOR
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_clear_echo_suppressor(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_CLEAR_PEER_FILE_TYPE_RULES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_CLEAR_PEER_FILE_TYPE_RULES
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_clear_peer_file_type_rules(void*_Nonnull ptr, RustBuffer peer_id, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_CONNECT_AND_SEND_FILE
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_CONNECT_AND_SEND_FILE
-void uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_file(void*_Nonnull ptr, RustBuffer addr, RustBuffer file_path, RustCallStatus *_Nonnull out_status
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_file(void*_Nonnull ptr, RustBuffer addr, RustBuffer file_path, RustBuffer cancel, RustCallStatus *_Nonnull out_status
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_CONNECT_AND_SEND_TEXT
@@ -341,16 +449,226 @@ void uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_file(void
 void uniffi_openclipboard_ffi_fn_method_clipboardnode_connect_and_send_text(void*_Nonnull ptr, RustBuffer addr, RustBuffer text, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_DIAGNOSTICS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_DIAGNOSTICS
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_diagnostics(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_DISABLE_QR_PAIRING_LISTENER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_DISABLE_QR_PAIRING_LISTENER
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_disable_qr_pairing_listener(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_DISCONNECT_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_DISCONNECT_PEER
+int8_t uniffi_openclipboard_ffi_fn_method_clipboardnode_disconnect_peer(void*_Nonnull ptr, RustBuffer peer_id, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_ENABLE_QR_PAIRING_LISTENER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_ENABLE_QR_PAIRING_LISTENER
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_enable_qr_pairing_listener(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_HISTORY
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_HISTORY
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_history(void*_Nonnull ptr, uint32_t limit, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_HISTORY_FOR_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_HISTORY_FOR_PEER
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_history_for_peer(void*_Nonnull ptr, RustBuffer peer_name, uint32_t limit, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_PEER_SUMMARY
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_PEER_SUMMARY
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_get_clipboard_peer_summary(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_IS_DISCOVERING
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_IS_DISCOVERING
+int8_t uniffi_openclipboard_ffi_fn_method_clipboardnode_is_discovering(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_LAST_CLIP_FOR_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_LAST_CLIP_FOR_PEER
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_last_clip_for_peer(void*_Nonnull ptr, RustBuffer peer_id, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_LINKED_DEVICES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_LINKED_DEVICES
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_linked_devices(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_LIST_PEERS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_LIST_PEERS
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_list_peers(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_PAIR_VIA_QR
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_PAIR_VIA_QR
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_pair_via_qr(void*_Nonnull ptr, RustBuffer qr_string, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_PEER_ID
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_PEER_ID
 RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_peer_id(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_PENDING_REQUESTS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_PENDING_REQUESTS
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_pending_requests(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_RECALL_FROM_HISTORY
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_RECALL_FROM_HISTORY
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_recall_from_history(void*_Nonnull ptr, RustBuffer entry_id, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_RECEIVED_FILES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_RECEIVED_FILES
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_received_files(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_RECENT_ERRORS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_RECENT_ERRORS
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_recent_errors(void*_Nonnull ptr, uint32_t limit, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_REMOVE_TRUSTED_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_REMOVE_TRUSTED_PEER
+int8_t uniffi_openclipboard_ffi_fn_method_clipboardnode_remove_trusted_peer(void*_Nonnull ptr, RustBuffer peer_id, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_RESEND_CURRENT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_RESEND_CURRENT
+int8_t uniffi_openclipboard_ffi_fn_method_clipboardnode_resend_current(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_RESUME_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_RESUME_PEER
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_resume_peer(void*_Nonnull ptr, RustBuffer peer_id, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SEND_CLIPBOARD_IMAGE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SEND_CLIPBOARD_IMAGE
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_send_clipboard_image(void*_Nonnull ptr, RustBuffer mime, uint32_t width, uint32_t height, RustBuffer bytes, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SEND_CLIPBOARD_TEXT
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SEND_CLIPBOARD_TEXT
 void uniffi_openclipboard_ffi_fn_method_clipboardnode_send_clipboard_text(void*_Nonnull ptr, RustBuffer text, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SEND_FILE_TO_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SEND_FILE_TO_PEER
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_send_file_to_peer(void*_Nonnull ptr, RustBuffer peer_id, RustBuffer file_path, uint64_t progress, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_DISCONNECT_GRACE_MS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_DISCONNECT_GRACE_MS
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_disconnect_grace_ms(void*_Nonnull ptr, uint64_t grace_ms, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_DOWNLOAD_DIR
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_DOWNLOAD_DIR
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_download_dir(void*_Nonnull ptr, RustBuffer dir, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_DRAIN_ON_STOP_MS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_DRAIN_ON_STOP_MS
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_drain_on_stop_ms(void*_Nonnull ptr, uint64_t timeout_ms, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_FILE_RATE_LIMIT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_FILE_RATE_LIMIT
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_rate_limit(void*_Nonnull ptr, uint64_t bytes_per_sec, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_FILE_TRANSFER_CONFIG
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_FILE_TRANSFER_CONFIG
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_transfer_config(void*_Nonnull ptr, uint32_t chunk_size, uint32_t window, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_FILE_TYPE_RULES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_FILE_TYPE_RULES
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_file_type_rules(void*_Nonnull ptr, RustBuffer allow, RustBuffer deny, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_HISTORY_FILE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_HISTORY_FILE
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_history_file(void*_Nonnull ptr, RustBuffer path, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_HISTORY_MAX_BYTES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_HISTORY_MAX_BYTES
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_history_max_bytes(void*_Nonnull ptr, uint64_t max_bytes, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_KEEPALIVE_MS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_KEEPALIVE_MS
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_keepalive_ms(void*_Nonnull ptr, uint64_t interval_ms, uint64_t timeout_ms, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_LINKED_DEVICE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_LINKED_DEVICE
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_linked_device(void*_Nonnull ptr, RustBuffer peer_id, int8_t linked, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_LINKED_DEVICES_FILE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_LINKED_DEVICES_FILE
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_linked_devices_file(void*_Nonnull ptr, RustBuffer path, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_MAX_CONCURRENT_FILE_SENDS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_MAX_CONCURRENT_FILE_SENDS
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_max_concurrent_file_sends(void*_Nonnull ptr, uint32_t limit, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_MAX_INCOMING_FILE_SIZE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_MAX_INCOMING_FILE_SIZE
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_max_incoming_file_size(void*_Nonnull ptr, uint64_t max_bytes, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_PARTIAL_FILES_DIR
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_PARTIAL_FILES_DIR
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_partial_files_dir(void*_Nonnull ptr, RustBuffer dir, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_PEER_FILE_RATE_LIMIT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_PEER_FILE_RATE_LIMIT
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_file_rate_limit(void*_Nonnull ptr, RustBuffer peer_id, uint64_t bytes_per_sec, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_PEER_FILE_TYPE_RULES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_PEER_FILE_TYPE_RULES
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_file_type_rules(void*_Nonnull ptr, RustBuffer peer_id, RustBuffer allow, RustBuffer deny, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_PEER_SYNC_DIRECTION
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_PEER_SYNC_DIRECTION
+int8_t uniffi_openclipboard_ffi_fn_method_clipboardnode_set_peer_sync_direction(void*_Nonnull ptr, RustBuffer peer_id, RustBuffer direction, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_PROFILE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_PROFILE
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_profile(void*_Nonnull ptr, RustBuffer trust_path, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_RECEIVED_FILES_LOG
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_RECEIVED_FILES_LOG
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_received_files_log(void*_Nonnull ptr, RustBuffer path, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_SYNC_WHITESPACE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SET_SYNC_WHITESPACE
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_set_sync_whitespace(void*_Nonnull ptr, int8_t enabled, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SNAPSHOT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SNAPSHOT
+RustBuffer uniffi_openclipboard_ffi_fn_method_clipboardnode_snapshot(void*_Nonnull ptr, uint32_t history_limit, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_START_DISCOVERY
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_START_DISCOVERY
 void uniffi_openclipboard_ffi_fn_method_clipboardnode_start_discovery(void*_Nonnull ptr, RustBuffer device_name, uint64_t handler, RustCallStatus *_Nonnull out_status
@@ -361,6 +679,11 @@ void uniffi_openclipboard_ffi_fn_method_clipboardnode_start_discovery(void*_Nonn
 void uniffi_openclipboard_ffi_fn_method_clipboardnode_start_listener(void*_Nonnull ptr, uint16_t port, uint64_t handler, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_START_MESH
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_START_MESH
+void uniffi_openclipboard_ffi_fn_method_clipboardnode_start_mesh(void*_Nonnull ptr, uint16_t port, RustBuffer device_name, uint64_t handler, uint64_t provider, uint64_t poll_interval_ms, RustBuffer cancel, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_START_SYNC
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_START_SYNC
 void uniffi_openclipboard_ffi_fn_method_clipboardnode_start_sync(void*_Nonnull ptr, uint16_t port, RustBuffer device_name, uint64_t handler, RustCallStatus *_Nonnull out_status
@@ -381,6 +704,16 @@ void uniffi_openclipboard_ffi_fn_method_clipboardnode_stop_discovery(void*_Nonnu
 void uniffi_openclipboard_ffi_fn_method_clipboardnode_stop_sync(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SYNC_TRUST
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_SYNC_TRUST
+uint32_t uniffi_openclipboard_ffi_fn_method_clipboardnode_sync_trust(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_TERMINATE_SESSION
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_CLIPBOARDNODE_TERMINATE_SESSION
+int8_t uniffi_openclipboard_ffi_fn_method_clipboardnode_terminate_session(void*_Nonnull ptr, RustBuffer peer_id, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_CLONE_IDENTITY
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_CLONE_IDENTITY
 void*_Nonnull uniffi_openclipboard_ffi_fn_clone_identity(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
@@ -426,6 +759,11 @@ void uniffi_openclipboard_ffi_fn_free_pairingpayload(void*_Nonnull ptr, RustCall
 RustBuffer uniffi_openclipboard_ffi_fn_method_pairingpayload_identity_pk(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_PAIRINGPAYLOAD_LAN_ADDRS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_PAIRINGPAYLOAD_LAN_ADDRS
+RustBuffer uniffi_openclipboard_ffi_fn_method_pairingpayload_lan_addrs(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_PAIRINGPAYLOAD_LAN_PORT
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_PAIRINGPAYLOAD_LAN_PORT
 uint16_t uniffi_openclipboard_ffi_fn_method_pairingpayload_lan_port(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
@@ -451,6 +789,11 @@ RustBuffer uniffi_openclipboard_ffi_fn_method_pairingpayload_peer_id(void*_Nonnu
 RustBuffer uniffi_openclipboard_ffi_fn_method_pairingpayload_to_qr_string(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_PAIRINGPAYLOAD_TO_QR_STRING_COMPACT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_PAIRINGPAYLOAD_TO_QR_STRING_COMPACT
+RustBuffer uniffi_openclipboard_ffi_fn_method_pairingpayload_to_qr_string_compact(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_PAIRINGPAYLOAD_VERSION
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_METHOD_PAIRINGPAYLOAD_VERSION
 uint8_t uniffi_openclipboard_ffi_fn_method_pairingpayload_version(void*_Nonnull ptr, RustCallStatus *_Nonnull out_status
@@ -486,6 +829,11 @@ RustBuffer uniffi_openclipboard_ffi_fn_method_truststore_list(void*_Nonnull ptr,
 int8_t uniffi_openclipboard_ffi_fn_method_truststore_remove(void*_Nonnull ptr, RustBuffer peer_id, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_INIT_CALLBACK_VTABLE_CLIPBOARDCALLBACK
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_INIT_CALLBACK_VTABLE_CLIPBOARDCALLBACK
+void uniffi_openclipboard_ffi_fn_init_callback_vtable_clipboardcallback(const UniffiVTableCallbackInterfaceClipboardCallback* _Nonnull vtable
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_INIT_CALLBACK_VTABLE_DISCOVERYHANDLER
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_INIT_CALLBACK_VTABLE_DISCOVERYHANDLER
 void uniffi_openclipboard_ffi_fn_init_callback_vtable_discoveryhandler(const UniffiVTableCallbackInterfaceDiscoveryHandler* _Nonnull vtable
@@ -496,11 +844,21 @@ void uniffi_openclipboard_ffi_fn_init_callback_vtable_discoveryhandler(const Uni
 void uniffi_openclipboard_ffi_fn_init_callback_vtable_eventhandler(const UniffiVTableCallbackInterfaceEventHandler* _Nonnull vtable
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_INIT_CALLBACK_VTABLE_FILEPROGRESSHANDLER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_INIT_CALLBACK_VTABLE_FILEPROGRESSHANDLER
+void uniffi_openclipboard_ffi_fn_init_callback_vtable_fileprogresshandler(const UniffiVTableCallbackInterfaceFileProgressHandler* _Nonnull vtable
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_CLIPBOARD_NODE_NEW
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_CLIPBOARD_NODE_NEW
 void*_Nonnull uniffi_openclipboard_ffi_fn_func_clipboard_node_new(RustBuffer identity_path, RustBuffer trust_path, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_CONFIRMATION_TRANSCRIPT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_CONFIRMATION_TRANSCRIPT
+RustBuffer uniffi_openclipboard_ffi_fn_func_confirmation_transcript(RustBuffer nonce, RustBuffer peer_a_id, RustBuffer peer_b_id, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_DEFAULT_IDENTITY_PATH
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_DEFAULT_IDENTITY_PATH
 RustBuffer uniffi_openclipboard_ffi_fn_func_default_identity_path(RustCallStatus *_Nonnull out_status
@@ -510,6 +868,12 @@ RustBuffer uniffi_openclipboard_ffi_fn_func_default_identity_path(RustCallStatus
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_DERIVE_CONFIRMATION_CODE
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_DERIVE_CONFIRMATION_CODE
 RustBuffer uniffi_openclipboard_ffi_fn_func_derive_confirmation_code(RustBuffer nonce, RustBuffer peer_a_id, RustBuffer peer_b_id, RustCallStatus *_Nonnull out_status
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_GET_LAN_ADDRESSES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_GET_LAN_ADDRESSES
+RustBuffer uniffi_openclipboard_ffi_fn_func_get_lan_addresses(RustCallStatus *_Nonnull out_status
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_IDENTITY_GENERATE
@@ -525,7 +889,7 @@ void*_Nonnull uniffi_openclipboard_ffi_fn_func_identity_load(RustBuffer path, Ru
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_PAIRING_PAYLOAD_CREATE
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_PAIRING_PAYLOAD_CREATE
-void*_Nonnull uniffi_openclipboard_ffi_fn_func_pairing_payload_create(uint8_t version, RustBuffer peer_id, RustBuffer name, RustBuffer identity_pk, uint16_t lan_port, RustBuffer nonce, RustCallStatus *_Nonnull out_status
+void*_Nonnull uniffi_openclipboard_ffi_fn_func_pairing_payload_create(uint8_t version, RustBuffer peer_id, RustBuffer name, RustBuffer identity_pk, uint16_t lan_port, RustBuffer nonce, RustBuffer lan_addrs, RustCallStatus *_Nonnull out_status
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_PAIRING_PAYLOAD_FROM_QR_STRING
@@ -533,6 +897,11 @@ void*_Nonnull uniffi_openclipboard_ffi_fn_func_pairing_payload_create(uint8_t ve
 void*_Nonnull uniffi_openclipboard_ffi_fn_func_pairing_payload_from_qr_string(RustBuffer s, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_PEER_VISUAL
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_PEER_VISUAL
+RustBuffer uniffi_openclipboard_ffi_fn_func_peer_visual(RustBuffer peer_id, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_TRUST_STORE_DEFAULT_PATH
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_TRUST_STORE_DEFAULT_PATH
 RustBuffer uniffi_openclipboard_ffi_fn_func_trust_store_default_path(RustCallStatus *_Nonnull out_status
@@ -544,6 +913,11 @@ RustBuffer uniffi_openclipboard_ffi_fn_func_trust_store_default_path(RustCallSta
 void*_Nonnull uniffi_openclipboard_ffi_fn_func_trust_store_open(RustBuffer path, RustCallStatus *_Nonnull out_status
 );
 #endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_VERIFY_CONFIRMATION_CODE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_FN_FUNC_VERIFY_CONFIRMATION_CODE
+int8_t uniffi_openclipboard_ffi_fn_func_verify_confirmation_code(RustBuffer transcript, RustBuffer code, RustCallStatus *_Nonnull out_status
+);
+#endif
 #ifndef UNIFFI_FFIDEF_FFI_OPENCLIPBOARD_FFI_RUSTBUFFER_ALLOC
 #define UNIFFI_FFIDEF_FFI_OPENCLIPBOARD_FFI_RUSTBUFFER_ALLOC
 RustBuffer ffi_openclipboard_ffi_rustbuffer_alloc(uint64_t size, RustCallStatus *_Nonnull out_status
@@ -828,6 +1202,12 @@ void ffi_openclipboard_ffi_rust_future_complete_void(uint64_t handle, RustCallSt
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_CLIPBOARD_NODE_NEW
 uint16_t uniffi_openclipboard_ffi_checksum_func_clipboard_node_new(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_CONFIRMATION_TRANSCRIPT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_CONFIRMATION_TRANSCRIPT
+uint16_t uniffi_openclipboard_ffi_checksum_func_confirmation_transcript(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_DEFAULT_IDENTITY_PATH
@@ -840,6 +1220,12 @@ uint16_t uniffi_openclipboard_ffi_checksum_func_default_identity_path(void
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_DERIVE_CONFIRMATION_CODE
 uint16_t uniffi_openclipboard_ffi_checksum_func_derive_confirmation_code(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_GET_LAN_ADDRESSES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_GET_LAN_ADDRESSES
+uint16_t uniffi_openclipboard_ffi_checksum_func_get_lan_addresses(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_IDENTITY_GENERATE
@@ -864,6 +1250,12 @@ uint16_t uniffi_openclipboard_ffi_checksum_func_pairing_payload_create(void
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_PAIRING_PAYLOAD_FROM_QR_STRING
 uint16_t uniffi_openclipboard_ffi_checksum_func_pairing_payload_from_qr_string(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_PEER_VISUAL
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_PEER_VISUAL
+uint16_t uniffi_openclipboard_ffi_checksum_func_peer_visual(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_TRUST_STORE_DEFAULT_PATH
@@ -876,6 +1268,48 @@ uint16_t uniffi_openclipboard_ffi_checksum_func_trust_store_default_path(void
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_TRUST_STORE_OPEN
 uint16_t uniffi_openclipboard_ffi_checksum_func_trust_store_open(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_VERIFY_CONFIRMATION_CODE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_FUNC_VERIFY_CONFIRMATION_CODE
+uint16_t uniffi_openclipboard_ffi_checksum_func_verify_confirmation_code(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CANCELLATIONTOKEN_CANCEL
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CANCELLATIONTOKEN_CANCEL
+uint16_t uniffi_openclipboard_ffi_checksum_method_cancellationtoken_cancel(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CANCELLATIONTOKEN_IS_CANCELLED
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CANCELLATIONTOKEN_IS_CANCELLED
+uint16_t uniffi_openclipboard_ffi_checksum_method_cancellationtoken_is_cancelled(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_ACCEPT_PENDING_REQUEST
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_ACCEPT_PENDING_REQUEST
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_accept_pending_request(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_ACTIVE_SESSIONS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_ACTIVE_SESSIONS
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_active_sessions(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_CLEAR_ECHO_SUPPRESSOR
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_CLEAR_ECHO_SUPPRESSOR
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_clear_echo_suppressor(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_CLEAR_PEER_FILE_TYPE_RULES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_CLEAR_PEER_FILE_TYPE_RULES
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_clear_peer_file_type_rules(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_CONNECT_AND_SEND_FILE
@@ -888,18 +1322,270 @@ uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_connect_and_send
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_CONNECT_AND_SEND_TEXT
 uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_connect_and_send_text(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_DIAGNOSTICS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_DIAGNOSTICS
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_diagnostics(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_DISABLE_QR_PAIRING_LISTENER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_DISABLE_QR_PAIRING_LISTENER
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_disable_qr_pairing_listener(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_DISCONNECT_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_DISCONNECT_PEER
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_disconnect_peer(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_ENABLE_QR_PAIRING_LISTENER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_ENABLE_QR_PAIRING_LISTENER
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_enable_qr_pairing_listener(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_HISTORY
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_HISTORY
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_history(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_HISTORY_FOR_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_HISTORY_FOR_PEER
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_history_for_peer(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_PEER_SUMMARY
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_GET_CLIPBOARD_PEER_SUMMARY
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_get_clipboard_peer_summary(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_IS_DISCOVERING
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_IS_DISCOVERING
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_is_discovering(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_LAST_CLIP_FOR_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_LAST_CLIP_FOR_PEER
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_last_clip_for_peer(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_LINKED_DEVICES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_LINKED_DEVICES
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_linked_devices(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_LIST_PEERS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_LIST_PEERS
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_list_peers(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_PAIR_VIA_QR
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_PAIR_VIA_QR
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_pair_via_qr(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_PEER_ID
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_PEER_ID
 uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_peer_id(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_PENDING_REQUESTS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_PENDING_REQUESTS
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_pending_requests(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_RECALL_FROM_HISTORY
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_RECALL_FROM_HISTORY
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_recall_from_history(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_RECEIVED_FILES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_RECEIVED_FILES
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_received_files(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_RECENT_ERRORS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_RECENT_ERRORS
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_recent_errors(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_REMOVE_TRUSTED_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_REMOVE_TRUSTED_PEER
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_remove_trusted_peer(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_RESEND_CURRENT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_RESEND_CURRENT
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_resend_current(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_RESUME_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_RESUME_PEER
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_resume_peer(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SEND_CLIPBOARD_IMAGE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SEND_CLIPBOARD_IMAGE
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_clipboard_image(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SEND_CLIPBOARD_TEXT
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SEND_CLIPBOARD_TEXT
 uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_clipboard_text(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SEND_FILE_TO_PEER
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SEND_FILE_TO_PEER
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_send_file_to_peer(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_DISCONNECT_GRACE_MS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_DISCONNECT_GRACE_MS
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_disconnect_grace_ms(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_DOWNLOAD_DIR
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_DOWNLOAD_DIR
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_download_dir(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_DRAIN_ON_STOP_MS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_DRAIN_ON_STOP_MS
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_drain_on_stop_ms(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_FILE_RATE_LIMIT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_FILE_RATE_LIMIT
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_rate_limit(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_FILE_TRANSFER_CONFIG
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_FILE_TRANSFER_CONFIG
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_transfer_config(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_FILE_TYPE_RULES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_FILE_TYPE_RULES
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_file_type_rules(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_HISTORY_FILE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_HISTORY_FILE
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_history_file(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_HISTORY_MAX_BYTES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_HISTORY_MAX_BYTES
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_history_max_bytes(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_KEEPALIVE_MS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_KEEPALIVE_MS
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_keepalive_ms(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_LINKED_DEVICE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_LINKED_DEVICE
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_linked_device(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_LINKED_DEVICES_FILE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_LINKED_DEVICES_FILE
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_linked_devices_file(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_MAX_CONCURRENT_FILE_SENDS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_MAX_CONCURRENT_FILE_SENDS
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_max_concurrent_file_sends(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_MAX_INCOMING_FILE_SIZE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_MAX_INCOMING_FILE_SIZE
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_max_incoming_file_size(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_PARTIAL_FILES_DIR
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_PARTIAL_FILES_DIR
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_partial_files_dir(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_PEER_FILE_RATE_LIMIT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_PEER_FILE_RATE_LIMIT
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_file_rate_limit(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_PEER_FILE_TYPE_RULES
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_PEER_FILE_TYPE_RULES
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_file_type_rules(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_PEER_SYNC_DIRECTION
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_PEER_SYNC_DIRECTION
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_peer_sync_direction(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_PROFILE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_PROFILE
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_profile(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_RECEIVED_FILES_LOG
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_RECEIVED_FILES_LOG
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_received_files_log(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_SYNC_WHITESPACE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SET_SYNC_WHITESPACE
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_set_sync_whitespace(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SNAPSHOT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SNAPSHOT
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_snapshot(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_START_DISCOVERY
@@ -912,6 +1598,12 @@ uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_discovery(
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_START_LISTENER
 uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_listener(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_START_MESH
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_START_MESH
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_start_mesh(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_START_SYNC
@@ -936,6 +1628,18 @@ uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_stop_discovery(v
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_STOP_SYNC
 uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_stop_sync(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SYNC_TRUST
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_SYNC_TRUST
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_sync_trust(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_TERMINATE_SESSION
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDNODE_TERMINATE_SESSION
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardnode_terminate_session(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_IDENTITY_INFO
@@ -966,6 +1670,12 @@ uint16_t uniffi_openclipboard_ffi_checksum_method_identity_save(void
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_PAIRINGPAYLOAD_IDENTITY_PK
 uint16_t uniffi_openclipboard_ffi_checksum_method_pairingpayload_identity_pk(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_PAIRINGPAYLOAD_LAN_ADDRS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_PAIRINGPAYLOAD_LAN_ADDRS
+uint16_t uniffi_openclipboard_ffi_checksum_method_pairingpayload_lan_addrs(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_PAIRINGPAYLOAD_LAN_PORT
@@ -996,6 +1706,12 @@ uint16_t uniffi_openclipboard_ffi_checksum_method_pairingpayload_peer_id(void
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_PAIRINGPAYLOAD_TO_QR_STRING
 uint16_t uniffi_openclipboard_ffi_checksum_method_pairingpayload_to_qr_string(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_PAIRINGPAYLOAD_TO_QR_STRING_COMPACT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_PAIRINGPAYLOAD_TO_QR_STRING_COMPACT
+uint16_t uniffi_openclipboard_ffi_checksum_method_pairingpayload_to_qr_string_compact(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_PAIRINGPAYLOAD_VERSION
@@ -1026,6 +1742,36 @@ uint16_t uniffi_openclipboard_ffi_checksum_method_truststore_list(void
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_TRUSTSTORE_REMOVE
 uint16_t uniffi_openclipboard_ffi_checksum_method_truststore_remove(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_CONSTRUCTOR_CANCELLATIONTOKEN_NEW
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_CONSTRUCTOR_CANCELLATIONTOKEN_NEW
+uint16_t uniffi_openclipboard_ffi_checksum_constructor_cancellationtoken_new(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDCALLBACK_READ_TEXT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDCALLBACK_READ_TEXT
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardcallback_read_text(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDCALLBACK_WRITE_TEXT
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDCALLBACK_WRITE_TEXT
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardcallback_write_text(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDCALLBACK_READ_IMAGE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDCALLBACK_READ_IMAGE
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardcallback_read_image(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDCALLBACK_WRITE_IMAGE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_CLIPBOARDCALLBACK_WRITE_IMAGE
+uint16_t uniffi_openclipboard_ffi_checksum_method_clipboardcallback_write_image(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_DISCOVERYHANDLER_ON_PEER_DISCOVERED
@@ -1068,6 +1814,18 @@ uint16_t uniffi_openclipboard_ffi_checksum_method_eventhandler_on_peer_disconnec
 #define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_EVENTHANDLER_ON_ERROR
 uint16_t uniffi_openclipboard_ffi_checksum_method_eventhandler_on_error(void
     
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_EVENTHANDLER_ON_CLIPBOARD_IMAGE
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_EVENTHANDLER_ON_CLIPBOARD_IMAGE
+uint16_t uniffi_openclipboard_ffi_checksum_method_eventhandler_on_clipboard_image(void
+    
+);
+#endif
+#ifndef UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_FILEPROGRESSHANDLER_ON_PROGRESS
+#define UNIFFI_FFIDEF_UNIFFI_OPENCLIPBOARD_FFI_CHECKSUM_METHOD_FILEPROGRESSHANDLER_ON_PROGRESS
+uint16_t uniffi_openclipboard_ffi_checksum_method_fileprogresshandler_on_progress(void
+    
 );
 #endif
 #ifndef UNIFFI_FFIDEF_FFI_OPENCLIPBOARD_FFI_UNIFFI_CONTRACT_VERSION